@@ -14,6 +14,9 @@ const MLKEM768_SEED_LEN: usize = 32;
 const MLKEM768_SHARED_SECRET_LEN: usize = 32;
 const XCHACHA20POLY1305_KEY_LEN: usize = 32;
 const XCHACHA20POLY1305_NONCE_LEN: usize = 24;
+const XCHACHA20POLY1305_TAG_LEN: usize = 16;
+const ENVELOPE_V2_MAGIC: [u8; 4] = *b"TBE\x02";
+const ENVELOPE_V2_PREAMBLE_LEN: usize = 8;
 
 fn read_input<'a>(ptr: *const u8, len: usize) -> Result<&'a [u8], i32> {
     if len == 0 {
@@ -73,7 +76,51 @@ pub extern "C" fn mlkem768_shared_secret_len() -> usize {
 
 #[no_mangle]
 pub extern "C" fn xchacha20poly1305_ciphertext_len(plaintext_len: usize) -> usize {
-    plaintext_len + 16
+    plaintext_len + XCHACHA20POLY1305_TAG_LEN
+}
+
+/// 1 for a V1 JSON envelope, 2 for a V2 binary container, -7 otherwise.
+#[no_mangle]
+pub extern "C" fn envelope_format(envelope_ptr: *const u8, envelope_len: usize) -> i32 {
+    let envelope = match read_input(envelope_ptr, envelope_len) {
+        Ok(value) => value,
+        Err(code) => return code,
+    };
+    if envelope.starts_with(&ENVELOPE_V2_MAGIC) {
+        return 2;
+    }
+    match envelope.iter().find(|byte| !byte.is_ascii_whitespace()) {
+        Some(b'{') => 1,
+        _ => -7,
+    }
+}
+
+/// Offset of the canonical JSON header in a V2 container.
+#[no_mangle]
+pub extern "C" fn envelope_v2_header_offset() -> usize {
+    ENVELOPE_V2_PREAMBLE_LEN
+}
+
+/// Header length of a V2 container; the ciphertext body starts at
+/// `envelope_v2_header_offset() + header_len`.
+#[no_mangle]
+pub extern "C" fn envelope_v2_header_len(envelope_ptr: *const u8, envelope_len: usize) -> i32 {
+    if envelope_len < ENVELOPE_V2_PREAMBLE_LEN {
+        return -2;
+    }
+    let envelope = match read_input(envelope_ptr, envelope_len) {
+        Ok(value) => value,
+        Err(code) => return code,
+    };
+    if !envelope.starts_with(&ENVELOPE_V2_MAGIC) {
+        return -7;
+    }
+    let header_len = u32::from_be_bytes([envelope[4], envelope[5], envelope[6], envelope[7]]);
+    if header_len > i32::MAX as u32 || envelope_len - ENVELOPE_V2_PREAMBLE_LEN < header_len as usize
+    {
+        return -3;
+    }
+    header_len as i32
 }
 
 #[no_mangle]
@@ -341,3 +388,57 @@ pub extern "C" fn xchacha20poly1305_encrypt(
         Err(code) => code,
     }
 }
+
+#[no_mangle]
+pub extern "C" fn xchacha20poly1305_decrypt(
+    key_ptr: *const u8,
+    key_len: usize,
+    nonce_ptr: *const u8,
+    nonce_len: usize,
+    ciphertext_ptr: *const u8,
+    ciphertext_len: usize,
+    plaintext_ptr: *mut u8,
+    plaintext_len: usize,
+) -> i32 {
+    if key_len != XCHACHA20POLY1305_KEY_LEN || nonce_len != XCHACHA20POLY1305_NONCE_LEN {
+        return -2;
+    }
+    if ciphertext_len < XCHACHA20POLY1305_TAG_LEN
+        || plaintext_len != ciphertext_len - XCHACHA20POLY1305_TAG_LEN
+    {
+        return -3;
+    }
+
+    let key_bytes = match read_input(key_ptr, key_len) {
+        Ok(value) => value,
+        Err(code) => return code,
+    };
+    let nonce_bytes = match read_input(nonce_ptr, nonce_len) {
+        Ok(value) => value,
+        Err(code) => return code,
+    };
+    let ciphertext_bytes = match read_input(ciphertext_ptr, ciphertext_len) {
+        Ok(value) => value,
+        Err(code) => return code,
+    };
+
+    let key_arr: [u8; XCHACHA20POLY1305_KEY_LEN] = match key_bytes.try_into() {
+        Ok(value) => value,
+        Err(_) => return -4,
+    };
+    let nonce_arr: [u8; XCHACHA20POLY1305_NONCE_LEN] = match nonce_bytes.try_into() {
+        Ok(value) => value,
+        Err(_) => return -5,
+    };
+
+    let cipher = XChaCha20Poly1305::new((&key_arr).into());
+    let plaintext = match cipher.decrypt(XNonce::from_slice(&nonce_arr), ciphertext_bytes) {
+        Ok(value) => value,
+        Err(_) => return -6,
+    };
+
+    match write_output(plaintext_ptr, plaintext_len, &plaintext) {
+        Ok(()) => 0,
+        Err(code) => code,
+    }
+}
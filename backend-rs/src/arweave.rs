@@ -1,4 +1,6 @@
 // src/arweave.rs
+pub mod merkle;
+
 use serde::Serialize;
 
 use crate::error::{AppError, AppResult};
@@ -18,20 +20,104 @@ pub struct ArweaveAnchor {
     pub tx_id: String,
 }
 
+/// Gateway view of a submitted transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArweaveTxStatus {
+    /// Accepted by the gateway but not yet mined.
+    Pending,
+    Confirmed {
+        block_height: i64,
+        confirmations: i64,
+    },
+    /// The gateway has no record of the transaction.
+    NotFound,
+}
+
+/// What the status poller should do with a tracked anchor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnchorPollAction {
+    Wait,
+    Confirm,
+    Drop,
+}
+
+/// A transaction is confirmed once it is `required_depth` blocks deep, and
+/// treated as dropped once the gateway has not seen it for `drop_after_secs`.
+pub fn anchor_poll_action(
+    status: &ArweaveTxStatus,
+    age_secs: i64,
+    required_depth: i64,
+    drop_after_secs: i64,
+) -> AnchorPollAction {
+    match status {
+        ArweaveTxStatus::Confirmed { confirmations, .. } if *confirmations >= required_depth => {
+            AnchorPollAction::Confirm
+        }
+        ArweaveTxStatus::NotFound if age_secs >= drop_after_secs => AnchorPollAction::Drop,
+        _ => AnchorPollAction::Wait,
+    }
+}
+
 /// Minimal Arweave client wrapper
 pub struct ArweaveClient {
     endpoint: String,
+    gateway: String,
     api_key: Option<String>,
 }
 
 impl ArweaveClient {
+    pub fn new(endpoint: String, gateway: String, api_key: Option<String>) -> Self {
+        Self {
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            gateway: gateway.trim_end_matches('/').to_string(),
+            api_key,
+        }
+    }
+
     pub fn from_env() -> Self {
         let endpoint = std::env::var("ARWEAVE_ENDPOINT")
             .unwrap_or_else(|_| "https://node2.bundlr.network".to_string());
+        let gateway = std::env::var("ARWEAVE_GATEWAY_URL")
+            .unwrap_or_else(|_| "https://arweave.net".to_string());
 
         let api_key = std::env::var("ARWEAVE_API_KEY").ok();
 
-        Self { endpoint, api_key }
+        Self::new(endpoint, gateway, api_key)
+    }
+
+    /// Simulated txids are never submitted, so there is nothing to poll.
+    pub fn is_simulated_tx(tx_id: &str) -> bool {
+        tx_id.starts_with("simulated-")
+    }
+
+    /// Query `GET {gateway}/tx/{id}/status`.
+    pub async fn tx_status(&self, tx_id: &str) -> AppResult<ArweaveTxStatus> {
+        let url = format!("{}/tx/{}/status", self.gateway, tx_id);
+        let res = reqwest::Client::new()
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| AppError::Internal(format!("Arweave gateway HTTP error: {e}")))?;
+
+        match res.status() {
+            reqwest::StatusCode::ACCEPTED => Ok(ArweaveTxStatus::Pending),
+            reqwest::StatusCode::NOT_FOUND => Ok(ArweaveTxStatus::NotFound),
+            status if status.is_success() => {
+                let body: serde_json::Value = res
+                    .json()
+                    .await
+                    .map_err(|e| AppError::Internal(format!("Arweave status parse error: {e}")))?;
+                Ok(ArweaveTxStatus::Confirmed {
+                    block_height: body["block_height"].as_i64().unwrap_or_default(),
+                    confirmations: body["number_of_confirmations"].as_i64().unwrap_or_default(),
+                })
+            }
+            status => Err(AppError::Internal(format!(
+                "Arweave gateway HTTP {}: {}",
+                status,
+                res.text().await.unwrap_or_default()
+            ))),
+        }
     }
 
     /// Anchor a hash and return TXID
@@ -81,13 +167,8 @@ impl ArweaveClient {
     }
 }
 
-/// The function filetrail.rs imports:
-///
-/// ```rust
-/// use crate::arweave::anchor_hash_to_arweave;
-/// ```
-///
-/// This is a convenience wrapper around ArweaveClient.
+/// Convenience wrapper around `ArweaveClient::from_env` for one-off anchors.
+/// The document pipeline (`c2c::workflow`) takes an explicit client instead.
 pub async fn anchor_hash_to_arweave(hash_hex: &str) -> AppResult<ArweaveAnchor> {
     let client = ArweaveClient::from_env();
 
@@ -101,3 +182,111 @@ pub async fn anchor_hash_to_arweave(hash_hex: &str) -> AppResult<ArweaveAnchor>
 
     Ok(ArweaveAnchor { tx_id: txid })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        anchor_poll_action, AnchorPollAction, ArweaveAnchorPayload, ArweaveClient, ArweaveTxStatus,
+    };
+    use axum::{
+        extract::Path,
+        http::StatusCode,
+        routing::{get, post},
+        Json, Router,
+    };
+    use serde_json::json;
+
+    async fn mock_gateway() -> String {
+        let app = Router::new()
+            .route(
+                "/tx/:id/status",
+                get(|Path(id): Path<String>| async move {
+                    match id.as_str() {
+                        "mined" => (
+                            StatusCode::OK,
+                            Json(json!({
+                                "block_height": 1_234_567,
+                                "block_indep_hash": "abc",
+                                "number_of_confirmations": 12
+                            })),
+                        ),
+                        "pending" => (StatusCode::ACCEPTED, Json(json!("Pending"))),
+                        _ => (StatusCode::NOT_FOUND, Json(json!("Not Found"))),
+                    }
+                }),
+            )
+            .route(
+                "/tx",
+                post(|| async { Json(json!({ "id": "resubmitted-tx" })) }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{addr}")
+    }
+
+    #[tokio::test]
+    async fn reads_status_and_resubmits_against_mock_gateway() {
+        let base = mock_gateway().await;
+        let client = ArweaveClient::new(base.clone(), base, Some("test-key".into()));
+
+        assert_eq!(
+            client.tx_status("mined").await.unwrap(),
+            ArweaveTxStatus::Confirmed {
+                block_height: 1_234_567,
+                confirmations: 12
+            }
+        );
+        assert_eq!(
+            client.tx_status("pending").await.unwrap(),
+            ArweaveTxStatus::Pending
+        );
+        assert_eq!(
+            client.tx_status("gone").await.unwrap(),
+            ArweaveTxStatus::NotFound
+        );
+
+        let payload = ArweaveAnchorPayload {
+            kind: "file_version_hash",
+            hash_hex: "00",
+            label: None,
+        };
+        assert_eq!(
+            client.anchor_hash(&payload).await.unwrap(),
+            "resubmitted-tx"
+        );
+    }
+
+    #[test]
+    fn poll_action_waits_for_depth_and_drop_window() {
+        let shallow = ArweaveTxStatus::Confirmed {
+            block_height: 10,
+            confirmations: 3,
+        };
+        let deep = ArweaveTxStatus::Confirmed {
+            block_height: 10,
+            confirmations: 10,
+        };
+
+        assert_eq!(
+            anchor_poll_action(&shallow, 9_999, 10, 3_600),
+            AnchorPollAction::Wait
+        );
+        assert_eq!(
+            anchor_poll_action(&deep, 0, 10, 3_600),
+            AnchorPollAction::Confirm
+        );
+        assert_eq!(
+            anchor_poll_action(&ArweaveTxStatus::Pending, 9_999, 10, 3_600),
+            AnchorPollAction::Wait
+        );
+        assert_eq!(
+            anchor_poll_action(&ArweaveTxStatus::NotFound, 60, 10, 3_600),
+            AnchorPollAction::Wait
+        );
+        assert_eq!(
+            anchor_poll_action(&ArweaveTxStatus::NotFound, 3_600, 10, 3_600),
+            AnchorPollAction::Drop
+        );
+    }
+}
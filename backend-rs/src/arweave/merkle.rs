@@ -0,0 +1,204 @@
+// src/arweave/merkle.rs
+
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+
+/// Leaves and interior nodes are domain-separated so an interior node can
+/// never be presented as a leaf. An odd node at the end of a level is
+/// promoted unchanged rather than duplicated.
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Side {
+    Left,
+    Right,
+}
+
+/// One sibling on the path from a leaf up to the root.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofStep {
+    pub side: Side,
+    pub hash_hex: String,
+}
+
+/// Everything a verifier needs to go from an anchored hash to the batch root.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InclusionProof {
+    /// The hash that was queued for anchoring (hex).
+    pub leaf_hex: String,
+    pub leaf_index: usize,
+    pub leaf_count: usize,
+    pub root_hex: String,
+    pub path: Vec<ProofStep>,
+}
+
+fn leaf_hash(value: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha3_256::new();
+    hasher.update([LEAF_PREFIX]);
+    hasher.update(value);
+    hasher.finalize().into()
+}
+
+fn node_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha3_256::new();
+    hasher.update([NODE_PREFIX]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+pub struct MerkleTree {
+    leaves_hex: Vec<String>,
+    /// `levels[0]` holds the leaf hashes, the last level holds the root.
+    levels: Vec<Vec<[u8; 32]>>,
+}
+
+impl MerkleTree {
+    /// Build a tree over hex-encoded hashes, in the given order.
+    pub fn from_hex_leaves(leaves_hex: &[String]) -> Result<Self, String> {
+        if leaves_hex.is_empty() {
+            return Err("merkle tree needs at least one leaf".into());
+        }
+
+        let mut level = leaves_hex
+            .iter()
+            .map(|leaf| {
+                hex::decode(leaf.trim())
+                    .map(|bytes| leaf_hash(&bytes))
+                    .map_err(|_| format!("leaf is not valid hex: {leaf}"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut levels = Vec::new();
+        while level.len() > 1 {
+            let next = level
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => node_hash(left, right),
+                    [single] => *single,
+                    _ => unreachable!(),
+                })
+                .collect();
+            levels.push(level);
+            level = next;
+        }
+        levels.push(level);
+
+        Ok(Self {
+            leaves_hex: leaves_hex
+                .iter()
+                .map(|leaf| leaf.trim().to_ascii_lowercase())
+                .collect(),
+            levels,
+        })
+    }
+
+    pub fn root_hex(&self) -> String {
+        hex::encode(self.levels[self.levels.len() - 1][0])
+    }
+
+    pub fn proof(&self, leaf_index: usize) -> Option<InclusionProof> {
+        let leaf_hex = self.leaves_hex.get(leaf_index)?.clone();
+        let mut path = Vec::new();
+        let mut index = leaf_index;
+
+        for level in &self.levels[..self.levels.len() - 1] {
+            let sibling = index ^ 1;
+            if let Some(hash) = level.get(sibling) {
+                path.push(ProofStep {
+                    side: if sibling < index {
+                        Side::Left
+                    } else {
+                        Side::Right
+                    },
+                    hash_hex: hex::encode(hash),
+                });
+            }
+            index /= 2;
+        }
+
+        Some(InclusionProof {
+            leaf_hex,
+            leaf_index,
+            leaf_count: self.leaves_hex.len(),
+            root_hex: self.root_hex(),
+            path,
+        })
+    }
+}
+
+/// Recompute the root from `proof.leaf_hex` and the sibling path.
+pub fn verify_inclusion(proof: &InclusionProof) -> bool {
+    let Ok(leaf) = hex::decode(proof.leaf_hex.trim()) else {
+        return false;
+    };
+
+    let mut current = leaf_hash(&leaf);
+    for step in &proof.path {
+        let Ok(sibling) = hex::decode(&step.hash_hex) else {
+            return false;
+        };
+        let Ok(sibling) = <[u8; 32]>::try_from(sibling.as_slice()) else {
+            return false;
+        };
+        current = match step.side {
+            Side::Left => node_hash(&sibling, &current),
+            Side::Right => node_hash(&current, &sibling),
+        };
+    }
+
+    hex::encode(current).eq_ignore_ascii_case(proof.root_hex.trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{verify_inclusion, MerkleTree};
+
+    fn leaves(count: usize) -> Vec<String> {
+        (0..count)
+            .map(|i| hex::encode(crate::pqc::sha3::sha3_256_bytes(&[i as u8])))
+            .collect()
+    }
+
+    #[test]
+    fn every_leaf_proves_into_the_root() {
+        for count in [1, 2, 3, 5, 8, 13] {
+            let tree = MerkleTree::from_hex_leaves(&leaves(count)).unwrap();
+            for index in 0..count {
+                let proof = tree.proof(index).unwrap();
+                assert_eq!(proof.root_hex, tree.root_hex());
+                assert!(verify_inclusion(&proof), "leaf {index} of {count}");
+            }
+            assert!(tree.proof(count).is_none());
+        }
+    }
+
+    #[test]
+    fn tampered_proofs_are_rejected() {
+        let tree = MerkleTree::from_hex_leaves(&leaves(5)).unwrap();
+        let proof = tree.proof(2).unwrap();
+
+        let mut wrong_leaf = proof.clone();
+        wrong_leaf.leaf_hex = leaves(6)[5].clone();
+        assert!(!verify_inclusion(&wrong_leaf));
+
+        let mut wrong_root = proof.clone();
+        wrong_root.root_hex = leaves(1)[0].clone();
+        assert!(!verify_inclusion(&wrong_root));
+
+        let mut truncated = proof;
+        truncated.path.pop();
+        assert!(!verify_inclusion(&truncated));
+    }
+
+    #[test]
+    fn single_leaf_root_is_domain_separated() {
+        let tree = MerkleTree::from_hex_leaves(&leaves(1)).unwrap();
+        let proof = tree.proof(0).unwrap();
+        assert!(proof.path.is_empty());
+        assert!(verify_inclusion(&proof));
+        assert_ne!(tree.root_hex(), leaves(1)[0]);
+    }
+}
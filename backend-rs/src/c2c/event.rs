@@ -1,10 +1,15 @@
 //src/c2c/event.rs
 
+use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
+use base64::Engine;
 use chrono::Utc;
 use serde_json::json;
 use uuid::Uuid;
 
 use crate::c2c::types::{C2CEvent, C2CEventKind};
+use crate::c2c::verify::canonical_event_message;
+use crate::error::{AppError, AppResult};
+use crate::identity::local_wallet::DeviceKey;
 
 /// Build a new C2C event for a document hash + optional extras.
 pub fn new_doc_event(
@@ -26,6 +31,65 @@ pub fn new_doc_event(
         actor_wallet,
         kind,
         payload,
-        signature_b64: None, // for later when PQC/EVM signing is ready
+        signature_b64: None,
+    }
+}
+
+/// Sign an event with a CLI device key. The signature type, public key and
+/// device id are added to the payload first so the signature covers them.
+pub fn sign_event(mut ev: C2CEvent, key: &DeviceKey) -> AppResult<C2CEvent> {
+    let payload = match &mut ev.payload {
+        serde_json::Value::Object(map) => map,
+        _ => return Err(AppError::BadRequest("C2C payload must be an object".into())),
+    };
+    payload.insert("signature_type".into(), json!("pq_mldsa65"));
+    payload.insert("pq_public_key_b64".into(), json!(key.public_key_b64));
+    payload.insert("signer_device".into(), json!(key.device_id));
+    ev.signature_b64 = None;
+
+    let signature = key.sign(canonical_event_message(&ev).as_bytes())?;
+    ev.signature_b64 = Some(BASE64_STANDARD.encode(signature));
+    Ok(ev)
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
+    use base64::Engine;
+
+    use super::{new_doc_event, sign_event};
+    use crate::c2c::types::C2CEventKind;
+    use crate::c2c::verify::{canonical_event_message, verify_event};
+    use crate::identity::local_wallet::LocalWallet;
+
+    #[test]
+    fn device_signed_events_verify() {
+        let wallet = LocalWallet::create("passphrase").unwrap();
+        let key = wallet.unlock("passphrase").unwrap();
+        let ev = new_doc_event(
+            wallet.actor_id(),
+            C2CEventKind::DocumentUploaded,
+            "ab".repeat(32),
+            None,
+            None,
+        );
+
+        let signed = sign_event(ev, &key).unwrap();
+        assert!(verify_event(&signed).is_ok());
+
+        let mut tampered = signed.clone();
+        tampered.payload["doc_hash"] = serde_json::json!("cd".repeat(32));
+        assert!(verify_event(&tampered).is_err());
+
+        // A valid signature from another device cannot claim this device id.
+        let other = LocalWallet::create("other").unwrap().unlock("other").unwrap();
+        let mut forged = sign_event(signed, &other).unwrap();
+        forged.payload["signer_device"] = serde_json::json!(wallet.actor_id());
+        forged.signature_b64 = None;
+        let signature = other
+            .sign(canonical_event_message(&forged).as_bytes())
+            .unwrap();
+        forged.signature_b64 = Some(BASE64_STANDARD.encode(signature));
+        assert!(verify_event(&forged).is_err());
     }
 }
@@ -0,0 +1,735 @@
+// src/c2c/evidence.rs
+
+use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
+use base64::Engine;
+use serde::Serialize;
+use serde_json::json;
+
+use crate::arweave::merkle::{verify_inclusion, InclusionProof};
+use crate::c2c::types::{C2CEvent, C2CEventKind};
+use crate::c2c::verify::{document_sign_message, verify_event, HYBRID_SIGNATURE_TYPE};
+use crate::crypto::canonical::canonicalize::canonical_json;
+use crate::error::{AppError, AppResult};
+use crate::identity::local_wallet::DEVICE_ID_PREFIX;
+use crate::identity::proof_of_key::{
+    decode_pq_public_key, key_binding_message, pq_key_fingerprint_hex, verify_wallet_signature,
+};
+use crate::pqc::dilithium;
+use crate::pqc::sha3 as pqc_sha3;
+
+/// Hash that links a `document_events` row to its predecessor.
+pub fn event_chain_hash_hex(
+    doc_id: uuid::Uuid,
+    actor_wallet: &str,
+    event_type: &str,
+    payload: &serde_json::Value,
+    created_at: chrono::DateTime<chrono::Utc>,
+    prev_event_hash_hex: Option<&str>,
+) -> AppResult<String> {
+    let canonical = canonical_json(&json!({
+        "doc_id": doc_id,
+        "actor_wallet": actor_wallet,
+        "event_type": event_type,
+        "payload": payload,
+        "created_at": created_at.to_rfc3339(),
+        "prev_event_hash_hex": prev_event_hash_hex
+    }));
+    Ok(hex::encode(pqc_sha3::sha3_256_bytes(&canonical)))
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EventCheck {
+    pub id: String,
+    pub event_type: String,
+    pub sealed: bool,
+    pub chain_link_valid: bool,
+    pub event_hash_valid: bool,
+    pub signature_type: Option<String>,
+    pub signature_valid: Option<bool>,
+    /// For ML-DSA signatures: whether the key came from the exported key
+    /// history. Envelope signers without a registered key sign with an
+    /// ad-hoc key, which proves possession but not identity.
+    pub pq_key_registered: Option<bool>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileCheck {
+    pub sha3_256_hex: String,
+    pub matches_current: bool,
+    pub matched_version: Option<i64>,
+    pub matched_doc_id: Option<String>,
+}
+
+/// A batched Arweave anchor: the leaf must fold up to the anchored root.
+#[derive(Debug, Clone, Serialize)]
+pub struct AnchorProofCheck {
+    pub kind: String,
+    pub hash_hex: String,
+    pub merkle_root_hex: String,
+    pub arweave_tx: Option<String>,
+    pub valid: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EvidenceReport {
+    pub doc_id: String,
+    pub bundle_hash_valid: Option<bool>,
+    pub events: Vec<EventCheck>,
+    pub unsealed_events: usize,
+    pub signatures_checked: usize,
+    pub anchor_proofs: Vec<AnchorProofCheck>,
+    pub file: Option<FileCheck>,
+}
+
+impl EvidenceReport {
+    /// False when any event is missing its `event_hash_hex`; a chain with
+    /// gaps cannot be shown to be intact.
+    pub fn chain_complete(&self) -> bool {
+        self.unsealed_events == 0
+    }
+
+    pub fn chain_valid(&self) -> bool {
+        self.chain_complete()
+            && self
+                .events
+                .iter()
+                .all(|ev| ev.chain_link_valid && ev.event_hash_valid)
+    }
+
+    pub fn signatures_valid(&self) -> bool {
+        self.events
+            .iter()
+            .all(|ev| ev.signature_valid != Some(false))
+    }
+
+    pub fn anchor_proofs_valid(&self) -> bool {
+        self.anchor_proofs.iter().all(|proof| proof.valid)
+    }
+
+    pub fn passed(&self) -> bool {
+        self.bundle_hash_valid != Some(false)
+            && self.chain_valid()
+            && self.signatures_valid()
+            && self.anchor_proofs_valid()
+            && self
+                .file
+                .as_ref()
+                .map(|file| file.matched_version.is_some())
+                .unwrap_or(true)
+    }
+}
+
+fn str_field<'a>(value: &'a serde_json::Value, key: &str) -> Option<&'a str> {
+    value
+        .get(key)
+        .and_then(|value| value.as_str())
+        .filter(|value| !value.is_empty())
+}
+
+/// Recomputes the `bundle_hash_hex` the server derived from everything
+/// except the `evidence_bundle` summary block.
+fn bundle_hash_matches(bundle: &serde_json::Value) -> Option<bool> {
+    let expected = bundle
+        .get("evidence_bundle")
+        .and_then(|value| str_field(value, "bundle_hash_hex"))?;
+    let mut core = bundle.as_object()?.clone();
+    core.remove("evidence_bundle");
+    let canonical = canonical_json(&serde_json::Value::Object(core));
+    Some(hex::encode(pqc_sha3::sha3_256_bytes(&canonical)) == expected)
+}
+
+/// Signed custody rows carry a `signature_type` either at the top level
+/// (public envelopes) or under `verification` (wallet signing).
+fn event_signature_type(payload: &serde_json::Value) -> Option<String> {
+    str_field(payload, "completion_signature_type")
+        .or_else(|| payload.get("verification").and_then(|v| str_field(v, "signature_type")))
+        .or_else(|| str_field(payload, "signature_type"))
+        .map(ToOwned::to_owned)
+}
+
+fn signed_c2c_event(
+    id: &str,
+    event_type: &str,
+    actor_wallet: &str,
+    payload: &serde_json::Value,
+    created_at: chrono::DateTime<chrono::Utc>,
+) -> C2CEvent {
+    // Guest envelopes record the signer wallet separately from the
+    // `guest-envelope:<id>` actor used for the chain.
+    let signer = str_field(payload, "signer_wallet")
+        .filter(|_| actor_wallet.starts_with("guest-envelope:"))
+        .unwrap_or(actor_wallet);
+    let kind = C2CEventKind::from_event_type(event_type).unwrap_or(C2CEventKind::DocumentSigned);
+
+    C2CEvent {
+        id: id.to_string(),
+        timestamp: created_at.timestamp().max(0) as u64,
+        actor_wallet: signer.to_string(),
+        kind,
+        payload: payload.clone(),
+        signature_b64: None,
+    }
+}
+
+/// The bundle's own lineage entry, which fixes the hash and version every
+/// SIGN event on this document must have signed.
+fn signed_document(bundle: &serde_json::Value, doc_id: &str) -> Option<(String, i32)> {
+    let entry = bundle
+        .get("lineage")
+        .and_then(|value| value.as_array())?
+        .iter()
+        .find(|entry| str_field(entry, "id") == Some(doc_id))?;
+    let version = entry.get("version").and_then(|v| v.as_i64())?;
+    let hash_hex = str_field(entry, "hash_hex")?.to_string();
+    Some((hash_hex, i32::try_from(version).ok()?))
+}
+
+fn payload_str<'a>(payload: &'a serde_json::Value, key: &str) -> Option<&'a str> {
+    str_field(payload, key).or_else(|| payload.get("verification").and_then(|v| str_field(v, key)))
+}
+
+/// An event's `pq_public_key_b64` is only trusted when the exported
+/// `pq_signing_keys` history shows it bound to the signer's wallet, with a
+/// binding that still verifies, and active when the event was written.
+fn check_pq_key(
+    pq_keys: &[serde_json::Value],
+    signer: &str,
+    public_key_b64: &str,
+    at: chrono::DateTime<chrono::Utc>,
+) -> Result<(), String> {
+    let key = pq_keys
+        .iter()
+        .find(|key| str_field(key, "public_key_b64") == Some(public_key_b64))
+        .ok_or("ML-DSA key is not in the bundle's key history")?;
+    let wallet = str_field(key, "wallet").unwrap_or_default();
+    let chain = str_field(key, "chain").unwrap_or_default();
+    let same_wallet = match chain {
+        "evm" => wallet.eq_ignore_ascii_case(signer),
+        _ => wallet == signer,
+    };
+    if !same_wallet {
+        return Err("ML-DSA key is registered to another wallet".into());
+    }
+
+    let timestamp = |field| {
+        str_field(key, field)
+            .map(|value| chrono::DateTime::parse_from_rfc3339(value).map_err(|_| field))
+    };
+    let active = str_field(key, "status") != Some("pending")
+        && matches!(timestamp("registered_at"), Some(Ok(registered_at)) if registered_at <= at)
+        && match timestamp("retired_at") {
+            None => true,
+            Some(Ok(retired_at)) => at < retired_at,
+            Some(Err(_)) => false,
+        };
+    if !active {
+        return Err("ML-DSA key was not active when the event was written".into());
+    }
+
+    let binding = str_field(key, "binding_message").unwrap_or_default();
+    let public_key = decode_pq_public_key(public_key_b64).map_err(|e| e.to_string())?;
+    let issued_at = binding
+        .lines()
+        .find_map(|line| line.strip_prefix("Issued At: "))
+        .unwrap_or_default();
+    let fingerprint_hex = pq_key_fingerprint_hex(&public_key);
+    if binding != key_binding_message(wallet, chain, &fingerprint_hex, issued_at) {
+        return Err("ML-DSA key binding does not name this wallet and key".into());
+    }
+    let wallet_signature = str_field(key, "wallet_signature").unwrap_or_default();
+    verify_wallet_signature(chain, wallet, binding, wallet_signature)
+        .map_err(|e| format!("ML-DSA key binding: {e}"))?;
+    let pq_signature = BASE64_STANDARD
+        .decode(str_field(key, "pq_signature_b64").unwrap_or_default())
+        .map_err(|_| "ML-DSA key binding has an invalid proof of possession")?;
+    if !dilithium::verify(&public_key, binding.as_bytes(), &pq_signature).unwrap_or(false) {
+        return Err("ML-DSA key binding has an invalid proof of possession".into());
+    }
+    Ok(())
+}
+
+fn check_event(
+    doc_id: uuid::Uuid,
+    document: Option<(&str, i32)>,
+    pq_keys: &[serde_json::Value],
+    row: &serde_json::Value,
+    previous_stored_hash: Option<&str>,
+) -> EventCheck {
+    let id = str_field(row, "id").unwrap_or_default().to_string();
+    let event_type = str_field(row, "event_type").unwrap_or_default().to_string();
+    let actor_wallet = str_field(row, "actor_wallet").unwrap_or_default();
+    let payload = row.get("payload").cloned().unwrap_or(serde_json::Value::Null);
+    let stored_prev = str_field(row, "prev_event_hash_hex");
+    let stored_hash = str_field(row, "event_hash_hex");
+    let mut check = EventCheck {
+        id: id.clone(),
+        event_type: event_type.clone(),
+        sealed: stored_hash.is_some(),
+        chain_link_valid: false,
+        event_hash_valid: false,
+        signature_type: event_signature_type(&payload),
+        signature_valid: None,
+        pq_key_registered: None,
+        error: None,
+    };
+
+    let created_at = match str_field(row, "created_at")
+        .map(chrono::DateTime::parse_from_rfc3339)
+    {
+        Some(Ok(value)) => value.with_timezone(&chrono::Utc),
+        _ => {
+            check.error = Some("event created_at is missing or not RFC 3339".into());
+            return check;
+        }
+    };
+
+    if let Some(stored_hash) = stored_hash {
+        // An unsealed row has no hash to link to, so the next sealed row
+        // starts a fresh link. chain_complete() still fails the bundle.
+        check.chain_link_valid = stored_prev == previous_stored_hash;
+        check.event_hash_valid = event_chain_hash_hex(
+            doc_id,
+            actor_wallet,
+            &event_type,
+            &payload,
+            created_at,
+            stored_prev,
+        )
+        .map(|computed| computed == stored_hash)
+        .unwrap_or(false);
+    }
+
+    // A valid signature over some other message proves nothing about this
+    // document, so SIGN events must have signed this exact version.
+    if event_type == "SIGN" {
+        let expected = document.map(|(hash_hex, version)| {
+            document_sign_message(doc_id, hash_hex, actor_wallet, version)
+        });
+        let error = match expected {
+            None => Some("bundle lineage has no entry for this document"),
+            Some(expected) if str_field(&payload, "signing_message") != Some(expected.as_str()) => {
+                Some("signing_message does not match the document hash and version")
+            }
+            Some(_) if check.signature_type.is_none() => Some("SIGN event has no signature_type"),
+            Some(_) => None,
+        };
+        if let Some(error) = error {
+            check.signature_valid = Some(false);
+            check.error = Some(error.into());
+            return check;
+        }
+    }
+
+    match check.signature_type.as_deref() {
+        None | Some("guest_attestation") => {}
+        Some(signature_type) => {
+            let ev = signed_c2c_event(&id, &event_type, actor_wallet, &payload, created_at);
+            if let Err(err) = verify_event(&ev) {
+                check.signature_valid = Some(false);
+                check.error = Some(err.to_string());
+                return check;
+            }
+            check.signature_valid = Some(true);
+
+            let uses_pq_key = matches!(
+                signature_type,
+                "pq_dilithium3" | "pq_mldsa65" | HYBRID_SIGNATURE_TYPE
+            );
+            // CLI device keys are bound by their `pqdev:` id, which
+            // verify_event has already checked.
+            let device_key = ev.actor_wallet.starts_with(DEVICE_ID_PREFIX)
+                || payload_str(&payload, "signer_device").is_some();
+            let ad_hoc_key = payload
+                .get("verification")
+                .and_then(|v| v.get("pq_key_registered"))
+                .and_then(|v| v.as_bool())
+                == Some(false);
+            if uses_pq_key && !device_key {
+                check.pq_key_registered = Some(!ad_hoc_key);
+            }
+            if check.pq_key_registered == Some(true) {
+                let public_key_b64 = payload_str(&payload, "pq_public_key_b64");
+                let key_check = check_pq_key(
+                    pq_keys,
+                    &ev.actor_wallet,
+                    public_key_b64.unwrap_or_default(),
+                    created_at,
+                );
+                if let Err(err) = key_check {
+                    check.signature_valid = Some(false);
+                    check.error = Some(err);
+                }
+            }
+        }
+    }
+
+    check
+}
+
+fn check_file(bundle: &serde_json::Value, bytes: &[u8]) -> FileCheck {
+    let sha3_256_hex = hex::encode(pqc_sha3::sha3_256_bytes(bytes));
+    let current_hash = bundle
+        .get("document")
+        .and_then(|doc| str_field(doc, "hash_hex"));
+    let matched = bundle
+        .get("lineage")
+        .and_then(|value| value.as_array())
+        .into_iter()
+        .flatten()
+        .find(|entry| {
+            str_field(entry, "hash_hex")
+                .map(|hash| hash.eq_ignore_ascii_case(&sha3_256_hex))
+                .unwrap_or(false)
+        });
+
+    FileCheck {
+        matches_current: current_hash
+            .map(|hash| hash.eq_ignore_ascii_case(&sha3_256_hex))
+            .unwrap_or(false),
+        matched_version: matched.and_then(|entry| entry.get("version")).and_then(|v| v.as_i64()),
+        matched_doc_id: matched.and_then(|entry| str_field(entry, "id")).map(ToOwned::to_owned),
+        sha3_256_hex,
+    }
+}
+
+fn check_anchor_proof(entry: &serde_json::Value) -> AnchorProofCheck {
+    let hash_hex = str_field(entry, "hash_hex").unwrap_or_default();
+    let merkle_root_hex = str_field(entry, "merkle_root_hex").unwrap_or_default();
+    let valid = entry
+        .get("proof")
+        .cloned()
+        .and_then(|proof| serde_json::from_value::<InclusionProof>(proof).ok())
+        .map(|proof| {
+            proof.leaf_hex.eq_ignore_ascii_case(hash_hex)
+                && proof.root_hex.eq_ignore_ascii_case(merkle_root_hex)
+                && verify_inclusion(&proof)
+        })
+        .unwrap_or(false);
+
+    AnchorProofCheck {
+        kind: str_field(entry, "kind").unwrap_or("unknown").to_string(),
+        hash_hex: hash_hex.to_string(),
+        merkle_root_hex: merkle_root_hex.to_string(),
+        arweave_tx: str_field(entry, "arweave_tx").map(ToOwned::to_owned),
+        valid,
+    }
+}
+
+/// Verifies an exported evidence bundle without database access.
+pub fn verify_bundle(bundle: &serde_json::Value, file: Option<&[u8]>) -> AppResult<EvidenceReport> {
+    let doc_id = bundle
+        .get("document")
+        .and_then(|doc| str_field(doc, "id"))
+        .ok_or_else(|| AppError::BadRequest("Bundle is missing document.id".into()))?;
+    let doc_uuid = uuid::Uuid::parse_str(doc_id)
+        .map_err(|_| AppError::BadRequest("Bundle document.id is not a UUID".into()))?;
+    let rows = bundle
+        .get("events")
+        .and_then(|value| value.as_array())
+        .ok_or_else(|| AppError::BadRequest("Bundle is missing events".into()))?;
+
+    let document = signed_document(bundle, doc_id);
+    let document = document
+        .as_ref()
+        .map(|(hash_hex, version)| (hash_hex.as_str(), *version));
+    let pq_keys = bundle
+        .get("pq_signing_keys")
+        .and_then(|value| value.as_array())
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    let mut previous_stored_hash: Option<&str> = None;
+    let mut events = Vec::with_capacity(rows.len());
+    for row in rows {
+        let check = check_event(doc_uuid, document, pq_keys, row, previous_stored_hash);
+        events.push(check);
+        previous_stored_hash = str_field(row, "event_hash_hex");
+    }
+
+    Ok(EvidenceReport {
+        doc_id: doc_id.to_string(),
+        bundle_hash_valid: bundle_hash_matches(bundle),
+        unsealed_events: events.iter().filter(|ev| !ev.sealed).count(),
+        signatures_checked: events.iter().filter(|ev| ev.signature_valid.is_some()).count(),
+        events,
+        anchor_proofs: bundle
+            .get("anchor_proofs")
+            .and_then(|value| value.as_array())
+            .into_iter()
+            .flatten()
+            .map(check_anchor_proof)
+            .collect(),
+        file: file.map(|bytes| check_file(bundle, bytes)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
+    use base64::Engine;
+    use ed25519_dalek::{Signer, SigningKey};
+    use serde_json::json;
+
+    use super::{event_chain_hash_hex, verify_bundle};
+    use crate::c2c::verify::document_sign_message;
+    use crate::crypto::canonical::canonicalize::canonical_json;
+    use crate::identity::proof_of_key::{key_binding_message, pq_key_fingerprint_hex};
+    use crate::pqc::dilithium;
+    use crate::pqc::sha3 as pqc_sha3;
+
+    fn sealed_event(
+        doc_id: uuid::Uuid,
+        event_type: &str,
+        payload: serde_json::Value,
+        created_at: &str,
+        prev: Option<&str>,
+    ) -> serde_json::Value {
+        sealed_event_by(doc_id, "0xowner", event_type, payload, created_at, prev)
+    }
+
+    fn sealed_event_by(
+        doc_id: uuid::Uuid,
+        actor_wallet: &str,
+        event_type: &str,
+        payload: serde_json::Value,
+        created_at: &str,
+        prev: Option<&str>,
+    ) -> serde_json::Value {
+        let at = chrono::DateTime::parse_from_rfc3339(created_at)
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+        let hash =
+            event_chain_hash_hex(doc_id, actor_wallet, event_type, &payload, at, prev).unwrap();
+        json!({
+            "id": uuid::Uuid::new_v4(),
+            "event_type": event_type,
+            "actor_wallet": actor_wallet,
+            "payload": payload,
+            "created_at": created_at,
+            "prev_event_hash_hex": prev,
+            "event_hash_hex": hash
+        })
+    }
+
+    fn sample_bundle(file_hash: &str) -> serde_json::Value {
+        let doc_id = uuid::Uuid::parse_str("66202c33-f9ee-45d4-83d5-5f970a4184e2").unwrap();
+        let upload = sealed_event(doc_id, "UPLOAD", json!({ "version": 1 }), "2025-01-01T00:00:00Z", None);
+        let view = sealed_event(
+            doc_id,
+            "VIEW",
+            json!({ "version": 1 }),
+            "2025-01-02T00:00:00Z",
+            upload["event_hash_hex"].as_str(),
+        );
+        let mut bundle = json!({
+            "document": { "id": doc_id, "hash_hex": file_hash },
+            "lineage": [{ "id": doc_id, "hash_hex": file_hash, "version": 1 }],
+            "events": [upload, view]
+        });
+        let hash = hex::encode(pqc_sha3::sha3_256_bytes(&canonical_json(&bundle)));
+        bundle["evidence_bundle"] = json!({ "bundle_hash_hex": hash });
+        bundle
+    }
+
+    #[test]
+    fn intact_bundle_and_matching_file_pass() {
+        let file = b"contract v1";
+        let bundle = sample_bundle(&hex::encode(pqc_sha3::sha3_256_bytes(file)));
+
+        let report = verify_bundle(&bundle, Some(file)).unwrap();
+
+        assert_eq!(report.bundle_hash_valid, Some(true));
+        assert!(report.chain_valid());
+        assert_eq!(report.file.as_ref().unwrap().matched_version, Some(1));
+        assert!(report.passed());
+    }
+
+    #[test]
+    fn tampered_event_payload_fails_chain() {
+        let file = b"contract v1";
+        let mut bundle = sample_bundle(&hex::encode(pqc_sha3::sha3_256_bytes(file)));
+        bundle["events"][0]["payload"]["version"] = json!(2);
+
+        let report = verify_bundle(&bundle, Some(b"other bytes")).unwrap();
+
+        assert_eq!(report.bundle_hash_valid, Some(false));
+        assert!(!report.events[0].event_hash_valid);
+        assert!(report.file.as_ref().unwrap().matched_version.is_none());
+        assert!(!report.passed());
+    }
+
+    #[test]
+    fn anchor_proofs_must_reach_the_merkle_root() {
+        use crate::arweave::merkle::MerkleTree;
+
+        let file = b"contract v1";
+        let mut bundle = sample_bundle(&hex::encode(pqc_sha3::sha3_256_bytes(file)));
+        let leaves: Vec<String> = bundle["events"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|event| event["event_hash_hex"].as_str().unwrap().to_string())
+            .collect();
+        let tree = MerkleTree::from_hex_leaves(&leaves).unwrap();
+        bundle["anchor_proofs"] = json!([{
+            "kind": "event",
+            "hash_hex": leaves[1],
+            "merkle_root_hex": tree.root_hex(),
+            "arweave_tx": "simulated-root",
+            "proof": tree.proof(1).unwrap()
+        }]);
+
+        let report = verify_bundle(&bundle, None).unwrap();
+        assert!(report.anchor_proofs_valid());
+
+        bundle["anchor_proofs"][0]["hash_hex"] = json!(leaves[0]);
+        let report = verify_bundle(&bundle, None).unwrap();
+        assert!(!report.anchor_proofs_valid());
+        assert!(!report.passed());
+    }
+
+    #[test]
+    fn bundle_with_stripped_event_hashes_does_not_pass() {
+        let file = b"contract v1";
+        let mut bundle = sample_bundle(&hex::encode(pqc_sha3::sha3_256_bytes(file)));
+        for event in bundle["events"].as_array_mut().unwrap() {
+            event.as_object_mut().unwrap().remove("event_hash_hex");
+            event["prev_event_hash_hex"] = serde_json::Value::Null;
+        }
+
+        let report = verify_bundle(&bundle, Some(file)).unwrap();
+
+        assert_eq!(report.unsealed_events, 2);
+        assert!(!report.chain_complete());
+        assert!(!report.chain_valid());
+        assert!(!report.passed());
+    }
+
+    fn wallet_key() -> (SigningKey, String) {
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+        let wallet = bs58::encode(key.verifying_key().to_bytes()).into_string();
+        (key, wallet)
+    }
+
+    /// The attestation `wallet` signs for `version` of the sample document.
+    fn sign_message(bundle: &serde_json::Value, wallet: &str, version: i32) -> String {
+        let doc_id = uuid::Uuid::parse_str(bundle["document"]["id"].as_str().unwrap()).unwrap();
+        let hash_hex = bundle["document"]["hash_hex"].as_str().unwrap();
+        document_sign_message(doc_id, hash_hex, wallet, version)
+    }
+
+    /// Appends a SIGN event by `wallet`, then reseals the bundle hash.
+    fn with_sign_event(
+        mut bundle: serde_json::Value,
+        wallet: &str,
+        payload: serde_json::Value,
+    ) -> serde_json::Value {
+        let doc_id = uuid::Uuid::parse_str(bundle["document"]["id"].as_str().unwrap()).unwrap();
+        let prev = bundle["events"][1]["event_hash_hex"]
+            .as_str()
+            .map(ToOwned::to_owned);
+        let sign = sealed_event_by(
+            doc_id,
+            wallet,
+            "SIGN",
+            payload,
+            "2025-01-03T00:00:00Z",
+            prev.as_deref(),
+        );
+        bundle.as_object_mut().unwrap().remove("evidence_bundle");
+        bundle["events"].as_array_mut().unwrap().push(sign);
+        let hash = hex::encode(pqc_sha3::sha3_256_bytes(&canonical_json(&bundle)));
+        bundle["evidence_bundle"] = json!({ "bundle_hash_hex": hash });
+        bundle
+    }
+
+    fn with_solana_sign_event(bundle: serde_json::Value, version: i32) -> serde_json::Value {
+        let (key, wallet) = wallet_key();
+        let message = sign_message(&bundle, &wallet, version);
+        let payload = json!({
+            "signature": BASE64_STANDARD.encode(key.sign(message.as_bytes()).to_bytes()),
+            "signing_message": message,
+            "verification": { "signature_type": "sol_ed25519" }
+        });
+        with_sign_event(bundle, &wallet, payload)
+    }
+
+    #[test]
+    fn sign_events_must_sign_the_lineage_hash_and_version() {
+        let file = b"contract v1";
+        let file_hash = hex::encode(pqc_sha3::sha3_256_bytes(file));
+
+        let bundle = with_solana_sign_event(sample_bundle(&file_hash), 1);
+        let report = verify_bundle(&bundle, Some(file)).unwrap();
+        assert_eq!(report.events[2].signature_valid, Some(true));
+        assert!(report.passed());
+
+        // A genuine signature, but over another version of the document.
+        let bundle = with_solana_sign_event(sample_bundle(&file_hash), 2);
+        let report = verify_bundle(&bundle, Some(file)).unwrap();
+        assert_eq!(report.events[2].signature_valid, Some(false));
+        assert!(!report.passed());
+    }
+
+    #[test]
+    fn pq_signatures_need_a_key_active_in_the_exported_history() {
+        let file = b"contract v1";
+        let file_hash = hex::encode(pqc_sha3::sha3_256_bytes(file));
+        let (wallet_signer, wallet) = wallet_key();
+        let pq = dilithium::generate_keypair();
+        let public_key_b64 = BASE64_STANDARD.encode(&pq.public_key);
+
+        let binding = key_binding_message(
+            &wallet,
+            "sol",
+            &pq_key_fingerprint_hex(&pq.public_key),
+            "2024-12-31T00:00:00Z",
+        );
+        let key_entry = json!({
+            "wallet": wallet,
+            "chain": "sol",
+            "public_key_b64": public_key_b64,
+            "status": "active",
+            "binding_message": binding,
+            "wallet_signature":
+                BASE64_STANDARD.encode(wallet_signer.sign(binding.as_bytes()).to_bytes()),
+            "pq_signature_b64":
+                BASE64_STANDARD.encode(dilithium::sign(&pq.secret_key, binding.as_bytes()).unwrap()),
+            "registered_at": "2024-12-31T00:00:00Z",
+            "retired_at": null
+        });
+        let signed = |keys: serde_json::Value| {
+            let mut bundle = sample_bundle(&file_hash);
+            bundle["pq_signing_keys"] = keys;
+            let message = sign_message(&bundle, &wallet, 1);
+            let signature = dilithium::sign(&pq.secret_key, message.as_bytes()).unwrap();
+            let payload = json!({
+                "signature": BASE64_STANDARD.encode(signature),
+                "signing_message": message,
+                "verification": {
+                    "signature_type": "pq_mldsa65",
+                    "pq_public_key_b64": public_key_b64
+                }
+            });
+            verify_bundle(&with_sign_event(bundle, &wallet, payload), Some(file)).unwrap()
+        };
+
+        let report = signed(json!([key_entry]));
+        assert_eq!(report.events[2].pq_key_registered, Some(true));
+        assert!(report.passed());
+
+        // The embedded key alone is not trusted.
+        let report = signed(json!([]));
+        assert_eq!(report.events[2].signature_valid, Some(false));
+        assert!(!report.passed());
+
+        let mut retired = key_entry.clone();
+        retired["status"] = json!("rotated");
+        retired["retired_at"] = json!("2025-01-02T00:00:00Z");
+        assert!(!signed(json!([retired])).passed());
+
+        let mut unbound = key_entry.clone();
+        unbound["wallet_signature"] = key_entry["pq_signature_b64"].clone();
+        assert!(!signed(json!([unbound])).passed());
+    }
+}
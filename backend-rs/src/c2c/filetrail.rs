@@ -1,17 +1,19 @@
 // src/c2c/filetrail.rs
 
 use crate::arweave::ArweaveAnchor;
-use crate::c2c::types::C2CEvent;
+use crate::c2c::ledger::{
+    doc_uuid_for, CustodyLedger, LedgerEvent, NewLedgerEvent, FILE_VERSION_EVENT,
+};
+use crate::c2c::types::C2CEventKind;
 use crate::error::AppResult;
 
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
-use std::fs::{self, OpenOptions};
-use std::io::Write;
-use std::path::{Path, PathBuf};
+use std::fs;
+use std::path::PathBuf;
 use uuid::Uuid;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum FileAction {
     Uploaded,
     Updated,
@@ -19,6 +21,17 @@ pub enum FileAction {
     Downloaded,
 }
 
+impl FileAction {
+    pub fn event_kind(self) -> C2CEventKind {
+        match self {
+            FileAction::Uploaded => C2CEventKind::DocumentUploaded,
+            FileAction::Updated => C2CEventKind::DocumentUpdated,
+            FileAction::Shared => C2CEventKind::DocumentShared,
+            FileAction::Downloaded => C2CEventKind::DocumentDownloaded,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileVersionRecord {
     pub id: String,
@@ -36,20 +49,20 @@ pub struct FileVersionRecord {
     pub timestamp: i64,
 }
 
-fn log_dir() -> AppResult<PathBuf> {
-    let base = dirs::config_dir().unwrap_or(std::env::current_dir()?);
-    let dir = base.join("tidbit-share-weave").join("c2c");
-    fs::create_dir_all(&dir)?;
-    Ok(dir)
-}
-
 fn log_path() -> AppResult<PathBuf> {
-    Ok(log_dir()?.join("filetrail.jsonl"))
+    let base = dirs::config_dir().unwrap_or(std::env::current_dir()?);
+    Ok(base
+        .join("tidbit-share-weave")
+        .join("c2c")
+        .join("filetrail.jsonl"))
 }
 
 /// Determine next version + parent hash
-fn next_version(logical_doc_id: &str) -> AppResult<(u64, Option<String>)> {
-    let history = load_history(logical_doc_id)?;
+async fn next_version(
+    ledger: &dyn CustodyLedger,
+    logical_doc_id: &str,
+) -> AppResult<(u64, Option<String>)> {
+    let history = load_history(ledger, logical_doc_id).await?;
     if let Some(last) = history.last() {
         Ok((last.version + 1, Some(last.sha256_hex.clone())))
     } else {
@@ -57,60 +70,78 @@ fn next_version(logical_doc_id: &str) -> AppResult<(u64, Option<String>)> {
     }
 }
 
-/// Append a new FileTrail record
-pub fn record_file_version(
-    logical_doc_id: &str,
-    action: FileAction,
-    path: &Path,
-    sha256_hex: &str,
-    ev: &C2CEvent,
+/// The caller-supplied half of a FileTrail record.
+pub struct NewFileVersion<'a> {
+    pub logical_doc_id: &'a str,
+    /// Overrides the next local version number when the caller already
+    /// tracks versions (the server's `documents.version`).
+    pub version: Option<u64>,
+    pub action: FileAction,
+    pub path: &'a str,
+    pub sha256_hex: &'a str,
+}
+
+/// Append a new FileTrail record as a `FILE_VERSION` ledger event
+pub async fn record_file_version(
+    ledger: &dyn CustodyLedger,
+    new: NewFileVersion<'_>,
+    ev: &LedgerEvent,
     anchor: Option<&ArweaveAnchor>,
 ) -> AppResult<FileVersionRecord> {
-    let (version, parent_hash) = next_version(logical_doc_id)?;
+    let (next, parent_hash) = next_version(ledger, new.logical_doc_id).await?;
 
     let record = FileVersionRecord {
         id: Uuid::new_v4().to_string(),
-        logical_doc_id: logical_doc_id.to_string(),
-        version,
-        action,
+        logical_doc_id: new.logical_doc_id.to_string(),
+        version: new.version.unwrap_or(next),
+        action: new.action,
         parent_hash,
-        path: path.to_string_lossy().into_owned(),
-        sha256_hex: sha256_hex.to_string(),
-        c2c_event_id: ev.id.clone(),
+        path: new.path.to_string(),
+        sha256_hex: new.sha256_hex.to_string(),
+        c2c_event_id: ev.id.to_string(),
         arweave_tx_id: anchor.map(|a| a.tx_id.clone()),
         timestamp: Utc::now().timestamp(),
     };
 
-    let json = serde_json::to_string(&record)?;
-    let mut file = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(log_path()?)?;
-
-    file.write_all(json.as_bytes())?;
-    file.write_all(b"\n")?;
+    ledger
+        .append(NewLedgerEvent::new(
+            ev.doc_id,
+            ev.actor_wallet.clone(),
+            FILE_VERSION_EVENT,
+            serde_json::to_value(&record)?,
+        ))
+        .await?;
 
     Ok(record)
 }
 
 /// Load full version history
-pub fn load_history(logical_doc_id: &str) -> AppResult<Vec<FileVersionRecord>> {
+pub async fn load_history(
+    ledger: &dyn CustodyLedger,
+    logical_doc_id: &str,
+) -> AppResult<Vec<FileVersionRecord>> {
+    let events = ledger.events_for_doc(doc_uuid_for(logical_doc_id)).await?;
+
+    let mut out: Vec<FileVersionRecord> = events
+        .into_iter()
+        .filter(|ev| ev.event_type == FILE_VERSION_EVENT)
+        .filter_map(|ev| serde_json::from_value(ev.payload).ok())
+        .collect();
+
+    out.sort_by_key(|r| r.version);
+    Ok(out)
+}
+
+/// Records from the pre-ledger `filetrail.jsonl`, for `tidbit ledger migrate`.
+pub fn load_legacy_records() -> AppResult<Vec<FileVersionRecord>> {
     let path = log_path()?;
     if !path.exists() {
         return Ok(Vec::new());
     }
 
     let data = fs::read_to_string(path)?;
-    let mut out = Vec::new();
-
-    for line in data.lines() {
-        if let Ok(rec) = serde_json::from_str::<FileVersionRecord>(line) {
-            if rec.logical_doc_id == logical_doc_id {
-                out.push(rec);
-            }
-        }
-    }
-
-    out.sort_by_key(|r| r.version);
-    Ok(out)
+    Ok(data
+        .lines()
+        .filter_map(|line| serde_json::from_str::<FileVersionRecord>(line).ok())
+        .collect())
 }
@@ -0,0 +1,682 @@
+// src/c2c/ledger.rs
+
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use chrono::{DateTime, SubsecRound, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use uuid::Uuid;
+
+use crate::c2c::evidence::event_chain_hash_hex;
+use crate::c2c::filetrail::FileVersionRecord;
+use crate::c2c::types::C2CEvent;
+use crate::error::{AppError, AppResult};
+use crate::pqc::sha3 as pqc_sha3;
+use crate::sqlx::{self, PgPool, PgRow, Row};
+
+/// Event type used for FileTrail version records.
+pub const FILE_VERSION_EVENT: &str = "FILE_VERSION";
+
+/// Optional keyed MAC over `event_hash_hex` (the server's audit HMAC).
+pub type EventSigner = fn(&[u8]) -> Option<String>;
+
+/// The one custody event shape, identical to a `document_events` row.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LedgerEvent {
+    pub id: Uuid,
+    pub doc_id: Uuid,
+    pub actor_wallet: String,
+    pub event_type: String,
+    pub payload: Value,
+    pub created_at: DateTime<Utc>,
+    pub prev_event_hash_hex: Option<String>,
+    pub event_hash_hex: String,
+    pub event_hmac_b64: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewLedgerEvent {
+    pub doc_id: Uuid,
+    pub actor_wallet: String,
+    pub event_type: String,
+    pub payload: Value,
+}
+
+impl NewLedgerEvent {
+    pub fn new(
+        doc_id: Uuid,
+        actor_wallet: impl Into<String>,
+        event_type: impl Into<String>,
+        payload: Value,
+    ) -> Self {
+        Self {
+            doc_id,
+            actor_wallet: actor_wallet.into(),
+            event_type: event_type.into(),
+            payload,
+        }
+    }
+
+    /// Wrap a C2C event. Its id, timestamp, kind and signature go under
+    /// `payload.c2c` so `LedgerEvent::to_c2c_event` can rebuild the signed
+    /// message.
+    pub fn from_c2c(doc_id: Uuid, ev: &C2CEvent) -> Self {
+        let mut payload = match &ev.payload {
+            Value::Object(map) => map.clone(),
+            other => Map::from_iter([("value".to_string(), other.clone())]),
+        };
+        payload.insert(
+            "c2c".into(),
+            json!({
+                "id": ev.id,
+                "timestamp": ev.timestamp,
+                "kind": ev.kind,
+                "signature_b64": ev.signature_b64,
+            }),
+        );
+        Self::new(
+            doc_id,
+            ev.actor_wallet.clone(),
+            ev.kind.as_event_type(),
+            Value::Object(payload),
+        )
+    }
+
+    /// Chain the event onto `prev_event_hash_hex`. The timestamp is cut to
+    /// microseconds so the hash survives a round trip through Postgres.
+    pub fn seal(
+        self,
+        prev_event_hash_hex: Option<String>,
+        signer: Option<EventSigner>,
+    ) -> AppResult<LedgerEvent> {
+        let created_at = Utc::now().trunc_subsecs(6);
+        let event_hash_hex = event_chain_hash_hex(
+            self.doc_id,
+            &self.actor_wallet,
+            &self.event_type,
+            &self.payload,
+            created_at,
+            prev_event_hash_hex.as_deref(),
+        )?;
+        let event_hmac_b64 = signer.and_then(|sign| sign(event_hash_hex.as_bytes()));
+
+        Ok(LedgerEvent {
+            id: Uuid::new_v4(),
+            doc_id: self.doc_id,
+            actor_wallet: self.actor_wallet,
+            event_type: self.event_type,
+            payload: self.payload,
+            created_at,
+            prev_event_hash_hex,
+            event_hash_hex,
+            event_hmac_b64,
+        })
+    }
+}
+
+impl LedgerEvent {
+    pub fn hash_valid(&self) -> bool {
+        event_chain_hash_hex(
+            self.doc_id,
+            &self.actor_wallet,
+            &self.event_type,
+            &self.payload,
+            self.created_at,
+            self.prev_event_hash_hex.as_deref(),
+        )
+        .map(|hash| hash == self.event_hash_hex)
+        .unwrap_or(false)
+    }
+
+    /// The original C2C event for entries written with `from_c2c`.
+    pub fn to_c2c_event(&self) -> Option<C2CEvent> {
+        let mut payload = self.payload.as_object()?.clone();
+        let c2c = payload.remove("c2c")?;
+        Some(C2CEvent {
+            id: c2c.get("id")?.as_str()?.to_string(),
+            timestamp: c2c.get("timestamp")?.as_u64()?,
+            actor_wallet: self.actor_wallet.clone(),
+            kind: serde_json::from_value(c2c.get("kind")?.clone()).ok()?,
+            payload: Value::Object(payload),
+            signature_b64: c2c
+                .get("signature_b64")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+        })
+    }
+
+    /// `payload.legacy.id` for events imported from the old stores.
+    pub fn legacy_id(&self) -> Option<&str> {
+        self.payload
+            .get("legacy")
+            .and_then(|legacy| legacy.get("id"))
+            .and_then(Value::as_str)
+    }
+}
+
+/// Check hashes and links for one document's events, oldest first.
+pub fn verify_chain(events: &[LedgerEvent]) -> Result<(), String> {
+    let mut prev: Option<&str> = None;
+    for ev in events {
+        if ev.prev_event_hash_hex.as_deref() != prev {
+            return Err(format!("event {} does not link to its predecessor", ev.id));
+        }
+        if !ev.hash_valid() {
+            return Err(format!("event {} hash does not match its contents", ev.id));
+        }
+        prev = Some(&ev.event_hash_hex);
+    }
+    Ok(())
+}
+
+/// Map a free-form document key (CLI logical id, content hash) onto the
+/// ledger's `doc_id`. UUIDs pass through so server ids line up.
+pub fn doc_uuid_for(key: &str) -> Uuid {
+    let key = key.trim();
+    if let Ok(id) = Uuid::parse_str(key) {
+        return id;
+    }
+    let digest = pqc_sha3::sha3_256_bytes(format!("tidbit-doc-key:{key}").as_bytes());
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&digest[..16]);
+    Uuid::from_bytes(bytes)
+}
+
+#[async_trait]
+pub trait CustodyLedger: Send + Sync {
+    /// Append an event, chained to the latest event for the same document.
+    async fn append(&self, event: NewLedgerEvent) -> AppResult<LedgerEvent>;
+
+    /// One document's events, oldest first.
+    async fn events_for_doc(&self, doc_id: Uuid) -> AppResult<Vec<LedgerEvent>>;
+
+    /// Whether an event imported from `legacy_id` already exists.
+    async fn contains_legacy(&self, legacy_id: &str) -> AppResult<bool>;
+}
+
+// ================================================================
+// POSTGRES (document_events)
+// ================================================================
+
+#[derive(Clone)]
+pub struct PgLedger {
+    db: PgPool,
+    signer: Option<EventSigner>,
+}
+
+impl PgLedger {
+    pub fn new(db: PgPool) -> Self {
+        Self { db, signer: None }
+    }
+
+    pub fn with_signer(mut self, signer: EventSigner) -> Self {
+        self.signer = Some(signer);
+        self
+    }
+}
+
+fn ledger_event_from_row(row: &PgRow) -> LedgerEvent {
+    LedgerEvent {
+        id: row.get("id"),
+        doc_id: row.get("doc_id"),
+        actor_wallet: row.get("actor_wallet"),
+        event_type: row.get("event_type"),
+        payload: row.get("payload"),
+        created_at: row.get("created_at"),
+        prev_event_hash_hex: row.get("prev_event_hash_hex"),
+        event_hash_hex: row
+            .get::<Option<String>, _>("event_hash_hex")
+            .unwrap_or_default(),
+        event_hmac_b64: row.get("event_hmac_b64"),
+    }
+}
+
+#[async_trait]
+impl CustodyLedger for PgLedger {
+    async fn append(&self, event: NewLedgerEvent) -> AppResult<LedgerEvent> {
+        // Concurrent writers (handlers, the scheduler, the anchor batcher and
+        // poller) append to the same document, so the head read and the
+        // insert run under one per-document lock or the chain could fork.
+        let mut tx = self
+            .db
+            .begin()
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+        sqlx::query("select pg_advisory_xact_lock(hashtext($1::text))")
+            .bind(event.doc_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+        let previous = sqlx::query(
+            r#"
+            select event_hash_hex
+            from document_events
+            where doc_id = $1
+            order by created_at desc, id desc
+            limit 1
+            "#,
+        )
+        .bind(event.doc_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+        let prev_event_hash_hex =
+            previous.and_then(|row| row.get::<Option<String>, _>("event_hash_hex"));
+        let ev = event.seal(prev_event_hash_hex, self.signer)?;
+
+        sqlx::query(
+            r#"
+            insert into document_events (
+                id,
+                doc_id,
+                actor_wallet,
+                event_type,
+                payload,
+                created_at,
+                prev_event_hash_hex,
+                event_hash_hex,
+                event_hmac_b64
+            )
+            values ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            "#,
+        )
+        .bind(ev.id)
+        .bind(ev.doc_id)
+        .bind(&ev.actor_wallet)
+        .bind(&ev.event_type)
+        .bind(&ev.payload)
+        .bind(ev.created_at)
+        .bind(&ev.prev_event_hash_hex)
+        .bind(&ev.event_hash_hex)
+        .bind(&ev.event_hmac_b64)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+        tx.commit()
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+
+        Ok(ev)
+    }
+
+    async fn events_for_doc(&self, doc_id: Uuid) -> AppResult<Vec<LedgerEvent>> {
+        let rows = sqlx::query(
+            r#"
+            select id, doc_id, actor_wallet, event_type, payload, created_at,
+                   prev_event_hash_hex, event_hash_hex, event_hmac_b64
+            from document_events
+            where doc_id = $1
+            order by created_at asc, id asc
+            "#,
+        )
+        .bind(doc_id)
+        .fetch_all(&self.db)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+        Ok(rows.iter().map(ledger_event_from_row).collect())
+    }
+
+    async fn contains_legacy(&self, legacy_id: &str) -> AppResult<bool> {
+        let row = sqlx::query(
+            "select exists(select 1 from document_events where payload->'legacy'->>'id' = $1) as found",
+        )
+        .bind(legacy_id)
+        .fetch_one(&self.db)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+        Ok(row.get::<bool, _>("found"))
+    }
+}
+
+// ================================================================
+// LOCAL FILE (JSON lines)
+// ================================================================
+
+/// Append-only JSONL ledger used by the CLI when no database is involved.
+#[derive(Clone)]
+pub struct FileLedger {
+    path: PathBuf,
+}
+
+impl FileLedger {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Uses `TIDBIT_LEDGER_PATH`, falling back to `~/.tidbit/ledger.jsonl`.
+    pub fn from_env() -> Self {
+        let path = std::env::var("TIDBIT_LEDGER_PATH")
+            .ok()
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+            .map(PathBuf::from)
+            .unwrap_or_else(|| {
+                let mut path = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
+                path.push(".tidbit/ledger.jsonl");
+                path
+            });
+        Self::new(path)
+    }
+
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+
+    /// Every event in append order.
+    pub fn all_events(&self) -> AppResult<Vec<LedgerEvent>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let data = fs::read_to_string(&self.path)?;
+        data.lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| serde_json::from_str(line).map_err(AppError::from))
+            .collect()
+    }
+
+    pub fn find(&self, id: &str) -> AppResult<Option<LedgerEvent>> {
+        Ok(self
+            .all_events()?
+            .into_iter()
+            .find(|ev| ev.id.to_string() == id.trim()))
+    }
+}
+
+#[async_trait]
+impl CustodyLedger for FileLedger {
+    async fn append(&self, event: NewLedgerEvent) -> AppResult<LedgerEvent> {
+        let prev_event_hash_hex = self
+            .events_for_doc(event.doc_id)
+            .await?
+            .pop()
+            .map(|ev| ev.event_hash_hex);
+        let ev = event.seal(prev_event_hash_hex, None)?;
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        let mut line = serde_json::to_vec(&ev)?;
+        line.push(b'\n');
+        file.write_all(&line)?;
+
+        Ok(ev)
+    }
+
+    async fn events_for_doc(&self, doc_id: Uuid) -> AppResult<Vec<LedgerEvent>> {
+        Ok(self
+            .all_events()?
+            .into_iter()
+            .filter(|ev| ev.doc_id == doc_id)
+            .collect())
+    }
+
+    async fn contains_legacy(&self, legacy_id: &str) -> AppResult<bool> {
+        Ok(self
+            .all_events()?
+            .iter()
+            .any(|ev| ev.legacy_id() == Some(legacy_id)))
+    }
+}
+
+// ================================================================
+// LEGACY IMPORT
+// ================================================================
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ImportSummary {
+    pub imported: usize,
+    pub skipped: usize,
+}
+
+/// Import old `C2CEvent`s (JSON files or `c2c_events` rows). The original
+/// id, timestamp and signature move under `payload.legacy`; the ledger
+/// timestamp is the import time so existing chains stay in order.
+pub async fn import_c2c_events(
+    ledger: &dyn CustodyLedger,
+    source: &str,
+    mut events: Vec<C2CEvent>,
+) -> AppResult<ImportSummary> {
+    events.sort_by_key(|ev| ev.timestamp);
+    let mut summary = ImportSummary::default();
+
+    for ev in events {
+        if ledger.contains_legacy(&ev.id).await? {
+            summary.skipped += 1;
+            continue;
+        }
+
+        let doc_key = ["document_id", "doc_hash", "hash_hex"]
+            .iter()
+            .find_map(|key| ev.payload.get(*key).and_then(Value::as_str))
+            .unwrap_or(&ev.id)
+            .to_string();
+        let mut payload = match ev.payload {
+            Value::Object(map) => Value::Object(map),
+            other => json!({ "value": other }),
+        };
+        payload["legacy"] = json!({
+            "source": source,
+            "id": ev.id,
+            "timestamp": ev.timestamp,
+            "signature_b64": ev.signature_b64,
+        });
+
+        ledger
+            .append(NewLedgerEvent::new(
+                doc_uuid_for(&doc_key),
+                ev.actor_wallet,
+                ev.kind.as_event_type(),
+                payload,
+            ))
+            .await?;
+        summary.imported += 1;
+    }
+
+    Ok(summary)
+}
+
+/// Import records from the old `filetrail.jsonl` as `FILE_VERSION` events.
+/// FileTrail never stored an actor, so it is taken from the linked C2C event
+/// in `actors` (event id -> wallet) when there is one.
+pub async fn import_filetrail_records(
+    ledger: &dyn CustodyLedger,
+    actors: &HashMap<String, String>,
+    mut records: Vec<FileVersionRecord>,
+) -> AppResult<ImportSummary> {
+    records.sort_by_key(|rec| (rec.timestamp, rec.version));
+    let mut summary = ImportSummary::default();
+
+    for rec in records {
+        if ledger.contains_legacy(&rec.id).await? {
+            summary.skipped += 1;
+            continue;
+        }
+
+        let mut payload = serde_json::to_value(&rec)?;
+        payload["legacy"] = json!({
+            "source": "filetrail",
+            "id": rec.id,
+            "timestamp": rec.timestamp,
+        });
+
+        ledger
+            .append(NewLedgerEvent::new(
+                doc_uuid_for(&rec.logical_doc_id),
+                actors
+                    .get(&rec.c2c_event_id)
+                    .map(String::as_str)
+                    .unwrap_or("local"),
+                FILE_VERSION_EVENT,
+                payload,
+            ))
+            .await?;
+        summary.imported += 1;
+    }
+
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::c2c::filetrail::FileAction;
+    use crate::c2c::types::C2CEventKind;
+
+    fn temp_ledger() -> FileLedger {
+        FileLedger::new(
+            std::env::temp_dir().join(format!("tidbit-ledger-{}.jsonl", Uuid::new_v4())),
+        )
+    }
+
+    #[tokio::test]
+    async fn file_ledger_chains_events_per_document() {
+        let ledger = temp_ledger();
+        let doc_a = doc_uuid_for("logical-a");
+        let doc_b = doc_uuid_for("logical-b");
+
+        let first = ledger
+            .append(NewLedgerEvent::new(
+                doc_a,
+                "0xabc",
+                "UPLOAD",
+                json!({ "n": 1 }),
+            ))
+            .await
+            .unwrap();
+        ledger
+            .append(NewLedgerEvent::new(doc_b, "0xabc", "UPLOAD", json!({})))
+            .await
+            .unwrap();
+        let second = ledger
+            .append(NewLedgerEvent::new(
+                doc_a,
+                "0xdef",
+                "SIGN",
+                json!({ "n": 2 }),
+            ))
+            .await
+            .unwrap();
+
+        assert_eq!(first.prev_event_hash_hex, None);
+        assert_eq!(
+            second.prev_event_hash_hex.as_deref(),
+            Some(first.event_hash_hex.as_str())
+        );
+
+        let events = ledger.events_for_doc(doc_a).await.unwrap();
+        assert_eq!(events, vec![first.clone(), second]);
+        assert!(verify_chain(&events).is_ok());
+
+        let mut tampered = events;
+        tampered[0].payload = json!({ "n": 9 });
+        assert!(verify_chain(&tampered).is_err());
+
+        assert_eq!(ledger.find(&first.id.to_string()).unwrap(), Some(first));
+        let _ = fs::remove_file(&ledger.path);
+    }
+
+    #[tokio::test]
+    async fn legacy_import_is_idempotent() {
+        let ledger = temp_ledger();
+        let legacy = C2CEvent {
+            id: "legacy-1".into(),
+            timestamp: 1_700_000_000,
+            actor_wallet: "0xabc".into(),
+            kind: C2CEventKind::DocumentUploaded,
+            payload: json!({ "doc_hash": "aa11" }),
+            signature_b64: None,
+        };
+        let record = FileVersionRecord {
+            id: "trail-1".into(),
+            logical_doc_id: "logical-a".into(),
+            version: 1,
+            action: FileAction::Uploaded,
+            parent_hash: None,
+            path: "/tmp/a.pdf".into(),
+            sha256_hex: "bb22".into(),
+            c2c_event_id: "legacy-1".into(),
+            arweave_tx_id: None,
+            timestamp: 1_700_000_001,
+        };
+
+        for expected in [
+            ImportSummary {
+                imported: 1,
+                skipped: 0,
+            },
+            ImportSummary {
+                imported: 0,
+                skipped: 1,
+            },
+        ] {
+            let c2c = import_c2c_events(&ledger, "c2c_events_json", vec![legacy.clone()])
+                .await
+                .unwrap();
+            let actors = HashMap::from([(legacy.id.clone(), legacy.actor_wallet.clone())]);
+            let trail = import_filetrail_records(&ledger, &actors, vec![record.clone()])
+                .await
+                .unwrap();
+            assert_eq!(c2c, expected);
+            assert_eq!(trail, expected);
+        }
+
+        let events = ledger.all_events().unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].doc_id, doc_uuid_for("aa11"));
+        assert_eq!(events[0].event_type, "UPLOAD");
+        assert_eq!(events[1].event_type, FILE_VERSION_EVENT);
+        assert_eq!(events[1].legacy_id(), Some("trail-1"));
+        assert_eq!(events[1].actor_wallet, "0xabc");
+        let _ = fs::remove_file(&ledger.path);
+    }
+
+    /// Runs against a scratch database with `migrations/` applied when
+    /// `TEST_DATABASE_URL` is set.
+    #[tokio::test]
+    async fn concurrent_pg_appends_keep_one_chain() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL is not set");
+            return;
+        };
+        let db = crate::sqlx::postgres::PgPoolOptions::new()
+            .max_connections(8)
+            .connect(&url)
+            .await
+            .unwrap();
+        let doc_id = Uuid::new_v4();
+        sqlx::query(
+            "insert into documents (id, owner_wallet, hash_hex, file_size, mime_type, storage_path) values ($1, '0xabc', $2, 0, 'text/plain', 'test')",
+        )
+        .bind(doc_id)
+        .bind(doc_id.simple().to_string())
+        .execute(&db)
+        .await
+        .unwrap();
+
+        let ledger = PgLedger::new(db.clone());
+        let appends = (0..16).map(|_| {
+            let ledger = ledger.clone();
+            tokio::spawn(async move {
+                ledger
+                    .append(NewLedgerEvent::new(doc_id, "0xabc", "VIEW", json!({})))
+                    .await
+            })
+        });
+        for append in appends.collect::<Vec<_>>() {
+            append.await.unwrap().unwrap();
+        }
+
+        let events = ledger.events_for_doc(doc_id).await.unwrap();
+        assert_eq!(events.len(), 16);
+        assert!(verify_chain(&events).is_ok());
+    }
+}
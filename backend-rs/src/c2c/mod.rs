@@ -1,9 +1,12 @@
 // src/c2c/mod.rs
 
 pub mod event;
+pub mod evidence;
 pub mod filetrail;
+pub mod ledger;
 pub mod onchain;
 pub mod record;
 pub mod store;
 pub mod types;
 pub mod verify;
+pub mod workflow;
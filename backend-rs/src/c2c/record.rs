@@ -1,56 +1,26 @@
 // src/c2c/record.rs
 
 use crate::c2c::event::new_doc_event;
-use crate::c2c::store::store_local_event;
-use crate::c2c::types::{C2CEvent, C2CEventKind};
-use crate::error::{AppError, AppResult};
+use crate::c2c::ledger::{doc_uuid_for, CustodyLedger, FileLedger, LedgerEvent, NewLedgerEvent};
+use crate::c2c::types::C2CEventKind;
+use crate::error::AppResult;
 
-/// Record a "document uploaded" C2C event.
-pub fn record_upload_event(
-    actor_wallet: String,
-    doc_hash_hex: String,
-    arweave_tx: Option<String>,
-) -> AppResult<C2CEvent> {
-    let ev = new_doc_event(
-        actor_wallet,
-        C2CEventKind::DocumentUploaded,
-        doc_hash_hex,
-        arweave_tx,
-        None,
-    );
-    store_local_event(&ev)?;
-    Ok(ev)
-}
-
-/// Record a "document signed" C2C event.
-pub fn record_sign_event(actor_wallet: String, doc_hash_hex: String) -> AppResult<C2CEvent> {
-    let ev = new_doc_event(
-        actor_wallet,
-        C2CEventKind::DocumentSigned,
-        doc_hash_hex,
-        None,
-        None,
-    );
-    store_local_event(&ev)?;
-    Ok(ev)
-}
-
-/// Record a "document shared" C2C event.
-pub fn record_share_event(
+/// Record a "document shared" C2C event in the local custody ledger.
+pub async fn record_share_event(
     from_wallet: String,
     to_wallet: String,
     envelope_id: String,
-) -> Result<C2CEvent, AppError> {
+) -> AppResult<LedgerEvent> {
     let ev = new_doc_event(
         from_wallet,
         C2CEventKind::DocumentShared,
-        envelope_id,
+        envelope_id.clone(),
         None,
         Some(serde_json::json!({
             "to": to_wallet
         })),
     );
-
-    store_local_event(&ev)?;
-    Ok(ev)
+    FileLedger::from_env()
+        .append(NewLedgerEvent::from_c2c(doc_uuid_for(&envelope_id), &ev))
+        .await
 }
@@ -1,9 +1,10 @@
 // src/c2c/store.rs
+//
+// Read-only access to the pre-ledger event stores, kept for
+// `tidbit ledger migrate`. New events go through `c2c::ledger`.
 
 use std::{fs, path::PathBuf};
 
-use serde_json::Value;
-
 use crate::c2c::types::{C2CEvent, C2CEventKind};
 use crate::error::{AppError, AppResult};
 use crate::sqlx::{self, PgPool, Row};
@@ -15,24 +16,10 @@ fn events_dir() -> PathBuf {
     dir
 }
 
-fn event_path(id: &str) -> PathBuf {
-    let mut p = events_dir();
-    p.push(format!("{id}.json"));
-    p
-}
-
 // ================================================================
 // LOCAL (JSON) STORE
 // ================================================================
 
-pub fn store_local_event(ev: &C2CEvent) -> AppResult<()> {
-    let dir = events_dir();
-    fs::create_dir_all(&dir)?;
-    let json = serde_json::to_string_pretty(ev)?;
-    fs::write(event_path(&ev.id), json)?;
-    Ok(())
-}
-
 pub fn load_all_events() -> AppResult<Vec<C2CEvent>> {
     let dir = events_dir();
     let mut out = Vec::new();
@@ -58,109 +45,30 @@ pub fn load_all_events() -> AppResult<Vec<C2CEvent>> {
     }
 
     out.sort_by_key(|e| e.timestamp);
-    out.reverse();
     Ok(out)
 }
 
-pub fn load_event_by_id(id: &str) -> AppResult<Option<C2CEvent>> {
-    let path = event_path(id);
-    if !path.exists() {
-        return Ok(None);
-    }
-    let data = fs::read_to_string(path)?;
-    let ev = serde_json::from_str(&data)?;
-    Ok(Some(ev))
-}
-
 // ================================================================
 // DB STORE
 // ================================================================
 
-fn kind_to_string(kind: &C2CEventKind) -> &'static str {
-    match kind {
-        C2CEventKind::DocumentUploaded => "UPLOAD",
-        C2CEventKind::DocumentDownloaded => "DOWNLOAD",
-        C2CEventKind::DocumentSigned => "SIGN",
-        C2CEventKind::DocumentUpdated => "UPDATE",
-        C2CEventKind::DocumentShared => "SHARE",
-    }
-}
-
-fn kind_from_string(s: &str) -> C2CEventKind {
-    match s {
-        "UPLOAD" => C2CEventKind::DocumentUploaded,
-        "DOWNLOAD" => C2CEventKind::DocumentDownloaded,
-        "SIGN" => C2CEventKind::DocumentSigned,
-        "UPDATE" => C2CEventKind::DocumentUpdated,
-        "SHARE" => C2CEventKind::DocumentShared,
-        _ => C2CEventKind::DocumentUpdated, // safe fallback
-    }
-}
-
-pub async fn store_db_event(db: &PgPool, ev: &C2CEvent, ip_address: Option<&str>) -> AppResult<()> {
-    let document_id = ev.payload.get("document_id").and_then(Value::as_str);
-    let version_id = ev.payload.get("version_id").and_then(Value::as_str);
-    let hash_hex = ev.payload.get("hash_hex").and_then(Value::as_str);
-
-    let document_id = document_id
-        .and_then(|s| uuid::Uuid::parse_str(s).ok())
-        .ok_or_else(|| AppError::Internal("Missing document_id in C2C payload".into()))?;
-
-    let version_id = version_id.and_then(|s| uuid::Uuid::parse_str(s).ok());
-
-    sqlx::query(
-        r#"
-        insert into c2c_events (
-            id,
-            owner_wallet,
-            document_id,
-            version_id,
-            action,
-            hash_hex,
-            signature,
-            ip_address,
-            created_at
-        )
-        values ($1,$2,$3,$4,$5,$6,$7,$8, to_timestamp($9 / 1000.0))
-        "#,
-    )
-    .bind(&ev.id)
-    .bind(&ev.actor_wallet)
-    .bind(document_id)
-    .bind(version_id)
-    .bind(kind_to_string(&ev.kind))
-    .bind(hash_hex)
-    .bind(&ev.signature_b64)
-    .bind(ip_address)
-    .bind(ev.timestamp as i64)
-    .execute(db)
-    .await
-    .map_err(|e| AppError::Internal(e.to_string()))?;
-
-    Ok(())
-}
-
-pub async fn load_db_events_for_document(
-    db: &PgPool,
-    owner_wallet: &str,
-    document_id: uuid::Uuid,
-) -> AppResult<Vec<C2CEvent>> {
+pub async fn load_db_events(db: &PgPool) -> AppResult<Vec<C2CEvent>> {
     let rows = sqlx::query(
         r#"
         select
           id,
           owner_wallet,
+          document_id,
+          version_id,
           action,
           hash_hex,
           signature,
-          extract(epoch from created_at) * 1000 as ts
+          ip_address,
+          extract(epoch from created_at)::float8 as ts
         from c2c_events
-        where owner_wallet = $1 and document_id = $2
-        order by created_at desc
+        order by created_at asc
         "#,
     )
-    .bind(owner_wallet)
-    .bind(document_id)
     .fetch_all(db)
     .await
     .map_err(|e| AppError::Internal(e.to_string()))?;
@@ -168,19 +76,25 @@ pub async fn load_db_events_for_document(
     let mut out = Vec::new();
 
     for row in rows {
-        let id: uuid::Uuid = row.get("id");
-        let owner_wallet: String = row.get("owner_wallet");
         let action: String = row.get("action");
+        let document_id: uuid::Uuid = row.get("document_id");
+        let version_id: Option<uuid::Uuid> = row.get("version_id");
         let hash_hex: Option<String> = row.get("hash_hex");
-        let signature: Option<String> = row.get("signature");
+        let ip_address: Option<String> = row.get("ip_address");
         let ts: f64 = row.get("ts");
 
         out.push(C2CEvent {
-            id: id.to_string(),
-            actor_wallet: owner_wallet,
-            kind: kind_from_string(&action),
-            payload: serde_json::json!({ "hash_hex": hash_hex }),
-            signature_b64: signature,
+            id: row.get("id"),
+            actor_wallet: row.get("owner_wallet"),
+            kind: C2CEventKind::from_event_type(&action).unwrap_or(C2CEventKind::DocumentUpdated),
+            payload: serde_json::json!({
+                "document_id": document_id,
+                "version_id": version_id,
+                "hash_hex": hash_hex,
+                "ip_address": ip_address,
+                "action": action,
+            }),
+            signature_b64: row.get("signature"),
             timestamp: ts as u64,
         });
     }
@@ -11,6 +11,30 @@ pub enum C2CEventKind {
     DocumentShared,
 }
 
+impl C2CEventKind {
+    pub fn as_event_type(&self) -> &'static str {
+        match self {
+            C2CEventKind::DocumentUploaded => "UPLOAD",
+            C2CEventKind::DocumentDownloaded => "DOWNLOAD",
+            C2CEventKind::DocumentSigned => "SIGN",
+            C2CEventKind::DocumentUpdated => "VERSION_CREATED",
+            C2CEventKind::DocumentShared => "SHARE",
+        }
+    }
+
+    /// Accepts the ledger names plus the old `c2c_events.action` values.
+    pub fn from_event_type(event_type: &str) -> Option<Self> {
+        Some(match event_type {
+            "UPLOAD" => C2CEventKind::DocumentUploaded,
+            "DOWNLOAD" => C2CEventKind::DocumentDownloaded,
+            "SIGN" => C2CEventKind::DocumentSigned,
+            "VERSION_CREATED" | "UPDATE" => C2CEventKind::DocumentUpdated,
+            "SHARE" => C2CEventKind::DocumentShared,
+            _ => return None,
+        })
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct C2CEvent {
     pub id: String,
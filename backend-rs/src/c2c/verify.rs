@@ -4,6 +4,7 @@ use base64::Engine;
 use crate::c2c::types::C2CEvent;
 use crate::crypto::canonical::canonicalize::canonical_json;
 use crate::error::{AppError, AppResult};
+use crate::identity::local_wallet::{device_id_for, DEVICE_ID_PREFIX};
 use crate::identity_web::evm::verify_evm_signature;
 use crate::identity_web::sol::verify_solana_signature;
 use crate::pqc::dilithium;
@@ -20,7 +21,8 @@ fn payload_string(payload: &serde_json::Value, key: &str) -> Option<String> {
         .map(ToOwned::to_owned)
 }
 
-fn canonical_event_message(ev: &C2CEvent) -> String {
+/// The message a C2C event signature covers: every field except the signature.
+pub fn canonical_event_message(ev: &C2CEvent) -> String {
     String::from_utf8(canonical_json(&serde_json::json!({
         "id": ev.id,
         "timestamp": ev.timestamp,
@@ -31,6 +33,28 @@ fn canonical_event_message(ev: &C2CEvent) -> String {
     .unwrap_or_default()
 }
 
+/// The message a wallet signs for a SIGN event. It binds the signature to
+/// one document version and its content hash.
+pub fn document_sign_message(
+    doc_id: uuid::Uuid,
+    hash_hex: &str,
+    wallet: &str,
+    version: i32,
+) -> String {
+    format!(
+        "TIDBIT Document Attestation\n\
+Document ID: {doc_id}\n\
+Hash: {hash_hex}\n\
+Action: SIGN\n\
+Wallet: {wallet}\n\
+Version: {version}"
+    )
+}
+
+/// A wallet signature and an ML-DSA signature over the same message. The
+/// event is valid only if both verify.
+pub const HYBRID_SIGNATURE_TYPE: &str = "hybrid_mldsa65";
+
 pub fn verify_event(ev: &C2CEvent) -> AppResult<()> {
     let actor_wallet = ev.actor_wallet.trim();
     if actor_wallet.is_empty() {
@@ -45,12 +69,57 @@ pub fn verify_event(ev: &C2CEvent) -> AppResult<()> {
     let signing_message = payload_string(&ev.payload, "signing_message")
         .unwrap_or_else(|| canonical_event_message(ev));
 
-    match signature_type.as_str() {
+    if signature_type == HYBRID_SIGNATURE_TYPE {
+        let wallet_signature_type = payload_string(&ev.payload, "wallet_signature_type")
+            .ok_or_else(|| {
+                AppError::BadRequest("Hybrid event is missing wallet_signature_type".into())
+            })?;
+        if !matches!(
+            wallet_signature_type.as_str(),
+            "evm_personal_sign" | "sol_ed25519"
+        ) {
+            return Err(AppError::BadRequest(format!(
+                "Unsupported hybrid wallet signature: {wallet_signature_type}"
+            )));
+        }
+        verify_signature(
+            ev,
+            &wallet_signature_type,
+            &signing_message,
+            payload_string(&ev.payload, "wallet_signature"),
+        )?;
+        return verify_signature(
+            ev,
+            "pq_mldsa65",
+            &signing_message,
+            payload_string(&ev.payload, "pq_signature"),
+        );
+    }
+
+    let signature = payload_string(&ev.payload, "signature").or_else(|| ev.signature_b64.clone());
+    verify_signature(ev, &signature_type, &signing_message, signature)
+}
+
+fn verify_signature(
+    ev: &C2CEvent,
+    signature_type: &str,
+    signing_message: &str,
+    signature: Option<String>,
+) -> AppResult<()> {
+    let actor_wallet = ev.actor_wallet.trim();
+
+    match signature_type {
         "evm_personal_sign" => {
-            let signature = payload_string(&ev.payload, "signature")
-                .or_else(|| ev.signature_b64.clone())
+            // Contract wallet signatures depend on chain state at signing
+            // time, so recovery cannot check them offline.
+            if payload_string(&ev.payload, "verification_method").as_deref() == Some("eip1271") {
+                return Err(AppError::BadRequest(
+                    "EIP-1271 signatures must be checked against the contract on-chain".into(),
+                ));
+            }
+            let signature = signature
                 .ok_or_else(|| AppError::BadRequest("Event is missing EVM signature".into()))?;
-            let recovered = verify_evm_signature(&signing_message, &signature)
+            let recovered = verify_evm_signature(signing_message, &signature)
                 .map_err(|_| AppError::Forbidden("Invalid EVM signature".into()))?
                 .to_lowercase();
 
@@ -61,20 +130,29 @@ pub fn verify_event(ev: &C2CEvent) -> AppResult<()> {
             }
         }
         "sol_ed25519" => {
-            let signature = payload_string(&ev.payload, "signature")
-                .or_else(|| ev.signature_b64.clone())
+            let signature = signature
                 .ok_or_else(|| AppError::BadRequest("Event is missing Solana signature".into()))?;
-            verify_solana_signature(&signing_message, actor_wallet, &signature)?;
+            verify_solana_signature(signing_message, actor_wallet, &signature)?;
         }
         "pq_dilithium3" | "pq_mldsa65" => {
             let pq_public_key_b64 = payload_string(&ev.payload, "pq_public_key_b64")
                 .ok_or_else(|| AppError::BadRequest("Event is missing pq_public_key_b64".into()))?;
-            let signature = payload_string(&ev.payload, "signature")
-                .or_else(|| ev.signature_b64.clone())
+            let signature = signature
                 .ok_or_else(|| AppError::BadRequest("Event is missing PQ signature".into()))?;
             let public_key = BASE64_STANDARD
                 .decode(pq_public_key_b64)
                 .map_err(|_| AppError::BadRequest("Invalid pq_public_key_b64".into()))?;
+            // CLI device keys are bound to their `pqdev:` id.
+            let device = payload_string(&ev.payload, "signer_device").or_else(|| {
+                actor_wallet
+                    .starts_with(DEVICE_ID_PREFIX)
+                    .then(|| actor_wallet.to_string())
+            });
+            if device.is_some_and(|device| device != device_id_for(&public_key)) {
+                return Err(AppError::Forbidden(
+                    "PQ public key does not match the signing device".into(),
+                ));
+            }
             let signed_message = BASE64_STANDARD
                 .decode(signature)
                 .map_err(|_| AppError::BadRequest("Invalid PQ signature encoding".into()))?;
@@ -93,3 +171,55 @@ pub fn verify_event(ev: &C2CEvent) -> AppResult<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use ed25519_dalek::{Signer, SigningKey};
+    use serde_json::json;
+
+    use super::*;
+    use crate::c2c::types::C2CEventKind;
+
+    #[test]
+    fn hybrid_events_need_both_signatures() {
+        let wallet_key = SigningKey::from_bytes(&[9u8; 32]);
+        let wallet = bs58::encode(wallet_key.verifying_key().to_bytes()).into_string();
+        let pq = dilithium::generate_keypair();
+        let message = "TIDBIT Document Signature\nVersion: 1";
+        let wallet_signature =
+            BASE64_STANDARD.encode(wallet_key.sign(message.as_bytes()).to_bytes());
+        let pq_signature =
+            BASE64_STANDARD.encode(dilithium::sign(&pq.secret_key, message.as_bytes()).unwrap());
+
+        let ev = C2CEvent {
+            id: "ev-1".into(),
+            timestamp: 0,
+            actor_wallet: wallet,
+            kind: C2CEventKind::DocumentSigned,
+            payload: json!({
+                "signature": wallet_signature,
+                "signing_message": message,
+                "verification": {
+                    "signature_type": HYBRID_SIGNATURE_TYPE,
+                    "wallet_signature_type": "sol_ed25519",
+                    "wallet_signature": wallet_signature,
+                    "pq_signature": pq_signature,
+                    "pq_public_key_b64": BASE64_STANDARD.encode(&pq.public_key)
+                }
+            }),
+            signature_b64: None,
+        };
+        assert!(verify_event(&ev).is_ok());
+
+        let other = dilithium::generate_keypair();
+        let mut bad_pq = ev.clone();
+        let forged = dilithium::sign(&other.secret_key, message.as_bytes()).unwrap();
+        bad_pq.payload["verification"]["pq_signature"] = json!(BASE64_STANDARD.encode(forged));
+        assert!(verify_event(&bad_pq).is_err());
+
+        let mut missing_wallet = ev.clone();
+        missing_wallet.payload["verification"]["wallet_signature"] = json!("");
+        missing_wallet.payload["signature"] = json!("");
+        assert!(verify_event(&missing_wallet).is_err());
+    }
+}
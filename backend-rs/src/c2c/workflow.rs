@@ -1,71 +1,339 @@
-use crate::c2c::types::{C2CEvent, C2CEventKind};
-use crate::c2c::event::record_c2c_event;
-use crate::c2c::filetrail::{record_file_version, FileAction};
-use crate::arweave::anchor_hash_to_arweave;
+// src/c2c/workflow.rs
+
+use chrono::Utc;
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::arweave::{ArweaveAnchor, ArweaveAnchorPayload, ArweaveClient};
+use crate::c2c::event::sign_event;
+use crate::c2c::filetrail::{record_file_version, FileAction, FileVersionRecord, NewFileVersion};
+use crate::c2c::ledger::{doc_uuid_for, CustodyLedger, LedgerEvent, NewLedgerEvent};
+use crate::c2c::types::C2CEvent;
 use crate::error::AppResult;
+use crate::identity::local_wallet::DeviceKey;
+use crate::pqc::sha3 as pqc_sha3;
+
+/// Whether the pipeline anchors the document hash after recording the event.
+pub enum AnchorMode<'a> {
+    /// Local only; the FileTrail record carries no txid.
+    Skip,
+    /// Anchor through this client. Without an API key it returns a
+    /// simulated txid instead of submitting.
+    Arweave(&'a ArweaveClient),
+}
 
-use sha2::{Sha256, Digest};
-use std::path::Path;
+pub struct DocumentEvent<'a> {
+    /// CLI logical id or server document UUID.
+    pub doc_key: &'a str,
+    pub action: FileAction,
+    pub actor_wallet: &'a str,
+    /// The bytes as handled by the caller; FileTrail records their SHA-256.
+    pub content: &'a [u8],
+    /// FileTrail SHA-256, computed from `content` when absent. Streamed
+    /// uploads never hold the whole file and hash it as it passes through.
+    pub sha256_hex: Option<&'a str>,
+    /// SHA3-256 document hash, computed from `content` when absent. Callers
+    /// that only hold ciphertext pass the declared plaintext hash.
+    pub hash_hex: Option<&'a str>,
+    pub path: &'a str,
+    pub version: Option<u64>,
+    /// Custody event type; defaults to the action's C2C type. Signed events
+    /// always use the C2C type.
+    pub event_type: Option<&'a str>,
+    /// CLI device key; when set the event is recorded as a signed C2C event.
+    pub signer: Option<&'a DeviceKey>,
+    /// Payload for the custody event. `doc_hash` is added when missing.
+    pub metadata: Value,
+}
+
+pub struct DocumentEventOutcome {
+    pub hash_hex: String,
+    pub event: LedgerEvent,
+    pub anchor: Option<ArweaveAnchor>,
+    /// Set when the Arweave submission failed. The event and FileTrail
+    /// version are still recorded; retrying the anchor is up to the caller.
+    pub anchor_error: Option<String>,
+    pub file_version: FileVersionRecord,
+}
 
 /// Unified pipeline for document actions:
 /// - Compute hash
-/// - Generate C2C event
 /// - Anchor to Arweave
+/// - Append the custody event, carrying `arweave_tx` when anchored
 /// - Save FileTrail version
 pub async fn record_document_event(
-    logical_doc_id: &str,
-    version: u64,
-    action: FileAction,
-    file_path: &Path,
-    actor_wallet: &str,
-) -> AppResult<()> {
-
+    ledger: &dyn CustodyLedger,
+    anchor: AnchorMode<'_>,
+    doc: DocumentEvent<'_>,
+) -> AppResult<DocumentEventOutcome> {
     //
-    // 1 — Hash file
+    // 1 — Hash content
     //
-    let data = std::fs::read(file_path)?;
-    let mut hasher = Sha256::new();
-    hasher.update(&data);
-    let sha = hasher.finalize();
-    let sha_hex = hex::encode(sha);
+    let hash_hex = doc
+        .hash_hex
+        .map(str::to_string)
+        .unwrap_or_else(|| hex::encode(pqc_sha3::sha3_256_bytes(doc.content)));
+    let sha256_hex = doc
+        .sha256_hex
+        .map(str::to_string)
+        .unwrap_or_else(|| hex::encode(Sha256::digest(doc.content)));
 
     //
-    // 2 — Build and store a C2C event
+    // 2 — Anchor the hash
     //
-    let ev = C2CEvent::new(
-        C2CEventKind::FileAction { action: format!("{:?}", action) },
-        actor_wallet.to_string(),
-        Some(sha_hex.clone()),
-    );
-
-    record_c2c_event(&ev)?;
+    let (anchor, anchor_error) = match anchor {
+        AnchorMode::Skip => (None, None),
+        AnchorMode::Arweave(client) => match client
+            .anchor_hash(&ArweaveAnchorPayload {
+                kind: "file_version_hash",
+                hash_hex: &hash_hex,
+                label: None,
+            })
+            .await
+        {
+            Ok(tx_id) => (Some(ArweaveAnchor { tx_id }), None),
+            Err(err) => (None, Some(err.to_string())),
+        },
+    };
 
     //
-    // 3 — Anchor the hash into Arweave (optional but recommended)
+    // 3 — Append the custody event
     //
-    let anchor = anchor_hash_to_arweave(&sha_hex).await?;
+    let mut payload = match doc.metadata {
+        Value::Object(map) => map,
+        Value::Null => Map::new(),
+        other => Map::from_iter([("metadata".to_string(), other)]),
+    };
+    payload
+        .entry("doc_hash")
+        .or_insert_with(|| Value::String(hash_hex.clone()));
+    if let Some(anchor) = &anchor {
+        payload.insert("arweave_tx".into(), Value::String(anchor.tx_id.clone()));
+    }
+    let doc_id = doc_uuid_for(doc.doc_key);
+    let new_event = match doc.signer {
+        Some(key) => {
+            let ev = sign_event(
+                C2CEvent {
+                    id: Uuid::new_v4().to_string(),
+                    timestamp: Utc::now().timestamp() as u64,
+                    actor_wallet: doc.actor_wallet.to_string(),
+                    kind: doc.action.event_kind(),
+                    payload: Value::Object(payload),
+                    signature_b64: None,
+                },
+                key,
+            )?;
+            NewLedgerEvent::from_c2c(doc_id, &ev)
+        }
+        None => NewLedgerEvent::new(
+            doc_id,
+            doc.actor_wallet,
+            doc.event_type
+                .unwrap_or_else(|| doc.action.event_kind().as_event_type()),
+            Value::Object(payload),
+        ),
+    };
+    let event = ledger.append(new_event).await?;
 
     //
-    // 4 — Record chain-of-custody version (FileTrail)
+    // 4 — Record the FileTrail version
     //
-    let _rec = record_file_version(
-        logical_doc_id,
-        version,
-        action,
-        file_path,
-        &sha_hex,
-        &ev,
-        Some(&anchor),
-    )?;
-
-    println!("📌 Document event recorded:");
-    println!("• logical_id: {}", logical_doc_id);
-    println!("• version: {}", version);
-    println!("• action: {:?}", action);
-    println!("• sha256: {}", sha_hex);
-    println!("• c2c_event_id: {}", ev.id);
-    println!("• arweave_tx: {}", anchor.tx_id);
-
-    Ok(())
+    let file_version = record_file_version(
+        ledger,
+        NewFileVersion {
+            logical_doc_id: doc.doc_key,
+            version: doc.version,
+            action: doc.action,
+            path: doc.path,
+            sha256_hex: &sha256_hex,
+        },
+        &event,
+        anchor.as_ref(),
+    )
+    .await?;
+
+    Ok(DocumentEventOutcome {
+        hash_hex,
+        event,
+        anchor,
+        anchor_error,
+        file_version,
+    })
 }
 
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{record_document_event, AnchorMode, DocumentEvent};
+    use crate::arweave::ArweaveClient;
+    use crate::c2c::filetrail::{load_history, FileAction};
+    use crate::c2c::ledger::{doc_uuid_for, verify_chain, CustodyLedger, FileLedger};
+    use crate::c2c::verify::verify_event;
+    use crate::identity::local_wallet::LocalWallet;
+
+    fn temp_ledger() -> (FileLedger, std::path::PathBuf) {
+        let path =
+            std::env::temp_dir().join(format!("tidbit-workflow-{}.jsonl", uuid::Uuid::new_v4()));
+        (FileLedger::new(&path), path)
+    }
+
+    fn upload<'a>(action: FileAction, content: &'a [u8]) -> DocumentEvent<'a> {
+        DocumentEvent {
+            doc_key: "logical-1",
+            action,
+            actor_wallet: "0xabc",
+            content,
+            sha256_hex: None,
+            hash_hex: None,
+            path: "/tmp/logical-1.pdf",
+            version: None,
+            event_type: None,
+            signer: None,
+            metadata: json!({ "label": "contract" }),
+        }
+    }
+
+    #[tokio::test]
+    async fn local_only_pipeline_chains_events_and_versions() {
+        let (ledger, path) = temp_ledger();
+
+        let first = record_document_event(
+            &ledger,
+            AnchorMode::Skip,
+            upload(FileAction::Uploaded, b"v1"),
+        )
+        .await
+        .unwrap();
+        let second = record_document_event(
+            &ledger,
+            AnchorMode::Skip,
+            upload(FileAction::Updated, b"v2"),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            first.hash_hex,
+            hex::encode(crate::pqc::sha3::sha3_256_bytes(b"v1"))
+        );
+        assert!(first.anchor.is_none());
+        assert_eq!(first.event.payload["doc_hash"], first.hash_hex);
+        assert_eq!(first.event.payload["label"], "contract");
+        assert_eq!(second.file_version.version, 2);
+        assert_eq!(
+            second.file_version.parent_hash,
+            Some(first.file_version.sha256_hex.clone())
+        );
+        assert_eq!(
+            second.file_version.c2c_event_id,
+            second.event.id.to_string()
+        );
+
+        let events = ledger
+            .events_for_doc(doc_uuid_for("logical-1"))
+            .await
+            .unwrap();
+        let types: Vec<_> = events.iter().map(|ev| ev.event_type.as_str()).collect();
+        assert_eq!(
+            types,
+            ["UPLOAD", "FILE_VERSION", "VERSION_CREATED", "FILE_VERSION"]
+        );
+        assert!(verify_chain(&events).is_ok());
+        assert_eq!(load_history(&ledger, "logical-1").await.unwrap().len(), 2);
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn simulated_arweave_pipeline_records_the_txid() {
+        let (ledger, path) = temp_ledger();
+        let client = ArweaveClient::new(
+            "http://127.0.0.1:9".into(),
+            "http://127.0.0.1:9".into(),
+            None,
+        );
+        let declared = "ab".repeat(32);
+
+        let outcome = record_document_event(
+            &ledger,
+            AnchorMode::Arweave(&client),
+            DocumentEvent {
+                hash_hex: Some(&declared),
+                version: Some(7),
+                event_type: Some("AGENT_VERSION_CREATED"),
+                ..upload(FileAction::Updated, b"ciphertext")
+            },
+        )
+        .await
+        .unwrap();
+
+        let tx_id = format!("simulated-{declared}");
+        assert_eq!(outcome.hash_hex, declared);
+        assert_eq!(outcome.anchor.map(|a| a.tx_id), Some(tx_id.clone()));
+        assert_eq!(outcome.event.event_type, "AGENT_VERSION_CREATED");
+        assert_eq!(outcome.event.payload["arweave_tx"], tx_id);
+        assert_eq!(outcome.file_version.version, 7);
+        assert_eq!(outcome.file_version.arweave_tx_id, Some(tx_id.clone()));
+
+        let history = load_history(&ledger, "logical-1").await.unwrap();
+        assert_eq!(history[0].arweave_tx_id, Some(tx_id));
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn failed_anchor_still_records_the_event_and_version() {
+        let (ledger, path) = temp_ledger();
+        let client = ArweaveClient::new(
+            "http://127.0.0.1:9".into(),
+            "http://127.0.0.1:9".into(),
+            Some("key".into()),
+        );
+
+        let outcome = record_document_event(
+            &ledger,
+            AnchorMode::Arweave(&client),
+            upload(FileAction::Uploaded, b"v1"),
+        )
+        .await
+        .unwrap();
+
+        assert!(outcome.anchor.is_none());
+        assert!(outcome.anchor_error.is_some());
+        assert!(outcome.event.payload.get("arweave_tx").is_none());
+        assert_eq!(outcome.file_version.arweave_tx_id, None);
+        assert_eq!(load_history(&ledger, "logical-1").await.unwrap().len(), 1);
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn signed_pipeline_events_round_trip_through_the_ledger() {
+        let (ledger, path) = temp_ledger();
+        let wallet = LocalWallet::create("passphrase").unwrap();
+        let key = wallet.unlock("passphrase").unwrap();
+
+        let outcome = record_document_event(
+            &ledger,
+            AnchorMode::Skip,
+            DocumentEvent {
+                actor_wallet: &wallet.id,
+                signer: Some(&key),
+                ..upload(FileAction::Uploaded, b"v1")
+            },
+        )
+        .await
+        .unwrap();
+
+        let stored = ledger.find(&outcome.event.id.to_string()).unwrap().unwrap();
+        let c2c = stored.to_c2c_event().unwrap();
+        assert_eq!(stored.event_type, "UPLOAD");
+        assert_eq!(c2c.payload["label"], "contract");
+        assert!(verify_event(&c2c).is_ok());
+
+        let _ = std::fs::remove_file(path);
+    }
+}
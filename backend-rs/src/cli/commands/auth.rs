@@ -13,20 +13,39 @@ use crate::cli::parser::AuthCommands;
 // TYPES
 // ======================================================
 
+#[derive(Debug, Serialize)]
+struct NonceRequest {
+    address: String,
+    chain_id: u64,
+}
+
 #[derive(Debug, Deserialize)]
 struct NonceResponse {
     session_id: String,
-    nonce: String,
-    message: String,
+    message: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
 struct VerifyRequest {
     session_id: String,
     address: String,
+    message: String,
     signature: String,
 }
 
+/// Login and rotation both hand back a fresh access session and refresh
+/// token.
+#[derive(Debug, Deserialize)]
+struct SessionResponse {
+    session_id: String,
+    refresh_token: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct RefreshRequest {
+    refresh_token: String,
+}
+
 // ======================================================
 // COMMANDS
 // ======================================================
@@ -35,28 +54,35 @@ pub async fn auth_evm(api: &str, private_key_hex: &str) -> Result<()> {
     let client = Client::new();
 
     // --------------------------------------------------
-    // 1. Request nonce
+    // 1. Load Ethereum wallet (MetaMask-compatible)
+    // --------------------------------------------------
+    let wallet =
+        LocalWallet::from_str(private_key_hex).map_err(|e| anyhow!("invalid private key: {e}"))?;
+
+    let address = format!("{:?}", wallet.address());
+
+    // --------------------------------------------------
+    // 2. Request a nonce and its EIP-4361 message
     // --------------------------------------------------
     let nonce_resp: NonceResponse = client
         .post(format!("{api}/api/identity/evm/nonce"))
+        .json(&NonceRequest {
+            address: address.clone(),
+            chain_id: wallet.chain_id(),
+        })
         .send()
         .await?
+        .error_for_status()?
         .json()
         .await?;
 
-    // --------------------------------------------------
-    // 2. Load Ethereum wallet (MetaMask-compatible)
-    // --------------------------------------------------
-    let wallet =
-        LocalWallet::from_str(private_key_hex).map_err(|e| anyhow!("invalid private key: {e}"))?;
-
-    let address = wallet.address().to_string();
-
     // --------------------------------------------------
     // 3. Sign EXACT message (same as MetaMask)
     // --------------------------------------------------
-    let message = nonce_resp.message.clone();
-    let signature: Signature = wallet.sign_message(message).await?;
+    let message = nonce_resp
+        .message
+        .ok_or_else(|| anyhow!("server did not return a sign-in message"))?;
+    let signature: Signature = wallet.sign_message(&message).await?;
 
     // --------------------------------------------------
     // 4. Verify with backend
@@ -64,6 +90,7 @@ pub async fn auth_evm(api: &str, private_key_hex: &str) -> Result<()> {
     let verify = VerifyRequest {
         session_id: nonce_resp.session_id.clone(),
         address: address.clone(),
+        message,
         signature: signature.to_string(),
     };
 
@@ -77,12 +104,42 @@ pub async fn auth_evm(api: &str, private_key_hex: &str) -> Result<()> {
         let body = resp.text().await?;
         return Err(anyhow!("verify failed: {body}"));
     }
+    let session: SessionResponse = resp.json().await?;
 
     println!("✅ Logged in as {address}");
-    println!("🆔 Session ID: {}", nonce_resp.session_id);
+    print_session_exports(&session);
+
+    Ok(())
+}
+
+fn print_session_exports(session: &SessionResponse) {
+    println!("🆔 Session ID: {}", session.session_id);
     println!("💡 Export it:");
-    println!("export TIDBIT_SESSION_ID={}", nonce_resp.session_id);
+    println!("export TIDBIT_SESSION_ID={}", session.session_id);
+    if let Some(refresh_token) = &session.refresh_token {
+        println!("export TIDBIT_REFRESH_TOKEN={refresh_token}");
+    }
+}
 
+/// Rotate to a new access session. The old refresh token stops working, so
+/// export the new one.
+pub async fn auth_refresh(api: &str, refresh_token: &str) -> Result<()> {
+    let resp = Client::new()
+        .post(format!("{api}/auth/session/rotate"))
+        .json(&RefreshRequest {
+            refresh_token: refresh_token.to_string(),
+        })
+        .send()
+        .await?;
+
+    if !resp.status().is_success() {
+        let body = resp.text().await?;
+        return Err(anyhow!("refresh failed: {body}"));
+    }
+    let session: SessionResponse = resp.json().await?;
+
+    println!("🔄 Session rotated");
+    print_session_exports(&session);
     Ok(())
 }
 
@@ -94,6 +151,11 @@ pub async fn handle_auth(cmd: AuthCommands) -> Result<()> {
                 std::env::var("TIDBIT_SESSION_ID").map_err(|_| anyhow!("TIDBIT_SESSION_ID not set"))?;
             auth_whoami(&api, &session_id).await?;
         }
+        AuthCommands::Refresh { api } => {
+            let refresh_token = std::env::var("TIDBIT_REFRESH_TOKEN")
+                .map_err(|_| anyhow!("TIDBIT_REFRESH_TOKEN not set"))?;
+            auth_refresh(&api, &refresh_token).await?;
+        }
         AuthCommands::Logout { api } => {
             let session_id =
                 std::env::var("TIDBIT_SESSION_ID").map_err(|_| anyhow!("TIDBIT_SESSION_ID not set"))?;
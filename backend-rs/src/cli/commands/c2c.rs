@@ -2,21 +2,28 @@
 
 use anyhow::Result;
 
-use crate::c2c::{onchain as c2c_onchain, store as c2c_store};
+use crate::c2c::ledger::FileLedger;
+use crate::c2c::onchain as c2c_onchain;
+use crate::c2c::verify::verify_event;
 use crate::cli::parser::C2cCommands;
-use crate::pqc::sha3 as pqc_sha3;
 
 pub async fn handle_c2c(cmd: C2cCommands) -> Result<()> {
+    let ledger = FileLedger::from_env();
+
     match cmd {
         // ===================================================
         // LIST EVENTS
         // ===================================================
         C2cCommands::List => {
-            let events = c2c_store::load_all_events()?;
+            let mut events = ledger.all_events()?;
+            events.reverse();
             for ev in events {
                 println!(
-                    "{} | {} | {:?} | actor={}",
-                    ev.id, ev.timestamp, ev.kind, ev.actor_wallet
+                    "{} | {} | {} | actor={}",
+                    ev.id,
+                    ev.created_at.to_rfc3339(),
+                    ev.event_type,
+                    ev.actor_wallet
                 );
             }
         }
@@ -24,7 +31,7 @@ pub async fn handle_c2c(cmd: C2cCommands) -> Result<()> {
         // ===================================================
         // SHOW EVENT
         // ===================================================
-        C2cCommands::Show { id } => match c2c_store::load_event_by_id(&id)? {
+        C2cCommands::Show { id } => match ledger.find(&id)? {
             Some(ev) => {
                 println!("{}", serde_json::to_string_pretty(&ev)?);
             }
@@ -37,7 +44,7 @@ pub async fn handle_c2c(cmd: C2cCommands) -> Result<()> {
         // ANCHOR EVENT (HASH ONLY)
         // ===================================================
         C2cCommands::Anchor { id } => {
-            let ev = match c2c_store::load_event_by_id(&id)? {
+            let ev = match ledger.find(&id)? {
                 Some(ev) => ev,
                 None => {
                     println!("No event with id={id}");
@@ -45,19 +52,36 @@ pub async fn handle_c2c(cmd: C2cCommands) -> Result<()> {
                 }
             };
 
-            // IMPORTANT:
-            // Strip signature before anchoring
-            let mut bare = ev.clone();
-            bare.signature_b64 = None;
-
-            let json = serde_json::to_vec(&bare)?;
-            let hash = pqc_sha3::sha3_256_bytes(&json);
+            // The chain hash already commits to the event and its predecessors.
+            let hash = hex::decode(&ev.event_hash_hex)?;
 
             // Currently anchors hash (EVM / stub / Arweave later)
             c2c_onchain::anchor_event_hash(&hash).await?;
 
             println!("Anchored event {} with hash {}", id, hex::encode(hash));
         }
+
+        // ===================================================
+        // VERIFY EVENT SIGNATURE
+        // ===================================================
+        C2cCommands::Verify { id } => {
+            let ev = match ledger.find(&id)? {
+                Some(ev) => ev,
+                None => {
+                    println!("No event with id={id}");
+                    return Ok(());
+                }
+            };
+
+            match ev.to_c2c_event() {
+                Some(c2c) => {
+                    verify_event(&c2c)?;
+                    let device = c2c.payload["signer_device"].as_str().unwrap_or("?");
+                    println!("✅ Event {id} signed by {device}");
+                }
+                None => println!("Event {id} carries no C2C signature"),
+            }
+        }
     }
 
     Ok(())
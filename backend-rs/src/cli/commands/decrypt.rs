@@ -3,7 +3,7 @@
 use std::path::PathBuf;
 
 use crate::crypto::canonical::{
-    DocumentEnvelopeV1,
+    read_document_envelope,
     keystore::{load_or_create_mlkem_keypair, load_envelope_json},
 };
 
@@ -36,10 +36,10 @@ pub async fn handle_decrypt(args: DecryptArgs) -> Result<(), AppError> {
     let bytes = load_envelope_json(&args.envelope_id)
         .map_err(|e| AppError::BadRequest(format!("load envelope: {e}")))?;
 
-    let env: DocumentEnvelopeV1 =
-        serde_json::from_slice(&bytes).map_err(|e| AppError::BadRequest(format!("parse envelope: {e}")))?;
+    let env = read_document_envelope(&bytes)
+        .map_err(|e| AppError::BadRequest(format!("parse envelope: {e}")))?;
 
-    if env.owner.to_lowercase() != owner_wallet {
+    if env.owner().to_lowercase() != owner_wallet {
         return Err(AppError::Forbidden("this envelope is not owned by the provided wallet".into()));
     }
 
@@ -5,13 +5,22 @@ use std::{fs, path::PathBuf};
 use anyhow::Result;
 use reqwest::Client;
 
-use crate::arweave::anchor_hash_to_arweave;
-use crate::c2c::{record, store as c2c_store};
+use crate::arweave::ArweaveClient;
+use crate::c2c::filetrail::{load_history, FileAction};
+use crate::c2c::ledger::{doc_uuid_for, FileLedger, PgLedger};
+use crate::c2c::workflow::{record_document_event, AnchorMode, DocumentEvent};
+use crate::cli::commands::wallet::unlock_device_key;
 use crate::cli::parser::DocCommands;
+use crate::crypto::canonical::{is_envelope_v2, read_document_envelope, ENVELOPE_V2_CONTENT_TYPE};
 use crate::identity::local_wallet::LocalWallet;
 use crate::pqc::sha3 as pqc_sha3;
 use crate::sqlx::{self, Row};
-use crate::storage::supabase::SupabaseStorage;
+use crate::storage::{blob_store_from_env, expected_object_path};
+
+const ENVELOPE_ENCRYPTION_MODES: [&str; 2] = [
+    "pq_envelope_server_managed",
+    "pq_envelope_browser_encrypted",
+];
 
 #[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
 pub struct DocEntry {
@@ -115,6 +124,7 @@ struct DocumentStorageRow {
     hash_hex: String,
     version: i32,
     storage_path: String,
+    encryption_mode: Option<String>,
 }
 
 async fn load_storage_rows(
@@ -126,7 +136,7 @@ async fn load_storage_rows(
         let doc_id = uuid::Uuid::parse_str(id)?;
         sqlx::query(
             r#"
-            select id, owner_wallet, hash_hex, version, storage_path
+            select id, owner_wallet, hash_hex, version, storage_path, encryption_mode
             from documents
             where id = $1 and is_deleted = false
             "#,
@@ -137,7 +147,7 @@ async fn load_storage_rows(
     } else if let Some(limit) = limit {
         sqlx::query(
             r#"
-            select id, owner_wallet, hash_hex, version, storage_path
+            select id, owner_wallet, hash_hex, version, storage_path, encryption_mode
             from documents
             where is_deleted = false
             order by created_at desc
@@ -150,7 +160,7 @@ async fn load_storage_rows(
     } else {
         sqlx::query(
             r#"
-            select id, owner_wallet, hash_hex, version, storage_path
+            select id, owner_wallet, hash_hex, version, storage_path, encryption_mode
             from documents
             where is_deleted = false
             order by created_at desc
@@ -168,6 +178,7 @@ async fn load_storage_rows(
             hash_hex: row.get("hash_hex"),
             version: row.get("version"),
             storage_path: row.get("storage_path"),
+            encryption_mode: row.get("encryption_mode"),
         })
         .collect())
 }
@@ -179,11 +190,7 @@ async fn repair_storage_paths(apply: bool, id: Option<String>, limit: Option<i64
         .connect(&database_url)
         .await?;
 
-    let storage = SupabaseStorage::new(
-        std::env::var("SUPABASE_URL")?,
-        std::env::var("SUPABASE_SERVICE_ROLE_KEY")?,
-        std::env::var("SUPABASE_BUCKET")?,
-    );
+    let storage = blob_store_from_env()?;
 
     let rows = load_storage_rows(&pool, id.as_deref(), limit).await?;
 
@@ -195,7 +202,7 @@ async fn repair_storage_paths(apply: bool, id: Option<String>, limit: Option<i64
     let mut conflicts = 0usize;
 
     for row in rows {
-        let expected_path = SupabaseStorage::expected_object_path(
+        let expected_path = expected_object_path(
             &row.owner_wallet,
             &row.id.to_string(),
             row.version,
@@ -250,7 +257,7 @@ async fn repair_storage_paths(apply: bool, id: Option<String>, limit: Option<i64
             }
             (false, false) => {
                 skipped_missing += 1;
-                println!("  action: skipped, neither old nor expected object exists in Supabase");
+                println!("  action: skipped, neither old nor expected object exists in {}", storage.backend_name());
             }
         }
     }
@@ -272,6 +279,146 @@ async fn repair_storage_paths(apply: bool, id: Option<String>, limit: Option<i64
     Ok(())
 }
 
+/// Rewrite stored V1 JSON envelopes as binary V2 containers. The ciphertext
+/// is carried over unchanged, so no keys are needed; each rewrite is recorded
+/// as a device-signed VERSION_CREATED event with a new FileTrail version.
+async fn upgrade_envelopes(apply: bool, id: Option<String>, limit: Option<i64>) -> Result<()> {
+    let database_url = std::env::var("DATABASE_URL")?;
+    let pool = sqlx::postgres::PgPoolOptions::new()
+        .max_connections(5)
+        .connect(&database_url)
+        .await?;
+
+    let storage = blob_store_from_env()?;
+    let ledger = PgLedger::new(pool.clone()).with_signer(crate::sign_hmac_b64);
+    let device_key = if apply {
+        Some(unlock_device_key()?)
+    } else {
+        None
+    };
+
+    let rows = load_storage_rows(&pool, id.as_deref(), limit).await?;
+
+    let mut current = 0usize;
+    let mut planned = 0usize;
+    let mut upgraded = 0usize;
+    let mut conflicts = 0usize;
+
+    for row in rows {
+        let is_envelope = row
+            .encryption_mode
+            .as_deref()
+            .is_some_and(|mode| ENVELOPE_ENCRYPTION_MODES.contains(&mode));
+        if !is_envelope {
+            continue;
+        }
+
+        let stored = storage.download_bytes(&row.storage_path).await?;
+        if is_envelope_v2(&stored) {
+            current += 1;
+            continue;
+        }
+        let envelope = read_document_envelope(&stored)
+            .map_err(|e| anyhow::anyhow!("document {}: {e}", row.id))?;
+        let upgraded_bytes = envelope.encode();
+
+        planned += 1;
+        println!("document {} v{}", row.id, row.version);
+        println!("  size: {} -> {} bytes", stored.len(), upgraded_bytes.len());
+
+        let Some(device_key) = device_key.as_ref() else {
+            continue;
+        };
+
+        let old_ciphertext_hash_hex = hex::encode(pqc_sha3::sha3_256_bytes(&stored));
+        let ciphertext_hash_hex = hex::encode(pqc_sha3::sha3_256_bytes(&upgraded_bytes));
+        let doc_key = row.id.to_string();
+        let storage_path = storage
+            .upload_bytes(
+                &row.owner_wallet,
+                &doc_key,
+                row.version,
+                &upgraded_bytes,
+                ENVELOPE_V2_CONTENT_TYPE,
+            )
+            .await?;
+
+        // Only repoint the row if the envelope was not rewritten meanwhile.
+        let result = sqlx::query(
+            r#"
+            update documents
+            set storage_path = $3,
+                ciphertext_hash_hex = $4
+            where id = $1
+              and storage_path = $2
+              and (ciphertext_hash_hex is null or ciphertext_hash_hex = $5)
+            "#,
+        )
+        .bind(row.id)
+        .bind(&row.storage_path)
+        .bind(&storage_path)
+        .bind(&ciphertext_hash_hex)
+        .bind(&old_ciphertext_hash_hex)
+        .execute(&pool)
+        .await?;
+        if result.rows_affected() == 0 {
+            conflicts += 1;
+            println!("  action: skipped, envelope changed while upgrading");
+            continue;
+        }
+        if storage_path != row.storage_path {
+            storage.delete_object(&row.storage_path).await?;
+        }
+
+        // The plaintext is unchanged, so FileTrail keeps its content hash.
+        let sha256_hex = load_history(&ledger, &doc_key)
+            .await?
+            .last()
+            .map(|record| record.sha256_hex.clone());
+        let outcome = record_document_event(
+            &ledger,
+            AnchorMode::Skip,
+            DocumentEvent {
+                doc_key: &doc_key,
+                action: FileAction::Updated,
+                actor_wallet: &device_key.device_id,
+                content: &upgraded_bytes,
+                sha256_hex: sha256_hex.as_deref(),
+                hash_hex: Some(&row.hash_hex),
+                path: &storage_path,
+                version: None,
+                event_type: None,
+                signer: Some(device_key),
+                metadata: serde_json::json!({
+                    "reason": "envelope_format_upgrade",
+                    "envelope_format": "v2",
+                    "envelope_id": envelope.id(),
+                    "previous_ciphertext_hash_hex": old_ciphertext_hash_hex,
+                    "ciphertext_hash_hex": ciphertext_hash_hex,
+                }),
+            },
+        )
+        .await?;
+
+        upgraded += 1;
+        println!("  action: upgraded, custody event {}", outcome.event.id);
+    }
+
+    println!();
+    println!("envelope upgrade summary");
+    println!("  already_v2: {current}");
+    println!("  v1: {planned}");
+    if apply {
+        println!("  upgraded: {upgraded}");
+        println!("  conflicts: {conflicts}");
+    } else {
+        println!("  dry_run: true");
+        println!("  apply with: cargo run -- doc upgrade-envelopes --apply");
+    }
+
+    Ok(())
+}
+
 // ======================================================
 // CLI dispatcher
 // ======================================================
@@ -291,30 +438,56 @@ pub async fn handle_doc(cmd: DocCommands) -> Result<()> {
             let logical_id = uuid::Uuid::new_v4().to_string();
 
             let owner = resolve_owner_wallet(use_session, owner_wallet).await?;
+            let device_key = unlock_device_key()?;
 
             let data_dir = docs_data_dir();
             fs::create_dir_all(&data_dir)?;
             let stored_path = data_dir.join(&hash_hex);
             fs::write(&stored_path, &bytes)?;
 
-            let arweave_tx = match store.as_str() {
-                "arweave" | "both" => Some(anchor_hash_to_arweave(&hash_hex).await?.tx_id),
-                _ => None,
+            let client = ArweaveClient::from_env();
+            let anchor = match store.as_str() {
+                "arweave" | "both" => AnchorMode::Arweave(&client),
+                _ => AnchorMode::Skip,
             };
+            let local_path = stored_path.to_string_lossy().into_owned();
+
+            let outcome = record_document_event(
+                &FileLedger::from_env(),
+                anchor,
+                DocumentEvent {
+                    doc_key: &logical_id,
+                    action: FileAction::Uploaded,
+                    actor_wallet: &owner,
+                    content: &bytes,
+                    sha256_hex: None,
+                    hash_hex: Some(&hash_hex),
+                    path: &local_path,
+                    version: None,
+                    event_type: None,
+                    signer: Some(&device_key),
+                    metadata: serde_json::json!({ "label": label }),
+                },
+            )
+            .await?;
+            if let Some(err) = &outcome.anchor_error {
+                eprintln!("Arweave anchor failed, document kept locally: {err}");
+            }
+            let arweave_tx = outcome.anchor.map(|anchor| anchor.tx_id);
 
             let mut idx = load_index()?;
             idx.push(DocEntry {
                 logical_id: logical_id.clone(),
-                hash_hex: hash_hex.clone(),
+                hash_hex: outcome.hash_hex,
                 label,
-                local_path: Some(stored_path.to_string_lossy().into()),
-                arweave_tx: arweave_tx.clone(),
-                owner_wallet: Some(owner.clone()),
+                local_path: Some(local_path),
+                arweave_tx,
+                owner_wallet: Some(owner),
             });
             save_index(&idx)?;
 
-            record::record_upload_event(owner, hash_hex, arweave_tx)?;
             println!("Uploaded document {}", logical_id);
+            println!("  custody event: {}", outcome.event.id);
         }
 
         // ---------------- Sign (CLI stub) ----------------
@@ -338,13 +511,19 @@ pub async fn handle_doc(cmd: DocCommands) -> Result<()> {
             let needle = id
                 .or(hash)
                 .ok_or_else(|| anyhow::anyhow!("--id or --hash required"))?;
-            let events = c2c_store::load_all_events()?;
-
-            for ev in events
-                .into_iter()
-                .filter(|e| e.payload.get("doc_hash").and_then(|v| v.as_str()) == Some(&needle))
-            {
-                println!("{} | {:?} | {}", ev.timestamp, ev.kind, ev.actor_wallet);
+            let doc_id = doc_uuid_for(&needle);
+            let events = FileLedger::from_env().all_events()?;
+
+            for ev in events.into_iter().filter(|e| {
+                e.doc_id == doc_id
+                    || e.payload.get("doc_hash").and_then(|v| v.as_str()) == Some(&needle)
+            }) {
+                println!(
+                    "{} | {} | {}",
+                    ev.created_at.to_rfc3339(),
+                    ev.event_type,
+                    ev.actor_wallet
+                );
             }
         }
 
@@ -352,6 +531,10 @@ pub async fn handle_doc(cmd: DocCommands) -> Result<()> {
             repair_storage_paths(apply, id, limit).await?;
         }
 
+        DocCommands::UpgradeEnvelopes { apply, id, limit } => {
+            upgrade_envelopes(apply, id, limit).await?;
+        }
+
         _ => {}
     }
 
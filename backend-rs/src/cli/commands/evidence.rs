@@ -0,0 +1,130 @@
+// src/cli/commands/evidence.rs
+
+use std::fs;
+
+use anyhow::Result;
+
+use crate::c2c::evidence::{verify_bundle, EventCheck, EvidenceReport};
+use crate::cli::parser::EvidenceCommands;
+
+fn pass_fail(ok: bool) -> &'static str {
+    if ok {
+        "PASS"
+    } else {
+        "FAIL"
+    }
+}
+
+fn print_report(report: &EvidenceReport) {
+    println!("evidence report for document {}", report.doc_id);
+
+    match report.bundle_hash_valid {
+        Some(ok) => println!("  bundle hash: {}", pass_fail(ok)),
+        None => println!("  bundle hash: not present"),
+    }
+
+    let broken = |ev: &&EventCheck| ev.sealed && (!ev.chain_link_valid || !ev.event_hash_valid);
+    let chain_status = if report.chain_valid() {
+        "PASS"
+    } else if report.events.iter().any(|ev| broken(&ev)) {
+        "FAIL"
+    } else {
+        "INCOMPLETE"
+    };
+    println!(
+        "  event chain: {} ({} events, {} unsealed)",
+        chain_status,
+        report.events.len(),
+        report.unsealed_events
+    );
+    if !report.chain_complete() {
+        println!("    unsealed events predate the hash chain; this document can never pass");
+    }
+    for ev in &report.events {
+        if !ev.sealed {
+            println!("    {} {}: no event hash", ev.id, ev.event_type);
+        } else if !ev.chain_link_valid || !ev.event_hash_valid {
+            println!(
+                "    {} {}: link={} hash={}",
+                ev.id,
+                ev.event_type,
+                pass_fail(ev.chain_link_valid),
+                pass_fail(ev.event_hash_valid)
+            );
+        }
+    }
+
+    println!(
+        "  signatures: {} ({} checked)",
+        pass_fail(report.signatures_valid()),
+        report.signatures_checked
+    );
+    let ad_hoc = report
+        .events
+        .iter()
+        .filter(|ev| ev.pq_key_registered == Some(false))
+        .count();
+    if ad_hoc > 0 {
+        println!("    {ad_hoc} signed with an unregistered ML-DSA key; signer identity not proven");
+    }
+    for ev in &report.events {
+        if ev.signature_valid == Some(false) {
+            println!(
+                "    {} {} [{}]: {}",
+                ev.id,
+                ev.event_type,
+                ev.signature_type.as_deref().unwrap_or("unknown"),
+                ev.error.as_deref().unwrap_or("invalid")
+            );
+        }
+    }
+
+    if !report.anchor_proofs.is_empty() {
+        println!(
+            "  anchor proofs: {} ({} checked)",
+            pass_fail(report.anchor_proofs_valid()),
+            report.anchor_proofs.len()
+        );
+        for proof in report.anchor_proofs.iter().filter(|proof| !proof.valid) {
+            println!(
+                "    {} {}: does not reach root {}",
+                proof.kind, proof.hash_hex, proof.merkle_root_hex
+            );
+        }
+    }
+
+    if let Some(file) = &report.file {
+        match (file.matched_version, file.matches_current) {
+            (Some(version), true) => println!("  file: PASS (current version {version})"),
+            (Some(version), false) => println!("  file: PASS (lineage version {version})"),
+            (None, _) => println!(
+                "  file: FAIL (sha3-256 {} not in lineage)",
+                file.sha3_256_hex
+            ),
+        }
+    }
+
+    println!("result: {}", pass_fail(report.passed()));
+}
+
+pub async fn handle_evidence(cmd: EvidenceCommands) -> Result<()> {
+    match cmd {
+        EvidenceCommands::Verify { bundle, file, json } => {
+            let bundle: serde_json::Value = serde_json::from_str(&fs::read_to_string(&bundle)?)?;
+            let file_bytes = file.map(fs::read).transpose()?;
+            let report = verify_bundle(&bundle, file_bytes.as_deref())?;
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                print_report(&report);
+            }
+
+            if !report.passed() {
+                anyhow::bail!("evidence verification failed");
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,78 @@
+// src/cli/commands/ledger.rs
+
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::Result;
+
+use crate::c2c::filetrail;
+use crate::c2c::ledger::{
+    import_c2c_events, import_filetrail_records, verify_chain, FileLedger, ImportSummary, PgLedger,
+};
+use crate::c2c::store as c2c_store;
+use crate::cli::parser::LedgerCommands;
+use crate::sqlx;
+
+fn print_summary(label: &str, summary: ImportSummary) {
+    println!(
+        "  {label}: {} imported, {} already present",
+        summary.imported, summary.skipped
+    );
+}
+
+pub async fn handle_ledger(cmd: LedgerCommands) -> Result<()> {
+    match cmd {
+        LedgerCommands::Migrate { database } => {
+            let ledger = FileLedger::from_env();
+            let legacy_events = c2c_store::load_all_events()?;
+            let actors: HashMap<String, String> = legacy_events
+                .iter()
+                .map(|ev| (ev.id.clone(), ev.actor_wallet.clone()))
+                .collect();
+
+            println!("local ledger: {}", ledger.path().display());
+            let summary = import_c2c_events(&ledger, "c2c_events_json", legacy_events).await?;
+            print_summary("c2c event files", summary);
+            let summary =
+                import_filetrail_records(&ledger, &actors, filetrail::load_legacy_records()?)
+                    .await?;
+            print_summary("filetrail records", summary);
+
+            if database {
+                let database_url = std::env::var("DATABASE_URL")?;
+                let pool = sqlx::postgres::PgPoolOptions::new()
+                    .max_connections(5)
+                    .connect(&database_url)
+                    .await?;
+                let rows = c2c_store::load_db_events(&pool).await?;
+                let ledger = PgLedger::new(pool).with_signer(crate::sign_hmac_b64);
+                let summary = import_c2c_events(&ledger, "c2c_events_table", rows).await?;
+                print_summary("c2c_events rows -> document_events", summary);
+            }
+        }
+
+        LedgerCommands::Verify => {
+            let mut by_doc = BTreeMap::new();
+            for ev in FileLedger::from_env().all_events()? {
+                by_doc.entry(ev.doc_id).or_insert_with(Vec::new).push(ev);
+            }
+
+            let mut broken = 0usize;
+            for (doc_id, events) in &by_doc {
+                if let Err(err) = verify_chain(events) {
+                    broken += 1;
+                    println!("FAIL {doc_id}: {err}");
+                }
+            }
+            println!(
+                "{} documents checked, {} broken chains",
+                by_doc.len(),
+                broken
+            );
+            if broken > 0 {
+                anyhow::bail!("ledger verification failed");
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -2,4 +2,8 @@
 pub mod auth;
 pub mod c2c;
 pub mod doc;
+pub mod evidence;
+pub mod ledger;
+pub mod scan;
+pub mod scan_url;
 pub mod wallet;
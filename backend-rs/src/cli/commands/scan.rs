@@ -1,33 +1,52 @@
 // src/cli/commands/scan.rs
 
-use crate::config::AppConfig;
-use crate::sanitizer::hybrid::hybrid_sanitize;
-use sha2::{Sha256, Digest};
+use std::fs;
 
-pub async fn scan_file(path: &str) -> anyhow::Result<()> {
-    let cfg = AppConfig::load();
-    let bytes = std::fs::read(path)?;
-    let mime = tree_magic_mini::from_u8(&bytes);
+use anyhow::Result;
 
-    let mut hasher = Sha256::new();
-    hasher.update(&bytes);
-    let sha256_hex = hex::encode(hasher.finalize());
+use crate::sanitizer::hybrid::hybrid_sanitize;
+use crate::sanitizer::{ScanReport, ScanVerdict};
 
-    hybrid_sanitize(
-        &bytes,
-        &mime,
-        &sha256_hex,
-        &cfg.vt_api_key,
-        &cfg.otx_api_key,
-        &cfg.gsb_api_key,
-        &cfg.hibp_api_key,
-        &cfg.ipinfo_key,
-        None,
-        None,
-    )
-    .await?;
+pub(crate) fn print_report(source: &str, report: &ScanReport) {
+    println!("scan report for {source}");
+    println!(
+        "  mime: declared {}, detected {}",
+        report.declared_mime,
+        report.detected_mime.as_deref().unwrap_or("unknown")
+    );
+    println!("  scanners: {}", report.scanners.join(", "));
+    for finding in &report.findings {
+        println!(
+            "  {} [{}] {}: {}",
+            finding.verdict.as_str(),
+            finding.scanner,
+            finding.rule,
+            finding.detail
+        );
+    }
+    for err in &report.errors {
+        println!("  error [{}]: {}", err.scanner, err.error);
+    }
+    println!("verdict: {}", report.verdict.as_str());
+}
 
-    println!("✅ File is clean: {path} ({mime})");
+/// Print the report and fail on a malicious verdict so CI can gate on it.
+pub(crate) fn finish(source: &str, report: &ScanReport, json: bool) -> Result<()> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(report)?);
+    } else {
+        print_report(source, report);
+    }
+
+    if report.verdict == ScanVerdict::Malicious {
+        anyhow::bail!("scan verdict for {source}: {}", report.summary());
+    }
     Ok(())
 }
 
+pub async fn handle_scan(path: String, mime_type: Option<String>, json: bool) -> Result<()> {
+    let bytes = fs::read(&path)?;
+    let mime_type = mime_type.unwrap_or_else(|| "application/octet-stream".to_string());
+    let report = hybrid_sanitize(&bytes, &mime_type, Some(&path)).await?;
+    finish(&path, &report, json)
+}
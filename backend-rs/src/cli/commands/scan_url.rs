@@ -1,26 +1,39 @@
 // src/cli/commands/scan_url.rs
 
-use crate::config::AppConfig;
+use anyhow::{Context, Result};
+
+use super::scan::finish;
 use crate::sanitizer::hybrid::hybrid_sanitize;
 
-pub async fn scan_url(url: &str) -> anyhow::Result<()> {
-    let cfg = AppConfig::load();
+/// Refuse to buffer more than this; the server's own upload limits are lower.
+const MAX_DOWNLOAD_BYTES: usize = 512 * 1024 * 1024;
 
-    hybrid_sanitize(
-        b"",
-        "text/plain",
-        "",
-        &cfg.vt_api_key,
-        &cfg.otx_api_key,
-        &cfg.gsb_api_key,
-        &cfg.hibp_api_key,
-        &cfg.ipinfo_key,
-        Some(url),
-        None,
-    )
-    .await?;
+async fn download(url: &str) -> Result<(Vec<u8>, Option<String>)> {
+    let mut resp = reqwest::get(url)
+        .await
+        .with_context(|| format!("fetch {url}"))?
+        .error_for_status()?;
+    let content_type = resp
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
 
-    println!("🌐 URL appears clean: {url}");
-    Ok(())
+    let mut bytes = Vec::new();
+    while let Some(chunk) = resp.chunk().await? {
+        if bytes.len() + chunk.len() > MAX_DOWNLOAD_BYTES {
+            anyhow::bail!("{url} is larger than {MAX_DOWNLOAD_BYTES} bytes");
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok((bytes, content_type))
 }
 
+pub async fn handle_scan_url(url: String, mime_type: Option<String>, json: bool) -> Result<()> {
+    let (bytes, content_type) = download(&url).await?;
+    let mime_type = mime_type
+        .or(content_type)
+        .unwrap_or_else(|| "application/octet-stream".to_string());
+    let report = hybrid_sanitize(&bytes, &mime_type, Some(&url)).await?;
+    finish(&url, &report, json)
+}
@@ -4,9 +4,7 @@ use std::path::PathBuf;
 
 use crate::crypto::canonical::{
     CanonicalDocumentV1,
-    DocumentEnvelopeV1,
-    canonicalize::canonical_json,
-    hash::envelope_id,
+    DocumentEnvelopeV2,
     keystore::{load_or_create_mlkem_keypair, save_envelope_json, envelope_path},
 };
 
@@ -56,8 +54,8 @@ pub async fn handle_upload(args: UploadArgs) -> Result<(), AppError> {
 
     let created_at = time::OffsetDateTime::now_utc().unix_timestamp();
 
-    let (env, eid) = DocumentEnvelopeV1::create_mlkem_owner(
-        owner_wallet.clone(),
+    let (env, eid) = DocumentEnvelopeV2::create_mlkem_owner(
+        &owner_wallet,
         &kp.pk_b64,
         created_at,
         doc,
@@ -65,15 +63,17 @@ pub async fn handle_upload(args: UploadArgs) -> Result<(), AppError> {
     )
     .map_err(|e| AppError::Crypto(format!("envelope create: {e}")))?;
 
-    // canonical bytes -> store
-    let canon = canonical_json(&env);
-    // recompute eid from stored bytes (hard guarantee)
-    let eid2 = envelope_id(&canon);
+    // container bytes -> store
+    let stored = env.encode();
+    // re-read the stored bytes and recompute eid (hard guarantee)
+    let eid2 = DocumentEnvelopeV2::decode(&stored)
+        .map_err(|e| AppError::Crypto(format!("envelope decode: {e}")))?
+        .id();
     if eid2 != eid {
-        return Err(AppError::Crypto("envelope id mismatch after canonicalization".into()));
+        return Err(AppError::Crypto("envelope id mismatch after encoding".into()));
     }
 
-    save_envelope_json(&eid, &canon)
+    save_envelope_json(&eid, &stored)
         .map_err(|e| AppError::Internal(format!("save envelope: {e}")))?;
 
     let p = envelope_path(&eid).map_err(|e| AppError::Internal(e))?;
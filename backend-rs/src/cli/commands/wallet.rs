@@ -2,11 +2,12 @@
 
 use crate::cli::parser::WalletCommands;
 use crate::crypto::canonical::keystore::load_or_create_mlkem_keypair;
+use crate::identity::local_wallet::{DeviceKey, LocalWallet};
 
 /// Entry point from main.rs
 pub async fn handle_wallet(cmd: WalletCommands) -> anyhow::Result<()> {
     match cmd {
-        WalletCommands::Init => wallet_init().await?,
+        WalletCommands::Init { force } => wallet_init(force).await?,
         WalletCommands::Show => wallet_show().await?,
     }
     Ok(())
@@ -18,12 +19,44 @@ fn default_wallet_id() -> String {
     "local-owner".to_string()
 }
 
-async fn wallet_init() -> anyhow::Result<()> {
-    let wallet = default_wallet_id();
+/// `TIDBIT_WALLET_PASSPHRASE`, or an interactive prompt.
+fn read_passphrase(prompt: &str) -> anyhow::Result<String> {
+    if let Ok(passphrase) = std::env::var("TIDBIT_WALLET_PASSPHRASE") {
+        return Ok(passphrase);
+    }
+    Ok(rpassword::prompt_password(prompt)?)
+}
+
+/// Unlock the CLI device key used to sign C2C events.
+pub fn unlock_device_key() -> anyhow::Result<DeviceKey> {
+    if !LocalWallet::exists() {
+        anyhow::bail!("No device key found; run `tidbit wallet init` first");
+    }
+    let wallet = LocalWallet::load()?;
+    let passphrase = read_passphrase("Wallet passphrase: ")?;
+    Ok(wallet.unlock(&passphrase)?)
+}
 
+async fn wallet_init(force: bool) -> anyhow::Result<()> {
+    if LocalWallet::exists() && !force {
+        anyhow::bail!("A device key already exists; pass --force to replace it");
+    }
+
+    let passphrase = read_passphrase("New wallet passphrase: ")?;
+    if std::env::var("TIDBIT_WALLET_PASSPHRASE").is_err()
+        && read_passphrase("Repeat passphrase: ")? != passphrase
+    {
+        anyhow::bail!("Passphrases do not match");
+    }
+    let device = LocalWallet::generate(&passphrase)?;
+
+    let wallet = default_wallet_id();
     let keys = load_or_create_mlkem_keypair(&wallet).map_err(|e| anyhow::anyhow!(e))?;
 
     println!("✅ Wallet initialized");
+    println!("device: {}", device.id);
+    println!("sig: {}", device.alg);
+    println!("mldsa_pk_b64: {}", device.public_key_b64);
     println!("wallet: {}", keys.wallet);
     println!("kem: {}", keys.kem);
     println!("mlkem_pk_b64: {}", keys.pk_b64);
@@ -36,6 +69,14 @@ async fn wallet_show() -> anyhow::Result<()> {
 
     let keys = load_or_create_mlkem_keypair(&wallet).map_err(|e| anyhow::anyhow!(e))?;
 
+    match LocalWallet::load() {
+        Ok(device) => {
+            println!("device: {}", device.id);
+            println!("sig: {}", device.alg);
+            println!("mldsa_pk_b64: {}", device.public_key_b64);
+        }
+        Err(_) => println!("device: none (run `tidbit wallet init`)"),
+    }
     println!("wallet: {}", keys.wallet);
     println!("kem: {}", keys.kem);
     println!("mlkem_pk_b64: {}", keys.pk_b64);
@@ -46,6 +46,44 @@ pub enum Commands {
         #[command(subcommand)]
         action: C2cCommands,
     },
+
+    /// Evidence bundle tools
+    Evidence {
+        #[command(subcommand)]
+        action: EvidenceCommands,
+    },
+
+    /// Custody ledger maintenance
+    Ledger {
+        #[command(subcommand)]
+        action: LedgerCommands,
+    },
+
+    /// Run the upload scanners over a local file
+    Scan {
+        path: String,
+
+        /// MIME type the upload would declare; checked against the magic bytes
+        #[arg(long)]
+        mime_type: Option<String>,
+
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Download a URL and run the upload scanners over it
+    ScanUrl {
+        url: String,
+
+        /// Declared MIME type; defaults to the response Content-Type
+        #[arg(long)]
+        mime_type: Option<String>,
+
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
 }
 
 // ======================================================
@@ -69,6 +107,12 @@ pub enum AuthCommands {
         api: String,
     },
 
+    /// Rotate the session using TIDBIT_REFRESH_TOKEN
+    Refresh {
+        #[arg(long, default_value = "http://localhost:4100")]
+        api: String,
+    },
+
     /// Logout current session
     Logout {
         #[arg(long, default_value = "http://localhost:4100")]
@@ -82,7 +126,12 @@ pub enum AuthCommands {
 
 #[derive(Subcommand, Debug)]
 pub enum WalletCommands {
-    Init,
+    /// Generate a passphrase-sealed ML-DSA-65 device key
+    Init {
+        /// Replace an existing device key
+        #[arg(long)]
+        force: bool,
+    },
     Show,
 }
 
@@ -178,7 +227,7 @@ pub enum DocCommands {
         wallet: Option<String>,
     },
 
-    /// Repair legacy Supabase storage paths for document rows
+    /// Repair legacy blob storage paths for document rows
     RepairStoragePaths {
         /// Apply the changes. Without this flag, prints a dry-run plan.
         #[arg(long)]
@@ -192,6 +241,21 @@ pub enum DocCommands {
         #[arg(long)]
         limit: Option<i64>,
     },
+
+    /// Rewrite stored V1 JSON envelopes in the binary V2 format
+    UpgradeEnvelopes {
+        /// Apply the changes. Without this flag, prints a dry-run plan.
+        #[arg(long)]
+        apply: bool,
+
+        /// Upgrade only one document id
+        #[arg(long)]
+        id: Option<String>,
+
+        /// Limit how many document rows to inspect
+        #[arg(long)]
+        limit: Option<i64>,
+    },
 }
 
 // ======================================================
@@ -203,4 +267,44 @@ pub enum C2cCommands {
     List,
     Show { id: String },
     Anchor { id: String },
+    /// Check an event's device signature
+    Verify { id: String },
+}
+
+// ======================================================
+// EVIDENCE
+// ======================================================
+
+#[derive(Subcommand, Debug)]
+pub enum EvidenceCommands {
+    /// Verify an exported evidence bundle offline
+    Verify {
+        /// Path to the JSON bundle from /api/doc/:id/evidence
+        bundle: String,
+
+        /// Original file to check against the bundle lineage
+        #[arg(long)]
+        file: Option<String>,
+
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+// ======================================================
+// LEDGER
+// ======================================================
+
+#[derive(Subcommand, Debug)]
+pub enum LedgerCommands {
+    /// Import legacy C2C event files and filetrail records into the ledger
+    Migrate {
+        /// Also import `c2c_events` rows into `document_events` (needs DATABASE_URL)
+        #[arg(long)]
+        database: bool,
+    },
+
+    /// Check the hash chain of every document in the local ledger
+    Verify,
 }
@@ -12,12 +12,10 @@ use chacha20poly1305::{
 use hkdf::Hkdf;
 use sha2::Sha256;
 
-use crate::crypto::canonical::canonicalize::canonical_json;
-use crate::crypto::canonical::hash::envelope_id;
 use crate::crypto::canonical::kem::{mlkem_decapsulate_b64, mlkem_encapsulate_b64};
 use crate::crypto::canonical::{CanonicalDocumentV1, EncryptionInfoV1, WrappedCekV1};
 
-fn normalize_wallet_identifier(wallet: &str) -> String {
+pub(crate) fn normalize_wallet_identifier(wallet: &str) -> String {
     let trimmed = wallet.trim();
     if trimmed.starts_with("0x") {
         trimmed.to_ascii_lowercase()
@@ -26,44 +24,87 @@ fn normalize_wallet_identifier(wallet: &str) -> String {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct DocumentEnvelopeV1 {
-    pub v: u16,
-    pub owner: String,
-    pub created_at: i64,
-    pub doc: CanonicalDocumentV1,
-    pub encryption: EncryptionInfoV1,
-    pub ciphertext_b64: String,
+fn derive_wrap_key(shared_secret: &[u8]) -> Result<[u8; 32], String> {
+    let hk = Hkdf::<Sha256>::new(None, shared_secret);
+    let mut wrap_key = [0u8; 32];
+    hk.expand(b"tidbit-cek-wrap-v1", &mut wrap_key)
+        .map_err(|_| "hkdf expand failed".to_string())?;
+    Ok(wrap_key)
 }
 
-impl DocumentEnvelopeV1 {
-    /// Owner-only convenience wrapper (kept for backwards compatibility).
-    pub fn create_mlkem_owner(
-        owner_wallet: String,
-        owner_mlkem_pk_b64: &str,
-        created_at: i64,
-        doc: CanonicalDocumentV1,
-        plaintext: &[u8],
-    ) -> Result<(Self, String), String> {
-        Self::create_mlkem_recipients(
-            owner_wallet.clone(),
-            vec![(owner_wallet, owner_mlkem_pk_b64.to_string())],
-            created_at,
-            doc,
-            plaintext,
-        )
+/// Wrap a CEK for one recipient via ML-KEM + HKDF + XChaCha20-Poly1305.
+pub(crate) fn wrap_cek_mlkem(
+    cek: &[u8; 32],
+    wallet: &str,
+    pk_b64: &str,
+) -> Result<WrappedCekV1, String> {
+    let (kem_ct_b64, shared_secret) = mlkem_encapsulate_b64(pk_b64)?;
+    let wrap_key = derive_wrap_key(&shared_secret)?;
+
+    let mut wrap_nonce_bytes = [0u8; 24];
+    rand::thread_rng().fill_bytes(&mut wrap_nonce_bytes);
+    let wrap_nonce = XNonce::from_slice(&wrap_nonce_bytes);
+
+    let wrap_cipher = XChaCha20Poly1305::new((&wrap_key).into());
+    let wrapped_cek = wrap_cipher
+        .encrypt(wrap_nonce, cek.as_ref())
+        .map_err(|_| "cek wrap failed".to_string())?;
+
+    Ok(WrappedCekV1 {
+        kem: "mlkem768".into(),
+        recipient: normalize_wallet_identifier(wallet),
+        kem_ct_b64,
+        wrap_nonce_b64: URL_SAFE_NO_PAD.encode(wrap_nonce_bytes),
+        wrapped_cek_b64: URL_SAFE_NO_PAD.encode(wrapped_cek),
+    })
+}
+
+/// Recover a CEK from one recipient's wrapped key.
+pub(crate) fn unwrap_cek_mlkem(wk: &WrappedCekV1, mlkem_sk_b64: &str) -> Result<[u8; 32], String> {
+    // 1) Decapsulate
+    let shared_secret = mlkem_decapsulate_b64(mlkem_sk_b64, &wk.kem_ct_b64)?;
+
+    // 2) Derive wrap key
+    let wrap_key = derive_wrap_key(&shared_secret)?;
+
+    // 3) Unwrap CEK
+    let wrap_nonce_bytes = URL_SAFE_NO_PAD
+        .decode(&wk.wrap_nonce_b64)
+        .map_err(|e| format!("wrap nonce decode: {e}"))?;
+
+    if wrap_nonce_bytes.len() != 24 {
+        return Err("wrap nonce invalid length".into());
+    }
+
+    let wrap_nonce = XNonce::from_slice(&wrap_nonce_bytes);
+
+    let wrapped_cek = URL_SAFE_NO_PAD
+        .decode(&wk.wrapped_cek_b64)
+        .map_err(|e| format!("wrapped cek decode: {e}"))?;
+
+    let wrap_cipher = XChaCha20Poly1305::new((&wrap_key).into());
+    let cek = wrap_cipher
+        .decrypt(wrap_nonce, wrapped_cek.as_ref())
+        .map_err(|_| "cek unwrap failed".to_string())?;
+
+    if cek.len() != 32 {
+        return Err("invalid CEK length".into());
     }
 
-    /// Multi-recipient envelope:
-    /// - single CEK encrypts payload
-    /// - CEK wrapped independently per-recipient via ML-KEM + HKDF + XChaCha20-Poly1305
-    pub fn create_mlkem_recipients(
-        owner_wallet: String,
-        recipients: Vec<(String, String)>, // (wallet, pk_b64)
-        created_at: i64,
-        doc: CanonicalDocumentV1,
+    let mut cek_arr = [0u8; 32];
+    cek_arr.copy_from_slice(&cek[..32]);
+    Ok(cek_arr)
+}
+
+/// Payload encryption and key wrapping shared by the V1 and V2 envelopes,
+/// which differ only in how the ciphertext is stored.
+impl EncryptionInfoV1 {
+    /// Encrypt `plaintext` under a fresh CEK and wrap the CEK independently
+    /// for each `(wallet, pk_b64)` recipient.
+    pub(crate) fn seal_mlkem(
+        recipients: Vec<(String, String)>,
         plaintext: &[u8],
-    ) -> Result<(DocumentEnvelopeV1, String), String> {
+    ) -> Result<(Self, Vec<u8>), String> {
         // 1) CEK
         let mut cek = [0u8; 32];
         rand::thread_rng().fill_bytes(&mut cek);
@@ -82,29 +123,7 @@ impl DocumentEnvelopeV1 {
         let mut wrapped_keys = Vec::new();
 
         for (wallet, pk_b64) in recipients {
-            let (kem_ct_b64, shared_secret) = mlkem_encapsulate_b64(&pk_b64)?;
-
-            let hk = Hkdf::<Sha256>::new(None, &shared_secret);
-            let mut wrap_key = [0u8; 32];
-            hk.expand(b"tidbit-cek-wrap-v1", &mut wrap_key)
-                .map_err(|_| "hkdf expand failed".to_string())?;
-
-            let mut wrap_nonce_bytes = [0u8; 24];
-            rand::thread_rng().fill_bytes(&mut wrap_nonce_bytes);
-            let wrap_nonce = XNonce::from_slice(&wrap_nonce_bytes);
-
-            let wrap_cipher = XChaCha20Poly1305::new((&wrap_key).into());
-            let wrapped_cek = wrap_cipher
-                .encrypt(wrap_nonce, cek.as_ref())
-                .map_err(|_| "cek wrap failed".to_string())?;
-
-            wrapped_keys.push(WrappedCekV1 {
-                kem: "mlkem768".into(),
-                recipient: normalize_wallet_identifier(&wallet),
-                kem_ct_b64,
-                wrap_nonce_b64: URL_SAFE_NO_PAD.encode(wrap_nonce_bytes),
-                wrapped_cek_b64: URL_SAFE_NO_PAD.encode(wrapped_cek),
-            });
+            wrapped_keys.push(wrap_cek_mlkem(&cek, &wallet, &pk_b64)?);
         }
 
         // 4) Metadata
@@ -115,81 +134,44 @@ impl DocumentEnvelopeV1 {
             wrapped_keys,
         };
 
-        // 5) Envelope
-        let env = DocumentEnvelopeV1 {
-            v: 1,
-            owner: normalize_wallet_identifier(&owner_wallet),
-            created_at,
-            doc,
-            encryption,
-            ciphertext_b64: URL_SAFE_NO_PAD.encode(ciphertext),
-        };
-
-        // 6) ID
-        let canon = canonical_json(&env);
-        let eid = envelope_id(&canon);
-
-        Ok((env, eid))
+        Ok((encryption, ciphertext))
     }
 
-    /// Generic decrypt for ANY recipient wallet present in wrapped_keys.
-    pub fn decrypt_for_wallet_mlkem(
-        &self,
-        wallet: &str,
-        mlkem_sk_b64: &str,
-    ) -> Result<Vec<u8>, String> {
+    pub(crate) fn wrapped_key_for(&self, wallet: &str) -> Option<&WrappedCekV1> {
         let normalized_wallet = normalize_wallet_identifier(wallet);
         let legacy_lowercase_wallet = wallet.trim().to_ascii_lowercase();
 
-        let wk = self
-            .encryption
-            .wrapped_keys
-            .iter()
-            .find(|k| {
-                k.recipient == normalized_wallet
-                    || (!wallet.trim().starts_with("0x") && k.recipient == legacy_lowercase_wallet)
-            })
-            .ok_or_else(|| "no wrapped key for this wallet".to_string())?;
-
-        // 1) Decapsulate
-        let shared_secret = mlkem_decapsulate_b64(mlkem_sk_b64, &wk.kem_ct_b64)?;
-
-        // 2) Derive wrap key
-        let hk = Hkdf::<Sha256>::new(None, &shared_secret);
-        let mut wrap_key = [0u8; 32];
-        hk.expand(b"tidbit-cek-wrap-v1", &mut wrap_key)
-            .map_err(|_| "hkdf expand failed".to_string())?;
-
-        // 3) Unwrap CEK
-        let wrap_nonce_bytes = URL_SAFE_NO_PAD
-            .decode(&wk.wrap_nonce_b64)
-            .map_err(|e| format!("wrap nonce decode: {e}"))?;
-
-        if wrap_nonce_bytes.len() != 24 {
-            return Err("wrap nonce invalid length".into());
-        }
-
-        let wrap_nonce = XNonce::from_slice(&wrap_nonce_bytes);
-
-        let wrapped_cek = URL_SAFE_NO_PAD
-            .decode(&wk.wrapped_cek_b64)
-            .map_err(|e| format!("wrapped cek decode: {e}"))?;
+        self.wrapped_keys.iter().find(|k| {
+            k.recipient == normalized_wallet
+                || (!wallet.trim().starts_with("0x") && k.recipient == legacy_lowercase_wallet)
+        })
+    }
 
-        let wrap_cipher = XChaCha20Poly1305::new((&wrap_key).into());
-        let cek = wrap_cipher
-            .decrypt(wrap_nonce, wrapped_cek.as_ref())
-            .map_err(|_| "cek unwrap failed".to_string())?;
+    pub(crate) fn recipients(&self) -> Vec<String> {
+        self.wrapped_keys
+            .iter()
+            .map(|k| k.recipient.clone())
+            .collect()
+    }
 
-        if cek.len() != 32 {
-            return Err("invalid CEK length".into());
-        }
+    fn unwrap_cek_mlkem(&self, wallet: &str, mlkem_sk_b64: &str) -> Result<[u8; 32], String> {
+        let wk = self
+            .wrapped_key_for(wallet)
+            .ok_or_else(|| "no wrapped key for this wallet".to_string())?;
+        unwrap_cek_mlkem(wk, mlkem_sk_b64)
+    }
 
-        let mut cek_arr = [0u8; 32];
-        cek_arr.copy_from_slice(&cek[..32]);
+    /// Decrypt `ciphertext` with the CEK wrapped for `wallet`.
+    pub(crate) fn open_mlkem(
+        &self,
+        wallet: &str,
+        mlkem_sk_b64: &str,
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>, String> {
+        let cek_arr = self.unwrap_cek_mlkem(wallet, mlkem_sk_b64)?;
 
-        // 4) Decrypt payload
         let payload_nonce_bytes = URL_SAFE_NO_PAD
-            .decode(&self.encryption.nonce_b64)
+            .decode(&self.nonce_b64)
             .map_err(|e| format!("payload nonce decode: {e}"))?;
 
         if payload_nonce_bytes.len() != 24 {
@@ -198,18 +180,36 @@ impl DocumentEnvelopeV1 {
 
         let payload_nonce = XNonce::from_slice(&payload_nonce_bytes);
 
-        let ciphertext = URL_SAFE_NO_PAD
-            .decode(&self.ciphertext_b64)
-            .map_err(|e| format!("ciphertext decode: {e}"))?;
-
         let payload_cipher = XChaCha20Poly1305::new((&cek_arr).into());
         payload_cipher
-            .decrypt(payload_nonce, ciphertext.as_ref())
+            .decrypt(payload_nonce, ciphertext)
             .map_err(|_| "payload decryption failed".to_string())
     }
 
-    /// Owner convenience wrapper.
-    pub fn decrypt_for_owner_mlkem(&self, owner_mlkem_sk_b64: &str) -> Result<Vec<u8>, String> {
-        self.decrypt_for_wallet_mlkem(&self.owner, owner_mlkem_sk_b64)
+    pub(crate) fn add_recipient_mlkem(
+        &mut self,
+        holder_wallet: &str,
+        holder_mlkem_sk_b64: &str,
+        recipient_wallet: &str,
+        recipient_mlkem_pk_b64: &str,
+    ) -> Result<(), String> {
+        let cek = self.unwrap_cek_mlkem(holder_wallet, holder_mlkem_sk_b64)?;
+        let wrapped = wrap_cek_mlkem(&cek, recipient_wallet, recipient_mlkem_pk_b64)?;
+        self.wrapped_keys
+            .retain(|k| k.recipient != wrapped.recipient);
+        self.wrapped_keys.push(wrapped);
+        Ok(())
     }
 }
+
+/// Legacy JSON envelope with a base64 body. It is only read now, through
+/// `read_document_envelope`; new and rewritten envelopes are V2.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentEnvelopeV1 {
+    pub v: u16,
+    pub owner: String,
+    pub created_at: i64,
+    pub doc: CanonicalDocumentV1,
+    pub encryption: EncryptionInfoV1,
+    pub ciphertext_b64: String,
+}
@@ -0,0 +1,249 @@
+// src/crypto/canonical/envelope_v2.rs
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::canonical::canonicalize::canonical_json;
+use crate::crypto::canonical::envelope::normalize_wallet_identifier;
+use crate::crypto::canonical::hash::envelope_id;
+use crate::crypto::canonical::{CanonicalDocumentV1, DocumentEnvelopeV1, EncryptionInfoV1};
+use crate::pqc::sha3 as pqc_sha3;
+
+/// Leading bytes of a V2 container. V1 envelopes are JSON and start with `{`.
+pub const ENVELOPE_V2_MAGIC: [u8; 4] = *b"TBE\x02";
+
+/// Magic plus the big-endian u32 header length.
+pub const ENVELOPE_V2_PREAMBLE_LEN: usize = 8;
+
+pub const ENVELOPE_V1_CONTENT_TYPE: &str = "application/tidbit-envelope+json";
+pub const ENVELOPE_V2_CONTENT_TYPE: &str = "application/tidbit-envelope";
+
+/// Headers are metadata plus a few wrapped keys; anything larger is corrupt.
+const MAX_HEADER_LEN: usize = 1 << 20;
+
+/// Canonical-JSON header of a V2 container. It binds the body through
+/// `ciphertext_sha3_256_hex`, so its hash is the envelope id.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnvelopeHeaderV2 {
+    pub v: u16,
+    pub owner: String,
+    pub created_at: i64,
+    pub doc: CanonicalDocumentV1,
+    pub encryption: EncryptionInfoV1,
+    pub ciphertext_len: u64,
+    pub ciphertext_sha3_256_hex: String,
+}
+
+/// Binary envelope container:
+///
+/// ```text
+/// "TBE\x02" | u32 BE header length | canonical JSON header | raw ciphertext
+/// ```
+#[derive(Debug, Clone)]
+pub struct DocumentEnvelopeV2 {
+    pub header: EnvelopeHeaderV2,
+    pub ciphertext: Vec<u8>,
+}
+
+pub fn is_envelope_v2(bytes: &[u8]) -> bool {
+    bytes.starts_with(&ENVELOPE_V2_MAGIC)
+}
+
+/// MIME type for stored envelope bytes of either version.
+pub fn envelope_content_type(bytes: &[u8]) -> &'static str {
+    if is_envelope_v2(bytes) {
+        ENVELOPE_V2_CONTENT_TYPE
+    } else {
+        ENVELOPE_V1_CONTENT_TYPE
+    }
+}
+
+/// Parse stored envelope bytes of either version. V1 is converted in memory;
+/// its ciphertext is only re-encoded, never re-encrypted.
+pub fn read_document_envelope(bytes: &[u8]) -> Result<DocumentEnvelopeV2, String> {
+    if is_envelope_v2(bytes) {
+        return DocumentEnvelopeV2::decode(bytes);
+    }
+    let v1: DocumentEnvelopeV1 =
+        serde_json::from_slice(bytes).map_err(|e| format!("envelope parse: {e}"))?;
+    DocumentEnvelopeV2::from_v1(v1)
+}
+
+impl DocumentEnvelopeV2 {
+    fn from_parts(
+        owner_wallet: &str,
+        created_at: i64,
+        doc: CanonicalDocumentV1,
+        encryption: EncryptionInfoV1,
+        ciphertext: Vec<u8>,
+    ) -> Self {
+        Self {
+            header: EnvelopeHeaderV2 {
+                v: 2,
+                owner: normalize_wallet_identifier(owner_wallet),
+                created_at,
+                doc,
+                encryption,
+                ciphertext_len: ciphertext.len() as u64,
+                ciphertext_sha3_256_hex: hex::encode(pqc_sha3::sha3_256_bytes(&ciphertext)),
+            },
+            ciphertext,
+        }
+    }
+
+    /// Owner-only envelope.
+    pub fn create_mlkem_owner(
+        owner_wallet: &str,
+        owner_mlkem_pk_b64: &str,
+        created_at: i64,
+        doc: CanonicalDocumentV1,
+        plaintext: &[u8],
+    ) -> Result<(Self, String), String> {
+        Self::create_mlkem_recipients(
+            owner_wallet,
+            vec![(owner_wallet.to_string(), owner_mlkem_pk_b64.to_string())],
+            created_at,
+            doc,
+            plaintext,
+        )
+    }
+
+    /// Same key scheme as `DocumentEnvelopeV1::create_mlkem_recipients`.
+    pub fn create_mlkem_recipients(
+        owner_wallet: &str,
+        recipients: Vec<(String, String)>, // (wallet, pk_b64)
+        created_at: i64,
+        doc: CanonicalDocumentV1,
+        plaintext: &[u8],
+    ) -> Result<(Self, String), String> {
+        let (encryption, ciphertext) = EncryptionInfoV1::seal_mlkem(recipients, plaintext)?;
+        let env = Self::from_parts(owner_wallet, created_at, doc, encryption, ciphertext);
+        let eid = env.id();
+        Ok((env, eid))
+    }
+
+    /// Lossless upgrade: same CEK, nonce and wrapped keys.
+    pub fn from_v1(v1: DocumentEnvelopeV1) -> Result<Self, String> {
+        if v1.v != 1 {
+            return Err(format!("unsupported envelope version: {}", v1.v));
+        }
+        let ciphertext = URL_SAFE_NO_PAD
+            .decode(&v1.ciphertext_b64)
+            .map_err(|e| format!("ciphertext decode: {e}"))?;
+        Ok(Self::from_parts(
+            &v1.owner,
+            v1.created_at,
+            v1.doc,
+            v1.encryption,
+            ciphertext,
+        ))
+    }
+
+    pub fn id(&self) -> String {
+        envelope_id(&canonical_json(&self.header))
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let header = canonical_json(&self.header);
+        let mut out =
+            Vec::with_capacity(ENVELOPE_V2_PREAMBLE_LEN + header.len() + self.ciphertext.len());
+        out.extend_from_slice(&ENVELOPE_V2_MAGIC);
+        out.extend_from_slice(&(header.len() as u32).to_be_bytes());
+        out.extend_from_slice(&header);
+        out.extend_from_slice(&self.ciphertext);
+        out
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, String> {
+        if !is_envelope_v2(bytes) || bytes.len() < ENVELOPE_V2_PREAMBLE_LEN {
+            return Err("not a V2 envelope".into());
+        }
+        let header_len = u32::from_be_bytes(
+            bytes[4..ENVELOPE_V2_PREAMBLE_LEN]
+                .try_into()
+                .expect("4-byte length"),
+        ) as usize;
+        if header_len > MAX_HEADER_LEN || bytes.len() < ENVELOPE_V2_PREAMBLE_LEN + header_len {
+            return Err("envelope header length out of range".into());
+        }
+        let (header_bytes, ciphertext) = bytes[ENVELOPE_V2_PREAMBLE_LEN..].split_at(header_len);
+        let header: EnvelopeHeaderV2 =
+            serde_json::from_slice(header_bytes).map_err(|e| format!("envelope header: {e}"))?;
+
+        if header.v != 2 {
+            return Err(format!("unsupported envelope version: {}", header.v));
+        }
+        if header.ciphertext_len != ciphertext.len() as u64
+            || header.ciphertext_sha3_256_hex != hex::encode(pqc_sha3::sha3_256_bytes(ciphertext))
+        {
+            return Err("envelope body does not match its header".into());
+        }
+
+        Ok(Self {
+            header,
+            ciphertext: ciphertext.to_vec(),
+        })
+    }
+
+    pub fn owner(&self) -> &str {
+        &self.header.owner
+    }
+
+    /// Whether `wallet` holds a wrapped CEK in this envelope.
+    pub fn has_recipient(&self, wallet: &str) -> bool {
+        self.header.encryption.wrapped_key_for(wallet).is_some()
+    }
+
+    /// Wallets that currently hold a wrapped CEK.
+    pub fn recipients(&self) -> Vec<String> {
+        self.header.encryption.recipients()
+    }
+
+    pub fn decrypt_for_wallet_mlkem(
+        &self,
+        wallet: &str,
+        mlkem_sk_b64: &str,
+    ) -> Result<Vec<u8>, String> {
+        self.header
+            .encryption
+            .open_mlkem(wallet, mlkem_sk_b64, &self.ciphertext)
+    }
+
+    pub fn decrypt_for_owner_mlkem(&self, owner_mlkem_sk_b64: &str) -> Result<Vec<u8>, String> {
+        self.decrypt_for_wallet_mlkem(&self.header.owner, owner_mlkem_sk_b64)
+    }
+
+    /// Grant an additional recipient access to the existing CEK; the body is
+    /// left untouched.
+    pub fn add_recipient_mlkem(
+        &mut self,
+        holder_wallet: &str,
+        holder_mlkem_sk_b64: &str,
+        recipient_wallet: &str,
+        recipient_mlkem_pk_b64: &str,
+    ) -> Result<(), String> {
+        self.header.encryption.add_recipient_mlkem(
+            holder_wallet,
+            holder_mlkem_sk_b64,
+            recipient_wallet,
+            recipient_mlkem_pk_b64,
+        )
+    }
+
+    /// Re-encrypt under a fresh CEK wrapped only for `recipients`.
+    pub fn rekey_mlkem(
+        &self,
+        holder_wallet: &str,
+        holder_mlkem_sk_b64: &str,
+        recipients: Vec<(String, String)>,
+    ) -> Result<(Self, String), String> {
+        let plaintext = self.decrypt_for_wallet_mlkem(holder_wallet, holder_mlkem_sk_b64)?;
+        Self::create_mlkem_recipients(
+            &self.header.owner,
+            recipients,
+            self.header.created_at,
+            self.header.doc.clone(),
+            &plaintext,
+        )
+    }
+}
@@ -3,18 +3,22 @@
 pub mod canonicalize;
 pub mod document;
 pub mod envelope;
+pub mod envelope_v2;
 pub mod hash;
 pub mod kem;
 pub mod keystore;
 pub mod metadata;
+pub mod stream;
 
 pub use canonicalize::*;
 pub use document::*;
 pub use envelope::*;
+pub use envelope_v2::*;
 pub use hash::*;
 pub use kem::*;
 pub use keystore::*;
 pub use metadata::*;
+pub use stream::*;
 
 #[cfg(test)]
 mod tests;
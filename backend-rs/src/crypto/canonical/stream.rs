@@ -0,0 +1,209 @@
+// src/crypto/canonical/stream.rs
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+use chacha20poly1305::{
+    aead::{Aead, KeyInit},
+    XChaCha20Poly1305, XNonce,
+};
+
+use crate::crypto::canonical::envelope::{
+    normalize_wallet_identifier, unwrap_cek_mlkem, wrap_cek_mlkem,
+};
+use crate::crypto::canonical::{CanonicalDocumentV1, WrappedCekV1};
+
+pub const STREAM_ALG: &str = "xchacha20poly1305-stream-be32";
+
+/// Plaintext bytes per segment. Every segment but the last is exactly this
+/// size, and it stays under axum's default request body limit.
+pub const STREAM_SEGMENT_SIZE: usize = 1 << 20;
+
+const NONCE_PREFIX_LEN: usize = 19;
+
+/// STREAM nonce: 19-byte random prefix, big-endian segment counter, and a
+/// final-segment flag, so segments cannot be reordered, dropped, or appended.
+fn segment_nonce(
+    prefix: &[u8; NONCE_PREFIX_LEN],
+    index: u64,
+    last: bool,
+) -> Result<[u8; 24], String> {
+    let counter = u32::try_from(index).map_err(|_| "stream segment index overflow".to_string())?;
+    let mut nonce = [0u8; 24];
+    nonce[..NONCE_PREFIX_LEN].copy_from_slice(prefix);
+    nonce[NONCE_PREFIX_LEN..23].copy_from_slice(&counter.to_be_bytes());
+    nonce[23] = last as u8;
+    Ok(nonce)
+}
+
+pub fn segment_count(size_bytes: u64, segment_size: usize) -> u64 {
+    size_bytes.div_ceil(segment_size as u64).max(1)
+}
+
+/// Key material for one streamed document, persisted while the upload is in
+/// progress. The CEK only ever exists wrapped for the owner.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamKeyV1 {
+    pub nonce_prefix_b64: String,
+    pub wrapped_key: WrappedCekV1,
+}
+
+impl StreamKeyV1 {
+    pub fn new_mlkem(owner_wallet: &str, owner_mlkem_pk_b64: &str) -> Result<Self, String> {
+        let mut cek = Zeroizing::new([0u8; 32]);
+        rand::thread_rng().fill_bytes(cek.as_mut());
+        let mut prefix = [0u8; NONCE_PREFIX_LEN];
+        rand::thread_rng().fill_bytes(&mut prefix);
+
+        Ok(Self {
+            nonce_prefix_b64: URL_SAFE_NO_PAD.encode(prefix),
+            wrapped_key: wrap_cek_mlkem(&cek, owner_wallet, owner_mlkem_pk_b64)?,
+        })
+    }
+
+    pub fn unlock(&self, mlkem_sk_b64: &str) -> Result<StreamCipher, String> {
+        StreamCipher::new(
+            unwrap_cek_mlkem(&self.wrapped_key, mlkem_sk_b64)?,
+            &self.nonce_prefix_b64,
+        )
+    }
+}
+
+pub struct StreamCipher {
+    cipher: XChaCha20Poly1305,
+    prefix: [u8; NONCE_PREFIX_LEN],
+}
+
+impl StreamCipher {
+    fn new(cek: [u8; 32], nonce_prefix_b64: &str) -> Result<Self, String> {
+        let cek = Zeroizing::new(cek);
+        let prefix = URL_SAFE_NO_PAD
+            .decode(nonce_prefix_b64)
+            .map_err(|e| format!("stream nonce prefix decode: {e}"))?
+            .try_into()
+            .map_err(|_| "stream nonce prefix invalid length".to_string())?;
+        Ok(Self {
+            cipher: XChaCha20Poly1305::new(cek.as_ref().into()),
+            prefix,
+        })
+    }
+
+    pub fn encrypt_segment(
+        &self,
+        index: u64,
+        last: bool,
+        plaintext: &[u8],
+    ) -> Result<Vec<u8>, String> {
+        let nonce = segment_nonce(&self.prefix, index, last)?;
+        self.cipher
+            .encrypt(XNonce::from_slice(&nonce), plaintext)
+            .map_err(|_| "segment encryption failed".to_string())
+    }
+
+    pub fn decrypt_segment(
+        &self,
+        index: u64,
+        last: bool,
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>, String> {
+        let nonce = segment_nonce(&self.prefix, index, last)?;
+        self.cipher
+            .decrypt(XNonce::from_slice(&nonce), ciphertext)
+            .map_err(|_| format!("segment {index} decryption failed"))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamEncryptionV1 {
+    /// "xchacha20poly1305-stream-be32"
+    pub alg: String,
+
+    /// STREAM nonce prefix (19 bytes) base64
+    pub nonce_prefix_b64: String,
+
+    pub segment_size: u32,
+    pub segment_count: u64,
+
+    /// Merkle root over the SHA3-256 of each stored segment, in order.
+    pub segments_root_hex: String,
+
+    /// How CEK is protected: "mlkem"
+    pub cek_wrap: String,
+
+    pub wrapped_keys: Vec<WrappedCekV1>,
+}
+
+/// Header of a segmented document. The ciphertext lives in separate
+/// per-segment objects next to the header.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentStreamEnvelopeV1 {
+    pub v: u16,
+    pub kind: String,
+    pub owner: String,
+    pub created_at: i64,
+    pub doc: CanonicalDocumentV1,
+    pub encryption: StreamEncryptionV1,
+}
+
+impl DocumentStreamEnvelopeV1 {
+    pub fn new(
+        owner_wallet: &str,
+        created_at: i64,
+        doc: CanonicalDocumentV1,
+        key: StreamKeyV1,
+        segments_root_hex: String,
+    ) -> Self {
+        let segment_count = segment_count(doc.size_bytes, STREAM_SEGMENT_SIZE);
+        Self {
+            v: 1,
+            kind: "stream".into(),
+            owner: normalize_wallet_identifier(owner_wallet),
+            created_at,
+            doc,
+            encryption: StreamEncryptionV1 {
+                alg: STREAM_ALG.into(),
+                nonce_prefix_b64: key.nonce_prefix_b64,
+                segment_size: STREAM_SEGMENT_SIZE as u32,
+                segment_count,
+                segments_root_hex,
+                cek_wrap: "mlkem".into(),
+                wrapped_keys: vec![key.wrapped_key],
+            },
+        }
+    }
+
+    pub fn cipher_for_wallet(
+        &self,
+        wallet: &str,
+        mlkem_sk_b64: &str,
+    ) -> Result<StreamCipher, String> {
+        if self.encryption.alg != STREAM_ALG {
+            return Err(format!("unsupported stream alg: {}", self.encryption.alg));
+        }
+        let wallet = normalize_wallet_identifier(wallet);
+        let wrapped = self
+            .encryption
+            .wrapped_keys
+            .iter()
+            .find(|k| k.recipient == wallet)
+            .ok_or_else(|| "no wrapped key for this wallet".to_string())?;
+        StreamCipher::new(
+            unwrap_cek_mlkem(wrapped, mlkem_sk_b64)?,
+            &self.encryption.nonce_prefix_b64,
+        )
+    }
+
+    pub fn has_recipient(&self, wallet: &str) -> bool {
+        let wallet = normalize_wallet_identifier(wallet);
+        self.encryption
+            .wrapped_keys
+            .iter()
+            .any(|k| k.recipient == wallet)
+    }
+
+    pub fn is_last_segment(&self, index: u64) -> bool {
+        index + 1 == self.encryption.segment_count
+    }
+}
@@ -1,6 +1,11 @@
 //src/crypto/canonical/tests.rs
 
-use crate::crypto::canonical::{CanonicalDocumentV1, DocumentEnvelopeV1};
+use crate::crypto::canonical::{
+    canonical_json, envelope_content_type, read_document_envelope, segment_count,
+    CanonicalDocumentV1, DocumentEnvelopeV1, DocumentEnvelopeV2, DocumentStreamEnvelopeV1,
+    EncryptionInfoV1, StreamKeyV1, ENVELOPE_V1_CONTENT_TYPE, ENVELOPE_V2_CONTENT_TYPE,
+    STREAM_SEGMENT_SIZE,
+};
 use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
 use fips203::ml_kem_768;
 use fips203::traits::{Encaps, SerDes as MlKemSerDes};
@@ -49,8 +54,8 @@ fn mixed_case_solana_owner_envelopes_decrypt_on_server_path() {
         Some("text/plain".to_string()),
     );
 
-    let (envelope, _) = DocumentEnvelopeV1::create_mlkem_owner(
-        wallet.to_string(),
+    let (envelope, _) = DocumentEnvelopeV2::create_mlkem_owner(
+        wallet,
         &kp.pk_b64,
         1_715_218_400,
         doc,
@@ -58,8 +63,8 @@ fn mixed_case_solana_owner_envelopes_decrypt_on_server_path() {
     )
     .expect("create envelope");
 
-    assert_eq!(envelope.owner, wallet);
-    assert_eq!(envelope.encryption.wrapped_keys[0].recipient, wallet);
+    assert_eq!(envelope.owner(), wallet);
+    assert_eq!(envelope.header.encryption.wrapped_keys[0].recipient, wallet);
 
     let decrypted = envelope
         .decrypt_for_owner_mlkem(&kp.sk_b64)
@@ -80,8 +85,8 @@ fn legacy_lowercased_solana_wrapped_keys_still_decrypt() {
         Some("text/plain".to_string()),
     );
 
-    let (mut envelope, _) = DocumentEnvelopeV1::create_mlkem_owner(
-        wallet.to_string(),
+    let (mut envelope, _) = DocumentEnvelopeV2::create_mlkem_owner(
+        wallet,
         &kp.pk_b64,
         1_715_218_401,
         doc,
@@ -89,7 +94,7 @@ fn legacy_lowercased_solana_wrapped_keys_still_decrypt() {
     )
     .expect("create envelope");
 
-    envelope.encryption.wrapped_keys[0].recipient = wallet.to_ascii_lowercase();
+    envelope.header.encryption.wrapped_keys[0].recipient = wallet.to_ascii_lowercase();
 
     let decrypted = envelope
         .decrypt_for_wallet_mlkem(wallet, &kp.sk_b64)
@@ -97,3 +102,149 @@ fn legacy_lowercased_solana_wrapped_keys_still_decrypt() {
 
     assert_eq!(decrypted, plaintext);
 }
+
+#[test]
+fn added_recipient_decrypts_without_payload_reencryption() {
+    let owner = mlkem_generate_keypair_b64();
+    let recipient = mlkem_generate_keypair_b64();
+    let plaintext = b"shared contract";
+    let doc = CanonicalDocumentV1::from_plaintext(
+        "logical-3".to_string(),
+        plaintext,
+        Some("contract.txt".to_string()),
+        Some("text/plain".to_string()),
+    );
+
+    let (mut envelope, _) = DocumentEnvelopeV2::create_mlkem_owner(
+        "0xowner",
+        &owner.pk_b64,
+        1_715_218_402,
+        doc,
+        plaintext,
+    )
+    .expect("create envelope");
+    let original_ciphertext = envelope.ciphertext.clone();
+
+    envelope
+        .add_recipient_mlkem("0xowner", &owner.sk_b64, "0xRecipient", &recipient.pk_b64)
+        .expect("add recipient");
+
+    assert_eq!(envelope.ciphertext, original_ciphertext);
+    assert!(envelope.has_recipient("0xrecipient"));
+    assert_eq!(
+        envelope
+            .decrypt_for_wallet_mlkem("0xrecipient", &recipient.sk_b64)
+            .expect("recipient decrypt"),
+        plaintext
+    );
+
+    let (rekeyed, _) = envelope
+        .rekey_mlkem(
+            "0xowner",
+            &owner.sk_b64,
+            vec![("0xowner".to_string(), owner.pk_b64.clone())],
+        )
+        .expect("rekey");
+
+    assert_ne!(rekeyed.ciphertext, original_ciphertext);
+    assert!(!rekeyed.has_recipient("0xrecipient"));
+    assert!(rekeyed
+        .decrypt_for_wallet_mlkem("0xrecipient", &recipient.sk_b64)
+        .is_err());
+    assert_eq!(
+        rekeyed.decrypt_for_owner_mlkem(&owner.sk_b64).expect("owner decrypt"),
+        plaintext
+    );
+}
+
+#[test]
+fn legacy_v1_envelopes_read_and_upgrade_to_binary_v2() {
+    let kp = mlkem_generate_keypair_b64();
+    let plaintext = vec![7u8; 4096];
+    let doc = CanonicalDocumentV1::from_plaintext(
+        "logical-4".to_string(),
+        &plaintext,
+        Some("legacy.txt".to_string()),
+        Some("text/plain".to_string()),
+    );
+    let (encryption, ciphertext) =
+        EncryptionInfoV1::seal_mlkem(vec![("0xowner".to_string(), kp.pk_b64.clone())], &plaintext)
+            .expect("seal");
+    let v1_bytes = canonical_json(&DocumentEnvelopeV1 {
+        v: 1,
+        owner: "0xowner".to_string(),
+        created_at: 1_715_218_403,
+        doc,
+        encryption,
+        ciphertext_b64: URL_SAFE_NO_PAD.encode(&ciphertext),
+    });
+
+    let upgraded = read_document_envelope(&v1_bytes).expect("read v1");
+    assert_eq!(envelope_content_type(&v1_bytes), ENVELOPE_V1_CONTENT_TYPE);
+    assert_eq!(upgraded.ciphertext, ciphertext);
+    assert_eq!(
+        upgraded.decrypt_for_owner_mlkem(&kp.sk_b64).expect("decrypt v1"),
+        plaintext
+    );
+
+    let v2_bytes = upgraded.encode();
+    assert_eq!(envelope_content_type(&v2_bytes), ENVELOPE_V2_CONTENT_TYPE);
+    assert!(v2_bytes.len() < v1_bytes.len());
+    let reread = read_document_envelope(&v2_bytes).expect("read v2");
+    assert_eq!(reread.id(), upgraded.id());
+    assert_eq!(
+        reread.decrypt_for_owner_mlkem(&kp.sk_b64).expect("decrypt v2"),
+        plaintext
+    );
+
+    let mut tampered = v2_bytes.clone();
+    *tampered.last_mut().unwrap() ^= 1;
+    assert!(read_document_envelope(&tampered).is_err());
+    assert!(read_document_envelope(&v2_bytes[..v2_bytes.len() - 1]).is_err());
+}
+
+#[test]
+fn stream_segments_roundtrip_and_reject_reordering() {
+    let owner = mlkem_generate_keypair_b64();
+    let key = StreamKeyV1::new_mlkem("0xOwner", &owner.pk_b64).expect("stream key");
+    let cipher = key.unlock(&owner.sk_b64).expect("unlock");
+
+    let plaintext = vec![7u8; STREAM_SEGMENT_SIZE + 10];
+    assert_eq!(segment_count(plaintext.len() as u64, STREAM_SEGMENT_SIZE), 2);
+    let segments: Vec<_> = plaintext
+        .chunks(STREAM_SEGMENT_SIZE)
+        .enumerate()
+        .map(|(i, chunk)| {
+            cipher
+                .encrypt_segment(i as u64, i == 1, chunk)
+                .expect("encrypt segment")
+        })
+        .collect();
+
+    let doc = CanonicalDocumentV1::from_plaintext(
+        "doc-stream".into(),
+        &plaintext,
+        Some("disk.img".into()),
+        Some("application/octet-stream".into()),
+    );
+    let header = DocumentStreamEnvelopeV1::new("0xOwner", 1_715_218_402, doc, key, "00".into());
+    assert_eq!(header.owner, "0xowner");
+    assert_eq!(header.encryption.segment_count, 2);
+    assert!(header.is_last_segment(1));
+
+    let reader = header
+        .cipher_for_wallet("0xowner", &owner.sk_b64)
+        .expect("owner cipher");
+    let mut decrypted = reader.decrypt_segment(0, false, &segments[0]).unwrap();
+    decrypted.extend(reader.decrypt_segment(1, true, &segments[1]).unwrap());
+    assert_eq!(decrypted, plaintext);
+
+    assert!(reader.decrypt_segment(1, true, &segments[0]).is_err());
+    assert!(reader.decrypt_segment(0, true, &segments[0]).is_err());
+    assert!(reader.decrypt_segment(1, false, &segments[1]).is_err());
+
+    let stranger = mlkem_generate_keypair_b64();
+    assert!(header
+        .cipher_for_wallet("0xowner", &stranger.sk_b64)
+        .is_err());
+}
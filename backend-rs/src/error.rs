@@ -23,6 +23,9 @@ pub enum AppError {
     #[error("not found: {0}")]
     NotFound(String), // ✅ ADD THIS
 
+    #[error("conflict: {0}")]
+    Conflict(String),
+
     #[error("internal error: {0}")]
     Internal(String),
 
@@ -51,6 +54,7 @@ impl IntoResponse for AppError {
             AppError::Forbidden(_) => StatusCode::FORBIDDEN,
             AppError::Auth(_) => StatusCode::UNAUTHORIZED,
             AppError::NotFound(_) => StatusCode::NOT_FOUND, // ✅ MAP TO 404
+            AppError::Conflict(_) => StatusCode::CONFLICT,
 
             // Crypto errors are internal failures, not user mistakes
             AppError::Crypto(_) => StatusCode::INTERNAL_SERVER_ERROR,
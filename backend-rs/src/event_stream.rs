@@ -0,0 +1,73 @@
+// src/event_stream.rs
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+
+use crate::c2c::ledger::LedgerEvent;
+use crate::error::AppError;
+use crate::sqlx::{self, PgListener, PgPool};
+
+/// Postgres channel every server instance listens on for new custody events.
+pub const EVENT_CHANNEL: &str = "document_events";
+
+/// How many notices a slow subscriber may fall behind before it is told to
+/// resync instead.
+const BROADCAST_CAPACITY: usize = 1024;
+
+/// What goes over NOTIFY. Payloads stay out of it (NOTIFY caps at 8000
+/// bytes); streams load the event row once access is checked.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventNotice {
+    pub event_id: uuid::Uuid,
+    pub doc_id: uuid::Uuid,
+    pub event_type: String,
+}
+
+pub type EventSender = broadcast::Sender<EventNotice>;
+
+pub async fn notify_document_event(db: &PgPool, ev: &LedgerEvent) -> Result<(), AppError> {
+    let notice = serde_json::to_string(&EventNotice {
+        event_id: ev.id,
+        doc_id: ev.doc_id,
+        event_type: ev.event_type.clone(),
+    })
+    .map_err(|e| AppError::Internal(e.to_string()))?;
+    sqlx::query("select pg_notify($1, $2)")
+        .bind(EVENT_CHANNEL)
+        .bind(notice)
+        .execute(db)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    Ok(())
+}
+
+/// Relay NOTIFY messages from Postgres to in-process subscribers. Events
+/// written by any instance reach every instance's streams this way.
+pub fn spawn_event_listener(db: PgPool) -> EventSender {
+    let (sender, _) = broadcast::channel(BROADCAST_CAPACITY);
+    let relay = sender.clone();
+    tokio::spawn(async move {
+        loop {
+            if let Err(err) = relay_notifications(&db, &relay).await {
+                eprintln!("events: listener stopped: {err}");
+            }
+            tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+        }
+    });
+    sender
+}
+
+async fn relay_notifications(db: &PgPool, relay: &EventSender) -> Result<(), sqlx::Error> {
+    let mut listener = PgListener::connect_with(db).await?;
+    listener.listen(EVENT_CHANNEL).await?;
+    loop {
+        let notification = listener.recv().await?;
+        match serde_json::from_str::<EventNotice>(notification.payload()) {
+            // No receivers just means no open streams on this instance.
+            Ok(notice) => {
+                let _ = relay.send(notice);
+            }
+            Err(err) => eprintln!("events: ignoring malformed notice: {err}"),
+        }
+    }
+}
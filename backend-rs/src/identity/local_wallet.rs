@@ -1,14 +1,84 @@
 //src/identity/local_wallet.rs
 
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
+use base64::Engine;
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    XChaCha20Poly1305, XNonce,
+};
+use rand::RngCore;
 use serde::{Deserialize, Serialize};
 use std::{fs, path::PathBuf};
+use zeroize::Zeroizing;
 
 use crate::error::{AppError, AppResult};
+use crate::pqc::{dilithium, sha3 as pqc_sha3};
 
+/// Signature scheme of the CLI device key.
+pub const DEVICE_KEY_ALG: &str = "mldsa65";
+
+/// Prefix for actor ids derived from a device public key.
+pub const DEVICE_ID_PREFIX: &str = "pqdev:";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalletKdf {
+    pub alg: String,
+    pub salt_b64: String,
+    pub m_cost_kib: u32,
+    pub t_cost: u32,
+    pub p_cost: u32,
+}
+
+/// On-disk CLI wallet: an ML-DSA-65 device keypair whose secret key is
+/// sealed with XChaCha20-Poly1305 under an Argon2id passphrase key.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LocalWallet {
-    /// Logical identifier for now (later: PQC keys, EVM address, etc.)
+    /// Device id, `pqdev:` + the public key fingerprint.
     pub id: String,
+    pub alg: String,
+    pub public_key_b64: String,
+    pub kdf: WalletKdf,
+    pub cipher: String,
+    pub nonce_b64: String,
+    pub encrypted_secret_key_b64: String,
+    pub created_at: i64,
+}
+
+/// An unlocked device key, ready to sign.
+pub struct DeviceKey {
+    pub device_id: String,
+    pub public_key_b64: String,
+    secret_key: Zeroizing<Vec<u8>>,
+}
+
+impl DeviceKey {
+    pub fn sign(&self, message: &[u8]) -> AppResult<Vec<u8>> {
+        dilithium::sign(&self.secret_key, message)
+    }
+}
+
+/// `pqdev:` + the first 20 bytes of SHA3-256(public key), hex encoded.
+pub fn device_id_for(public_key: &[u8]) -> String {
+    let digest = pqc_sha3::sha3_256_bytes(public_key);
+    format!("{DEVICE_ID_PREFIX}{}", hex::encode(&digest[..20]))
+}
+
+fn derive_key(passphrase: &str, kdf: &WalletKdf) -> AppResult<Zeroizing<[u8; 32]>> {
+    if kdf.alg != "argon2id" {
+        return Err(AppError::Crypto(format!("Unsupported wallet KDF: {}", kdf.alg)));
+    }
+    let salt = BASE64_STANDARD
+        .decode(&kdf.salt_b64)
+        .map_err(|_| AppError::Crypto("Invalid wallet KDF salt".into()))?;
+    let params = Params::new(kdf.m_cost_kib, kdf.t_cost, kdf.p_cost, Some(32))
+        .map_err(|e| AppError::Crypto(format!("Invalid wallet KDF params: {e}")))?;
+
+    let mut key = Zeroizing::new([0u8; 32]);
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), &salt, key.as_mut())
+        .map_err(|e| AppError::Crypto(format!("Wallet key derivation failed: {e}")))?;
+    Ok(key)
 }
 
 impl LocalWallet {
@@ -19,12 +89,60 @@ impl LocalWallet {
         dir
     }
 
-    /// Create a new wallet and persist it. Passphrase is ignored *for now*.
-    pub fn generate(_passphrase: &str) -> AppResult<Self> {
-        let wallet = LocalWallet {
-            id: "local-dev-wallet".to_string(),
+    pub fn exists() -> bool {
+        Self::path().exists()
+    }
+
+    /// Generate a device keypair and seal its secret key under `passphrase`.
+    pub fn create(passphrase: &str) -> AppResult<Self> {
+        if passphrase.is_empty() {
+            return Err(AppError::BadRequest("Wallet passphrase must not be empty".into()));
+        }
+
+        let keypair = dilithium::generate_keypair();
+        let secret_key = Zeroizing::new(keypair.secret_key);
+        let id = device_id_for(&keypair.public_key);
+
+        let mut salt = [0u8; 16];
+        let mut nonce = [0u8; 24];
+        rand::thread_rng().fill_bytes(&mut salt);
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let defaults = Params::default();
+        let kdf = WalletKdf {
+            alg: "argon2id".into(),
+            salt_b64: BASE64_STANDARD.encode(salt),
+            m_cost_kib: defaults.m_cost(),
+            t_cost: defaults.t_cost(),
+            p_cost: defaults.p_cost(),
         };
 
+        let key = derive_key(passphrase, &kdf)?;
+        let ciphertext = XChaCha20Poly1305::new(key.as_ref().into())
+            .encrypt(
+                XNonce::from_slice(&nonce),
+                Payload {
+                    msg: &secret_key,
+                    aad: id.as_bytes(),
+                },
+            )
+            .map_err(|_| AppError::Crypto("Wallet key encryption failed".into()))?;
+
+        Ok(LocalWallet {
+            id,
+            alg: DEVICE_KEY_ALG.into(),
+            public_key_b64: BASE64_STANDARD.encode(&keypair.public_key),
+            kdf,
+            cipher: "xchacha20poly1305".into(),
+            nonce_b64: BASE64_STANDARD.encode(nonce),
+            encrypted_secret_key_b64: BASE64_STANDARD.encode(ciphertext),
+            created_at: chrono::Utc::now().timestamp(),
+        })
+    }
+
+    /// Create a wallet and persist it to `~/.tidbit/wallet.json`.
+    pub fn generate(passphrase: &str) -> AppResult<Self> {
+        let wallet = Self::create(passphrase)?;
+
         if let Some(parent) = Self::path().parent() {
             fs::create_dir_all(parent)?;
         }
@@ -36,12 +154,77 @@ impl LocalWallet {
     }
 
     pub fn load() -> AppResult<Self> {
-        let data = fs::read_to_string(Self::path()).map_err(|e| AppError::Io(e))?;
-        let w = serde_json::from_str(&data)?;
-        Ok(w)
+        let data = fs::read_to_string(Self::path()).map_err(AppError::Io)?;
+        serde_json::from_str(&data).map_err(|_| {
+            AppError::BadRequest(
+                "~/.tidbit/wallet.json has no device key; run `tidbit wallet init --force`".into(),
+            )
+        })
+    }
+
+    /// Decrypt the device secret key.
+    pub fn unlock(&self, passphrase: &str) -> AppResult<DeviceKey> {
+        if self.alg != DEVICE_KEY_ALG || self.cipher != "xchacha20poly1305" {
+            return Err(AppError::Crypto(format!(
+                "Unsupported wallet format: {} / {}",
+                self.alg, self.cipher
+            )));
+        }
+
+        let nonce = BASE64_STANDARD
+            .decode(&self.nonce_b64)
+            .ok()
+            .filter(|nonce| nonce.len() == 24)
+            .ok_or_else(|| AppError::Crypto("Invalid wallet nonce".into()))?;
+        let ciphertext = BASE64_STANDARD
+            .decode(&self.encrypted_secret_key_b64)
+            .map_err(|_| AppError::Crypto("Invalid wallet ciphertext".into()))?;
+
+        let key = derive_key(passphrase, &self.kdf)?;
+        let secret_key = XChaCha20Poly1305::new(key.as_ref().into())
+            .decrypt(
+                XNonce::from_slice(&nonce),
+                Payload {
+                    msg: &ciphertext,
+                    aad: self.id.as_bytes(),
+                },
+            )
+            .map_err(|_| AppError::Auth("Wrong wallet passphrase".into()))?;
+
+        Ok(DeviceKey {
+            device_id: self.id.clone(),
+            public_key_b64: self.public_key_b64.clone(),
+            secret_key: Zeroizing::new(secret_key),
+        })
     }
 
     pub fn actor_id(&self) -> String {
         self.id.clone()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
+    use base64::Engine;
+
+    use super::{device_id_for, LocalWallet};
+    use crate::pqc::dilithium;
+
+    #[test]
+    fn sealed_device_key_unlocks_and_signs() {
+        let wallet = LocalWallet::create("correct horse").unwrap();
+        let public_key = BASE64_STANDARD.decode(&wallet.public_key_b64).unwrap();
+        assert_eq!(wallet.id, device_id_for(&public_key));
+
+        assert!(wallet.unlock("wrong horse").is_err());
+
+        let key = wallet.unlock("correct horse").unwrap();
+        let signature = key.sign(b"hello").unwrap();
+        assert!(dilithium::verify(&public_key, b"hello", &signature).unwrap());
+
+        let mut moved = wallet.clone();
+        moved.id = device_id_for(b"another key");
+        assert!(moved.unlock("correct horse").is_err());
+    }
+}
@@ -1,2 +1,4 @@
 pub mod local_wallet;
+pub mod proof_of_key;
+pub mod registry;
 pub mod wallet_verify;
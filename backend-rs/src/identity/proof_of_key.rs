@@ -0,0 +1,196 @@
+// src/identity/proof_of_key.rs
+
+use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use fips204::ml_dsa_65;
+use sha2::{Digest, Sha256};
+
+use crate::error::AppError;
+use crate::identity_web::evm::verify_evm_signature;
+use crate::identity_web::sol::verify_solana_signature;
+use crate::pqc::dilithium;
+
+pub const PQ_KEY_ALGORITHM: &str = "ml-dsa-65";
+
+/// A binding must be registered within this many seconds of `issued_at`.
+pub const BINDING_MAX_AGE_SECS: i64 = 600;
+
+pub fn pq_key_fingerprint_hex(public_key: &[u8]) -> String {
+    hex::encode(Sha256::digest(public_key))
+}
+
+/// The text both the wallet and the ML-DSA key sign to bind one to the other.
+pub fn key_binding_message(
+    wallet: &str,
+    chain: &str,
+    fingerprint_hex: &str,
+    issued_at: &str,
+) -> String {
+    format!(
+        "TIDBIT ML-DSA Key Binding\n\
+Wallet: {wallet}\n\
+Chain: {chain}\n\
+Algorithm: ML-DSA-65\n\
+Key Fingerprint: {fingerprint_hex}\n\
+Issued At: {issued_at}\n\
+Version: 1"
+    )
+}
+
+/// A registration request: the wallet's signature proves the account wants
+/// the key, the ML-DSA signature proves the caller holds its secret half.
+pub struct KeyBindingProof<'a> {
+    pub wallet: &'a str,
+    pub chain: &'a str,
+    pub public_key_b64: &'a str,
+    pub issued_at: &'a str,
+    pub wallet_signature: &'a str,
+    pub pq_signature_b64: &'a str,
+}
+
+#[derive(Debug)]
+pub struct VerifiedKeyBinding {
+    pub public_key_b64: String,
+    pub fingerprint_hex: String,
+    pub message: String,
+}
+
+pub fn decode_pq_public_key(public_key_b64: &str) -> Result<Vec<u8>, AppError> {
+    let public_key = BASE64_STANDARD
+        .decode(public_key_b64.trim())
+        .map_err(|_| AppError::BadRequest("Invalid pq_public_key_b64".into()))?;
+    if public_key.len() != ml_dsa_65::PK_LEN {
+        return Err(AppError::BadRequest(
+            "pq_public_key_b64 is not an ML-DSA-65 public key".into(),
+        ));
+    }
+    Ok(public_key)
+}
+
+pub fn verify_wallet_signature(
+    chain: &str,
+    wallet: &str,
+    message: &str,
+    signature: &str,
+) -> Result<(), AppError> {
+    match chain {
+        "evm" => {
+            let recovered = verify_evm_signature(message, signature)
+                .map_err(|_| AppError::BadRequest("Invalid EVM signature".into()))?;
+            if !recovered.eq_ignore_ascii_case(wallet) {
+                return Err(AppError::Forbidden(
+                    "Wallet signature does not match the active wallet".into(),
+                ));
+            }
+            Ok(())
+        }
+        "sol" => verify_solana_signature(message, wallet, signature),
+        _ => Err(AppError::BadRequest("Unsupported wallet chain".into())),
+    }
+}
+
+pub fn verify_key_binding(
+    proof: &KeyBindingProof<'_>,
+    now: DateTime<Utc>,
+) -> Result<VerifiedKeyBinding, AppError> {
+    let issued_at = DateTime::parse_from_rfc3339(proof.issued_at)
+        .map_err(|_| AppError::BadRequest("issued_at must be an RFC 3339 timestamp".into()))?
+        .with_timezone(&Utc);
+    let age = now.signed_duration_since(issued_at).num_seconds();
+    if !(-60..=BINDING_MAX_AGE_SECS).contains(&age) {
+        return Err(AppError::BadRequest(
+            "Key binding has expired; request a new one".into(),
+        ));
+    }
+
+    let public_key = decode_pq_public_key(proof.public_key_b64)?;
+    let fingerprint_hex = pq_key_fingerprint_hex(&public_key);
+    let message = key_binding_message(proof.wallet, proof.chain, &fingerprint_hex, proof.issued_at);
+
+    verify_wallet_signature(proof.chain, proof.wallet, &message, proof.wallet_signature)?;
+
+    let pq_signature = BASE64_STANDARD
+        .decode(proof.pq_signature_b64.trim())
+        .map_err(|_| AppError::BadRequest("Invalid pq_signature_b64".into()))?;
+    let possessed = dilithium::verify(&public_key, message.as_bytes(), &pq_signature)
+        .map_err(|_| AppError::BadRequest("Invalid ML-DSA proof of possession".into()))?;
+    if !possessed {
+        return Err(AppError::Forbidden(
+            "ML-DSA proof of possession failed".into(),
+        ));
+    }
+
+    Ok(VerifiedKeyBinding {
+        public_key_b64: BASE64_STANDARD.encode(&public_key),
+        fingerprint_hex,
+        message,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    #[test]
+    fn binding_needs_wallet_and_pq_signatures() {
+        let wallet_key = SigningKey::from_bytes(&[7u8; 32]);
+        let wallet = bs58::encode(wallet_key.verifying_key().to_bytes()).into_string();
+        let pq = dilithium::generate_keypair();
+        let public_key_b64 = BASE64_STANDARD.encode(&pq.public_key);
+        let now = Utc::now();
+        let issued_at = now.to_rfc3339();
+
+        let message = key_binding_message(
+            &wallet,
+            "sol",
+            &pq_key_fingerprint_hex(&pq.public_key),
+            &issued_at,
+        );
+        let wallet_signature =
+            BASE64_STANDARD.encode(wallet_key.sign(message.as_bytes()).to_bytes());
+        let pq_signature_b64 =
+            BASE64_STANDARD.encode(dilithium::sign(&pq.secret_key, message.as_bytes()).unwrap());
+        let proof = KeyBindingProof {
+            wallet: &wallet,
+            chain: "sol",
+            public_key_b64: &public_key_b64,
+            issued_at: &issued_at,
+            wallet_signature: &wallet_signature,
+            pq_signature_b64: &pq_signature_b64,
+        };
+
+        let verified = verify_key_binding(&proof, now).unwrap();
+        assert_eq!(
+            verified.fingerprint_hex,
+            pq_key_fingerprint_hex(&pq.public_key)
+        );
+        assert!(verify_key_binding(&proof, now + chrono::Duration::hours(1)).is_err());
+
+        // The wallet can sign for someone else's public key, but without that
+        // key's secret half the proof of possession fails.
+        let other = dilithium::generate_keypair();
+        let other_b64 = BASE64_STANDARD.encode(&other.public_key);
+        let message = key_binding_message(
+            &wallet,
+            "sol",
+            &pq_key_fingerprint_hex(&other.public_key),
+            &issued_at,
+        );
+        let wallet_signature =
+            BASE64_STANDARD.encode(wallet_key.sign(message.as_bytes()).to_bytes());
+        let pq_signature_b64 =
+            BASE64_STANDARD.encode(dilithium::sign(&pq.secret_key, message.as_bytes()).unwrap());
+        let borrowed = KeyBindingProof {
+            public_key_b64: &other_b64,
+            wallet_signature: &wallet_signature,
+            pq_signature_b64: &pq_signature_b64,
+            ..proof
+        };
+        assert!(matches!(
+            verify_key_binding(&borrowed, now),
+            Err(AppError::Forbidden(_))
+        ));
+    }
+}
@@ -0,0 +1,215 @@
+// src/identity/registry.rs
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::error::AppError;
+use crate::identity::proof_of_key::{VerifiedKeyBinding, PQ_KEY_ALGORITHM};
+use crate::sqlx::{self, PgPool, PgRow, Row};
+
+/// One ML-DSA key bound to a wallet. Keys are never deleted: a rotated key
+/// points at its successor and a revoked key keeps its reason, so evidence
+/// exports can show which key was live when a signature was made.
+#[derive(Debug, Clone, Serialize)]
+pub struct PqSigningKey {
+    pub id: uuid::Uuid,
+    pub wallet: String,
+    pub chain: String,
+    pub algorithm: String,
+    pub public_key_b64: String,
+    pub fingerprint_hex: String,
+    pub status: String,
+    pub binding_message: String,
+    pub wallet_signature: String,
+    pub pq_signature_b64: String,
+    pub registered_at: DateTime<Utc>,
+    pub retired_at: Option<DateTime<Utc>>,
+    pub retired_reason: Option<String>,
+    pub replaced_by: Option<uuid::Uuid>,
+}
+
+const KEY_COLUMNS: &str = "id, wallet, chain, algorithm, public_key_b64, fingerprint_hex, status, \
+     binding_message, wallet_signature, pq_signature_b64, registered_at, retired_at, \
+     retired_reason, replaced_by";
+
+impl PqSigningKey {
+    fn from_row(row: &PgRow) -> Self {
+        Self {
+            id: row.get("id"),
+            wallet: row.get("wallet"),
+            chain: row.get("chain"),
+            algorithm: row.get("algorithm"),
+            public_key_b64: row.get("public_key_b64"),
+            fingerprint_hex: row.get("fingerprint_hex"),
+            status: row.get("status"),
+            binding_message: row.get("binding_message"),
+            wallet_signature: row.get("wallet_signature"),
+            pq_signature_b64: row.get("pq_signature_b64"),
+            registered_at: row.get("registered_at"),
+            retired_at: row.get("retired_at"),
+            retired_reason: row.get("retired_reason"),
+            replaced_by: row.get("replaced_by"),
+        }
+    }
+}
+
+fn db_error(e: sqlx::Error) -> AppError {
+    AppError::Internal(e.to_string())
+}
+
+pub async fn active_pq_key(
+    db: &PgPool,
+    wallet: &str,
+    chain: &str,
+) -> Result<Option<PqSigningKey>, AppError> {
+    let row = sqlx::query(&format!(
+        "select {KEY_COLUMNS} from pq_signing_keys where wallet = $1 and chain = $2 and status = 'active'"
+    ))
+    .bind(wallet)
+    .bind(chain)
+    .fetch_optional(db)
+    .await
+    .map_err(db_error)?;
+    Ok(row.as_ref().map(PqSigningKey::from_row))
+}
+
+/// Every key the wallet has bound, oldest first.
+pub async fn pq_key_history(
+    db: &PgPool,
+    wallet: &str,
+    chain: &str,
+) -> Result<Vec<PqSigningKey>, AppError> {
+    let rows = sqlx::query(&format!(
+        "select {KEY_COLUMNS} from pq_signing_keys where wallet = $1 and chain = $2 order by registered_at asc"
+    ))
+    .bind(wallet)
+    .bind(chain)
+    .fetch_all(db)
+    .await
+    .map_err(db_error)?;
+    Ok(rows.iter().map(PqSigningKey::from_row).collect())
+}
+
+/// Bind a verified key to the wallet, rotating out the current key if there
+/// is one. A fingerprint can only ever be registered once, so a replayed
+/// binding cannot bring back a retired key.
+pub async fn register_pq_key(
+    db: &PgPool,
+    wallet: &str,
+    chain: &str,
+    binding: &VerifiedKeyBinding,
+    wallet_signature: &str,
+    pq_signature_b64: &str,
+) -> Result<(PqSigningKey, Option<PqSigningKey>), AppError> {
+    let existing = sqlx::query("select 1 from pq_signing_keys where fingerprint_hex = $1")
+        .bind(&binding.fingerprint_hex)
+        .fetch_optional(db)
+        .await
+        .map_err(db_error)?;
+    if existing.is_some() {
+        return Err(AppError::BadRequest(
+            "This ML-DSA key has already been registered".into(),
+        ));
+    }
+
+    let id = uuid::Uuid::new_v4();
+    let mut tx = db.begin().await.map_err(db_error)?;
+    sqlx::query(
+        r#"
+        insert into pq_signing_keys
+            (id, wallet, chain, algorithm, public_key_b64, fingerprint_hex, status,
+             binding_message, wallet_signature, pq_signature_b64)
+        values ($1, $2, $3, $4, $5, $6, 'pending', $7, $8, $9)
+        "#,
+    )
+    .bind(id)
+    .bind(wallet)
+    .bind(chain)
+    .bind(PQ_KEY_ALGORITHM)
+    .bind(&binding.public_key_b64)
+    .bind(&binding.fingerprint_hex)
+    .bind(&binding.message)
+    .bind(wallet_signature)
+    .bind(pq_signature_b64)
+    .execute(&mut *tx)
+    .await
+    .map_err(db_error)?;
+
+    let rotated = sqlx::query(&format!(
+        r#"
+        update pq_signing_keys
+        set status = 'rotated', retired_at = now(), retired_reason = 'rotated', replaced_by = $3
+        where wallet = $1 and chain = $2 and status = 'active'
+        returning {KEY_COLUMNS}
+        "#
+    ))
+    .bind(wallet)
+    .bind(chain)
+    .bind(id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(db_error)?;
+
+    let key = sqlx::query(&format!(
+        "update pq_signing_keys set status = 'active' where id = $1 returning {KEY_COLUMNS}"
+    ))
+    .bind(id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+
+    Ok((
+        PqSigningKey::from_row(&key),
+        rotated.as_ref().map(PqSigningKey::from_row),
+    ))
+}
+
+pub async fn revoke_pq_key(
+    db: &PgPool,
+    wallet: &str,
+    chain: &str,
+    reason: &str,
+) -> Result<Option<PqSigningKey>, AppError> {
+    let row = sqlx::query(&format!(
+        r#"
+        update pq_signing_keys
+        set status = 'revoked', retired_at = now(), retired_reason = $3
+        where wallet = $1 and chain = $2 and status = 'active'
+        returning {KEY_COLUMNS}
+        "#
+    ))
+    .bind(wallet)
+    .bind(chain)
+    .bind(reason)
+    .fetch_optional(db)
+    .await
+    .map_err(db_error)?;
+    Ok(row.as_ref().map(PqSigningKey::from_row))
+}
+
+/// Full history of every wallet whose registered key signed the document.
+pub async fn pq_key_history_for_document(
+    db: &PgPool,
+    doc_id: uuid::Uuid,
+) -> Result<Vec<PqSigningKey>, AppError> {
+    let rows = sqlx::query(&format!(
+        r#"
+        select {KEY_COLUMNS}
+        from pq_signing_keys
+        where (wallet, chain) in (
+            select k.wallet, k.chain
+            from pq_signing_keys k
+            join document_events e
+              on e.payload -> 'verification' ->> 'pq_key_fingerprint_hex' = k.fingerprint_hex
+            where e.doc_id = $1
+        )
+        order by wallet, registered_at asc
+        "#
+    ))
+    .bind(doc_id)
+    .fetch_all(db)
+    .await
+    .map_err(db_error)?;
+    Ok(rows.iter().map(PqSigningKey::from_row).collect())
+}
@@ -0,0 +1,510 @@
+// src/identity_web/devices.rs
+
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+
+use crate::error::AppError;
+use crate::sqlx::{self, PgPool, Row};
+
+/// Upper bound for a wallet's `max_devices` setting.
+pub const MAX_DEVICE_LIMIT: u32 = 50;
+
+/// Revoke reason for an older session on the same device, or on any device
+/// under the single-session policy.
+pub const REASON_SUPERSEDED: &str = "superseded_by_new_login";
+/// Revoke reason for sessions pushed out by a max-devices limit.
+pub const REASON_DEVICE_LIMIT: &str = "device_limit_exceeded";
+
+pub const ACCOUNT_EVENT_NEW_DEVICE_LOGIN: &str = "NEW_DEVICE_LOGIN";
+pub const ACCOUNT_EVENT_SESSION_POLICY_CHANGED: &str = "SESSION_POLICY_CHANGED";
+pub const ACCOUNT_EVENT_DEVICE_TRUSTED: &str = "DEVICE_TRUSTED";
+pub const ACCOUNT_EVENT_DEVICE_UNTRUSTED: &str = "DEVICE_UNTRUSTED";
+pub const ACCOUNT_EVENT_SESSION_REUSE_DETECTED: &str = "SESSION_REUSE_DETECTED";
+
+fn db_error(e: sqlx::Error) -> AppError {
+    AppError::Internal(e.to_string())
+}
+
+/// How many devices a wallet may stay signed in on at once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionPolicy {
+    /// A new login revokes every other session.
+    Single,
+    /// Up to N devices; the least recently used untrusted device goes first.
+    MaxDevices(u32),
+    Unlimited,
+}
+
+impl SessionPolicy {
+    pub fn parse(mode: &str, max_devices: Option<i64>) -> Result<Self, AppError> {
+        match mode.trim().to_ascii_lowercase().as_str() {
+            "single" => Ok(SessionPolicy::Single),
+            "unlimited" => Ok(SessionPolicy::Unlimited),
+            "max_devices" => {
+                let limit = max_devices.ok_or_else(|| {
+                    AppError::BadRequest("max_devices is required for this policy".into())
+                })?;
+                if !(1..=MAX_DEVICE_LIMIT as i64).contains(&limit) {
+                    return Err(AppError::BadRequest(format!(
+                        "max_devices must be between 1 and {MAX_DEVICE_LIMIT}"
+                    )));
+                }
+                Ok(SessionPolicy::MaxDevices(limit as u32))
+            }
+            _ => Err(AppError::BadRequest(
+                "Session policy must be single, max_devices or unlimited".into(),
+            )),
+        }
+    }
+
+    /// `SESSION_POLICY` (and `SESSION_MAX_DEVICES` for `max_devices`) sets
+    /// the policy for wallets that have not chosen one. Single-session is
+    /// the default.
+    pub fn default_from_env() -> Self {
+        let mode = std::env::var("SESSION_POLICY").unwrap_or_default();
+        let max_devices = std::env::var("SESSION_MAX_DEVICES")
+            .ok()
+            .and_then(|value| value.trim().parse().ok());
+        SessionPolicy::parse(&mode, max_devices).unwrap_or(SessionPolicy::Single)
+    }
+
+    pub fn mode(self) -> &'static str {
+        match self {
+            SessionPolicy::Single => "single",
+            SessionPolicy::MaxDevices(_) => "max_devices",
+            SessionPolicy::Unlimited => "unlimited",
+        }
+    }
+
+    pub fn max_devices(self) -> Option<u32> {
+        match self {
+            SessionPolicy::Single => Some(1),
+            SessionPolicy::MaxDevices(limit) => Some(limit),
+            SessionPolicy::Unlimited => None,
+        }
+    }
+
+    /// `source` is `wallet` when the wallet chose this policy, `default`
+    /// when it comes from server configuration.
+    pub fn to_json(self, source: &str) -> serde_json::Value {
+        serde_json::json!({
+            "mode": self.mode(),
+            "max_devices": self.max_devices(),
+            "source": source
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ActiveSession {
+    pub session_id: String,
+    pub device_id: Option<String>,
+    pub last_seen_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TrustedDevice {
+    pub device_id: String,
+    pub name: String,
+    pub trusted_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountEvent {
+    pub id: uuid::Uuid,
+    pub event_type: String,
+    pub payload: serde_json::Value,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Which active sessions to revoke, and why, so that `keep_session_id`
+/// fits the policy. Sessions without a device id count as their own device.
+pub fn sessions_to_revoke(
+    policy: SessionPolicy,
+    keep_session_id: &str,
+    active: &[ActiveSession],
+    trusted: &HashSet<String>,
+) -> Vec<(String, &'static str)> {
+    let keep_device = active
+        .iter()
+        .find(|session| session.session_id == keep_session_id)
+        .and_then(|session| session.device_id.clone());
+    let others = active
+        .iter()
+        .filter(|session| session.session_id != keep_session_id);
+
+    if policy == SessionPolicy::Single {
+        return others
+            .map(|session| (session.session_id.clone(), REASON_SUPERSEDED))
+            .collect();
+    }
+
+    let mut revoke = Vec::new();
+    let mut devices: HashMap<&str, Vec<&ActiveSession>> = HashMap::new();
+    for session in others {
+        match session.device_id.as_deref() {
+            Some(device) if Some(device) == keep_device.as_deref() => {
+                revoke.push((session.session_id.clone(), REASON_SUPERSEDED));
+            }
+            Some(device) => devices.entry(device).or_default().push(session),
+            None => devices
+                .entry(session.session_id.as_str())
+                .or_default()
+                .push(session),
+        }
+    }
+
+    if let SessionPolicy::MaxDevices(limit) = policy {
+        // The kept device takes one slot.
+        let allowed = limit.saturating_sub(1) as usize;
+        if devices.len() > allowed {
+            let mut ranked: Vec<_> = devices.into_iter().collect();
+            // Untrusted first, then least recently seen.
+            ranked.sort_by_key(|(device, sessions)| {
+                (
+                    trusted.contains(*device),
+                    sessions.iter().map(|s| s.last_seen_at).max(),
+                )
+            });
+            let excess = ranked.len() - allowed;
+            for (_, sessions) in ranked.into_iter().take(excess) {
+                revoke.extend(
+                    sessions
+                        .into_iter()
+                        .map(|session| (session.session_id.clone(), REASON_DEVICE_LIMIT)),
+                );
+            }
+        }
+    }
+    revoke
+}
+
+pub async fn load_session_policy(
+    db: &PgPool,
+    wallet: &str,
+) -> Result<Option<SessionPolicy>, AppError> {
+    let row =
+        sqlx::query("select mode, max_devices from wallet_session_policies where wallet = $1")
+            .bind(wallet)
+            .fetch_optional(db)
+            .await
+            .map_err(db_error)?;
+    row.map(|row| {
+        SessionPolicy::parse(
+            &row.get::<String, _>("mode"),
+            row.get::<Option<i32>, _>("max_devices").map(i64::from),
+        )
+    })
+    .transpose()
+}
+
+pub async fn save_session_policy(
+    db: &PgPool,
+    wallet: &str,
+    policy: SessionPolicy,
+) -> Result<(), AppError> {
+    sqlx::query(
+        r#"
+        insert into wallet_session_policies (wallet, mode, max_devices, updated_at)
+        values ($1, $2, $3, now())
+        on conflict (wallet) do update set
+            mode = excluded.mode,
+            max_devices = excluded.max_devices,
+            updated_at = excluded.updated_at
+        "#,
+    )
+    .bind(wallet)
+    .bind(policy.mode())
+    .bind(match policy {
+        SessionPolicy::MaxDevices(limit) => Some(limit as i32),
+        _ => None,
+    })
+    .execute(db)
+    .await
+    .map_err(db_error)?;
+    Ok(())
+}
+
+pub async fn list_trusted_devices(
+    db: &PgPool,
+    wallet: &str,
+) -> Result<Vec<TrustedDevice>, AppError> {
+    let rows = sqlx::query(
+        r#"
+        select device_id, name, trusted_at
+        from wallet_trusted_devices
+        where wallet = $1
+        order by trusted_at desc
+        "#,
+    )
+    .bind(wallet)
+    .fetch_all(db)
+    .await
+    .map_err(db_error)?;
+    Ok(rows
+        .into_iter()
+        .map(|row| TrustedDevice {
+            device_id: row.get("device_id"),
+            name: row.get("name"),
+            trusted_at: row.get("trusted_at"),
+        })
+        .collect())
+}
+
+/// Trust a device, or rename one that is already trusted.
+pub async fn trust_device(
+    db: &PgPool,
+    wallet: &str,
+    device_id: &str,
+    name: &str,
+) -> Result<(), AppError> {
+    sqlx::query(
+        r#"
+        insert into wallet_trusted_devices (wallet, device_id, name)
+        values ($1, $2, $3)
+        on conflict (wallet, device_id) do update set name = excluded.name
+        "#,
+    )
+    .bind(wallet)
+    .bind(device_id)
+    .bind(name)
+    .execute(db)
+    .await
+    .map_err(db_error)?;
+    Ok(())
+}
+
+pub async fn untrust_device(db: &PgPool, wallet: &str, device_id: &str) -> Result<bool, AppError> {
+    let result =
+        sqlx::query("delete from wallet_trusted_devices where wallet = $1 and device_id = $2")
+            .bind(wallet)
+            .bind(device_id)
+            .execute(db)
+            .await
+            .map_err(db_error)?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Whether the wallet has trusted this device or signed in from it before,
+/// not counting `current_session_id`.
+pub async fn device_known(
+    db: &PgPool,
+    wallet: &str,
+    device_id: &str,
+    current_session_id: &str,
+) -> Result<bool, AppError> {
+    let row = sqlx::query(
+        r#"
+        select
+            exists (
+                select 1 from wallet_trusted_devices
+                where wallet = $1 and device_id = $2
+            )
+            or exists (
+                select 1 from wallet_sessions
+                where (
+                        ($4 = true and lower(wallet) = lower($1))
+                     or ($4 = false and wallet = $1)
+                      )
+                  and device_id = $2
+                  and session_id <> $3
+            ) as known
+        "#,
+    )
+    .bind(wallet)
+    .bind(device_id)
+    .bind(current_session_id)
+    .bind(wallet.starts_with("0x"))
+    .fetch_one(db)
+    .await
+    .map_err(db_error)?;
+    Ok(row.get("known"))
+}
+
+/// Sessions that can still be used or refreshed. An idle device whose
+/// short access session has lapsed still holds a live refresh token, so it
+/// counts against the policy until that expires too.
+pub async fn load_active_sessions(
+    db: &PgPool,
+    wallet: &str,
+) -> Result<Vec<ActiveSession>, AppError> {
+    let is_evm = wallet.starts_with("0x");
+    let rows = sqlx::query(
+        r#"
+        select session_id, device_id, extract(epoch from last_seen_at)::bigint as last_seen_at
+        from wallet_sessions
+        where (
+                ($2 = true and lower(wallet) = lower($1))
+             or ($2 = false and wallet = $1)
+              )
+          and revoked_at is null
+          and (expires_at > now() or refresh_expires_at > now())
+        "#,
+    )
+    .bind(wallet)
+    .bind(is_evm)
+    .fetch_all(db)
+    .await
+    .map_err(db_error)?;
+    Ok(rows
+        .into_iter()
+        .map(|row| ActiveSession {
+            session_id: row.get("session_id"),
+            device_id: row.get("device_id"),
+            last_seen_at: row.get("last_seen_at"),
+        })
+        .collect())
+}
+
+/// Revoke each session with its reason, pointing it at the session that
+/// displaced it.
+pub async fn revoke_sessions(
+    db: &PgPool,
+    revocations: &[(String, &'static str)],
+    replaced_by_session_id: &str,
+) -> Result<u64, AppError> {
+    let mut revoked = 0;
+    for (session_id, reason) in revocations {
+        revoked += sqlx::query(
+            r#"
+            update wallet_sessions
+            set revoked_at = now(),
+                revoked_reason = $2,
+                replaced_by_session_id = $3
+            where session_id = $1
+              and revoked_at is null
+            "#,
+        )
+        .bind(session_id)
+        .bind(*reason)
+        .bind(replaced_by_session_id)
+        .execute(db)
+        .await
+        .map_err(db_error)?
+        .rows_affected();
+    }
+    Ok(revoked)
+}
+
+pub async fn record_account_event(
+    db: &PgPool,
+    wallet: &str,
+    event_type: &str,
+    payload: serde_json::Value,
+) -> Result<(), AppError> {
+    sqlx::query(
+        "insert into wallet_account_events (id, wallet, event_type, payload) values ($1, $2, $3, $4)",
+    )
+    .bind(uuid::Uuid::new_v4())
+    .bind(wallet)
+    .bind(event_type)
+    .bind(payload)
+    .execute(db)
+    .await
+    .map_err(db_error)?;
+    Ok(())
+}
+
+pub async fn list_account_events(
+    db: &PgPool,
+    wallet: &str,
+    limit: i64,
+) -> Result<Vec<AccountEvent>, AppError> {
+    let rows = sqlx::query(
+        r#"
+        select id, event_type, payload, created_at
+        from wallet_account_events
+        where wallet = $1
+        order by created_at desc
+        limit $2
+        "#,
+    )
+    .bind(wallet)
+    .bind(limit)
+    .fetch_all(db)
+    .await
+    .map_err(db_error)?;
+    Ok(rows
+        .into_iter()
+        .map(|row| AccountEvent {
+            id: row.get("id"),
+            event_type: row.get("event_type"),
+            payload: row.get("payload"),
+            created_at: row.get("created_at"),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(id: &str, device: Option<&str>, last_seen_at: i64) -> ActiveSession {
+        ActiveSession {
+            session_id: id.into(),
+            device_id: device.map(str::to_string),
+            last_seen_at,
+        }
+    }
+
+    fn revoked(revocations: Vec<(String, &'static str)>) -> Vec<(String, &'static str)> {
+        let mut revocations = revocations;
+        revocations.sort();
+        revocations
+    }
+
+    #[test]
+    fn policy_parsing_bounds_the_device_limit() {
+        assert_eq!(
+            SessionPolicy::parse("max_devices", Some(3)).unwrap(),
+            SessionPolicy::MaxDevices(3)
+        );
+        assert_eq!(
+            SessionPolicy::parse("Unlimited", None).unwrap(),
+            SessionPolicy::Unlimited
+        );
+        assert!(SessionPolicy::parse("max_devices", None).is_err());
+        assert!(SessionPolicy::parse("max_devices", Some(0)).is_err());
+        assert!(SessionPolicy::parse("max_devices", Some(51)).is_err());
+        assert!(SessionPolicy::parse("everything", None).is_err());
+    }
+
+    #[test]
+    fn device_limit_evicts_untrusted_and_stale_devices_first() {
+        let active = vec![
+            session("new", Some("laptop"), 100),
+            session("laptop-old", Some("laptop"), 90),
+            session("phone", Some("phone"), 10),
+            session("tablet", Some("tablet"), 50),
+            session("cli", None, 80),
+        ];
+        let trusted = HashSet::from(["phone".to_string()]);
+
+        assert_eq!(
+            revoked(sessions_to_revoke(
+                SessionPolicy::MaxDevices(3),
+                "new",
+                &active,
+                &trusted
+            )),
+            vec![
+                ("laptop-old".to_string(), REASON_SUPERSEDED),
+                ("tablet".to_string(), REASON_DEVICE_LIMIT),
+            ]
+        );
+        assert_eq!(
+            revoked(sessions_to_revoke(
+                SessionPolicy::Unlimited,
+                "new",
+                &active,
+                &trusted
+            )),
+            vec![("laptop-old".to_string(), REASON_SUPERSEDED)]
+        );
+        assert_eq!(
+            sessions_to_revoke(SessionPolicy::Single, "new", &active, &trusted).len(),
+            4
+        );
+    }
+}
@@ -0,0 +1,360 @@
+// src/identity_web/eip1271.rs
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use ethers_core::abi::{encode, Token};
+use ethers_core::types::{Address, H256};
+use ethers_core::utils::hash_message;
+use reqwest::Client;
+use serde::Serialize;
+use tokio::sync::OnceCell;
+
+use crate::error::AppError;
+use crate::identity_web::evm::verify_evm_signature;
+
+/// `isValidSignature(bytes32,bytes)` selector, which is also the value a
+/// contract returns when it accepts the signature.
+pub const EIP1271_MAGIC_VALUE: [u8; 4] = [0x16, 0x26, 0xba, 0x7e];
+
+/// How an EVM wallet signature was accepted. Recorded with the custody
+/// event so a verifier knows whether recovery alone is enough to check it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EvmSignatureMethod {
+    /// The signature recovers to the wallet address.
+    Ecdsa,
+    /// The wallet is a contract and its `isValidSignature` accepted the
+    /// signature at the time of signing.
+    Eip1271,
+}
+
+/// Asks a smart-contract account whether it accepts a signature.
+#[async_trait]
+pub trait ContractSignatureVerifier: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// The chain whose contract state this verifier reads.
+    async fn chain_id(&self) -> Result<u64, AppError>;
+
+    /// `Ok(false)` covers accounts without code and contracts that reject
+    /// or revert. Errors are reserved for an unreachable node.
+    async fn is_valid_signature(
+        &self,
+        account: Address,
+        hash: H256,
+        signature: &[u8],
+    ) -> Result<bool, AppError>;
+}
+
+pub type SharedContractVerifier = Arc<dyn ContractSignatureVerifier>;
+
+/// `EIP1271_RPC_URL` names the node used to call `isValidSignature`.
+/// Without it only ECDSA signatures are accepted.
+pub fn contract_verifier_from_env() -> Option<SharedContractVerifier> {
+    std::env::var("EIP1271_RPC_URL")
+        .ok()
+        .map(|url| url.trim().to_string())
+        .filter(|url| !url.is_empty())
+        .map(|url| Arc::new(JsonRpcContractVerifier::new(url)) as SharedContractVerifier)
+}
+
+/// Calls contracts through a node's JSON-RPC API with `eth_call` against
+/// the latest block.
+pub struct JsonRpcContractVerifier {
+    client: Client,
+    url: String,
+    chain_id: OnceCell<u64>,
+}
+
+impl JsonRpcContractVerifier {
+    pub fn new(url: String) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .unwrap_or_default();
+        Self {
+            client,
+            url,
+            chain_id: OnceCell::new(),
+        }
+    }
+
+    /// Send one request. A JSON-RPC error object comes back as `Ok(Err)` so
+    /// callers can treat a revert as a rejection rather than an outage.
+    async fn call(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<Result<serde_json::Value, String>, AppError> {
+        let response: serde_json::Value = self
+            .client
+            .post(&self.url)
+            .json(&serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": method,
+                "params": params
+            }))
+            .send()
+            .await
+            .map_err(|e| AppError::Internal(format!("eip1271 rpc: {e}")))?
+            .error_for_status()
+            .map_err(|e| AppError::Internal(format!("eip1271 rpc: {e}")))?
+            .json()
+            .await
+            .map_err(|e| AppError::Internal(format!("eip1271 rpc: {e}")))?;
+
+        if let Some(error) = response.get("error") {
+            return Ok(Err(error.to_string()));
+        }
+        Ok(Ok(response
+            .get("result")
+            .cloned()
+            .unwrap_or(serde_json::Value::Null)))
+    }
+}
+
+fn rpc_bytes(value: &serde_json::Value) -> Result<Vec<u8>, AppError> {
+    let text = value
+        .as_str()
+        .ok_or_else(|| AppError::Internal("eip1271 rpc: expected a hex string".into()))?;
+    hex::decode(text.trim_start_matches("0x"))
+        .map_err(|_| AppError::Internal("eip1271 rpc: invalid hex in response".into()))
+}
+
+/// Calldata for `isValidSignature(bytes32 hash, bytes signature)`.
+pub fn is_valid_signature_calldata(hash: H256, signature: &[u8]) -> Vec<u8> {
+    let mut data = EIP1271_MAGIC_VALUE.to_vec();
+    data.extend(encode(&[
+        Token::FixedBytes(hash.as_bytes().to_vec()),
+        Token::Bytes(signature.to_vec()),
+    ]));
+    data
+}
+
+#[async_trait]
+impl ContractSignatureVerifier for JsonRpcContractVerifier {
+    fn name(&self) -> &'static str {
+        "json-rpc"
+    }
+
+    async fn chain_id(&self) -> Result<u64, AppError> {
+        self.chain_id
+            .get_or_try_init(|| async {
+                let result = self
+                    .call("eth_chainId", serde_json::json!([]))
+                    .await?
+                    .map_err(|e| AppError::Internal(format!("eip1271 rpc: {e}")))?;
+                let hex = result.as_str().unwrap_or_default().trim_start_matches("0x");
+                u64::from_str_radix(hex, 16)
+                    .map_err(|_| AppError::Internal("eip1271 rpc: invalid chain id".into()))
+            })
+            .await
+            .copied()
+    }
+
+    async fn is_valid_signature(
+        &self,
+        account: Address,
+        hash: H256,
+        signature: &[u8],
+    ) -> Result<bool, AppError> {
+        let account = format!("{account:?}");
+        let code = match self
+            .call("eth_getCode", serde_json::json!([account, "latest"]))
+            .await?
+        {
+            Ok(code) => rpc_bytes(&code)?,
+            Err(e) => return Err(AppError::Internal(format!("eip1271 rpc: {e}"))),
+        };
+        if code.is_empty() {
+            return Ok(false);
+        }
+
+        let data = format!(
+            "0x{}",
+            hex::encode(is_valid_signature_calldata(hash, signature))
+        );
+        let returned = match self
+            .call(
+                "eth_call",
+                serde_json::json!([{ "to": account, "data": data }, "latest"]),
+            )
+            .await?
+        {
+            Ok(returned) => rpc_bytes(&returned)?,
+            Err(_) => return Ok(false),
+        };
+        Ok(returned.get(..4) == Some(&EIP1271_MAGIC_VALUE[..]))
+    }
+}
+
+/// Whether `signature` recovers to `wallet` as a plain EOA signature.
+pub fn ecdsa_signer_matches(message: &str, signature: &str, wallet: &str) -> bool {
+    verify_evm_signature(message, signature)
+        .map(|recovered| recovered.eq_ignore_ascii_case(wallet.trim()))
+        .unwrap_or(false)
+}
+
+/// Ask the contract at `wallet` whether it accepts `signature` over the
+/// EIP-191 hash of `message`, the same digest `personal_sign` covers.
+pub async fn contract_signature_matches(
+    contracts: &dyn ContractSignatureVerifier,
+    message: &str,
+    signature: &str,
+    wallet: &str,
+) -> Result<bool, AppError> {
+    let Ok(account) = wallet.trim().parse::<Address>() else {
+        return Ok(false);
+    };
+    let Ok(signature) = hex::decode(signature.trim().trim_start_matches("0x")) else {
+        return Ok(false);
+    };
+    contracts
+        .is_valid_signature(account, hash_message(message), &signature)
+        .await
+}
+
+/// Accept an EVM wallet signature by ECDSA recovery, falling back to
+/// EIP-1271 when a contract verifier is configured. `None` means the
+/// signature is not valid for `wallet` either way.
+pub async fn verify_evm_wallet_signature(
+    contracts: Option<&dyn ContractSignatureVerifier>,
+    message: &str,
+    signature: &str,
+    wallet: &str,
+) -> Result<Option<EvmSignatureMethod>, AppError> {
+    if ecdsa_signer_matches(message, signature, wallet) {
+        return Ok(Some(EvmSignatureMethod::Ecdsa));
+    }
+    let Some(contracts) = contracts else {
+        return Ok(None);
+    };
+    Ok(
+        contract_signature_matches(contracts, message, signature, wallet)
+            .await?
+            .then_some(EvmSignatureMethod::Eip1271),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Accepts any signature for one account, as a 1-of-1 multisig would for
+    /// its owner's key.
+    struct AcceptingContract(Address);
+
+    #[async_trait]
+    impl ContractSignatureVerifier for AcceptingContract {
+        fn name(&self) -> &'static str {
+            "test"
+        }
+
+        async fn chain_id(&self) -> Result<u64, AppError> {
+            Ok(1)
+        }
+
+        async fn is_valid_signature(
+            &self,
+            account: Address,
+            _hash: H256,
+            _signature: &[u8],
+        ) -> Result<bool, AppError> {
+            Ok(account == self.0)
+        }
+    }
+
+    #[test]
+    fn calldata_starts_with_the_selector_and_pads_the_signature() {
+        let data = is_valid_signature_calldata(H256::repeat_byte(0xab), &[1, 2, 3]);
+        assert_eq!(&data[..4], &EIP1271_MAGIC_VALUE);
+        assert_eq!(&data[4..36], H256::repeat_byte(0xab).as_bytes());
+        // head (hash, offset) + length word + one padded word
+        assert_eq!(data.len(), 4 + 32 * 4);
+        assert_eq!(data[4 + 32 * 3 - 1], 3);
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_the_contract_when_recovery_does_not_match() {
+        let safe: Address = "0x00000000000000000000000000000000000051fe"
+            .parse()
+            .unwrap();
+        let wallet = format!("{safe:?}");
+        let contracts = AcceptingContract(safe);
+        let signature = format!("0x{}", hex::encode([7u8; 65]));
+
+        assert_eq!(
+            verify_evm_wallet_signature(None, "hello", &signature, &wallet)
+                .await
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            verify_evm_wallet_signature(Some(&contracts), "hello", &signature, &wallet)
+                .await
+                .unwrap(),
+            Some(EvmSignatureMethod::Eip1271)
+        );
+        assert_eq!(
+            verify_evm_wallet_signature(
+                Some(&contracts),
+                "hello",
+                &signature,
+                "0x00000000000000000000000000000000000000aa"
+            )
+            .await
+            .unwrap(),
+            None
+        );
+    }
+
+    /// Runs against a local anvil node when `ANVIL_RPC_URL` is set, e.g.
+    /// `ANVIL_RPC_URL=http://127.0.0.1:8545 cargo test eip1271`.
+    #[tokio::test]
+    async fn json_rpc_verifier_calls_is_valid_signature_on_anvil() {
+        let Ok(url) = std::env::var("ANVIL_RPC_URL") else {
+            eprintln!("skipping: ANVIL_RPC_URL is not set");
+            return;
+        };
+        let verifier = JsonRpcContractVerifier::new(url);
+
+        // Runtime code that returns the magic value for any call, and one
+        // that returns 0xffffffff.
+        let accepting = "0x631626ba7e60e01b60005260206000f3";
+        let rejecting = "0x63ffffffff60e01b60005260206000f3";
+        let accept_at = "0x0000000000000000000000000000000000001271";
+        let reject_at = "0x0000000000000000000000000000000000001272";
+        for (address, code) in [(accept_at, accepting), (reject_at, rejecting)] {
+            verifier
+                .call("anvil_setCode", serde_json::json!([address, code]))
+                .await
+                .unwrap()
+                .unwrap();
+        }
+
+        let hash = hash_message("sign in");
+        assert!(verifier
+            .is_valid_signature(accept_at.parse().unwrap(), hash, &[1; 65])
+            .await
+            .unwrap());
+        assert!(!verifier
+            .is_valid_signature(reject_at.parse().unwrap(), hash, &[1; 65])
+            .await
+            .unwrap());
+        // An account with no code is not a contract wallet.
+        assert!(!verifier
+            .is_valid_signature(
+                "0x0000000000000000000000000000000000001273"
+                    .parse()
+                    .unwrap(),
+                hash,
+                &[1; 65]
+            )
+            .await
+            .unwrap());
+        assert_eq!(verifier.chain_id().await.unwrap(), 31337);
+    }
+}
@@ -1,9 +1,16 @@
 // src/identity_web/evm.rs
 
 use axum::{extract::State, Json};
-use serde::{Deserialize, Serialize};
+use chrono::Utc;
+use serde::Deserialize;
 
 use crate::error::AppError;
+use crate::identity_web::eip1271::{
+    contract_signature_matches, ecdsa_signer_matches, ContractSignatureVerifier, EvmSignatureMethod,
+};
+use crate::identity_web::sign_in::{
+    SignInChain, SignInMessage, SignInNonceRequest, SignInNonceResponse, SignInPolicy,
+};
 use crate::identity_web::state::AuthState;
 
 use ethers_core::types::Signature;
@@ -15,24 +22,21 @@ use crate::crypto::canonical::keystore::load_or_create_mlkem_keypair;
 // NONCE
 // ============================================================
 
-#[derive(Serialize)]
-pub struct EvmNonceResponse {
-    pub session_id: String,
-    pub nonce: String,
-    pub message: String,
-}
-
 pub async fn evm_nonce_handler(
     State(st): State<AuthState>,
-) -> Result<Json<EvmNonceResponse>, AppError> {
+    body: Option<Json<SignInNonceRequest>>,
+) -> Result<Json<SignInNonceResponse>, AppError> {
     let (session_id, nonce) = st.create_nonce().await?;
-    let message = evm_login_message(&nonce);
+    let policy = SignInPolicy::from_env(SignInChain::Ethereum)?;
+    let request = body.map(|Json(body)| body).unwrap_or_default();
 
-    Ok(Json(EvmNonceResponse {
+    Ok(Json(SignInNonceResponse::new(
+        SignInChain::Ethereum,
+        policy,
         session_id,
         nonce,
-        message,
-    }))
+        &request,
+    )?))
 }
 
 // ============================================================
@@ -43,6 +47,8 @@ pub async fn evm_nonce_handler(
 pub struct EvmVerifyRequest {
     pub session_id: String,
     pub address: String,
+    /// The EIP-4361 message exactly as signed.
+    pub message: String,
     pub signature: String,
     pub visitor_id: Option<String>,
     pub attribution: Option<serde_json::Value>,
@@ -71,15 +77,8 @@ pub async fn evm_verify_handler(
         .await?
         .ok_or_else(|| AppError::Auth("Unknown or expired session".into()))?;
 
-    let message = evm_login_message(&nonce);
-
-    let recovered = verify_evm_signature(&message, signature)
-        .map_err(|_| AppError::Auth("Invalid signature".into()))?
-        .to_lowercase();
-
-    if recovered != address {
-        return Err(AppError::Auth("Signature does not match address".into()));
-    }
+    let policy = SignInPolicy::from_env(SignInChain::Ethereum)?;
+    verify_siwe_login(&policy, None, &req.message, signature, &address, &nonce).await?;
 
     st.bind_wallet(sid.to_string(), address.clone(), "evm", None, None, None)
         .await?;
@@ -106,12 +105,111 @@ pub fn verify_evm_signature(message: &str, signature: &str) -> Result<String, ()
     Ok(format!("{:?}", recovered))
 }
 
-pub fn evm_login_message(nonce: &str) -> String {
-    format!(
-        "TIDBIT Authentication\n\
-Nonce: {}\n\
-Purpose: Login\n\
-Version: 1",
-        nonce
-    )
+/// Check an EIP-4361 login: the message must be bound to this domain, an
+/// accepted chain and the session nonce, and be signed by the address it
+/// names. Contract wallets are checked through EIP-1271 on the verifier's
+/// chain. Returns the lowercased address and how the signature was accepted.
+pub async fn verify_siwe_login(
+    policy: &SignInPolicy,
+    contracts: Option<&dyn ContractSignatureVerifier>,
+    message: &str,
+    signature: &str,
+    claimed_address: &str,
+    nonce: &str,
+) -> Result<(String, EvmSignatureMethod), AppError> {
+    let parsed = SignInMessage::parse(message, SignInChain::Ethereum)?;
+    policy.check(&parsed, nonce, Utc::now())?;
+
+    let address = SignInChain::Ethereum
+        .format_address(&parsed.address)?
+        .to_lowercase();
+    if address != claimed_address.trim().to_lowercase() {
+        return Err(AppError::Auth(
+            "Sign-in message is for a different address".into(),
+        ));
+    }
+    if ecdsa_signer_matches(message, signature, &address) {
+        return Ok((address, EvmSignatureMethod::Ecdsa));
+    }
+
+    let Some(contracts) = contracts else {
+        return Err(AppError::Auth("Signature does not match address".into()));
+    };
+    // A contract account only exists on the chain it was deployed to.
+    let contract_chain = contracts.chain_id().await?;
+    if parsed.chain_id != contract_chain.to_string() {
+        return Err(AppError::Auth(format!(
+            "Signature does not match address (contract wallets sign in on chain {contract_chain})"
+        )));
+    }
+    if !contract_signature_matches(contracts, message, signature, &address).await? {
+        return Err(AppError::Auth("Signature does not match address".into()));
+    }
+    Ok((address, EvmSignatureMethod::Eip1271))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::verify_siwe_login;
+    use crate::identity_web::eip1271::EvmSignatureMethod;
+    use crate::identity_web::sign_in::{SignInChain, SignInPolicy};
+    use ethers_signers::{LocalWallet, Signer};
+
+    #[tokio::test]
+    async fn siwe_login_recovers_the_named_address() {
+        let wallet: LocalWallet =
+            "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318"
+                .parse()
+                .unwrap();
+        let address = format!("{:?}", wallet.address());
+        let policy = SignInPolicy {
+            domain: "tidbit.example".into(),
+            uri: "https://tidbit.example".into(),
+            chain_ids: vec!["1".into()],
+        };
+        let message = policy
+            .login_message(
+                &SignInChain::Ethereum.format_address(&address).unwrap(),
+                "1",
+                "nonce1234abcd",
+                chrono::Utc::now(),
+            )
+            .render(SignInChain::Ethereum);
+        let signature = wallet.sign_message(&message).await.unwrap().to_string();
+
+        assert_eq!(
+            verify_siwe_login(
+                &policy,
+                None,
+                &message,
+                &signature,
+                &address,
+                "nonce1234abcd"
+            )
+            .await
+            .unwrap(),
+            (address.to_lowercase(), EvmSignatureMethod::Ecdsa)
+        );
+        assert!(
+            verify_siwe_login(&policy, None, &message, &signature, &address, "stale")
+                .await
+                .is_err()
+        );
+
+        let elsewhere = SignInPolicy {
+            domain: "phish.example".into(),
+            uri: "https://phish.example".into(),
+            ..policy
+        };
+        assert!(verify_siwe_login(
+            &elsewhere,
+            None,
+            &message,
+            &signature,
+            &address,
+            "nonce1234abcd"
+        )
+        .await
+        .is_err());
+    }
 }
@@ -1,6 +1,9 @@
 // src/identity_web/mod.rs
 
+pub mod devices;
+pub mod eip1271;
 pub mod evm;
+pub mod sign_in;
 pub mod sol;
 pub mod state;
 
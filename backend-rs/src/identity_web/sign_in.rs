@@ -0,0 +1,562 @@
+// src/identity_web/sign_in.rs
+//
+// Sign-In-With-Ethereum (EIP-4361) and Sign-In-With-Solana messages. Both
+// share one text layout; only the account line and the chain id differ.
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+
+/// How far a client clock may run ahead of ours.
+const CLOCK_SKEW_SECS: i64 = 60;
+
+/// A login message is only good for as long as its nonce.
+pub const SIGN_IN_TTL_SECS: i64 = 60 * 15;
+
+const STATEMENT: &str = "Sign in to TIDBIT.";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignInChain {
+    Ethereum,
+    Solana,
+}
+
+impl SignInChain {
+    fn account_line(self) -> &'static str {
+        match self {
+            SignInChain::Ethereum => " wants you to sign in with your Ethereum account:",
+            SignInChain::Solana => " wants you to sign in with your Solana account:",
+        }
+    }
+
+    fn chain_ids_env(self) -> &'static str {
+        match self {
+            SignInChain::Ethereum => "SIWE_CHAIN_IDS",
+            SignInChain::Solana => "SIWS_CHAIN_IDS",
+        }
+    }
+
+    fn default_chain_ids(self) -> &'static str {
+        match self {
+            SignInChain::Ethereum => "1,10,137,8453,42161",
+            SignInChain::Solana => "mainnet",
+        }
+    }
+
+    /// EIP-4361 chain ids are decimal; Solana uses cluster names with an
+    /// optional `solana:` prefix.
+    pub fn normalize_chain_id(self, raw: &str) -> Option<String> {
+        let raw = raw.trim();
+        match self {
+            SignInChain::Ethereum => raw
+                .parse::<u64>()
+                .ok()
+                .or_else(|| {
+                    raw.strip_prefix("0x")
+                        .and_then(|hex| u64::from_str_radix(hex, 16).ok())
+                })
+                .map(|id| id.to_string()),
+            SignInChain::Solana => {
+                let cluster = raw.strip_prefix("solana:").unwrap_or(raw);
+                (!cluster.is_empty()
+                    && cluster
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '-'))
+                .then(|| cluster.to_ascii_lowercase())
+            }
+        }
+    }
+
+    /// The address as it must appear in the message: EIP-55 checksummed for
+    /// Ethereum, base58 for Solana.
+    pub fn format_address(self, raw: &str) -> Result<String, AppError> {
+        let raw = raw.trim();
+        match self {
+            SignInChain::Ethereum => {
+                let address: ethers_core::types::Address = raw
+                    .parse()
+                    .map_err(|_| AppError::BadRequest("Invalid EVM address".into()))?;
+                let checksummed = ethers_core::utils::to_checksum(&address, None);
+                // All-lowercase is tolerated; mixed case must be a valid checksum.
+                if raw != checksummed && raw != raw.to_ascii_lowercase() {
+                    return Err(AppError::Auth("EVM address checksum is invalid".into()));
+                }
+                Ok(checksummed)
+            }
+            SignInChain::Solana => {
+                let bytes = bs58::decode(raw)
+                    .into_vec()
+                    .map_err(|_| AppError::BadRequest("Invalid Solana wallet address".into()))?;
+                if bytes.len() != 32 {
+                    return Err(AppError::BadRequest(
+                        "Invalid Solana wallet address length".into(),
+                    ));
+                }
+                Ok(raw.to_string())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignInMessage {
+    pub domain: String,
+    pub address: String,
+    pub statement: Option<String>,
+    pub uri: String,
+    pub version: String,
+    pub chain_id: String,
+    pub nonce: String,
+    pub issued_at: DateTime<Utc>,
+    pub expiration_time: Option<DateTime<Utc>>,
+    pub not_before: Option<DateTime<Utc>>,
+    pub request_id: Option<String>,
+    pub resources: Vec<String>,
+}
+
+fn timestamp(value: &DateTime<Utc>) -> String {
+    value.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+fn parse_timestamp(field: &str, value: &str) -> Result<DateTime<Utc>, AppError> {
+    DateTime::parse_from_rfc3339(value.trim())
+        .map(|value| value.with_timezone(&Utc))
+        .map_err(|_| AppError::Auth(format!("Sign-in message has an invalid {field}")))
+}
+
+fn malformed(detail: &str) -> AppError {
+    AppError::Auth(format!("Malformed sign-in message: {detail}"))
+}
+
+impl SignInMessage {
+    pub fn render(&self, chain: SignInChain) -> String {
+        let mut lines = vec![
+            format!("{}{}", self.domain, chain.account_line()),
+            self.address.clone(),
+            String::new(),
+        ];
+        if let Some(statement) = &self.statement {
+            lines.push(statement.clone());
+            lines.push(String::new());
+        }
+        lines.push(format!("URI: {}", self.uri));
+        lines.push(format!("Version: {}", self.version));
+        lines.push(format!("Chain ID: {}", self.chain_id));
+        lines.push(format!("Nonce: {}", self.nonce));
+        lines.push(format!("Issued At: {}", timestamp(&self.issued_at)));
+        if let Some(value) = &self.expiration_time {
+            lines.push(format!("Expiration Time: {}", timestamp(value)));
+        }
+        if let Some(value) = &self.not_before {
+            lines.push(format!("Not Before: {}", timestamp(value)));
+        }
+        if let Some(value) = &self.request_id {
+            lines.push(format!("Request ID: {value}"));
+        }
+        if !self.resources.is_empty() {
+            lines.push("Resources:".into());
+            lines.extend(
+                self.resources
+                    .iter()
+                    .map(|resource| format!("- {resource}")),
+            );
+        }
+        lines.join("\n")
+    }
+
+    /// Parse the fields in the order the spec fixes. Every field this server
+    /// checks (URI, version, chain id, nonce, issued-at) is required, also
+    /// for Solana where the format leaves them optional.
+    pub fn parse(text: &str, chain: SignInChain) -> Result<Self, AppError> {
+        let mut lines = text.split('\n').map(|line| line.trim_end_matches('\r'));
+
+        let header = lines.next().unwrap_or_default();
+        let domain = header
+            .strip_suffix(chain.account_line())
+            .ok_or_else(|| malformed("unexpected first line"))?;
+        let domain = domain
+            .split_once("://")
+            .map(|(_, rest)| rest)
+            .unwrap_or(domain);
+        let address = lines
+            .next()
+            .filter(|line| !line.trim().is_empty())
+            .ok_or_else(|| malformed("missing address"))?
+            .trim()
+            .to_string();
+        if lines.next() != Some("") {
+            return Err(malformed("expected a blank line after the address"));
+        }
+
+        let mut rest: Vec<&str> = lines.collect();
+        let mut statement = None;
+        match rest.first().copied() {
+            Some("") => {
+                rest.remove(0);
+            }
+            Some(line) if !line.starts_with("URI: ") => {
+                statement = Some(line.to_string());
+                rest.remove(0);
+                if rest.first().copied() != Some("") {
+                    return Err(malformed("expected a blank line after the statement"));
+                }
+                rest.remove(0);
+            }
+            _ => {}
+        }
+
+        let mut fields = rest.into_iter().peekable();
+        let mut field = |prefix: &str| {
+            fields
+                .next_if(|line| line.starts_with(prefix))
+                .map(|line| line[prefix.len()..].to_string())
+        };
+        let uri = field("URI: ").ok_or_else(|| malformed("missing URI"))?;
+        let version = field("Version: ").ok_or_else(|| malformed("missing Version"))?;
+        let chain_id = field("Chain ID: ").ok_or_else(|| malformed("missing Chain ID"))?;
+        let nonce = field("Nonce: ").ok_or_else(|| malformed("missing Nonce"))?;
+        let issued_at = field("Issued At: ").ok_or_else(|| malformed("missing Issued At"))?;
+        let expiration_time = field("Expiration Time: ");
+        let not_before = field("Not Before: ");
+        let request_id = field("Request ID: ");
+        let mut resources = Vec::new();
+        if field("Resources:").is_some() {
+            while let Some(resource) = field("- ") {
+                resources.push(resource);
+            }
+        }
+        if fields.any(|line| !line.is_empty()) {
+            return Err(malformed("unexpected trailing lines"));
+        }
+
+        Ok(SignInMessage {
+            domain: domain.to_string(),
+            address,
+            statement,
+            uri,
+            version,
+            chain_id: chain
+                .normalize_chain_id(&chain_id)
+                .ok_or_else(|| malformed("invalid Chain ID"))?,
+            nonce,
+            issued_at: parse_timestamp("Issued At", &issued_at)?,
+            expiration_time: expiration_time
+                .map(|value| parse_timestamp("Expiration Time", &value))
+                .transpose()?,
+            not_before: not_before
+                .map(|value| parse_timestamp("Not Before", &value))
+                .transpose()?,
+            request_id,
+            resources,
+        })
+    }
+}
+
+/// Where and on which chains this server accepts sign-in messages.
+#[derive(Debug, Clone, Serialize)]
+pub struct SignInPolicy {
+    pub domain: String,
+    pub uri: String,
+    pub chain_ids: Vec<String>,
+}
+
+/// A sign-in setting that must be present and non-empty.
+fn required_env(name: &str) -> Result<String, AppError> {
+    std::env::var(name)
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .ok_or_else(|| AppError::Internal(format!("{name} must be set for wallet sign-in")))
+}
+
+/// `host[:port]` of an absolute http(s) URI.
+fn uri_authority(uri: &reqwest::Url) -> Option<String> {
+    if !matches!(uri.scheme(), "http" | "https") {
+        return None;
+    }
+    uri.host_str().map(|host| match uri.port() {
+        Some(port) => format!("{host}:{port}"),
+        None => host.to_string(),
+    })
+}
+
+impl SignInPolicy {
+    /// `SIGN_IN_DOMAIN` is required: the domain a login is bound to must
+    /// never come from request headers, which the client controls.
+    /// `SIGN_IN_URI` defaults to `https://<domain>` (`http` for localhost)
+    /// and must point at the same host.
+    pub fn from_env(chain: SignInChain) -> Result<Self, AppError> {
+        let domain = required_env("SIGN_IN_DOMAIN")?.to_ascii_lowercase();
+        if domain.contains("://") || domain.contains('/') {
+            return Err(AppError::Internal(
+                "SIGN_IN_DOMAIN must be a host[:port], not a URL".into(),
+            ));
+        }
+        let uri = match required_env("SIGN_IN_URI") {
+            Ok(uri) => uri.trim_end_matches('/').to_string(),
+            Err(_) if domain.starts_with("localhost") || domain.starts_with("127.0.0.1") => {
+                format!("http://{domain}")
+            }
+            Err(_) => format!("https://{domain}"),
+        };
+        let authority = reqwest::Url::parse(&uri)
+            .ok()
+            .as_ref()
+            .and_then(uri_authority)
+            .ok_or_else(|| AppError::Internal("SIGN_IN_URI must be an http(s) URL".into()))?;
+        if !authority.eq_ignore_ascii_case(&domain) {
+            return Err(AppError::Internal(
+                "SIGN_IN_URI must use the SIGN_IN_DOMAIN host".into(),
+            ));
+        }
+        let chain_ids = std::env::var(chain.chain_ids_env())
+            .ok()
+            .filter(|value| !value.trim().is_empty())
+            .unwrap_or_else(|| chain.default_chain_ids().to_string())
+            .split(',')
+            .filter_map(|value| chain.normalize_chain_id(value))
+            .collect();
+        Ok(SignInPolicy {
+            domain,
+            uri,
+            chain_ids,
+        })
+    }
+
+    pub fn login_message(
+        &self,
+        address: &str,
+        chain_id: &str,
+        nonce: &str,
+        now: DateTime<Utc>,
+    ) -> SignInMessage {
+        SignInMessage {
+            domain: self.domain.clone(),
+            address: address.to_string(),
+            statement: Some(STATEMENT.to_string()),
+            uri: self.uri.clone(),
+            version: "1".into(),
+            chain_id: chain_id.to_string(),
+            nonce: nonce.to_string(),
+            issued_at: now,
+            expiration_time: Some(now + chrono::Duration::seconds(SIGN_IN_TTL_SECS)),
+            not_before: None,
+            request_id: None,
+            resources: Vec::new(),
+        }
+    }
+
+    pub fn allows_chain(&self, chain_id: &str) -> bool {
+        self.chain_ids.iter().any(|allowed| allowed == chain_id)
+    }
+
+    /// Everything except the signature itself.
+    pub fn check(
+        &self,
+        message: &SignInMessage,
+        expected_nonce: &str,
+        now: DateTime<Utc>,
+    ) -> Result<(), AppError> {
+        if !message.domain.eq_ignore_ascii_case(&self.domain) {
+            return Err(AppError::Auth(format!(
+                "Sign-in message is for {}, not {}",
+                message.domain, self.domain
+            )));
+        }
+        let uri = reqwest::Url::parse(&message.uri)
+            .map_err(|_| AppError::Auth("Sign-in message URI is invalid".into()))?;
+        let expected = reqwest::Url::parse(&self.uri)
+            .map_err(|_| AppError::Internal("SIGN_IN_URI is invalid".into()))?;
+        if uri_authority(&uri).is_none() || uri.origin() != expected.origin() {
+            return Err(AppError::Auth(format!(
+                "Sign-in message URI is not {}",
+                self.uri
+            )));
+        }
+        if message.version != "1" {
+            return Err(AppError::Auth("Unsupported sign-in message version".into()));
+        }
+        if !self.allows_chain(&message.chain_id) {
+            return Err(AppError::Auth(format!(
+                "Chain {} is not accepted for sign-in",
+                message.chain_id
+            )));
+        }
+        if message.nonce != expected_nonce {
+            return Err(AppError::Auth("Sign-in nonce does not match".into()));
+        }
+
+        let skew = chrono::Duration::seconds(CLOCK_SKEW_SECS);
+        if message.issued_at > now + skew {
+            return Err(AppError::Auth(
+                "Sign-in message is issued in the future".into(),
+            ));
+        }
+        if message.issued_at < now - chrono::Duration::seconds(SIGN_IN_TTL_SECS) {
+            return Err(AppError::Auth("Sign-in message is too old".into()));
+        }
+        if message
+            .expiration_time
+            .is_some_and(|expires| expires <= now)
+        {
+            return Err(AppError::Auth("Sign-in message has expired".into()));
+        }
+        if message
+            .not_before
+            .is_some_and(|not_before| not_before > now + skew)
+        {
+            return Err(AppError::Auth("Sign-in message is not valid yet".into()));
+        }
+        Ok(())
+    }
+}
+
+/// Optional on the nonce routes. With both fields the server returns the
+/// exact message to sign.
+#[derive(Debug, Default, Deserialize)]
+pub struct SignInNonceRequest {
+    pub address: Option<String>,
+    pub chain_id: Option<serde_json::Value>,
+}
+
+#[derive(Serialize)]
+pub struct SignInNonceResponse {
+    pub session_id: String,
+    pub nonce: String,
+    /// Ready to sign when the request named an address and chain.
+    pub message: Option<String>,
+    pub domain: String,
+    pub uri: String,
+    pub chain_ids: Vec<String>,
+    pub expires_in_secs: i64,
+}
+
+impl SignInNonceResponse {
+    pub fn new(
+        chain: SignInChain,
+        policy: SignInPolicy,
+        session_id: String,
+        nonce: String,
+        request: &SignInNonceRequest,
+    ) -> Result<Self, AppError> {
+        let chain_id = request.chain_id.as_ref().map(|value| match value {
+            serde_json::Value::String(text) => text.clone(),
+            other => other.to_string(),
+        });
+        let message = match (request.address.as_deref(), chain_id) {
+            (Some(address), Some(chain_id)) => {
+                let chain_id = chain
+                    .normalize_chain_id(&chain_id)
+                    .filter(|chain_id| policy.allows_chain(chain_id))
+                    .ok_or_else(|| {
+                        AppError::BadRequest(format!(
+                            "Chain {chain_id} is not accepted for sign-in"
+                        ))
+                    })?;
+                let address = chain.format_address(address)?;
+                Some(
+                    policy
+                        .login_message(&address, &chain_id, &nonce, Utc::now())
+                        .render(chain),
+                )
+            }
+            _ => None,
+        };
+        Ok(SignInNonceResponse {
+            session_id,
+            nonce,
+            message,
+            domain: policy.domain,
+            uri: policy.uri,
+            chain_ids: policy.chain_ids,
+            expires_in_secs: SIGN_IN_TTL_SECS,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> SignInPolicy {
+        SignInPolicy {
+            domain: "tidbit.example".into(),
+            uri: "https://tidbit.example".into(),
+            chain_ids: vec!["1".into(), "8453".into()],
+        }
+    }
+
+    #[test]
+    fn siwe_messages_round_trip() {
+        let now = Utc::now();
+        let message = policy().login_message(
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+            "1",
+            "abc12345def",
+            now,
+        );
+        let text = message.render(SignInChain::Ethereum);
+        assert!(text.starts_with(
+            "tidbit.example wants you to sign in with your Ethereum account:\n0x5aAeb"
+        ));
+        let parsed = SignInMessage::parse(&text, SignInChain::Ethereum).unwrap();
+        assert_eq!(parsed.nonce, message.nonce);
+        assert_eq!(parsed.statement.as_deref(), Some(STATEMENT));
+        assert_eq!(parsed.issued_at.timestamp_millis(), now.timestamp_millis());
+        assert!(policy().check(&parsed, "abc12345def", now).is_ok());
+        assert!(SignInMessage::parse(&text, SignInChain::Solana).is_err());
+    }
+
+    #[test]
+    fn foreign_domains_chains_and_stale_messages_are_refused() {
+        let now = Utc::now();
+        let mut message = policy().login_message("0xabc", "1", "nonce1234", now);
+        assert!(policy().check(&message, "other-nonce", now).is_err());
+
+        message.domain = "phish.example".into();
+        assert!(policy().check(&message, "nonce1234", now).is_err());
+
+        let mut message = policy().login_message("0xabc", "5", "nonce1234", now);
+        assert!(policy().check(&message, "nonce1234", now).is_err());
+
+        message.chain_id = "8453".into();
+        message.uri = "https://phish.example/login".into();
+        assert!(policy().check(&message, "nonce1234", now).is_err());
+        message.uri = "http://tidbit.example".into();
+        assert!(policy().check(&message, "nonce1234", now).is_err());
+        message.uri = "https://tidbit.example/login".into();
+        assert!(policy().check(&message, "nonce1234", now).is_ok());
+
+        let message = policy().login_message("0xabc", "1", "nonce1234", now);
+        let later = now + chrono::Duration::seconds(SIGN_IN_TTL_SECS + 1);
+        assert!(policy().check(&message, "nonce1234", later).is_err());
+
+        let mut message = policy().login_message("0xabc", "1", "nonce1234", now);
+        message.not_before = Some(now + chrono::Duration::hours(1));
+        assert!(policy().check(&message, "nonce1234", now).is_err());
+    }
+
+    #[test]
+    fn siws_messages_accept_cluster_chain_ids() {
+        let now = Utc::now();
+        let policy = SignInPolicy {
+            chain_ids: vec!["mainnet".into()],
+            ..policy()
+        };
+        let mut message = policy.login_message(
+            "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin",
+            "mainnet",
+            "nonce1234",
+            now,
+        );
+        message.statement = None;
+        let text = message
+            .render(SignInChain::Solana)
+            .replace("Chain ID: mainnet", "Chain ID: solana:mainnet");
+        let parsed = SignInMessage::parse(&text, SignInChain::Solana).unwrap();
+        assert_eq!(parsed.chain_id, "mainnet");
+        assert!(parsed.statement.is_none());
+        assert!(policy.check(&parsed, "nonce1234", now).is_ok());
+    }
+}
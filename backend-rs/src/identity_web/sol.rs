@@ -1,36 +1,39 @@
 use axum::{extract::State, Json};
 use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
 use base64::Engine;
+use chrono::Utc;
 use ed25519_dalek::{Signature as Ed25519Signature, Verifier, VerifyingKey};
 use serde::{Deserialize, Serialize};
 
 use crate::error::AppError;
 
+use super::sign_in::{
+    SignInChain, SignInMessage, SignInNonceRequest, SignInNonceResponse, SignInPolicy,
+};
 use super::state::AuthState;
 
-#[derive(Serialize)]
-pub struct SolNonceResponse {
-    pub session_id: String,
-    pub nonce: String,
-    pub message: String,
-}
-
 pub async fn sol_nonce_handler(
     State(st): State<AuthState>,
-) -> Result<Json<SolNonceResponse>, AppError> {
+    body: Option<Json<SignInNonceRequest>>,
+) -> Result<Json<SignInNonceResponse>, AppError> {
     let (session_id, nonce) = st.create_nonce().await?;
-    let message = sol_login_message(&nonce);
-    Ok(Json(SolNonceResponse {
+    let policy = SignInPolicy::from_env(SignInChain::Solana)?;
+    let request = body.map(|Json(body)| body).unwrap_or_default();
+    Ok(Json(SignInNonceResponse::new(
+        SignInChain::Solana,
+        policy,
         session_id,
         nonce,
-        message,
-    }))
+        &request,
+    )?))
 }
 
 #[derive(Deserialize)]
 pub struct SolVerifyRequest {
     pub session_id: String,
     pub address: String,
+    /// The Sign-In-With-Solana message exactly as signed.
+    pub message: String,
     pub signature: String,
     pub visitor_id: Option<String>,
     pub attribution: Option<serde_json::Value>,
@@ -64,10 +67,8 @@ pub async fn sol_verify_handler(
         .take_nonce(session_id)
         .await?
         .ok_or_else(|| AppError::Auth("Unknown or expired session".into()))?;
-    let message = sol_login_message(&nonce);
-
-    verify_solana_signature(&message, address, signature)
-        .map_err(|_| AppError::Auth("Invalid Solana signature".into()))?;
+    let policy = SignInPolicy::from_env(SignInChain::Solana)?;
+    verify_siws_login(&policy, &req.message, address, signature, &nonce)?;
 
     st.bind_wallet(
         session_id.to_string(),
@@ -85,14 +86,23 @@ pub async fn sol_verify_handler(
     }))
 }
 
-pub fn sol_login_message(nonce: &str) -> String {
-    format!(
-        "TIDBIT Authentication\n\
-Nonce: {}\n\
-Purpose: Login\n\
-Version: 1",
-        nonce
-    )
+/// The Solana counterpart of `verify_siwe_login`.
+pub fn verify_siws_login(
+    policy: &SignInPolicy,
+    message: &str,
+    claimed_address: &str,
+    signature: &str,
+    nonce: &str,
+) -> Result<(), AppError> {
+    let parsed = SignInMessage::parse(message, SignInChain::Solana)?;
+    policy.check(&parsed, nonce, Utc::now())?;
+    if parsed.address != claimed_address.trim() {
+        return Err(AppError::Auth(
+            "Sign-in message is for a different address".into(),
+        ));
+    }
+    verify_solana_signature(message, &parsed.address, signature)
+        .map_err(|_| AppError::Auth("Invalid Solana signature".into()))
 }
 
 pub fn verify_solana_signature(
@@ -139,7 +149,8 @@ fn decode_signature_blob(signature: &str) -> Result<Vec<u8>, AppError> {
 
 #[cfg(test)]
 mod tests {
-    use super::{sol_login_message, verify_solana_signature};
+    use super::{verify_siws_login, verify_solana_signature};
+    use crate::identity_web::sign_in::{SignInChain, SignInPolicy};
     use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
     use base64::Engine;
     use ed25519_dalek::{Signer, SigningKey};
@@ -148,11 +159,11 @@ mod tests {
     fn verifies_hex_encoded_solana_signature() {
         let signing_key = SigningKey::from_bytes(&[7u8; 32]);
         let wallet = bs58::encode(signing_key.verifying_key().to_bytes()).into_string();
-        let message = sol_login_message("nonce-123");
+        let message = "nonce-123";
         let signature = signing_key.sign(message.as_bytes());
         let signature_hex = hex::encode(signature.to_bytes());
 
-        let verified = verify_solana_signature(&message, &wallet, &signature_hex);
+        let verified = verify_solana_signature(message, &wallet, &signature_hex);
 
         assert!(verified.is_ok());
     }
@@ -161,12 +172,38 @@ mod tests {
     fn verifies_base64_encoded_solana_signature() {
         let signing_key = SigningKey::from_bytes(&[9u8; 32]);
         let wallet = bs58::encode(signing_key.verifying_key().to_bytes()).into_string();
-        let message = sol_login_message("nonce-456");
+        let message = "nonce-456";
         let signature = signing_key.sign(message.as_bytes());
         let signature_b64 = BASE64_STANDARD.encode(signature.to_bytes());
 
-        let verified = verify_solana_signature(&message, &wallet, &signature_b64);
+        let verified = verify_solana_signature(message, &wallet, &signature_b64);
 
         assert!(verified.is_ok());
     }
+
+    #[test]
+    fn siws_login_binds_signature_to_the_named_wallet() {
+        let signing_key = SigningKey::from_bytes(&[11u8; 32]);
+        let wallet = bs58::encode(signing_key.verifying_key().to_bytes()).into_string();
+        let policy = SignInPolicy {
+            domain: "tidbit.example".into(),
+            uri: "https://tidbit.example".into(),
+            chain_ids: vec!["mainnet".into()],
+        };
+        let message = policy
+            .login_message(&wallet, "mainnet", "nonce789abc", chrono::Utc::now())
+            .render(SignInChain::Solana);
+        let signature = hex::encode(signing_key.sign(message.as_bytes()).to_bytes());
+
+        assert!(verify_siws_login(&policy, &message, &wallet, &signature, "nonce789abc").is_ok());
+        assert!(verify_siws_login(&policy, &message, &wallet, &signature, "other").is_err());
+
+        let other = bs58::encode(
+            SigningKey::from_bytes(&[12u8; 32])
+                .verifying_key()
+                .to_bytes(),
+        )
+        .into_string();
+        assert!(verify_siws_login(&policy, &message, &other, &signature, "nonce789abc").is_err());
+    }
 }
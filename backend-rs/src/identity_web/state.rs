@@ -1,10 +1,27 @@
 use crate::error::AppError;
+use crate::identity_web::devices::{
+    device_known, list_trusted_devices, load_active_sessions, load_session_policy,
+    record_account_event, revoke_sessions, sessions_to_revoke, SessionPolicy,
+    ACCOUNT_EVENT_NEW_DEVICE_LOGIN, ACCOUNT_EVENT_SESSION_REUSE_DETECTED,
+};
 use crate::sqlx::{PgPool, Row};
+use rand::RngCore;
 use time::OffsetDateTime;
 
-const SESSION_TTL_SECONDS: i64 = 60 * 60 * 24;
+/// Access sessions are short-lived; clients renew them with the refresh
+/// token issued alongside.
+const SESSION_TTL_SECONDS: i64 = 60 * 15;
 const NONCE_TTL_SECONDS: i64 = 60 * 15;
-const SESSION_ROTATE_AFTER_SECONDS: i64 = 60 * 60 * 12;
+/// Refresh when this little of the access session is left.
+const SESSION_REFRESH_WINDOW_SECONDS: i64 = 60 * 5;
+/// Each refresh token is good for one rotation within this window.
+const REFRESH_TTL_SECONDS: i64 = 60 * 60 * 24 * 7;
+/// A refresh token rotated out this recently is only rejected, so two tabs
+/// racing to refresh don't sign the whole login out.
+const ROTATION_GRACE_SECONDS: i64 = 30;
+
+const REVOKED_ROTATED: &str = "rotated";
+const REVOKED_REUSE_DETECTED: &str = "session_reuse_detected";
 
 fn normalize_wallet_for_chain(wallet: &str, chain: &str) -> String {
     let trimmed = wallet.trim();
@@ -28,6 +45,8 @@ pub struct WalletSession {
     pub last_seen_at: i64,
     pub device_id: Option<String>,
     pub user_agent: Option<String>,
+    /// Only set when the session is issued, by login or rotation.
+    pub refresh_token: Option<String>,
 }
 
 impl WalletSession {
@@ -44,10 +63,34 @@ impl WalletSession {
     }
 
     pub fn rotation_recommended(&self) -> bool {
-        (self.last_seen_at - self.created_at) >= SESSION_ROTATE_AFTER_SECONDS
+        (self.expires_at - self.last_seen_at) <= SESSION_REFRESH_WINDOW_SECONDS
     }
 }
 
+fn refresh_token_hash(token: &str) -> String {
+    hex::encode(crate::pqc::sha3::sha3_256_bytes(token.trim().as_bytes()))
+}
+
+/// A new refresh token and the hash stored for it.
+fn issue_refresh_token() -> (String, String) {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    let token = format!("rt_{}", hex::encode(bytes));
+    let hash = refresh_token_hash(&token);
+    (token, hash)
+}
+
+/// Whether a refresh token that was rotated out at `revoked_at` being
+/// presented now means someone kept a copy of it.
+fn is_rotated_credential_reuse(
+    revoked_reason: Option<&str>,
+    revoked_at: Option<i64>,
+    now: i64,
+) -> bool {
+    revoked_reason == Some(REVOKED_ROTATED)
+        && revoked_at.is_some_and(|revoked_at| now - revoked_at > ROTATION_GRACE_SECONDS)
+}
+
 #[derive(Clone, Debug)]
 pub struct WalletSessionRecord {
     pub session_id: String,
@@ -76,7 +119,8 @@ impl AuthState {
 
     pub async fn create_nonce(&self) -> Result<(String, String), AppError> {
         let session_id = uuid::Uuid::new_v4().to_string();
-        let nonce = uuid::Uuid::new_v4().to_string();
+        // EIP-4361 nonces must be alphanumeric.
+        let nonce = uuid::Uuid::new_v4().simple().to_string();
         let now = OffsetDateTime::now_utc();
         let expires_at = now + time::Duration::seconds(NONCE_TTL_SECONDS);
 
@@ -129,8 +173,15 @@ impl AuthState {
     ) -> Result<WalletSession, AppError> {
         let now = OffsetDateTime::now_utc();
         let expires_at = now + time::Duration::seconds(SESSION_TTL_SECONDS);
+        let refresh_expires_at = now + time::Duration::seconds(REFRESH_TTL_SECONDS);
+        let (refresh_token, refresh_hash) = issue_refresh_token();
         let session_family_id = uuid::Uuid::new_v4();
         let wallet = normalize_wallet_for_chain(&wallet, chain);
+        let device_id = device_id.map(str::trim).filter(|value| !value.is_empty());
+        let new_device = match device_id {
+            Some(device_id) => !device_known(&self.db, &wallet, device_id, &session_id).await?,
+            None => false,
+        };
 
         crate::sqlx::query(
             r#"
@@ -144,7 +195,9 @@ impl AuthState {
                 expires_at,
                 device_id,
                 user_agent,
-                ip_address
+                ip_address,
+                refresh_token_hash,
+                refresh_expires_at
             )
             values (
                 $1,
@@ -156,7 +209,9 @@ impl AuthState {
                 to_timestamp($6),
                 $7,
                 $8,
-                $9
+                $9,
+                $10,
+                to_timestamp($11)
             )
             on conflict (session_id)
             do update set
@@ -167,6 +222,8 @@ impl AuthState {
                 device_id = excluded.device_id,
                 user_agent = excluded.user_agent,
                 ip_address = excluded.ip_address,
+                refresh_token_hash = excluded.refresh_token_hash,
+                refresh_expires_at = excluded.refresh_expires_at,
                 revoked_at = null,
                 revoked_reason = null,
                 replaced_by_session_id = null
@@ -178,15 +235,31 @@ impl AuthState {
         .bind(chain)
         .bind(now.unix_timestamp())
         .bind(expires_at.unix_timestamp())
-        .bind(device_id.map(str::trim))
+        .bind(device_id)
         .bind(user_agent.map(str::trim))
         .bind(ip_address.map(str::trim))
+        .bind(&refresh_hash)
+        .bind(refresh_expires_at.unix_timestamp())
         .execute(&self.db)
         .await
         .map_err(|e| AppError::Internal(e.to_string()))?;
 
-        self.revoke_other_wallet_sessions(&wallet, &session_id)
+        if new_device {
+            record_account_event(
+                &self.db,
+                &wallet,
+                ACCOUNT_EVENT_NEW_DEVICE_LOGIN,
+                serde_json::json!({
+                    "session_id": session_id,
+                    "chain": chain,
+                    "device_id": device_id,
+                    "user_agent": user_agent.map(str::trim),
+                    "ip_address": ip_address.map(str::trim)
+                }),
+            )
             .await?;
+        }
+        self.apply_session_policy(&wallet, &session_id).await?;
 
         Ok(WalletSession {
             session_id,
@@ -197,9 +270,52 @@ impl AuthState {
             last_seen_at: now.unix_timestamp(),
             device_id: device_id.map(str::to_string),
             user_agent: user_agent.map(str::to_string),
+            refresh_token: Some(refresh_token),
         })
     }
 
+    /// Revoke every live session descended from the same login after a
+    /// rotated-out refresh token comes back, and log it for the wallet.
+    async fn revoke_session_family(
+        &self,
+        session_family_id: uuid::Uuid,
+        wallet: &str,
+        presented_session_id: &str,
+    ) -> Result<u64, AppError> {
+        let revoked = crate::sqlx::query(
+            r#"
+            update wallet_sessions
+            set revoked_at = now(),
+                revoked_reason = $2
+            where session_family_id = $1
+              and revoked_at is null
+            "#,
+        )
+        .bind(session_family_id)
+        .bind(REVOKED_REUSE_DETECTED)
+        .execute(&self.db)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+        .rows_affected();
+
+        eprintln!(
+            "security: rotated-out refresh token replayed for session family {session_family_id}; revoked {revoked} session(s)"
+        );
+        record_account_event(
+            &self.db,
+            wallet,
+            ACCOUNT_EVENT_SESSION_REUSE_DETECTED,
+            serde_json::json!({
+                "session_family_id": session_family_id,
+                "session_id": presented_session_id,
+                "credential": "refresh token",
+                "sessions_revoked": revoked
+            }),
+        )
+        .await?;
+        Ok(revoked)
+    }
+
     pub async fn get_session(
         &self,
         session_id: &str,
@@ -215,16 +331,14 @@ impl AuthState {
                 extract(epoch from created_at)::bigint as created_at,
                 extract(epoch from expires_at)::bigint as expires_at,
                 extract(epoch from last_seen_at)::bigint as last_seen_at,
+                extract(epoch from revoked_at)::bigint as revoked_at,
                 device_id,
                 user_agent
             from wallet_sessions
             where session_id = $1
-              and revoked_at is null
-              and expires_at > to_timestamp($2)
             "#,
         )
         .bind(session_id.trim())
-        .bind(now)
         .fetch_optional(&self.db)
         .await
         .map_err(|e| AppError::Internal(e.to_string()))?;
@@ -233,6 +347,16 @@ impl AuthState {
             return Ok(None);
         };
 
+        // A rotated-out access session id is simply no longer valid. Open
+        // event streams keep the id they connected with, so seeing one again
+        // is routine; only a replayed refresh token means theft.
+        if row.get::<Option<i64>, _>("revoked_at").is_some() {
+            return Ok(None);
+        }
+        if row.get::<i64, _>("expires_at") <= now {
+            return Ok(None);
+        }
+
         let stored_device_id: Option<String> = row.get("device_id");
         if let Some(expected) = stored_device_id.as_deref() {
             match presented_device_id
@@ -266,6 +390,7 @@ impl AuthState {
             last_seen_at: now,
             device_id: stored_device_id,
             user_agent: row.get("user_agent"),
+            refresh_token: None,
         }))
     }
 
@@ -280,37 +405,32 @@ impl AuthState {
         Ok(())
     }
 
-    pub async fn revoke_other_wallet_sessions(
+    /// The wallet's own session policy, or the server default.
+    pub async fn session_policy(&self, wallet: &str) -> Result<(SessionPolicy, bool), AppError> {
+        let wallet = normalize_wallet_for_chain(wallet, "");
+        Ok(match load_session_policy(&self.db, &wallet).await? {
+            Some(policy) => (policy, true),
+            None => (SessionPolicy::default_from_env(), false),
+        })
+    }
+
+    /// Revoke whatever active sessions `keep_session_id` pushes past the
+    /// wallet's session policy.
+    pub async fn apply_session_policy(
         &self,
         wallet: &str,
         keep_session_id: &str,
     ) -> Result<u64, AppError> {
         let wallet = normalize_wallet_for_chain(wallet, "");
-        let is_evm = wallet.starts_with("0x");
-        let result = crate::sqlx::query(
-            r#"
-            update wallet_sessions
-            set revoked_at = now(),
-                revoked_reason = 'superseded_by_new_login',
-                replaced_by_session_id = $3
-            where (
-                    ($4 = true and lower(wallet) = lower($1))
-                 or ($4 = false and wallet = $1)
-                  )
-              and session_id <> $2
-              and revoked_at is null
-              and expires_at > now()
-            "#,
-        )
-        .bind(&wallet)
-        .bind(keep_session_id.trim())
-        .bind(keep_session_id.trim())
-        .bind(is_evm)
-        .execute(&self.db)
-        .await
-        .map_err(|e| AppError::Internal(e.to_string()))?;
-
-        Ok(result.rows_affected())
+        let (policy, _) = self.session_policy(&wallet).await?;
+        let active = load_active_sessions(&self.db, &wallet).await?;
+        let trusted = list_trusted_devices(&self.db, &wallet)
+            .await?
+            .into_iter()
+            .map(|device| device.device_id)
+            .collect();
+        let revocations = sessions_to_revoke(policy, keep_session_id.trim(), &active, &trusted);
+        revoke_sessions(&self.db, &revocations, keep_session_id.trim()).await
     }
 
     pub async fn list_wallet_sessions(
@@ -402,29 +522,111 @@ impl AuthState {
         Ok(result.rows_affected() > 0)
     }
 
+    /// Trade a refresh token for a new access session and refresh token in
+    /// the same family. The old pair is rotated out; presenting it again
+    /// later revokes the family.
     pub async fn rotate_session(
         &self,
-        session_id: &str,
+        refresh_token: &str,
         presented_device_id: Option<&str>,
         user_agent: Option<&str>,
         ip_address: Option<&str>,
     ) -> Result<Option<WalletSession>, AppError> {
-        let Some(current) = self.get_session(session_id, presented_device_id).await? else {
+        let now = OffsetDateTime::now_utc();
+        let row = crate::sqlx::query(
+            r#"
+            select
+                session_id,
+                session_family_id,
+                wallet,
+                chain,
+                device_id,
+                user_agent,
+                extract(epoch from revoked_at)::bigint as revoked_at,
+                revoked_reason,
+                extract(epoch from refresh_expires_at)::bigint as refresh_expires_at
+            from wallet_sessions
+            where refresh_token_hash = $1
+            "#,
+        )
+        .bind(refresh_token_hash(refresh_token))
+        .fetch_optional(&self.db)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+        let Some(row) = row else {
             return Ok(None);
         };
+        let session_id: String = row.get("session_id");
+        let session_family_id: uuid::Uuid = row.get("session_family_id");
+        let chain: String = row.get("chain");
+        let wallet = normalize_wallet_for_chain(&row.get::<String, _>("wallet"), &chain);
 
+        let revoked_at: Option<i64> = row.get("revoked_at");
+        if revoked_at.is_some() {
+            let revoked_reason: Option<String> = row.get("revoked_reason");
+            if is_rotated_credential_reuse(
+                revoked_reason.as_deref(),
+                revoked_at,
+                now.unix_timestamp(),
+            ) {
+                self.revoke_session_family(session_family_id, &wallet, &session_id)
+                    .await?;
+                return Err(AppError::Auth(
+                    "Refresh token was already used; this login has been signed out everywhere"
+                        .into(),
+                ));
+            }
+            return Ok(None);
+        }
+        let refresh_expires_at: Option<i64> = row.get("refresh_expires_at");
+        if refresh_expires_at.is_none_or(|expires_at| expires_at <= now.unix_timestamp()) {
+            return Ok(None);
+        }
+
+        let stored_device_id: Option<String> = row.get("device_id");
+        let presented_device_id = presented_device_id
+            .map(str::trim)
+            .filter(|value| !value.is_empty());
+        if stored_device_id.is_some() && presented_device_id != stored_device_id.as_deref() {
+            return Ok(None);
+        }
+
+        let stored_user_agent: Option<String> = row.get("user_agent");
         let new_session_id = uuid::Uuid::new_v4().to_string();
-        let now = OffsetDateTime::now_utc();
         let expires_at = now + time::Duration::seconds(SESSION_TTL_SECONDS);
-
-        let family_row = crate::sqlx::query(
-            "select session_family_id from wallet_sessions where session_id = $1",
+        let new_refresh_expires_at = now + time::Duration::seconds(REFRESH_TTL_SECONDS);
+        let (new_refresh_token, new_refresh_hash) = issue_refresh_token();
+        let device_id = presented_device_id.map(str::to_string).or(stored_device_id);
+        let user_agent = user_agent.map(str::to_string).or(stored_user_agent);
+
+        let mut tx = self
+            .db
+            .begin()
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+
+        // Only one caller can rotate a given refresh token.
+        let claimed = crate::sqlx::query(
+            r#"
+            update wallet_sessions
+            set revoked_at = now(),
+                revoked_reason = $3,
+                replaced_by_session_id = $2
+            where session_id = $1
+              and revoked_at is null
+            "#,
         )
-        .bind(session_id.trim())
-        .fetch_one(&self.db)
+        .bind(&session_id)
+        .bind(&new_session_id)
+        .bind(REVOKED_ROTATED)
+        .execute(&mut *tx)
         .await
-        .map_err(|e| AppError::Internal(e.to_string()))?;
-        let session_family_id: uuid::Uuid = family_row.get("session_family_id");
+        .map_err(|e| AppError::Internal(e.to_string()))?
+        .rows_affected();
+        if claimed == 0 {
+            return Ok(None);
+        }
 
         crate::sqlx::query(
             r#"
@@ -438,7 +640,9 @@ impl AuthState {
                 expires_at,
                 device_id,
                 user_agent,
-                ip_address
+                ip_address,
+                refresh_token_hash,
+                refresh_expires_at
             )
             values (
                 $1,
@@ -450,49 +654,78 @@ impl AuthState {
                 to_timestamp($6),
                 $7,
                 $8,
-                $9
+                $9,
+                $10,
+                to_timestamp($11)
             )
             "#,
         )
         .bind(&new_session_id)
         .bind(session_family_id)
-        .bind(&current.wallet)
-        .bind(&current.chain)
+        .bind(&wallet)
+        .bind(&chain)
         .bind(now.unix_timestamp())
         .bind(expires_at.unix_timestamp())
-        .bind(presented_device_id.or(current.device_id.as_deref()))
-        .bind(user_agent.or(current.user_agent.as_deref()))
+        .bind(device_id.as_deref())
+        .bind(user_agent.as_deref())
         .bind(ip_address)
-        .execute(&self.db)
+        .bind(&new_refresh_hash)
+        .bind(new_refresh_expires_at.unix_timestamp())
+        .execute(&mut *tx)
         .await
         .map_err(|e| AppError::Internal(e.to_string()))?;
 
-        crate::sqlx::query(
-            r#"
-            update wallet_sessions
-            set revoked_at = now(),
-                revoked_reason = 'rotated',
-                replaced_by_session_id = $2
-            where session_id = $1
-            "#,
-        )
-        .bind(session_id.trim())
-        .bind(&new_session_id)
-        .execute(&self.db)
-        .await
-        .map_err(|e| AppError::Internal(e.to_string()))?;
+        tx.commit()
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+        // The policy may have tightened since this login was issued.
+        self.apply_session_policy(&wallet, &new_session_id).await?;
 
         Ok(Some(WalletSession {
             session_id: new_session_id,
-            wallet: current.wallet,
-            chain: current.chain,
+            wallet,
+            chain,
             created_at: now.unix_timestamp(),
             expires_at: expires_at.unix_timestamp(),
             last_seen_at: now.unix_timestamp(),
-            device_id: presented_device_id
-                .map(str::to_string)
-                .or(current.device_id),
-            user_agent: user_agent.map(str::to_string).or(current.user_agent),
+            device_id,
+            user_agent,
+            refresh_token: Some(new_refresh_token),
         }))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_a_rotated_credential_past_the_grace_period_counts_as_reuse() {
+        let now = 1_000_000;
+        assert!(is_rotated_credential_reuse(
+            Some(REVOKED_ROTATED),
+            Some(now - ROTATION_GRACE_SECONDS - 1),
+            now
+        ));
+        assert!(!is_rotated_credential_reuse(
+            Some(REVOKED_ROTATED),
+            Some(now - 5),
+            now
+        ));
+        assert!(!is_rotated_credential_reuse(
+            Some("logout"),
+            Some(now - 3_600),
+            now
+        ));
+        assert!(!is_rotated_credential_reuse(None, None, now));
+    }
+
+    #[test]
+    fn refresh_tokens_are_stored_hashed() {
+        let (token, hash) = issue_refresh_token();
+        assert!(token.starts_with("rt_"));
+        assert_eq!(hash, refresh_token_hash(&token));
+        assert_ne!(hash, token);
+        assert_ne!(issue_refresh_token().0, token);
+    }
+}
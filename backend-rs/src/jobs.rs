@@ -0,0 +1,144 @@
+// src/jobs.rs
+
+use chrono::{DateTime, Utc};
+
+use crate::error::AppError;
+use crate::sqlx::{self, PgPool, Row};
+
+/// A job is retried with backoff until it has run this many times.
+pub const MAX_JOB_ATTEMPTS: i32 = 5;
+
+/// A `running` job whose worker has held it this long is assumed lost to a
+/// crash or restart and is claimed again.
+const STALE_LOCK_SECS: i64 = 600;
+
+#[derive(Debug, Clone)]
+pub struct ScheduledJob {
+    pub id: uuid::Uuid,
+    pub kind: String,
+    pub payload: serde_json::Value,
+    pub attempts: i32,
+}
+
+fn db_error(e: sqlx::Error) -> AppError {
+    AppError::Internal(e.to_string())
+}
+
+/// How long to wait before running a job again after its `attempts`-th failure.
+pub fn retry_delay(attempts: i32) -> chrono::Duration {
+    let exponent = attempts.clamp(1, 10) as u32 - 1;
+    chrono::Duration::seconds((60 * 2_i64.pow(exponent)).min(3_600))
+}
+
+/// Queue a job. A `dedupe_key` makes scheduling idempotent: a second job
+/// with the same key is dropped.
+pub async fn schedule_job(
+    db: &PgPool,
+    kind: &str,
+    run_at: DateTime<Utc>,
+    payload: serde_json::Value,
+    dedupe_key: Option<&str>,
+) -> Result<(), AppError> {
+    sqlx::query(
+        r#"
+        insert into scheduled_jobs (id, kind, run_at, payload, dedupe_key)
+        values ($1, $2, $3, $4, $5)
+        on conflict (dedupe_key) do nothing
+        "#,
+    )
+    .bind(uuid::Uuid::new_v4())
+    .bind(kind)
+    .bind(run_at)
+    .bind(payload)
+    .bind(dedupe_key)
+    .execute(db)
+    .await
+    .map_err(db_error)?;
+    Ok(())
+}
+
+/// Claim up to `limit` due jobs. `skip locked` lets several server
+/// processes share one queue without running a job twice.
+pub async fn claim_due_jobs(db: &PgPool, limit: i64) -> Result<Vec<ScheduledJob>, AppError> {
+    let rows = sqlx::query(
+        r#"
+        update scheduled_jobs
+        set status = 'running', locked_at = now(), attempts = attempts + 1
+        where id in (
+            select id
+            from scheduled_jobs
+            where (status = 'pending' and run_at <= now())
+               or (status = 'running' and locked_at < now() - make_interval(secs => $2))
+            order by run_at
+            limit $1
+            for update skip locked
+        )
+        returning id, kind, payload, attempts
+        "#,
+    )
+    .bind(limit)
+    .bind(STALE_LOCK_SECS as f64)
+    .fetch_all(db)
+    .await
+    .map_err(db_error)?;
+
+    Ok(rows
+        .iter()
+        .map(|row| ScheduledJob {
+            id: row.get("id"),
+            kind: row.get("kind"),
+            payload: row.get("payload"),
+            attempts: row.get("attempts"),
+        })
+        .collect())
+}
+
+pub async fn complete_job(db: &PgPool, job_id: uuid::Uuid) -> Result<(), AppError> {
+    sqlx::query(
+        "update scheduled_jobs set status = 'done', completed_at = now(), locked_at = null where id = $1",
+    )
+    .bind(job_id)
+    .execute(db)
+    .await
+    .map_err(db_error)?;
+    Ok(())
+}
+
+/// Put a failed job back in the queue, or give up on it once it has used
+/// all its attempts.
+pub async fn fail_job(db: &PgPool, job: &ScheduledJob, error: &str) -> Result<(), AppError> {
+    let (status, run_at) = if job.attempts >= MAX_JOB_ATTEMPTS {
+        ("failed", Utc::now())
+    } else {
+        ("pending", Utc::now() + retry_delay(job.attempts))
+    };
+    sqlx::query(
+        r#"
+        update scheduled_jobs
+        set status = $2, run_at = $3, last_error = $4, locked_at = null
+        where id = $1
+        "#,
+    )
+    .bind(job.id)
+    .bind(status)
+    .bind(run_at)
+    .bind(error)
+    .execute(db)
+    .await
+    .map_err(db_error)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retries_back_off_up_to_an_hour() {
+        assert_eq!(retry_delay(1), chrono::Duration::seconds(60));
+        assert_eq!(retry_delay(2), chrono::Duration::seconds(120));
+        assert_eq!(retry_delay(4), chrono::Duration::seconds(480));
+        assert_eq!(retry_delay(9), chrono::Duration::seconds(3_600));
+        assert_eq!(retry_delay(0), retry_delay(1));
+    }
+}
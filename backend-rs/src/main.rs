@@ -7,18 +7,24 @@ mod config;
 mod crypto;
 mod delivery;
 mod error;
+mod event_stream;
 mod identity;
 mod identity_web;
+mod jobs;
 mod models;
 mod pqc;
 mod routes;
 mod sanitizer;
+mod signing_workflow;
 mod sqlx;
 mod storage;
+mod webhooks;
 
-use axum::extract::{Multipart, Path, Query, State};
+use axum::body::{Body, Bytes};
+use axum::extract::{ConnectInfo, Multipart, Path, Query, State};
 use axum::http::HeaderMap;
 use axum::http::{header, HeaderValue, Method, StatusCode};
+use axum::response::sse::{Event, KeepAlive, Sse};
 use axum::response::{Html, IntoResponse, Response};
 use axum::routing::{get, post};
 use axum::{Json, Router};
@@ -28,43 +34,98 @@ use argon2::Argon2;
 use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
 use base64::Engine;
 use clap::Parser;
-use cli::commands::{auth, c2c as cli_c2c, doc, wallet};
+use cli::commands::{auth, c2c as cli_c2c, doc, evidence, ledger, scan, scan_url, wallet};
 use cli::parser::{Cli, Commands};
 use hmac::{Hmac, Mac};
 use rand::RngCore;
 use serde::Deserialize;
 use serde_json::json;
 use sha1::Sha1;
-use sha2::Sha256;
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
 
 use tower_http::cors::CorsLayer;
 use tower_http::services::{ServeDir, ServeFile};
 
+use crate::arweave::merkle::MerkleTree;
+use crate::c2c::evidence::event_chain_hash_hex;
+use crate::c2c::filetrail::FileAction;
+use crate::c2c::ledger::{CustodyLedger, LedgerEvent, NewLedgerEvent, PgLedger};
+use crate::c2c::verify::{document_sign_message, HYBRID_SIGNATURE_TYPE};
+use crate::c2c::workflow::{record_document_event, AnchorMode, DocumentEvent};
 use crate::crypto::aes_gcm;
 use crate::crypto::canonical::{
     canonicalize::canonical_json,
     kem::mlkem_generate_keypair_b64,
     keystore::{load_mlkem_keypair_if_exists, MlKemKeypairFile},
-    CanonicalDocumentV1, DocumentEnvelopeV1,
+    envelope_content_type, read_document_envelope, segment_count, CanonicalDocumentV1,
+    DocumentEnvelopeV2, DocumentStreamEnvelopeV1, StreamCipher, StreamKeyV1,
+    ENVELOPE_V1_CONTENT_TYPE, ENVELOPE_V2_CONTENT_TYPE, STREAM_SEGMENT_SIZE,
 };
 use crate::delivery::{send_email_invite, send_sms_invite, DeliveryOutcome};
 use crate::error::AppError;
-use crate::identity_web::evm::{
-    evm_login_message, verify_evm_signature, EvmNonceResponse, EvmVerifyRequest,
+use crate::event_stream::{
+    notify_document_event, spawn_event_listener, EventNotice, EventSender, EVENT_CHANNEL,
 };
-use crate::identity_web::sol::verify_solana_signature;
+use crate::identity::proof_of_key::{
+    decode_pq_public_key, key_binding_message, pq_key_fingerprint_hex, verify_key_binding,
+    verify_wallet_signature, KeyBindingProof, BINDING_MAX_AGE_SECS,
+};
+use crate::identity::registry::{
+    active_pq_key, pq_key_history, pq_key_history_for_document, register_pq_key, revoke_pq_key,
+    PqSigningKey,
+};
+use crate::identity_web::devices::{
+    list_account_events, list_trusted_devices, record_account_event, save_session_policy,
+    trust_device, untrust_device, SessionPolicy, TrustedDevice, ACCOUNT_EVENT_DEVICE_TRUSTED,
+    ACCOUNT_EVENT_DEVICE_UNTRUSTED, ACCOUNT_EVENT_SESSION_POLICY_CHANGED,
+};
+use crate::identity_web::eip1271::{
+    contract_verifier_from_env, verify_evm_wallet_signature, EvmSignatureMethod,
+    SharedContractVerifier,
+};
+use crate::identity_web::evm::{verify_evm_signature, verify_siwe_login, EvmVerifyRequest};
+use crate::identity_web::sign_in::{
+    SignInChain, SignInNonceRequest, SignInNonceResponse, SignInPolicy,
+};
+use crate::identity_web::sol::{verify_siws_login, verify_solana_signature};
 use crate::identity_web::state::WalletSession;
+use crate::jobs::{
+    claim_due_jobs, complete_job, fail_job, schedule_job, ScheduledJob, MAX_JOB_ATTEMPTS,
+};
 use crate::models::{
-    AgentRegisterRequest, AgentSignRequest, AgentVersionRequest, DocumentPolicyUpdateRequest,
-    InboxActionRequest, PublicEnvelopeSignRequest, ShareRequest, SignRequest,
-    SignerAnnotationField,
+    AgentRegisterRequest, AgentSignRequest, AgentVersionRequest, ChunkedUploadInitRequest,
+    DocumentPolicyUpdateRequest, InboxActionRequest, PqKeyChallengeRequest, PqKeyRegisterRequest,
+    PqKeyRevokeRequest, PublicEnvelopeSignRequest, QuarantineRequest, SessionPolicyRequest,
+    SessionRefreshRequest, ShareRequest, SignRequest, SignerAnnotationField,
+    SigningWorkflowRequest, TrustedDeviceRequest, WebhookSubscriptionRequest,
+    WorkflowCancelRequest, WorkflowSignerRequest,
 };
 use crate::pqc::dilithium;
 use crate::pqc::sha3 as pqc_sha3;
+use crate::signing_workflow::{
+    cancel_signing_workflow, claim_signer_notification, complete_signing_workflow,
+    insert_signing_workflow, list_signing_workflows, load_signing_workflow,
+    mark_workflow_signers_signed, release_signer_notification, resolve_steps, set_signer_envelope,
+    NewSigningWorkflow, NewWorkflowSigner, SigningWorkflow, WorkflowSigner, WorkflowSignerMatch,
+    ROUTING_SEQUENTIAL, SIGNER_SIGNED, WORKFLOW_ACTIVE,
+};
 use crate::sqlx::postgres::PgPoolOptions;
 use crate::sqlx::{PgPool, Row};
-use storage::supabase::SupabaseStorage;
+use crate::webhooks::{
+    disable_webhook_subscription, enqueue_webhook_deliveries, generate_webhook_secret,
+    insert_webhook_subscription, list_webhook_deliveries, list_webhook_subscriptions,
+    load_webhook_subscription, normalize_event_filter, replay_webhook_delivery,
+    resolve_webhook_target, send_webhook_delivery, webhook_pq_key, JOB_WEBHOOK_DELIVERY,
+};
+use sanitizer::ip_reputation::{
+    ip_reputation_from_env, IpPolicyAction, IpReputationPolicy, SharedIpReputation, TrustedProxies,
+};
+use sanitizer::{
+    ScanInput, ScanPipeline, ScanReport, ScanVerdict, SharedScanPipeline, WindowedScan,
+};
+use storage::{blob_store_from_env, stream_segment_path, BlobStore, SharedBlobStore};
 
 // ================================================================
 // APP STATE
@@ -74,7 +135,12 @@ use storage::supabase::SupabaseStorage;
 struct AppState {
     auth: identity_web::AuthState,
     db: PgPool,
-    storage: SupabaseStorage,
+    events: EventSender,
+    storage: SharedBlobStore,
+    scanner: SharedScanPipeline,
+    ip_reputation: Option<SharedIpReputation>,
+    trusted_proxies: std::sync::Arc<TrustedProxies>,
+    contracts: Option<SharedContractVerifier>,
     admin_wallets: Vec<AdminWalletIdentity>,
     admin_console_path: String,
 }
@@ -121,6 +187,7 @@ struct DocumentAccessRecord {
     arweave_tx: Option<String>,
     encryption_mode: String,
     ciphertext_hash_hex: Option<String>,
+    quarantined_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 struct CreatedDocumentRecord {
@@ -145,7 +212,7 @@ struct AgentIdentityRecord {
 }
 
 struct DocumentBytesResponse {
-    bytes: Vec<u8>,
+    body: Body,
     mime_type: String,
     label: Option<String>,
     hash_hex: String,
@@ -157,6 +224,8 @@ struct DocumentBytesResponse {
 
 const ENCRYPTION_MODE_SERVER_MANAGED: &str = "pq_envelope_server_managed";
 const ENCRYPTION_MODE_BROWSER_ENCRYPTED: &str = "pq_envelope_browser_encrypted";
+/// Server-managed CEK with the ciphertext split into STREAM segments.
+const ENCRYPTION_MODE_SERVER_STREAM: &str = "pq_stream_server_managed";
 const CLIENT_ENCRYPTED_UPLOAD_MODE: &str = "browser_pq_envelope_v1";
 
 // ================================================================
@@ -173,6 +242,18 @@ async fn main() -> anyhow::Result<()> {
         Commands::Wallet { action } => wallet::handle_wallet(action).await,
         Commands::Doc { action } => doc::handle_doc(action).await,
         Commands::C2c { action } => cli_c2c::handle_c2c(action).await,
+        Commands::Evidence { action } => evidence::handle_evidence(action).await,
+        Commands::Ledger { action } => ledger::handle_ledger(action).await,
+        Commands::Scan {
+            path,
+            mime_type,
+            json,
+        } => scan::handle_scan(path, mime_type, json).await,
+        Commands::ScanUrl {
+            url,
+            mime_type,
+            json,
+        } => scan_url::handle_scan_url(url, mime_type, json).await,
     };
 
     if let Err(err) = &result {
@@ -204,19 +285,65 @@ async fn start_server() -> anyhow::Result<()> {
     let admin_console_path = admin_console_path_from_env();
 
     eprintln!("boot: loading storage environment");
-    let storage = SupabaseStorage::new(
-        std::env::var("SUPABASE_URL")?,
-        std::env::var("SUPABASE_SERVICE_ROLE_KEY")?,
-        std::env::var("SUPABASE_BUCKET")?,
+    let storage = blob_store_from_env()?;
+    eprintln!("boot: storage backend = {}", storage.backend_name());
+    let scanner = std::sync::Arc::new(ScanPipeline::from_env());
+    eprintln!(
+        "boot: upload scanners = {}",
+        scanner.scanner_names().join(", ")
     );
+    let ip_reputation = ip_reputation_from_env()?;
+    eprintln!(
+        "boot: public link ip reputation = {}",
+        ip_reputation
+            .as_ref()
+            .map(|provider| provider.name())
+            .unwrap_or("disabled")
+    );
+    let trusted_proxies = std::sync::Arc::new(TrustedProxies::from_env()?);
+    eprintln!("boot: trusted proxies = {}", trusted_proxies.describe());
+    let sign_in = SignInPolicy::from_env(SignInChain::Ethereum)?;
+    eprintln!(
+        "boot: wallet sign-in domain = {} ({})",
+        sign_in.domain, sign_in.uri
+    );
+    let contracts = contract_verifier_from_env();
+    eprintln!(
+        "boot: eip-1271 contract wallets = {}",
+        contracts
+            .as_ref()
+            .map(|verifier| verifier.name())
+            .unwrap_or("disabled")
+    );
+
+    if let Some(window) = anchor_batch_window() {
+        eprintln!("boot: arweave anchor batching every {}s", window.as_secs());
+        spawn_anchor_batcher(pool.clone(), window);
+    }
+    if let Some(interval) = arweave_poll_interval() {
+        eprintln!("boot: arweave status polling every {}s", interval.as_secs());
+        spawn_arweave_status_poller(pool.clone(), interval);
+    }
+
+    eprintln!("boot: live custody events on channel {EVENT_CHANNEL}");
+    let events = spawn_event_listener(pool.clone());
 
     let state = AppState {
         auth: auth_state,
         db: pool,
+        events,
         storage,
+        scanner,
+        ip_reputation,
+        trusted_proxies,
+        contracts,
         admin_wallets,
         admin_console_path: admin_console_path.clone(),
     };
+    if let Some(interval) = job_poll_interval() {
+        eprintln!("boot: scheduled jobs every {}s", interval.as_secs());
+        spawn_job_scheduler(state.clone(), interval);
+    }
 
     let static_files = ServeDir::new("web").append_index_html_on_directories(true);
     let landing_page = ServeFile::new("web/landing.html");
@@ -235,6 +362,7 @@ async fn start_server() -> anyhow::Result<()> {
         .route("/api/identity/sol/nonce", post(sol_nonce_handler_app))
         .route("/api/identity/sol/verify", post(sol_verify_handler_app))
         .route("/api/public/verify", post(public_verify_handler))
+        .route("/api/public/verify/:hash", get(public_verify_hash_handler))
         .route("/api/analytics/track", post(analytics_track_handler))
         .route("/api/public/envelope/:token", get(public_envelope_handler))
         .route(
@@ -265,12 +393,26 @@ async fn start_server() -> anyhow::Result<()> {
             "/api/admin/growth/overview",
             get(admin_growth_overview_handler),
         )
+        .route("/api/admin/quarantine", get(admin_quarantine_list_handler))
+        .route(
+            "/api/admin/quarantine/:id",
+            post(admin_quarantine_doc_handler),
+        )
+        .route(
+            "/api/admin/quarantine/:id/release",
+            post(admin_release_quarantine_handler),
+        )
+        .route(
+            "/api/admin/quarantine/:id/purge",
+            post(admin_purge_quarantine_handler),
+        )
         .route("/api/overview", get(overview_handler))
         .route("/api/account/status", get(account_status_handler))
         .route("/api/doc/list", get(list_docs_handler))
         .route("/api/shared", get(list_shared_handler))
         .route("/api/activity/shared", get(list_shared_activity_handler))
         .route("/api/doc/:id/events", get(list_doc_events_handler))
+        .route("/api/events/stream", get(custody_event_stream_handler))
         .route("/api/doc/:id/evidence", get(export_doc_evidence_handler))
         .route(
             "/api/doc/:id/evidence/anchor",
@@ -281,20 +423,57 @@ async fn start_server() -> anyhow::Result<()> {
             get(get_document_policy_handler).post(set_document_policy_handler),
         )
         .route("/api/doc/upload", post(upload_doc_handler))
+        .route("/api/doc/upload/chunked", post(init_chunked_upload_handler))
+        .route(
+            "/api/doc/upload/chunked/:upload_id",
+            get(chunked_upload_status_handler),
+        )
+        .route(
+            "/api/doc/upload/chunked/:upload_id/segment/:index",
+            post(put_upload_segment_handler),
+        )
+        .route(
+            "/api/doc/upload/chunked/:upload_id/complete",
+            post(complete_chunked_upload_handler),
+        )
         .route(
             "/api/doc/:id/version",
             post(create_document_version_handler),
         )
         .route("/api/doc/:id/review", get(review_doc_handler))
         .route("/api/doc/:id/blob", get(doc_blob_handler))
+        .route("/api/doc/:id/envelope", get(doc_envelope_handler))
         .route("/api/doc/:id/download", get(download_doc_handler))
         .route("/api/doc/:id/sign", post(sign_doc_handler))
         .route("/api/doc/:id/delete", post(delete_doc_handler))
+        .route("/api/doc/:id/quarantine", post(quarantine_doc_handler))
         .route("/api/doc/:id/share", post(share_doc_handler))
         .route(
             "/api/doc/:id/share/:envelope_id/revoke",
             post(revoke_share_handler),
         )
+        .route(
+            "/api/doc/:id/workflows",
+            get(list_signing_workflows_handler).post(create_signing_workflow_handler),
+        )
+        .route("/api/workflows/:id", get(signing_workflow_handler))
+        .route(
+            "/api/workflows/:id/cancel",
+            post(cancel_signing_workflow_handler),
+        )
+        .route(
+            "/api/webhooks",
+            get(list_webhooks_handler).post(create_webhook_handler),
+        )
+        .route("/api/webhooks/:id/disable", post(disable_webhook_handler))
+        .route(
+            "/api/webhooks/:id/deliveries",
+            get(webhook_deliveries_handler),
+        )
+        .route(
+            "/api/webhooks/deliveries/:id/replay",
+            post(replay_webhook_delivery_handler),
+        )
         .route("/api/agent/doc/:id/review", get(agent_review_doc_handler))
         .route("/api/agent/doc/:id/sign", post(agent_sign_doc_handler))
         .route(
@@ -310,7 +489,23 @@ async fn start_server() -> anyhow::Result<()> {
             "/auth/session/:session_id/revoke",
             post(revoke_specific_session_handler),
         )
+        .route("/auth/session-policy", post(update_session_policy_handler))
+        .route("/auth/trusted-devices", post(trust_device_handler))
+        .route(
+            "/auth/trusted-devices/:device_id/remove",
+            post(untrust_device_handler),
+        )
         .route("/auth/logout", post(logout_handler))
+        .route("/api/identity/pq-key", get(pq_key_handler))
+        .route(
+            "/api/identity/pq-key/challenge",
+            post(pq_key_challenge_handler),
+        )
+        .route(
+            "/api/identity/pq-key/register",
+            post(pq_key_register_handler),
+        )
+        .route("/api/identity/pq-key/revoke", post(pq_key_revoke_handler))
         .with_state(state)
         .layer(configured_cors_layer()?);
     let app = app.route(admin_console_path.as_str(), get(admin_console_handler));
@@ -326,13 +521,17 @@ async fn start_server() -> anyhow::Result<()> {
         .ok()
         .and_then(|value| value.trim().parse::<u16>().ok())
         .unwrap_or(4100);
-    let addr: std::net::SocketAddr = format!("0.0.0.0:{port}").parse().unwrap();
+    let addr: SocketAddr = format!("0.0.0.0:{port}").parse().unwrap();
 
     eprintln!("boot: binding server to http://{addr}");
 
     let listener = tokio::net::TcpListener::bind(addr).await?;
     eprintln!("boot: listener ready");
-    axum::serve(listener, app).await?;
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await?;
 
     Ok(())
 }
@@ -734,6 +933,69 @@ async fn ensure_runtime_schema(db: &PgPool) -> anyhow::Result<()> {
     )
     .execute(db)
     .await?;
+    sqlx::query(
+        "alter table wallet_sessions add column if not exists refresh_token_hash text null",
+    )
+    .execute(db)
+    .await?;
+    sqlx::query(
+        "alter table wallet_sessions add column if not exists refresh_expires_at timestamptz null",
+    )
+    .execute(db)
+    .await?;
+    sqlx::query(
+        "create unique index if not exists idx_wallet_sessions_refresh_token on wallet_sessions (refresh_token_hash) where refresh_token_hash is not null",
+    )
+    .execute(db)
+    .await?;
+    sqlx::query(
+        "create index if not exists idx_wallet_sessions_family on wallet_sessions (session_family_id)",
+    )
+    .execute(db)
+    .await?;
+    sqlx::query(
+        r#"
+        create table if not exists wallet_session_policies (
+            wallet text primary key,
+            mode text not null,
+            max_devices integer null,
+            updated_at timestamptz not null default now()
+        )
+        "#,
+    )
+    .execute(db)
+    .await?;
+    sqlx::query(
+        r#"
+        create table if not exists wallet_trusted_devices (
+            wallet text not null,
+            device_id text not null,
+            name text not null,
+            trusted_at timestamptz not null default now(),
+            primary key (wallet, device_id)
+        )
+        "#,
+    )
+    .execute(db)
+    .await?;
+    sqlx::query(
+        r#"
+        create table if not exists wallet_account_events (
+            id uuid primary key,
+            wallet text not null,
+            event_type text not null,
+            payload jsonb not null default '{}'::jsonb,
+            created_at timestamptz not null default now()
+        )
+        "#,
+    )
+    .execute(db)
+    .await?;
+    sqlx::query(
+        "create index if not exists idx_wallet_account_events_wallet on wallet_account_events (wallet, created_at desc)",
+    )
+    .execute(db)
+    .await?;
     sqlx::query(
         r#"
         create table if not exists growth_events (
@@ -875,833 +1137,1158 @@ async fn ensure_runtime_schema(db: &PgPool) -> anyhow::Result<()> {
     )
     .execute(db)
     .await?;
-    Ok(())
-}
-
-#[derive(Deserialize)]
-struct AdminGrowthQuery {
-    days: Option<i64>,
-}
-
-fn clamp_admin_days(days: Option<i64>) -> i64 {
-    days.unwrap_or(30).clamp(7, 180)
-}
-
-#[derive(Deserialize)]
-struct AdminAuthBootstrapRequest {
-    password: String,
-}
-
-#[derive(Deserialize)]
-struct AdminAuthLoginRequest {
-    password: String,
-    totp_code: Option<String>,
-}
-
-#[derive(Deserialize)]
-struct AdminAuthChangePasswordRequest {
-    current_password: String,
-    new_password: String,
-    totp_code: Option<String>,
-}
-
-#[derive(Deserialize)]
-struct AdminAuthMfaEnrollRequest {
-    current_password: String,
-}
-
-#[derive(Deserialize)]
-struct AdminAuthMfaVerifyRequest {
-    current_password: String,
-    totp_code: String,
-}
-
-#[derive(Deserialize)]
-struct AdminAuthMfaDisableRequest {
-    current_password: String,
-    totp_code: String,
-}
-
-fn admin_console_session_hours() -> i64 {
-    std::env::var("ADMIN_CONSOLE_SESSION_HOURS")
-        .ok()
-        .and_then(|value| value.trim().parse::<i64>().ok())
-        .unwrap_or(12)
-        .clamp(1, 24 * 7)
-}
-
-fn admin_console_password_min_length() -> usize {
-    12
-}
-
-fn admin_session_header(headers: &HeaderMap) -> Option<String> {
-    header_value(headers, "x-admin-session-id")
-        .map(|value| value.trim().to_string())
-        .filter(|value| !value.is_empty())
-}
-
-fn validate_admin_password_strength(password: &str) -> Result<(), AppError> {
-    if password.trim().len() < admin_console_password_min_length() {
-        return Err(AppError::BadRequest(format!(
-            "Admin password must be at least {} characters",
-            admin_console_password_min_length()
-        )));
-    }
-    Ok(())
-}
-
-fn hash_admin_password(password: &str) -> Result<String, AppError> {
-    let salt = SaltString::generate(&mut OsRng);
-    Argon2::default()
-        .hash_password(password.as_bytes(), &salt)
-        .map(|hash| hash.to_string())
-        .map_err(|err| AppError::Internal(format!("admin password hash failed: {err}")))
-}
-
-fn verify_admin_password(hash: &str, password: &str) -> Result<bool, AppError> {
-    let parsed = PasswordHash::new(hash)
-        .map_err(|err| AppError::Internal(format!("stored admin password hash invalid: {err}")))?;
-    Ok(Argon2::default()
-        .verify_password(password.as_bytes(), &parsed)
-        .is_ok())
-}
-
-fn random_admin_session_id() -> String {
-    uuid::Uuid::new_v4().simple().to_string()
-}
-
-fn normalize_totp_code(value: &str) -> String {
-    value.chars().filter(|ch| ch.is_ascii_digit()).collect()
-}
-
-fn base32_encode(bytes: &[u8]) -> String {
-    const ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
-    let mut output = String::new();
-    let mut buffer: u16 = 0;
-    let mut bits_left = 0u8;
-
-    for byte in bytes {
-        buffer = (buffer << 8) | u16::from(*byte);
-        bits_left += 8;
-        while bits_left >= 5 {
-            let idx = ((buffer >> (bits_left - 5)) & 0x1f) as usize;
-            output.push(ALPHABET[idx] as char);
-            bits_left -= 5;
-        }
-    }
-
-    if bits_left > 0 {
-        let idx = ((buffer << (5 - bits_left)) & 0x1f) as usize;
-        output.push(ALPHABET[idx] as char);
-    }
-
-    output
-}
-
-fn base32_decode(input: &str) -> Result<Vec<u8>, AppError> {
-    let mut buffer: u32 = 0;
-    let mut bits_left = 0u8;
-    let mut output = Vec::new();
-
-    for ch in input.chars() {
-        if ch == '=' || ch.is_ascii_whitespace() || ch == '-' {
-            continue;
-        }
-        let value = match ch.to_ascii_uppercase() {
-            'A'..='Z' => ch.to_ascii_uppercase() as u8 - b'A',
-            '2'..='7' => ch as u8 - b'2' + 26,
-            _ => {
-                return Err(AppError::BadRequest(
-                    "TOTP secret contains invalid Base32 characters".into(),
-                ))
-            }
-        };
-        buffer = (buffer << 5) | u32::from(value);
-        bits_left += 5;
-        while bits_left >= 8 {
-            output.push(((buffer >> (bits_left - 8)) & 0xff) as u8);
-            bits_left -= 8;
-        }
-    }
-
-    Ok(output)
-}
-
-fn generate_totp_secret_b32() -> String {
-    let mut bytes = [0u8; 20];
-    rand::thread_rng().fill_bytes(&mut bytes);
-    base32_encode(&bytes)
-}
-
-fn compute_totp_code(secret_b32: &str, unix_ts: i64) -> Result<String, AppError> {
-    let secret = base32_decode(secret_b32)?;
-    let counter = (unix_ts.max(0) as u64) / 30;
-    let mut mac = Hmac::<Sha1>::new_from_slice(&secret)
-        .map_err(|err| AppError::Internal(format!("totp hmac init failed: {err}")))?;
-    mac.update(&counter.to_be_bytes());
-    let digest = mac.finalize().into_bytes();
-    let offset = usize::from(digest[digest.len() - 1] & 0x0f);
-    let binary = ((u32::from(digest[offset]) & 0x7f) << 24)
-        | (u32::from(digest[offset + 1]) << 16)
-        | (u32::from(digest[offset + 2]) << 8)
-        | u32::from(digest[offset + 3]);
-    Ok(format!("{:06}", binary % 1_000_000))
-}
-
-fn verify_totp_code(secret_b32: &str, code: &str) -> Result<bool, AppError> {
-    let normalized = normalize_totp_code(code);
-    if normalized.len() != 6 {
-        return Ok(false);
-    }
-    let now = chrono::Utc::now().timestamp();
-    for offset in -1..=1 {
-        let candidate = compute_totp_code(secret_b32, now + (offset * 30))?;
-        if candidate == normalized {
-            return Ok(true);
-        }
-    }
-    Ok(false)
-}
-
-fn admin_totp_otpauth_url(wallet: &str, secret_b32: &str) -> String {
-    let issuer = "TIDBIT-share-WEAVE";
-    let label = format!("{issuer}:{wallet}");
-    format!(
-        "otpauth://totp/{}?secret={}&issuer={}&algorithm=SHA1&digits=6&period=30",
-        urlencoding::encode(&label),
-        secret_b32,
-        urlencoding::encode(issuer)
+    sqlx::query(
+        r#"
+        create table if not exists anchor_batches (
+            id uuid primary key,
+            root_hex text not null,
+            leaf_count integer not null,
+            arweave_tx text null,
+            error text null,
+            created_at timestamptz not null default now(),
+            anchored_at timestamptz null
+        )
+        "#,
     )
-}
-
-async fn load_admin_credential(
-    db: &PgPool,
-    wallet: &str,
-) -> Result<Option<AdminCredentialRecord>, AppError> {
-    let row = sqlx::query(
+    .execute(db)
+    .await?;
+    sqlx::query(
         r#"
-        select wallet, password_hash, mfa_enabled, totp_secret_b32, pending_totp_secret_b32, created_at, updated_at
-        from admin_credentials
-        where wallet = $1
+        create table if not exists anchor_leaves (
+            id uuid primary key,
+            doc_id uuid not null,
+            share_id uuid null,
+            event_id uuid null,
+            kind text not null,
+            hash_hex text not null,
+            batch_id uuid null,
+            claimed_at timestamptz null,
+            proof_json jsonb null,
+            created_at timestamptz not null default now()
+        )
         "#,
     )
-    .bind(wallet)
-    .fetch_optional(db)
-    .await
-    .map_err(|e| AppError::Internal(e.to_string()))?;
-
-    Ok(row.map(|row| AdminCredentialRecord {
-        wallet: row.get("wallet"),
-        password_hash: row.get("password_hash"),
-        mfa_enabled: row.get("mfa_enabled"),
-        totp_secret_b32: row.get("totp_secret_b32"),
-        pending_totp_secret_b32: row.get("pending_totp_secret_b32"),
-        created_at: row.get("created_at"),
-        updated_at: row.get("updated_at"),
-    }))
-}
-
-async fn load_active_admin_console_session(
-    db: &PgPool,
-    admin_session_id: &str,
-) -> Result<Option<AdminConsoleSessionRecord>, AppError> {
-    let row = sqlx::query(
+    .execute(db)
+    .await?;
+    sqlx::query(
+        "create index if not exists idx_anchor_leaves_pending on anchor_leaves (created_at, id) where batch_id is null",
+    )
+    .execute(db)
+    .await?;
+    sqlx::query(
+        "create index if not exists idx_anchor_leaves_doc on anchor_leaves (doc_id, created_at)",
+    )
+    .execute(db)
+    .await?;
+    sqlx::query(
         r#"
-        select admin_session_id, wallet, chain, created_at, last_seen_at, expires_at, device_id, user_agent, ip_address
-        from admin_console_sessions
-        where admin_session_id = $1
-          and revoked_at is null
-          and expires_at > now()
+        create table if not exists arweave_anchors (
+            tx_id text primary key,
+            kind text not null,
+            hash_hex text not null,
+            doc_id uuid null,
+            share_id uuid null,
+            batch_id uuid null,
+            status text not null default 'pending',
+            confirmations bigint null,
+            block_height bigint null,
+            replaced_by_tx text null,
+            submitted_at timestamptz not null default now(),
+            last_checked_at timestamptz null,
+            check_failures integer not null default 0,
+            last_check_error text null,
+            confirmed_at timestamptz null
+        )
         "#,
     )
-    .bind(admin_session_id)
-    .fetch_optional(db)
-    .await
-    .map_err(|e| AppError::Internal(e.to_string()))?;
-
-    Ok(row.map(|row| AdminConsoleSessionRecord {
-        admin_session_id: row.get("admin_session_id"),
-        wallet: row.get("wallet"),
-        chain: row.get("chain"),
-        created_at: row.get("created_at"),
-        last_seen_at: row.get("last_seen_at"),
-        expires_at: row.get("expires_at"),
-        device_id: row.get("device_id"),
-        user_agent: row.get("user_agent"),
-        ip_address: row.get("ip_address"),
-    }))
-}
-
-async fn create_admin_console_session(
-    db: &PgPool,
-    session: &WalletSession,
-    headers: &HeaderMap,
-) -> Result<AdminConsoleSessionRecord, AppError> {
-    let admin_session_id = random_admin_session_id();
-    let expires_at = chrono::Utc::now() + chrono::Duration::hours(admin_console_session_hours());
-    let created = sqlx::query(
+    .execute(db)
+    .await?;
+    sqlx::query(
+        "create index if not exists idx_arweave_anchors_pending on arweave_anchors (last_checked_at nulls first) where status = 'pending'",
+    )
+    .execute(db)
+    .await?;
+    sqlx::query(
         r#"
-        insert into admin_console_sessions (
-            admin_session_id, wallet, chain, expires_at, device_id, user_agent, ip_address
+        create table if not exists document_uploads (
+            id uuid primary key,
+            owner_wallet text not null,
+            doc_id uuid not null,
+            parent_id uuid null,
+            version integer not null,
+            label text null,
+            mime_type text not null,
+            file_size bigint not null,
+            segment_count bigint not null,
+            declared_hash_hex text null,
+            change_summary text null,
+            anchor_to_arweave boolean not null default false,
+            stream_key_json jsonb not null,
+            status text not null default 'open',
+            created_at timestamptz not null default now(),
+            completed_at timestamptz null
         )
-        values ($1, $2, $3, $4, $5, $6, $7)
-        returning admin_session_id, wallet, chain, created_at, last_seen_at, expires_at, device_id, user_agent, ip_address
         "#,
     )
-    .bind(&admin_session_id)
-    .bind(&session.wallet)
-    .bind(&session.chain)
-    .bind(expires_at)
-    .bind(header_value(headers, "x-device-id"))
-    .bind(header_value(headers, "user-agent"))
-    .bind(header_value(headers, "x-forwarded-for"))
-    .fetch_one(db)
-    .await
-    .map_err(|e| AppError::Internal(e.to_string()))?;
-
-    Ok(AdminConsoleSessionRecord {
-        admin_session_id: created.get("admin_session_id"),
-        wallet: created.get("wallet"),
-        chain: created.get("chain"),
-        created_at: created.get("created_at"),
-        last_seen_at: created.get("last_seen_at"),
-        expires_at: created.get("expires_at"),
-        device_id: created.get("device_id"),
-        user_agent: created.get("user_agent"),
-        ip_address: created.get("ip_address"),
-    })
-}
-
-async fn revoke_admin_console_sessions_for_wallet(
-    db: &PgPool,
-    wallet: &str,
-    keep_admin_session_id: Option<&str>,
-) -> Result<(), AppError> {
-    if let Some(keep) = keep_admin_session_id {
-        sqlx::query(
-            "update admin_console_sessions set revoked_at = now() where wallet = $1 and revoked_at is null and admin_session_id <> $2",
+    .execute(db)
+    .await?;
+    sqlx::query(
+        r#"
+        create table if not exists document_upload_segments (
+            upload_id uuid not null references document_uploads(id) on delete cascade,
+            segment_index bigint not null,
+            ciphertext_sha3_hex text not null,
+            created_at timestamptz not null default now(),
+            primary key (upload_id, segment_index)
         )
-        .bind(wallet)
-        .bind(keep)
-        .execute(db)
-        .await
-        .map_err(|e| AppError::Internal(e.to_string()))?;
-    } else {
-        sqlx::query(
-            "update admin_console_sessions set revoked_at = now() where wallet = $1 and revoked_at is null",
+        "#,
+    )
+    .execute(db)
+    .await?;
+    sqlx::query(
+        r#"
+        alter table documents
+            add column if not exists quarantined_at timestamptz,
+            add column if not exists quarantined_by text,
+            add column if not exists quarantine_reason text
+        "#,
+    )
+    .execute(db)
+    .await?;
+    sqlx::query(
+        "create index if not exists idx_documents_quarantined on documents (quarantined_at) where quarantined_at is not null",
+    )
+    .execute(db)
+    .await?;
+    // Rotated keys point at their successor; only one key per wallet is active.
+    sqlx::query(
+        r#"
+        create table if not exists pq_signing_keys (
+            id uuid primary key,
+            wallet text not null,
+            chain text not null,
+            algorithm text not null,
+            public_key_b64 text not null,
+            fingerprint_hex text not null unique,
+            status text not null default 'active',
+            binding_message text not null,
+            wallet_signature text not null,
+            pq_signature_b64 text not null,
+            registered_at timestamptz not null default now(),
+            retired_at timestamptz null,
+            retired_reason text null,
+            replaced_by uuid null references pq_signing_keys(id)
         )
-        .bind(wallet)
-        .execute(db)
-        .await
-        .map_err(|e| AppError::Internal(e.to_string()))?;
-    }
-    Ok(())
-}
-
-async fn maybe_load_admin_console_session_for_wallet(
-    db: &PgPool,
-    headers: &HeaderMap,
-    session: &WalletSession,
-) -> Result<Option<AdminConsoleSessionRecord>, AppError> {
-    let Some(admin_session_id) = admin_session_header(headers) else {
-        return Ok(None);
-    };
-    let Some(record) = load_active_admin_console_session(db, &admin_session_id).await? else {
-        return Ok(None);
-    };
-    if record.wallet != session.wallet {
-        return Ok(None);
-    }
-    Ok(Some(record))
-}
-
-async fn require_admin_console_access(
-    st: &AppState,
-    headers: &HeaderMap,
-) -> Result<(WalletSession, AdminConsoleSessionRecord), AppError> {
-    let session = require_admin_session(st, headers).await?;
-    let admin_session_id = admin_session_header(headers).ok_or_else(|| {
-        AppError::Auth("Admin password verification is required for this console".into())
-    })?;
-    let record = load_active_admin_console_session(&st.db, &admin_session_id)
-        .await?
-        .ok_or_else(|| {
-            AppError::Auth("Admin password verification is required for this console".into())
-        })?;
-
-    if record.wallet != session.wallet {
-        return Err(AppError::Auth(
-            "Admin verification does not match the active wallet session".into(),
-        ));
-    }
-
+        "#,
+    )
+    .execute(db)
+    .await?;
     sqlx::query(
-        "update admin_console_sessions set last_seen_at = now() where admin_session_id = $1",
+        "create unique index if not exists idx_pq_signing_keys_active on pq_signing_keys (wallet, chain) where status = 'active'",
     )
-    .bind(&record.admin_session_id)
-    .execute(&st.db)
-    .await
-    .map_err(|e| AppError::Internal(e.to_string()))?;
-
-    Ok((session, record))
+    .execute(db)
+    .await?;
+    // Deferred work such as share reminders, expiry and escalation. Jobs
+    // survive restarts; a `running` job with a stale lock is picked up again.
+    sqlx::query(
+        r#"
+        create table if not exists scheduled_jobs (
+            id uuid primary key,
+            kind text not null,
+            run_at timestamptz not null,
+            payload jsonb not null default '{}'::jsonb,
+            status text not null default 'pending',
+            attempts int not null default 0,
+            last_error text null,
+            locked_at timestamptz null,
+            dedupe_key text null unique,
+            created_at timestamptz not null default now(),
+            completed_at timestamptz null
+        )
+        "#,
+    )
+    .execute(db)
+    .await?;
+    sqlx::query(
+        "create index if not exists idx_scheduled_jobs_due on scheduled_jobs (status, run_at)",
+    )
+    .execute(db)
+    .await?;
+    // Shares created before the scheduler existed still need to expire.
+    sqlx::query(
+        r#"
+        insert into scheduled_jobs (id, kind, run_at, payload, dedupe_key)
+        select gen_random_uuid(), 'share_expiry', s.expires_at,
+               jsonb_build_object('share_id', s.id), 'share_expiry:' || s.id
+        from document_shares s
+        where s.expires_at is not null
+          and s.revoked_at is null
+          and s.status not in ('completed', 'expired', 'revoked', 'dismissed')
+        on conflict (dedupe_key) do nothing
+        "#,
+    )
+    .execute(db)
+    .await?;
+    // Outbound webhooks. The secret is kept to sign every delivery and is
+    // only shown to the owner when the subscription is created.
+    sqlx::query(
+        r#"
+        create table if not exists webhook_subscriptions (
+            id uuid primary key,
+            owner_wallet text not null,
+            url text not null,
+            event_types_json jsonb not null default '[]'::jsonb,
+            pq_signed boolean not null default false,
+            secret text not null,
+            status text not null default 'active',
+            created_at timestamptz not null default now(),
+            disabled_at timestamptz null
+        )
+        "#,
+    )
+    .execute(db)
+    .await?;
+    sqlx::query(
+        "create index if not exists idx_webhook_subscriptions_owner on webhook_subscriptions (owner_wallet, status)",
+    )
+    .execute(db)
+    .await?;
+    sqlx::query(
+        r#"
+        create table if not exists webhook_deliveries (
+            id uuid primary key,
+            subscription_id uuid not null references webhook_subscriptions(id) on delete cascade,
+            event_id uuid not null,
+            doc_id uuid not null,
+            event_type text not null,
+            body jsonb not null,
+            status text not null default 'pending',
+            attempts int not null default 0,
+            response_status int null,
+            last_error text null,
+            replay_of uuid null,
+            created_at timestamptz not null default now(),
+            last_attempt_at timestamptz null,
+            delivered_at timestamptz null
+        )
+        "#,
+    )
+    .execute(db)
+    .await?;
+    sqlx::query(
+        "create index if not exists idx_webhook_deliveries_subscription on webhook_deliveries (subscription_id, created_at desc)",
+    )
+    .execute(db)
+    .await?;
+    // Multi-party signing: signers in the same step sign in parallel, and a
+    // step's envelopes are only created once the step before it is complete.
+    sqlx::query(
+        r#"
+        create table if not exists signing_workflows (
+            id uuid primary key,
+            doc_id uuid not null,
+            owner_wallet text not null,
+            owner_chain text not null,
+            routing text not null,
+            status text not null default 'active',
+            note text null,
+            expires_in_hours int not null,
+            created_at timestamptz not null default now(),
+            completed_at timestamptz null,
+            cancelled_at timestamptz null,
+            cancel_reason text null
+        )
+        "#,
+    )
+    .execute(db)
+    .await?;
+    sqlx::query(
+        r#"
+        create table if not exists signing_workflow_signers (
+            id uuid primary key,
+            workflow_id uuid not null references signing_workflows(id) on delete cascade,
+            position int not null,
+            step int not null,
+            role text not null,
+            required boolean not null default true,
+            signer_name text null,
+            signer_wallet text null,
+            signer_chain text null,
+            signer_email text null,
+            signer_phone text null,
+            agent_id uuid null,
+            status text not null default 'waiting',
+            envelope_id uuid null,
+            sign_event_id uuid null,
+            notified_at timestamptz null,
+            signed_at timestamptz null
+        )
+        "#,
+    )
+    .execute(db)
+    .await?;
+    sqlx::query(
+        "create index if not exists idx_signing_workflows_doc_status on signing_workflows (doc_id, status)",
+    )
+    .execute(db)
+    .await?;
+    sqlx::query(
+        "create index if not exists idx_signing_workflow_signers_workflow on signing_workflow_signers (workflow_id, step)",
+    )
+    .execute(db)
+    .await?;
+    // Start tracking anchors that were submitted before confirmation polling existed.
+    sqlx::query(
+        r#"
+        insert into arweave_anchors (tx_id, kind, hash_hex, doc_id, submitted_at)
+        select arweave_tx, 'document', hash_hex, id, created_at
+        from documents
+        where arweave_tx is not null and arweave_tx not like 'simulated-%'
+        on conflict (tx_id) do nothing
+        "#,
+    )
+    .execute(db)
+    .await?;
+    sqlx::query(
+        r#"
+        insert into arweave_anchors (tx_id, kind, hash_hex, doc_id, share_id, submitted_at)
+        select share_arweave_tx, 'share', share_anchor_hash_hex, doc_id, id, coalesce(share_anchored_at, created_at)
+        from document_shares
+        where share_arweave_tx is not null
+          and share_anchor_hash_hex is not null
+          and share_arweave_tx not like 'simulated-%'
+        on conflict (tx_id) do nothing
+        "#,
+    )
+    .execute(db)
+    .await?;
+    Ok(())
 }
 
-fn admin_auth_status_json(
-    console_path: &str,
-    wallet_session: &WalletSession,
-    credential: Option<&AdminCredentialRecord>,
-    admin_session: Option<&AdminConsoleSessionRecord>,
-) -> serde_json::Value {
-    json!({
-        "ok": true,
-        "wallet": wallet_session.wallet,
-        "chain": wallet_session.chain,
-        "username": wallet_session.wallet,
-        "console_path": console_path,
-        "password_configured": credential.is_some(),
-        "mfa_enabled": credential.map(|item| item.mfa_enabled).unwrap_or(false),
-        "mfa_pending": credential
-            .and_then(|item| item.pending_totp_secret_b32.as_ref())
-            .is_some(),
-        "admin_session_active": admin_session.is_some(),
-        "admin_session_expires_at": admin_session.map(|item| item.expires_at),
-        "needs_setup": credential.is_none()
-    })
+#[derive(Deserialize)]
+struct AdminGrowthQuery {
+    days: Option<i64>,
 }
 
-async fn admin_console_handler() -> Response {
-    (
-        StatusCode::OK,
-        [
-            (
-                header::HeaderName::from_static("x-robots-tag"),
-                HeaderValue::from_static("noindex, nofollow, noarchive"),
-            ),
-            (
-                header::CACHE_CONTROL,
-                HeaderValue::from_static("no-store, max-age=0"),
-            ),
-        ],
-        Html(include_str!("../web/admin.html")),
-    )
-        .into_response()
+fn clamp_admin_days(days: Option<i64>) -> i64 {
+    days.unwrap_or(30).clamp(7, 180)
 }
 
-async fn admin_access_handler(
-    State(st): State<AppState>,
-    headers: HeaderMap,
-) -> Result<Json<serde_json::Value>, AppError> {
-    let session = require_admin_session(&st, &headers).await?;
-    Ok(Json(json!({
-        "ok": true,
-        "wallet": session.wallet,
-        "chain": session.chain,
-        "console_path": st.admin_console_path
-    })))
+#[derive(Deserialize)]
+struct AdminAuthBootstrapRequest {
+    password: String,
 }
 
-async fn admin_auth_status_handler(
-    State(st): State<AppState>,
-    headers: HeaderMap,
-) -> Result<Json<serde_json::Value>, AppError> {
-    let session = require_admin_session(&st, &headers).await?;
-    let credential = load_admin_credential(&st.db, &session.wallet).await?;
-    let admin_session = maybe_load_admin_console_session_for_wallet(&st.db, &headers, &session).await?;
+#[derive(Deserialize)]
+struct AdminAuthLoginRequest {
+    password: String,
+    totp_code: Option<String>,
+}
 
-    Ok(Json(admin_auth_status_json(
-        &st.admin_console_path,
-        &session,
-        credential.as_ref(),
-        admin_session.as_ref(),
-    )))
+#[derive(Deserialize)]
+struct AdminAuthChangePasswordRequest {
+    current_password: String,
+    new_password: String,
+    totp_code: Option<String>,
 }
 
-async fn admin_auth_bootstrap_handler(
-    State(st): State<AppState>,
-    headers: HeaderMap,
-    Json(body): Json<AdminAuthBootstrapRequest>,
-) -> Result<Json<serde_json::Value>, AppError> {
-    let session = require_admin_session(&st, &headers).await?;
-    if load_admin_credential(&st.db, &session.wallet).await?.is_some() {
-        return Err(AppError::BadRequest(
-            "Admin password already exists for this wallet".into(),
-        ));
-    }
+#[derive(Deserialize)]
+struct AdminAuthMfaEnrollRequest {
+    current_password: String,
+}
 
-    validate_admin_password_strength(&body.password)?;
-    let password_hash = hash_admin_password(&body.password)?;
-    sqlx::query(
-        r#"
-        insert into admin_credentials (wallet, password_hash)
-        values ($1, $2)
-        "#,
-    )
-    .bind(&session.wallet)
-    .bind(password_hash)
-    .execute(&st.db)
-    .await
-    .map_err(|e| AppError::Internal(e.to_string()))?;
+#[derive(Deserialize)]
+struct AdminAuthMfaVerifyRequest {
+    current_password: String,
+    totp_code: String,
+}
 
-    let admin_session = create_admin_console_session(&st.db, &session, &headers).await?;
-    let credential = load_admin_credential(&st.db, &session.wallet)
-        .await?
-        .ok_or_else(|| AppError::Internal("Admin credential was not persisted".into()))?;
+#[derive(Deserialize)]
+struct AdminAuthMfaDisableRequest {
+    current_password: String,
+    totp_code: String,
+}
 
-    Ok(Json(json!({
-        "ok": true,
-        "admin_session_id": admin_session.admin_session_id,
-        "admin_session_expires_at": admin_session.expires_at,
-        "status": admin_auth_status_json(&st.admin_console_path, &session, Some(&credential), Some(&admin_session))
-    })))
+fn admin_console_session_hours() -> i64 {
+    std::env::var("ADMIN_CONSOLE_SESSION_HOURS")
+        .ok()
+        .and_then(|value| value.trim().parse::<i64>().ok())
+        .unwrap_or(12)
+        .clamp(1, 24 * 7)
 }
 
-async fn admin_auth_login_handler(
-    State(st): State<AppState>,
-    headers: HeaderMap,
-    Json(body): Json<AdminAuthLoginRequest>,
-) -> Result<Json<serde_json::Value>, AppError> {
-    let session = require_admin_session(&st, &headers).await?;
-    let credential = load_admin_credential(&st.db, &session.wallet)
-        .await?
-        .ok_or_else(|| AppError::BadRequest("Set an admin password for this wallet first".into()))?;
+fn admin_console_password_min_length() -> usize {
+    12
+}
 
-    if !verify_admin_password(&credential.password_hash, &body.password)? {
-        return Err(AppError::Forbidden("Admin password is incorrect".into()));
+fn admin_session_header(headers: &HeaderMap) -> Option<String> {
+    header_value(headers, "x-admin-session-id")
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+fn validate_admin_password_strength(password: &str) -> Result<(), AppError> {
+    if password.trim().len() < admin_console_password_min_length() {
+        return Err(AppError::BadRequest(format!(
+            "Admin password must be at least {} characters",
+            admin_console_password_min_length()
+        )));
     }
+    Ok(())
+}
 
-    if credential.mfa_enabled {
-        let secret = credential.totp_secret_b32.as_deref().ok_or_else(|| {
-            AppError::Internal("Admin MFA is enabled but no TOTP secret is stored".into())
-        })?;
-        let provided_code = body.totp_code.as_deref().ok_or_else(|| {
-            AppError::BadRequest("A 6-digit MFA code is required for this admin wallet".into())
-        })?;
-        if !verify_totp_code(secret, provided_code)? {
-            return Err(AppError::Forbidden("Admin MFA code is incorrect".into()));
-        }
-    }
+fn hash_admin_password(password: &str) -> Result<String, AppError> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|err| AppError::Internal(format!("admin password hash failed: {err}")))
+}
 
-    let admin_session = create_admin_console_session(&st.db, &session, &headers).await?;
-    revoke_admin_console_sessions_for_wallet(&st.db, &session.wallet, Some(&admin_session.admin_session_id))
-        .await?;
+fn verify_admin_password(hash: &str, password: &str) -> Result<bool, AppError> {
+    let parsed = PasswordHash::new(hash)
+        .map_err(|err| AppError::Internal(format!("stored admin password hash invalid: {err}")))?;
+    Ok(Argon2::default()
+        .verify_password(password.as_bytes(), &parsed)
+        .is_ok())
+}
 
-    Ok(Json(json!({
-        "ok": true,
-        "admin_session_id": admin_session.admin_session_id,
-        "admin_session_expires_at": admin_session.expires_at,
-        "status": admin_auth_status_json(&st.admin_console_path, &session, Some(&credential), Some(&admin_session))
-    })))
+fn random_admin_session_id() -> String {
+    uuid::Uuid::new_v4().simple().to_string()
 }
 
-async fn admin_auth_logout_handler(
-    State(st): State<AppState>,
-    headers: HeaderMap,
-) -> Result<Json<serde_json::Value>, AppError> {
-    let session = require_admin_session(&st, &headers).await?;
-    if let Some(admin_session_id) = admin_session_header(&headers) {
-        sqlx::query(
-            "update admin_console_sessions set revoked_at = now() where admin_session_id = $1 and wallet = $2 and revoked_at is null",
-        )
-        .bind(admin_session_id)
-        .bind(&session.wallet)
-        .execute(&st.db)
-        .await
-        .map_err(|e| AppError::Internal(e.to_string()))?;
-    }
-    Ok(Json(json!({ "ok": true })))
+fn normalize_totp_code(value: &str) -> String {
+    value.chars().filter(|ch| ch.is_ascii_digit()).collect()
 }
 
-async fn admin_auth_change_password_handler(
-    State(st): State<AppState>,
-    headers: HeaderMap,
-    Json(body): Json<AdminAuthChangePasswordRequest>,
-) -> Result<Json<serde_json::Value>, AppError> {
-    let (session, admin_session) = require_admin_console_access(&st, &headers).await?;
-    let credential = load_admin_credential(&st.db, &session.wallet)
-        .await?
-        .ok_or_else(|| AppError::BadRequest("Set an admin password for this wallet first".into()))?;
+fn base32_encode(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+    let mut output = String::new();
+    let mut buffer: u16 = 0;
+    let mut bits_left = 0u8;
 
-    if !verify_admin_password(&credential.password_hash, &body.current_password)? {
-        return Err(AppError::Forbidden("Current admin password is incorrect".into()));
-    }
-    if credential.mfa_enabled {
-        let secret = credential.totp_secret_b32.as_deref().ok_or_else(|| {
-            AppError::Internal("Admin MFA is enabled but no TOTP secret is stored".into())
-        })?;
-        let totp_code = body.totp_code.as_deref().ok_or_else(|| {
-            AppError::BadRequest("A 6-digit MFA code is required to change the admin password".into())
-        })?;
-        if !verify_totp_code(secret, totp_code)? {
-            return Err(AppError::Forbidden("Admin MFA code is incorrect".into()));
+    for byte in bytes {
+        buffer = (buffer << 8) | u16::from(*byte);
+        bits_left += 8;
+        while bits_left >= 5 {
+            let idx = ((buffer >> (bits_left - 5)) & 0x1f) as usize;
+            output.push(ALPHABET[idx] as char);
+            bits_left -= 5;
         }
     }
 
-    validate_admin_password_strength(&body.new_password)?;
-    let new_hash = hash_admin_password(&body.new_password)?;
-    sqlx::query(
-        "update admin_credentials set password_hash = $2, updated_at = now() where wallet = $1",
-    )
-    .bind(&session.wallet)
-    .bind(new_hash)
-    .execute(&st.db)
-    .await
-    .map_err(|e| AppError::Internal(e.to_string()))?;
-    revoke_admin_console_sessions_for_wallet(&st.db, &session.wallet, Some(&admin_session.admin_session_id))
-        .await?;
-
-    let refreshed = load_admin_credential(&st.db, &session.wallet)
-        .await?
-        .ok_or_else(|| AppError::Internal("Admin credential disappeared after password change".into()))?;
+    if bits_left > 0 {
+        let idx = ((buffer << (5 - bits_left)) & 0x1f) as usize;
+        output.push(ALPHABET[idx] as char);
+    }
 
-    Ok(Json(json!({
-        "ok": true,
-        "message": "Admin password updated",
-        "status": admin_auth_status_json(&st.admin_console_path, &session, Some(&refreshed), Some(&admin_session))
-    })))
+    output
 }
 
-async fn admin_auth_mfa_enroll_handler(
-    State(st): State<AppState>,
-    headers: HeaderMap,
-    Json(body): Json<AdminAuthMfaEnrollRequest>,
-) -> Result<Json<serde_json::Value>, AppError> {
-    let (session, admin_session) = require_admin_console_access(&st, &headers).await?;
-    let credential = load_admin_credential(&st.db, &session.wallet)
-        .await?
-        .ok_or_else(|| AppError::BadRequest("Set an admin password for this wallet first".into()))?;
+fn base32_decode(input: &str) -> Result<Vec<u8>, AppError> {
+    let mut buffer: u32 = 0;
+    let mut bits_left = 0u8;
+    let mut output = Vec::new();
 
-    if !verify_admin_password(&credential.password_hash, &body.current_password)? {
-        return Err(AppError::Forbidden("Current admin password is incorrect".into()));
+    for ch in input.chars() {
+        if ch == '=' || ch.is_ascii_whitespace() || ch == '-' {
+            continue;
+        }
+        let value = match ch.to_ascii_uppercase() {
+            'A'..='Z' => ch.to_ascii_uppercase() as u8 - b'A',
+            '2'..='7' => ch as u8 - b'2' + 26,
+            _ => {
+                return Err(AppError::BadRequest(
+                    "TOTP secret contains invalid Base32 characters".into(),
+                ))
+            }
+        };
+        buffer = (buffer << 5) | u32::from(value);
+        bits_left += 5;
+        while bits_left >= 8 {
+            output.push(((buffer >> (bits_left - 8)) & 0xff) as u8);
+            bits_left -= 8;
+        }
     }
 
-    let secret_b32 = generate_totp_secret_b32();
-    let otpauth_url = admin_totp_otpauth_url(&session.wallet, &secret_b32);
-    sqlx::query(
-        "update admin_credentials set pending_totp_secret_b32 = $2, updated_at = now() where wallet = $1",
-    )
-    .bind(&session.wallet)
-    .bind(&secret_b32)
-    .execute(&st.db)
-    .await
-    .map_err(|e| AppError::Internal(e.to_string()))?;
-
-    let refreshed = load_admin_credential(&st.db, &session.wallet)
-        .await?
-        .ok_or_else(|| AppError::Internal("Admin credential disappeared during MFA enrollment".into()))?;
+    Ok(output)
+}
 
-    Ok(Json(json!({
-        "ok": true,
-        "secret_b32": secret_b32,
-        "otpauth_url": otpauth_url,
-        "status": admin_auth_status_json(&st.admin_console_path, &session, Some(&refreshed), Some(&admin_session))
-    })))
+fn generate_totp_secret_b32() -> String {
+    let mut bytes = [0u8; 20];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    base32_encode(&bytes)
 }
 
-async fn admin_auth_mfa_verify_handler(
-    State(st): State<AppState>,
-    headers: HeaderMap,
-    Json(body): Json<AdminAuthMfaVerifyRequest>,
-) -> Result<Json<serde_json::Value>, AppError> {
-    let (session, _admin_session) = require_admin_console_access(&st, &headers).await?;
-    let credential = load_admin_credential(&st.db, &session.wallet)
-        .await?
-        .ok_or_else(|| AppError::BadRequest("Set an admin password for this wallet first".into()))?;
+fn compute_totp_code(secret_b32: &str, unix_ts: i64) -> Result<String, AppError> {
+    let secret = base32_decode(secret_b32)?;
+    let counter = (unix_ts.max(0) as u64) / 30;
+    let mut mac = Hmac::<Sha1>::new_from_slice(&secret)
+        .map_err(|err| AppError::Internal(format!("totp hmac init failed: {err}")))?;
+    mac.update(&counter.to_be_bytes());
+    let digest = mac.finalize().into_bytes();
+    let offset = usize::from(digest[digest.len() - 1] & 0x0f);
+    let binary = ((u32::from(digest[offset]) & 0x7f) << 24)
+        | (u32::from(digest[offset + 1]) << 16)
+        | (u32::from(digest[offset + 2]) << 8)
+        | u32::from(digest[offset + 3]);
+    Ok(format!("{:06}", binary % 1_000_000))
+}
 
-    if !verify_admin_password(&credential.password_hash, &body.current_password)? {
-        return Err(AppError::Forbidden("Current admin password is incorrect".into()));
+fn verify_totp_code(secret_b32: &str, code: &str) -> Result<bool, AppError> {
+    let normalized = normalize_totp_code(code);
+    if normalized.len() != 6 {
+        return Ok(false);
     }
-
-    let pending_secret = credential
-        .pending_totp_secret_b32
-        .as_deref()
-        .ok_or_else(|| AppError::BadRequest("Start MFA enrollment before verifying a code".into()))?;
-    if !verify_totp_code(pending_secret, &body.totp_code)? {
-        return Err(AppError::Forbidden("Admin MFA code is incorrect".into()));
+    let now = chrono::Utc::now().timestamp();
+    for offset in -1..=1 {
+        let candidate = compute_totp_code(secret_b32, now + (offset * 30))?;
+        if candidate == normalized {
+            return Ok(true);
+        }
     }
+    Ok(false)
+}
 
-    sqlx::query(
+fn admin_totp_otpauth_url(wallet: &str, secret_b32: &str) -> String {
+    let issuer = "TIDBIT-share-WEAVE";
+    let label = format!("{issuer}:{wallet}");
+    format!(
+        "otpauth://totp/{}?secret={}&issuer={}&algorithm=SHA1&digits=6&period=30",
+        urlencoding::encode(&label),
+        secret_b32,
+        urlencoding::encode(issuer)
+    )
+}
+
+async fn load_admin_credential(
+    db: &PgPool,
+    wallet: &str,
+) -> Result<Option<AdminCredentialRecord>, AppError> {
+    let row = sqlx::query(
         r#"
-        update admin_credentials
-        set
-            totp_secret_b32 = $2,
-            pending_totp_secret_b32 = null,
-            mfa_enabled = true,
-            updated_at = now()
+        select wallet, password_hash, mfa_enabled, totp_secret_b32, pending_totp_secret_b32, created_at, updated_at
+        from admin_credentials
         where wallet = $1
         "#,
     )
-    .bind(&session.wallet)
-    .bind(pending_secret)
-    .execute(&st.db)
+    .bind(wallet)
+    .fetch_optional(db)
     .await
     .map_err(|e| AppError::Internal(e.to_string()))?;
-    revoke_admin_console_sessions_for_wallet(&st.db, &session.wallet, None).await?;
-
-    let refreshed = load_admin_credential(&st.db, &session.wallet)
-        .await?
-        .ok_or_else(|| AppError::Internal("Admin credential disappeared after enabling MFA".into()))?;
 
-    Ok(Json(json!({
-        "ok": true,
-        "message": "MFA enabled. Sign in again with password and TOTP.",
-        "status": admin_auth_status_json(&st.admin_console_path, &session, Some(&refreshed), None)
-    })))
-}
-
-async fn admin_auth_mfa_disable_handler(
-    State(st): State<AppState>,
-    headers: HeaderMap,
-    Json(body): Json<AdminAuthMfaDisableRequest>,
-) -> Result<Json<serde_json::Value>, AppError> {
-    let (session, admin_session) = require_admin_console_access(&st, &headers).await?;
-    let credential = load_admin_credential(&st.db, &session.wallet)
-        .await?
-        .ok_or_else(|| AppError::BadRequest("Set an admin password for this wallet first".into()))?;
+    Ok(row.map(|row| AdminCredentialRecord {
+        wallet: row.get("wallet"),
+        password_hash: row.get("password_hash"),
+        mfa_enabled: row.get("mfa_enabled"),
+        totp_secret_b32: row.get("totp_secret_b32"),
+        pending_totp_secret_b32: row.get("pending_totp_secret_b32"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    }))
+}
 
-    if !credential.mfa_enabled {
-        return Err(AppError::BadRequest("MFA is not enabled for this admin wallet".into()));
+async fn load_active_admin_console_session(
+    db: &PgPool,
+    admin_session_id: &str,
+) -> Result<Option<AdminConsoleSessionRecord>, AppError> {
+    let row = sqlx::query(
+        r#"
+        select admin_session_id, wallet, chain, created_at, last_seen_at, expires_at, device_id, user_agent, ip_address
+        from admin_console_sessions
+        where admin_session_id = $1
+          and revoked_at is null
+          and expires_at > now()
+        "#,
+    )
+    .bind(admin_session_id)
+    .fetch_optional(db)
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    Ok(row.map(|row| AdminConsoleSessionRecord {
+        admin_session_id: row.get("admin_session_id"),
+        wallet: row.get("wallet"),
+        chain: row.get("chain"),
+        created_at: row.get("created_at"),
+        last_seen_at: row.get("last_seen_at"),
+        expires_at: row.get("expires_at"),
+        device_id: row.get("device_id"),
+        user_agent: row.get("user_agent"),
+        ip_address: row.get("ip_address"),
+    }))
+}
+
+async fn create_admin_console_session(
+    db: &PgPool,
+    session: &WalletSession,
+    headers: &HeaderMap,
+) -> Result<AdminConsoleSessionRecord, AppError> {
+    let admin_session_id = random_admin_session_id();
+    let expires_at = chrono::Utc::now() + chrono::Duration::hours(admin_console_session_hours());
+    let created = sqlx::query(
+        r#"
+        insert into admin_console_sessions (
+            admin_session_id, wallet, chain, expires_at, device_id, user_agent, ip_address
+        )
+        values ($1, $2, $3, $4, $5, $6, $7)
+        returning admin_session_id, wallet, chain, created_at, last_seen_at, expires_at, device_id, user_agent, ip_address
+        "#,
+    )
+    .bind(&admin_session_id)
+    .bind(&session.wallet)
+    .bind(&session.chain)
+    .bind(expires_at)
+    .bind(header_value(headers, "x-device-id"))
+    .bind(header_value(headers, "user-agent"))
+    .bind(header_value(headers, "x-forwarded-for"))
+    .fetch_one(db)
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    Ok(AdminConsoleSessionRecord {
+        admin_session_id: created.get("admin_session_id"),
+        wallet: created.get("wallet"),
+        chain: created.get("chain"),
+        created_at: created.get("created_at"),
+        last_seen_at: created.get("last_seen_at"),
+        expires_at: created.get("expires_at"),
+        device_id: created.get("device_id"),
+        user_agent: created.get("user_agent"),
+        ip_address: created.get("ip_address"),
+    })
+}
+
+async fn revoke_admin_console_sessions_for_wallet(
+    db: &PgPool,
+    wallet: &str,
+    keep_admin_session_id: Option<&str>,
+) -> Result<(), AppError> {
+    if let Some(keep) = keep_admin_session_id {
+        sqlx::query(
+            "update admin_console_sessions set revoked_at = now() where wallet = $1 and revoked_at is null and admin_session_id <> $2",
+        )
+        .bind(wallet)
+        .bind(keep)
+        .execute(db)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    } else {
+        sqlx::query(
+            "update admin_console_sessions set revoked_at = now() where wallet = $1 and revoked_at is null",
+        )
+        .bind(wallet)
+        .execute(db)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
     }
-    if !verify_admin_password(&credential.password_hash, &body.current_password)? {
-        return Err(AppError::Forbidden("Current admin password is incorrect".into()));
+    Ok(())
+}
+
+async fn maybe_load_admin_console_session_for_wallet(
+    db: &PgPool,
+    headers: &HeaderMap,
+    session: &WalletSession,
+) -> Result<Option<AdminConsoleSessionRecord>, AppError> {
+    let Some(admin_session_id) = admin_session_header(headers) else {
+        return Ok(None);
+    };
+    let Some(record) = load_active_admin_console_session(db, &admin_session_id).await? else {
+        return Ok(None);
+    };
+    if record.wallet != session.wallet {
+        return Ok(None);
     }
-    let secret = credential.totp_secret_b32.as_deref().ok_or_else(|| {
-        AppError::Internal("Admin MFA is enabled but no TOTP secret is stored".into())
+    Ok(Some(record))
+}
+
+async fn require_admin_console_access(
+    st: &AppState,
+    headers: &HeaderMap,
+) -> Result<(WalletSession, AdminConsoleSessionRecord), AppError> {
+    let session = require_admin_session(st, headers).await?;
+    let admin_session_id = admin_session_header(headers).ok_or_else(|| {
+        AppError::Auth("Admin password verification is required for this console".into())
     })?;
-    if !verify_totp_code(secret, &body.totp_code)? {
-        return Err(AppError::Forbidden("Admin MFA code is incorrect".into()));
+    let record = load_active_admin_console_session(&st.db, &admin_session_id)
+        .await?
+        .ok_or_else(|| {
+            AppError::Auth("Admin password verification is required for this console".into())
+        })?;
+
+    if record.wallet != session.wallet {
+        return Err(AppError::Auth(
+            "Admin verification does not match the active wallet session".into(),
+        ));
     }
 
     sqlx::query(
-        r#"
-        update admin_credentials
-        set
-            mfa_enabled = false,
-            totp_secret_b32 = null,
-            pending_totp_secret_b32 = null,
-            updated_at = now()
-        where wallet = $1
-        "#,
+        "update admin_console_sessions set last_seen_at = now() where admin_session_id = $1",
     )
-    .bind(&session.wallet)
+    .bind(&record.admin_session_id)
     .execute(&st.db)
     .await
     .map_err(|e| AppError::Internal(e.to_string()))?;
-    revoke_admin_console_sessions_for_wallet(&st.db, &session.wallet, Some(&admin_session.admin_session_id))
-        .await?;
 
-    let refreshed = load_admin_credential(&st.db, &session.wallet)
-        .await?
-        .ok_or_else(|| AppError::Internal("Admin credential disappeared after disabling MFA".into()))?;
+    Ok((session, record))
+}
+
+fn admin_auth_status_json(
+    console_path: &str,
+    wallet_session: &WalletSession,
+    credential: Option<&AdminCredentialRecord>,
+    admin_session: Option<&AdminConsoleSessionRecord>,
+) -> serde_json::Value {
+    json!({
+        "ok": true,
+        "wallet": wallet_session.wallet,
+        "chain": wallet_session.chain,
+        "username": wallet_session.wallet,
+        "console_path": console_path,
+        "password_configured": credential.is_some(),
+        "mfa_enabled": credential.map(|item| item.mfa_enabled).unwrap_or(false),
+        "mfa_pending": credential
+            .and_then(|item| item.pending_totp_secret_b32.as_ref())
+            .is_some(),
+        "admin_session_active": admin_session.is_some(),
+        "admin_session_expires_at": admin_session.map(|item| item.expires_at),
+        "needs_setup": credential.is_none()
+    })
+}
+
+async fn admin_console_handler() -> Response {
+    (
+        StatusCode::OK,
+        [
+            (
+                header::HeaderName::from_static("x-robots-tag"),
+                HeaderValue::from_static("noindex, nofollow, noarchive"),
+            ),
+            (
+                header::CACHE_CONTROL,
+                HeaderValue::from_static("no-store, max-age=0"),
+            ),
+        ],
+        Html(include_str!("../web/admin.html")),
+    )
+        .into_response()
+}
 
+async fn admin_access_handler(
+    State(st): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let session = require_admin_session(&st, &headers).await?;
     Ok(Json(json!({
         "ok": true,
-        "message": "MFA disabled",
-        "status": admin_auth_status_json(&st.admin_console_path, &session, Some(&refreshed), Some(&admin_session))
+        "wallet": session.wallet,
+        "chain": session.chain,
+        "console_path": st.admin_console_path
     })))
 }
 
-async fn admin_growth_overview_handler(
+async fn admin_auth_status_handler(
     State(st): State<AppState>,
     headers: HeaderMap,
-    Query(query): Query<AdminGrowthQuery>,
 ) -> Result<Json<serde_json::Value>, AppError> {
-    let (session, _) = require_admin_console_access(&st, &headers).await?;
-    let days = clamp_admin_days(query.days);
-    let days_i32 = days as i32;
+    let session = require_admin_session(&st, &headers).await?;
+    let credential = load_admin_credential(&st.db, &session.wallet).await?;
+    let admin_session = maybe_load_admin_console_session_for_wallet(&st.db, &headers, &session).await?;
 
-    let kpis = sqlx::query(
-        r#"
-        with first_seen as (
-            select chain, wallet, min(created_at) as first_seen_at
-            from wallet_sessions
-            group by chain, wallet
-        )
-        select
-            (select count(*) from first_seen) as total_wallets,
-            (select count(*) from first_seen where first_seen_at >= now() - interval '1 day') as new_wallets_1d,
-            (select count(*) from first_seen where first_seen_at >= now() - interval '7 day') as new_wallets_7d,
-            (select count(*) from first_seen where first_seen_at >= now() - interval '30 day') as new_wallets_30d,
-            (select count(distinct chain || ':' || wallet) from wallet_sessions where last_seen_at >= now() - interval '1 day') as active_wallets_1d,
-            (select count(distinct chain || ':' || wallet) from wallet_sessions where last_seen_at >= now() - interval '7 day') as active_wallets_7d,
-            (select count(distinct chain || ':' || wallet) from wallet_sessions where last_seen_at >= now() - interval '30 day') as active_wallets_30d,
-            (select count(*) from wallet_sessions where created_at >= now() - interval '30 day') as wallet_logins_30d,
-            (select count(*) from wallet_sessions where revoked_at is null and expires_at > now()) as active_sessions_now,
-            (select count(distinct coalesce(device_id, session_id)) from wallet_sessions where last_seen_at >= now() - interval '30 day') as active_devices_30d,
-            (select count(*) from documents where is_deleted = false) as total_docs,
-            (select count(*) from documents where is_deleted = false and parent_id is not null) as total_versions,
-            (select count(*) from document_shares) as total_shares,
-            (select count(*) from document_shares where share_arweave_tx is not null) as total_share_anchors,
-            (select count(*) from document_events where event_type in ('SIGN', 'ENVELOPE_COMPLETED', 'AGENT_SIGN')) as total_sign_events,
-            (select count(*) from agent_identities where coalesce(is_active, true)) as total_agents
+    Ok(Json(admin_auth_status_json(
+        &st.admin_console_path,
+        &session,
+        credential.as_ref(),
+        admin_session.as_ref(),
+    )))
+}
+
+async fn admin_auth_bootstrap_handler(
+    State(st): State<AppState>,
+    headers: HeaderMap,
+    Json(body): Json<AdminAuthBootstrapRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let session = require_admin_session(&st, &headers).await?;
+    if load_admin_credential(&st.db, &session.wallet).await?.is_some() {
+        return Err(AppError::BadRequest(
+            "Admin password already exists for this wallet".into(),
+        ));
+    }
+
+    validate_admin_password_strength(&body.password)?;
+    let password_hash = hash_admin_password(&body.password)?;
+    sqlx::query(
+        r#"
+        insert into admin_credentials (wallet, password_hash)
+        values ($1, $2)
         "#,
     )
-    .fetch_one(&st.db)
+    .bind(&session.wallet)
+    .bind(password_hash)
+    .execute(&st.db)
     .await
     .map_err(|e| AppError::Internal(e.to_string()))?;
 
-    let usage = sqlx::query(
-        r#"
-        select
-            (select count(*) from documents where is_deleted = false and parent_id is null and created_at >= now() - ($1::int * interval '1 day')) as uploads_window,
-            (select count(*) from documents where is_deleted = false and parent_id is not null and created_at >= now() - ($1::int * interval '1 day')) as versions_window,
-            (select count(*) from document_shares where created_at >= now() - ($1::int * interval '1 day')) as shares_window,
-            (select count(*) from document_events where event_type in ('SIGN', 'ENVELOPE_COMPLETED', 'AGENT_SIGN') and created_at >= now() - ($1::int * interval '1 day')) as signs_window,
-            (select count(*) from document_events where event_type = 'VIEW' and created_at >= now() - ($1::int * interval '1 day')) as views_window,
-            (select count(*) from document_events where event_type in ('DOWNLOAD', 'INBOX_DOWNLOADED') and created_at >= now() - ($1::int * interval '1 day')) as downloads_window,
-            (select count(*) from document_events where event_type like 'INBOX_%' and created_at >= now() - ($1::int * interval '1 day')) as inbox_actions_window,
-            (select count(*) from document_events where event_type = 'ENVELOPE_OPENED' and created_at >= now() - ($1::int * interval '1 day')) as public_opens_window,
-            (select count(*) from document_events where event_type = 'ENVELOPE_COMPLETED' and created_at >= now() - ($1::int * interval '1 day')) as public_completions_window,
-            (select count(*) from document_shares where share_arweave_tx is not null and share_anchored_at >= now() - ($1::int * interval '1 day')) as share_anchors_window,
-            (select count(*) from agent_identities where created_at >= now() - ($1::int * interval '1 day')) as agents_window
-        "#,
+    let admin_session = create_admin_console_session(&st.db, &session, &headers).await?;
+    let credential = load_admin_credential(&st.db, &session.wallet)
+        .await?
+        .ok_or_else(|| AppError::Internal("Admin credential was not persisted".into()))?;
+
+    Ok(Json(json!({
+        "ok": true,
+        "admin_session_id": admin_session.admin_session_id,
+        "admin_session_expires_at": admin_session.expires_at,
+        "status": admin_auth_status_json(&st.admin_console_path, &session, Some(&credential), Some(&admin_session))
+    })))
+}
+
+async fn admin_auth_login_handler(
+    State(st): State<AppState>,
+    headers: HeaderMap,
+    Json(body): Json<AdminAuthLoginRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let session = require_admin_session(&st, &headers).await?;
+    let credential = load_admin_credential(&st.db, &session.wallet)
+        .await?
+        .ok_or_else(|| AppError::BadRequest("Set an admin password for this wallet first".into()))?;
+
+    if !verify_admin_password(&credential.password_hash, &body.password)? {
+        return Err(AppError::Forbidden("Admin password is incorrect".into()));
+    }
+
+    if credential.mfa_enabled {
+        let secret = credential.totp_secret_b32.as_deref().ok_or_else(|| {
+            AppError::Internal("Admin MFA is enabled but no TOTP secret is stored".into())
+        })?;
+        let provided_code = body.totp_code.as_deref().ok_or_else(|| {
+            AppError::BadRequest("A 6-digit MFA code is required for this admin wallet".into())
+        })?;
+        if !verify_totp_code(secret, provided_code)? {
+            return Err(AppError::Forbidden("Admin MFA code is incorrect".into()));
+        }
+    }
+
+    let admin_session = create_admin_console_session(&st.db, &session, &headers).await?;
+    revoke_admin_console_sessions_for_wallet(&st.db, &session.wallet, Some(&admin_session.admin_session_id))
+        .await?;
+
+    Ok(Json(json!({
+        "ok": true,
+        "admin_session_id": admin_session.admin_session_id,
+        "admin_session_expires_at": admin_session.expires_at,
+        "status": admin_auth_status_json(&st.admin_console_path, &session, Some(&credential), Some(&admin_session))
+    })))
+}
+
+async fn admin_auth_logout_handler(
+    State(st): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let session = require_admin_session(&st, &headers).await?;
+    if let Some(admin_session_id) = admin_session_header(&headers) {
+        sqlx::query(
+            "update admin_console_sessions set revoked_at = now() where admin_session_id = $1 and wallet = $2 and revoked_at is null",
+        )
+        .bind(admin_session_id)
+        .bind(&session.wallet)
+        .execute(&st.db)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    }
+    Ok(Json(json!({ "ok": true })))
+}
+
+async fn admin_auth_change_password_handler(
+    State(st): State<AppState>,
+    headers: HeaderMap,
+    Json(body): Json<AdminAuthChangePasswordRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let (session, admin_session) = require_admin_console_access(&st, &headers).await?;
+    let credential = load_admin_credential(&st.db, &session.wallet)
+        .await?
+        .ok_or_else(|| AppError::BadRequest("Set an admin password for this wallet first".into()))?;
+
+    if !verify_admin_password(&credential.password_hash, &body.current_password)? {
+        return Err(AppError::Forbidden("Current admin password is incorrect".into()));
+    }
+    if credential.mfa_enabled {
+        let secret = credential.totp_secret_b32.as_deref().ok_or_else(|| {
+            AppError::Internal("Admin MFA is enabled but no TOTP secret is stored".into())
+        })?;
+        let totp_code = body.totp_code.as_deref().ok_or_else(|| {
+            AppError::BadRequest("A 6-digit MFA code is required to change the admin password".into())
+        })?;
+        if !verify_totp_code(secret, totp_code)? {
+            return Err(AppError::Forbidden("Admin MFA code is incorrect".into()));
+        }
+    }
+
+    validate_admin_password_strength(&body.new_password)?;
+    let new_hash = hash_admin_password(&body.new_password)?;
+    sqlx::query(
+        "update admin_credentials set password_hash = $2, updated_at = now() where wallet = $1",
     )
-    .bind(days_i32)
-    .fetch_one(&st.db)
+    .bind(&session.wallet)
+    .bind(new_hash)
+    .execute(&st.db)
     .await
     .map_err(|e| AppError::Internal(e.to_string()))?;
+    revoke_admin_console_sessions_for_wallet(&st.db, &session.wallet, Some(&admin_session.admin_session_id))
+        .await?;
 
-    let acquisition = sqlx::query(
-        r#"
-        with visitor_window as (
-            select nullif(properties_json->>'visitor_id', '') as visitor_id
-            from growth_events
-            where occurred_at >= now() - ($1::int * interval '1 day')
-              and actor_kind = 'visitor'
-              and event_type = 'PAGE_VIEW'
-        ),
-        visitor_rollup as (
-            select visitor_id
-            from visitor_window
-            where visitor_id is not null
-            group by visitor_id
-        ),
-        login_visitors as (
-            select distinct nullif(properties_json->>'visitor_id', '') as visitor_id
-            from growth_events
-            where event_type = 'WALLET_LOGIN'
-              and nullif(properties_json->>'visitor_id', '') is not null
-        )
-        select
-            (select count(*) from visitor_window) as page_views_window,
-            (select count(*) from visitor_rollup) as unique_visitors_window,
-            (select count(*) from visitor_rollup vr join login_visitors lv on lv.visitor_id = vr.visitor_id) as converted_visitors_window,
-            (
-                select count(*)
-                from visitor_rollup vr
-                left join login_visitors lv on lv.visitor_id = vr.visitor_id
-                where lv.visitor_id is null
-            ) as anonymous_visitors_window
-        "#,
+    let refreshed = load_admin_credential(&st.db, &session.wallet)
+        .await?
+        .ok_or_else(|| AppError::Internal("Admin credential disappeared after password change".into()))?;
+
+    Ok(Json(json!({
+        "ok": true,
+        "message": "Admin password updated",
+        "status": admin_auth_status_json(&st.admin_console_path, &session, Some(&refreshed), Some(&admin_session))
+    })))
+}
+
+async fn admin_auth_mfa_enroll_handler(
+    State(st): State<AppState>,
+    headers: HeaderMap,
+    Json(body): Json<AdminAuthMfaEnrollRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let (session, admin_session) = require_admin_console_access(&st, &headers).await?;
+    let credential = load_admin_credential(&st.db, &session.wallet)
+        .await?
+        .ok_or_else(|| AppError::BadRequest("Set an admin password for this wallet first".into()))?;
+
+    if !verify_admin_password(&credential.password_hash, &body.current_password)? {
+        return Err(AppError::Forbidden("Current admin password is incorrect".into()));
+    }
+
+    let secret_b32 = generate_totp_secret_b32();
+    let otpauth_url = admin_totp_otpauth_url(&session.wallet, &secret_b32);
+    sqlx::query(
+        "update admin_credentials set pending_totp_secret_b32 = $2, updated_at = now() where wallet = $1",
     )
-    .bind(days_i32)
-    .fetch_one(&st.db)
+    .bind(&session.wallet)
+    .bind(&secret_b32)
+    .execute(&st.db)
     .await
     .map_err(|e| AppError::Internal(e.to_string()))?;
 
-    let acquisition_source_rows = sqlx::query(
+    let refreshed = load_admin_credential(&st.db, &session.wallet)
+        .await?
+        .ok_or_else(|| AppError::Internal("Admin credential disappeared during MFA enrollment".into()))?;
+
+    Ok(Json(json!({
+        "ok": true,
+        "secret_b32": secret_b32,
+        "otpauth_url": otpauth_url,
+        "status": admin_auth_status_json(&st.admin_console_path, &session, Some(&refreshed), Some(&admin_session))
+    })))
+}
+
+async fn admin_auth_mfa_verify_handler(
+    State(st): State<AppState>,
+    headers: HeaderMap,
+    Json(body): Json<AdminAuthMfaVerifyRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let (session, _admin_session) = require_admin_console_access(&st, &headers).await?;
+    let credential = load_admin_credential(&st.db, &session.wallet)
+        .await?
+        .ok_or_else(|| AppError::BadRequest("Set an admin password for this wallet first".into()))?;
+
+    if !verify_admin_password(&credential.password_hash, &body.current_password)? {
+        return Err(AppError::Forbidden("Current admin password is incorrect".into()));
+    }
+
+    let pending_secret = credential
+        .pending_totp_secret_b32
+        .as_deref()
+        .ok_or_else(|| AppError::BadRequest("Start MFA enrollment before verifying a code".into()))?;
+    if !verify_totp_code(pending_secret, &body.totp_code)? {
+        return Err(AppError::Forbidden("Admin MFA code is incorrect".into()));
+    }
+
+    sqlx::query(
         r#"
-        with visitor_window as (
-            select
+        update admin_credentials
+        set
+            totp_secret_b32 = $2,
+            pending_totp_secret_b32 = null,
+            mfa_enabled = true,
+            updated_at = now()
+        where wallet = $1
+        "#,
+    )
+    .bind(&session.wallet)
+    .bind(pending_secret)
+    .execute(&st.db)
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?;
+    revoke_admin_console_sessions_for_wallet(&st.db, &session.wallet, None).await?;
+
+    let refreshed = load_admin_credential(&st.db, &session.wallet)
+        .await?
+        .ok_or_else(|| AppError::Internal("Admin credential disappeared after enabling MFA".into()))?;
+
+    Ok(Json(json!({
+        "ok": true,
+        "message": "MFA enabled. Sign in again with password and TOTP.",
+        "status": admin_auth_status_json(&st.admin_console_path, &session, Some(&refreshed), None)
+    })))
+}
+
+async fn admin_auth_mfa_disable_handler(
+    State(st): State<AppState>,
+    headers: HeaderMap,
+    Json(body): Json<AdminAuthMfaDisableRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let (session, admin_session) = require_admin_console_access(&st, &headers).await?;
+    let credential = load_admin_credential(&st.db, &session.wallet)
+        .await?
+        .ok_or_else(|| AppError::BadRequest("Set an admin password for this wallet first".into()))?;
+
+    if !credential.mfa_enabled {
+        return Err(AppError::BadRequest("MFA is not enabled for this admin wallet".into()));
+    }
+    if !verify_admin_password(&credential.password_hash, &body.current_password)? {
+        return Err(AppError::Forbidden("Current admin password is incorrect".into()));
+    }
+    let secret = credential.totp_secret_b32.as_deref().ok_or_else(|| {
+        AppError::Internal("Admin MFA is enabled but no TOTP secret is stored".into())
+    })?;
+    if !verify_totp_code(secret, &body.totp_code)? {
+        return Err(AppError::Forbidden("Admin MFA code is incorrect".into()));
+    }
+
+    sqlx::query(
+        r#"
+        update admin_credentials
+        set
+            mfa_enabled = false,
+            totp_secret_b32 = null,
+            pending_totp_secret_b32 = null,
+            updated_at = now()
+        where wallet = $1
+        "#,
+    )
+    .bind(&session.wallet)
+    .execute(&st.db)
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?;
+    revoke_admin_console_sessions_for_wallet(&st.db, &session.wallet, Some(&admin_session.admin_session_id))
+        .await?;
+
+    let refreshed = load_admin_credential(&st.db, &session.wallet)
+        .await?
+        .ok_or_else(|| AppError::Internal("Admin credential disappeared after disabling MFA".into()))?;
+
+    Ok(Json(json!({
+        "ok": true,
+        "message": "MFA disabled",
+        "status": admin_auth_status_json(&st.admin_console_path, &session, Some(&refreshed), Some(&admin_session))
+    })))
+}
+
+async fn admin_growth_overview_handler(
+    State(st): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<AdminGrowthQuery>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let (session, _) = require_admin_console_access(&st, &headers).await?;
+    let days = clamp_admin_days(query.days);
+    let days_i32 = days as i32;
+
+    let kpis = sqlx::query(
+        r#"
+        with first_seen as (
+            select chain, wallet, min(created_at) as first_seen_at
+            from wallet_sessions
+            group by chain, wallet
+        )
+        select
+            (select count(*) from first_seen) as total_wallets,
+            (select count(*) from first_seen where first_seen_at >= now() - interval '1 day') as new_wallets_1d,
+            (select count(*) from first_seen where first_seen_at >= now() - interval '7 day') as new_wallets_7d,
+            (select count(*) from first_seen where first_seen_at >= now() - interval '30 day') as new_wallets_30d,
+            (select count(distinct chain || ':' || wallet) from wallet_sessions where last_seen_at >= now() - interval '1 day') as active_wallets_1d,
+            (select count(distinct chain || ':' || wallet) from wallet_sessions where last_seen_at >= now() - interval '7 day') as active_wallets_7d,
+            (select count(distinct chain || ':' || wallet) from wallet_sessions where last_seen_at >= now() - interval '30 day') as active_wallets_30d,
+            (select count(*) from wallet_sessions where created_at >= now() - interval '30 day') as wallet_logins_30d,
+            (select count(*) from wallet_sessions where revoked_at is null and expires_at > now()) as active_sessions_now,
+            (select count(distinct coalesce(device_id, session_id)) from wallet_sessions where last_seen_at >= now() - interval '30 day') as active_devices_30d,
+            (select count(*) from documents where is_deleted = false) as total_docs,
+            (select count(*) from documents where is_deleted = false and parent_id is not null) as total_versions,
+            (select count(*) from document_shares) as total_shares,
+            (select count(*) from document_shares where share_arweave_tx is not null) as total_share_anchors,
+            (select count(*) from document_events where event_type in ('SIGN', 'ENVELOPE_COMPLETED', 'AGENT_SIGN')) as total_sign_events,
+            (select count(*) from agent_identities where coalesce(is_active, true)) as total_agents
+        "#,
+    )
+    .fetch_one(&st.db)
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let usage = sqlx::query(
+        r#"
+        select
+            (select count(*) from documents where is_deleted = false and parent_id is null and created_at >= now() - ($1::int * interval '1 day')) as uploads_window,
+            (select count(*) from documents where is_deleted = false and parent_id is not null and created_at >= now() - ($1::int * interval '1 day')) as versions_window,
+            (select count(*) from document_shares where created_at >= now() - ($1::int * interval '1 day')) as shares_window,
+            (select count(*) from document_events where event_type in ('SIGN', 'ENVELOPE_COMPLETED', 'AGENT_SIGN') and created_at >= now() - ($1::int * interval '1 day')) as signs_window,
+            (select count(*) from document_events where event_type = 'VIEW' and created_at >= now() - ($1::int * interval '1 day')) as views_window,
+            (select count(*) from document_events where event_type in ('DOWNLOAD', 'INBOX_DOWNLOADED') and created_at >= now() - ($1::int * interval '1 day')) as downloads_window,
+            (select count(*) from document_events where event_type like 'INBOX_%' and created_at >= now() - ($1::int * interval '1 day')) as inbox_actions_window,
+            (select count(*) from document_events where event_type = 'ENVELOPE_OPENED' and created_at >= now() - ($1::int * interval '1 day')) as public_opens_window,
+            (select count(*) from document_events where event_type = 'ENVELOPE_COMPLETED' and created_at >= now() - ($1::int * interval '1 day')) as public_completions_window,
+            (select count(*) from document_shares where share_arweave_tx is not null and share_anchored_at >= now() - ($1::int * interval '1 day')) as share_anchors_window,
+            (select count(*) from agent_identities where created_at >= now() - ($1::int * interval '1 day')) as agents_window
+        "#,
+    )
+    .bind(days_i32)
+    .fetch_one(&st.db)
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let acquisition = sqlx::query(
+        r#"
+        with visitor_window as (
+            select nullif(properties_json->>'visitor_id', '') as visitor_id
+            from growth_events
+            where occurred_at >= now() - ($1::int * interval '1 day')
+              and actor_kind = 'visitor'
+              and event_type = 'PAGE_VIEW'
+        ),
+        visitor_rollup as (
+            select visitor_id
+            from visitor_window
+            where visitor_id is not null
+            group by visitor_id
+        ),
+        login_visitors as (
+            select distinct nullif(properties_json->>'visitor_id', '') as visitor_id
+            from growth_events
+            where event_type = 'WALLET_LOGIN'
+              and nullif(properties_json->>'visitor_id', '') is not null
+        )
+        select
+            (select count(*) from visitor_window) as page_views_window,
+            (select count(*) from visitor_rollup) as unique_visitors_window,
+            (select count(*) from visitor_rollup vr join login_visitors lv on lv.visitor_id = vr.visitor_id) as converted_visitors_window,
+            (
+                select count(*)
+                from visitor_rollup vr
+                left join login_visitors lv on lv.visitor_id = vr.visitor_id
+                where lv.visitor_id is null
+            ) as anonymous_visitors_window
+        "#,
+    )
+    .bind(days_i32)
+    .fetch_one(&st.db)
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let acquisition_source_rows = sqlx::query(
+        r#"
+        with visitor_window as (
+            select
                 occurred_at,
                 nullif(properties_json->>'visitor_id', '') as visitor_id,
                 nullif(coalesce(properties_json->>'first_utm_source', properties_json->>'utm_source'), '') as utm_source,
@@ -2803,11 +3390,13 @@ fn device_id_from_headers(headers: &HeaderMap) -> Option<String> {
         .map(ToOwned::to_owned)
 }
 
-fn ip_from_headers(headers: &HeaderMap) -> Option<String> {
-    header_value(headers, "x-forwarded-for")
-        .or_else(|| header_value(headers, "x-real-ip"))
-        .map(|value| value.split(',').next().unwrap_or("").trim().to_string())
-        .filter(|value| !value.is_empty())
+/// The caller's address: the socket peer, or the hop a trusted proxy put
+/// in `X-Forwarded-For`. See `TrustedProxies::client_ip`.
+fn client_ip(st: &AppState, peer: SocketAddr, headers: &HeaderMap) -> IpAddr {
+    st.trusted_proxies.client_ip(
+        peer.ip(),
+        header_value(headers, "x-forwarded-for").as_deref(),
+    )
 }
 
 fn user_agent_from_headers(headers: &HeaderMap) -> Option<String> {
@@ -2879,10 +3468,25 @@ fn default_document_policy() -> serde_json::Value {
         "allow_agent_sign": false,
         "require_human_countersign": true,
         "allowed_agent_ids": [],
-        "allowed_wallet_signers": []
+        "allowed_wallet_signers": [],
+        "redact_owner_on_public_verify": true,
+        "require_hybrid_signature": false,
+        "ip_reputation": {
+            "tor": "flag",
+            "vpn": "allow",
+            "proxy": "allow",
+            "hosting": "allow"
+        }
     })
 }
 
+fn policy_requires_hybrid_signature(policy: &serde_json::Value) -> bool {
+    policy
+        .get("require_hybrid_signature")
+        .and_then(|value| value.as_bool())
+        .unwrap_or(false)
+}
+
 async fn load_document_policy(
     db: &PgPool,
     doc_id: uuid::Uuid,
@@ -2983,6 +3587,7 @@ async fn load_document_access_record(
             d.arweave_tx,
             coalesce(d.encryption_mode, 'plaintext_server_managed') as encryption_mode,
             d.ciphertext_hash_hex,
+            d.quarantined_at,
             exists (
               select 1
               from document_shares s
@@ -3018,10 +3623,15 @@ async fn load_document_access_record(
 
     let owner_wallet: String = row.get("owner_wallet");
     let shared_with_actor: bool = row.get("shared_with_actor");
+    let quarantined_at: Option<chrono::DateTime<chrono::Utc>> = row.get("quarantined_at");
 
     if !wallet_can_access_document(&owner_wallet, actor_wallet, shared_with_actor) {
         return Err(AppError::NotFound("Document not found".into()));
     }
+    // Quarantined documents stay visible to their owner only.
+    if quarantined_at.is_some() && !owner_wallet.eq_ignore_ascii_case(actor_wallet) {
+        return Err(AppError::NotFound("Document not found".into()));
+    }
 
     Ok(DocumentAccessRecord {
         id: row.get("id"),
@@ -3037,9 +3647,19 @@ async fn load_document_access_record(
         arweave_tx: row.get("arweave_tx"),
         encryption_mode: row.get("encryption_mode"),
         ciphertext_hash_hex: row.get("ciphertext_hash_hex"),
+        quarantined_at,
     })
 }
 
+fn ensure_not_quarantined(access: &DocumentAccessRecord) -> Result<(), AppError> {
+    if access.quarantined_at.is_some() {
+        return Err(AppError::Forbidden(
+            "Document is quarantined pending admin review".into(),
+        ));
+    }
+    Ok(())
+}
+
 fn bool_from_form_text(value: &str) -> bool {
     matches!(
         value.trim().to_ascii_lowercase().as_str(),
@@ -3059,68 +3679,619 @@ fn auto_share_anchor_enabled() -> bool {
         .unwrap_or_else(|_| auto_anchor_enabled())
 }
 
-fn share_anchor_hash_hex(
-    doc_id: uuid::Uuid,
-    doc_hash_hex: &str,
-    sender_wallet: &str,
-    sender_chain: &str,
-    recipient_wallet: Option<&str>,
-    recipient_chain: Option<&str>,
-    recipient_name: Option<&str>,
-    recipient_email: Option<&str>,
-    recipient_phone: Option<&str>,
-    note: Option<&str>,
-    envelope_id: uuid::Uuid,
-    expires_at: chrono::DateTime<chrono::Utc>,
-    one_time_use: bool,
-    download_allowed: bool,
-    allow_guest_sign: Option<bool>,
-) -> String {
-    let canonical = canonical_json(&json!({
-        "doc_id": doc_id,
-        "doc_hash_hex": doc_hash_hex,
-        "sender_wallet": sender_wallet,
-        "sender_chain": sender_chain,
-        "recipient_wallet": recipient_wallet,
-        "recipient_chain": recipient_chain,
-        "recipient_name": recipient_name,
-        "recipient_email": recipient_email,
-        "recipient_phone": recipient_phone,
-        "note": note,
-        "envelope_id": envelope_id,
-        "expires_at": expires_at.to_rfc3339(),
-        "one_time_use": one_time_use,
-        "download_allowed": download_allowed,
-        "allow_guest_sign": allow_guest_sign
-    }));
-    hex::encode(pqc_sha3::sha3_256_bytes(&canonical))
-}
-
-fn header_value(headers: &HeaderMap, name: &str) -> Option<String> {
-    headers
-        .get(name)
-        .and_then(|value| value.to_str().ok())
-        .map(ToOwned::to_owned)
+/// Merkle batching replaces per-hash anchoring when `ARWEAVE_BATCH_WINDOW_SECS`
+/// is set: hashes are queued and only each window's root goes to Arweave.
+fn anchor_batch_window() -> Option<std::time::Duration> {
+    std::env::var("ARWEAVE_BATCH_WINDOW_SECS")
+        .ok()
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .filter(|secs| *secs > 0)
+        .map(std::time::Duration::from_secs)
 }
 
-fn multipart_error(context: &str, err: impl std::fmt::Display) -> AppError {
-    AppError::BadRequest(format!("{context}: {err}"))
+fn anchor_batch_max_leaves() -> i64 {
+    std::env::var("ARWEAVE_BATCH_MAX_LEAVES")
+        .ok()
+        .and_then(|value| value.trim().parse::<i64>().ok())
+        .unwrap_or(4096)
+        .clamp(1, 65_536)
 }
 
-#[derive(Default)]
-struct ParsedMultipartUpload {
-    file_bytes: Option<Vec<u8>>,
-    label: Option<String>,
-    mime_type: Option<String>,
-    original_name: Option<String>,
-    anchor_to_arweave: bool,
-    encryption_source: Option<String>,
-    change_summary: Option<String>,
-    editor_mode: Option<String>,
-    before_hash_hex: Option<String>,
+/// How long a batch may hold its leaves without an Arweave transaction
+/// before another run takes them back. Well beyond one upload's timeout.
+fn anchor_batch_claim_timeout() -> std::time::Duration {
+    std::env::var("ARWEAVE_BATCH_CLAIM_TIMEOUT_SECS")
+        .ok()
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .map(|secs| secs.max(60))
+        .map(std::time::Duration::from_secs)
+        .unwrap_or(std::time::Duration::from_secs(15 * 60))
 }
 
-async fn parse_document_multipart(
+async fn enqueue_anchor_leaf(
+    db: &PgPool,
+    doc_id: uuid::Uuid,
+    share_id: Option<uuid::Uuid>,
+    event_id: Option<uuid::Uuid>,
+    kind: &str,
+    hash_hex: &str,
+) -> Result<(), AppError> {
+    hex::decode(hash_hex)
+        .map_err(|_| AppError::Internal(format!("anchor leaf is not hex: {hash_hex}")))?;
+    sqlx::query(
+        r#"
+        insert into anchor_leaves (id, doc_id, share_id, event_id, kind, hash_hex)
+        values ($1, $2, $3, $4, $5, $6)
+        "#,
+    )
+    .bind(uuid::Uuid::new_v4())
+    .bind(doc_id)
+    .bind(share_id)
+    .bind(event_id)
+    .bind(kind)
+    .bind(hash_hex.to_ascii_lowercase())
+    .execute(db)
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?;
+    Ok(())
+}
+
+/// Release leaves claimed by a batch that never got an Arweave transaction,
+/// e.g. because the server stopped mid-batch, so the next run anchors them.
+/// Returns the number of leaves released.
+async fn release_stale_anchor_claims(
+    db: &PgPool,
+    timeout: std::time::Duration,
+) -> Result<u64, AppError> {
+    let released = sqlx::query(
+        r#"
+        with stale as (
+            select l.id, l.batch_id
+            from anchor_leaves l
+            where l.batch_id is not null
+              and l.claimed_at < now() - make_interval(secs => $1)
+              and not exists (
+                  select 1 from anchor_batches b
+                  where b.id = l.batch_id and b.arweave_tx is not null
+              )
+            for update of l skip locked
+        ),
+        abandoned as (
+            update anchor_batches
+            set error = coalesce(error, 'claim expired before anchoring')
+            where id in (select batch_id from stale)
+        ),
+        released as (
+            update anchor_leaves l
+            set batch_id = null, claimed_at = null
+            from stale
+            where l.id = stale.id
+            returning l.id
+        )
+        select count(*) as released from released
+        "#,
+    )
+    .bind(timeout.as_secs_f64())
+    .fetch_one(db)
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+    .get::<i64, _>("released");
+    Ok(released as u64)
+}
+
+/// Claim pending leaves, anchor their Merkle root, and store each leaf's
+/// inclusion proof. Returns the number of leaves anchored.
+async fn run_anchor_batch(db: &PgPool) -> Result<usize, AppError> {
+    let batch_id = uuid::Uuid::new_v4();
+    let mut leaves = sqlx::query(
+        r#"
+        update anchor_leaves
+        set batch_id = $1, claimed_at = now()
+        where id in (
+            select id
+            from anchor_leaves
+            where batch_id is null
+            order by created_at, id
+            limit $2
+            for update skip locked
+        )
+        returning id, doc_id, share_id, kind, hash_hex, created_at
+        "#,
+    )
+    .bind(batch_id)
+    .bind(anchor_batch_max_leaves())
+    .fetch_all(db)
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?;
+    if leaves.is_empty() {
+        return Ok(0);
+    }
+    leaves.sort_by_key(|row| {
+        (
+            row.get::<chrono::DateTime<chrono::Utc>, _>("created_at"),
+            row.get::<uuid::Uuid, _>("id"),
+        )
+    });
+
+    let hashes = leaves
+        .iter()
+        .map(|row| row.get::<String, _>("hash_hex"))
+        .collect::<Vec<_>>();
+    let tree = MerkleTree::from_hex_leaves(&hashes).map_err(AppError::Internal)?;
+    let root_hex = tree.root_hex();
+
+    sqlx::query("insert into anchor_batches (id, root_hex, leaf_count) values ($1, $2, $3)")
+        .bind(batch_id)
+        .bind(&root_hex)
+        .bind(leaves.len() as i32)
+        .execute(db)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let client = crate::arweave::ArweaveClient::from_env();
+    let batch_label = batch_id.to_string();
+    let payload = crate::arweave::ArweaveAnchorPayload {
+        kind: "merkle_root",
+        hash_hex: &root_hex,
+        label: Some(&batch_label),
+    };
+    let tx_id = match client.anchor_hash(&payload).await {
+        Ok(tx_id) => tx_id,
+        Err(err) => {
+            // Release the leaves so the next window retries them.
+            sqlx::query("update anchor_batches set error = $2 where id = $1")
+                .bind(batch_id)
+                .bind(err.to_string())
+                .execute(db)
+                .await
+                .map_err(|e| AppError::Internal(e.to_string()))?;
+            sqlx::query(
+                "update anchor_leaves set batch_id = null, claimed_at = null where batch_id = $1",
+            )
+            .bind(batch_id)
+            .execute(db)
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+            return Err(err);
+        }
+    };
+
+    sqlx::query("update anchor_batches set arweave_tx = $2, anchored_at = now() where id = $1")
+        .bind(batch_id)
+        .bind(&tx_id)
+        .execute(db)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    track_arweave_anchor(db, &tx_id, "batch", &root_hex, None, None, Some(batch_id)).await?;
+
+    for (index, row) in leaves.iter().enumerate() {
+        let leaf_id: uuid::Uuid = row.get("id");
+        let doc_id: uuid::Uuid = row.get("doc_id");
+        let share_id: Option<uuid::Uuid> = row.get("share_id");
+        let kind: String = row.get("kind");
+        let proof = tree
+            .proof(index)
+            .ok_or_else(|| AppError::Internal("merkle proof index out of range".into()))?;
+
+        sqlx::query("update anchor_leaves set proof_json = $2 where id = $1")
+            .bind(leaf_id)
+            .bind(serde_json::to_value(&proof).map_err(|e| AppError::Internal(e.to_string()))?)
+            .execute(db)
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+
+        let target = match kind.as_str() {
+            "document" => {
+                "update documents set arweave_tx = $2 where id = $1 and arweave_tx is null"
+            }
+            "evidence_bundle" => {
+                "update documents set evidence_bundle_arweave_tx = $2 where id = $1"
+            }
+            "share" => {
+                "update document_shares set share_arweave_tx = $2, share_anchored_at = now() where id = $1"
+            }
+            _ => continue,
+        };
+        sqlx::query(target)
+            .bind(if kind == "share" {
+                share_id.unwrap_or_default()
+            } else {
+                doc_id
+            })
+            .bind(&tx_id)
+            .execute(db)
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+
+        insert_document_event(
+            db,
+            doc_id,
+            "system:anchor-batcher",
+            "ANCHOR_BATCHED",
+            json!({
+                "kind": kind,
+                "share_id": share_id,
+                "hash_hex": proof.leaf_hex,
+                "batch_id": batch_id,
+                "merkle_root_hex": root_hex,
+                "leaf_index": index,
+                "leaf_count": leaves.len(),
+                "arweave_tx": tx_id
+            }),
+        )
+        .await?;
+    }
+
+    Ok(leaves.len())
+}
+
+fn spawn_anchor_batcher(db: PgPool, window: std::time::Duration) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(window);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            // The first tick fires at startup, so this also recovers
+            // batches cut short by a restart.
+            match release_stale_anchor_claims(&db, anchor_batch_claim_timeout()).await {
+                Ok(0) => {}
+                Ok(count) => eprintln!("anchor: released {count} stale leaf claims"),
+                Err(err) => eprintln!("anchor: releasing stale claims failed: {err}"),
+            }
+            match run_anchor_batch(&db).await {
+                Ok(0) => {}
+                Ok(count) => eprintln!("anchor: batched {count} hashes"),
+                Err(err) => eprintln!("anchor: batch failed: {err}"),
+            }
+        }
+    });
+}
+
+/// Record a submitted anchor so the status poller can confirm or resubmit it.
+async fn track_arweave_anchor(
+    db: &PgPool,
+    tx_id: &str,
+    kind: &str,
+    hash_hex: &str,
+    doc_id: Option<uuid::Uuid>,
+    share_id: Option<uuid::Uuid>,
+    batch_id: Option<uuid::Uuid>,
+) -> Result<(), AppError> {
+    if crate::arweave::ArweaveClient::is_simulated_tx(tx_id) {
+        return Ok(());
+    }
+    sqlx::query(
+        r#"
+        insert into arweave_anchors (tx_id, kind, hash_hex, doc_id, share_id, batch_id)
+        values ($1, $2, $3, $4, $5, $6)
+        on conflict (tx_id) do nothing
+        "#,
+    )
+    .bind(tx_id)
+    .bind(kind)
+    .bind(hash_hex)
+    .bind(doc_id)
+    .bind(share_id)
+    .bind(batch_id)
+    .execute(db)
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?;
+    Ok(())
+}
+
+fn arweave_poll_interval() -> Option<std::time::Duration> {
+    std::env::var("ARWEAVE_API_KEY").ok()?;
+    let secs = std::env::var("ARWEAVE_POLL_INTERVAL_SECS")
+        .ok()
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .unwrap_or(300);
+    (secs > 0).then(|| std::time::Duration::from_secs(secs))
+}
+
+fn arweave_confirmation_depth() -> i64 {
+    std::env::var("ARWEAVE_CONFIRMATION_DEPTH")
+        .ok()
+        .and_then(|value| value.trim().parse::<i64>().ok())
+        .unwrap_or(10)
+        .max(1)
+}
+
+fn arweave_drop_after_secs() -> i64 {
+    std::env::var("ARWEAVE_DROP_AFTER_SECS")
+        .ok()
+        .and_then(|value| value.trim().parse::<i64>().ok())
+        .unwrap_or(3_600)
+        .max(60)
+}
+
+/// Documents whose custody is affected by a tracked anchor. Batch roots fan
+/// out to the documents that had non-event leaves in the batch, so the
+/// resulting custody events do not themselves keep re-triggering batches.
+async fn arweave_anchor_doc_ids(
+    db: &PgPool,
+    doc_id: Option<uuid::Uuid>,
+    batch_id: Option<uuid::Uuid>,
+) -> Result<Vec<uuid::Uuid>, AppError> {
+    let Some(batch_id) = batch_id else {
+        return Ok(doc_id.into_iter().collect());
+    };
+    let rows = sqlx::query(
+        "select distinct doc_id from anchor_leaves where batch_id = $1 and kind <> 'event'",
+    )
+    .bind(batch_id)
+    .fetch_all(db)
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?;
+    Ok(rows.into_iter().map(|row| row.get("doc_id")).collect())
+}
+
+/// Point every column that referenced a dropped transaction at its replacement.
+async fn replace_arweave_tx(db: &PgPool, old_tx: &str, new_tx: &str) -> Result<(), AppError> {
+    for statement in [
+        "update documents set arweave_tx = $2 where arweave_tx = $1",
+        "update documents set evidence_bundle_arweave_tx = $2 where evidence_bundle_arweave_tx = $1",
+        "update document_shares set share_arweave_tx = $2 where share_arweave_tx = $1",
+        "update anchor_batches set arweave_tx = $2 where arweave_tx = $1",
+    ] {
+        sqlx::query(statement)
+            .bind(old_tx)
+            .bind(new_tx)
+            .execute(db)
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+    }
+    Ok(())
+}
+
+/// Check pending anchors against the gateway. Confirmed transactions raise
+/// `ANCHOR_CONFIRMED`; transactions the gateway never saw within the drop
+/// window raise `ANCHOR_DROPPED` and are resubmitted with the same hash.
+async fn poll_arweave_anchors(
+    db: &PgPool,
+    client: &crate::arweave::ArweaveClient,
+) -> Result<usize, AppError> {
+    use crate::arweave::{anchor_poll_action, AnchorPollAction, ArweaveTxStatus};
+
+    let rows = sqlx::query(
+        r#"
+        select tx_id, kind, hash_hex, doc_id, share_id, batch_id, submitted_at
+        from arweave_anchors
+        where status = 'pending'
+        order by last_checked_at nulls first, submitted_at
+        limit 100
+        "#,
+    )
+    .fetch_all(db)
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let required_depth = arweave_confirmation_depth();
+    let drop_after_secs = arweave_drop_after_secs();
+    let mut settled = 0usize;
+
+    for row in rows {
+        let tx_id: String = row.get("tx_id");
+        let kind: String = row.get("kind");
+        let hash_hex: String = row.get("hash_hex");
+        let doc_id: Option<uuid::Uuid> = row.get("doc_id");
+        let share_id: Option<uuid::Uuid> = row.get("share_id");
+        let batch_id: Option<uuid::Uuid> = row.get("batch_id");
+        let submitted_at: chrono::DateTime<chrono::Utc> = row.get("submitted_at");
+        let age_secs = (chrono::Utc::now() - submitted_at).num_seconds();
+
+        // One unreachable transaction must not hold up the rest of the batch:
+        // record the failure, push the row to the back of the queue and move on.
+        let status = match client.tx_status(&tx_id).await {
+            Ok(status) => status,
+            Err(err) => {
+                eprintln!("anchor: status check for {tx_id} failed: {err}");
+                sqlx::query(
+                    r#"
+                    update arweave_anchors
+                    set last_checked_at = now(),
+                        check_failures = check_failures + 1,
+                        last_check_error = $2
+                    where tx_id = $1
+                    "#,
+                )
+                .bind(&tx_id)
+                .bind(err.to_string())
+                .execute(db)
+                .await
+                .map_err(|e| AppError::Internal(e.to_string()))?;
+                continue;
+            }
+        };
+        let (block_height, confirmations) = match &status {
+            ArweaveTxStatus::Confirmed {
+                block_height,
+                confirmations,
+            } => (Some(*block_height), Some(*confirmations)),
+            _ => (None, None),
+        };
+        sqlx::query(
+            r#"
+            update arweave_anchors
+            set last_checked_at = now(),
+                check_failures = 0,
+                last_check_error = null,
+                block_height = coalesce($2, block_height),
+                confirmations = coalesce($3, confirmations)
+            where tx_id = $1
+            "#,
+        )
+        .bind(&tx_id)
+        .bind(block_height)
+        .bind(confirmations)
+        .execute(db)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+        match anchor_poll_action(&status, age_secs, required_depth, drop_after_secs) {
+            AnchorPollAction::Wait => continue,
+            AnchorPollAction::Confirm => {
+                sqlx::query(
+                    "update arweave_anchors set status = 'confirmed', confirmed_at = now() where tx_id = $1",
+                )
+                .bind(&tx_id)
+                .execute(db)
+                .await
+                .map_err(|e| AppError::Internal(e.to_string()))?;
+
+                for affected in arweave_anchor_doc_ids(db, doc_id, batch_id).await? {
+                    insert_document_event(
+                        db,
+                        affected,
+                        "system:anchor-poller",
+                        "ANCHOR_CONFIRMED",
+                        json!({
+                            "kind": kind,
+                            "hash_hex": hash_hex,
+                            "share_id": share_id,
+                            "batch_id": batch_id,
+                            "arweave_tx": tx_id,
+                            "block_height": block_height,
+                            "confirmations": confirmations
+                        }),
+                    )
+                    .await?;
+                }
+            }
+            AnchorPollAction::Drop => {
+                let label = batch_id.map(|id| id.to_string());
+                let payload = crate::arweave::ArweaveAnchorPayload {
+                    kind: match kind.as_str() {
+                        "batch" => "merkle_root",
+                        "share" => "share_event_hash",
+                        _ => "file_version_hash",
+                    },
+                    hash_hex: &hash_hex,
+                    label: label.as_deref(),
+                };
+                // Leave the row pending on failure so the next tick retries.
+                let new_tx = match client.anchor_hash(&payload).await {
+                    Ok(new_tx) => new_tx,
+                    Err(err) => {
+                        eprintln!("anchor: resubmitting dropped {tx_id} failed: {err}");
+                        continue;
+                    }
+                };
+
+                sqlx::query(
+                    "update arweave_anchors set status = 'dropped', replaced_by_tx = $2 where tx_id = $1",
+                )
+                .bind(&tx_id)
+                .bind(&new_tx)
+                .execute(db)
+                .await
+                .map_err(|e| AppError::Internal(e.to_string()))?;
+                replace_arweave_tx(db, &tx_id, &new_tx).await?;
+                track_arweave_anchor(db, &new_tx, &kind, &hash_hex, doc_id, share_id, batch_id)
+                    .await?;
+
+                for affected in arweave_anchor_doc_ids(db, doc_id, batch_id).await? {
+                    insert_document_event(
+                        db,
+                        affected,
+                        "system:anchor-poller",
+                        "ANCHOR_DROPPED",
+                        json!({
+                            "kind": kind,
+                            "hash_hex": hash_hex,
+                            "share_id": share_id,
+                            "batch_id": batch_id,
+                            "arweave_tx": tx_id,
+                            "resubmitted_tx": new_tx
+                        }),
+                    )
+                    .await?;
+                }
+            }
+        }
+        settled += 1;
+    }
+
+    Ok(settled)
+}
+
+fn spawn_arweave_status_poller(db: PgPool, interval: std::time::Duration) {
+    tokio::spawn(async move {
+        let client = crate::arweave::ArweaveClient::from_env();
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            match poll_arweave_anchors(&db, &client).await {
+                Ok(0) => {}
+                Ok(count) => eprintln!("anchor: settled {count} arweave transactions"),
+                Err(err) => eprintln!("anchor: status poll failed: {err}"),
+            }
+        }
+    });
+}
+
+fn share_anchor_hash_hex(
+    doc_id: uuid::Uuid,
+    doc_hash_hex: &str,
+    sender_wallet: &str,
+    sender_chain: &str,
+    recipient_wallet: Option<&str>,
+    recipient_chain: Option<&str>,
+    recipient_name: Option<&str>,
+    recipient_email: Option<&str>,
+    recipient_phone: Option<&str>,
+    note: Option<&str>,
+    envelope_id: uuid::Uuid,
+    expires_at: chrono::DateTime<chrono::Utc>,
+    one_time_use: bool,
+    download_allowed: bool,
+    allow_guest_sign: Option<bool>,
+) -> String {
+    let canonical = canonical_json(&json!({
+        "doc_id": doc_id,
+        "doc_hash_hex": doc_hash_hex,
+        "sender_wallet": sender_wallet,
+        "sender_chain": sender_chain,
+        "recipient_wallet": recipient_wallet,
+        "recipient_chain": recipient_chain,
+        "recipient_name": recipient_name,
+        "recipient_email": recipient_email,
+        "recipient_phone": recipient_phone,
+        "note": note,
+        "envelope_id": envelope_id,
+        "expires_at": expires_at.to_rfc3339(),
+        "one_time_use": one_time_use,
+        "download_allowed": download_allowed,
+        "allow_guest_sign": allow_guest_sign
+    }));
+    hex::encode(pqc_sha3::sha3_256_bytes(&canonical))
+}
+
+fn header_value(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(ToOwned::to_owned)
+}
+
+fn multipart_error(context: &str, err: impl std::fmt::Display) -> AppError {
+    AppError::BadRequest(format!("{context}: {err}"))
+}
+
+#[derive(Default)]
+struct ParsedMultipartUpload {
+    file_bytes: Option<Vec<u8>>,
+    label: Option<String>,
+    mime_type: Option<String>,
+    original_name: Option<String>,
+    anchor_to_arweave: bool,
+    encryption_source: Option<String>,
+    change_summary: Option<String>,
+    editor_mode: Option<String>,
+    before_hash_hex: Option<String>,
+}
+
+async fn parse_document_multipart(
     mut multipart: Multipart,
     context_prefix: &str,
     default_anchor_to_arweave: bool,
@@ -3196,23 +4367,46 @@ async fn parse_document_multipart(
     Ok(parsed)
 }
 
-fn event_chain_hash_hex(
-    doc_id: uuid::Uuid,
-    actor_wallet: &str,
-    event_type: &str,
-    payload: &serde_json::Value,
-    created_at: chrono::DateTime<chrono::Utc>,
-    prev_event_hash_hex: Option<&str>,
-) -> Result<String, AppError> {
-    let canonical = canonical_json(&json!({
-        "doc_id": doc_id,
-        "actor_wallet": actor_wallet,
-        "event_type": event_type,
-        "payload": payload,
-        "created_at": created_at.to_rfc3339(),
-        "prev_event_hash_hex": prev_event_hash_hex
-    }));
-    Ok(hex::encode(pqc_sha3::sha3_256_bytes(&canonical)))
+/// `PgLedger` with the server's audit HMAC. When batching is on every event
+/// also queues a Merkle leaf.
+#[derive(Clone)]
+struct ServerLedger {
+    db: PgPool,
+}
+
+#[async_trait::async_trait]
+impl CustodyLedger for ServerLedger {
+    async fn append(&self, event: NewLedgerEvent) -> Result<LedgerEvent, AppError> {
+        let ev = PgLedger::new(self.db.clone())
+            .with_signer(sign_hmac_b64)
+            .append(event)
+            .await?;
+
+        if anchor_batch_window().is_some() {
+            enqueue_anchor_leaf(&self.db, ev.doc_id, None, Some(ev.id), "event", &ev.event_hash_hex)
+                .await?;
+        }
+        if let Err(err) = notify_document_event(&self.db, &ev).await {
+            eprintln!("events: could not notify {}: {err}", ev.id);
+        }
+        // A webhook outage or bad subscription must never block the custody write.
+        if let Err(err) = enqueue_webhook_deliveries(&self.db, &ev).await {
+            eprintln!(
+                "webhooks: could not queue {} {}: {err}",
+                ev.event_type, ev.id
+            );
+        }
+
+        Ok(ev)
+    }
+
+    async fn events_for_doc(&self, doc_id: uuid::Uuid) -> Result<Vec<LedgerEvent>, AppError> {
+        PgLedger::new(self.db.clone()).events_for_doc(doc_id).await
+    }
+
+    async fn contains_legacy(&self, legacy_id: &str) -> Result<bool, AppError> {
+        PgLedger::new(self.db.clone()).contains_legacy(legacy_id).await
+    }
 }
 
 async fn insert_document_event(
@@ -3222,63 +4416,96 @@ async fn insert_document_event(
     event_type: &str,
     payload: serde_json::Value,
 ) -> Result<uuid::Uuid, AppError> {
-    let previous = crate::sqlx::query(
-        r#"
-        select event_hash_hex
-        from document_events
-        where doc_id = $1
-        order by created_at desc, id desc
-        limit 1
-        "#,
-    )
-    .bind(doc_id)
-    .fetch_optional(db)
-    .await
-    .map_err(|e| AppError::Internal(e.to_string()))?;
-    let prev_event_hash_hex =
-        previous.and_then(|row| row.get::<Option<String>, _>("event_hash_hex"));
-    let created_at = chrono::Utc::now();
-    let event_hash_hex = event_chain_hash_hex(
-        doc_id,
-        actor_wallet,
-        event_type,
-        &payload,
-        created_at,
-        prev_event_hash_hex.as_deref(),
-    )?;
-    let event_hmac_b64 = sign_hmac_b64(event_hash_hex.as_bytes());
-    let id = uuid::Uuid::new_v4();
+    let ledger = ServerLedger { db: db.clone() };
+    let ev = ledger
+        .append(NewLedgerEvent::new(doc_id, actor_wallet, event_type, payload))
+        .await?;
+    Ok(ev.id)
+}
 
-    crate::sqlx::query(
-        r#"
-        insert into document_events (
-            id,
-            doc_id,
+/// Write an inline anchor's txid back to the document row and start
+/// tracking its confirmation.
+async fn store_document_anchor(
+    db: &PgPool,
+    doc_id: uuid::Uuid,
+    hash_hex: &str,
+    tx_id: &str,
+) -> Result<(), AppError> {
+    sqlx::query("update documents set arweave_tx = $1 where id = $2")
+        .bind(tx_id)
+        .bind(doc_id)
+        .execute(db)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    track_arweave_anchor(db, tx_id, "document", hash_hex, Some(doc_id), None, None).await
+}
+
+/// Run a newly persisted document through the shared c2c pipeline: inline
+/// Arweave anchor (unless batching covers it), custody event, then the
+/// FileTrail record. The txid is written back to the document row. A failed
+/// anchor does not fail the upload; like a batched one, it is queued and
+/// recorded later by a `DOCUMENT_ANCHORED` event.
+async fn record_document_pipeline(
+    st: &AppState,
+    record: &mut CreatedDocumentRecord,
+    content_sha256_hex: &str,
+    actor_wallet: &str,
+    event_type: &str,
+    payload: serde_json::Value,
+    anchor_to_arweave: bool,
+) -> Result<(), AppError> {
+    let ledger = ServerLedger { db: st.db.clone() };
+    let client = crate::arweave::ArweaveClient::from_env();
+    let anchor = if anchor_to_arweave && anchor_batch_window().is_none() {
+        AnchorMode::Arweave(&client)
+    } else {
+        AnchorMode::Skip
+    };
+    let doc_key = record.id.to_string();
+
+    let outcome = record_document_event(
+        &ledger,
+        anchor,
+        DocumentEvent {
+            doc_key: &doc_key,
+            action: if record.parent_id.is_some() {
+                FileAction::Updated
+            } else {
+                FileAction::Uploaded
+            },
             actor_wallet,
-            event_type,
-            payload,
-            created_at,
-            prev_event_hash_hex,
-            event_hash_hex,
-            event_hmac_b64
-        )
-        values ($1, $2, $3, $4, $5, $6, $7, $8, $9)
-        "#,
+            content: &[],
+            sha256_hex: Some(content_sha256_hex),
+            hash_hex: Some(&record.hash_hex),
+            path: &record.storage_path,
+            version: Some(record.version.max(1) as u64),
+            event_type: Some(event_type),
+            signer: None,
+            metadata: payload,
+        },
     )
-    .bind(id)
-    .bind(doc_id)
-    .bind(actor_wallet)
-    .bind(event_type)
-    .bind(payload)
-    .bind(created_at)
-    .bind(prev_event_hash_hex)
-    .bind(&event_hash_hex)
-    .bind(event_hmac_b64)
-    .execute(db)
-    .await
-    .map_err(|e| AppError::Internal(e.to_string()))?;
+    .await?;
+
+    if let Some(anchor) = outcome.anchor {
+        store_document_anchor(&st.db, record.id, &record.hash_hex, &anchor.tx_id).await?;
+        record.arweave_tx = Some(anchor.tx_id);
+    }
+    if let Some(err) = outcome.anchor_error {
+        eprintln!(
+            "anchor: inline anchor of {} failed, queued for retry: {err}",
+            record.id
+        );
+        schedule_job(
+            &st.db,
+            JOB_DOCUMENT_ANCHOR,
+            chrono::Utc::now(),
+            json!({ "doc_id": record.id }),
+            Some(&format!("{JOB_DOCUMENT_ANCHOR}:{}", record.id)),
+        )
+        .await?;
+    }
 
-    Ok(id)
+    Ok(())
 }
 
 fn session_actor_json(session: &WalletSession) -> serde_json::Value {
@@ -3343,7 +4570,11 @@ fn custody_payload(
     serde_json::Value::Object(payload)
 }
 
-fn public_custody_payload(base: serde_json::Value, headers: &HeaderMap) -> serde_json::Value {
+fn public_custody_payload(
+    base: serde_json::Value,
+    headers: &HeaderMap,
+    ip_reputation: &serde_json::Value,
+) -> serde_json::Value {
     let mut payload = match base {
         serde_json::Value::Object(map) => map,
         other => {
@@ -3370,6 +4601,7 @@ fn public_custody_payload(base: serde_json::Value, headers: &HeaderMap) -> serde
         "x_real_ip".into(),
         json!(header_value(headers, "x-real-ip")),
     );
+    payload.insert("ip_reputation".into(), ip_reputation.clone());
 
     serde_json::Value::Object(payload)
 }
@@ -3378,8 +4610,9 @@ fn public_custody_payload_for_envelope(
     base: serde_json::Value,
     envelope_id: uuid::Uuid,
     headers: &HeaderMap,
+    ip_reputation: &serde_json::Value,
 ) -> serde_json::Value {
-    let mut payload = match public_custody_payload(base, headers) {
+    let mut payload = match public_custody_payload(base, headers, ip_reputation) {
         serde_json::Value::Object(map) => map,
         _ => serde_json::Map::new(),
     };
@@ -3387,6 +4620,78 @@ fn public_custody_payload_for_envelope(
     serde_json::Value::Object(payload)
 }
 
+/// Looks up the caller's address and applies the document's `ip_reputation`
+/// policy. The returned summary goes into the public custody payload; a deny
+/// is recorded as `ENVELOPE_IP_DENIED` before the request is refused. Lookup
+/// failures are recorded but never block the link. The address comes from
+/// the connection, so a client cannot pick it with a forwarded header.
+async fn check_public_ip_reputation(
+    st: &AppState,
+    doc_id: uuid::Uuid,
+    envelope_id: uuid::Uuid,
+    policy: &serde_json::Value,
+    peer: SocketAddr,
+    headers: &HeaderMap,
+) -> Result<serde_json::Value, AppError> {
+    let ip = client_ip(st, peer, headers);
+    let Some(provider) = st.ip_reputation.as_ref() else {
+        return Ok(json!({ "status": "unchecked", "ip": ip }));
+    };
+
+    let reputation = match provider.lookup(ip).await {
+        Ok(Some(reputation)) => reputation,
+        Ok(None) => {
+            return Ok(json!({
+                "status": "not_listed",
+                "provider": provider.name(),
+                "ip": ip,
+                "action": IpPolicyAction::Allow
+            }))
+        }
+        Err(e) => {
+            return Ok(json!({
+                "status": "error",
+                "provider": provider.name(),
+                "ip": ip,
+                "error": e.to_string(),
+                "action": IpPolicyAction::Allow
+            }))
+        }
+    };
+
+    let (action, matched) = IpReputationPolicy::from_document_policy(policy).evaluate(&reputation);
+    let summary = json!({
+        "status": "listed",
+        "provider": provider.name(),
+        "ip": ip,
+        "categories": reputation.categories(),
+        "matched": matched,
+        "action": action
+    });
+
+    if action == IpPolicyAction::Deny {
+        insert_document_event(
+            &st.db,
+            doc_id,
+            &format!("guest-envelope:{envelope_id}"),
+            "ENVELOPE_IP_DENIED",
+            public_custody_payload_for_envelope(
+                json!({ "envelope_id": envelope_id }),
+                envelope_id,
+                headers,
+                &summary,
+            ),
+        )
+        .await?;
+        return Err(AppError::Forbidden(format!(
+            "This document does not accept access from {} networks",
+            matched.join("/")
+        )));
+    }
+
+    Ok(summary)
+}
+
 fn agent_custody_payload(
     base: serde_json::Value,
     agent: &AgentIdentityRecord,
@@ -3468,17 +4773,17 @@ async fn build_document_envelope(
         Some(mime_type.to_string()),
     );
     let created_at = time::OffsetDateTime::now_utc().unix_timestamp();
-    let (envelope, _) = DocumentEnvelopeV1::create_mlkem_owner(
-        owner_wallet.to_string(),
+    let (envelope, _) = DocumentEnvelopeV2::create_mlkem_owner(
+        owner_wallet,
         &keys.pk_b64,
         created_at,
         doc,
         plaintext,
     )
     .map_err(AppError::Crypto)?;
-    let canonical = canonical_json(&envelope);
-    let ciphertext_hash_hex = hex::encode(pqc_sha3::sha3_256_bytes(&canonical));
-    Ok((canonical, ciphertext_hash_hex))
+    let stored_bytes = envelope.encode();
+    let ciphertext_hash_hex = hex::encode(pqc_sha3::sha3_256_bytes(&stored_bytes));
+    Ok((stored_bytes, ciphertext_hash_hex))
 }
 
 fn is_envelope_encryption_mode(mode: &str) -> bool {
@@ -3494,31 +4799,229 @@ async fn decrypt_document_envelope(
     encrypted_bytes: &[u8],
 ) -> Result<Vec<u8>, AppError> {
     let keys = load_or_create_server_mlkem_keypair(db, owner_wallet).await?;
-    let envelope: DocumentEnvelopeV1 = serde_json::from_slice(encrypted_bytes)
-        .map_err(|e| AppError::Crypto(format!("envelope parse: {e}")))?;
+    let envelope = read_document_envelope(encrypted_bytes).map_err(AppError::Crypto)?;
     envelope
         .decrypt_for_owner_mlkem(&keys.sk_b64)
         .map_err(AppError::Crypto)
 }
 
-async fn load_document_bytes_for_access(
+async fn load_wallet_mlkem_public_key(
+    db: &PgPool,
+    wallet: &str,
+) -> Result<Option<String>, AppError> {
+    let chain = infer_wallet_chain(wallet);
+    let wallet = normalize_wallet_for_chain(wallet, chain);
+    let row = sqlx::query(
+        r#"
+        select pk_b64
+        from wallet_mlkem_keys
+        where (
+                ($2 = 'evm' and lower(wallet) = lower($1))
+             or ($2 = 'sol' and wallet = $1)
+              )
+        limit 1
+        "#,
+    )
+    .bind(&wallet)
+    .bind(chain)
+    .fetch_optional(db)
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    Ok(row.map(|row| row.get("pk_b64")))
+}
+
+async fn load_stored_envelope(
     st: &AppState,
     access: &DocumentAccessRecord,
-) -> Result<DocumentBytesResponse, AppError> {
+) -> Result<DocumentEnvelopeV2, AppError> {
+    let stored = st
+        .storage
+        .download_bytes(&access.storage_path)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    read_document_envelope(&stored).map_err(AppError::Crypto)
+}
+
+/// Upload a rewritten envelope and repoint the document at it. The row is only
+/// updated if it still references the envelope we read, so two concurrent
+/// rewrites cannot silently drop each other's recipients. Rewrites are always
+/// stored as V2.
+async fn replace_stored_envelope(
+    st: &AppState,
+    access: &DocumentAccessRecord,
+    envelope: &DocumentEnvelopeV2,
+) -> Result<String, AppError> {
+    let stored_bytes = envelope.encode();
+    let ciphertext_hash_hex = hex::encode(pqc_sha3::sha3_256_bytes(&stored_bytes));
+    let storage_path = st
+        .storage
+        .upload_bytes(
+            &access.owner_wallet,
+            &access.id.to_string(),
+            access.version,
+            &stored_bytes,
+            ENVELOPE_V2_CONTENT_TYPE,
+        )
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let result = sqlx::query(
+        r#"
+        update documents
+        set storage_path = $3,
+            ciphertext_hash_hex = $4
+        where id = $1
+          and storage_path = $2
+        "#,
+    )
+    .bind(access.id)
+    .bind(&access.storage_path)
+    .bind(&storage_path)
+    .bind(&ciphertext_hash_hex)
+    .execute(&st.db)
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    if result.rows_affected() == 0 {
+        let _ = st.storage.delete_object(&storage_path).await;
+        return Err(AppError::BadRequest(
+            "Document envelope changed while it was being updated; retry".into(),
+        ));
+    }
+
+    if storage_path != access.storage_path {
+        if let Err(err) = st.storage.delete_object(&access.storage_path).await {
+            eprintln!(
+                "storage: failed to delete superseded envelope {}: {err}",
+                access.storage_path
+            );
+        }
+    }
+
+    Ok(ciphertext_hash_hex)
+}
+
+async fn load_stream_header(
+    st: &AppState,
+    access: &DocumentAccessRecord,
+) -> Result<DocumentStreamEnvelopeV1, AppError> {
     let stored = st
         .storage
         .download_bytes(&access.storage_path)
         .await
         .map_err(|e| AppError::Internal(e.to_string()))?;
+    serde_json::from_slice(&stored)
+        .map_err(|e| AppError::Crypto(format!("stream envelope parse: {e}")))
+}
+
+/// Wrap the document CEK for a wallet recipient with a registered ML-KEM key,
+/// leaving the payload ciphertext as is. Returns false when the document is
+/// not envelope-encrypted or the recipient has no key. Streamed documents
+/// cannot be re-keyed without rewriting every segment, so a key is never
+/// handed out for them; the error lands in `ENVELOPE_RECIPIENT_FAILED`.
+async fn add_envelope_recipient(
+    st: &AppState,
+    access: &DocumentAccessRecord,
+    recipient_wallet: &str,
+) -> Result<bool, AppError> {
+    let streamed = access.encryption_mode == ENCRYPTION_MODE_SERVER_STREAM;
+    if !streamed && !is_envelope_encryption_mode(&access.encryption_mode) {
+        return Ok(false);
+    }
+    let Some(recipient_pk_b64) = load_wallet_mlkem_public_key(&st.db, recipient_wallet).await?
+    else {
+        return Ok(false);
+    };
+    if streamed {
+        return Err(AppError::BadRequest(
+            "Streamed documents are wrapped only for the owner; the recipient's key was not added"
+                .into(),
+        ));
+    }
+
+    let owner_keys = load_or_create_server_mlkem_keypair(&st.db, &access.owner_wallet).await?;
+    let mut envelope = load_stored_envelope(st, access).await?;
+    let holder = envelope.owner().to_string();
+    envelope
+        .add_recipient_mlkem(&holder, &owner_keys.sk_b64, recipient_wallet, &recipient_pk_b64)
+        .map_err(AppError::Crypto)?;
+    replace_stored_envelope(st, access, &envelope).await?;
+    Ok(true)
+}
+
+/// Re-encrypt under a fresh CEK without `revoked_wallet`. Remaining recipients
+/// are re-wrapped if their ML-KEM key is still registered. Returns the new
+/// recipient list, or None when the revoked wallet never held a wrapped key.
+async fn rekey_envelope_without(
+    st: &AppState,
+    access: &DocumentAccessRecord,
+    revoked_wallet: &str,
+) -> Result<Option<Vec<String>>, AppError> {
+    if access.encryption_mode == ENCRYPTION_MODE_SERVER_STREAM {
+        let header = load_stream_header(st, access).await?;
+        if header.has_recipient(revoked_wallet) {
+            return Err(AppError::BadRequest(
+                "Streamed documents cannot be re-keyed; the revoked wallet still holds a wrapped key"
+                    .into(),
+            ));
+        }
+        return Ok(None);
+    }
+    if !is_envelope_encryption_mode(&access.encryption_mode) {
+        return Ok(None);
+    }
+    let envelope = load_stored_envelope(st, access).await?;
+    if !envelope.has_recipient(revoked_wallet) {
+        return Ok(None);
+    }
+
+    let owner_keys = load_or_create_server_mlkem_keypair(&st.db, &access.owner_wallet).await?;
+    let normalize = |wallet: &str| normalize_wallet_for_chain(wallet, infer_wallet_chain(wallet));
+    let owner = normalize(envelope.owner());
+    let revoked = normalize(revoked_wallet);
+
+    let mut recipients = vec![(envelope.owner().to_string(), owner_keys.pk_b64.clone())];
+    for wallet in envelope.recipients() {
+        let wallet = normalize(&wallet);
+        if wallet == owner || wallet == revoked {
+            continue;
+        }
+        if let Some(pk_b64) = load_wallet_mlkem_public_key(&st.db, &wallet).await? {
+            recipients.push((wallet, pk_b64));
+        }
+    }
 
-    let bytes = if is_envelope_encryption_mode(&access.encryption_mode) {
-        decrypt_document_envelope(&st.db, &access.owner_wallet, &stored).await?
+    let (rekeyed, _) = envelope
+        .rekey_mlkem(envelope.owner(), &owner_keys.sk_b64, recipients)
+        .map_err(AppError::Crypto)?;
+    replace_stored_envelope(st, access, &rekeyed).await?;
+    Ok(Some(rekeyed.recipients()))
+}
+
+async fn load_document_bytes_for_access(
+    st: &AppState,
+    access: &DocumentAccessRecord,
+) -> Result<DocumentBytesResponse, AppError> {
+    ensure_not_quarantined(access)?;
+    let body = if access.encryption_mode == ENCRYPTION_MODE_SERVER_STREAM {
+        stream_document_body(st, access).await?
     } else {
-        stored
+        let stored = st
+            .storage
+            .download_bytes(&access.storage_path)
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+
+        Body::from(if is_envelope_encryption_mode(&access.encryption_mode) {
+            decrypt_document_envelope(&st.db, &access.owner_wallet, &stored).await?
+        } else {
+            stored
+        })
     };
 
     Ok(DocumentBytesResponse {
-        bytes,
+        body,
         mime_type: access.mime_type.clone(),
         label: access.label.clone(),
         hash_hex: access.hash_hex.clone(),
@@ -3529,15 +5032,45 @@ async fn load_document_bytes_for_access(
     })
 }
 
-fn document_sign_message(doc_id: uuid::Uuid, hash_hex: &str, wallet: &str, version: i32) -> String {
-    format!(
-        "TIDBIT Document Attestation\n\
-Document ID: {doc_id}\n\
-Hash: {hash_hex}\n\
-Action: SIGN\n\
-Wallet: {wallet}\n\
-Version: {version}"
-    )
+/// Decrypt a streamed document one segment at a time, so the response never
+/// holds more than a single segment in memory.
+async fn stream_document_body(
+    st: &AppState,
+    access: &DocumentAccessRecord,
+) -> Result<Body, AppError> {
+    let header = load_stream_header(st, access).await?;
+    let keys = load_or_create_server_mlkem_keypair(&st.db, &access.owner_wallet).await?;
+    let cipher = header
+        .cipher_for_wallet(&header.owner, &keys.sk_b64)
+        .map_err(AppError::Crypto)?;
+
+    let storage = st.storage.clone();
+    let owner = access.owner_wallet.clone();
+    let doc_id = access.id.to_string();
+    let version = access.version;
+    let header = std::sync::Arc::new(header);
+    let cipher = std::sync::Arc::new(cipher);
+    let segments = futures_util::stream::try_unfold(0u64, move |index| {
+        let storage = storage.clone();
+        let header = header.clone();
+        let cipher = cipher.clone();
+        let path = stream_segment_path(&owner, &doc_id, version, index);
+        async move {
+            if index >= header.encryption.segment_count {
+                return Ok(None);
+            }
+            let ciphertext = storage
+                .download_bytes(&path)
+                .await
+                .map_err(|e| AppError::Internal(e.to_string()))?;
+            let plaintext = cipher
+                .decrypt_segment(index, header.is_last_segment(index), &ciphertext)
+                .map_err(AppError::Crypto)?;
+            Ok::<_, AppError>(Some((Bytes::from(plaintext), index + 1)))
+        }
+    });
+
+    Ok(Body::from_stream(segments))
 }
 
 fn public_envelope_sign_message(
@@ -3733,16 +5266,214 @@ fn derive_share_status(
     }
 }
 
-fn active_inbox_status(status: &str) -> bool {
-    matches!(
-        status,
-        "wallet_shared"
-            | "wallet_shared_with_delivery_issues"
-            | "sent"
-            | "created"
-            | "delivery_issue"
-            | "opened"
-    )
+/// A share envelope opened on the owner's behalf outside `share_doc_handler`,
+/// e.g. for a workflow step or an escalation.
+struct ShareEnvelopeRequest<'a> {
+    doc: &'a DocumentAccessRecord,
+    /// Set by retried jobs so every attempt targets the same envelope.
+    envelope_id: Option<uuid::Uuid>,
+    recipient_wallet: Option<&'a str>,
+    recipient_chain: Option<&'a str>,
+    recipient_name: Option<&'a str>,
+    recipient_email: Option<&'a str>,
+    recipient_phone: Option<&'a str>,
+    note: Option<&'a str>,
+    expires_at: chrono::DateTime<chrono::Utc>,
+    one_time_use: bool,
+    download_allowed: bool,
+    allow_guest_sign: Option<bool>,
+}
+
+struct IssuedShareEnvelope {
+    share_id: uuid::Uuid,
+    doc_id: uuid::Uuid,
+    envelope_id: uuid::Uuid,
+    access_token_hash: String,
+    has_provider_request: bool,
+    deliveries: Vec<DeliveryOutcome>,
+    delivery_errors: Vec<String>,
+    status: &'static str,
+    recipient_key_wrapped: bool,
+    recipient_key_error: Option<String>,
+}
+
+/// Insert the share row, send invites, and wrap the document key for a
+/// wallet recipient. Custody events are left to the caller.
+async fn issue_share_envelope(
+    st: &AppState,
+    request: &ShareEnvelopeRequest<'_>,
+) -> Result<IssuedShareEnvelope, AppError> {
+    let doc = request.doc;
+    let envelope_id = request.envelope_id.unwrap_or_else(uuid::Uuid::new_v4);
+    let access_token = uuid::Uuid::new_v4().simple().to_string();
+    let access_token_hash = access_token_hash_hex(&access_token);
+    let has_wallet_route = request.recipient_wallet.is_some();
+    let has_provider_request =
+        request.recipient_email.is_some() || request.recipient_phone.is_some();
+
+    let share_id = sqlx::query(
+        r#"insert into document_shares
+        (doc_id, sender_wallet, recipient_wallet, recipient_chain, recipient_name, envelope_id, note, recipient_email, recipient_phone, access_token_hash, expires_at, one_time_use, download_allowed, allow_guest_sign, status, delivery_json)
+        values ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11,$12,$13,$14,$15,$16)
+        returning id"#,
+    )
+    .bind(doc.id)
+    .bind(&doc.owner_wallet)
+    .bind(request.recipient_wallet)
+    .bind(request.recipient_chain)
+    .bind(request.recipient_name)
+    .bind(envelope_id)
+    .bind(request.note)
+    .bind(request.recipient_email)
+    .bind(request.recipient_phone)
+    .bind(&access_token_hash)
+    .bind(request.expires_at)
+    .bind(request.one_time_use)
+    .bind(request.download_allowed)
+    .bind(request.allow_guest_sign)
+    .bind(if has_wallet_route { "wallet_shared" } else { "created" })
+    .bind(json!([]))
+    .fetch_one(&st.db)
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+    .get::<uuid::Uuid, _>("id");
+
+    let (mut deliveries, delivery_errors) = dispatch_share_deliveries(
+        request.recipient_email,
+        request.recipient_phone,
+        doc.label.as_deref(),
+        doc.id,
+        &doc.hash_hex,
+        envelope_id,
+        request.recipient_name,
+        &envelope_signing_url(&access_token),
+    )
+    .await;
+    if let Some(wallet) = request.recipient_wallet {
+        deliveries.insert(
+            0,
+            DeliveryOutcome {
+                channel: "wallet",
+                provider: "tidbit",
+                recipient: wallet.to_string(),
+                external_id: Some(envelope_id.to_string()),
+                status: "available_in_inbox",
+            },
+        );
+    }
+    let status = derive_share_status(
+        has_wallet_route,
+        has_provider_request,
+        &deliveries,
+        &delivery_errors,
+    );
+    sqlx::query("update document_shares set delivery_json = $2, status = $3 where id = $1")
+        .bind(share_id)
+        .bind(serde_json::to_value(&deliveries).map_err(|e| AppError::Internal(e.to_string()))?)
+        .bind(status)
+        .execute(&st.db)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let mut recipient_key_wrapped = false;
+    let mut recipient_key_error: Option<String> = None;
+    if let Some(wallet) = request.recipient_wallet {
+        match add_envelope_recipient(st, doc, wallet).await {
+            Ok(wrapped) => recipient_key_wrapped = wrapped,
+            Err(err) => recipient_key_error = Some(err.to_string()),
+        }
+    }
+
+    Ok(IssuedShareEnvelope {
+        share_id,
+        doc_id: doc.id,
+        envelope_id,
+        access_token_hash,
+        has_provider_request,
+        deliveries,
+        delivery_errors,
+        status,
+        recipient_key_wrapped,
+        recipient_key_error,
+    })
+}
+
+async fn record_envelope_recipient_outcome(
+    db: &PgPool,
+    issued: &IssuedShareEnvelope,
+    actor_wallet: &str,
+    recipient_wallet: Option<&str>,
+    recipient_chain: Option<&str>,
+    wrap: impl Fn(serde_json::Value) -> serde_json::Value,
+) -> Result<(), AppError> {
+    if !issued.recipient_key_wrapped && issued.recipient_key_error.is_none() {
+        return Ok(());
+    }
+    insert_document_event(
+        db,
+        issued.doc_id,
+        actor_wallet,
+        if issued.recipient_key_wrapped {
+            "ENVELOPE_RECIPIENT_ADDED"
+        } else {
+            "ENVELOPE_RECIPIENT_FAILED"
+        },
+        wrap(json!({
+            "envelope_id": issued.envelope_id,
+            "recipient_wallet": recipient_wallet,
+            "recipient_chain": recipient_chain,
+            "error": issued.recipient_key_error
+        })),
+    )
+    .await?;
+    Ok(())
+}
+
+/// One `DELIVERY_DISPATCHED` per outcome and one `DELIVERY_FAILED` per
+/// error. `context` (at least the `envelope_id`) goes into every payload.
+async fn record_delivery_outcomes(
+    db: &PgPool,
+    doc_id: uuid::Uuid,
+    actor_wallet: &str,
+    deliveries: &[DeliveryOutcome],
+    delivery_errors: &[String],
+    context: serde_json::Value,
+    wrap: impl Fn(serde_json::Value) -> serde_json::Value,
+) -> Result<(), AppError> {
+    for outcome in deliveries {
+        let mut payload = context.clone();
+        payload["channel"] = json!(outcome.channel);
+        payload["provider"] = json!(outcome.provider);
+        payload["recipient"] = json!(outcome.recipient);
+        payload["external_id"] = json!(outcome.external_id);
+        payload["status"] = json!(outcome.status);
+        insert_document_event(
+            db,
+            doc_id,
+            actor_wallet,
+            "DELIVERY_DISPATCHED",
+            wrap(payload),
+        )
+        .await?;
+    }
+    for error in delivery_errors {
+        let mut payload = context.clone();
+        payload["error"] = json!(error);
+        insert_document_event(db, doc_id, actor_wallet, "DELIVERY_FAILED", wrap(payload)).await?;
+    }
+    Ok(())
+}
+
+fn active_inbox_status(status: &str) -> bool {
+    matches!(
+        status,
+        "wallet_shared"
+            | "wallet_shared_with_delivery_issues"
+            | "sent"
+            | "created"
+            | "delivery_issue"
+            | "opened"
+    )
 }
 
 async fn create_document_record(
@@ -3835,26 +5566,15 @@ async fn persist_document_record(
             &id.to_string(),
             version,
             &stored_bytes,
-            "application/tidbit-envelope+json",
+            envelope_content_type(&stored_bytes),
         )
         .await
         .map_err(|e| AppError::Internal(e.to_string()))?;
 
-    let arweave_tx = if anchor_to_arweave {
-        Some(
-            crate::arweave::anchor_hash_to_arweave(&hash_hex)
-                .await
-                .map_err(|e| AppError::Internal(e.to_string()))?
-                .tx_id,
-        )
-    } else {
-        None
-    };
-
     sqlx::query(
         r#"insert into documents
-        (id, owner_wallet, hash_hex, label, file_size, mime_type, storage_path, version, parent_id, arweave_tx, is_deleted, encryption_mode, ciphertext_hash_hex)
-        values ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,false,$11,$12)"#,
+        (id, owner_wallet, hash_hex, label, file_size, mime_type, storage_path, version, parent_id, is_deleted, encryption_mode, ciphertext_hash_hex)
+        values ($1,$2,$3,$4,$5,$6,$7,$8,$9,false,$10,$11)"#,
     )
     .bind(id)
     .bind(&owner_wallet)
@@ -3865,13 +5585,18 @@ async fn persist_document_record(
     .bind(&storage_path)
     .bind(version)
     .bind(parent_id)
-    .bind(&arweave_tx)
     .bind(encryption_mode)
     .bind(&ciphertext_hash_hex)
     .execute(&st.db)
     .await
     .map_err(|e| AppError::Internal(e.to_string()))?;
 
+    // Inline anchors are submitted by `record_document_pipeline` once the
+    // custody event exists; batched anchors are queued here.
+    if anchor_to_arweave && anchor_batch_window().is_some() {
+        enqueue_anchor_leaf(&st.db, id, None, None, "document", &hash_hex).await?;
+    }
+
     Ok(CreatedDocumentRecord {
         id,
         hash_hex,
@@ -3880,7 +5605,7 @@ async fn persist_document_record(
         version,
         mime_type,
         parent_id,
-        arweave_tx,
+        arweave_tx: None,
         encryption_mode: encryption_mode.to_string(),
     })
 }
@@ -3895,31 +5620,26 @@ async fn create_document_record_from_client_envelope(
     parent_version: Option<i32>,
     anchor_to_arweave: bool,
 ) -> Result<CreatedDocumentRecord, AppError> {
-    let mut envelope: DocumentEnvelopeV1 = serde_json::from_slice(envelope_bytes)
+    let mut envelope = read_document_envelope(envelope_bytes)
         .map_err(|e| AppError::BadRequest(format!("Invalid encrypted upload envelope: {e}")))?;
+    let header = &mut envelope.header;
     let owner_wallet = normalize_wallet_for_chain(owner_wallet, infer_wallet_chain(owner_wallet));
-    if envelope.v != 1 {
-        return Err(AppError::BadRequest(
-            "Unsupported encrypted upload envelope version".into(),
-        ));
-    }
-    envelope.owner =
-        normalize_wallet_for_chain(&envelope.owner, infer_wallet_chain(&envelope.owner));
-    envelope.encryption.wrapped_keys.iter_mut().for_each(|key| {
+    header.owner = normalize_wallet_for_chain(&header.owner, infer_wallet_chain(&header.owner));
+    header.encryption.wrapped_keys.iter_mut().for_each(|key| {
         key.recipient =
             normalize_wallet_for_chain(&key.recipient, infer_wallet_chain(&key.recipient));
     });
-    if envelope.owner != owner_wallet {
+    if header.owner != owner_wallet {
         return Err(AppError::Forbidden(
             "Encrypted upload envelope owner does not match the active wallet".into(),
         ));
     }
-    if envelope.encryption.alg != "xchacha20poly1305" || envelope.encryption.cek_wrap != "mlkem" {
+    if header.encryption.alg != "xchacha20poly1305" || header.encryption.cek_wrap != "mlkem" {
         return Err(AppError::BadRequest(
             "Encrypted upload envelope uses an unsupported algorithm".into(),
         ));
     }
-    if !envelope
+    if !header
         .encryption
         .wrapped_keys
         .iter()
@@ -3930,7 +5650,7 @@ async fn create_document_record_from_client_envelope(
         ));
     }
 
-    let hash_hex = envelope
+    let hash_hex = header
         .doc
         .plaintext_sha3_256_hex
         .trim()
@@ -3945,33 +5665,36 @@ async fn create_document_record_from_client_envelope(
 
     let id = uuid::Uuid::new_v4();
     let version = parent_version.map(|value| value + 1).unwrap_or(1);
-    envelope.owner = owner_wallet;
-    envelope.doc.logical_id = id.to_string();
+    header.owner = owner_wallet.clone();
+    header.doc.logical_id = id.to_string();
 
     let label = label
         .map(|value| value.trim().to_string())
         .filter(|value| !value.is_empty())
         .or_else(|| {
-            envelope
+            header
                 .doc
                 .filename
                 .clone()
                 .map(|value| value.trim().to_string())
                 .filter(|value| !value.is_empty())
         });
-    envelope.doc.filename = label.clone();
+    header.doc.filename = label.clone();
 
     let mime_type = fallback_mime_type
         .and_then(|value| {
             let trimmed = value.trim().to_string();
-            if trimmed.is_empty() || trimmed == "application/tidbit-envelope+json" {
+            if trimmed.is_empty()
+                || trimmed == ENVELOPE_V1_CONTENT_TYPE
+                || trimmed == ENVELOPE_V2_CONTENT_TYPE
+            {
                 None
             } else {
                 Some(trimmed)
             }
         })
         .or_else(|| {
-            envelope
+            header
                 .doc
                 .mime
                 .clone()
@@ -3979,19 +5702,20 @@ async fn create_document_record_from_client_envelope(
                 .filter(|value| !value.is_empty())
         })
         .unwrap_or_else(|| "application/octet-stream".to_string());
-    envelope.doc.mime = Some(mime_type.clone());
+    header.doc.mime = Some(mime_type.clone());
+    let file_size = header.doc.size_bytes as i64;
 
-    let stored_bytes = canonical_json(&envelope);
+    let stored_bytes = envelope.encode();
     let ciphertext_hash_hex = hex::encode(pqc_sha3::sha3_256_bytes(&stored_bytes));
     persist_document_record(
         st,
-        &envelope.owner,
+        &owner_wallet,
         id,
         version,
         hash_hex,
         label,
         mime_type,
-        envelope.doc.size_bytes as i64,
+        file_size,
         parent_id,
         anchor_to_arweave,
         stored_bytes,
@@ -4007,20 +5731,23 @@ async fn create_document_record_from_client_envelope(
 
 async fn evm_nonce_handler_app(
     State(st): State<AppState>,
-    headers: HeaderMap,
-) -> Result<Json<EvmNonceResponse>, AppError> {
+    body: Option<Json<SignInNonceRequest>>,
+) -> Result<Json<SignInNonceResponse>, AppError> {
     let (session_id, nonce) = st.auth.create_nonce().await?;
-    let message = evm_login_message(&nonce);
-    let _ = headers;
-    Ok(Json(EvmNonceResponse {
+    let policy = SignInPolicy::from_env(SignInChain::Ethereum)?;
+    let request = body.map(|Json(body)| body).unwrap_or_default();
+    Ok(Json(SignInNonceResponse::new(
+        SignInChain::Ethereum,
+        policy,
         session_id,
         nonce,
-        message,
-    }))
+        &request,
+    )?))
 }
 
 async fn evm_verify_handler_app(
     State(st): State<AppState>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
     headers: HeaderMap,
     Json(body): Json<EvmVerifyRequest>,
 ) -> Result<Json<serde_json::Value>, AppError> {
@@ -4044,14 +5771,16 @@ async fn evm_verify_handler_app(
         .await?
         .ok_or_else(|| AppError::Auth("Unknown or expired session".into()))?;
 
-    let message = evm_login_message(&nonce);
-    let recovered = verify_evm_signature(&message, signature)
-        .map_err(|_| AppError::Auth("Invalid signature".into()))?
-        .to_lowercase();
-
-    if recovered != address {
-        return Err(AppError::Auth("Signature does not match address".into()));
-    }
+    let policy = SignInPolicy::from_env(SignInChain::Ethereum)?;
+    let (_, signature_method) = verify_siwe_login(
+        &policy,
+        st.contracts.as_deref(),
+        &body.message,
+        signature,
+        &address,
+        &nonce,
+    )
+    .await?;
 
     let session = st
         .auth
@@ -4061,7 +5790,7 @@ async fn evm_verify_handler_app(
             "evm",
             device_id_from_headers(&headers).as_deref(),
             user_agent_from_headers(&headers).as_deref(),
-            ip_from_headers(&headers).as_deref(),
+            Some(client_ip(&st, peer, &headers).to_string()).as_deref(),
         )
         .await?;
 
@@ -4086,8 +5815,9 @@ async fn evm_verify_handler_app(
             serde_json::Map::from_iter([
                 ("device_id".into(), json!(session.device_id)),
                 ("user_agent".into(), json!(session.user_agent)),
-                ("ip_address".into(), json!(ip_from_headers(&headers))),
+                ("ip_address".into(), json!(client_ip(&st, peer, &headers))),
                 ("login_method".into(), json!("evm")),
+                ("verification_method".into(), json!(signature_method)),
             ]),
             visitor_id.as_deref(),
             body.attribution.as_ref(),
@@ -4098,6 +5828,8 @@ async fn evm_verify_handler_app(
     Ok(Json(json!({
         "ok": true,
         "session_id": session.session_id,
+        "refresh_token": session.refresh_token,
+        "expires_at": session.expires_at,
         "wallet": address,
         "chain": "evm",
         "mlkem_pk_b64": keys.pk_b64
@@ -4106,18 +5838,23 @@ async fn evm_verify_handler_app(
 
 async fn sol_nonce_handler_app(
     State(st): State<AppState>,
-) -> Result<Json<identity_web::sol::SolNonceResponse>, AppError> {
+    body: Option<Json<SignInNonceRequest>>,
+) -> Result<Json<SignInNonceResponse>, AppError> {
     let (session_id, nonce) = st.auth.create_nonce().await?;
-    let message = identity_web::sol::sol_login_message(&nonce);
-    Ok(Json(identity_web::sol::SolNonceResponse {
+    let policy = SignInPolicy::from_env(SignInChain::Solana)?;
+    let request = body.map(|Json(body)| body).unwrap_or_default();
+    Ok(Json(SignInNonceResponse::new(
+        SignInChain::Solana,
+        policy,
         session_id,
         nonce,
-        message,
-    }))
+        &request,
+    )?))
 }
 
 async fn sol_verify_handler_app(
     State(st): State<AppState>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
     headers: HeaderMap,
     Json(body): Json<identity_web::sol::SolVerifyRequest>,
 ) -> Result<Json<serde_json::Value>, AppError> {
@@ -4140,9 +5877,8 @@ async fn sol_verify_handler_app(
         .take_nonce(session_id)
         .await?
         .ok_or_else(|| AppError::Auth("Unknown or expired session".into()))?;
-    let message = identity_web::sol::sol_login_message(&nonce);
-    verify_solana_signature(&message, address, signature)
-        .map_err(|_| AppError::Auth("Invalid Solana signature".into()))?;
+    let policy = SignInPolicy::from_env(SignInChain::Solana)?;
+    verify_siws_login(&policy, &body.message, address, signature, &nonce)?;
     let session = st
         .auth
         .bind_wallet(
@@ -4151,7 +5887,7 @@ async fn sol_verify_handler_app(
             "sol",
             device_id_from_headers(&headers).as_deref(),
             user_agent_from_headers(&headers).as_deref(),
-            ip_from_headers(&headers).as_deref(),
+            Some(client_ip(&st, peer, &headers).to_string()).as_deref(),
         )
         .await?;
 
@@ -4176,7 +5912,7 @@ async fn sol_verify_handler_app(
             serde_json::Map::from_iter([
                 ("device_id".into(), json!(session.device_id)),
                 ("user_agent".into(), json!(session.user_agent)),
-                ("ip_address".into(), json!(ip_from_headers(&headers))),
+                ("ip_address".into(), json!(client_ip(&st, peer, &headers))),
                 ("login_method".into(), json!("sol")),
             ]),
             visitor_id.as_deref(),
@@ -4188,6 +5924,8 @@ async fn sol_verify_handler_app(
     Ok(Json(json!({
         "ok": true,
         "session_id": session.session_id,
+        "refresh_token": session.refresh_token,
+        "expires_at": session.expires_at,
         "wallet": address,
         "chain": "sol",
         "mlkem_pk_b64": keys.pk_b64
@@ -4222,6 +5960,8 @@ async fn list_docs_handler(
             d.mime_type,
             d.parent_id,
             d.arweave_tx,
+            d.quarantined_at,
+            d.quarantine_reason,
             (
               select max(e.created_at)
               from document_events e
@@ -4244,6 +5984,7 @@ async fn list_docs_handler(
               select 1
               from document_shares s
               where s.doc_id = d.id
+                and d.quarantined_at is null
                 and s.recipient_wallet is not null
                 and s.status not in ('dismissed')
                 and (
@@ -4282,6 +6023,8 @@ async fn list_docs_handler(
                     "parent_id": r.get::<Option<uuid::Uuid>,_>("parent_id"),
                     "arweave_tx": r.get::<Option<String>,_>("arweave_tx"),
                     "last_signed_at": r.get::<Option<chrono::DateTime<chrono::Utc>>,_>("last_signed_at"),
+                    "quarantined_at": r.get::<Option<chrono::DateTime<chrono::Utc>>,_>("quarantined_at"),
+                    "quarantine_reason": r.get::<Option<String>,_>("quarantine_reason"),
                     "access_kind": r.get::<String,_>("access_kind")
                 })
             })
@@ -4411,6 +6154,7 @@ async fn list_shared_activity_handler(
               select 1
               from document_shares s
               where s.doc_id = d.id
+                and d.quarantined_at is null
                 and s.recipient_wallet is not null
                 and s.status not in ('dismissed')
                 and (
@@ -4766,6 +6510,25 @@ async fn export_doc_evidence_handler(
     .await
     .map_err(|e| AppError::Internal(e.to_string()))?;
 
+    let anchor_proofs = sqlx::query(
+        r#"
+        select l.kind, l.hash_hex, l.share_id, l.event_id, l.proof_json,
+               b.id as batch_id, b.root_hex, b.arweave_tx, b.anchored_at
+        from anchor_leaves l
+        join anchor_batches b on b.id = l.batch_id
+        where l.doc_id = $1
+          and l.proof_json is not null
+          and b.arweave_tx is not null
+        order by l.created_at asc
+        "#,
+    )
+    .bind(id)
+    .fetch_all(&st.db)
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let pq_signing_keys = pq_key_history_for_document(&st.db, id).await?;
+
     let mut previous_event_hash: Option<String> = None;
     let mut event_chain_complete = true;
     let mut event_chain_valid = true;
@@ -4896,6 +6659,18 @@ async fn export_doc_evidence_handler(
             "completion_count": row.get::<i32,_>("completion_count"),
             "created_at": row.get::<chrono::DateTime<chrono::Utc>,_>("created_at")
         })).collect::<Vec<_>>(),
+        "anchor_proofs": anchor_proofs.into_iter().map(|row| json!({
+            "kind": row.get::<String,_>("kind"),
+            "hash_hex": row.get::<String,_>("hash_hex"),
+            "share_id": row.get::<Option<uuid::Uuid>,_>("share_id"),
+            "event_id": row.get::<Option<uuid::Uuid>,_>("event_id"),
+            "batch_id": row.get::<uuid::Uuid,_>("batch_id"),
+            "merkle_root_hex": row.get::<String,_>("root_hex"),
+            "arweave_tx": row.get::<Option<String>,_>("arweave_tx"),
+            "anchored_at": row.get::<Option<chrono::DateTime<chrono::Utc>>,_>("anchored_at"),
+            "proof": row.get::<serde_json::Value,_>("proof_json")
+        })).collect::<Vec<_>>(),
+        "pq_signing_keys": pq_signing_keys,
         "events": exported_events
     });
 
@@ -4915,7 +6690,7 @@ async fn export_doc_evidence_handler(
     export_json.insert(
         "evidence_bundle".into(),
         json!({
-            "schema_version": 2,
+            "schema_version": 3,
             "bundle_hash_hex": bundle_hash_hex,
             "bundle_signature_b64": bundle_signature_b64,
             "bundle_signature_key_id": audit_key_id(),
@@ -4951,6 +6726,34 @@ async fn anchor_evidence_bundle_handler(
     let evidence_bytes =
         serde_json::to_vec(&evidence_json).map_err(|e| AppError::Internal(e.to_string()))?;
     let evidence_hash_hex = hex::encode(pqc_sha3::sha3_256_bytes(&evidence_bytes));
+
+    if anchor_batch_window().is_some() {
+        enqueue_anchor_leaf(&st.db, id, None, None, "evidence_bundle", &evidence_hash_hex).await?;
+        insert_document_event(
+            &st.db,
+            id,
+            &wallet,
+            "EVIDENCE_ANCHOR_QUEUED",
+            custody_payload(
+                json!({
+                    "evidence_hash_hex": evidence_hash_hex,
+                    "document_hash_hex": doc.hash_hex,
+                    "version": doc.version
+                }),
+                &session,
+                &headers,
+            ),
+        )
+        .await?;
+
+        return Ok(Json(json!({
+            "ok": true,
+            "doc_id": id,
+            "evidence_hash_hex": evidence_hash_hex,
+            "queued": true
+        })));
+    }
+
     let tx_id = crate::arweave::anchor_hash_to_arweave(&evidence_hash_hex)
         .await
         .map_err(|e| AppError::Internal(e.to_string()))?
@@ -4962,6 +6765,8 @@ async fn anchor_evidence_bundle_handler(
         .execute(&st.db)
         .await
         .map_err(|e| AppError::Internal(e.to_string()))?;
+    track_arweave_anchor(&st.db, &tx_id, "evidence_bundle", &evidence_hash_hex, Some(id), None, None)
+        .await?;
 
     insert_document_event(
         &st.db,
@@ -5046,21 +6851,20 @@ async fn set_document_policy_handler(
     .await
     .map_err(|e| AppError::Internal(e.to_string()))?;
 
-    sqlx::query(
-        "insert into document_events (doc_id, actor_wallet, event_type, payload) values ($1,$2,'POLICY_UPDATED',$3)",
+    insert_document_event(
+        &st.db,
+        id,
+        &wallet,
+        "POLICY_UPDATED",
+        custody_payload(
+            json!({
+                "policy_json": body.policy_json
+            }),
+            &session,
+            &headers,
+        ),
     )
-    .bind(id)
-    .bind(&wallet)
-    .bind(custody_payload(
-        json!({
-            "policy_json": body.policy_json
-        }),
-        &session,
-        &headers,
-    ))
-    .execute(&st.db)
-    .await
-    .map_err(|e| AppError::Internal(e.to_string()))?;
+    .await?;
 
     Ok(Json(json!({
         "ok": true,
@@ -5271,24 +7075,23 @@ async fn agent_review_doc_handler(
         ));
     }
 
-    sqlx::query(
-        "insert into document_events (doc_id, actor_wallet, event_type, payload) values ($1,$2,'AGENT_REVIEW',$3)",
+    insert_document_event(
+        &st.db,
+        id,
+        &format!("agent:{}", agent.id),
+        "AGENT_REVIEW",
+        agent_custody_payload(
+            json!({
+                "hash_hex": doc.hash_hex,
+                "version": doc.version,
+                "mime_type": doc.mime_type,
+                "policy_json": policy
+            }),
+            &agent,
+            &headers,
+        ),
     )
-    .bind(id)
-    .bind(format!("agent:{}", agent.id))
-    .bind(agent_custody_payload(
-        json!({
-            "hash_hex": doc.hash_hex,
-            "version": doc.version,
-            "mime_type": doc.mime_type,
-            "policy_json": policy
-        }),
-        &agent,
-        &headers,
-    ))
-    .execute(&st.db)
-    .await
-    .map_err(|e| AppError::Internal(e.to_string()))?;
+    .await?;
 
     Ok(Json(json!({
         "ok": true,
@@ -5326,36 +7129,40 @@ async fn agent_sign_doc_handler(
         ));
     }
 
-    let require_human_countersign = policy
-        .get("require_human_countersign")
-        .and_then(|value| value.as_bool())
-        .unwrap_or(true);
+    // An agent cannot produce a wallet-bound hybrid signature, so a
+    // document that requires one always needs a human countersign.
+    let require_human_countersign = policy_requires_hybrid_signature(&policy)
+        || policy
+            .get("require_human_countersign")
+            .and_then(|value| value.as_bool())
+            .unwrap_or(true);
     let event_type = if require_human_countersign {
         "AGENT_SIGN_PROPOSED"
     } else {
         "AGENT_SIGN"
     };
 
-    sqlx::query(
-        "insert into document_events (doc_id, actor_wallet, event_type, payload) values ($1,$2,$3,$4)",
+    let sign_event_id = insert_document_event(
+        &st.db,
+        id,
+        &format!("agent:{}", agent.id),
+        event_type,
+        agent_custody_payload(
+            json!({
+                "hash_hex": doc.hash_hex,
+                "version": doc.version,
+                "sign_reason": body.sign_reason,
+                "summary": body.summary,
+                "require_human_countersign": require_human_countersign
+            }),
+            &agent,
+            &headers,
+        ),
     )
-    .bind(id)
-    .bind(format!("agent:{}", agent.id))
-    .bind(event_type)
-    .bind(agent_custody_payload(
-        json!({
-            "hash_hex": doc.hash_hex,
-            "version": doc.version,
-            "sign_reason": body.sign_reason,
-            "summary": body.summary,
-            "require_human_countersign": require_human_countersign
-        }),
-        &agent,
-        &headers,
-    ))
-    .execute(&st.db)
-    .await
-    .map_err(|e| AppError::Internal(e.to_string()))?;
+    .await?;
+    // A proposal completes the agent's own workflow step; a required human
+    // countersign is modelled as a separate signer.
+    record_workflow_signature(&st, id, WorkflowSignerMatch::Agent(agent.id), sign_event_id).await?;
 
     Ok(Json(json!({
         "ok": true,
@@ -5390,8 +7197,9 @@ async fn agent_version_doc_handler(
     let bytes = base64::engine::general_purpose::STANDARD
         .decode(body.content_b64.trim())
         .map_err(|_| AppError::BadRequest("Invalid content_b64".into()))?;
+    let anchor_to_arweave = body.anchor_to_arweave.unwrap_or_else(auto_anchor_enabled);
 
-    let record = create_document_record(
+    let mut record = create_document_record(
         &st,
         &agent.owner_wallet,
         &bytes,
@@ -5404,16 +7212,11 @@ async fn agent_version_doc_handler(
             .unwrap_or_else(|| parent.mime_type.clone()),
         Some(parent_doc_id),
         Some(parent.version),
-        body.anchor_to_arweave.unwrap_or_else(auto_anchor_enabled),
+        anchor_to_arweave,
     )
     .await?;
 
-    sqlx::query(
-        "insert into document_events (doc_id, actor_wallet, event_type, payload) values ($1,$2,'AGENT_VERSION_CREATED',$3)",
-    )
-    .bind(record.id)
-    .bind(format!("agent:{}", agent.id))
-    .bind(agent_custody_payload(
+    let payload = agent_custody_payload(
         json!({
             "hash_hex": record.hash_hex,
             "version": record.version,
@@ -5422,17 +7225,23 @@ async fn agent_version_doc_handler(
             "parent_id": record.parent_id,
             "parent_hash_hex": parent.hash_hex,
             "parent_version": parent.version,
-            "arweave_tx": record.arweave_tx,
             "change_summary": body.change_summary,
             "editor_mode": "agent_api",
             "before_snapshot_hash_hex": parent.hash_hex
         }),
         &agent,
         &headers,
-    ))
-    .execute(&st.db)
-    .await
-    .map_err(|e| AppError::Internal(e.to_string()))?;
+    );
+    record_document_pipeline(
+        &st,
+        &mut record,
+        &hex::encode(Sha256::digest(&bytes)),
+        &format!("agent:{}", agent.id),
+        "AGENT_VERSION_CREATED",
+        payload,
+        anchor_to_arweave,
+    )
+    .await?;
 
     Ok(Json(json!({
         "ok": true,
@@ -5447,6 +7256,68 @@ async fn agent_version_doc_handler(
 // UPLOAD
 // ================================================================
 
+// ================================================================
+// UPLOAD SCANNING
+// ================================================================
+
+async fn scan_upload(
+    st: &AppState,
+    bytes: &[u8],
+    mime_type: &str,
+    filename: Option<&str>,
+) -> ScanReport {
+    st.scanner
+        .scan(&ScanInput {
+            bytes,
+            declared_mime: mime_type,
+            filename,
+        })
+        .await
+}
+
+fn rejected_upload_error(report: &ScanReport) -> AppError {
+    AppError::BadRequest(format!("Upload rejected by scanner: {}", report.summary()))
+}
+
+/// Append the `SCAN` custody event. `report` is `None` for browser-encrypted
+/// uploads, whose plaintext the server never sees.
+async fn record_scan_event(
+    st: &AppState,
+    doc_id: uuid::Uuid,
+    sha256_hex: &str,
+    report: Option<&ScanReport>,
+    rejected: bool,
+    session: &WalletSession,
+    headers: &HeaderMap,
+) -> Result<(), AppError> {
+    let base = match report {
+        Some(report) => json!({
+            "verdict": report.verdict,
+            "rejected": rejected,
+            "sha256_hex": sha256_hex,
+            "declared_mime": report.declared_mime,
+            "detected_mime": report.detected_mime,
+            "scanners": report.scanners,
+            "findings": report.findings,
+            "errors": report.errors
+        }),
+        None => json!({
+            "verdict": "unscanned",
+            "reason": "client_encrypted",
+            "sha256_hex": sha256_hex
+        }),
+    };
+    insert_document_event(
+        &st.db,
+        doc_id,
+        "system:sanitizer",
+        "SCAN",
+        custody_payload(base, session, headers),
+    )
+    .await?;
+    Ok(())
+}
+
 async fn upload_doc_handler(
     State(st): State<AppState>,
     headers: HeaderMap,
@@ -5474,7 +7345,8 @@ async fn upload_doc_handler(
         .map(|value| value.trim().to_string())
         .filter(|value| !value.is_empty())
         .or(original_name);
-    let record = if encryption_source == Some(CLIENT_ENCRYPTED_UPLOAD_MODE) {
+    let mut scan_report = None;
+    let mut record = if encryption_source == Some(CLIENT_ENCRYPTED_UPLOAD_MODE) {
         create_document_record_from_client_envelope(
             &st,
             &wallet,
@@ -5488,6 +7360,11 @@ async fn upload_doc_handler(
         .await?
     } else {
         let mime_type = parsed_mime_type.unwrap_or_else(|| "application/octet-stream".to_string());
+        let report = scan_upload(&st, &bytes, &mime_type, label.as_deref()).await;
+        if report.is_malicious() {
+            return Err(rejected_upload_error(&report));
+        }
+        scan_report = Some(report);
         create_document_record(
             &st,
             &wallet,
@@ -5500,13 +7377,9 @@ async fn upload_doc_handler(
         )
         .await?
     };
+    let sha256_hex = hex::encode(Sha256::digest(&bytes));
 
-    sqlx::query(
-        "insert into document_events (doc_id, actor_wallet, event_type, payload) values ($1,$2,'UPLOAD',$3)",
-    )
-    .bind(record.id)
-    .bind(&wallet)
-    .bind(custody_payload(
+    let payload = custody_payload(
         json!({
             "hash_hex": record.hash_hex,
             "storage_path": record.storage_path,
@@ -5514,15 +7387,32 @@ async fn upload_doc_handler(
             "mime_type": record.mime_type,
             "label": record.label,
             "parent_id": record.parent_id,
-            "arweave_tx": record.arweave_tx,
             "encryption_mode": record.encryption_mode
         }),
         &session,
         &headers,
-    ))
-    .execute(&st.db)
-    .await
-    .map_err(|e| AppError::Internal(e.to_string()))?;
+    );
+    record_document_pipeline(
+        &st,
+        &mut record,
+        &sha256_hex,
+        &wallet,
+        "UPLOAD",
+        payload,
+        anchor_to_arweave,
+    )
+    .await?;
+    record_scan_event(
+        &st,
+        record.id,
+        &sha256_hex,
+        scan_report.as_ref(),
+        false,
+        &session,
+        &headers,
+    )
+    .await?;
+    let quarantined = quarantine_scanned_upload(&st, &record, scan_report.as_ref()).await?;
     record_growth_event(
         &st.db,
         "DOC_UPLOADED",
@@ -5549,7 +7439,8 @@ async fn upload_doc_handler(
         "ok": true,
         "id": record.id,
         "version": record.version,
-        "arweave_tx": record.arweave_tx
+        "arweave_tx": record.arweave_tx,
+        "quarantined": quarantined
     })))
 }
 
@@ -5596,7 +7487,9 @@ async fn create_document_version_handler(
         .map(str::trim)
         .filter(|value| !value.is_empty());
 
-    let record = if encryption_source == Some(CLIENT_ENCRYPTED_UPLOAD_MODE) {
+    let sha256_hex = hex::encode(Sha256::digest(&bytes));
+    let mut scan_report = None;
+    let mut record = if encryption_source == Some(CLIENT_ENCRYPTED_UPLOAD_MODE) {
         create_document_record_from_client_envelope(
             &st,
             &wallet,
@@ -5610,6 +7503,22 @@ async fn create_document_version_handler(
         .await?
     } else {
         let mime_type = parsed_mime_type.unwrap_or_else(|| parent.mime_type.clone());
+        let report = scan_upload(&st, &bytes, &mime_type, label.as_deref()).await;
+        if report.is_malicious() {
+            // No version row exists; keep the rejected attempt on the parent.
+            record_scan_event(
+                &st,
+                parent_doc_id,
+                &sha256_hex,
+                Some(&report),
+                true,
+                &session,
+                &headers,
+            )
+            .await?;
+            return Err(rejected_upload_error(&report));
+        }
+        scan_report = Some(report);
         create_document_record(
             &st,
             &wallet,
@@ -5623,12 +7532,7 @@ async fn create_document_version_handler(
         .await?
     };
 
-    sqlx::query(
-        "insert into document_events (doc_id, actor_wallet, event_type, payload) values ($1,$2,'VERSION_CREATED',$3)",
-    )
-    .bind(record.id)
-    .bind(&wallet)
-    .bind(custody_payload(
+    let payload = custody_payload(
         json!({
             "hash_hex": record.hash_hex,
             "storage_path": record.storage_path,
@@ -5638,7 +7542,6 @@ async fn create_document_version_handler(
             "parent_id": record.parent_id,
             "parent_hash_hex": parent.hash_hex,
             "parent_version": parent.version,
-            "arweave_tx": record.arweave_tx,
             "encryption_mode": record.encryption_mode,
             "editor_mode": editor_mode,
             "change_summary": change_summary,
@@ -5646,10 +7549,28 @@ async fn create_document_version_handler(
         }),
         &session,
         &headers,
-    ))
-    .execute(&st.db)
-    .await
-    .map_err(|e| AppError::Internal(e.to_string()))?;
+    );
+    record_document_pipeline(
+        &st,
+        &mut record,
+        &sha256_hex,
+        &wallet,
+        "VERSION_CREATED",
+        payload,
+        anchor_to_arweave,
+    )
+    .await?;
+    record_scan_event(
+        &st,
+        record.id,
+        &sha256_hex,
+        scan_report.as_ref(),
+        false,
+        &session,
+        &headers,
+    )
+    .await?;
+    let quarantined = quarantine_scanned_upload(&st, &record, scan_report.as_ref()).await?;
     record_growth_event(
         &st.db,
         "DOC_VERSION_CREATED",
@@ -5679,102 +7600,2543 @@ async fn create_document_version_handler(
         "id": record.id,
         "version": record.version,
         "parent_id": parent_doc_id,
-        "arweave_tx": record.arweave_tx
+        "arweave_tx": record.arweave_tx,
+        "quarantined": quarantined
     })))
 }
 
-async fn review_doc_handler(
+// ================================================================
+// CHUNKED UPLOADS
+// ================================================================
+
+fn chunked_upload_max_bytes() -> i64 {
+    std::env::var("CHUNKED_UPLOAD_MAX_BYTES")
+        .ok()
+        .and_then(|value| value.trim().parse::<i64>().ok())
+        .unwrap_or(16 * 1024 * 1024 * 1024)
+}
+
+/// Plaintext bytes of a chunked upload handed to the scanners at once. The
+/// whole file is scanned window by window; the default stays under clamd's
+/// 25 MiB `StreamMaxLength`.
+fn chunked_upload_scan_window_bytes() -> usize {
+    std::env::var("CHUNKED_UPLOAD_SCAN_WINDOW_BYTES")
+        .ok()
+        .and_then(|value| value.trim().parse::<usize>().ok())
+        .unwrap_or(16 * 1024 * 1024)
+}
+
+struct ChunkedUploadRecord {
+    id: uuid::Uuid,
+    owner_wallet: String,
+    doc_id: uuid::Uuid,
+    parent_id: Option<uuid::Uuid>,
+    version: i32,
+    label: Option<String>,
+    mime_type: String,
+    file_size: i64,
+    segment_count: i64,
+    declared_hash_hex: Option<String>,
+    change_summary: Option<String>,
+    anchor_to_arweave: bool,
+    stream_key: StreamKeyV1,
+    status: String,
+}
+
+impl ChunkedUploadRecord {
+    /// Plaintext length the segment at `index` must have.
+    fn segment_len(&self, index: i64) -> usize {
+        let offset = index * STREAM_SEGMENT_SIZE as i64;
+        (self.file_size - offset).clamp(0, STREAM_SEGMENT_SIZE as i64) as usize
+    }
+
+    fn segment_path(&self, index: i64) -> String {
+        stream_segment_path(
+            &self.owner_wallet,
+            &self.doc_id.to_string(),
+            self.version,
+            index as u64,
+        )
+    }
+}
+
+async fn load_chunked_upload(
+    db: &PgPool,
+    upload_id: uuid::Uuid,
+    session: &WalletSession,
+) -> Result<ChunkedUploadRecord, AppError> {
+    let owner_wallet = normalize_wallet_for_chain(&session.wallet, &session.chain);
+    let row = sqlx::query(
+        r#"
+        select id, owner_wallet, doc_id, parent_id, version, label, mime_type, file_size,
+               segment_count, declared_hash_hex, change_summary, anchor_to_arweave,
+               stream_key_json, status
+        from document_uploads
+        where id = $1 and owner_wallet = $2
+        "#,
+    )
+    .bind(upload_id)
+    .bind(&owner_wallet)
+    .fetch_optional(db)
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+    .ok_or_else(|| AppError::NotFound("Upload not found".into()))?;
+
+    Ok(ChunkedUploadRecord {
+        id: row.get("id"),
+        owner_wallet: row.get("owner_wallet"),
+        doc_id: row.get("doc_id"),
+        parent_id: row.get("parent_id"),
+        version: row.get("version"),
+        label: row.get("label"),
+        mime_type: row.get("mime_type"),
+        file_size: row.get("file_size"),
+        segment_count: row.get("segment_count"),
+        declared_hash_hex: row.get("declared_hash_hex"),
+        change_summary: row.get("change_summary"),
+        anchor_to_arweave: row.get("anchor_to_arweave"),
+        stream_key: serde_json::from_value(row.get("stream_key_json"))?,
+        status: row.get("status"),
+    })
+}
+
+async fn received_upload_segments(
+    db: &PgPool,
+    upload_id: uuid::Uuid,
+) -> Result<Vec<(i64, String)>, AppError> {
+    Ok(sqlx::query(
+        r#"
+        select segment_index, ciphertext_sha3_hex
+        from document_upload_segments
+        where upload_id = $1
+        order by segment_index asc
+        "#,
+    )
+    .bind(upload_id)
+    .fetch_all(db)
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+    .into_iter()
+    .map(|row| (row.get("segment_index"), row.get("ciphertext_sha3_hex")))
+    .collect())
+}
+
+/// Plaintext digests of a chunked upload.
+struct AssembledChunkedUpload {
+    hash_hex: String,
+    sha256_hex: String,
+}
+
+/// Read the stored segments back one at a time, checking each against its
+/// upload receipt. The plaintext is hashed and fed to `scan` as it goes, so
+/// no more than a segment and a scan window are held at once.
+async fn assemble_chunked_upload(
+    storage: &dyn BlobStore,
+    upload: &ChunkedUploadRecord,
+    cipher: &StreamCipher,
+    segments: &[(i64, String)],
+    scan: &mut WindowedScan<'_>,
+) -> Result<AssembledChunkedUpload, AppError> {
+    let mut sha3 = sha3::Sha3_256::new();
+    let mut sha256 = Sha256::new();
+    for (index, ciphertext_sha3_hex) in segments {
+        let ciphertext = storage
+            .download_bytes(&upload.segment_path(*index))
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+        if hex::encode(pqc_sha3::sha3_256_bytes(&ciphertext)) != *ciphertext_sha3_hex {
+            return Err(AppError::Crypto(format!(
+                "Stored segment {index} does not match its upload receipt"
+            )));
+        }
+        let plaintext = cipher
+            .decrypt_segment(
+                *index as u64,
+                *index + 1 == upload.segment_count,
+                &ciphertext,
+            )
+            .map_err(AppError::Crypto)?;
+        sha3.update(&plaintext);
+        sha256.update(&plaintext);
+        scan.update(&plaintext).await;
+    }
+    Ok(AssembledChunkedUpload {
+        hash_hex: hex::encode(sha3.finalize()),
+        sha256_hex: hex::encode(sha256.finalize()),
+    })
+}
+
+/// Mark an upload that can never complete as rejected and delete its stored
+/// segments. The segment receipts stay behind as a record of the attempt.
+async fn reject_chunked_upload(
+    st: &AppState,
+    upload: &ChunkedUploadRecord,
+    segments: &[(i64, String)],
+) -> Result<(), AppError> {
+    sqlx::query("update document_uploads set status = 'rejected' where id = $1")
+        .bind(upload.id)
+        .execute(&st.db)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    for (index, _) in segments {
+        let path = upload.segment_path(*index);
+        if let Err(err) = st.storage.delete_object(&path).await {
+            eprintln!("storage: failed to delete rejected upload segment {path}: {err}");
+        }
+    }
+    Ok(())
+}
+
+async fn init_chunked_upload_handler(
     State(st): State<AppState>,
     headers: HeaderMap,
-    Path(id): Path<uuid::Uuid>,
+    Json(body): Json<ChunkedUploadInitRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let session = require_session_from_headers(&st, &headers).await?;
+    let owner_wallet = normalize_wallet_for_chain(&session.wallet, &session.chain);
+
+    if body.file_size < 0 || body.file_size > chunked_upload_max_bytes() {
+        return Err(AppError::BadRequest(format!(
+            "file_size must be between 0 and {} bytes",
+            chunked_upload_max_bytes()
+        )));
+    }
+    let declared_hash_hex = body
+        .plaintext_sha3_256_hex
+        .as_deref()
+        .map(|value| value.trim().to_ascii_lowercase())
+        .filter(|value| !value.is_empty());
+    if declared_hash_hex
+        .as_deref()
+        .is_some_and(|value| value.len() != 64 || hex::decode(value).is_err())
+    {
+        return Err(AppError::BadRequest(
+            "plaintext_sha3_256_hex must be a hex-encoded SHA3-256 digest".into(),
+        ));
+    }
+
+    let (parent_id, version, parent_label, parent_mime_type) = match body.parent_id {
+        Some(parent_id) => {
+            let parent =
+                load_document_access_record(&st.db, parent_id, &session.wallet, &session.chain)
+                    .await?;
+            if !parent.owner_wallet.eq_ignore_ascii_case(&session.wallet) {
+                return Err(AppError::Forbidden(
+                    "Only the document owner can create a new version".into(),
+                ));
+            }
+            (
+                Some(parent_id),
+                parent.version + 1,
+                parent.label,
+                Some(parent.mime_type),
+            )
+        }
+        None => (None, 1, None, None),
+    };
+    let label = body
+        .label
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .or(parent_label);
+    let mime_type = body
+        .mime_type
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .or(parent_mime_type)
+        .unwrap_or_else(|| "application/octet-stream".to_string());
+
+    let keys = load_or_create_server_mlkem_keypair(&st.db, &owner_wallet).await?;
+    let stream_key =
+        StreamKeyV1::new_mlkem(&owner_wallet, &keys.pk_b64).map_err(AppError::Crypto)?;
+    let segments = segment_count(body.file_size as u64, STREAM_SEGMENT_SIZE) as i64;
+    let upload_id = uuid::Uuid::new_v4();
+    let doc_id = uuid::Uuid::new_v4();
+
+    sqlx::query(
+        r#"
+        insert into document_uploads (
+            id, owner_wallet, doc_id, parent_id, version, label, mime_type, file_size,
+            segment_count, declared_hash_hex, change_summary, anchor_to_arweave, stream_key_json
+        )
+        values ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11,$12,$13)
+        "#,
+    )
+    .bind(upload_id)
+    .bind(&owner_wallet)
+    .bind(doc_id)
+    .bind(parent_id)
+    .bind(version)
+    .bind(&label)
+    .bind(&mime_type)
+    .bind(body.file_size)
+    .bind(segments)
+    .bind(&declared_hash_hex)
+    .bind(&body.change_summary)
+    .bind(body.anchor_to_arweave.unwrap_or_else(auto_anchor_enabled))
+    .bind(serde_json::to_value(&stream_key)?)
+    .execute(&st.db)
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    Ok(Json(json!({
+        "ok": true,
+        "upload_id": upload_id,
+        "doc_id": doc_id,
+        "version": version,
+        "segment_size": STREAM_SEGMENT_SIZE,
+        "segment_count": segments
+    })))
+}
+
+async fn chunked_upload_status_handler(
+    State(st): State<AppState>,
+    headers: HeaderMap,
+    Path(upload_id): Path<uuid::Uuid>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let session = require_session_from_headers(&st, &headers).await?;
+    let upload = load_chunked_upload(&st.db, upload_id, &session).await?;
+    let received: Vec<i64> = received_upload_segments(&st.db, upload.id)
+        .await?
+        .into_iter()
+        .map(|(index, _)| index)
+        .collect();
+
+    Ok(Json(json!({
+        "upload_id": upload.id,
+        "doc_id": upload.doc_id,
+        "status": upload.status,
+        "file_size": upload.file_size,
+        "segment_size": STREAM_SEGMENT_SIZE,
+        "segment_count": upload.segment_count,
+        "received": received
+    })))
+}
+
+/// Encrypt one plaintext segment and store it. Re-sending a segment replaces
+/// it, so clients resume by re-sending whatever `received` is missing.
+async fn put_upload_segment_handler(
+    State(st): State<AppState>,
+    headers: HeaderMap,
+    Path((upload_id, index)): Path<(uuid::Uuid, i64)>,
+    body: Bytes,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let session = require_session_from_headers(&st, &headers).await?;
+    let upload = load_chunked_upload(&st.db, upload_id, &session).await?;
+    if upload.status != "open" {
+        return Err(AppError::BadRequest(format!("Upload is {}", upload.status)));
+    }
+    if index < 0 || index >= upload.segment_count {
+        return Err(AppError::BadRequest(format!(
+            "Segment index must be between 0 and {}",
+            upload.segment_count - 1
+        )));
+    }
+    let expected_len = upload.segment_len(index);
+    if body.len() != expected_len {
+        return Err(AppError::BadRequest(format!(
+            "Segment {index} must be {expected_len} bytes, got {}",
+            body.len()
+        )));
+    }
+
+    let keys = load_or_create_server_mlkem_keypair(&st.db, &upload.owner_wallet).await?;
+    let ciphertext = upload
+        .stream_key
+        .unlock(&keys.sk_b64)
+        .and_then(|cipher| {
+            cipher.encrypt_segment(index as u64, index + 1 == upload.segment_count, &body)
+        })
+        .map_err(AppError::Crypto)?;
+    let ciphertext_sha3_hex = hex::encode(pqc_sha3::sha3_256_bytes(&ciphertext));
+
+    // The nonce is fixed per index, so a segment can only ever be written
+    // once: different bytes under the same nonce would leak their XOR.
+    // Resending identical bytes encrypts to the same ciphertext and is
+    // accepted as a retry.
+    let claimed = sqlx::query(
+        r#"
+        insert into document_upload_segments (upload_id, segment_index, ciphertext_sha3_hex)
+        values ($1, $2, $3)
+        on conflict (upload_id, segment_index) do nothing
+        "#,
+    )
+    .bind(upload.id)
+    .bind(index)
+    .bind(&ciphertext_sha3_hex)
+    .execute(&st.db)
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+    .rows_affected()
+        > 0;
+    if !claimed {
+        let stored: String = sqlx::query(
+            "select ciphertext_sha3_hex from document_upload_segments where upload_id = $1 and segment_index = $2",
+        )
+        .bind(upload.id)
+        .bind(index)
+        .fetch_one(&st.db)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+        .get("ciphertext_sha3_hex");
+        if stored != ciphertext_sha3_hex {
+            return Err(AppError::Conflict(format!(
+                "Segment {index} was already uploaded with different content"
+            )));
+        }
+        return Ok(Json(json!({
+            "ok": true,
+            "index": index,
+            "ciphertext_sha3_hex": ciphertext_sha3_hex
+        })));
+    }
+
+    if let Err(err) = st
+        .storage
+        .put_object(
+            &upload.segment_path(index),
+            &ciphertext,
+            "application/octet-stream",
+        )
+        .await
+    {
+        // Release the index so the same bytes can be sent again.
+        sqlx::query(
+            "delete from document_upload_segments where upload_id = $1 and segment_index = $2",
+        )
+        .bind(upload.id)
+        .bind(index)
+        .execute(&st.db)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+        return Err(AppError::Internal(err.to_string()));
+    }
+
+    Ok(Json(json!({
+        "ok": true,
+        "index": index,
+        "ciphertext_sha3_hex": ciphertext_sha3_hex
+    })))
+}
+
+async fn complete_chunked_upload_handler(
+    State(st): State<AppState>,
+    headers: HeaderMap,
+    Path(upload_id): Path<uuid::Uuid>,
 ) -> Result<Json<serde_json::Value>, AppError> {
     let session = require_session_from_headers(&st, &headers).await?;
     let wallet = session.wallet.clone();
-    let doc = load_document_access_record(&st.db, id, &wallet, &session.chain).await?;
+    let upload = load_chunked_upload(&st.db, upload_id, &session).await?;
+    if upload.status != "open" {
+        return Err(AppError::BadRequest(format!("Upload is {}", upload.status)));
+    }
+    let segments = received_upload_segments(&st.db, upload.id).await?;
+    if segments.len() as i64 != upload.segment_count {
+        return Err(AppError::BadRequest(format!(
+            "Upload has {} of {} segments",
+            segments.len(),
+            upload.segment_count
+        )));
+    }
+
+    let keys = load_or_create_server_mlkem_keypair(&st.db, &upload.owner_wallet).await?;
+    let cipher = upload
+        .stream_key
+        .unlock(&keys.sk_b64)
+        .map_err(AppError::Crypto)?;
+    let mut scan = st.scanner.windowed(
+        &upload.mime_type,
+        upload.label.as_deref(),
+        chunked_upload_scan_window_bytes(),
+    );
+    let assembled =
+        assemble_chunked_upload(st.storage.as_ref(), &upload, &cipher, &segments, &mut scan)
+            .await?;
+    let scan_report = scan.finish().await;
+    let hash_hex = assembled.hash_hex;
+    let sha256_hex = assembled.sha256_hex;
+    if let Some(declared) = upload.declared_hash_hex.as_deref() {
+        if declared != hash_hex {
+            // Segments are write-once, so this upload can never match.
+            reject_chunked_upload(&st, &upload, &segments).await?;
+            return Err(AppError::BadRequest(
+                "Uploaded content does not match plaintext_sha3_256_hex".into(),
+            ));
+        }
+    }
+
+    if scan_report.is_malicious() {
+        reject_chunked_upload(&st, &upload, &segments).await?;
+        if let Some(parent_id) = upload.parent_id {
+            // No version row exists; keep the rejected attempt on the parent.
+            record_scan_event(
+                &st,
+                parent_id,
+                &sha256_hex,
+                Some(&scan_report),
+                true,
+                &session,
+                &headers,
+            )
+            .await?;
+        }
+        return Err(rejected_upload_error(&scan_report));
+    }
+
+    let segment_hashes: Vec<String> = segments.into_iter().map(|(_, hash)| hash).collect();
+    let segments_root_hex = MerkleTree::from_hex_leaves(&segment_hashes)
+        .map_err(AppError::Internal)?
+        .root_hex();
+    let header = DocumentStreamEnvelopeV1::new(
+        &upload.owner_wallet,
+        time::OffsetDateTime::now_utc().unix_timestamp(),
+        CanonicalDocumentV1 {
+            logical_id: upload.doc_id.to_string(),
+            filename: upload.label.clone(),
+            mime: Some(upload.mime_type.clone()),
+            plaintext_sha3_256_hex: hash_hex.clone(),
+            size_bytes: upload.file_size as u64,
+        },
+        upload.stream_key.clone(),
+        segments_root_hex,
+    );
+    let header_bytes = canonical_json(&header);
+    let ciphertext_hash_hex = hex::encode(pqc_sha3::sha3_256_bytes(&header_bytes));
+
+    let claimed = sqlx::query(
+        "update document_uploads set status = 'completed', completed_at = now() where id = $1 and status = 'open'",
+    )
+    .bind(upload.id)
+    .execute(&st.db)
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?;
+    if claimed.rows_affected() == 0 {
+        return Err(AppError::BadRequest("Upload is already completed".into()));
+    }
+
+    let persisted = persist_document_record(
+        &st,
+        &upload.owner_wallet,
+        upload.doc_id,
+        upload.version,
+        hash_hex,
+        upload.label.clone(),
+        upload.mime_type.clone(),
+        upload.file_size,
+        upload.parent_id,
+        upload.anchor_to_arweave,
+        header_bytes,
+        ENCRYPTION_MODE_SERVER_STREAM,
+        ciphertext_hash_hex,
+    )
+    .await;
+    let reopen = || async {
+        let _ = sqlx::query(
+            "update document_uploads set status = 'open', completed_at = null where id = $1",
+        )
+        .bind(upload.id)
+        .execute(&st.db)
+        .await;
+    };
+    let mut record = match persisted {
+        Ok(record) => record,
+        Err(err) => {
+            reopen().await;
+            return Err(err);
+        }
+    };
+
+    // The scan is the document's first custody event, and a document is
+    // never left behind without one.
+    let scanned = record_scan_event(
+        &st,
+        record.id,
+        &sha256_hex,
+        Some(&scan_report),
+        false,
+        &session,
+        &headers,
+    )
+    .await;
+    if let Err(err) = scanned {
+        let _ = sqlx::query("delete from documents where id = $1")
+            .bind(record.id)
+            .execute(&st.db)
+            .await;
+        let _ = st.storage.delete_object(&record.storage_path).await;
+        reopen().await;
+        return Err(err);
+    }
+    let quarantined = quarantine_scanned_upload(&st, &record, Some(&scan_report)).await?;
+
+    let event_type = if upload.parent_id.is_some() {
+        "VERSION_CREATED"
+    } else {
+        "UPLOAD"
+    };
+    let payload = custody_payload(
+        json!({
+            "hash_hex": record.hash_hex,
+            "storage_path": record.storage_path,
+            "version": record.version,
+            "mime_type": record.mime_type,
+            "label": record.label,
+            "parent_id": record.parent_id,
+            "encryption_mode": record.encryption_mode,
+            "upload_id": upload.id,
+            "segment_count": upload.segment_count,
+            "change_summary": upload.change_summary
+        }),
+        &session,
+        &headers,
+    );
+    record_document_pipeline(
+        &st,
+        &mut record,
+        &sha256_hex,
+        &wallet,
+        event_type,
+        payload,
+        upload.anchor_to_arweave,
+    )
+    .await?;
+    record_growth_event(
+        &st.db,
+        if upload.parent_id.is_some() {
+            "DOC_VERSION_CREATED"
+        } else {
+            "DOC_UPLOADED"
+        },
+        "wallet",
+        Some(&wallet),
+        Some(&session.chain),
+        Some(&session.session_id),
+        Some(record.id),
+        None,
+        None,
+        json!({
+            "version": record.version,
+            "mime_type": record.mime_type.clone(),
+            "label": record.label.clone(),
+            "parent_id": record.parent_id,
+            "arweave_tx": record.arweave_tx.clone(),
+            "anchor_to_arweave": upload.anchor_to_arweave,
+            "encryption_mode": record.encryption_mode.clone(),
+            "chunked": true
+        }),
+    )
+    .await;
+
+    Ok(Json(json!({
+        "ok": true,
+        "id": record.id,
+        "version": record.version,
+        "parent_id": record.parent_id,
+        "hash_hex": record.hash_hex,
+        "arweave_tx": record.arweave_tx,
+        "quarantined": quarantined
+    })))
+}
+
+async fn review_doc_handler(
+    State(st): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<uuid::Uuid>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let session = require_session_from_headers(&st, &headers).await?;
+    let wallet = session.wallet.clone();
+    let doc = load_document_access_record(&st.db, id, &wallet, &session.chain).await?;
+
+    insert_document_event(
+        &st.db,
+        id,
+        &wallet,
+        "VIEW",
+        custody_payload(
+            json!({
+                "owner_wallet": doc.owner_wallet,
+                "label": doc.label,
+                "hash_hex": doc.hash_hex,
+                "version": doc.version,
+                "mime_type": doc.mime_type,
+                "parent_id": doc.parent_id,
+                "arweave_tx": doc.arweave_tx
+            }),
+            &session,
+            &headers,
+        ),
+    )
+    .await?;
+    record_growth_event(
+        &st.db,
+        "DOC_VIEWED",
+        "wallet",
+        Some(&wallet),
+        Some(&session.chain),
+        Some(&session.session_id),
+        Some(id),
+        None,
+        None,
+        json!({
+            "owner_wallet": doc.owner_wallet.clone(),
+            "version": doc.version,
+            "mime_type": doc.mime_type.clone()
+        }),
+    )
+    .await;
+
+    Ok(Json(json!({
+        "url": internal_blob_url(id),
+        "blob_url": internal_blob_url(id),
+        "owner_wallet": doc.owner_wallet,
+        "label": doc.label,
+        "hash_hex": doc.hash_hex,
+        "version": doc.version,
+        "mime_type": doc.mime_type,
+        "parent_id": doc.parent_id,
+        "arweave_tx": doc.arweave_tx,
+        "encryption_mode": doc.encryption_mode,
+        "ciphertext_hash_hex": doc.ciphertext_hash_hex,
+        "quarantined_at": doc.quarantined_at,
+        "onlyoffice_enabled": onlyoffice_editor_enabled() && is_office_mime_type(&doc.mime_type)
+    })))
+}
+
+async fn doc_blob_handler(
+    State(st): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<uuid::Uuid>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Response, AppError> {
+    let session = require_session_from_headers(&st, &headers).await?;
+    let access = load_document_access_record(&st.db, id, &session.wallet, &session.chain).await?;
+    let doc = load_document_bytes_for_access(&st, &access).await?;
+    let disposition = params
+        .get("download")
+        .map(|value| value == "1" || value == "true");
+    let filename = doc
+        .label
+        .clone()
+        .filter(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| format!("{id}.bin"));
+    let content_disposition = if disposition.unwrap_or(false) {
+        format!("attachment; filename=\"{}\"", filename.replace('"', ""))
+    } else {
+        "inline".to_string()
+    };
+
+    Ok((
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, doc.mime_type),
+            (header::CONTENT_DISPOSITION, content_disposition),
+            (
+                header::HeaderName::from_static("x-tidbit-hash"),
+                doc.hash_hex,
+            ),
+            (
+                header::HeaderName::from_static("x-tidbit-version"),
+                doc.version.to_string(),
+            ),
+            (
+                header::HeaderName::from_static("x-tidbit-encryption-mode"),
+                doc.encryption_mode,
+            ),
+        ],
+        doc.body,
+    )
+        .into_response())
+}
+
+/// Stored envelope for wallets holding their own wrapped CEK, so they can
+/// decrypt client-side instead of going through `/blob`.
+async fn doc_envelope_handler(
+    State(st): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<uuid::Uuid>,
+) -> Result<Response, AppError> {
+    let session = require_session_from_headers(&st, &headers).await?;
+    let access = load_document_access_record(&st.db, id, &session.wallet, &session.chain).await?;
+    ensure_not_quarantined(&access)?;
+    if !is_envelope_encryption_mode(&access.encryption_mode) {
+        return Err(AppError::BadRequest(
+            "Document is not envelope-encrypted".into(),
+        ));
+    }
+
+    let stored = st
+        .storage
+        .download_bytes(&access.storage_path)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    let envelope = read_document_envelope(&stored).map_err(AppError::Crypto)?;
+    if !envelope.has_recipient(&session.wallet) {
+        return Err(AppError::Forbidden(
+            "No wrapped key for this wallet; use the blob endpoint".into(),
+        ));
+    }
+
+    Ok((
+        StatusCode::OK,
+        [
+            (
+                header::CONTENT_TYPE,
+                envelope_content_type(&stored).to_string(),
+            ),
+            (
+                header::HeaderName::from_static("x-tidbit-ciphertext-hash"),
+                access.ciphertext_hash_hex.unwrap_or_default(),
+            ),
+        ],
+        stored,
+    )
+        .into_response())
+}
+
+// ================================================================
+// DOWNLOAD
+// ================================================================
+
+async fn download_doc_handler(
+    State(st): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<uuid::Uuid>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let session = require_session_from_headers(&st, &headers).await?;
+    let wallet = session.wallet.clone();
+    let doc = load_document_access_record(&st.db, id, &wallet, &session.chain).await?;
+    ensure_not_quarantined(&doc)?;
+
+    insert_document_event(
+        &st.db,
+        id,
+        &wallet,
+        "DOWNLOAD",
+        custody_payload(
+            json!({
+                "owner_wallet": doc.owner_wallet,
+                "label": doc.label,
+                "hash_hex": doc.hash_hex,
+                "version": doc.version,
+                "mime_type": doc.mime_type,
+                "parent_id": doc.parent_id,
+                "arweave_tx": doc.arweave_tx
+            }),
+            &session,
+            &headers,
+        ),
+    )
+    .await?;
+    record_growth_event(
+        &st.db,
+        "DOC_DOWNLOADED",
+        "wallet",
+        Some(&wallet),
+        Some(&session.chain),
+        Some(&session.session_id),
+        Some(id),
+        None,
+        None,
+        json!({
+            "owner_wallet": doc.owner_wallet.clone(),
+            "version": doc.version,
+            "mime_type": doc.mime_type.clone()
+        }),
+    )
+    .await;
+
+    Ok(Json(json!({
+        "url": format!("{}?download=1", internal_blob_url(id)),
+        "blob_url": internal_blob_url(id),
+        "encryption_mode": doc.encryption_mode
+    })))
+}
+
+// ================================================================
+// SIGN
+// ================================================================
+
+async fn sign_doc_handler(
+    State(st): State<AppState>,
+    headers: HeaderMap,
+    Path(doc_id): Path<uuid::Uuid>,
+    Json(body): Json<SignRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let session = require_session_from_headers(&st, &headers).await?;
+    let wallet = session.wallet.clone();
+    let doc = load_document_access_record(&st.db, doc_id, &wallet, &session.chain)
+        .await
+        .map_err(|_| {
+            AppError::Forbidden("You do not have signing access to this document".into())
+        })?;
+
+    if body.signature.trim().is_empty() {
+        return Err(AppError::BadRequest("Missing signature".into()));
+    }
+
+    let signature_type = body
+        .signature_type
+        .clone()
+        .unwrap_or_else(|| "evm_personal_sign".to_string());
+    let canonical_message = document_sign_message(doc_id, &doc.hash_hex, &wallet, doc.version);
+
+    let policy = load_document_policy(&st.db, doc_id, &doc.owner_wallet).await?;
+    if policy_requires_hybrid_signature(&policy) && signature_type != HYBRID_SIGNATURE_TYPE {
+        return Err(AppError::Forbidden(
+            "This document requires a hybrid wallet + ML-DSA signature".into(),
+        ));
+    }
+
+    let verification_payload = match signature_type.as_str() {
+        "evm_personal_sign" => {
+            let method = verify_evm_wallet_signature(
+                st.contracts.as_deref(),
+                &canonical_message,
+                &body.signature,
+                &wallet,
+            )
+            .await?
+            .ok_or_else(|| {
+                AppError::Forbidden("Signature does not match the active wallet".into())
+            })?;
+
+            match method {
+                EvmSignatureMethod::Ecdsa => json!({
+                    "signature_type": signature_type,
+                    "verification_method": method,
+                    "recovered_wallet": wallet.to_lowercase()
+                }),
+                EvmSignatureMethod::Eip1271 => {
+                    let chain_id = match st.contracts.as_deref() {
+                        Some(contracts) => Some(contracts.chain_id().await?),
+                        None => None,
+                    };
+                    json!({
+                        "signature_type": signature_type,
+                        "verification_method": method,
+                        "contract_wallet": wallet.to_lowercase(),
+                        "chain_id": chain_id
+                    })
+                }
+            }
+        }
+        "sol_ed25519" => {
+            verify_solana_signature(&canonical_message, &wallet, &body.signature)?;
+
+            json!({
+                "signature_type": signature_type,
+                "verified_wallet": wallet,
+                "chain": "sol"
+            })
+        }
+        "pq_dilithium3" | "pq_mldsa65" => {
+            let (key_wallet, key_chain) = pq_key_owner(&session);
+            let key = verify_registered_pq_signature(
+                &st.db,
+                &key_wallet,
+                key_chain,
+                body.pq_public_key_b64.as_deref(),
+                &canonical_message,
+                &body.signature,
+            )
+            .await?;
+
+            let mut verification = pq_key_verification_json(&key);
+            verification["signature_type"] = json!(signature_type);
+            verification
+        }
+        HYBRID_SIGNATURE_TYPE => {
+            let pq_signature = body
+                .pq_signature
+                .as_deref()
+                .filter(|value| !value.trim().is_empty())
+                .ok_or_else(|| {
+                    AppError::BadRequest("pq_signature is required for hybrid signing".into())
+                })?;
+            let (key_wallet, key_chain) = pq_key_owner(&session);
+            // The wallet half is checked like a plain EVM signature, so
+            // contract wallets can sign hybrid through EIP-1271.
+            let wallet_method = if key_chain == "evm" {
+                let method = verify_evm_wallet_signature(
+                    st.contracts.as_deref(),
+                    &canonical_message,
+                    &body.signature,
+                    &key_wallet,
+                )
+                .await?
+                .ok_or_else(|| {
+                    AppError::Forbidden("Wallet signature does not match the active wallet".into())
+                })?;
+                Some(method)
+            } else {
+                verify_wallet_signature(
+                    key_chain,
+                    &key_wallet,
+                    &canonical_message,
+                    &body.signature,
+                )?;
+                None
+            };
+            let key = verify_registered_pq_signature(
+                &st.db,
+                &key_wallet,
+                key_chain,
+                body.pq_public_key_b64.as_deref(),
+                &canonical_message,
+                pq_signature,
+            )
+            .await?;
+
+            let mut verification = pq_key_verification_json(&key);
+            verification["signature_type"] = json!(signature_type);
+            verification["wallet_signature_type"] = json!(hybrid_wallet_signature_type(key_chain));
+            verification["wallet_signature"] = json!(body.signature);
+            verification["pq_signature"] = json!(pq_signature);
+            if let Some(method) = wallet_method {
+                verification["verification_method"] = json!(method);
+                if method == EvmSignatureMethod::Eip1271 {
+                    verification["contract_wallet"] = json!(key_wallet);
+                    if let Some(contracts) = st.contracts.as_deref() {
+                        verification["chain_id"] = json!(contracts.chain_id().await?);
+                    }
+                }
+            }
+            verification
+        }
+        _ => {
+            return Err(AppError::BadRequest("Unsupported signature_type".into()));
+        }
+    };
+
+    let sign_event_id = insert_document_event(
+        &st.db,
+        doc_id,
+        &wallet,
+        "SIGN",
+        custody_payload(
+            json!({
+                "signature": body.signature,
+                "hash_hex": doc.hash_hex,
+                "version": doc.version,
+                "mime_type": doc.mime_type,
+                "parent_id": doc.parent_id,
+                "arweave_tx": doc.arweave_tx,
+                "signing_message": canonical_message,
+                "verification": verification_payload
+            }),
+            &session,
+            &headers,
+        ),
+    )
+    .await?;
+    let signer_chain =
+        canonical_chain(&session.chain).unwrap_or_else(|| infer_wallet_chain(&wallet));
+    record_workflow_signature(
+        &st,
+        doc_id,
+        WorkflowSignerMatch::Wallet {
+            wallet: &normalize_wallet_for_chain(&wallet, signer_chain),
+            chain: signer_chain,
+        },
+        sign_event_id,
+    )
+    .await?;
+    record_growth_event(
+        &st.db,
+        "DOC_SIGNED",
+        "wallet",
+        Some(&wallet),
+        Some(&session.chain),
+        Some(&session.session_id),
+        Some(doc_id),
+        None,
+        None,
+        json!({
+            "version": doc.version,
+            "mime_type": doc.mime_type,
+            "signature_type": signature_type,
+            "verification": verification_payload
+        }),
+    )
+    .await;
+
+    Ok(Json(json!({ "ok": true })))
+}
+
+// ================================================================
+// DELETE
+// ================================================================
+
+async fn delete_doc_handler(
+    State(st): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<uuid::Uuid>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let session = require_session_from_headers(&st, &headers).await?;
+    let wallet = normalize_wallet_for_chain(
+        &session.wallet,
+        canonical_chain(&session.chain).unwrap_or_else(|| infer_wallet_chain(&session.wallet)),
+    );
+    let chain =
+        canonical_chain(&session.chain).unwrap_or_else(|| infer_wallet_chain(&session.wallet));
+
+    let result = sqlx::query(
+        r#"
+        update documents
+        set is_deleted = true
+        where id = $1
+          and (
+                ($3 = 'evm' and lower(owner_wallet) = lower($2))
+             or ($3 = 'sol' and owner_wallet = $2)
+              )
+        "#,
+    )
+    .bind(id)
+    .bind(&wallet)
+    .bind(chain)
+    .execute(&st.db)
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound("Document not found".into()));
+    }
+
+    insert_document_event(
+        &st.db,
+        id,
+        &wallet,
+        "DELETE",
+        custody_payload(json!({}), &session, &headers),
+    )
+    .await?;
+
+    Ok(Json(json!({ "ok": true })))
+}
+
+// ================================================================
+// QUARANTINE
+// ================================================================
+
+/// Hold a document for admin review. Returns false if it was already held.
+async fn quarantine_document(
+    db: &PgPool,
+    doc_id: uuid::Uuid,
+    actor: &str,
+    reason: &str,
+    payload: serde_json::Value,
+) -> Result<bool, AppError> {
+    let result = sqlx::query(
+        r#"
+        update documents
+        set quarantined_at = now(),
+            quarantined_by = $2,
+            quarantine_reason = $3
+        where id = $1
+          and is_deleted = false
+          and quarantined_at is null
+        "#,
+    )
+    .bind(doc_id)
+    .bind(actor)
+    .bind(reason)
+    .execute(db)
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    if result.rows_affected() == 0 {
+        return Ok(false);
+    }
+    insert_document_event(db, doc_id, actor, "QUARANTINED", payload).await?;
+    Ok(true)
+}
+
+/// Hold a newly stored upload for review when its scan calls for it.
+/// Returns whether the document is now quarantined.
+async fn quarantine_scanned_upload(
+    st: &AppState,
+    record: &CreatedDocumentRecord,
+    report: Option<&ScanReport>,
+) -> Result<bool, AppError> {
+    let Some(reason) = report.and_then(scan_quarantine_reason) else {
+        return Ok(false);
+    };
+    quarantine_document(
+        &st.db,
+        record.id,
+        "system:sanitizer",
+        &reason,
+        json!({ "reason": reason, "source": "scan", "hash_hex": record.hash_hex }),
+    )
+    .await
+}
+
+/// Suspicious scan findings that hold an upload for review. Active content
+/// alone is only recorded; forms and macros are common in real documents.
+fn scan_quarantine_reason(report: &ScanReport) -> Option<String> {
+    let rules: Vec<&str> = report
+        .findings
+        .iter()
+        .filter(|f| f.verdict == ScanVerdict::Suspicious && f.scanner != "active_content")
+        .map(|f| f.rule.as_str())
+        .collect();
+    (!rules.is_empty()).then(|| format!("scan: {}", rules.join(", ")))
+}
+
+fn quarantine_reason_from_request(body: &QuarantineRequest, fallback: &str) -> String {
+    body.reason
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .unwrap_or(fallback)
+        .to_string()
+}
+
+async fn quarantine_doc_handler(
+    State(st): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<uuid::Uuid>,
+    Json(body): Json<QuarantineRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let session = require_session_from_headers(&st, &headers).await?;
+    let doc = load_document_access_record(&st.db, id, &session.wallet, &session.chain).await?;
+    if !doc.owner_wallet.eq_ignore_ascii_case(&session.wallet) {
+        return Err(AppError::Forbidden(
+            "Only the document owner can quarantine it".into(),
+        ));
+    }
+
+    let reason = quarantine_reason_from_request(&body, "owner request");
+    let quarantined = quarantine_document(
+        &st.db,
+        id,
+        &session.wallet,
+        &reason,
+        custody_payload(
+            json!({ "reason": reason, "source": "owner", "hash_hex": doc.hash_hex }),
+            &session,
+            &headers,
+        ),
+    )
+    .await?;
+
+    Ok(Json(json!({ "ok": true, "quarantined": quarantined })))
+}
+
+async fn admin_quarantine_list_handler(
+    State(st): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<serde_json::Value>, AppError> {
+    require_admin_console_access(&st, &headers).await?;
+
+    let rows = sqlx::query(
+        r#"
+        select
+            d.id,
+            d.owner_wallet,
+            d.label,
+            d.hash_hex,
+            d.version,
+            d.mime_type,
+            d.quarantined_at,
+            d.quarantined_by,
+            d.quarantine_reason,
+            (
+              select e.payload
+              from document_events e
+              where e.doc_id = d.id
+                and e.event_type = 'SCAN'
+              order by e.created_at desc
+              limit 1
+            ) as last_scan
+        from documents d
+        where d.quarantined_at is not null
+          and d.is_deleted = false
+        order by d.quarantined_at desc
+        "#,
+    )
+    .fetch_all(&st.db)
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let items: Vec<serde_json::Value> = rows
+        .into_iter()
+        .map(|r| {
+            json!({
+                "id": r.get::<uuid::Uuid, _>("id"),
+                "owner_wallet": r.get::<String, _>("owner_wallet"),
+                "label": r.get::<Option<String>, _>("label"),
+                "hash_hex": r.get::<String, _>("hash_hex"),
+                "version": r.get::<i32, _>("version"),
+                "mime_type": r.get::<Option<String>, _>("mime_type"),
+                "quarantined_at": r.get::<Option<chrono::DateTime<chrono::Utc>>, _>("quarantined_at"),
+                "quarantined_by": r.get::<Option<String>, _>("quarantined_by"),
+                "quarantine_reason": r.get::<Option<String>, _>("quarantine_reason"),
+                "last_scan": r.get::<Option<serde_json::Value>, _>("last_scan")
+            })
+        })
+        .collect();
+
+    Ok(Json(json!({ "ok": true, "items": items })))
+}
+
+async fn admin_quarantine_doc_handler(
+    State(st): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<uuid::Uuid>,
+    Json(body): Json<QuarantineRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let (session, admin_session) = require_admin_console_access(&st, &headers).await?;
+    let reason = quarantine_reason_from_request(&body, "admin review");
+    let quarantined = quarantine_document(
+        &st.db,
+        id,
+        &session.wallet,
+        &reason,
+        custody_payload(
+            json!({
+                "reason": reason,
+                "source": "admin",
+                "admin_session_id": admin_session.admin_session_id
+            }),
+            &session,
+            &headers,
+        ),
+    )
+    .await?;
+
+    Ok(Json(json!({ "ok": true, "quarantined": quarantined })))
+}
+
+async fn admin_release_quarantine_handler(
+    State(st): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<uuid::Uuid>,
+    Json(body): Json<QuarantineRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let (session, admin_session) = require_admin_console_access(&st, &headers).await?;
+    let row = sqlx::query(
+        r#"
+        update documents d
+        set quarantined_at = null,
+            quarantined_by = null,
+            quarantine_reason = null
+        from documents prev
+        where d.id = $1
+          and prev.id = d.id
+          and d.is_deleted = false
+          and d.quarantined_at is not null
+        returning prev.quarantined_at, prev.quarantined_by, prev.quarantine_reason
+        "#,
+    )
+    .bind(id)
+    .fetch_optional(&st.db)
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+    .ok_or_else(|| AppError::NotFound("Quarantined document not found".into()))?;
+
+    insert_document_event(
+        &st.db,
+        id,
+        &session.wallet,
+        "QUARANTINE_RELEASED",
+        custody_payload(
+            json!({
+                "reason": quarantine_reason_from_request(&body, "released after review"),
+                "quarantined_at": row.get::<Option<chrono::DateTime<chrono::Utc>>, _>("quarantined_at"),
+                "quarantined_by": row.get::<Option<String>, _>("quarantined_by"),
+                "quarantine_reason": row.get::<Option<String>, _>("quarantine_reason"),
+                "admin_session_id": admin_session.admin_session_id
+            }),
+            &session,
+            &headers,
+        ),
+    )
+    .await?;
+
+    Ok(Json(json!({ "ok": true, "released": true })))
+}
+
+/// Delete a quarantined document's blobs, revoke its shares and soft-delete
+/// the row. The custody trail is kept.
+async fn admin_purge_quarantine_handler(
+    State(st): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<uuid::Uuid>,
+    Json(body): Json<QuarantineRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let (session, admin_session) = require_admin_console_access(&st, &headers).await?;
+    let row = sqlx::query(
+        r#"
+        select
+            owner_wallet,
+            storage_path,
+            version,
+            hash_hex,
+            coalesce(encryption_mode, 'plaintext_server_managed') as encryption_mode,
+            quarantine_reason
+        from documents
+        where id = $1
+          and is_deleted = false
+          and quarantined_at is not null
+        "#,
+    )
+    .bind(id)
+    .fetch_optional(&st.db)
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+    .ok_or_else(|| AppError::NotFound("Quarantined document not found".into()))?;
+
+    let owner_wallet: String = row.get("owner_wallet");
+    let storage_path: String = row.get("storage_path");
+    let version: i32 = row.get("version");
+    let encryption_mode: String = row.get("encryption_mode");
+
+    let mut deleted_objects = vec![storage_path.clone()];
+    if encryption_mode == ENCRYPTION_MODE_SERVER_STREAM {
+        let stored = st
+            .storage
+            .download_bytes(&storage_path)
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+        let header: DocumentStreamEnvelopeV1 = serde_json::from_slice(&stored)
+            .map_err(|e| AppError::Crypto(format!("stream envelope parse: {e}")))?;
+        for index in 0..header.encryption.segment_count {
+            deleted_objects.push(stream_segment_path(
+                &owner_wallet,
+                &id.to_string(),
+                version,
+                index,
+            ));
+        }
+    }
+    for path in &deleted_objects {
+        st.storage
+            .delete_object(path)
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+    }
+
+    sqlx::query("update documents set is_deleted = true where id = $1")
+        .bind(id)
+        .execute(&st.db)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    let revoked = sqlx::query(
+        r#"
+        update document_shares
+        set revoked_at = now(),
+            revoked_reason = 'quarantine_purged'
+        where doc_id = $1
+          and revoked_at is null
+        "#,
+    )
+    .bind(id)
+    .execute(&st.db)
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+    .rows_affected();
+
+    insert_document_event(
+        &st.db,
+        id,
+        &session.wallet,
+        "QUARANTINE_PURGED",
+        custody_payload(
+            json!({
+                "reason": quarantine_reason_from_request(&body, "purged after review"),
+                "quarantine_reason": row.get::<Option<String>, _>("quarantine_reason"),
+                "hash_hex": row.get::<String, _>("hash_hex"),
+                "deleted_objects": deleted_objects.len(),
+                "revoked_shares": revoked,
+                "admin_session_id": admin_session.admin_session_id
+            }),
+            &session,
+            &headers,
+        ),
+    )
+    .await?;
+
+    Ok(Json(json!({ "ok": true, "purged": true, "revoked_shares": revoked })))
+}
+
+// ================================================================
+// SHARE
+// ================================================================
+
+async fn share_doc_handler(
+    State(st): State<AppState>,
+    headers: HeaderMap,
+    Path(doc_id): Path<uuid::Uuid>,
+    Json(body): Json<ShareRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let session = require_session_from_headers(&st, &headers).await?;
+    let sender = normalize_wallet_for_chain(
+        &session.wallet,
+        canonical_chain(&session.chain).unwrap_or_else(|| infer_wallet_chain(&session.wallet)),
+    );
+    let sender_chain =
+        canonical_chain(&session.chain).unwrap_or_else(|| infer_wallet_chain(&sender));
+    let requested_wallet = body
+        .recipient_wallet
+        .clone()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+    let requested_email = body
+        .recipient_email
+        .clone()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+    let requested_phone = body
+        .recipient_phone
+        .clone()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+
+    if requested_wallet.is_none() && requested_email.is_none() && requested_phone.is_none() {
+        return Err(AppError::BadRequest(
+            "Provide at least one recipient path: wallet, email, or phone".into(),
+        ));
+    }
+
+    let doc = sqlx::query(
+        r#"
+        select label, hash_hex, quarantined_at
+        from documents
+        where id = $1
+          and (
+                ($3 = 'evm' and lower(owner_wallet) = lower($2))
+             or ($3 = 'sol' and owner_wallet = $2)
+              )
+          and is_deleted = false
+        "#,
+    )
+    .bind(doc_id)
+    .bind(&sender)
+    .bind(sender_chain)
+    .fetch_optional(&st.db)
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let Some(doc) = doc else {
+        return Err(AppError::Forbidden(
+            "You can only share documents you own".into(),
+        ));
+    };
+
+    if doc
+        .get::<Option<chrono::DateTime<chrono::Utc>>, _>("quarantined_at")
+        .is_some()
+    {
+        return Err(AppError::Forbidden(
+            "Quarantined documents cannot be shared".into(),
+        ));
+    }
+
+    let label: Option<String> = doc.get("label");
+    let hash_hex: String = doc.get("hash_hex");
+    let envelope_id = uuid::Uuid::new_v4();
+    let access_token = uuid::Uuid::new_v4().simple().to_string();
+    let access_token_hash = access_token_hash_hex(&access_token);
+    let expires_in_hours = clamp_share_expiry_hours(body.expires_in_hours);
+    let expires_at = chrono::Utc::now() + chrono::Duration::hours(expires_in_hours);
+    let one_time_use = body.one_time_use.unwrap_or(false);
+    let download_allowed = body.download_allowed.unwrap_or(true);
+    let allow_guest_sign = body.allow_guest_sign;
+    let anchor_to_arweave = body
+        .anchor_to_arweave
+        .unwrap_or_else(auto_share_anchor_enabled);
+    let recipient_chain = requested_wallet.as_deref().map(|wallet| {
+        body.recipient_chain
+            .as_deref()
+            .and_then(canonical_chain)
+            .unwrap_or_else(|| infer_wallet_chain(wallet))
+            .to_string()
+    });
+    let recipient_wallet = requested_wallet.as_deref().map(|wallet| {
+        normalize_wallet_for_chain(
+            wallet,
+            recipient_chain
+                .as_deref()
+                .unwrap_or_else(|| infer_wallet_chain(wallet)),
+        )
+    });
+    let recipient_name = body
+        .recipient_name
+        .clone()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+    let escalation = share_escalation_target(&body, expires_in_hours)?;
+    let signing_url = envelope_signing_url(&access_token);
+    let has_wallet_route = recipient_wallet.is_some();
+    let has_provider_request = requested_email.is_some() || requested_phone.is_some();
+    let initial_status = if has_wallet_route {
+        "wallet_shared"
+    } else {
+        "created"
+    };
+    let share_anchor_hash_hex = anchor_to_arweave.then(|| {
+        share_anchor_hash_hex(
+            doc_id,
+            &hash_hex,
+            &sender,
+            sender_chain,
+            recipient_wallet.as_deref(),
+            recipient_chain.as_deref(),
+            recipient_name.as_deref(),
+            requested_email.as_deref(),
+            requested_phone.as_deref(),
+            body.note.as_deref(),
+            envelope_id,
+            expires_at,
+            one_time_use,
+            download_allowed,
+            allow_guest_sign,
+        )
+    });
+
+    let share_id = sqlx::query(
+        r#"insert into document_shares
+        (doc_id, sender_wallet, recipient_wallet, recipient_chain, recipient_name, envelope_id, note, recipient_email, recipient_phone, access_token_hash, expires_at, one_time_use, download_allowed, allow_guest_sign, status, delivery_json, share_anchor_hash_hex, share_arweave_tx, share_anchored_at)
+        values ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11,$12,$13,$14,$15,$16,$17,null,null)
+        returning id"#,
+    )
+    .bind(doc_id)
+    .bind(&sender)
+    .bind(&recipient_wallet)
+    .bind(&recipient_chain)
+    .bind(&recipient_name)
+    .bind(envelope_id)
+    .bind(&body.note)
+    .bind(&requested_email)
+    .bind(&requested_phone)
+    .bind(&access_token_hash)
+    .bind(expires_at)
+    .bind(one_time_use)
+    .bind(download_allowed)
+    .bind(allow_guest_sign)
+    .bind(initial_status)
+    .bind(json!([]))
+    .bind(&share_anchor_hash_hex)
+    .fetch_one(&st.db)
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?
+    .get::<uuid::Uuid, _>("id");
+
+    let (mut deliveries, delivery_errors) = dispatch_share_deliveries(
+        requested_email.as_deref(),
+        requested_phone.as_deref(),
+        label.as_deref(),
+        doc_id,
+        &hash_hex,
+        envelope_id,
+        recipient_name.as_deref(),
+        &signing_url,
+    )
+    .await;
+
+    if let Some(wallet) = recipient_wallet.clone() {
+        deliveries.insert(
+            0,
+            DeliveryOutcome {
+                channel: "wallet",
+                provider: "tidbit",
+                recipient: wallet,
+                external_id: Some(envelope_id.to_string()),
+                status: "available_in_inbox",
+            },
+        );
+    }
+
+    let final_status = derive_share_status(
+        has_wallet_route,
+        has_provider_request,
+        &deliveries,
+        &delivery_errors,
+    );
+
+    sqlx::query(
+        "update document_shares set delivery_json = $2, status = $3 where access_token_hash = $1",
+    )
+    .bind(&access_token_hash)
+    .bind(serde_json::to_value(&deliveries).map_err(|e| AppError::Internal(e.to_string()))?)
+    .bind(final_status)
+    .execute(&st.db)
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let mut share_arweave_tx: Option<String> = None;
+    let mut share_anchor_error: Option<String> = None;
+    let share_anchor_queued = share_anchor_hash_hex.is_some() && anchor_batch_window().is_some();
+    if let Some(anchor_hash_hex) = share_anchor_hash_hex.as_deref().filter(|_| share_anchor_queued) {
+        enqueue_anchor_leaf(&st.db, doc_id, Some(share_id), None, "share", anchor_hash_hex)
+            .await?;
+    }
+    if let Some(anchor_hash_hex) = share_anchor_hash_hex.clone().filter(|_| !share_anchor_queued) {
+        let client = crate::arweave::ArweaveClient::from_env();
+        let payload = crate::arweave::ArweaveAnchorPayload {
+            kind: "share_event_hash",
+            hash_hex: &anchor_hash_hex,
+            label: label.as_deref(),
+        };
+        match client.anchor_hash(&payload).await {
+            Ok(tx_id) => {
+                share_arweave_tx = Some(tx_id.clone());
+                sqlx::query(
+                    "update document_shares set share_arweave_tx = $2, share_anchored_at = now() where access_token_hash = $1",
+                )
+                .bind(&access_token_hash)
+                .bind(&tx_id)
+                .execute(&st.db)
+                .await
+                .map_err(|e| AppError::Internal(e.to_string()))?;
+                track_arweave_anchor(
+                    &st.db,
+                    &tx_id,
+                    "share",
+                    &anchor_hash_hex,
+                    Some(doc_id),
+                    Some(share_id),
+                    None,
+                )
+                .await?;
+
+                insert_document_event(
+                    &st.db,
+                    doc_id,
+                    &sender,
+                    "SHARE_ANCHORED",
+                    custody_payload(
+                        json!({
+                            "envelope_id": envelope_id,
+                            "share_anchor_hash_hex": anchor_hash_hex,
+                            "share_arweave_tx": tx_id
+                        }),
+                        &session,
+                        &headers,
+                    ),
+                )
+                .await?;
+                record_growth_event(
+                    &st.db,
+                    "SHARE_ANCHORED",
+                    "wallet",
+                    Some(&sender),
+                    Some(&session.chain),
+                    Some(&session.session_id),
+                    Some(doc_id),
+                    Some(envelope_id),
+                    None,
+                    json!({
+                        "recipient_chain": recipient_chain.clone(),
+                        "share_anchor_hash_hex": anchor_hash_hex,
+                        "share_arweave_tx": tx_id
+                    }),
+                )
+                .await;
+            }
+            Err(err) => {
+                share_anchor_error = Some(err.to_string());
+                insert_document_event(
+                    &st.db,
+                    doc_id,
+                    &sender,
+                    "SHARE_ANCHOR_FAILED",
+                    custody_payload(
+                        json!({
+                            "envelope_id": envelope_id,
+                            "share_anchor_hash_hex": anchor_hash_hex,
+                            "error": share_anchor_error
+                        }),
+                        &session,
+                        &headers,
+                    ),
+                )
+                .await?;
+            }
+        }
+    }
+
+    let mut recipient_key_wrapped = false;
+    let mut recipient_key_error: Option<String> = None;
+    if let Some(wallet) = recipient_wallet.as_deref() {
+        let access = load_document_access_record(&st.db, doc_id, &sender, sender_chain).await?;
+        match add_envelope_recipient(&st, &access, wallet).await {
+            Ok(wrapped) => recipient_key_wrapped = wrapped,
+            Err(err) => recipient_key_error = Some(err.to_string()),
+        }
+    }
+
+    insert_document_event(
+        &st.db,
+        doc_id,
+        &sender,
+        "SHARE",
+        custody_payload(
+            json!({
+                "recipient_wallet": recipient_wallet,
+                "recipient_chain": recipient_chain,
+                "note": body.note,
+                "envelope_id": envelope_id,
+                "signature": body.signature,
+                "recipient_name": recipient_name,
+                "recipient_email": requested_email,
+                "recipient_phone": requested_phone,
+                "access_token_hash": access_token_hash,
+                "expires_at": expires_at,
+                "one_time_use": one_time_use,
+                "download_allowed": download_allowed,
+                "allow_guest_sign": allow_guest_sign,
+                "share_anchor_hash_hex": share_anchor_hash_hex,
+                "share_arweave_tx": share_arweave_tx,
+                "share_anchor_error": share_anchor_error,
+                "share_anchor_queued": share_anchor_queued,
+                "anchor_to_arweave": anchor_to_arweave
+            }),
+            &session,
+            &headers,
+        ),
+    )
+    .await?;
+
+    if recipient_key_wrapped || recipient_key_error.is_some() {
+        insert_document_event(
+            &st.db,
+            doc_id,
+            &sender,
+            if recipient_key_wrapped {
+                "ENVELOPE_RECIPIENT_ADDED"
+            } else {
+                "ENVELOPE_RECIPIENT_FAILED"
+            },
+            custody_payload(
+                json!({
+                    "envelope_id": envelope_id,
+                    "recipient_wallet": recipient_wallet,
+                    "recipient_chain": recipient_chain,
+                    "error": recipient_key_error
+                }),
+                &session,
+                &headers,
+            ),
+        )
+        .await?;
+    }
+
+    record_delivery_outcomes(
+        &st.db,
+        doc_id,
+        &sender,
+        &deliveries,
+        &delivery_errors,
+        json!({
+            "envelope_id": envelope_id,
+            "recipient_chain": recipient_chain,
+            "sender_chain": sender_chain
+        }),
+        |payload| custody_payload(payload, &session, &headers),
+    )
+    .await?;
+    schedule_share_jobs(
+        &st.db,
+        share_id,
+        has_provider_request,
+        expires_at,
+        body.remind_before_expiry_hours
+            .unwrap_or_else(default_share_reminder_hours),
+        escalation,
+    )
+    .await?;
+    record_growth_event(
+        &st.db,
+        "SHARE_CREATED",
+        "wallet",
+        Some(&sender),
+        Some(&session.chain),
+        Some(&session.session_id),
+        Some(doc_id),
+        Some(envelope_id),
+        None,
+        json!({
+            "recipient_chain": recipient_chain.clone(),
+            "has_wallet_route": has_wallet_route,
+            "has_email_route": requested_email.is_some(),
+            "has_phone_route": requested_phone.is_some(),
+            "status": final_status,
+            "one_time_use": one_time_use,
+            "download_allowed": download_allowed,
+            "allow_guest_sign": allow_guest_sign,
+            "anchor_to_arweave": anchor_to_arweave,
+            "share_arweave_tx": share_arweave_tx.clone()
+        }),
+    )
+    .await;
+
+    Ok(Json(json!({
+        "ok": true,
+        "envelope_id": envelope_id,
+        "doc_id": doc_id,
+        "label": label,
+        "hash_hex": hash_hex,
+        "recipient_wallet": recipient_wallet,
+        "recipient_chain": recipient_chain,
+        "recipient_name": recipient_name,
+        "recipient_email": requested_email,
+        "recipient_phone": requested_phone,
+        "signing_url": signing_url,
+        "status": final_status,
+        "expires_at": expires_at,
+        "one_time_use": one_time_use,
+        "download_allowed": download_allowed,
+        "allow_guest_sign": allow_guest_sign,
+        "anchor_to_arweave": anchor_to_arweave,
+        "share_anchor_hash_hex": share_anchor_hash_hex,
+        "share_arweave_tx": share_arweave_tx,
+        "share_anchor_error": share_anchor_error,
+        "share_anchor_queued": share_anchor_queued,
+        "recipient_key_wrapped": recipient_key_wrapped,
+        "recipient_key_error": recipient_key_error,
+        "delivery": deliveries,
+        "delivery_errors": delivery_errors
+    })))
+}
+
+async fn revoke_share_handler(
+    State(st): State<AppState>,
+    headers: HeaderMap,
+    Path((doc_id, envelope_id)): Path<(uuid::Uuid, uuid::Uuid)>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let session = require_session_from_headers(&st, &headers).await?;
+    let sender = session.wallet.clone();
+
+    let updated = sqlx::query(
+        r#"
+        update document_shares
+        set
+            revoked_at = now(),
+            revoked_reason = 'sender_revoked',
+            status = 'revoked'
+        where doc_id = $1
+          and envelope_id = $2
+          and sender_wallet = $3
+          and revoked_at is null
+        returning recipient_wallet, recipient_chain, recipient_email, recipient_phone
+        "#,
+    )
+    .bind(doc_id)
+    .bind(envelope_id)
+    .bind(&sender)
+    .fetch_optional(&st.db)
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let Some(updated) = updated else {
+        return Err(AppError::NotFound(
+            "Share not found or already revoked".into(),
+        ));
+    };
+
+    insert_document_event(
+        &st.db,
+        doc_id,
+        &sender,
+        "SHARE_REVOKED",
+        custody_payload(
+            json!({
+                "envelope_id": envelope_id,
+                "recipient_wallet": updated.get::<Option<String>, _>("recipient_wallet"),
+                "recipient_chain": updated.get::<Option<String>, _>("recipient_chain"),
+                "recipient_email": updated.get::<Option<String>, _>("recipient_email"),
+                "recipient_phone": updated.get::<Option<String>, _>("recipient_phone"),
+                "reason": "sender_revoked"
+            }),
+            &session,
+            &headers,
+        ),
+    )
+    .await?;
+
+    let recipient_wallet: Option<String> = updated.get("recipient_wallet");
+    let mut rekeyed_recipients: Option<Vec<String>> = None;
+    let mut rekey_error: Option<String> = None;
+    if let Some(wallet) = recipient_wallet.as_deref() {
+        match rekey_after_revoke(&st, doc_id, &session, wallet).await {
+            Ok(recipients) => rekeyed_recipients = recipients,
+            Err(err) => rekey_error = Some(err.to_string()),
+        }
+    }
+    if rekeyed_recipients.is_some() || rekey_error.is_some() {
+        insert_document_event(
+            &st.db,
+            doc_id,
+            &sender,
+            if rekeyed_recipients.is_some() {
+                "ENVELOPE_REKEYED"
+            } else {
+                "ENVELOPE_REKEY_FAILED"
+            },
+            custody_payload(
+                json!({
+                    "envelope_id": envelope_id,
+                    "removed_recipient": recipient_wallet,
+                    "recipients": rekeyed_recipients,
+                    "error": rekey_error
+                }),
+                &session,
+                &headers,
+            ),
+        )
+        .await?;
+    }
+    record_growth_event(
+        &st.db,
+        "SHARE_REVOKED",
+        "wallet",
+        Some(&sender),
+        Some(&session.chain),
+        Some(&session.session_id),
+        Some(doc_id),
+        Some(envelope_id),
+        None,
+        json!({
+            "reason": "sender_revoked"
+        }),
+    )
+    .await;
+
+    Ok(Json(json!({
+        "ok": true,
+        "doc_id": doc_id,
+        "envelope_id": envelope_id,
+        "status": "revoked",
+        "envelope_rekeyed": rekeyed_recipients.is_some(),
+        "rekey_error": rekey_error
+    })))
+}
+
+/// Rotate the CEK on revoke unless the wallet still holds another active
+/// share of the same document.
+async fn rekey_after_revoke(
+    st: &AppState,
+    doc_id: uuid::Uuid,
+    session: &WalletSession,
+    recipient_wallet: &str,
+) -> Result<Option<Vec<String>>, AppError> {
+    let row = sqlx::query(
+        r#"
+        select exists (
+          select 1
+          from document_shares
+          where doc_id = $1
+            and revoked_at is null
+            and recipient_wallet is not null
+            and (
+              ($2 like '0x%' and lower(recipient_wallet) = lower($2))
+              or
+              ($2 not like '0x%' and recipient_wallet = $2)
+            )
+        ) as still_shared
+        "#,
+    )
+    .bind(doc_id)
+    .bind(recipient_wallet)
+    .fetch_one(&st.db)
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?;
+    if row.get::<bool, _>("still_shared") {
+        return Ok(None);
+    }
+
+    let access =
+        load_document_access_record(&st.db, doc_id, &session.wallet, &session.chain).await?;
+    rekey_envelope_without(st, &access, recipient_wallet).await
+}
+
+async fn list_inbox_handler(
+    State(st): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let session = require_session_from_headers(&st, &headers).await?;
+    let wallet = normalize_wallet_for_chain(
+        &session.wallet,
+        canonical_chain(&session.chain).unwrap_or_else(|| infer_wallet_chain(&session.wallet)),
+    );
+    let chain =
+        canonical_chain(&session.chain).unwrap_or_else(|| infer_wallet_chain(&session.wallet));
+
+    let rows = sqlx::query(
+        r#"
+        select
+            s.doc_id,
+            s.sender_wallet,
+            s.recipient_wallet,
+            s.recipient_chain,
+            s.envelope_id,
+            s.note,
+            s.status,
+            d.label,
+            d.hash_hex,
+            d.version
+        from document_shares s
+        join documents d on d.id = s.doc_id
+        where s.recipient_wallet is not null
+          and (
+            (coalesce(s.recipient_chain, '') = 'evm' and $2 = 'evm' and lower(s.recipient_wallet) = lower($1))
+            or
+            (coalesce(s.recipient_chain, '') = 'sol' and $2 = 'sol' and s.recipient_wallet = $1)
+            or
+            (s.recipient_chain is null and (
+              ($1 like '0x%' and lower(s.recipient_wallet) = lower($1))
+              or
+              ($1 not like '0x%' and s.recipient_wallet = $1)
+            ))
+          )
+          and s.status in ('wallet_shared', 'wallet_shared_with_delivery_issues', 'sent', 'created', 'delivery_issue', 'opened')
+          and d.is_deleted = false
+          and d.quarantined_at is null
+        order by d.created_at desc
+        "#,
+    )
+    .bind(&wallet)
+    .bind(chain)
+    .fetch_all(&st.db)
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    Ok(Json(json!({
+        "items": rows
+            .into_iter()
+            .map(|r| json!({
+                "doc_id": r.get::<uuid::Uuid,_>("doc_id"),
+                "sender_wallet": r.get::<String,_>("sender_wallet"),
+                "recipient_wallet": r.get::<Option<String>,_>("recipient_wallet"),
+                "recipient_chain": r.get::<Option<String>,_>("recipient_chain"),
+                "envelope_id": r.get::<uuid::Uuid,_>("envelope_id"),
+                "note": r.get::<Option<String>,_>("note"),
+                "status": r.get::<String,_>("status"),
+                "label": r.get::<Option<String>,_>("label"),
+                "hash_hex": r.get::<String,_>("hash_hex"),
+                "version": r.get::<i32,_>("version")
+            }))
+            .collect::<Vec<_>>()
+    })))
+}
+
+async fn inbox_action_handler(
+    State(st): State<AppState>,
+    headers: HeaderMap,
+    Path(envelope_id): Path<uuid::Uuid>,
+    Json(body): Json<InboxActionRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let session = require_session_from_headers(&st, &headers).await?;
+    let wallet = normalize_wallet_for_chain(
+        &session.wallet,
+        canonical_chain(&session.chain).unwrap_or_else(|| infer_wallet_chain(&session.wallet)),
+    );
+    let chain =
+        canonical_chain(&session.chain).unwrap_or_else(|| infer_wallet_chain(&session.wallet));
+    let action = body.action.trim().to_ascii_lowercase();
+
+    let row = sqlx::query(
+        r#"
+        select s.doc_id, s.status, s.recipient_wallet, s.recipient_chain
+        from document_shares s
+        join documents d on d.id = s.doc_id
+        where s.envelope_id = $1
+          and d.is_deleted = false
+          and d.quarantined_at is null
+          and s.recipient_wallet is not null
+          and (
+            (coalesce(s.recipient_chain, '') = 'evm' and $3 = 'evm' and lower(s.recipient_wallet) = lower($2))
+            or
+            (coalesce(s.recipient_chain, '') = 'sol' and $3 = 'sol' and s.recipient_wallet = $2)
+            or
+            (s.recipient_chain is null and (
+              ($2 like '0x%' and lower(s.recipient_wallet) = lower($2))
+              or
+              ($2 not like '0x%' and s.recipient_wallet = $2)
+            ))
+          )
+        "#,
+    )
+    .bind(envelope_id)
+    .bind(&wallet)
+    .bind(chain)
+    .fetch_optional(&st.db)
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let Some(row) = row else {
+        return Err(AppError::NotFound("Inbox item not found".into()));
+    };
+
+    let doc_id: uuid::Uuid = row.get("doc_id");
+    let next_status = match action.as_str() {
+        "review" | "open" | "download" | "sign" => "accepted",
+        "delete" | "dismiss" => "dismissed",
+        _ => return Err(AppError::BadRequest("Unsupported inbox action".into())),
+    };
+    let event_type = match action.as_str() {
+        "review" | "open" => "INBOX_REVIEWED",
+        "download" => "INBOX_DOWNLOADED",
+        "sign" => "INBOX_SIGNED",
+        "delete" | "dismiss" => "INBOX_DISMISSED",
+        _ => unreachable!(),
+    };
+
+    sqlx::query(
+        "update document_shares set status = $2, viewed_at = coalesce(viewed_at, now()) where envelope_id = $1",
+    )
+    .bind(envelope_id)
+    .bind(next_status)
+    .execute(&st.db)
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    insert_document_event(
+        &st.db,
+        doc_id,
+        &session.wallet,
+        event_type,
+        custody_payload(
+            json!({
+                "envelope_id": envelope_id,
+                "inbox_action": action,
+                "recipient_wallet": row.get::<Option<String>,_>("recipient_wallet"),
+                "recipient_chain": row.get::<Option<String>,_>("recipient_chain"),
+                "share_status": next_status
+            }),
+            &session,
+            &headers,
+        ),
+    )
+    .await?;
+    record_growth_event(
+        &st.db,
+        "INBOX_ACTION",
+        "wallet",
+        Some(&session.wallet),
+        Some(&session.chain),
+        Some(&session.session_id),
+        Some(doc_id),
+        Some(envelope_id),
+        None,
+        json!({
+            "action": action.clone(),
+            "status": next_status
+        }),
+    )
+    .await;
+
+    Ok(Json(json!({
+        "ok": true,
+        "doc_id": doc_id,
+        "envelope_id": envelope_id,
+        "status": next_status,
+        "action": action
+    })))
+}
+
+async fn public_envelope_handler(
+    State(st): State<AppState>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Path(token): Path<String>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let token_hash = access_token_hash_hex(token.trim());
+    let row = sqlx::query(
+        r#"
+        select
+            s.id as share_id,
+            s.doc_id,
+            s.sender_wallet,
+            s.recipient_wallet,
+            s.recipient_chain,
+            s.recipient_name,
+            s.recipient_email,
+            s.recipient_phone,
+            s.note,
+            s.envelope_id,
+            s.status,
+            s.viewed_at,
+            s.delivery_json,
+            s.annotation_json,
+            s.expires_at,
+            s.revoked_at,
+            s.share_anchor_hash_hex,
+            s.share_arweave_tx,
+            s.share_anchored_at,
+            s.one_time_use,
+            s.download_allowed,
+            s.allow_guest_sign,
+            s.completion_count,
+            d.label,
+            d.hash_hex,
+            d.version,
+            d.mime_type,
+            d.storage_path,
+            d.owner_wallet,
+            d.parent_id,
+            d.arweave_tx
+        from document_shares s
+        join documents d on d.id = s.doc_id
+        where s.access_token_hash = $1
+          and d.is_deleted = false
+          and d.quarantined_at is null
+        "#,
+    )
+    .bind(&token_hash)
+    .fetch_optional(&st.db)
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let Some(row) = row else {
+        return Err(AppError::NotFound("Envelope not found".into()));
+    };
+
+    let doc_id: uuid::Uuid = row.get("doc_id");
+    let envelope_id: uuid::Uuid = row.get("envelope_id");
+    let status: String = row.get("status");
+    let expires_at: Option<chrono::DateTime<chrono::Utc>> = row.get("expires_at");
+    let revoked_at: Option<chrono::DateTime<chrono::Utc>> = row.get("revoked_at");
+    let one_time_use: bool = row.get("one_time_use");
+    let download_allowed: bool = row.get("download_allowed");
+    let completion_count: i32 = row.get("completion_count");
+    let viewed_at: Option<chrono::DateTime<chrono::Utc>> = row.get("viewed_at");
+    if revoked_at.is_some() {
+        return Err(AppError::Forbidden(
+            "This share link has been revoked".into(),
+        ));
+    }
+    if expires_at
+        .map(|value| value <= chrono::Utc::now())
+        .unwrap_or(false)
+    {
+        return Err(AppError::Forbidden("This share link has expired".into()));
+    }
+    if one_time_use && completion_count > 0 {
+        return Err(AppError::Forbidden(
+            "This one-time share link has already been used".into(),
+        ));
+    }
+    let owner_wallet: String = row.get("owner_wallet");
+    let policy = load_document_policy(&st.db, doc_id, &owner_wallet).await?;
+    let ip_reputation =
+        check_public_ip_reputation(&st, doc_id, envelope_id, &policy, peer, &headers).await?;
+    let allow_guest_sign = row
+        .get::<Option<bool>, _>("allow_guest_sign")
+        .unwrap_or_else(|| {
+            policy
+                .get("allow_guest_sign")
+                .and_then(|value| value.as_bool())
+                .unwrap_or(false)
+        });
+    let allowed_signature_types = if policy_requires_hybrid_signature(&policy) {
+        vec![HYBRID_SIGNATURE_TYPE]
+    } else {
+        let mut modes = Vec::new();
+        if allow_guest_sign && public_guest_attestation_enabled() {
+            modes.push("guest_attestation");
+        }
+        modes.push("evm_personal_sign");
+        modes.push("sol_ed25519");
+        modes.push("pq_mldsa65");
+        modes.push(HYBRID_SIGNATURE_TYPE);
+        modes
+    };
+    if viewed_at.is_none() {
+        sqlx::query("update document_shares set viewed_at = now(), open_count = open_count + 1, status = 'opened' where access_token_hash = $1 and viewed_at is null")
+            .bind(&token_hash)
+            .execute(&st.db)
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+
+        insert_document_event(
+            &st.db,
+            doc_id,
+            &format!("guest-envelope:{envelope_id}"),
+            "ENVELOPE_OPENED",
+            public_custody_payload_for_envelope(
+                json!({
+                    "envelope_id": envelope_id,
+                    "recipient_wallet": row.get::<Option<String>,_>("recipient_wallet"),
+                    "recipient_chain": row.get::<Option<String>,_>("recipient_chain"),
+                    "recipient_name": row.get::<Option<String>,_>("recipient_name"),
+                    "recipient_email": row.get::<Option<String>,_>("recipient_email"),
+                    "recipient_phone": row.get::<Option<String>,_>("recipient_phone"),
+                    "status": "opened"
+                }),
+                envelope_id,
+                &headers,
+                &ip_reputation,
+            ),
+        )
+        .await?;
+        record_growth_event(
+            &st.db,
+            "PUBLIC_ENVELOPE_OPENED",
+            "public",
+            None,
+            None,
+            None,
+            Some(doc_id),
+            Some(envelope_id),
+            None,
+            json!({
+                "recipient_chain": row.get::<Option<String>,_>("recipient_chain"),
+                "status": "opened"
+            }),
+        )
+        .await;
+    }
+
+    Ok(Json(json!({
+        "doc_id": doc_id,
+        "envelope_id": envelope_id,
+        "sender_wallet": row.get::<String,_>("sender_wallet"),
+        "recipient_wallet": row.get::<Option<String>,_>("recipient_wallet"),
+        "recipient_chain": row.get::<Option<String>,_>("recipient_chain"),
+        "recipient_name": row.get::<Option<String>,_>("recipient_name"),
+        "recipient_email": row.get::<Option<String>,_>("recipient_email"),
+        "recipient_phone": row.get::<Option<String>,_>("recipient_phone"),
+        "note": row.get::<Option<String>,_>("note"),
+        "delivery": row.get::<serde_json::Value,_>("delivery_json"),
+        "annotation_json": row.get::<serde_json::Value,_>("annotation_json"),
+        "status": if viewed_at.is_none() { "opened" } else { status.as_str() },
+        "expires_at": expires_at,
+        "one_time_use": one_time_use,
+        "download_allowed": download_allowed,
+        "allow_guest_sign": allow_guest_sign,
+        "share_anchor_hash_hex": row.get::<Option<String>,_>("share_anchor_hash_hex"),
+        "share_arweave_tx": row.get::<Option<String>,_>("share_arweave_tx"),
+        "share_anchored_at": row.get::<Option<chrono::DateTime<chrono::Utc>>,_>("share_anchored_at"),
+        "allowed_signature_types": allowed_signature_types,
+        "label": row.get::<Option<String>,_>("label"),
+        "hash_hex": row.get::<String,_>("hash_hex"),
+        "version": row.get::<i32,_>("version"),
+        "mime_type": row.get::<String,_>("mime_type"),
+        "parent_id": row.get::<Option<uuid::Uuid>,_>("parent_id"),
+        "arweave_tx": row.get::<Option<String>,_>("arweave_tx"),
+        "url": public_blob_url(token.trim()),
+        "blob_url": public_blob_url(token.trim())
+    })))
+}
+
+async fn public_envelope_blob_handler(
+    State(st): State<AppState>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Path(token): Path<String>,
+) -> Result<Response, AppError> {
+    let token_hash = access_token_hash_hex(token.trim());
+    let row = sqlx::query(
+        r#"
+        select
+            d.id,
+            d.storage_path,
+            d.owner_wallet,
+            d.label,
+            d.hash_hex,
+            d.version,
+            d.mime_type,
+            d.parent_id,
+            d.arweave_tx,
+            coalesce(d.encryption_mode, 'plaintext_server_managed') as encryption_mode,
+            d.ciphertext_hash_hex,
+            d.quarantined_at,
+            s.expires_at,
+            s.revoked_at,
+            s.one_time_use,
+            s.download_allowed,
+            s.completion_count,
+            s.envelope_id
+        from document_shares s
+        join documents d on d.id = s.doc_id
+        where s.access_token_hash = $1
+          and d.is_deleted = false
+        "#,
+    )
+    .bind(&token_hash)
+    .fetch_optional(&st.db)
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let Some(row) = row else {
+        return Err(AppError::NotFound("Envelope not found".into()));
+    };
+    let expires_at: Option<chrono::DateTime<chrono::Utc>> = row.get("expires_at");
+    let revoked_at: Option<chrono::DateTime<chrono::Utc>> = row.get("revoked_at");
+    let one_time_use: bool = row.get("one_time_use");
+    let download_allowed: bool = row.get("download_allowed");
+    let completion_count: i32 = row.get("completion_count");
+    let envelope_id: uuid::Uuid = row.get("envelope_id");
+
+    if revoked_at.is_some() {
+        return Err(AppError::Forbidden(
+            "This share link has been revoked".into(),
+        ));
+    }
+    if expires_at
+        .map(|value| value <= chrono::Utc::now())
+        .unwrap_or(false)
+    {
+        return Err(AppError::Forbidden("This share link has expired".into()));
+    }
+    if one_time_use && completion_count > 0 {
+        return Err(AppError::Forbidden(
+            "This one-time share link has already been used".into(),
+        ));
+    }
+
+    let access = DocumentAccessRecord {
+        id: row.get("id"),
+        storage_path: row.get("storage_path"),
+        owner_wallet: row.get("owner_wallet"),
+        label: row.get("label"),
+        hash_hex: row.get("hash_hex"),
+        version: row.get("version"),
+        mime_type: row.get("mime_type"),
+        parent_id: row.get("parent_id"),
+        arweave_tx: row.get("arweave_tx"),
+        encryption_mode: row.get("encryption_mode"),
+        ciphertext_hash_hex: row.get("ciphertext_hash_hex"),
+        quarantined_at: row.get("quarantined_at"),
+    };
+    let policy = load_document_policy(&st.db, access.id, &access.owner_wallet).await?;
+    check_public_ip_reputation(&st, access.id, envelope_id, &policy, peer, &headers).await?;
 
-    sqlx::query(
-        "insert into document_events (doc_id, actor_wallet, event_type, payload) values ($1,$2,'VIEW',$3)",
-    )
-    .bind(id)
-    .bind(&wallet)
-    .bind(custody_payload(
-        json!({
-            "owner_wallet": doc.owner_wallet,
-            "label": doc.label,
-            "hash_hex": doc.hash_hex,
-            "version": doc.version,
-            "mime_type": doc.mime_type,
-            "parent_id": doc.parent_id,
-            "arweave_tx": doc.arweave_tx
-        }),
-        &session,
-        &headers,
-    ))
-    .execute(&st.db)
-    .await
-    .map_err(|e| AppError::Internal(e.to_string()))?;
+    let doc = load_document_bytes_for_access(&st, &access).await?;
     record_growth_event(
         &st.db,
-        "DOC_VIEWED",
-        "wallet",
-        Some(&wallet),
-        Some(&session.chain),
-        Some(&session.session_id),
-        Some(id),
+        "PUBLIC_ENVELOPE_DOWNLOADED",
+        "public",
+        None,
         None,
         None,
+        Some(access.id),
+        Some(envelope_id),
+        None,
         json!({
-            "owner_wallet": doc.owner_wallet.clone(),
-            "version": doc.version,
-            "mime_type": doc.mime_type.clone()
+            "download_allowed": download_allowed,
+            "version": access.version,
+            "mime_type": access.mime_type
         }),
     )
     .await;
 
-    Ok(Json(json!({
-        "url": internal_blob_url(id),
-        "blob_url": internal_blob_url(id),
-        "owner_wallet": doc.owner_wallet,
-        "label": doc.label,
-        "hash_hex": doc.hash_hex,
-        "version": doc.version,
-        "mime_type": doc.mime_type,
-        "parent_id": doc.parent_id,
-        "arweave_tx": doc.arweave_tx,
-        "encryption_mode": doc.encryption_mode,
-        "ciphertext_hash_hex": doc.ciphertext_hash_hex,
-        "onlyoffice_enabled": onlyoffice_editor_enabled() && is_office_mime_type(&doc.mime_type)
-    })))
-}
-
-async fn doc_blob_handler(
-    State(st): State<AppState>,
-    headers: HeaderMap,
-    Path(id): Path<uuid::Uuid>,
-    Query(params): Query<HashMap<String, String>>,
-) -> Result<Response, AppError> {
-    let session = require_session_from_headers(&st, &headers).await?;
-    let access = load_document_access_record(&st.db, id, &session.wallet, &session.chain).await?;
-    let doc = load_document_bytes_for_access(&st, &access).await?;
-    let disposition = params
-        .get("download")
-        .map(|value| value == "1" || value == "true");
-    let filename = doc
-        .label
-        .clone()
-        .filter(|value| !value.trim().is_empty())
-        .unwrap_or_else(|| format!("{id}.bin"));
-    let content_disposition = if disposition.unwrap_or(false) {
-        format!("attachment; filename=\"{}\"", filename.replace('"', ""))
-    } else {
-        "inline".to_string()
-    };
-
     Ok((
         StatusCode::OK,
         [
             (header::CONTENT_TYPE, doc.mime_type),
-            (header::CONTENT_DISPOSITION, content_disposition),
+            (
+                header::CONTENT_DISPOSITION,
+                if download_allowed {
+                    "inline".to_string()
+                } else {
+                    "inline; filename=\"preview\"".to_string()
+                },
+            ),
+            (header::CACHE_CONTROL, "no-store, max-age=0".to_string()),
             (
                 header::HeaderName::from_static("x-tidbit-hash"),
                 doc.hash_hex,
@@ -5788,134 +10150,206 @@ async fn doc_blob_handler(
                 doc.encryption_mode,
             ),
         ],
-        doc.bytes,
+        doc.body,
     )
         .into_response())
 }
 
-// ================================================================
-// DOWNLOAD
-// ================================================================
-
-async fn download_doc_handler(
+async fn public_envelope_sign_handler(
     State(st): State<AppState>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
     headers: HeaderMap,
-    Path(id): Path<uuid::Uuid>,
+    Path(token): Path<String>,
+    Json(body): Json<PublicEnvelopeSignRequest>,
 ) -> Result<Json<serde_json::Value>, AppError> {
-    let session = require_session_from_headers(&st, &headers).await?;
-    let wallet = session.wallet.clone();
-    let doc = load_document_access_record(&st.db, id, &wallet, &session.chain).await?;
-
-    sqlx::query(
-        "insert into document_events (doc_id, actor_wallet, event_type, payload) values ($1,$2,'DOWNLOAD',$3)",
+    let token_hash = access_token_hash_hex(token.trim());
+    let row = sqlx::query(
+        r#"
+        select
+            s.doc_id,
+            s.envelope_id,
+            s.status,
+            s.expires_at,
+            s.revoked_at,
+            s.one_time_use,
+            s.allow_guest_sign,
+            s.completion_count,
+            d.owner_wallet,
+            d.hash_hex,
+            d.version,
+            d.mime_type,
+            d.parent_id,
+            d.arweave_tx
+        from document_shares s
+        join documents d on d.id = s.doc_id
+        where s.access_token_hash = $1
+          and d.is_deleted = false
+          and d.quarantined_at is null
+        "#,
     )
-    .bind(id)
-    .bind(&wallet)
-    .bind(custody_payload(
-        json!({
-            "owner_wallet": doc.owner_wallet,
-            "label": doc.label,
-            "hash_hex": doc.hash_hex,
-            "version": doc.version,
-            "mime_type": doc.mime_type,
-            "parent_id": doc.parent_id,
-            "arweave_tx": doc.arweave_tx
-        }),
-        &session,
-        &headers,
-    ))
-    .execute(&st.db)
+    .bind(&token_hash)
+    .fetch_optional(&st.db)
     .await
     .map_err(|e| AppError::Internal(e.to_string()))?;
-    record_growth_event(
-        &st.db,
-        "DOC_DOWNLOADED",
-        "wallet",
-        Some(&wallet),
-        Some(&session.chain),
-        Some(&session.session_id),
-        Some(id),
-        None,
-        None,
-        json!({
-            "owner_wallet": doc.owner_wallet.clone(),
-            "version": doc.version,
-            "mime_type": doc.mime_type.clone()
-        }),
-    )
-    .await;
 
-    Ok(Json(json!({
-        "url": format!("{}?download=1", internal_blob_url(id)),
-        "blob_url": internal_blob_url(id),
-        "encryption_mode": doc.encryption_mode
-    })))
-}
+    let Some(row) = row else {
+        return Err(AppError::NotFound("Envelope not found".into()));
+    };
 
-// ================================================================
-// SIGN
-// ================================================================
+    if !body.consent {
+        return Err(AppError::BadRequest("Recipient consent is required".into()));
+    }
+    if body.signer_name.trim().is_empty() {
+        return Err(AppError::BadRequest("Signer name is required".into()));
+    }
 
-async fn sign_doc_handler(
-    State(st): State<AppState>,
-    headers: HeaderMap,
-    Path(doc_id): Path<uuid::Uuid>,
-    Json(body): Json<SignRequest>,
-) -> Result<Json<serde_json::Value>, AppError> {
-    let session = require_session_from_headers(&st, &headers).await?;
-    let wallet = session.wallet.clone();
-    let doc = load_document_access_record(&st.db, doc_id, &wallet, &session.chain)
-        .await
-        .map_err(|_| {
-            AppError::Forbidden("You do not have signing access to this document".into())
-        })?;
+    let doc_id: uuid::Uuid = row.get("doc_id");
+    let envelope_id: uuid::Uuid = row.get("envelope_id");
+    let hash_hex: String = row.get("hash_hex");
+    let version: i32 = row.get("version");
+    let expires_at: Option<chrono::DateTime<chrono::Utc>> = row.get("expires_at");
+    let revoked_at: Option<chrono::DateTime<chrono::Utc>> = row.get("revoked_at");
+    let one_time_use: bool = row.get("one_time_use");
+    let completion_count: i32 = row.get("completion_count");
+    let owner_wallet: String = row.get("owner_wallet");
+    let policy = load_document_policy(&st.db, doc_id, &owner_wallet).await?;
+    let allow_guest_sign = row
+        .get::<Option<bool>, _>("allow_guest_sign")
+        .unwrap_or_else(|| {
+            policy
+                .get("allow_guest_sign")
+                .and_then(|value| value.as_bool())
+                .unwrap_or(false)
+        });
+    let guest_allowed = allow_guest_sign && public_guest_attestation_enabled();
 
-    if body.signature.trim().is_empty() {
-        return Err(AppError::BadRequest("Missing signature".into()));
+    if revoked_at.is_some() {
+        return Err(AppError::Forbidden(
+            "This share link has been revoked".into(),
+        ));
+    }
+    if expires_at
+        .map(|value| value <= chrono::Utc::now())
+        .unwrap_or(false)
+    {
+        return Err(AppError::Forbidden("This share link has expired".into()));
+    }
+    if one_time_use && completion_count > 0 {
+        return Err(AppError::Forbidden(
+            "This one-time share link has already been used".into(),
+        ));
+    }
+    if row.get::<String, _>("status") == "completed" {
+        return Err(AppError::Forbidden(
+            "This envelope has already been completed".into(),
+        ));
     }
+    let ip_reputation =
+        check_public_ip_reputation(&st, doc_id, envelope_id, &policy, peer, &headers).await?;
 
-    let signature_type = body
-        .signature_type
+    let signature_type = body.signature_type.clone().unwrap_or_else(|| {
+        if guest_allowed {
+            "guest_attestation".to_string()
+        } else {
+            "evm_personal_sign".to_string()
+        }
+    });
+    let signer_identity = body
+        .wallet_address
         .clone()
-        .unwrap_or_else(|| "evm_personal_sign".to_string());
-    let canonical_message = document_sign_message(doc_id, &doc.hash_hex, &wallet, doc.version);
+        .filter(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| body.signer_name.trim().to_string());
+    let canonical_message =
+        public_envelope_sign_message(envelope_id, doc_id, &hash_hex, &signer_identity, version);
+    let annotation_fields = normalize_annotation_fields(body.annotation_fields.clone());
 
-    let verification_payload = match signature_type.as_str() {
+    if policy_requires_hybrid_signature(&policy) && signature_type != HYBRID_SIGNATURE_TYPE {
+        return Err(AppError::Forbidden(
+            "This document requires a hybrid wallet + ML-DSA signature".into(),
+        ));
+    }
+
+    let verification = match signature_type.as_str() {
+        "guest_attestation" => json!({
+            "signature_type": "guest_attestation",
+            "consent": true
+        }),
         "evm_personal_sign" => {
-            let recovered = verify_evm_signature(&canonical_message, &body.signature)
+            let wallet_address = body.wallet_address.clone().ok_or_else(|| {
+                AppError::BadRequest("wallet_address is required for EVM signing".into())
+            })?;
+            let signature = body.signature.clone().ok_or_else(|| {
+                AppError::BadRequest("signature is required for EVM signing".into())
+            })?;
+            let recovered = verify_evm_signature(&canonical_message, &signature)
                 .map_err(|_| AppError::BadRequest("Invalid EVM signature".into()))?
                 .to_lowercase();
 
-            if recovered != wallet.to_lowercase() {
+            if recovered != wallet_address.to_lowercase() {
                 return Err(AppError::Forbidden(
-                    "Signature does not match the active wallet".into(),
+                    "EVM signature does not match wallet_address".into(),
                 ));
             }
 
             json!({
-                "signature_type": signature_type,
+                "signature_type": "evm_personal_sign",
+                "wallet_address": wallet_address,
+                "signature": signature,
                 "recovered_wallet": recovered
             })
         }
         "sol_ed25519" => {
-            verify_solana_signature(&canonical_message, &wallet, &body.signature)?;
+            let wallet_address = body.wallet_address.clone().ok_or_else(|| {
+                AppError::BadRequest("wallet_address is required for Solana signing".into())
+            })?;
+            let signature = body.signature.clone().ok_or_else(|| {
+                AppError::BadRequest("signature is required for Solana signing".into())
+            })?;
+
+            verify_solana_signature(&canonical_message, &wallet_address, &signature)?;
 
             json!({
-                "signature_type": signature_type,
-                "verified_wallet": wallet,
+                "signature_type": "sol_ed25519",
+                "wallet_address": wallet_address,
+                "signature": signature,
                 "chain": "sol"
             })
         }
         "pq_dilithium3" | "pq_mldsa65" => {
-            let public_key_b64 = body
-                .pq_public_key_b64
-                .clone()
-                .ok_or_else(|| AppError::BadRequest("Missing pq_public_key_b64".into()))?;
+            // Recipients who registered a key are held to it; anyone else
+            // signs with an ad-hoc key, recorded as unregistered.
+            let registered = match body
+                .wallet_address
+                .as_deref()
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+            {
+                Some(wallet) => {
+                    let chain = infer_wallet_chain(wallet);
+                    registered_pq_key_for(
+                        &st.db,
+                        &normalize_wallet_for_chain(wallet, chain),
+                        chain,
+                        body.pq_public_key_b64.as_deref(),
+                    )
+                    .await?
+                }
+                None => None,
+            };
+            let pq_public_key_b64 = match &registered {
+                Some(key) => key.public_key_b64.clone(),
+                None => body.pq_public_key_b64.clone().ok_or_else(|| {
+                    AppError::BadRequest("pq_public_key_b64 is required for PQ signing".into())
+                })?,
+            };
+            let signature = body.signature.clone().ok_or_else(|| {
+                AppError::BadRequest("signature is required for PQ signing".into())
+            })?;
             let public_key = base64::engine::general_purpose::STANDARD
-                .decode(&public_key_b64)
+                .decode(&pq_public_key_b64)
                 .map_err(|_| AppError::BadRequest("Invalid pq_public_key_b64".into()))?;
             let signed_message = base64::engine::general_purpose::STANDARD
-                .decode(&body.signature)
+                .decode(&signature)
                 .map_err(|_| AppError::BadRequest("Invalid PQ signed message encoding".into()))?;
             let verified =
                 dilithium::verify(&public_key, canonical_message.as_bytes(), &signed_message)
@@ -5927,1363 +10361,1646 @@ async fn sign_doc_handler(
                 ));
             }
 
-            json!({
-                "signature_type": signature_type,
-                "pq_public_key_b64": public_key_b64
-            })
+            let mut verification = match &registered {
+                Some(key) => pq_key_verification_json(key),
+                None => json!({ "pq_public_key_b64": pq_public_key_b64 }),
+            };
+            verification["signature_type"] = json!("pq_mldsa65");
+            verification["signature"] = json!(signature);
+            verification["pq_key_registered"] = json!(registered.is_some());
+            verification
         }
-        _ => {
-            return Err(AppError::BadRequest("Unsupported signature_type".into()));
+        HYBRID_SIGNATURE_TYPE => {
+            // Both halves are tied to the recipient's wallet, so only a
+            // registered ML-DSA key is accepted here.
+            let wallet_address = body
+                .wallet_address
+                .as_deref()
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .ok_or_else(|| {
+                    AppError::BadRequest("wallet_address is required for hybrid signing".into())
+                })?;
+            let signature = body.signature.clone().ok_or_else(|| {
+                AppError::BadRequest("signature is required for hybrid signing".into())
+            })?;
+            let pq_signature = body.pq_signature.clone().ok_or_else(|| {
+                AppError::BadRequest("pq_signature is required for hybrid signing".into())
+            })?;
+            let chain = infer_wallet_chain(wallet_address);
+            verify_wallet_signature(chain, wallet_address, &canonical_message, &signature)?;
+            let key = verify_registered_pq_signature(
+                &st.db,
+                &normalize_wallet_for_chain(wallet_address, chain),
+                chain,
+                body.pq_public_key_b64.as_deref(),
+                &canonical_message,
+                &pq_signature,
+            )
+            .await?;
+
+            let mut verification = pq_key_verification_json(&key);
+            verification["signature_type"] = json!(HYBRID_SIGNATURE_TYPE);
+            verification["wallet_address"] = json!(wallet_address);
+            verification["wallet_signature_type"] = json!(hybrid_wallet_signature_type(chain));
+            verification["wallet_signature"] = json!(signature);
+            verification["pq_signature"] = json!(pq_signature);
+            verification["pq_key_registered"] = json!(true);
+            verification
         }
+        _ => return Err(AppError::BadRequest("Unsupported signature_type".into())),
     };
 
+    if signature_type == "guest_attestation" && !guest_allowed {
+        return Err(AppError::Forbidden(
+            "Guest attestation is disabled for this envelope".into(),
+        ));
+    }
+
+    let annotation_json = json!({
+        "annotation_text": body.annotation_text,
+        "annotation_fields": annotation_fields,
+        "sign_reason": body.sign_reason,
+        "consent": body.consent
+    });
+
     sqlx::query(
-        "insert into document_events (doc_id, actor_wallet, event_type, payload) values ($1,$2,'SIGN',$3)",
+        r#"
+        update document_shares
+        set
+            status = 'completed',
+            signed_at = now(),
+            completed_at = now(),
+            signer_name = $1,
+            signer_email = $2,
+            signer_title = $3,
+            signer_org = $4,
+            signer_wallet = $5,
+            sign_reason = $6,
+            annotation_json = $7,
+            completion_signature_type = $8,
+            completion_count = completion_count + 1
+        where access_token_hash = $9
+        "#,
     )
-    .bind(doc_id)
-    .bind(&wallet)
-    .bind(custody_payload(
-        json!({
-            "signature": body.signature,
-            "hash_hex": doc.hash_hex,
-            "version": doc.version,
-            "mime_type": doc.mime_type,
-            "parent_id": doc.parent_id,
-            "arweave_tx": doc.arweave_tx,
-            "signing_message": canonical_message,
-            "verification": verification_payload
-        }),
-        &session,
-        &headers,
-    ))
+    .bind(body.signer_name.trim())
+    .bind(body.signer_email.as_deref())
+    .bind(body.signer_title.as_deref())
+    .bind(body.signer_org.as_deref())
+    .bind(body.wallet_address.as_deref())
+    .bind(body.sign_reason.as_deref())
+    .bind(&annotation_json)
+    .bind(&signature_type)
+    .bind(&token_hash)
     .execute(&st.db)
     .await
     .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let completed_event_id = insert_document_event(
+        &st.db,
+        doc_id,
+        &format!("guest-envelope:{envelope_id}"),
+        "ENVELOPE_COMPLETED",
+        public_custody_payload_for_envelope(
+            json!({
+                "envelope_id": envelope_id,
+                "hash_hex": hash_hex,
+                "version": version,
+                "mime_type": row.get::<String,_>("mime_type"),
+                "parent_id": row.get::<Option<uuid::Uuid>,_>("parent_id"),
+                "arweave_tx": row.get::<Option<String>,_>("arweave_tx"),
+                "signer_name": body.signer_name.trim(),
+                "signer_email": body.signer_email,
+                "signer_title": body.signer_title,
+                "signer_org": body.signer_org,
+                "signer_wallet": body.wallet_address,
+                "completion_signature_type": signature_type,
+                "annotation_json": annotation_json,
+                "signing_message": canonical_message,
+                "verification": verification
+            }),
+            envelope_id,
+            &headers,
+            &ip_reputation,
+        ),
+    )
+    .await?;
+    record_workflow_signature(
+        &st,
+        doc_id,
+        WorkflowSignerMatch::Envelope(envelope_id),
+        completed_event_id,
+    )
+    .await?;
+    let signer_chain = body
+        .wallet_address
+        .as_deref()
+        .map(infer_wallet_chain)
+        .map(str::to_string);
+    let normalized_signer_wallet = body.wallet_address.as_deref().map(|wallet| {
+        normalize_wallet_for_chain(
+            wallet,
+            signer_chain
+                .as_deref()
+                .unwrap_or_else(|| infer_wallet_chain(wallet)),
+        )
+    });
     record_growth_event(
         &st.db,
-        "DOC_SIGNED",
-        "wallet",
-        Some(&wallet),
-        Some(&session.chain),
-        Some(&session.session_id),
-        Some(doc_id),
+        "PUBLIC_ENVELOPE_COMPLETED",
+        "public",
+        normalized_signer_wallet.as_deref(),
+        signer_chain.as_deref(),
         None,
+        Some(doc_id),
+        Some(envelope_id),
         None,
         json!({
-            "version": doc.version,
-            "mime_type": doc.mime_type,
-            "signature_type": signature_type,
-            "verification": verification_payload
+            "signature_type": signature_type.clone(),
+            "signer_name": body.signer_name.trim(),
+            "has_wallet": body.wallet_address.is_some()
         }),
     )
     .await;
 
-    Ok(Json(json!({ "ok": true })))
+    Ok(Json(json!({
+        "ok": true,
+        "envelope_id": envelope_id,
+        "status": "completed"
+    })))
 }
 
 // ================================================================
-// DELETE
+// SIGNING WORKFLOWS
 // ================================================================
 
-async fn delete_doc_handler(
-    State(st): State<AppState>,
-    headers: HeaderMap,
-    Path(id): Path<uuid::Uuid>,
-) -> Result<Json<serde_json::Value>, AppError> {
-    let session = require_session_from_headers(&st, &headers).await?;
-    let wallet = normalize_wallet_for_chain(
-        &session.wallet,
-        canonical_chain(&session.chain).unwrap_or_else(|| infer_wallet_chain(&session.wallet)),
-    );
-    let chain =
-        canonical_chain(&session.chain).unwrap_or_else(|| infer_wallet_chain(&session.wallet));
-
-    let result = sqlx::query(
-        r#"
-        update documents
-        set is_deleted = true
-        where id = $1
-          and (
-                ($3 = 'evm' and lower(owner_wallet) = lower($2))
-             or ($3 = 'sol' and owner_wallet = $2)
-              )
-        "#,
-    )
-    .bind(id)
-    .bind(&wallet)
-    .bind(chain)
-    .execute(&st.db)
-    .await
-    .map_err(|e| AppError::Internal(e.to_string()))?;
-
-    if result.rows_affected() == 0 {
-        return Err(AppError::NotFound("Document not found".into()));
-    }
+/// Events written while a workflow advances on its own, e.g. notifying the
+/// next step after someone else signed.
+fn workflow_custody_payload(base: serde_json::Value, workflow_id: uuid::Uuid) -> serde_json::Value {
+    let mut payload = match base {
+        serde_json::Value::Object(map) => map,
+        other => {
+            let mut map = serde_json::Map::new();
+            map.insert("data".into(), other);
+            map
+        }
+    };
 
-    sqlx::query(
-        "insert into document_events (doc_id, actor_wallet, event_type, payload) values ($1,$2,'DELETE',$3)",
-    )
-    .bind(id)
-    .bind(&wallet)
-    .bind(custody_payload(json!({}), &session, &headers))
-    .execute(&st.db)
-    .await
-    .map_err(|e| AppError::Internal(e.to_string()))?;
+    payload.insert("recorded_at".into(), json!(chrono::Utc::now()));
+    payload.insert(
+        "actor".into(),
+        json!({
+            "kind": "signing_workflow",
+            "workflow_id": workflow_id
+        }),
+    );
+    payload.insert("actor_chain".into(), json!("signing-workflow"));
+    payload.insert("workflow_id".into(), json!(workflow_id));
 
-    Ok(Json(json!({ "ok": true })))
+    serde_json::Value::Object(payload)
 }
 
-// ================================================================
-// SHARE
-// ================================================================
-
-async fn share_doc_handler(
-    State(st): State<AppState>,
-    headers: HeaderMap,
-    Path(doc_id): Path<uuid::Uuid>,
-    Json(body): Json<ShareRequest>,
-) -> Result<Json<serde_json::Value>, AppError> {
-    let session = require_session_from_headers(&st, &headers).await?;
-    let sender = normalize_wallet_for_chain(
-        &session.wallet,
-        canonical_chain(&session.chain).unwrap_or_else(|| infer_wallet_chain(&session.wallet)),
-    );
-    let sender_chain =
-        canonical_chain(&session.chain).unwrap_or_else(|| infer_wallet_chain(&sender));
-    let requested_wallet = body
-        .recipient_wallet
-        .clone()
-        .map(|value| value.trim().to_string())
-        .filter(|value| !value.is_empty());
-    let requested_email = body
-        .recipient_email
-        .clone()
-        .map(|value| value.trim().to_string())
-        .filter(|value| !value.is_empty());
-    let requested_phone = body
-        .recipient_phone
-        .clone()
-        .map(|value| value.trim().to_string())
-        .filter(|value| !value.is_empty());
+async fn load_owned_signing_workflow(
+    st: &AppState,
+    session: &WalletSession,
+    workflow_id: uuid::Uuid,
+) -> Result<SigningWorkflow, AppError> {
+    let chain =
+        canonical_chain(&session.chain).unwrap_or_else(|| infer_wallet_chain(&session.wallet));
+    let owner = normalize_wallet_for_chain(&session.wallet, chain);
+    let workflow = load_signing_workflow(&st.db, workflow_id)
+        .await?
+        .filter(|workflow| {
+            workflow.owner_chain == chain && workflow.owner_wallet.eq_ignore_ascii_case(&owner)
+        })
+        .ok_or_else(|| AppError::NotFound("Signing workflow not found".into()))?;
+    Ok(workflow)
+}
 
-    if requested_wallet.is_none() && requested_email.is_none() && requested_phone.is_none() {
+/// Validate one requested signer. Exactly one route is allowed: a wallet,
+/// an email and/or phone, or an agent.
+async fn resolve_workflow_signer(
+    db: &PgPool,
+    owner_wallet: &str,
+    step: i32,
+    body: &WorkflowSignerRequest,
+) -> Result<NewWorkflowSigner, AppError> {
+    let trimmed = |value: &Option<String>| {
+        value
+            .as_deref()
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(ToOwned::to_owned)
+    };
+    let wallet = trimmed(&body.wallet);
+    let email = trimmed(&body.email);
+    let phone = trimmed(&body.phone);
+    let routes = [
+        wallet.is_some(),
+        email.is_some() || phone.is_some(),
+        body.agent_id.is_some(),
+    ];
+    if routes.iter().filter(|set| **set).count() != 1 {
         return Err(AppError::BadRequest(
-            "Provide at least one recipient path: wallet, email, or phone".into(),
+            "Each signer needs exactly one of wallet, email/phone, or agent_id".into(),
         ));
     }
 
-    let doc = sqlx::query(
-        r#"
-        select label, hash_hex
-        from documents
-        where id = $1
-          and (
-                ($3 = 'evm' and lower(owner_wallet) = lower($2))
-             or ($3 = 'sol' and owner_wallet = $2)
-              )
-          and is_deleted = false
-        "#,
-    )
-    .bind(doc_id)
-    .bind(&sender)
-    .bind(sender_chain)
-    .fetch_optional(&st.db)
-    .await
-    .map_err(|e| AppError::Internal(e.to_string()))?;
-
-    let Some(doc) = doc else {
-        return Err(AppError::Forbidden(
-            "You can only share documents you own".into(),
-        ));
-    };
+    if let Some(agent_id) = body.agent_id {
+        let agent = sqlx::query(
+            "select owner_wallet from agent_identities where id = $1 and coalesce(is_active, true)",
+        )
+        .bind(agent_id)
+        .fetch_optional(db)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+        let owned = agent
+            .map(|row| row.get::<String, _>("owner_wallet"))
+            .is_some_and(|agent_owner| agent_owner.eq_ignore_ascii_case(owner_wallet));
+        if !owned {
+            return Err(AppError::BadRequest(format!(
+                "Agent {agent_id} is not an active agent you own"
+            )));
+        }
+    }
 
-    let label: Option<String> = doc.get("label");
-    let hash_hex: String = doc.get("hash_hex");
-    let envelope_id = uuid::Uuid::new_v4();
-    let access_token = uuid::Uuid::new_v4().simple().to_string();
-    let access_token_hash = access_token_hash_hex(&access_token);
-    let expires_in_hours = clamp_share_expiry_hours(body.expires_in_hours);
-    let expires_at = chrono::Utc::now() + chrono::Duration::hours(expires_in_hours);
-    let one_time_use = body.one_time_use.unwrap_or(false);
-    let download_allowed = body.download_allowed.unwrap_or(true);
-    let allow_guest_sign = body.allow_guest_sign;
-    let anchor_to_arweave = body
-        .anchor_to_arweave
-        .unwrap_or_else(auto_share_anchor_enabled);
-    let recipient_chain = requested_wallet.as_deref().map(|wallet| {
-        body.recipient_chain
+    let chain = wallet.as_deref().map(|wallet| {
+        body.chain
             .as_deref()
             .and_then(canonical_chain)
             .unwrap_or_else(|| infer_wallet_chain(wallet))
-            .to_string()
-    });
-    let recipient_wallet = requested_wallet.as_deref().map(|wallet| {
-        normalize_wallet_for_chain(
-            wallet,
-            recipient_chain
-                .as_deref()
-                .unwrap_or_else(|| infer_wallet_chain(wallet)),
-        )
     });
-    let recipient_name = body
-        .recipient_name
-        .clone()
-        .map(|value| value.trim().to_string())
-        .filter(|value| !value.is_empty());
-    let signing_url = envelope_signing_url(&access_token);
-    let has_wallet_route = recipient_wallet.is_some();
-    let has_provider_request = requested_email.is_some() || requested_phone.is_some();
-    let initial_status = if has_wallet_route {
-        "wallet_shared"
+    let default_role = if body.agent_id.is_some() {
+        "agent_signer"
     } else {
-        "created"
+        "signer"
     };
-    let share_anchor_hash_hex = anchor_to_arweave.then(|| {
-        share_anchor_hash_hex(
-            doc_id,
-            &hash_hex,
-            &sender,
-            &sender_chain,
-            recipient_wallet.as_deref(),
-            recipient_chain.as_deref(),
-            recipient_name.as_deref(),
-            requested_email.as_deref(),
-            requested_phone.as_deref(),
-            body.note.as_deref(),
-            envelope_id,
-            expires_at,
-            one_time_use,
-            download_allowed,
-            allow_guest_sign,
-        )
-    });
-
-    sqlx::query(
-        r#"insert into document_shares
-        (doc_id, sender_wallet, recipient_wallet, recipient_chain, recipient_name, envelope_id, note, recipient_email, recipient_phone, access_token_hash, expires_at, one_time_use, download_allowed, allow_guest_sign, status, delivery_json, share_anchor_hash_hex, share_arweave_tx, share_anchored_at)
-        values ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11,$12,$13,$14,$15,$16,$17,null,null)"#,
-    )
-    .bind(doc_id)
-    .bind(&sender)
-    .bind(&recipient_wallet)
-    .bind(&recipient_chain)
-    .bind(&recipient_name)
-    .bind(envelope_id)
-    .bind(&body.note)
-    .bind(&requested_email)
-    .bind(&requested_phone)
-    .bind(&access_token_hash)
-    .bind(expires_at)
-    .bind(one_time_use)
-    .bind(download_allowed)
-    .bind(allow_guest_sign)
-    .bind(initial_status)
-    .bind(json!([]))
-    .bind(&share_anchor_hash_hex)
-    .execute(&st.db)
-    .await
-    .map_err(|e| AppError::Internal(e.to_string()))?;
 
-    let (mut deliveries, delivery_errors) = dispatch_share_deliveries(
-        requested_email.as_deref(),
-        requested_phone.as_deref(),
-        label.as_deref(),
-        doc_id,
-        &hash_hex,
-        envelope_id,
-        recipient_name.as_deref(),
-        &signing_url,
-    )
-    .await;
+    Ok(NewWorkflowSigner {
+        step,
+        role: trimmed(&body.role).unwrap_or_else(|| default_role.to_string()),
+        required: body.required.unwrap_or(true),
+        name: trimmed(&body.name),
+        wallet: wallet
+            .as_deref()
+            .zip(chain)
+            .map(|(wallet, chain)| normalize_wallet_for_chain(wallet, chain)),
+        chain: chain.map(ToOwned::to_owned),
+        email,
+        phone,
+        agent_id: body.agent_id,
+    })
+}
 
-    if let Some(wallet) = recipient_wallet.clone() {
-        deliveries.insert(
-            0,
-            DeliveryOutcome {
-                channel: "wallet",
-                provider: "tidbit",
-                recipient: wallet,
-                external_id: Some(envelope_id.to_string()),
-                status: "available_in_inbox",
-            },
-        );
+/// Claim a signer and notify them. If anything after the claim fails the
+/// signer goes back to waiting, so a later advance retries them.
+async fn notify_workflow_signer(
+    st: &AppState,
+    workflow: &SigningWorkflow,
+    signer: &WorkflowSigner,
+) -> Result<(), AppError> {
+    if !claim_signer_notification(&st.db, signer.id).await? {
+        return Ok(());
     }
+    let result = send_workflow_signer_notification(st, workflow, signer).await;
+    if result.is_err() {
+        release_signer_notification(&st.db, signer.id).await?;
+    }
+    result
+}
 
-    let final_status = derive_share_status(
-        has_wallet_route,
-        has_provider_request,
-        &deliveries,
-        &delivery_errors,
-    );
-
-    sqlx::query(
-        "update document_shares set delivery_json = $2, status = $3 where access_token_hash = $1",
-    )
-    .bind(&access_token_hash)
-    .bind(serde_json::to_value(&deliveries).map_err(|e| AppError::Internal(e.to_string()))?)
-    .bind(final_status)
-    .execute(&st.db)
-    .await
-    .map_err(|e| AppError::Internal(e.to_string()))?;
-
-    let mut share_arweave_tx: Option<String> = None;
-    let mut share_anchor_error: Option<String> = None;
-    if let Some(anchor_hash_hex) = share_anchor_hash_hex.clone() {
-        let client = crate::arweave::ArweaveClient::from_env();
-        let payload = crate::arweave::ArweaveAnchorPayload {
-            kind: "share_event_hash",
-            hash_hex: &anchor_hash_hex,
-            label: label.as_deref(),
-        };
-        match client.anchor_hash(&payload).await {
-            Ok(tx_id) => {
-                share_arweave_tx = Some(tx_id.clone());
-                sqlx::query(
-                    "update document_shares set share_arweave_tx = $2, share_anchored_at = now() where access_token_hash = $1",
-                )
-                .bind(&access_token_hash)
-                .bind(&tx_id)
-                .execute(&st.db)
-                .await
-                .map_err(|e| AppError::Internal(e.to_string()))?;
+/// Open a share envelope for a wallet or email/phone signer and send the
+/// invite. Agent signers have nothing to deliver; they pick the document up
+/// through the agent API once notified.
+async fn send_workflow_signer_notification(
+    st: &AppState,
+    workflow: &SigningWorkflow,
+    signer: &WorkflowSigner,
+) -> Result<(), AppError> {
+    let signer_json = json!({
+        "signer_id": signer.id,
+        "role": signer.role,
+        "step": signer.step,
+        "required": signer.required,
+        "recipient_wallet": signer.wallet,
+        "recipient_chain": signer.chain,
+        "recipient_email": signer.email,
+        "recipient_phone": signer.phone,
+        "agent_id": signer.agent_id
+    });
 
-                insert_document_event(
-                    &st.db,
-                    doc_id,
-                    &sender,
-                    "SHARE_ANCHORED",
-                    custody_payload(
-                        json!({
-                            "envelope_id": envelope_id,
-                            "share_anchor_hash_hex": anchor_hash_hex,
-                            "share_arweave_tx": tx_id
-                        }),
-                        &session,
-                        &headers,
-                    ),
-                )
-                .await?;
-                record_growth_event(
-                    &st.db,
-                    "SHARE_ANCHORED",
-                    "wallet",
-                    Some(&sender),
-                    Some(&session.chain),
-                    Some(&session.session_id),
-                    Some(doc_id),
-                    Some(envelope_id),
-                    None,
-                    json!({
-                        "recipient_chain": recipient_chain.clone(),
-                        "share_anchor_hash_hex": anchor_hash_hex,
-                        "share_arweave_tx": tx_id
-                    }),
-                )
-                .await;
-            }
-            Err(err) => {
-                share_anchor_error = Some(err.to_string());
-                insert_document_event(
-                    &st.db,
-                    doc_id,
-                    &sender,
-                    "SHARE_ANCHOR_FAILED",
-                    custody_payload(
-                        json!({
-                            "envelope_id": envelope_id,
-                            "share_anchor_hash_hex": anchor_hash_hex,
-                            "error": share_anchor_error
-                        }),
-                        &session,
-                        &headers,
-                    ),
-                )
-                .await?;
-            }
-        }
+    if signer.agent_id.is_some() {
+        insert_document_event(
+            &st.db,
+            workflow.doc_id,
+            &workflow.owner_wallet,
+            "WORKFLOW_SIGNER_NOTIFIED",
+            workflow_custody_payload(signer_json, workflow.id),
+        )
+        .await?;
+        return Ok(());
     }
 
+    let access = load_document_access_record(
+        &st.db,
+        workflow.doc_id,
+        &workflow.owner_wallet,
+        &workflow.owner_chain,
+    )
+    .await?;
+    let expires_at = chrono::Utc::now() + chrono::Duration::hours(workflow.expires_in_hours);
+    let issued = issue_share_envelope(
+        st,
+        &ShareEnvelopeRequest {
+            doc: &access,
+            envelope_id: None,
+            recipient_wallet: signer.wallet.as_deref(),
+            recipient_chain: signer.chain.as_deref(),
+            recipient_name: signer.name.as_deref(),
+            recipient_email: signer.email.as_deref(),
+            recipient_phone: signer.phone.as_deref(),
+            note: workflow.note.as_deref(),
+            expires_at,
+            one_time_use: true,
+            download_allowed: true,
+            allow_guest_sign: None,
+        },
+    )
+    .await?;
+    let envelope_id = issued.envelope_id;
+    let final_status = issued.status;
+    set_signer_envelope(&st.db, signer.id, envelope_id).await?;
+    schedule_share_jobs(
+        &st.db,
+        issued.share_id,
+        issued.has_provider_request,
+        expires_at,
+        default_share_reminder_hours(),
+        None,
+    )
+    .await?;
+
+    let mut share_json = signer_json.clone();
+    share_json["envelope_id"] = json!(envelope_id);
+    share_json["note"] = json!(workflow.note);
+    share_json["access_token_hash"] = json!(issued.access_token_hash);
+    share_json["expires_at"] = json!(expires_at);
+    share_json["one_time_use"] = json!(true);
     insert_document_event(
         &st.db,
-        doc_id,
-        &sender,
+        workflow.doc_id,
+        &workflow.owner_wallet,
         "SHARE",
-        custody_payload(
-            json!({
-                "recipient_wallet": recipient_wallet,
-                "recipient_chain": recipient_chain,
-                "note": body.note,
-                "envelope_id": envelope_id,
-                "signature": body.signature,
-                "recipient_name": recipient_name,
-                "recipient_email": requested_email,
-                "recipient_phone": requested_phone,
-                "access_token_hash": access_token_hash,
-                "expires_at": expires_at,
-                "one_time_use": one_time_use,
-                "download_allowed": download_allowed,
-                "allow_guest_sign": allow_guest_sign,
-                "share_anchor_hash_hex": share_anchor_hash_hex,
-                "share_arweave_tx": share_arweave_tx,
-                "share_anchor_error": share_anchor_error,
-                "anchor_to_arweave": anchor_to_arweave
-            }),
-            &session,
-            &headers,
+        workflow_custody_payload(share_json, workflow.id),
+    )
+    .await?;
+    record_envelope_recipient_outcome(
+        &st.db,
+        &issued,
+        &workflow.owner_wallet,
+        signer.wallet.as_deref(),
+        signer.chain.as_deref(),
+        |payload| workflow_custody_payload(payload, workflow.id),
+    )
+    .await?;
+    record_delivery_outcomes(
+        &st.db,
+        workflow.doc_id,
+        &workflow.owner_wallet,
+        &issued.deliveries,
+        &issued.delivery_errors,
+        json!({
+            "envelope_id": envelope_id,
+            "recipient_chain": signer.chain,
+            "sender_chain": workflow.owner_chain
+        }),
+        |payload| workflow_custody_payload(payload, workflow.id),
+    )
+    .await?;
+    insert_document_event(
+        &st.db,
+        workflow.doc_id,
+        &workflow.owner_wallet,
+        "WORKFLOW_SIGNER_NOTIFIED",
+        workflow_custody_payload(
+            {
+                let mut payload = signer_json;
+                payload["envelope_id"] = json!(envelope_id);
+                payload["share_status"] = json!(final_status);
+                payload
+            },
+            workflow.id,
         ),
     )
     .await?;
 
-    for outcome in &deliveries {
+    Ok(())
+}
+
+/// Notify whoever's step is now open, or close the workflow out with a
+/// `FULLY_EXECUTED` event once every required signer has signed.
+async fn advance_signing_workflow(st: &AppState, workflow_id: uuid::Uuid) -> Result<(), AppError> {
+    let Some(workflow) = load_signing_workflow(&st.db, workflow_id).await? else {
+        return Ok(());
+    };
+    if workflow.status != WORKFLOW_ACTIVE {
+        return Ok(());
+    }
+
+    let next = signing_workflow::advance(&workflow.signers);
+    for idx in next.notify {
+        notify_workflow_signer(st, &workflow, &workflow.signers[idx]).await?;
+    }
+    if next.fully_executed && complete_signing_workflow(&st.db, workflow.id).await? {
+        let signatures: Vec<serde_json::Value> = workflow
+            .signers
+            .iter()
+            .filter(|signer| signer.status == SIGNER_SIGNED)
+            .map(|signer| {
+                json!({
+                    "signer_id": signer.id,
+                    "role": signer.role,
+                    "step": signer.step,
+                    "wallet": signer.wallet,
+                    "email": signer.email,
+                    "agent_id": signer.agent_id,
+                    "envelope_id": signer.envelope_id,
+                    "sign_event_id": signer.sign_event_id,
+                    "signed_at": signer.signed_at
+                })
+            })
+            .collect();
         insert_document_event(
             &st.db,
-            doc_id,
-            &sender,
-            "DELIVERY_DISPATCHED",
-            custody_payload(
+            workflow.doc_id,
+            &workflow.owner_wallet,
+            "FULLY_EXECUTED",
+            workflow_custody_payload(
                 json!({
-                    "envelope_id": envelope_id,
-                    "channel": outcome.channel,
-                    "provider": outcome.provider,
-                    "recipient": outcome.recipient,
-                    "external_id": outcome.external_id,
-                    "status": outcome.status,
-                    "recipient_chain": recipient_chain,
-                    "sender_chain": sender_chain
+                    "routing": workflow.routing,
+                    "signatures": signatures
                 }),
-                &session,
-                &headers,
+                workflow.id,
             ),
         )
         .await?;
     }
+    Ok(())
+}
+
+/// Advance a workflow from a route whose own writes are already committed.
+/// Failing the request there would only invite a duplicate retry, so the
+/// error is logged and the advance is queued as a job instead.
+async fn advance_signing_workflow_or_queue(
+    st: &AppState,
+    workflow_id: uuid::Uuid,
+    cause: uuid::Uuid,
+) {
+    let Err(err) = advance_signing_workflow(st, workflow_id).await else {
+        return;
+    };
+    eprintln!("workflow: advancing {workflow_id} failed, queued for retry: {err}");
+    if let Err(err) = schedule_job(
+        &st.db,
+        JOB_WORKFLOW_ADVANCE,
+        chrono::Utc::now(),
+        json!({ "workflow_id": workflow_id }),
+        Some(&format!("{JOB_WORKFLOW_ADVANCE}:{workflow_id}:{cause}")),
+    )
+    .await
+    {
+        eprintln!("workflow: queueing advance of {workflow_id} failed: {err}");
+    }
+}
 
-    for error in &delivery_errors {
+/// Called by every signing route after its `SIGN`-type event is written.
+async fn record_workflow_signature(
+    st: &AppState,
+    doc_id: uuid::Uuid,
+    signer: WorkflowSignerMatch<'_>,
+    sign_event_id: uuid::Uuid,
+) -> Result<(), AppError> {
+    let completed = mark_workflow_signers_signed(&st.db, doc_id, signer, sign_event_id).await?;
+    for signer in &completed {
         insert_document_event(
             &st.db,
             doc_id,
-            &sender,
-            "DELIVERY_FAILED",
-            custody_payload(
+            &signer.owner_wallet,
+            "WORKFLOW_SIGNER_COMPLETED",
+            workflow_custody_payload(
                 json!({
-                    "envelope_id": envelope_id,
-                    "error": error
+                    "signer_id": signer.signer_id,
+                    "role": signer.role,
+                    "sign_event_id": sign_event_id
                 }),
-                &session,
-                &headers,
+                signer.workflow_id,
             ),
         )
         .await?;
     }
-    record_growth_event(
-        &st.db,
-        "SHARE_CREATED",
-        "wallet",
-        Some(&sender),
-        Some(&session.chain),
-        Some(&session.session_id),
-        Some(doc_id),
-        Some(envelope_id),
-        None,
-        json!({
-            "recipient_chain": recipient_chain.clone(),
-            "has_wallet_route": has_wallet_route,
-            "has_email_route": requested_email.is_some(),
-            "has_phone_route": requested_phone.is_some(),
-            "status": final_status,
-            "one_time_use": one_time_use,
-            "download_allowed": download_allowed,
-            "allow_guest_sign": allow_guest_sign,
-            "anchor_to_arweave": anchor_to_arweave,
-            "share_arweave_tx": share_arweave_tx.clone()
-        }),
-    )
-    .await;
-
-    Ok(Json(json!({
-        "ok": true,
-        "envelope_id": envelope_id,
-        "doc_id": doc_id,
-        "label": label,
-        "hash_hex": hash_hex,
-        "recipient_wallet": recipient_wallet,
-        "recipient_chain": recipient_chain,
-        "recipient_name": recipient_name,
-        "recipient_email": requested_email,
-        "recipient_phone": requested_phone,
-        "signing_url": signing_url,
-        "status": final_status,
-        "expires_at": expires_at,
-        "one_time_use": one_time_use,
-        "download_allowed": download_allowed,
-        "allow_guest_sign": allow_guest_sign,
-        "anchor_to_arweave": anchor_to_arweave,
-        "share_anchor_hash_hex": share_anchor_hash_hex,
-        "share_arweave_tx": share_arweave_tx,
-        "share_anchor_error": share_anchor_error,
-        "delivery": deliveries,
-        "delivery_errors": delivery_errors
-    })))
-}
-
-async fn revoke_share_handler(
-    State(st): State<AppState>,
-    headers: HeaderMap,
-    Path((doc_id, envelope_id)): Path<(uuid::Uuid, uuid::Uuid)>,
-) -> Result<Json<serde_json::Value>, AppError> {
-    let session = require_session_from_headers(&st, &headers).await?;
-    let sender = session.wallet.clone();
-
-    let updated = sqlx::query(
-        r#"
-        update document_shares
-        set
-            revoked_at = now(),
-            revoked_reason = 'sender_revoked',
-            status = 'revoked'
-        where doc_id = $1
-          and envelope_id = $2
-          and sender_wallet = $3
-          and revoked_at is null
-        returning recipient_wallet, recipient_chain, recipient_email, recipient_phone
-        "#,
-    )
-    .bind(doc_id)
-    .bind(envelope_id)
-    .bind(&sender)
-    .fetch_optional(&st.db)
-    .await
-    .map_err(|e| AppError::Internal(e.to_string()))?;
-
-    let Some(updated) = updated else {
-        return Err(AppError::NotFound(
-            "Share not found or already revoked".into(),
-        ));
-    };
-
-    insert_document_event(
-        &st.db,
-        doc_id,
-        &sender,
-        "SHARE_REVOKED",
-        custody_payload(
-            json!({
-                "envelope_id": envelope_id,
-                "recipient_wallet": updated.get::<Option<String>, _>("recipient_wallet"),
-                "recipient_chain": updated.get::<Option<String>, _>("recipient_chain"),
-                "recipient_email": updated.get::<Option<String>, _>("recipient_email"),
-                "recipient_phone": updated.get::<Option<String>, _>("recipient_phone"),
-                "reason": "sender_revoked"
-            }),
-            &session,
-            &headers,
-        ),
-    )
-    .await?;
-    record_growth_event(
-        &st.db,
-        "SHARE_REVOKED",
-        "wallet",
-        Some(&sender),
-        Some(&session.chain),
-        Some(&session.session_id),
-        Some(doc_id),
-        Some(envelope_id),
-        None,
-        json!({
-            "reason": "sender_revoked"
-        }),
-    )
-    .await;
 
-    Ok(Json(json!({
-        "ok": true,
-        "doc_id": doc_id,
-        "envelope_id": envelope_id,
-        "status": "revoked"
-    })))
+    let mut workflow_ids: Vec<uuid::Uuid> =
+        completed.iter().map(|signer| signer.workflow_id).collect();
+    workflow_ids.sort();
+    workflow_ids.dedup();
+    for workflow_id in workflow_ids {
+        advance_signing_workflow_or_queue(st, workflow_id, sign_event_id).await;
+    }
+    Ok(())
 }
 
-async fn list_inbox_handler(
+async fn create_signing_workflow_handler(
     State(st): State<AppState>,
     headers: HeaderMap,
+    Path(doc_id): Path<uuid::Uuid>,
+    Json(body): Json<SigningWorkflowRequest>,
 ) -> Result<Json<serde_json::Value>, AppError> {
     let session = require_session_from_headers(&st, &headers).await?;
-    let wallet = normalize_wallet_for_chain(
-        &session.wallet,
-        canonical_chain(&session.chain).unwrap_or_else(|| infer_wallet_chain(&session.wallet)),
-    );
     let chain =
         canonical_chain(&session.chain).unwrap_or_else(|| infer_wallet_chain(&session.wallet));
+    let owner = normalize_wallet_for_chain(&session.wallet, chain);
+    let access = load_document_access_record(&st.db, doc_id, &owner, chain).await?;
+    if !access.owner_wallet.eq_ignore_ascii_case(&owner) {
+        return Err(AppError::Forbidden(
+            "You can only create signing workflows for documents you own".into(),
+        ));
+    }
+    ensure_not_quarantined(&access)?;
 
-    let rows = sqlx::query(
-        r#"
-        select
-            s.doc_id,
-            s.sender_wallet,
-            s.recipient_wallet,
-            s.recipient_chain,
-            s.envelope_id,
-            s.note,
-            s.status,
-            d.label,
-            d.hash_hex,
-            d.version
-        from document_shares s
-        join documents d on d.id = s.doc_id
-        where s.recipient_wallet is not null
-          and (
-            (coalesce(s.recipient_chain, '') = 'evm' and $2 = 'evm' and lower(s.recipient_wallet) = lower($1))
-            or
-            (coalesce(s.recipient_chain, '') = 'sol' and $2 = 'sol' and s.recipient_wallet = $1)
-            or
-            (s.recipient_chain is null and (
-              ($1 like '0x%' and lower(s.recipient_wallet) = lower($1))
-              or
-              ($1 not like '0x%' and s.recipient_wallet = $1)
-            ))
-          )
-          and s.status in ('wallet_shared', 'wallet_shared_with_delivery_issues', 'sent', 'created', 'delivery_issue', 'opened')
-          and d.is_deleted = false
-        order by d.created_at desc
-        "#,
+    if body.signers.is_empty() {
+        return Err(AppError::BadRequest("Add at least one signer".into()));
+    }
+    let routing = body
+        .routing
+        .as_deref()
+        .map(|value| value.trim().to_ascii_lowercase())
+        .unwrap_or_else(|| ROUTING_SEQUENTIAL.to_string());
+    let steps = resolve_steps(
+        &routing,
+        &body
+            .signers
+            .iter()
+            .map(|signer| signer.step)
+            .collect::<Vec<_>>(),
+    )?;
+    let mut signers = Vec::with_capacity(body.signers.len());
+    for (signer, step) in body.signers.iter().zip(steps) {
+        signers.push(resolve_workflow_signer(&st.db, &access.owner_wallet, step, signer).await?);
+    }
+    if !signers.iter().any(|signer| signer.required) {
+        return Err(AppError::BadRequest(
+            "At least one signer must be required".into(),
+        ));
+    }
+
+    let note = body
+        .note
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty());
+    let workflow_id = insert_signing_workflow(
+        &st.db,
+        &NewSigningWorkflow {
+            doc_id,
+            owner_wallet: &access.owner_wallet,
+            owner_chain: chain,
+            routing: &routing,
+            note,
+            expires_in_hours: clamp_share_expiry_hours(body.expires_in_hours),
+            signers: &signers,
+        },
     )
-    .bind(&wallet)
-    .bind(chain)
-    .fetch_all(&st.db)
-    .await
-    .map_err(|e| AppError::Internal(e.to_string()))?;
+    .await?;
 
-    Ok(Json(json!({
-        "items": rows
-            .into_iter()
-            .map(|r| json!({
-                "doc_id": r.get::<uuid::Uuid,_>("doc_id"),
-                "sender_wallet": r.get::<String,_>("sender_wallet"),
-                "recipient_wallet": r.get::<Option<String>,_>("recipient_wallet"),
-                "recipient_chain": r.get::<Option<String>,_>("recipient_chain"),
-                "envelope_id": r.get::<uuid::Uuid,_>("envelope_id"),
-                "note": r.get::<Option<String>,_>("note"),
-                "status": r.get::<String,_>("status"),
-                "label": r.get::<Option<String>,_>("label"),
-                "hash_hex": r.get::<String,_>("hash_hex"),
-                "version": r.get::<i32,_>("version")
-            }))
-            .collect::<Vec<_>>()
-    })))
+    insert_document_event(
+        &st.db,
+        doc_id,
+        &owner,
+        "WORKFLOW_CREATED",
+        custody_payload(
+            json!({
+                "workflow_id": workflow_id,
+                "routing": routing,
+                "note": note,
+                "signers": signers
+                    .iter()
+                    .map(|signer| json!({
+                        "step": signer.step,
+                        "role": signer.role,
+                        "required": signer.required,
+                        "wallet": signer.wallet,
+                        "chain": signer.chain,
+                        "email": signer.email,
+                        "phone": signer.phone,
+                        "agent_id": signer.agent_id
+                    }))
+                    .collect::<Vec<_>>()
+            }),
+            &session,
+            &headers,
+        ),
+    )
+    .await?;
+    advance_signing_workflow_or_queue(&st, workflow_id, workflow_id).await;
+
+    let workflow = load_owned_signing_workflow(&st, &session, workflow_id).await?;
+    Ok(Json(json!({ "ok": true, "workflow": workflow })))
 }
 
-async fn inbox_action_handler(
+async fn list_signing_workflows_handler(
     State(st): State<AppState>,
     headers: HeaderMap,
-    Path(envelope_id): Path<uuid::Uuid>,
-    Json(body): Json<InboxActionRequest>,
+    Path(doc_id): Path<uuid::Uuid>,
 ) -> Result<Json<serde_json::Value>, AppError> {
     let session = require_session_from_headers(&st, &headers).await?;
-    let wallet = normalize_wallet_for_chain(
-        &session.wallet,
-        canonical_chain(&session.chain).unwrap_or_else(|| infer_wallet_chain(&session.wallet)),
-    );
     let chain =
         canonical_chain(&session.chain).unwrap_or_else(|| infer_wallet_chain(&session.wallet));
-    let action = body.action.trim().to_ascii_lowercase();
+    let owner = normalize_wallet_for_chain(&session.wallet, chain);
+    let access = load_document_access_record(&st.db, doc_id, &owner, chain).await?;
+    if !access.owner_wallet.eq_ignore_ascii_case(&owner) {
+        return Err(AppError::Forbidden(
+            "Only the document owner can view its signing workflows".into(),
+        ));
+    }
 
-    let row = sqlx::query(
-        r#"
-        select s.doc_id, s.status, s.recipient_wallet, s.recipient_chain
-        from document_shares s
-        join documents d on d.id = s.doc_id
-        where s.envelope_id = $1
-          and d.is_deleted = false
-          and s.recipient_wallet is not null
-          and (
-            (coalesce(s.recipient_chain, '') = 'evm' and $3 = 'evm' and lower(s.recipient_wallet) = lower($2))
-            or
-            (coalesce(s.recipient_chain, '') = 'sol' and $3 = 'sol' and s.recipient_wallet = $2)
-            or
-            (s.recipient_chain is null and (
-              ($2 like '0x%' and lower(s.recipient_wallet) = lower($2))
-              or
-              ($2 not like '0x%' and s.recipient_wallet = $2)
-            ))
-          )
-        "#,
-    )
-    .bind(envelope_id)
-    .bind(&wallet)
-    .bind(chain)
-    .fetch_optional(&st.db)
-    .await
-    .map_err(|e| AppError::Internal(e.to_string()))?;
+    let workflows = list_signing_workflows(&st.db, doc_id).await?;
+    Ok(Json(json!({ "workflows": workflows })))
+}
 
-    let Some(row) = row else {
-        return Err(AppError::NotFound("Inbox item not found".into()));
-    };
+async fn signing_workflow_handler(
+    State(st): State<AppState>,
+    headers: HeaderMap,
+    Path(workflow_id): Path<uuid::Uuid>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let session = require_session_from_headers(&st, &headers).await?;
+    let workflow = load_owned_signing_workflow(&st, &session, workflow_id).await?;
+    Ok(Json(json!({ "workflow": workflow })))
+}
 
-    let doc_id: uuid::Uuid = row.get("doc_id");
-    let next_status = match action.as_str() {
-        "review" | "open" | "download" | "sign" => "accepted",
-        "delete" | "dismiss" => "dismissed",
-        _ => return Err(AppError::BadRequest("Unsupported inbox action".into())),
-    };
-    let event_type = match action.as_str() {
-        "review" | "open" => "INBOX_REVIEWED",
-        "download" => "INBOX_DOWNLOADED",
-        "sign" => "INBOX_SIGNED",
-        "delete" | "dismiss" => "INBOX_DISMISSED",
-        _ => unreachable!(),
+async fn cancel_signing_workflow_handler(
+    State(st): State<AppState>,
+    headers: HeaderMap,
+    Path(workflow_id): Path<uuid::Uuid>,
+    Json(body): Json<WorkflowCancelRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let session = require_session_from_headers(&st, &headers).await?;
+    let workflow = load_owned_signing_workflow(&st, &session, workflow_id).await?;
+    let reason = body
+        .reason
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .unwrap_or("owner_cancelled")
+        .to_string();
+
+    let Some(outstanding) = cancel_signing_workflow(&st.db, workflow_id, &reason).await? else {
+        return Err(AppError::BadRequest(format!(
+            "Signing workflow is already {}",
+            workflow.status
+        )));
     };
 
+    // Pending invites stop working; envelopes that were already signed stay.
     sqlx::query(
-        "update document_shares set status = $2, viewed_at = coalesce(viewed_at, now()) where envelope_id = $1",
+        r#"
+        update document_shares
+        set revoked_at = now(), revoked_reason = 'workflow_cancelled', status = 'revoked'
+        where doc_id = $1
+          and envelope_id = any($2)
+          and revoked_at is null
+          and status <> 'completed'
+        "#,
     )
-    .bind(envelope_id)
-    .bind(next_status)
+    .bind(workflow.doc_id)
+    .bind(&outstanding)
     .execute(&st.db)
     .await
     .map_err(|e| AppError::Internal(e.to_string()))?;
 
-    sqlx::query(
-        "insert into document_events (doc_id, actor_wallet, event_type, payload) values ($1,$2,$3,$4)",
-    )
-    .bind(doc_id)
-    .bind(&session.wallet)
-    .bind(event_type)
-    .bind(custody_payload(
-        json!({
-            "envelope_id": envelope_id,
-            "inbox_action": action,
-            "recipient_wallet": row.get::<Option<String>,_>("recipient_wallet"),
-            "recipient_chain": row.get::<Option<String>,_>("recipient_chain"),
-            "share_status": next_status
-        }),
-        &session,
-        &headers,
-    ))
-    .execute(&st.db)
-    .await
-    .map_err(|e| AppError::Internal(e.to_string()))?;
-    record_growth_event(
+    insert_document_event(
         &st.db,
-        "INBOX_ACTION",
-        "wallet",
-        Some(&session.wallet),
-        Some(&session.chain),
-        Some(&session.session_id),
-        Some(doc_id),
-        Some(envelope_id),
-        None,
-        json!({
-            "action": action.clone(),
-            "status": next_status
-        }),
+        workflow.doc_id,
+        &session.wallet,
+        "WORKFLOW_CANCELLED",
+        custody_payload(
+            json!({
+                "workflow_id": workflow_id,
+                "reason": reason,
+                "revoked_envelopes": outstanding
+            }),
+            &session,
+            &headers,
+        ),
     )
-    .await;
+    .await?;
 
-    Ok(Json(json!({
-        "ok": true,
-        "doc_id": doc_id,
-        "envelope_id": envelope_id,
-        "status": next_status,
-        "action": action
-    })))
+    let workflow = load_owned_signing_workflow(&st, &session, workflow_id).await?;
+    Ok(Json(json!({ "ok": true, "workflow": workflow })))
 }
 
-async fn public_envelope_handler(
-    State(st): State<AppState>,
-    headers: HeaderMap,
-    Path(token): Path<String>,
-) -> Result<Json<serde_json::Value>, AppError> {
-    let token_hash = access_token_hash_hex(token.trim());
-    let row = sqlx::query(
-        r#"
-        select
-            s.id as share_id,
-            s.doc_id,
-            s.sender_wallet,
-            s.recipient_wallet,
-            s.recipient_chain,
-            s.recipient_name,
-            s.recipient_email,
-            s.recipient_phone,
-            s.note,
-            s.envelope_id,
-            s.status,
-            s.viewed_at,
-            s.delivery_json,
-            s.annotation_json,
-            s.expires_at,
-            s.revoked_at,
-            s.share_anchor_hash_hex,
-            s.share_arweave_tx,
-            s.share_anchored_at,
-            s.one_time_use,
-            s.download_allowed,
-            s.allow_guest_sign,
-            s.completion_count,
-            d.label,
-            d.hash_hex,
-            d.version,
-            d.mime_type,
-            d.storage_path,
-            d.owner_wallet,
-            d.parent_id,
-            d.arweave_tx
+// ================================================================
+// SCHEDULED JOBS
+// ================================================================
+
+const JOB_SHARE_REMINDER: &str = "share_reminder";
+const JOB_SHARE_EXPIRY: &str = "share_expiry";
+const JOB_SHARE_ESCALATION: &str = "share_escalation";
+const JOB_WORKFLOW_ADVANCE: &str = "workflow_advance";
+const JOB_DOCUMENT_ANCHOR: &str = "document_anchor";
+const SCHEDULER_ACTOR: &str = "system:scheduler";
+
+fn job_poll_interval() -> Option<std::time::Duration> {
+    let secs = std::env::var("JOB_POLL_INTERVAL_SECS")
+        .ok()
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .unwrap_or(30);
+    (secs > 0).then(|| std::time::Duration::from_secs(secs))
+}
+
+fn default_share_reminder_hours() -> i64 {
+    std::env::var("SHARE_REMINDER_HOURS")
+        .ok()
+        .and_then(|value| value.trim().parse::<i64>().ok())
+        .unwrap_or(24)
+        .max(0)
+}
+
+/// When to resend an invite, or `None` if reminders are off or the slot
+/// has already passed.
+fn share_reminder_at(
+    expires_at: chrono::DateTime<chrono::Utc>,
+    hours_before: i64,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Option<chrono::DateTime<chrono::Utc>> {
+    if hours_before <= 0 {
+        return None;
+    }
+    let remind_at = expires_at - chrono::Duration::hours(hours_before);
+    (remind_at > now).then_some(remind_at)
+}
+
+/// The alternate recipient from a share request, with the delay in hours.
+fn share_escalation_target(
+    body: &ShareRequest,
+    expires_in_hours: i64,
+) -> Result<Option<(i64, serde_json::Value)>, AppError> {
+    let trimmed = |value: &Option<String>| {
+        value
+            .as_deref()
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(ToOwned::to_owned)
+    };
+    let wallet = trimmed(&body.escalate_to_wallet);
+    let email = trimmed(&body.escalate_to_email);
+    let phone = trimmed(&body.escalate_to_phone);
+    let Some(after_hours) = body.escalate_after_hours else {
+        if wallet.is_some() || email.is_some() || phone.is_some() {
+            return Err(AppError::BadRequest(
+                "escalate_after_hours is required with an escalation recipient".into(),
+            ));
+        }
+        return Ok(None);
+    };
+    if wallet.is_none() && email.is_none() && phone.is_none() {
+        return Err(AppError::BadRequest(
+            "Provide an escalation wallet, email, or phone".into(),
+        ));
+    }
+    if after_hours < 1 || after_hours >= expires_in_hours {
+        return Err(AppError::BadRequest(
+            "escalate_after_hours must be at least 1 and before the share expires".into(),
+        ));
+    }
+
+    let chain = wallet.as_deref().map(|wallet| {
+        body.escalate_to_chain
+            .as_deref()
+            .and_then(canonical_chain)
+            .unwrap_or_else(|| infer_wallet_chain(wallet))
+    });
+    Ok(Some((
+        after_hours,
+        json!({
+            "wallet": wallet
+                .as_deref()
+                .zip(chain)
+                .map(|(wallet, chain)| normalize_wallet_for_chain(wallet, chain)),
+            "chain": chain,
+            "name": trimmed(&body.escalate_to_name),
+            "email": email,
+            "phone": phone
+        }),
+    )))
+}
+
+/// Reminder, expiry and optional escalation jobs for a new share. Reminders
+/// only go to email/SMS recipients; wallet recipients see the inbox.
+async fn schedule_share_jobs(
+    db: &PgPool,
+    share_id: uuid::Uuid,
+    has_provider_request: bool,
+    expires_at: chrono::DateTime<chrono::Utc>,
+    reminder_hours: i64,
+    escalation: Option<(i64, serde_json::Value)>,
+) -> Result<(), AppError> {
+    let now = chrono::Utc::now();
+    if let Some(remind_at) =
+        share_reminder_at(expires_at, reminder_hours, now).filter(|_| has_provider_request)
+    {
+        schedule_job(
+            db,
+            JOB_SHARE_REMINDER,
+            remind_at,
+            json!({ "share_id": share_id }),
+            Some(&format!("{JOB_SHARE_REMINDER}:{share_id}")),
+        )
+        .await?;
+    }
+    schedule_job(
+        db,
+        JOB_SHARE_EXPIRY,
+        expires_at,
+        json!({ "share_id": share_id }),
+        Some(&format!("{JOB_SHARE_EXPIRY}:{share_id}")),
+    )
+    .await?;
+    if let Some((after_hours, target)) = escalation {
+        schedule_job(
+            db,
+            JOB_SHARE_ESCALATION,
+            now + chrono::Duration::hours(after_hours),
+            json!({ "share_id": share_id, "escalate_to": target }),
+            Some(&format!("{JOB_SHARE_ESCALATION}:{share_id}")),
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+/// A share nobody has acted on yet: not signed, revoked, dismissed or expired.
+struct PendingShare {
+    id: uuid::Uuid,
+    doc_id: uuid::Uuid,
+    envelope_id: uuid::Uuid,
+    sender_wallet: String,
+    recipient_wallet: Option<String>,
+    recipient_chain: Option<String>,
+    recipient_name: Option<String>,
+    recipient_email: Option<String>,
+    recipient_phone: Option<String>,
+    note: Option<String>,
+    expires_at: chrono::DateTime<chrono::Utc>,
+    one_time_use: bool,
+    download_allowed: bool,
+    allow_guest_sign: Option<bool>,
+}
+
+async fn load_pending_share(
+    db: &PgPool,
+    share_id: uuid::Uuid,
+) -> Result<Option<PendingShare>, AppError> {
+    let row = sqlx::query(
+        r#"
+        select
+            s.id, s.doc_id, s.envelope_id, s.sender_wallet, s.recipient_wallet,
+            s.recipient_chain, s.recipient_name, s.recipient_email, s.recipient_phone,
+            s.note, s.expires_at, s.one_time_use, s.download_allowed, s.allow_guest_sign
         from document_shares s
         join documents d on d.id = s.doc_id
-        where s.access_token_hash = $1
+        where s.id = $1
           and d.is_deleted = false
+          and d.quarantined_at is null
+          and s.revoked_at is null
+          and s.completion_count = 0
+          and s.status not in ('completed', 'expired', 'revoked', 'dismissed')
+          and s.expires_at > now()
+          and not exists (
+            select 1
+            from document_events e
+            where e.doc_id = s.doc_id
+              and e.event_type = 'SIGN'
+              and e.created_at >= s.created_at
+              and s.recipient_wallet is not null
+              and lower(e.actor_wallet) = lower(s.recipient_wallet)
+          )
         "#,
     )
-    .bind(&token_hash)
-    .fetch_optional(&st.db)
+    .bind(share_id)
+    .fetch_optional(db)
     .await
     .map_err(|e| AppError::Internal(e.to_string()))?;
 
-    let Some(row) = row else {
-        return Err(AppError::NotFound("Envelope not found".into()));
-    };
+    Ok(row.map(|row| PendingShare {
+        id: row.get("id"),
+        doc_id: row.get("doc_id"),
+        envelope_id: row.get("envelope_id"),
+        sender_wallet: row.get("sender_wallet"),
+        recipient_wallet: row.get("recipient_wallet"),
+        recipient_chain: row.get("recipient_chain"),
+        recipient_name: row.get("recipient_name"),
+        recipient_email: row.get("recipient_email"),
+        recipient_phone: row.get("recipient_phone"),
+        note: row.get("note"),
+        expires_at: row.get("expires_at"),
+        one_time_use: row.get("one_time_use"),
+        download_allowed: row.get("download_allowed"),
+        allow_guest_sign: row.get("allow_guest_sign"),
+    }))
+}
 
-    let doc_id: uuid::Uuid = row.get("doc_id");
-    let envelope_id: uuid::Uuid = row.get("envelope_id");
-    let status: String = row.get("status");
-    let expires_at: Option<chrono::DateTime<chrono::Utc>> = row.get("expires_at");
-    let revoked_at: Option<chrono::DateTime<chrono::Utc>> = row.get("revoked_at");
-    let one_time_use: bool = row.get("one_time_use");
-    let download_allowed: bool = row.get("download_allowed");
-    let completion_count: i32 = row.get("completion_count");
-    let viewed_at: Option<chrono::DateTime<chrono::Utc>> = row.get("viewed_at");
-    if revoked_at.is_some() {
-        return Err(AppError::Forbidden(
-            "This share link has been revoked".into(),
-        ));
-    }
-    if expires_at
-        .map(|value| value <= chrono::Utc::now())
-        .unwrap_or(false)
-    {
-        return Err(AppError::Forbidden("This share link has expired".into()));
-    }
-    if one_time_use && completion_count > 0 {
-        return Err(AppError::Forbidden(
-            "This one-time share link has already been used".into(),
-        ));
-    }
-    let owner_wallet: String = row.get("owner_wallet");
-    let policy = load_document_policy(&st.db, doc_id, &owner_wallet).await?;
-    let allow_guest_sign = row
-        .get::<Option<bool>, _>("allow_guest_sign")
-        .unwrap_or_else(|| {
-            policy
-                .get("allow_guest_sign")
-                .and_then(|value| value.as_bool())
-                .unwrap_or(false)
-        });
-    let allowed_signature_types = {
-        let mut modes = Vec::new();
-        if allow_guest_sign && public_guest_attestation_enabled() {
-            modes.push("guest_attestation");
-        }
-        modes.push("evm_personal_sign");
-        modes.push("sol_ed25519");
-        modes.push("pq_mldsa65");
-        modes
+fn job_share_id(job: &ScheduledJob) -> Result<uuid::Uuid, AppError> {
+    job.payload
+        .get("share_id")
+        .and_then(|value| value.as_str())
+        .and_then(|value| uuid::Uuid::parse_str(value).ok())
+        .ok_or_else(|| AppError::BadRequest(format!("{} job has no share_id", job.kind)))
+}
+
+/// Resend the email/SMS invite. Only the token hash is stored, so the
+/// reminder carries a fresh link and the previous one stops working once
+/// at least one delivery has gone out. If none did, the old link is kept
+/// and the job fails so the scheduler retries it.
+async fn send_share_reminder(st: &AppState, job: &ScheduledJob) -> Result<(), AppError> {
+    let Some(share) = load_pending_share(&st.db, job_share_id(job)?).await? else {
+        return Ok(());
     };
-    if viewed_at.is_none() {
-        sqlx::query("update document_shares set viewed_at = now(), open_count = open_count + 1, status = 'opened' where access_token_hash = $1 and viewed_at is null")
-            .bind(&token_hash)
-            .execute(&st.db)
-            .await
-            .map_err(|e| AppError::Internal(e.to_string()))?;
+    if share.recipient_email.is_none() && share.recipient_phone.is_none() {
+        return Ok(());
+    }
+    let doc = sqlx::query("select label, hash_hex from documents where id = $1")
+        .bind(share.doc_id)
+        .fetch_one(&st.db)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    let label: Option<String> = doc.get("label");
+    let hash_hex: String = doc.get("hash_hex");
 
-        insert_document_event(
-            &st.db,
-            doc_id,
-            &format!("guest-envelope:{envelope_id}"),
-            "ENVELOPE_OPENED",
-            public_custody_payload_for_envelope(
+    let access_token = uuid::Uuid::new_v4().simple().to_string();
+    let access_token_hash = access_token_hash_hex(&access_token);
+    let (deliveries, delivery_errors) = dispatch_share_deliveries(
+        share.recipient_email.as_deref(),
+        share.recipient_phone.as_deref(),
+        label.as_deref(),
+        share.doc_id,
+        &hash_hex,
+        share.envelope_id,
+        share.recipient_name.as_deref(),
+        &envelope_signing_url(&access_token),
+    )
+    .await;
+    if deliveries.is_empty() {
+        if job.attempts >= MAX_JOB_ATTEMPTS {
+            record_delivery_outcomes(
+                &st.db,
+                share.doc_id,
+                SCHEDULER_ACTOR,
+                &deliveries,
+                &delivery_errors,
                 json!({
-                    "envelope_id": envelope_id,
-                    "recipient_wallet": row.get::<Option<String>,_>("recipient_wallet"),
-                    "recipient_chain": row.get::<Option<String>,_>("recipient_chain"),
-                    "recipient_name": row.get::<Option<String>,_>("recipient_name"),
-                    "recipient_email": row.get::<Option<String>,_>("recipient_email"),
-                    "recipient_phone": row.get::<Option<String>,_>("recipient_phone"),
-                    "status": "opened"
+                    "envelope_id": share.envelope_id,
+                    "reminder": true
                 }),
-                envelope_id,
-                &headers,
-            ),
-        )
-        .await?;
-        record_growth_event(
-            &st.db,
-            "PUBLIC_ENVELOPE_OPENED",
-            "public",
-            None,
-            None,
-            None,
-            Some(doc_id),
-            Some(envelope_id),
-            None,
-            json!({
-                "recipient_chain": row.get::<Option<String>,_>("recipient_chain"),
-                "status": "opened"
-            }),
-        )
-        .await;
+                |payload| payload,
+            )
+            .await?;
+        }
+        return Err(AppError::Internal(format!(
+            "share reminder was not delivered: {}",
+            delivery_errors.join("; ")
+        )));
     }
+    sqlx::query(
+        r#"
+        update document_shares
+        set access_token_hash = $2,
+            delivery_json = coalesce(delivery_json, '[]'::jsonb) || $3
+        where id = $1
+        "#,
+    )
+    .bind(share.id)
+    .bind(&access_token_hash)
+    .bind(serde_json::to_value(&deliveries).map_err(|e| AppError::Internal(e.to_string()))?)
+    .execute(&st.db)
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?;
 
-    Ok(Json(json!({
-        "doc_id": doc_id,
-        "envelope_id": envelope_id,
-        "sender_wallet": row.get::<String,_>("sender_wallet"),
-        "recipient_wallet": row.get::<Option<String>,_>("recipient_wallet"),
-        "recipient_chain": row.get::<Option<String>,_>("recipient_chain"),
-        "recipient_name": row.get::<Option<String>,_>("recipient_name"),
-        "recipient_email": row.get::<Option<String>,_>("recipient_email"),
-        "recipient_phone": row.get::<Option<String>,_>("recipient_phone"),
-        "note": row.get::<Option<String>,_>("note"),
-        "delivery": row.get::<serde_json::Value,_>("delivery_json"),
-        "annotation_json": row.get::<serde_json::Value,_>("annotation_json"),
-        "status": if viewed_at.is_none() { "opened" } else { status.as_str() },
-        "expires_at": expires_at,
-        "one_time_use": one_time_use,
-        "download_allowed": download_allowed,
-        "allow_guest_sign": allow_guest_sign,
-        "share_anchor_hash_hex": row.get::<Option<String>,_>("share_anchor_hash_hex"),
-        "share_arweave_tx": row.get::<Option<String>,_>("share_arweave_tx"),
-        "share_anchored_at": row.get::<Option<chrono::DateTime<chrono::Utc>>,_>("share_anchored_at"),
-        "allowed_signature_types": allowed_signature_types,
-        "label": row.get::<Option<String>,_>("label"),
-        "hash_hex": row.get::<String,_>("hash_hex"),
-        "version": row.get::<i32,_>("version"),
-        "mime_type": row.get::<String,_>("mime_type"),
-        "parent_id": row.get::<Option<uuid::Uuid>,_>("parent_id"),
-        "arweave_tx": row.get::<Option<String>,_>("arweave_tx"),
-        "url": public_blob_url(token.trim()),
-        "blob_url": public_blob_url(token.trim())
-    })))
+    insert_document_event(
+        &st.db,
+        share.doc_id,
+        SCHEDULER_ACTOR,
+        "SHARE_REMINDER_SENT",
+        json!({
+            "envelope_id": share.envelope_id,
+            "expires_at": share.expires_at,
+            "access_token_hash": access_token_hash,
+            "delivered": deliveries.len(),
+            "failed": delivery_errors.len()
+        }),
+    )
+    .await?;
+    record_delivery_outcomes(
+        &st.db,
+        share.doc_id,
+        SCHEDULER_ACTOR,
+        &deliveries,
+        &delivery_errors,
+        json!({
+            "envelope_id": share.envelope_id,
+            "reminder": true
+        }),
+        |payload| payload,
+    )
+    .await
 }
 
-async fn public_envelope_blob_handler(
-    State(st): State<AppState>,
-    Path(token): Path<String>,
-) -> Result<Response, AppError> {
-    let token_hash = access_token_hash_hex(token.trim());
+async fn expire_share(st: &AppState, job: &ScheduledJob) -> Result<(), AppError> {
     let row = sqlx::query(
         r#"
-        select
-            d.id,
-            d.storage_path,
-            d.owner_wallet,
-            d.label,
-            d.hash_hex,
-            d.version,
-            d.mime_type,
-            d.parent_id,
-            d.arweave_tx,
-            coalesce(d.encryption_mode, 'plaintext_server_managed') as encryption_mode,
-            d.ciphertext_hash_hex,
-            s.expires_at,
-            s.revoked_at,
-            s.one_time_use,
-            s.download_allowed,
-            s.completion_count,
-            s.envelope_id
-        from document_shares s
-        join documents d on d.id = s.doc_id
-        where s.access_token_hash = $1
-          and d.is_deleted = false
+        update document_shares
+        set status = 'expired'
+        where id = $1
+          and revoked_at is null
+          and expires_at <= now()
+          and status not in ('completed', 'expired', 'revoked', 'dismissed')
+        returning doc_id, envelope_id, recipient_wallet, recipient_chain, recipient_email,
+                  recipient_phone, expires_at
         "#,
     )
-    .bind(&token_hash)
+    .bind(job_share_id(job)?)
     .fetch_optional(&st.db)
     .await
     .map_err(|e| AppError::Internal(e.to_string()))?;
-
     let Some(row) = row else {
-        return Err(AppError::NotFound("Envelope not found".into()));
+        return Ok(());
     };
-    let expires_at: Option<chrono::DateTime<chrono::Utc>> = row.get("expires_at");
-    let revoked_at: Option<chrono::DateTime<chrono::Utc>> = row.get("revoked_at");
-    let one_time_use: bool = row.get("one_time_use");
-    let download_allowed: bool = row.get("download_allowed");
-    let completion_count: i32 = row.get("completion_count");
-    let envelope_id: uuid::Uuid = row.get("envelope_id");
-
-    if revoked_at.is_some() {
-        return Err(AppError::Forbidden(
-            "This share link has been revoked".into(),
-        ));
-    }
-    if expires_at
-        .map(|value| value <= chrono::Utc::now())
-        .unwrap_or(false)
-    {
-        return Err(AppError::Forbidden("This share link has expired".into()));
-    }
-    if one_time_use && completion_count > 0 {
-        return Err(AppError::Forbidden(
-            "This one-time share link has already been used".into(),
-        ));
-    }
 
-    let access = DocumentAccessRecord {
-        id: row.get("id"),
-        storage_path: row.get("storage_path"),
-        owner_wallet: row.get("owner_wallet"),
-        label: row.get("label"),
-        hash_hex: row.get("hash_hex"),
-        version: row.get("version"),
-        mime_type: row.get("mime_type"),
-        parent_id: row.get("parent_id"),
-        arweave_tx: row.get("arweave_tx"),
-        encryption_mode: row.get("encryption_mode"),
-        ciphertext_hash_hex: row.get("ciphertext_hash_hex"),
+    insert_document_event(
+        &st.db,
+        row.get("doc_id"),
+        SCHEDULER_ACTOR,
+        "SHARE_EXPIRED",
+        json!({
+            "envelope_id": row.get::<uuid::Uuid, _>("envelope_id"),
+            "recipient_wallet": row.get::<Option<String>, _>("recipient_wallet"),
+            "recipient_chain": row.get::<Option<String>, _>("recipient_chain"),
+            "recipient_email": row.get::<Option<String>, _>("recipient_email"),
+            "recipient_phone": row.get::<Option<String>, _>("recipient_phone"),
+            "expires_at": row.get::<chrono::DateTime<chrono::Utc>, _>("expires_at")
+        }),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Invite the alternate recipient with a new envelope on the same terms.
+/// The original envelope stays open, so whoever signs first completes it.
+/// The new envelope takes the job's id, so a retry after a partial failure
+/// does not invite the alternate recipient a second time.
+async fn escalate_share(st: &AppState, job: &ScheduledJob) -> Result<(), AppError> {
+    let Some(share) = load_pending_share(&st.db, job_share_id(job)?).await? else {
+        return Ok(());
+    };
+    let already_escalated = sqlx::query("select 1 from document_shares where envelope_id = $1")
+        .bind(job.id)
+        .fetch_optional(&st.db)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+        .is_some();
+    if already_escalated {
+        return Ok(());
+    }
+    let target = job.payload.get("escalate_to").cloned().unwrap_or_default();
+    let field = |key: &str| {
+        target
+            .get(key)
+            .and_then(|value| value.as_str())
+            .map(ToOwned::to_owned)
     };
+    let (wallet, chain, name, email, phone) = (
+        field("wallet"),
+        field("chain"),
+        field("name"),
+        field("email"),
+        field("phone"),
+    );
 
-    let doc = load_document_bytes_for_access(&st, &access).await?;
-    record_growth_event(
+    let sender_chain = infer_wallet_chain(&share.sender_wallet);
+    let access =
+        load_document_access_record(&st.db, share.doc_id, &share.sender_wallet, sender_chain)
+            .await?;
+    let issued = issue_share_envelope(
+        st,
+        &ShareEnvelopeRequest {
+            doc: &access,
+            envelope_id: Some(job.id),
+            recipient_wallet: wallet.as_deref(),
+            recipient_chain: chain.as_deref(),
+            recipient_name: name.as_deref(),
+            recipient_email: email.as_deref(),
+            recipient_phone: phone.as_deref(),
+            note: share.note.as_deref(),
+            expires_at: share.expires_at,
+            one_time_use: share.one_time_use,
+            download_allowed: share.download_allowed,
+            allow_guest_sign: share.allow_guest_sign,
+        },
+    )
+    .await?;
+    schedule_share_jobs(&st.db, issued.share_id, false, share.expires_at, 0, None).await?;
+
+    insert_document_event(
         &st.db,
-        "PUBLIC_ENVELOPE_DOWNLOADED",
-        "public",
-        None,
-        None,
-        None,
-        Some(access.id),
-        Some(envelope_id),
-        None,
+        share.doc_id,
+        SCHEDULER_ACTOR,
+        "SHARE_ESCALATED",
         json!({
-            "download_allowed": download_allowed,
-            "version": access.version,
-            "mime_type": access.mime_type
+            "from_envelope_id": share.envelope_id,
+            "from_recipient_wallet": share.recipient_wallet,
+            "from_recipient_chain": share.recipient_chain,
+            "from_recipient_email": share.recipient_email,
+            "from_recipient_phone": share.recipient_phone,
+            "envelope_id": issued.envelope_id,
+            "recipient_wallet": wallet,
+            "recipient_chain": chain,
+            "recipient_name": name,
+            "recipient_email": email,
+            "recipient_phone": phone,
+            "access_token_hash": issued.access_token_hash,
+            "expires_at": share.expires_at,
+            "share_status": issued.status
         }),
     )
-    .await;
-
-    Ok((
-        StatusCode::OK,
-        [
-            (header::CONTENT_TYPE, doc.mime_type),
-            (
-                header::CONTENT_DISPOSITION,
-                if download_allowed {
-                    "inline".to_string()
-                } else {
-                    "inline; filename=\"preview\"".to_string()
-                },
-            ),
-            (header::CACHE_CONTROL, "no-store, max-age=0".to_string()),
-            (
-                header::HeaderName::from_static("x-tidbit-hash"),
-                doc.hash_hex,
-            ),
-            (
-                header::HeaderName::from_static("x-tidbit-version"),
-                doc.version.to_string(),
-            ),
-            (
-                header::HeaderName::from_static("x-tidbit-encryption-mode"),
-                doc.encryption_mode,
-            ),
-        ],
-        doc.bytes,
+    .await?;
+    record_envelope_recipient_outcome(
+        &st.db,
+        &issued,
+        SCHEDULER_ACTOR,
+        wallet.as_deref(),
+        chain.as_deref(),
+        |payload| payload,
     )
-        .into_response())
+    .await?;
+    record_delivery_outcomes(
+        &st.db,
+        share.doc_id,
+        SCHEDULER_ACTOR,
+        &issued.deliveries,
+        &issued.delivery_errors,
+        json!({
+            "envelope_id": issued.envelope_id,
+            "escalated_from": share.envelope_id,
+            "recipient_chain": chain,
+            "sender_chain": sender_chain
+        }),
+        |payload| payload,
+    )
+    .await
 }
 
-async fn public_envelope_sign_handler(
-    State(st): State<AppState>,
-    headers: HeaderMap,
-    Path(token): Path<String>,
-    Json(body): Json<PublicEnvelopeSignRequest>,
-) -> Result<Json<serde_json::Value>, AppError> {
-    let token_hash = access_token_hash_hex(token.trim());
+/// Retry an inline document anchor that failed during upload.
+async fn anchor_document(st: &AppState, job: &ScheduledJob) -> Result<(), AppError> {
+    let doc_id = job
+        .payload
+        .get("doc_id")
+        .and_then(|value| value.as_str())
+        .and_then(|value| uuid::Uuid::parse_str(value).ok())
+        .ok_or_else(|| AppError::BadRequest("document anchor job has no doc_id".into()))?;
     let row = sqlx::query(
-        r#"
-        select
-            s.doc_id,
-            s.envelope_id,
-            s.status,
-            s.expires_at,
-            s.revoked_at,
-            s.one_time_use,
-            s.allow_guest_sign,
-            s.completion_count,
-            d.owner_wallet,
-            d.hash_hex,
-            d.version,
-            d.mime_type,
-            d.parent_id,
-            d.arweave_tx
-        from document_shares s
-        join documents d on d.id = s.doc_id
-        where s.access_token_hash = $1
-          and d.is_deleted = false
-        "#,
+        "select hash_hex from documents where id = $1 and arweave_tx is null and is_deleted = false",
     )
-    .bind(&token_hash)
+    .bind(doc_id)
     .fetch_optional(&st.db)
     .await
     .map_err(|e| AppError::Internal(e.to_string()))?;
-
     let Some(row) = row else {
-        return Err(AppError::NotFound("Envelope not found".into()));
+        return Ok(());
     };
+    let hash_hex: String = row.get("hash_hex");
 
-    if !body.consent {
-        return Err(AppError::BadRequest("Recipient consent is required".into()));
+    let tx_id = crate::arweave::ArweaveClient::from_env()
+        .anchor_hash(&crate::arweave::ArweaveAnchorPayload {
+            kind: "file_version_hash",
+            hash_hex: &hash_hex,
+            label: None,
+        })
+        .await?;
+    store_document_anchor(&st.db, doc_id, &hash_hex, &tx_id).await?;
+    insert_document_event(
+        &st.db,
+        doc_id,
+        SCHEDULER_ACTOR,
+        "DOCUMENT_ANCHORED",
+        json!({
+            "doc_hash": hash_hex,
+            "arweave_tx": tx_id
+        }),
+    )
+    .await?;
+    Ok(())
+}
+
+async fn run_scheduled_job(st: &AppState, job: &ScheduledJob) -> Result<(), AppError> {
+    match job.kind.as_str() {
+        JOB_SHARE_REMINDER => send_share_reminder(st, job).await,
+        JOB_SHARE_EXPIRY => expire_share(st, job).await,
+        JOB_SHARE_ESCALATION => escalate_share(st, job).await,
+        JOB_DOCUMENT_ANCHOR => anchor_document(st, job).await,
+        JOB_WEBHOOK_DELIVERY => {
+            let delivery_id = job
+                .payload
+                .get("delivery_id")
+                .and_then(|value| value.as_str())
+                .and_then(|value| uuid::Uuid::parse_str(value).ok())
+                .ok_or_else(|| AppError::BadRequest("webhook job has no delivery_id".into()))?;
+            send_webhook_delivery(&st.db, delivery_id, job.attempts >= MAX_JOB_ATTEMPTS).await
+        }
+        JOB_WORKFLOW_ADVANCE => {
+            let workflow_id = job
+                .payload
+                .get("workflow_id")
+                .and_then(|value| value.as_str())
+                .and_then(|value| uuid::Uuid::parse_str(value).ok())
+                .ok_or_else(|| AppError::BadRequest("workflow job has no workflow_id".into()))?;
+            advance_signing_workflow(st, workflow_id).await
+        }
+        other => Err(AppError::BadRequest(format!("Unknown job kind {other}"))),
     }
-    if body.signer_name.trim().is_empty() {
-        return Err(AppError::BadRequest("Signer name is required".into()));
+}
+
+async fn run_due_jobs(st: &AppState) -> Result<usize, AppError> {
+    let jobs = claim_due_jobs(&st.db, 25).await?;
+    for job in &jobs {
+        match run_scheduled_job(st, job).await {
+            Ok(()) => complete_job(&st.db, job.id).await?,
+            Err(err) => {
+                eprintln!("jobs: {} {} failed: {err}", job.kind, job.id);
+                fail_job(&st.db, job, &err.to_string()).await?;
+            }
+        }
     }
+    Ok(jobs.len())
+}
+
+fn spawn_job_scheduler(st: AppState, interval: std::time::Duration) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            match run_due_jobs(&st).await {
+                Ok(0) => {}
+                Ok(count) => eprintln!("jobs: ran {count} scheduled jobs"),
+                Err(err) => eprintln!("jobs: run failed: {err}"),
+            }
+        }
+    });
+}
 
-    let doc_id: uuid::Uuid = row.get("doc_id");
-    let envelope_id: uuid::Uuid = row.get("envelope_id");
-    let hash_hex: String = row.get("hash_hex");
-    let version: i32 = row.get("version");
-    let expires_at: Option<chrono::DateTime<chrono::Utc>> = row.get("expires_at");
-    let revoked_at: Option<chrono::DateTime<chrono::Utc>> = row.get("revoked_at");
-    let one_time_use: bool = row.get("one_time_use");
-    let completion_count: i32 = row.get("completion_count");
-    let owner_wallet: String = row.get("owner_wallet");
-    let policy = load_document_policy(&st.db, doc_id, &owner_wallet).await?;
-    let allow_guest_sign = row
-        .get::<Option<bool>, _>("allow_guest_sign")
-        .unwrap_or_else(|| {
-            policy
-                .get("allow_guest_sign")
-                .and_then(|value| value.as_bool())
-                .unwrap_or(false)
-        });
-    let guest_allowed = allow_guest_sign && public_guest_attestation_enabled();
+// ================================================================
+// WEBHOOKS
+// ================================================================
 
-    if revoked_at.is_some() {
-        return Err(AppError::Forbidden(
-            "This share link has been revoked".into(),
-        ));
-    }
-    if expires_at
-        .map(|value| value <= chrono::Utc::now())
-        .unwrap_or(false)
-    {
-        return Err(AppError::Forbidden("This share link has expired".into()));
+const MAX_WEBHOOKS_PER_WALLET: usize = 20;
+
+/// Webhook targets must be https; plain http is only for local testing.
+/// Scheme and credential checks, then the same public-address check the
+/// sender repeats before every delivery.
+async fn validate_webhook_url(raw: &str) -> Result<String, AppError> {
+    let url = reqwest::Url::parse(raw.trim())
+        .map_err(|_| AppError::BadRequest("Webhook url is not a valid URL".into()))?;
+    let allow_http = std::env::var("WEBHOOK_ALLOW_HTTP")
+        .map(|value| value.trim().eq_ignore_ascii_case("true"))
+        .unwrap_or(false);
+    match url.scheme() {
+        "https" => {}
+        "http" if allow_http => {}
+        _ => return Err(AppError::BadRequest("Webhook url must use https".into())),
     }
-    if one_time_use && completion_count > 0 {
-        return Err(AppError::Forbidden(
-            "This one-time share link has already been used".into(),
+    if url.host_str().is_none() || !url.username().is_empty() || url.password().is_some() {
+        return Err(AppError::BadRequest(
+            "Webhook url needs a host and no credentials".into(),
         ));
     }
-    if row.get::<String, _>("status") == "completed" {
-        return Err(AppError::Forbidden(
-            "This envelope has already been completed".into(),
+    resolve_webhook_target(&url).await?;
+    Ok(url.to_string())
+}
+
+async fn create_webhook_handler(
+    State(st): State<AppState>,
+    headers: HeaderMap,
+    Json(body): Json<WebhookSubscriptionRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let session = require_session_from_headers(&st, &headers).await?;
+    let url = validate_webhook_url(&body.url).await?;
+    let mut event_types = body
+        .event_types
+        .unwrap_or_default()
+        .iter()
+        .map(|value| normalize_event_filter(value))
+        .collect::<Result<Vec<_>, _>>()?;
+    event_types.sort();
+    event_types.dedup();
+
+    let pq_signed = body.pq_signed.unwrap_or(false);
+    let pq_key = webhook_pq_key();
+    if pq_signed && pq_key.is_none() {
+        return Err(AppError::BadRequest(
+            "ML-DSA webhook signatures are not configured on this server".into(),
         ));
     }
 
-    let signature_type = body.signature_type.clone().unwrap_or_else(|| {
-        if guest_allowed {
-            "guest_attestation".to_string()
-        } else {
-            "evm_personal_sign".to_string()
-        }
-    });
-    let signer_identity = body
-        .wallet_address
-        .clone()
-        .filter(|value| !value.trim().is_empty())
-        .unwrap_or_else(|| body.signer_name.trim().to_string());
-    let canonical_message =
-        public_envelope_sign_message(envelope_id, doc_id, &hash_hex, &signer_identity, version);
-    let annotation_fields = normalize_annotation_fields(body.annotation_fields.clone());
+    let existing = list_webhook_subscriptions(&st.db, &session.wallet).await?;
+    if existing
+        .iter()
+        .filter(|subscription| subscription.status == "active")
+        .count()
+        >= MAX_WEBHOOKS_PER_WALLET
+    {
+        return Err(AppError::BadRequest(format!(
+            "A wallet can have at most {MAX_WEBHOOKS_PER_WALLET} active webhooks"
+        )));
+    }
 
-    let verification = match signature_type.as_str() {
-        "guest_attestation" => json!({
-            "signature_type": "guest_attestation",
-            "consent": true
-        }),
-        "evm_personal_sign" => {
-            let wallet_address = body.wallet_address.clone().ok_or_else(|| {
-                AppError::BadRequest("wallet_address is required for EVM signing".into())
-            })?;
-            let signature = body.signature.clone().ok_or_else(|| {
-                AppError::BadRequest("signature is required for EVM signing".into())
-            })?;
-            let recovered = verify_evm_signature(&canonical_message, &signature)
-                .map_err(|_| AppError::BadRequest("Invalid EVM signature".into()))?
-                .to_lowercase();
+    let secret = generate_webhook_secret();
+    let subscription = insert_webhook_subscription(
+        &st.db,
+        &session.wallet,
+        &url,
+        &event_types,
+        pq_signed,
+        &secret,
+    )
+    .await?;
 
-            if recovered != wallet_address.to_lowercase() {
-                return Err(AppError::Forbidden(
-                    "EVM signature does not match wallet_address".into(),
-                ));
-            }
+    Ok(Json(json!({
+        "ok": true,
+        "subscription": subscription,
+        "secret": secret,
+        "pq_public_key_b64": pq_key.map(|key| key.public_key_b64)
+    })))
+}
 
-            json!({
-                "signature_type": "evm_personal_sign",
-                "wallet_address": wallet_address,
-                "signature": signature,
-                "recovered_wallet": recovered
-            })
-        }
-        "sol_ed25519" => {
-            let wallet_address = body.wallet_address.clone().ok_or_else(|| {
-                AppError::BadRequest("wallet_address is required for Solana signing".into())
-            })?;
-            let signature = body.signature.clone().ok_or_else(|| {
-                AppError::BadRequest("signature is required for Solana signing".into())
-            })?;
+async fn list_webhooks_handler(
+    State(st): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let session = require_session_from_headers(&st, &headers).await?;
+    let subscriptions = list_webhook_subscriptions(&st.db, &session.wallet).await?;
+    Ok(Json(json!({
+        "subscriptions": subscriptions,
+        "pq_public_key_b64": webhook_pq_key().map(|key| key.public_key_b64)
+    })))
+}
+
+async fn disable_webhook_handler(
+    State(st): State<AppState>,
+    headers: HeaderMap,
+    Path(subscription_id): Path<uuid::Uuid>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let session = require_session_from_headers(&st, &headers).await?;
+    if load_webhook_subscription(&st.db, subscription_id, &session.wallet)
+        .await?
+        .is_none()
+    {
+        return Err(AppError::NotFound("Webhook not found".into()));
+    }
+    let disabled = disable_webhook_subscription(&st.db, subscription_id, &session.wallet).await?;
+    Ok(Json(json!({ "ok": true, "disabled": disabled })))
+}
+
+async fn webhook_deliveries_handler(
+    State(st): State<AppState>,
+    headers: HeaderMap,
+    Path(subscription_id): Path<uuid::Uuid>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let session = require_session_from_headers(&st, &headers).await?;
+    let Some(subscription) =
+        load_webhook_subscription(&st.db, subscription_id, &session.wallet).await?
+    else {
+        return Err(AppError::NotFound("Webhook not found".into()));
+    };
+    let limit = params
+        .get("limit")
+        .and_then(|value| value.parse::<i64>().ok())
+        .unwrap_or(50)
+        .clamp(1, 200);
+    let deliveries = list_webhook_deliveries(&st.db, subscription.id, limit).await?;
+    Ok(Json(json!({
+        "subscription": subscription,
+        "deliveries": deliveries
+    })))
+}
 
-            verify_solana_signature(&canonical_message, &wallet_address, &signature)?;
+async fn replay_webhook_delivery_handler(
+    State(st): State<AppState>,
+    headers: HeaderMap,
+    Path(delivery_id): Path<uuid::Uuid>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let session = require_session_from_headers(&st, &headers).await?;
+    let replay_id = replay_webhook_delivery(&st.db, delivery_id, &session.wallet)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Webhook delivery not found".into()))?;
+    Ok(Json(json!({
+        "ok": true,
+        "delivery_id": replay_id,
+        "replay_of": delivery_id
+    })))
+}
 
-            json!({
-                "signature_type": "sol_ed25519",
-                "wallet_address": wallet_address,
-                "signature": signature,
-                "chain": "sol"
-            })
+// ================================================================
+// LIVE EVENTS
+// ================================================================
+
+/// How long a stream trusts its last session and document access checks.
+/// Revoked sessions and shares stop receiving events within this window.
+const STREAM_RECHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+struct CustodyEventStream {
+    st: AppState,
+    session: WalletSession,
+    session_checked_at: std::time::Instant,
+    doc_filter: Option<uuid::Uuid>,
+    notices: tokio::sync::broadcast::Receiver<EventNotice>,
+    access: HashMap<uuid::Uuid, (bool, std::time::Instant)>,
+    /// Fires between notices too, so a quiet stream still notices a
+    /// revoked session or share.
+    recheck: tokio::time::Interval,
+    closed: bool,
+}
+
+impl CustodyEventStream {
+    async fn session_still_valid(&mut self) -> bool {
+        if self.session_checked_at.elapsed() < STREAM_RECHECK_INTERVAL {
+            return true;
         }
-        "pq_dilithium3" | "pq_mldsa65" => {
-            let pq_public_key_b64 = body.pq_public_key_b64.clone().ok_or_else(|| {
-                AppError::BadRequest("pq_public_key_b64 is required for PQ signing".into())
-            })?;
-            let signature = body.signature.clone().ok_or_else(|| {
-                AppError::BadRequest("signature is required for PQ signing".into())
-            })?;
-            let public_key = base64::engine::general_purpose::STANDARD
-                .decode(&pq_public_key_b64)
-                .map_err(|_| AppError::BadRequest("Invalid pq_public_key_b64".into()))?;
-            let signed_message = base64::engine::general_purpose::STANDARD
-                .decode(&signature)
-                .map_err(|_| AppError::BadRequest("Invalid PQ signed message encoding".into()))?;
-            let verified =
-                dilithium::verify(&public_key, canonical_message.as_bytes(), &signed_message)
-                    .map_err(|e| AppError::Internal(e.to_string()))?;
+        self.session_checked_at = std::time::Instant::now();
+        matches!(
+            self.st
+                .auth
+                .get_session(&self.session.session_id, self.session.device_id.as_deref())
+                .await,
+            Ok(Some(_))
+        )
+    }
 
-            if !verified {
-                return Err(AppError::Forbidden(
-                    "PQ signature verification failed".into(),
-                ));
+    async fn can_access(&mut self, doc_id: uuid::Uuid) -> bool {
+        if let Some((allowed, checked_at)) = self.access.get(&doc_id) {
+            if checked_at.elapsed() < STREAM_RECHECK_INTERVAL {
+                return *allowed;
             }
-
-            json!({
-                "signature_type": "pq_mldsa65",
-                "pq_public_key_b64": pq_public_key_b64,
-                "signature": signature
-            })
         }
-        _ => return Err(AppError::BadRequest("Unsupported signature_type".into())),
-    };
+        let allowed = load_document_access_record(
+            &self.st.db,
+            doc_id,
+            &self.session.wallet,
+            &self.session.chain,
+        )
+        .await
+        .is_ok();
+        self.access
+            .insert(doc_id, (allowed, std::time::Instant::now()));
+        allowed
+    }
 
-    if signature_type == "guest_attestation" && !guest_allowed {
-        return Err(AppError::Forbidden(
-            "Guest attestation is disabled for this envelope".into(),
-        ));
+    /// A final `resync` event when the session or the streamed document's
+    /// access is gone. The client reconnects with its current session.
+    async fn end_if_revoked(&mut self) -> Option<Event> {
+        let reason = if !self.session_still_valid().await {
+            "session"
+        } else if let Some(doc_id) = self.doc_filter {
+            if self.can_access(doc_id).await {
+                return None;
+            }
+            "access"
+        } else {
+            return None;
+        };
+        self.closed = true;
+        Some(Event::default().event("resync").data(reason))
     }
 
-    let annotation_json = json!({
-        "annotation_text": body.annotation_text,
-        "annotation_fields": annotation_fields,
-        "sign_reason": body.sign_reason,
-        "consent": body.consent
-    });
+    /// Wait for the next event this session may see. `None` ends the stream.
+    async fn next_event(&mut self) -> Option<Event> {
+        if self.closed {
+            return None;
+        }
+        loop {
+            let received = tokio::select! {
+                received = self.notices.recv() => received,
+                _ = self.recheck.tick() => {
+                    match self.end_if_revoked().await {
+                        Some(event) => return Some(event),
+                        None => continue,
+                    }
+                }
+            };
+            let notice = match received {
+                Ok(notice) => notice,
+                // The client missed events; it should re-fetch its timelines.
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                    return Some(Event::default().event("resync").data(skipped.to_string()));
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => return None,
+            };
+            if let Some(event) = self.end_if_revoked().await {
+                return Some(event);
+            }
+            if self
+                .doc_filter
+                .is_some_and(|doc_id| doc_id != notice.doc_id)
+                || !self.can_access(notice.doc_id).await
+            {
+                continue;
+            }
+            match load_stream_event(&self.st.db, notice.event_id).await {
+                Ok(Some(event)) => {
+                    return Some(
+                        Event::default()
+                            .event("custody")
+                            .id(notice.event_id.to_string())
+                            .data(event.to_string()),
+                    )
+                }
+                Ok(None) => continue,
+                Err(err) => eprintln!("events: could not load {}: {err}", notice.event_id),
+            }
+        }
+    }
+}
 
-    sqlx::query(
+/// The same shape as `/api/doc/:id/events`, plus the document id.
+async fn load_stream_event(
+    db: &PgPool,
+    event_id: uuid::Uuid,
+) -> Result<Option<serde_json::Value>, AppError> {
+    let row = sqlx::query(
         r#"
-        update document_shares
-        set
-            status = 'completed',
-            signed_at = now(),
-            completed_at = now(),
-            signer_name = $1,
-            signer_email = $2,
-            signer_title = $3,
-            signer_org = $4,
-            signer_wallet = $5,
-            sign_reason = $6,
-            annotation_json = $7,
-            completion_signature_type = $8,
-            completion_count = completion_count + 1
-        where access_token_hash = $9
+        select id, doc_id, event_type, actor_wallet, payload, created_at
+        from document_events
+        where id = $1
         "#,
     )
-    .bind(body.signer_name.trim())
-    .bind(body.signer_email.as_deref())
-    .bind(body.signer_title.as_deref())
-    .bind(body.signer_org.as_deref())
-    .bind(body.wallet_address.as_deref())
-    .bind(body.sign_reason.as_deref())
-    .bind(&annotation_json)
-    .bind(&signature_type)
-    .bind(&token_hash)
-    .execute(&st.db)
+    .bind(event_id)
+    .fetch_optional(db)
     .await
     .map_err(|e| AppError::Internal(e.to_string()))?;
 
-    insert_document_event(
-        &st.db,
-        doc_id,
-        &format!("guest-envelope:{envelope_id}"),
-        "ENVELOPE_COMPLETED",
-        public_custody_payload_for_envelope(
-            json!({
-                "envelope_id": envelope_id,
-                "hash_hex": hash_hex,
-                "version": version,
-                "mime_type": row.get::<String,_>("mime_type"),
-                "parent_id": row.get::<Option<uuid::Uuid>,_>("parent_id"),
-                "arweave_tx": row.get::<Option<String>,_>("arweave_tx"),
-                "signer_name": body.signer_name.trim(),
-                "signer_email": body.signer_email,
-                "signer_title": body.signer_title,
-                "signer_org": body.signer_org,
-                "signer_wallet": body.wallet_address,
-                "completion_signature_type": signature_type,
-                "annotation_json": annotation_json,
-                "signing_message": canonical_message,
-                "verification": verification
-            }),
-            envelope_id,
-            &headers,
-        ),
-    )
-    .await?;
-    let signer_chain = body
-        .wallet_address
-        .as_deref()
-        .map(infer_wallet_chain)
-        .map(str::to_string);
-    let normalized_signer_wallet = body.wallet_address.as_deref().map(|wallet| {
-        normalize_wallet_for_chain(
-            wallet,
-            signer_chain
-                .as_deref()
-                .unwrap_or_else(|| infer_wallet_chain(wallet)),
-        )
-    });
-    record_growth_event(
-        &st.db,
-        "PUBLIC_ENVELOPE_COMPLETED",
-        "public",
-        normalized_signer_wallet.as_deref(),
-        signer_chain.as_deref(),
-        None,
-        Some(doc_id),
-        Some(envelope_id),
-        None,
+    Ok(row.map(|r| {
         json!({
-            "signature_type": signature_type.clone(),
-            "signer_name": body.signer_name.trim(),
-            "has_wallet": body.wallet_address.is_some()
-        }),
-    )
-    .await;
+            "id": r.get::<uuid::Uuid,_>("id"),
+            "doc_id": r.get::<uuid::Uuid,_>("doc_id"),
+            "event_type": r.get::<String,_>("event_type"),
+            "actor_wallet": r.get::<String,_>("actor_wallet"),
+            "payload": r.get::<serde_json::Value,_>("payload"),
+            "created_at": r.get::<chrono::DateTime<chrono::Utc>,_>("created_at")
+        })
+    }))
+}
 
-    Ok(Json(json!({
-        "ok": true,
-        "envelope_id": envelope_id,
-        "status": "completed"
-    })))
+/// Server-sent custody events for every document the session can open, or
+/// for one document with `?doc_id=`.
+async fn custody_event_stream_handler(
+    State(st): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Sse<impl futures_util::Stream<Item = Result<Event, std::convert::Infallible>>>, AppError>
+{
+    let session = require_session_from_headers(&st, &headers).await?;
+    let doc_filter = params
+        .get("doc_id")
+        .map(|value| {
+            uuid::Uuid::parse_str(value.trim())
+                .map_err(|_| AppError::BadRequest("doc_id must be a UUID".into()))
+        })
+        .transpose()?;
+    if let Some(doc_id) = doc_filter {
+        load_document_access_record(&st.db, doc_id, &session.wallet, &session.chain).await?;
+    }
+
+    let stream = CustodyEventStream {
+        notices: st.events.subscribe(),
+        st,
+        session,
+        session_checked_at: std::time::Instant::now(),
+        doc_filter,
+        access: HashMap::new(),
+        recheck: {
+            let mut recheck = tokio::time::interval_at(
+                tokio::time::Instant::now() + STREAM_RECHECK_INTERVAL,
+                STREAM_RECHECK_INTERVAL,
+            );
+            recheck.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            recheck
+        },
+        closed: false,
+    };
+    let events = futures_util::stream::unfold(stream, |mut stream| async move {
+        let event = stream.next_event().await?;
+        Some((Ok(event), stream))
+    });
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
 }
 
 // ================================================================
@@ -7321,7 +12038,13 @@ async fn session_info_handler(
 fn wallet_session_record_json(
     record: &identity_web::state::WalletSessionRecord,
     current_session_id: &str,
+    trusted_devices: &[TrustedDevice],
 ) -> serde_json::Value {
+    let trusted = record.device_id.as_deref().and_then(|device_id| {
+        trusted_devices
+            .iter()
+            .find(|device| device.device_id == device_id)
+    });
     json!({
         "session_id": record.session_id,
         "wallet": record.wallet,
@@ -7335,6 +12058,8 @@ fn wallet_session_record_json(
         "device_id": record.device_id,
         "user_agent": record.user_agent,
         "ip_address": record.ip_address,
+        "trusted": trusted.is_some(),
+        "device_name": trusted.map(|device| device.name.clone()),
         "current": record.session_id == current_session_id,
         "active": record.revoked_at.is_none() && record.expires_at > chrono::Utc::now().timestamp()
     })
@@ -7346,37 +12071,162 @@ async fn list_sessions_handler(
 ) -> Result<Json<serde_json::Value>, AppError> {
     let sess = require_session_from_headers(&st, &headers).await?;
     let sessions = st.auth.list_wallet_sessions(&sess.wallet, 20).await?;
+    let trusted_devices = list_trusted_devices(&st.db, &sess.wallet).await?;
+    let (policy, custom_policy) = st.auth.session_policy(&sess.wallet).await?;
+    let account_events = list_account_events(&st.db, &sess.wallet, 20).await?;
 
     Ok(Json(json!({
         "wallet": sess.wallet,
         "chain": sess.chain,
         "current_session_id": sess.session_id,
+        "current_device_id": sess.device_id,
+        "policy": policy.to_json(if custom_policy { "wallet" } else { "default" }),
+        "trusted_devices": trusted_devices,
+        "account_events": account_events,
         "items": sessions
             .iter()
-            .map(|record| wallet_session_record_json(record, &sess.session_id))
+            .map(|record| wallet_session_record_json(record, &sess.session_id, &trusted_devices))
             .collect::<Vec<_>>()
     })))
 }
 
+/// Change how many devices the wallet may stay signed in on. The new policy
+/// applies at once, keeping the calling session.
+async fn update_session_policy_handler(
+    State(st): State<AppState>,
+    headers: HeaderMap,
+    Json(body): Json<SessionPolicyRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let sess = require_session_from_headers(&st, &headers).await?;
+    let policy = SessionPolicy::parse(&body.mode, body.max_devices)?;
+    let (previous, _) = st.auth.session_policy(&sess.wallet).await?;
+
+    save_session_policy(&st.db, &sess.wallet, policy).await?;
+    let revoked = st
+        .auth
+        .apply_session_policy(&sess.wallet, &sess.session_id)
+        .await?;
+    record_account_event(
+        &st.db,
+        &sess.wallet,
+        ACCOUNT_EVENT_SESSION_POLICY_CHANGED,
+        json!({
+            "session_id": sess.session_id,
+            "previous": previous.to_json("wallet"),
+            "policy": policy.to_json("wallet"),
+            "sessions_revoked": revoked
+        }),
+    )
+    .await?;
+
+    Ok(Json(json!({
+        "ok": true,
+        "policy": policy.to_json("wallet"),
+        "sessions_revoked": revoked
+    })))
+}
+
+async fn trust_device_handler(
+    State(st): State<AppState>,
+    headers: HeaderMap,
+    Json(body): Json<TrustedDeviceRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let sess = require_session_from_headers(&st, &headers).await?;
+    let device_id = body
+        .device_id
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+        .or_else(|| sess.device_id.clone())
+        .ok_or_else(|| AppError::BadRequest("Missing device_id".into()))?;
+    let name = body.name.trim();
+    if name.is_empty() || name.chars().count() > 80 {
+        return Err(AppError::BadRequest(
+            "Device name must be 1 to 80 characters".into(),
+        ));
+    }
+
+    // Only devices this wallet has signed in from can be trusted.
+    let known = st
+        .auth
+        .list_wallet_sessions(&sess.wallet, 200)
+        .await?
+        .iter()
+        .any(|record| record.device_id.as_deref() == Some(device_id.as_str()));
+    if !known {
+        return Err(AppError::NotFound(
+            "No session from this device for the wallet".into(),
+        ));
+    }
+
+    trust_device(&st.db, &sess.wallet, &device_id, name).await?;
+    record_account_event(
+        &st.db,
+        &sess.wallet,
+        ACCOUNT_EVENT_DEVICE_TRUSTED,
+        json!({
+            "session_id": sess.session_id,
+            "device_id": device_id,
+            "name": name
+        }),
+    )
+    .await?;
+
+    Ok(Json(json!({
+        "ok": true,
+        "device_id": device_id,
+        "name": name
+    })))
+}
+
+async fn untrust_device_handler(
+    State(st): State<AppState>,
+    headers: HeaderMap,
+    Path(device_id): Path<String>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let sess = require_session_from_headers(&st, &headers).await?;
+    let device_id = device_id.trim();
+    if !untrust_device(&st.db, &sess.wallet, device_id).await? {
+        return Err(AppError::NotFound("Device is not trusted".into()));
+    }
+    record_account_event(
+        &st.db,
+        &sess.wallet,
+        ACCOUNT_EVENT_DEVICE_UNTRUSTED,
+        json!({
+            "session_id": sess.session_id,
+            "device_id": device_id
+        }),
+    )
+    .await?;
+
+    Ok(Json(json!({ "ok": true })))
+}
+
+/// Exchange a refresh token for a new access session and refresh token.
+/// The access session may already have expired.
 async fn rotate_session_handler(
     State(st): State<AppState>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
     headers: HeaderMap,
+    Json(body): Json<SessionRefreshRequest>,
 ) -> Result<Json<serde_json::Value>, AppError> {
-    let sid = headers
-        .get("x-session-id")
-        .and_then(|v| v.to_str().ok())
-        .ok_or_else(|| AppError::Auth("Missing session".into()))?;
+    let refresh_token = body.refresh_token.trim();
+    if refresh_token.is_empty() {
+        return Err(AppError::Auth("Missing refresh_token".into()));
+    }
 
     let rotated = st
         .auth
         .rotate_session(
-            sid,
+            refresh_token,
             device_id_from_headers(&headers).as_deref(),
             user_agent_from_headers(&headers).as_deref(),
-            ip_from_headers(&headers).as_deref(),
+            Some(client_ip(&st, peer, &headers).to_string()).as_deref(),
         )
         .await?
-        .ok_or_else(|| AppError::Auth("Invalid or expired session".into()))?;
+        .ok_or_else(|| AppError::Auth("Invalid or expired refresh token".into()))?;
 
     let keys = load_or_create_server_mlkem_keypair(&st.db, &rotated.wallet).await?;
 
@@ -7384,6 +12234,7 @@ async fn rotate_session_handler(
         "active": true,
         "rotated": true,
         "session_id": rotated.session_id,
+        "refresh_token": rotated.refresh_token,
         "wallet": rotated.wallet,
         "chain": rotated.chain,
         "created_at": rotated.created_at,
@@ -7447,6 +12298,174 @@ async fn logout_handler(
     Ok(Json(json!({ "ok": true })))
 }
 
+// ================================================================
+// PQ SIGNING KEYS
+// ================================================================
+
+/// The wallet and chain a session's ML-DSA keys are registered under.
+fn pq_key_owner(session: &WalletSession) -> (String, &'static str) {
+    let chain =
+        canonical_chain(&session.chain).unwrap_or_else(|| infer_wallet_chain(&session.wallet));
+    (normalize_wallet_for_chain(&session.wallet, chain), chain)
+}
+
+/// The wallet's active registered key. A key sent by the client must be that
+/// key; it is only a hint and never trusted on its own.
+async fn registered_pq_key_for(
+    db: &PgPool,
+    wallet: &str,
+    chain: &str,
+    sent_public_key_b64: Option<&str>,
+) -> Result<Option<PqSigningKey>, AppError> {
+    let Some(key) = active_pq_key(db, wallet, chain).await? else {
+        return Ok(None);
+    };
+    if let Some(sent) = sent_public_key_b64.filter(|value| !value.trim().is_empty()) {
+        if decode_pq_public_key(sent)? != decode_pq_public_key(&key.public_key_b64)? {
+            return Err(AppError::Forbidden(
+                "pq_public_key_b64 is not this wallet's registered ML-DSA key".into(),
+            ));
+        }
+    }
+    Ok(Some(key))
+}
+
+/// Verify an ML-DSA signature over `message` with the wallet's registered key.
+async fn verify_registered_pq_signature(
+    db: &PgPool,
+    wallet: &str,
+    chain: &str,
+    sent_public_key_b64: Option<&str>,
+    message: &str,
+    signature_b64: &str,
+) -> Result<PqSigningKey, AppError> {
+    let key = registered_pq_key_for(db, wallet, chain, sent_public_key_b64)
+        .await?
+        .ok_or_else(|| {
+            AppError::Forbidden(
+                "Register an ML-DSA key for this wallet before signing with it".into(),
+            )
+        })?;
+    let public_key = decode_pq_public_key(&key.public_key_b64)?;
+    let signed_message = base64::engine::general_purpose::STANDARD
+        .decode(signature_b64.trim())
+        .map_err(|_| AppError::BadRequest("Invalid PQ signed message encoding".into()))?;
+    let verified = dilithium::verify(&public_key, message.as_bytes(), &signed_message)
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    if !verified {
+        return Err(AppError::Forbidden(
+            "PQ signature verification failed".into(),
+        ));
+    }
+    Ok(key)
+}
+
+/// The classical half of a hybrid signature is the wallet's native scheme.
+fn hybrid_wallet_signature_type(chain: &str) -> &'static str {
+    if chain == "sol" {
+        "sol_ed25519"
+    } else {
+        "evm_personal_sign"
+    }
+}
+
+fn pq_key_verification_json(key: &PqSigningKey) -> serde_json::Value {
+    json!({
+        "pq_public_key_b64": key.public_key_b64,
+        "pq_key_id": key.id,
+        "pq_key_fingerprint_hex": key.fingerprint_hex,
+        "pq_key_registered_at": key.registered_at
+    })
+}
+
+async fn pq_key_handler(
+    State(st): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let session = require_session_from_headers(&st, &headers).await?;
+    let (wallet, chain) = pq_key_owner(&session);
+    let history = pq_key_history(&st.db, &wallet, chain).await?;
+    let active = history.iter().find(|key| key.status == "active");
+
+    Ok(Json(json!({
+        "wallet": wallet,
+        "chain": chain,
+        "active": active,
+        "history": history
+    })))
+}
+
+async fn pq_key_challenge_handler(
+    State(st): State<AppState>,
+    headers: HeaderMap,
+    Json(body): Json<PqKeyChallengeRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let session = require_session_from_headers(&st, &headers).await?;
+    let (wallet, chain) = pq_key_owner(&session);
+    let public_key = decode_pq_public_key(&body.public_key_b64)?;
+    let fingerprint_hex = pq_key_fingerprint_hex(&public_key);
+    let issued_at = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+
+    Ok(Json(json!({
+        "message": key_binding_message(&wallet, chain, &fingerprint_hex, &issued_at),
+        "issued_at": issued_at,
+        "fingerprint_hex": fingerprint_hex,
+        "expires_in_secs": BINDING_MAX_AGE_SECS
+    })))
+}
+
+async fn pq_key_register_handler(
+    State(st): State<AppState>,
+    headers: HeaderMap,
+    Json(body): Json<PqKeyRegisterRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let session = require_session_from_headers(&st, &headers).await?;
+    let (wallet, chain) = pq_key_owner(&session);
+    let binding = verify_key_binding(
+        &KeyBindingProof {
+            wallet: &wallet,
+            chain,
+            public_key_b64: &body.public_key_b64,
+            issued_at: body.issued_at.trim(),
+            wallet_signature: body.wallet_signature.trim(),
+            pq_signature_b64: &body.pq_signature_b64,
+        },
+        chrono::Utc::now(),
+    )?;
+    let (key, rotated) = register_pq_key(
+        &st.db,
+        &wallet,
+        chain,
+        &binding,
+        body.wallet_signature.trim(),
+        body.pq_signature_b64.trim(),
+    )
+    .await?;
+
+    Ok(Json(json!({ "ok": true, "key": key, "rotated": rotated })))
+}
+
+async fn pq_key_revoke_handler(
+    State(st): State<AppState>,
+    headers: HeaderMap,
+    Json(body): Json<PqKeyRevokeRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let session = require_session_from_headers(&st, &headers).await?;
+    let (wallet, chain) = pq_key_owner(&session);
+    let reason = body
+        .reason
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .unwrap_or("owner revoked");
+    let key = revoke_pq_key(&st.db, &wallet, chain, reason)
+        .await?
+        .ok_or_else(|| AppError::NotFound("No active ML-DSA key to revoke".into()))?;
+
+    Ok(Json(json!({ "ok": true, "key": key })))
+}
+
 // ================================================================
 // PUBLIC VERIFY
 // ================================================================
@@ -7477,16 +12496,225 @@ async fn public_verify_handler(
 
     let hash_hex = hex::encode(pqc_sha3::sha3_256_bytes(&bytes));
 
-    let row = sqlx::query("select 1 from documents where hash_hex = $1 and is_deleted = false")
-        .bind(&hash_hex)
-        .fetch_optional(&st.db)
+    Ok(Json(public_verify_report(&st.db, &hash_hex).await?))
+}
+
+async fn public_verify_hash_handler(
+    State(st): State<AppState>,
+    Path(hash): Path<String>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let hash_hex = hash.trim().to_ascii_lowercase();
+    if hash_hex.len() != 64 || !hash_hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(AppError::BadRequest(
+            "hash must be a hex-encoded SHA3-256 digest".into(),
+        ));
+    }
+
+    Ok(Json(public_verify_report(&st.db, &hash_hex).await?))
+}
+
+const PUBLIC_VERIFY_MAX_MATCHES: i64 = 20;
+
+/// Provenance for every live document whose content hash is `hash_hex`:
+/// lineage and version, signatures over that exact hash, and the anchor.
+async fn public_verify_report(db: &PgPool, hash_hex: &str) -> Result<serde_json::Value, AppError> {
+    let rows = sqlx::query(
+        r#"
+        with recursive lineage (match_id, id, parent_id) as (
+            select id, id, parent_id
+            from documents
+            where hash_hex = $1 and is_deleted = false
+            union all
+            select l.match_id, d.id, d.parent_id
+            from lineage l
+            join documents d on d.id = l.parent_id
+        )
+        select d.id, d.owner_wallet, d.version, d.arweave_tx, d.created_at,
+               (select l.id from lineage l where l.match_id = d.id and l.parent_id is null limit 1) as lineage_id
+        from documents d
+        where d.hash_hex = $1 and d.is_deleted = false
+        order by d.created_at asc
+        limit $2
+        "#,
+    )
+    .bind(hash_hex)
+    .bind(PUBLIC_VERIFY_MAX_MATCHES)
+    .fetch_all(db)
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let mut matches = Vec::with_capacity(rows.len());
+    for row in rows {
+        let doc_id: uuid::Uuid = row.get("id");
+        let owner_wallet: String = row.get("owner_wallet");
+        let policy = load_document_policy(db, doc_id, &owner_wallet).await?;
+        let redact_owner = policy
+            .get("redact_owner_on_public_verify")
+            .and_then(|value| value.as_bool())
+            .unwrap_or(true);
+
+        let signatures = sqlx::query(
+            r#"
+            select event_type, actor_wallet, payload, created_at
+            from document_events
+            where doc_id = $1
+              and event_type in ('SIGN', 'AGENT_SIGN', 'ENVELOPE_COMPLETED')
+              and payload->>'hash_hex' = $2
+            order by created_at asc
+            "#,
+        )
+        .bind(doc_id)
+        .bind(hash_hex)
+        .fetch_all(db)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+        .into_iter()
+        .filter_map(|event| {
+            public_signature_entry(
+                &event.get::<String, _>("event_type"),
+                &event.get::<String, _>("actor_wallet"),
+                &event.get::<serde_json::Value, _>("payload"),
+                event.get("created_at"),
+                redact_owner.then_some(owner_wallet.as_str()),
+            )
+        })
+        .collect::<Vec<_>>();
+
+        let anchor = public_anchor_status(db, doc_id, hash_hex, row.get("arweave_tx")).await?;
+
+        matches.push(json!({
+            "doc_id": doc_id,
+            "lineage_id": row.get::<Option<uuid::Uuid>, _>("lineage_id").unwrap_or(doc_id),
+            "version": row.get::<i32, _>("version"),
+            "uploaded_at": row.get::<chrono::DateTime<chrono::Utc>, _>("created_at"),
+            "owner_wallet": (!redact_owner).then_some(owner_wallet),
+            "owner_redacted": redact_owner,
+            "signatures": signatures,
+            "anchor": anchor
+        }));
+    }
+
+    Ok(json!({
+        "verified": !matches.is_empty(),
+        "hash": hash_hex,
+        "matches": matches
+    }))
+}
+
+/// Public view of a signing event. Guest signer names and emails are never
+/// exposed, and the signer wallet is withheld when it is the redacted owner.
+fn public_signature_entry(
+    event_type: &str,
+    actor_wallet: &str,
+    payload: &serde_json::Value,
+    signed_at: chrono::DateTime<chrono::Utc>,
+    redacted_owner: Option<&str>,
+) -> Option<serde_json::Value> {
+    let (signature_type, signer_wallet) = match event_type {
+        "SIGN" => (
+            payload["verification"]["signature_type"]
+                .as_str()
+                .unwrap_or("unknown"),
+            Some(actor_wallet),
+        ),
+        "AGENT_SIGN" => ("agent", Some(actor_wallet)),
+        "ENVELOPE_COMPLETED" => (
+            payload["completion_signature_type"]
+                .as_str()
+                .unwrap_or("guest"),
+            payload["signer_wallet"].as_str(),
+        ),
+        _ => return None,
+    };
+    let signer_wallet = signer_wallet.filter(|wallet| {
+        !redacted_owner.is_some_and(|owner| owner.eq_ignore_ascii_case(wallet))
+    });
+
+    Some(json!({
+        "signature_type": signature_type,
+        "signer_wallet": signer_wallet,
+        "signed_at": signed_at
+    }))
+}
+
+/// The document's own txid, or the batch that anchored it, with the latest
+/// confirmation state. Dropped transactions resolve to their resubmission.
+async fn public_anchor_status(
+    db: &PgPool,
+    doc_id: uuid::Uuid,
+    hash_hex: &str,
+    doc_arweave_tx: Option<String>,
+) -> Result<serde_json::Value, AppError> {
+    let mut proof = None;
+    let mut tx_id = doc_arweave_tx;
+    if tx_id.is_none() {
+        let leaf = sqlx::query(
+            r#"
+            select b.arweave_tx, l.proof_json
+            from anchor_leaves l
+            join anchor_batches b on b.id = l.batch_id
+            where l.doc_id = $1
+              and l.kind = 'document'
+              and l.hash_hex = $2
+              and b.arweave_tx is not null
+            order by l.created_at asc
+            limit 1
+            "#,
+        )
+        .bind(doc_id)
+        .bind(hash_hex)
+        .fetch_optional(db)
         .await
         .map_err(|e| AppError::Internal(e.to_string()))?;
+        if let Some(leaf) = leaf {
+            tx_id = leaf.get("arweave_tx");
+            proof = leaf.get::<Option<serde_json::Value>, _>("proof_json");
+        }
+    }
 
-    Ok(Json(json!({
-        "verified": row.is_some(),
-        "hash": hash_hex
-    })))
+    let Some(mut tx_id) = tx_id else {
+        return Ok(json!({ "arweave_tx": null, "status": "not_anchored" }));
+    };
+    if crate::arweave::ArweaveClient::is_simulated_tx(&tx_id) {
+        return Ok(json!({ "arweave_tx": tx_id, "status": "simulated" }));
+    }
+
+    // Bounded so a bad replacement chain cannot loop forever.
+    for _ in 0..8 {
+        let row = sqlx::query(
+            r#"
+            select status, confirmations, block_height, confirmed_at, replaced_by_tx
+            from arweave_anchors
+            where tx_id = $1
+            "#,
+        )
+        .bind(&tx_id)
+        .fetch_optional(db)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+        let Some(row) = row else {
+            break;
+        };
+        if let Some(next) = row.get::<Option<String>, _>("replaced_by_tx") {
+            tx_id = next;
+            continue;
+        }
+        return Ok(json!({
+            "arweave_tx": tx_id,
+            "status": row.get::<String, _>("status"),
+            "confirmations": row.get::<Option<i64>, _>("confirmations"),
+            "block_height": row.get::<Option<i64>, _>("block_height"),
+            "confirmed_at": row.get::<Option<chrono::DateTime<chrono::Utc>>, _>("confirmed_at"),
+            "merkle_proof": proof
+        }));
+    }
+
+    Ok(json!({
+        "arweave_tx": tx_id,
+        "status": "untracked",
+        "merkle_proof": proof
+    }))
 }
 
 #[cfg(test)]
@@ -7495,10 +12723,20 @@ mod tests {
         admin_console_password_min_length, admin_totp_otpauth_url, base32_decode,
         base32_encode, bool_from_form_text, build_share_event_payload, compute_totp_code,
         document_sign_message, hash_admin_password, normalize_annotation_fields,
-        normalize_totp_code, validate_admin_password_strength, verify_admin_password,
+        normalize_totp_code, public_signature_entry, scan_quarantine_reason, share_reminder_at,
+        validate_admin_password_strength, verify_admin_password,
         verify_totp_code, wallet_can_access_document,
     };
+    use super::{
+        assemble_chunked_upload, pqc_sha3, ChunkedUploadRecord, StreamKeyV1, STREAM_SEGMENT_SIZE,
+    };
+    use crate::crypto::canonical::mlkem_generate_keypair_b64;
     use crate::models::SignerAnnotationField;
+    use crate::sanitizer::mime_check::MimeScanner;
+    use crate::sanitizer::{ScanFinding, ScanPipeline, ScanReport, ScanVerdict};
+    use crate::storage::local::LocalDiskStorage;
+    use crate::storage::BlobStore;
+    use sha2::{Digest, Sha256};
 
     #[test]
     fn owner_or_share_recipient_can_access_document() {
@@ -7605,4 +12843,331 @@ mod tests {
         assert!(otpauth.contains("secret="));
         assert!(otpauth.contains("issuer=TIDBIT-share-WEAVE"));
     }
+
+    #[test]
+    fn public_signature_entries_hide_redacted_owner_and_guest_identity() {
+        let now = chrono::Utc::now();
+        let owner_sign = serde_json::json!({
+            "hash_hex": "ab",
+            "verification": { "signature_type": "evm_personal_sign" }
+        });
+        let guest_sign = serde_json::json!({
+            "hash_hex": "ab",
+            "signer_name": "Jordan Example",
+            "signer_email": "jordan@example.com",
+            "signer_wallet": "0xguest",
+            "completion_signature_type": "typed_name"
+        });
+
+        let entry = public_signature_entry("SIGN", "0xOwner", &owner_sign, now, None).unwrap();
+        assert_eq!(entry["signature_type"], "evm_personal_sign");
+        assert_eq!(entry["signer_wallet"], "0xOwner");
+
+        let entry =
+            public_signature_entry("SIGN", "0xOwner", &owner_sign, now, Some("0xowner")).unwrap();
+        assert!(entry["signer_wallet"].is_null());
+
+        let entry = public_signature_entry(
+            "ENVELOPE_COMPLETED",
+            "guest-envelope:1",
+            &guest_sign,
+            now,
+            Some("0xowner"),
+        )
+        .unwrap();
+        assert_eq!(entry["signature_type"], "typed_name");
+        assert_eq!(entry["signer_wallet"], "0xguest");
+        assert!(!entry.to_string().contains("jordan"));
+
+        assert!(public_signature_entry("VIEW", "0xowner", &owner_sign, now, None).is_none());
+    }
+
+    #[test]
+    fn suspicious_scans_quarantine_unless_only_active_content() {
+        let finding = |scanner, rule: &str| ScanFinding {
+            scanner,
+            verdict: ScanVerdict::Suspicious,
+            rule: rule.to_string(),
+            detail: String::new(),
+        };
+        let mut report = ScanReport {
+            verdict: ScanVerdict::Suspicious,
+            filename: None,
+            declared_mime: "application/pdf".into(),
+            detected_mime: Some("image/png".into()),
+            scanners: vec!["mime", "active_content"],
+            findings: vec![finding("active_content", "pdf_javascript")],
+            errors: Vec::new(),
+        };
+        assert_eq!(scan_quarantine_reason(&report), None);
+
+        report.findings.push(finding("mime", "mime_mismatch"));
+        assert_eq!(
+            scan_quarantine_reason(&report).as_deref(),
+            Some("scan: mime_mismatch")
+        );
+    }
+
+    #[tokio::test]
+    async fn chunked_upload_with_mismatched_content_is_quarantined() {
+        let root = std::env::temp_dir().join(format!("tidbit-chunked-{}", uuid::Uuid::new_v4()));
+        let storage = LocalDiskStorage::new(&root);
+        let keys = mlkem_generate_keypair_b64();
+
+        // A PNG declared as a PDF, long enough to span two segments.
+        let mut plaintext = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR".to_vec();
+        plaintext.resize(STREAM_SEGMENT_SIZE + 100, 0);
+        let upload = ChunkedUploadRecord {
+            id: uuid::Uuid::new_v4(),
+            owner_wallet: "0xowner".into(),
+            doc_id: uuid::Uuid::new_v4(),
+            parent_id: None,
+            version: 1,
+            label: Some("report.pdf".into()),
+            mime_type: "application/pdf".into(),
+            file_size: plaintext.len() as i64,
+            segment_count: 2,
+            declared_hash_hex: None,
+            change_summary: None,
+            anchor_to_arweave: false,
+            stream_key: StreamKeyV1::new_mlkem("0xowner", &keys.pk_b64).unwrap(),
+            status: "open".into(),
+        };
+        let cipher = upload.stream_key.unlock(&keys.sk_b64).unwrap();
+        let mut segments = Vec::new();
+        for (index, chunk) in plaintext.chunks(STREAM_SEGMENT_SIZE).enumerate() {
+            let index = index as i64;
+            let ciphertext = cipher
+                .encrypt_segment(index as u64, index == 1, chunk)
+                .unwrap();
+            storage
+                .put_object(
+                    &upload.segment_path(index),
+                    &ciphertext,
+                    "application/octet-stream",
+                )
+                .await
+                .unwrap();
+            segments.push((index, hex::encode(pqc_sha3::sha3_256_bytes(&ciphertext))));
+        }
+
+        let pipeline = ScanPipeline::new(vec![Box::new(MimeScanner)], true);
+        let mut scan = pipeline.windowed(&upload.mime_type, upload.label.as_deref(), 0);
+        let assembled = assemble_chunked_upload(&storage, &upload, &cipher, &segments, &mut scan)
+            .await
+            .unwrap();
+        assert_eq!(
+            assembled.sha256_hex,
+            hex::encode(Sha256::digest(&plaintext))
+        );
+
+        // Every byte was scanned, so even a fail-closed pipeline accepts it.
+        let report = scan.finish().await;
+        assert!(report.errors.is_empty());
+        assert!(!report.is_malicious());
+        assert_eq!(
+            scan_quarantine_reason(&report).as_deref(),
+            Some("scan: mime_mismatch")
+        );
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    /// Runs against a scratch database with `migrations/` applied when
+    /// `TEST_DATABASE_URL` is set, e.g.
+    /// `TEST_DATABASE_URL=postgres://postgres@127.0.0.1/postgres cargo test session`.
+    #[tokio::test]
+    async fn rotated_access_session_ids_stop_working_without_revoking_the_login() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL is not set");
+            return;
+        };
+        let db = crate::sqlx::postgres::PgPoolOptions::new()
+            .max_connections(2)
+            .connect(&url)
+            .await
+            .unwrap();
+        super::ensure_runtime_schema(&db).await.unwrap();
+        let auth = crate::identity_web::AuthState::new(db.clone());
+
+        let wallet = format!("0x{}", uuid::Uuid::new_v4().simple());
+        let login = auth
+            .bind_wallet(
+                uuid::Uuid::new_v4().to_string(),
+                wallet,
+                "evm",
+                Some("device-1"),
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        let refresh = login.refresh_token.as_deref().unwrap();
+        let rotated = auth
+            .rotate_session(refresh, Some("device-1"), None, None)
+            .await
+            .unwrap()
+            .unwrap();
+
+        // Well past the grace period, as for an event stream left open
+        // across a rotation.
+        crate::sqlx::query(
+            "update wallet_sessions set revoked_at = now() - interval '10 minutes' where session_id = $1",
+        )
+        .bind(&login.session_id)
+        .execute(&db)
+        .await
+        .unwrap();
+        let stale = auth
+            .get_session(&login.session_id, Some("device-1"))
+            .await
+            .unwrap();
+        assert!(stale.is_none());
+        assert!(auth
+            .get_session(&rotated.session_id, Some("device-1"))
+            .await
+            .unwrap()
+            .is_some());
+
+        // Replaying the old refresh token still signs the login out.
+        assert!(auth
+            .rotate_session(refresh, Some("device-1"), None, None)
+            .await
+            .is_err());
+        assert!(auth
+            .get_session(&rotated.session_id, Some("device-1"))
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn idle_devices_still_count_against_the_session_policy() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL is not set");
+            return;
+        };
+        let db = crate::sqlx::postgres::PgPoolOptions::new()
+            .max_connections(2)
+            .connect(&url)
+            .await
+            .unwrap();
+        super::ensure_runtime_schema(&db).await.unwrap();
+        let auth = crate::identity_web::AuthState::new(db.clone());
+        let wallet = format!("0x{}", uuid::Uuid::new_v4().simple());
+        crate::identity_web::devices::save_session_policy(
+            &db,
+            &wallet,
+            crate::identity_web::devices::SessionPolicy::Single,
+        )
+        .await
+        .unwrap();
+
+        let idle = auth
+            .bind_wallet(
+                uuid::Uuid::new_v4().to_string(),
+                wallet.clone(),
+                "evm",
+                Some("device-1"),
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        // The access session lapses; the refresh token is still good.
+        crate::sqlx::query(
+            "update wallet_sessions set expires_at = now() - interval '1 minute' where session_id = $1",
+        )
+        .bind(&idle.session_id)
+        .execute(&db)
+        .await
+        .unwrap();
+        auth.bind_wallet(
+            uuid::Uuid::new_v4().to_string(),
+            wallet,
+            "evm",
+            Some("device-2"),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+
+        let comeback = auth
+            .rotate_session(
+                idle.refresh_token.as_deref().unwrap(),
+                Some("device-1"),
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(comeback.is_none());
+    }
+
+    #[test]
+    fn share_reminders_land_before_expiry_or_not_at_all() {
+        let now = chrono::Utc::now();
+        let expires_at = now + chrono::Duration::hours(72);
+        assert_eq!(
+            share_reminder_at(expires_at, 24, now),
+            Some(now + chrono::Duration::hours(48))
+        );
+        assert_eq!(share_reminder_at(expires_at, 72, now), None);
+        assert_eq!(share_reminder_at(expires_at, 0, now), None);
+    }
+
+    #[tokio::test]
+    async fn stale_anchor_claims_are_released_unless_the_batch_was_anchored() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("skipping: TEST_DATABASE_URL is not set");
+            return;
+        };
+        let db = crate::sqlx::postgres::PgPoolOptions::new()
+            .max_connections(2)
+            .connect(&url)
+            .await
+            .unwrap();
+        super::ensure_runtime_schema(&db).await.unwrap();
+
+        // One batch crashed before its root was stored, one was anchored.
+        let (crashed, anchored) = (uuid::Uuid::new_v4(), uuid::Uuid::new_v4());
+        crate::sqlx::query(
+            "insert into anchor_batches (id, root_hex, leaf_count, arweave_tx) values ($1, '00', 1, 'tx')",
+        )
+        .bind(anchored)
+        .execute(&db)
+        .await
+        .unwrap();
+        let mut leaves = Vec::new();
+        for (batch_id, age) in [(crashed, 20), (anchored, 20), (crashed, 1)] {
+            let leaf_id = uuid::Uuid::new_v4();
+            crate::sqlx::query(
+                "insert into anchor_leaves (id, doc_id, kind, hash_hex, batch_id, claimed_at) values ($1, $2, 'event', '00', $3, now() - make_interval(mins => $4))",
+            )
+            .bind(leaf_id)
+            .bind(uuid::Uuid::new_v4())
+            .bind(batch_id)
+            .bind(age)
+            .execute(&db)
+            .await
+            .unwrap();
+            leaves.push(leaf_id);
+        }
+
+        super::release_stale_anchor_claims(&db, std::time::Duration::from_secs(600))
+            .await
+            .unwrap();
+        let mut claimed = Vec::new();
+        for leaf_id in &leaves {
+            let row = crate::sqlx::query("select batch_id from anchor_leaves where id = $1")
+                .bind(leaf_id)
+                .fetch_one(&db)
+                .await
+                .unwrap();
+            let batch_id: Option<uuid::Uuid> = crate::sqlx::Row::get(&row, "batch_id");
+            claimed.push(batch_id);
+        }
+        assert_eq!(claimed, vec![None, Some(anchored), Some(crashed)]);
+    }
 }
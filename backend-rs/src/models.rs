@@ -32,6 +32,27 @@ pub struct SignRequest {
     pub signature: String,
     pub signature_type: Option<String>,
     pub pq_public_key_b64: Option<String>,
+    /// The ML-DSA half of a `hybrid_mldsa65` signature; `signature` is the wallet half.
+    pub pq_signature: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct PqKeyChallengeRequest {
+    pub public_key_b64: String,
+}
+
+#[derive(Deserialize)]
+pub struct PqKeyRegisterRequest {
+    pub public_key_b64: String,
+    /// The `issued_at` from the challenge; the binding message is rebuilt from it.
+    pub issued_at: String,
+    pub wallet_signature: String,
+    pub pq_signature_b64: String,
+}
+
+#[derive(Deserialize)]
+pub struct PqKeyRevokeRequest {
+    pub reason: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -48,6 +69,16 @@ pub struct ShareRequest {
     pub download_allowed: Option<bool>,
     pub allow_guest_sign: Option<bool>,
     pub anchor_to_arweave: Option<bool>,
+    /// Hours before expiry to resend the email/SMS invite; 0 disables it.
+    pub remind_before_expiry_hours: Option<i64>,
+    /// If the share is still unsigned this many hours after it was sent,
+    /// invite the `escalate_to_*` recipient as well.
+    pub escalate_after_hours: Option<i64>,
+    pub escalate_to_wallet: Option<String>,
+    pub escalate_to_chain: Option<String>,
+    pub escalate_to_name: Option<String>,
+    pub escalate_to_email: Option<String>,
+    pub escalate_to_phone: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -72,6 +103,7 @@ pub struct PublicEnvelopeSignRequest {
     pub wallet_address: Option<String>,
     pub signature: Option<String>,
     pub pq_public_key_b64: Option<String>,
+    pub pq_signature: Option<String>,
     pub annotation_fields: Option<Vec<SignerAnnotationField>>,
 }
 
@@ -103,6 +135,82 @@ pub struct AgentVersionRequest {
     pub anchor_to_arweave: Option<bool>,
 }
 
+#[derive(Deserialize)]
+pub struct ChunkedUploadInitRequest {
+    pub file_size: i64,
+    pub label: Option<String>,
+    pub mime_type: Option<String>,
+    /// Create a new version of this document instead of a new one.
+    pub parent_id: Option<uuid::Uuid>,
+    pub anchor_to_arweave: Option<bool>,
+    pub change_summary: Option<String>,
+    /// Checked against the reassembled plaintext on completion.
+    pub plaintext_sha3_256_hex: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct QuarantineRequest {
+    pub reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct SigningWorkflowRequest {
+    /// `sequential` (default) or `parallel`.
+    pub routing: Option<String>,
+    pub signers: Vec<WorkflowSignerRequest>,
+    pub note: Option<String>,
+    pub expires_in_hours: Option<i64>,
+}
+
+/// One of `wallet`, `email`/`phone`, or `agent_id` says how to reach the signer.
+#[derive(Deserialize)]
+pub struct WorkflowSignerRequest {
+    pub role: Option<String>,
+    /// Signers with the same step sign in parallel; defaults from `routing`.
+    pub step: Option<i32>,
+    pub required: Option<bool>,
+    pub name: Option<String>,
+    pub wallet: Option<String>,
+    pub chain: Option<String>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub agent_id: Option<uuid::Uuid>,
+}
+
+#[derive(Deserialize)]
+pub struct WorkflowCancelRequest {
+    pub reason: Option<String>,
+}
+
+/// `event_types` filters by custody event type; `INBOX_*` style prefixes are
+/// allowed and an empty list means every event.
+#[derive(Deserialize)]
+pub struct WebhookSubscriptionRequest {
+    pub url: String,
+    pub event_types: Option<Vec<String>>,
+    pub pq_signed: Option<bool>,
+}
+
+/// `mode` is `single`, `max_devices` or `unlimited`; `max_devices` is
+/// required with the second.
+#[derive(Deserialize)]
+pub struct SessionPolicyRequest {
+    pub mode: String,
+    pub max_devices: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct SessionRefreshRequest {
+    pub refresh_token: String,
+}
+
+/// Without `device_id` the calling device is trusted.
+#[derive(Deserialize)]
+pub struct TrustedDeviceRequest {
+    pub device_id: Option<String>,
+    pub name: String,
+}
+
 #[derive(Deserialize)]
 pub struct InboxActionRequest {
     pub action: String,
@@ -29,7 +29,7 @@ pub async fn share_envelope_v2(
         .await?
         .ok_or_else(|| AppError::Auth("invalid session".into()))?;
 
-    record_share_event(session.wallet, req.to_wallet, req.envelope_id)?;
+    record_share_event(session.wallet, req.to_wallet, req.envelope_id).await?;
 
     Ok(Json(ShareResponse { ok: true }))
 }
@@ -0,0 +1,138 @@
+// src/sanitizer/active_content.rs
+
+use anyhow::Result;
+use async_trait::async_trait;
+
+use super::{ScanFinding, ScanInput, ScanVerdict, Scanner};
+
+const PDF_MAGIC: &[u8] = b"%PDF-";
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+const OLE_MAGIC: &[u8] = b"\xD0\xCF\x11\xE0\xA1\xB1\x1A\xE1";
+const RTF_MAGIC: &[u8] = b"{\\rtf";
+
+/// PDF names that run script, launch programs or carry payloads.
+const PDF_ACTIVE_NAMES: &[(&str, &str)] = &[
+    ("/JavaScript", "pdf_javascript"),
+    ("/JS", "pdf_javascript"),
+    ("/OpenAction", "pdf_open_action"),
+    ("/AA", "pdf_additional_actions"),
+    ("/Launch", "pdf_launch_action"),
+    ("/EmbeddedFile", "pdf_embedded_file"),
+    ("/RichMedia", "pdf_rich_media"),
+    ("/XFA", "pdf_xfa_form"),
+];
+
+/// OOXML part names, matched against the zip's local file headers.
+const OOXML_ACTIVE_PARTS: &[(&str, &str)] = &[
+    ("vbaProject.bin", "office_macro"),
+    ("activeX/", "office_activex"),
+    ("oleObject", "office_embedded_object"),
+];
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack.windows(needle.len()).any(|w| w == needle)
+}
+
+/// PDF name tokens end at whitespace or a delimiter, so `/JS` must not match
+/// `/JSON`.
+fn contains_pdf_name(bytes: &[u8], name: &str) -> bool {
+    let name = name.as_bytes();
+    bytes.windows(name.len()).enumerate().any(|(i, w)| {
+        w == name
+            && bytes
+                .get(i + name.len())
+                .is_none_or(|next| next.is_ascii_whitespace() || b"/<>[]()%".contains(next))
+    })
+}
+
+fn utf16le(text: &str) -> Vec<u8> {
+    text.encode_utf16().flat_map(u16::to_le_bytes).collect()
+}
+
+/// Detects macros, scripts and embedded objects in PDF, Office and RTF files.
+/// These are flagged suspicious rather than rejected; plenty of legitimate
+/// documents carry forms or macros.
+pub struct ActiveContentScanner;
+
+impl ActiveContentScanner {
+    fn findings(bytes: &[u8]) -> Vec<(&'static str, String)> {
+        let mut found: Vec<(&'static str, String)> = Vec::new();
+        let mut push = |rule: &'static str, detail: String| {
+            if !found.iter().any(|(r, _)| *r == rule) {
+                found.push((rule, detail));
+            }
+        };
+
+        if bytes.starts_with(PDF_MAGIC) {
+            for (name, rule) in PDF_ACTIVE_NAMES {
+                if contains_pdf_name(bytes, name) {
+                    push(rule, format!("PDF contains {name}"));
+                }
+            }
+        } else if bytes.starts_with(ZIP_MAGIC) {
+            for (part, rule) in OOXML_ACTIVE_PARTS {
+                if contains(bytes, part.as_bytes()) {
+                    push(rule, format!("archive contains {part}"));
+                }
+            }
+        } else if bytes.starts_with(OLE_MAGIC) {
+            // Word keeps the project under Macros/VBA, Excel under
+            // _VBA_PROJECT_CUR; both hold a _VBA_PROJECT stream.
+            if contains(bytes, &utf16le("_VBA_PROJECT")) {
+                push("office_macro", "compound file has a VBA project".into());
+            }
+        } else if bytes.starts_with(RTF_MAGIC)
+            && (contains(bytes, b"\\objdata") || contains(bytes, b"\\object"))
+        {
+            push("rtf_embedded_object", "RTF embeds an OLE object".into());
+        }
+
+        found
+    }
+}
+
+#[async_trait]
+impl Scanner for ActiveContentScanner {
+    fn name(&self) -> &'static str {
+        "active_content"
+    }
+
+    async fn scan(&self, input: &ScanInput<'_>) -> Result<Vec<ScanFinding>> {
+        Ok(Self::findings(input.bytes)
+            .into_iter()
+            .map(|(rule, detail)| ScanFinding {
+                scanner: "active_content",
+                verdict: ScanVerdict::Suspicious,
+                rule: rule.to_string(),
+                detail,
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(bytes: &[u8]) -> Vec<&'static str> {
+        ActiveContentScanner::findings(bytes)
+            .into_iter()
+            .map(|(rule, _)| rule)
+            .collect()
+    }
+
+    #[test]
+    fn detects_pdf_script_and_office_macros() {
+        let pdf = b"%PDF-1.7\n1 0 obj << /OpenAction << /S /JavaScript /JS (app.alert(1)) >> >>";
+        assert_eq!(rules(pdf), vec!["pdf_javascript", "pdf_open_action"]);
+        assert!(rules(b"%PDF-1.7\n<< /Type /Catalog /JSON 1 >>").is_empty());
+
+        let docm = b"PK\x03\x04\x14\0\0\0word/vbaProject.bin\0";
+        assert_eq!(rules(docm), vec!["office_macro"]);
+        assert!(rules(b"PK\x03\x04\x14\0\0\0word/document.xml").is_empty());
+
+        let mut xls = OLE_MAGIC.to_vec();
+        xls.extend(utf16le("_VBA_PROJECT_CUR"));
+        assert_eq!(rules(&xls), vec!["office_macro"]);
+    }
+}
@@ -0,0 +1,142 @@
+// src/sanitizer/clamav.rs
+
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use super::{ScanFinding, ScanInput, ScanVerdict, Scanner};
+
+/// clamd's default `StreamMaxLength` is 25 MiB; chunks stay well under it.
+const INSTREAM_CHUNK: usize = 64 * 1024;
+
+#[derive(Debug, Clone)]
+enum ClamdEndpoint {
+    Unix(String),
+    Tcp(String),
+}
+
+/// Streams uploads to a clamd daemon with `zINSTREAM`.
+pub struct ClamdScanner {
+    endpoint: ClamdEndpoint,
+    timeout: Duration,
+}
+
+impl ClamdScanner {
+    /// `CLAMD_SOCKET` (unix socket path) takes precedence over `CLAMD_ADDR`
+    /// (`host:port`). `CLAMD_TIMEOUT_SECS` defaults to 30.
+    pub fn from_env() -> Option<Self> {
+        let env = |key: &str| {
+            std::env::var(key)
+                .ok()
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        let endpoint = match (env("CLAMD_SOCKET"), env("CLAMD_ADDR")) {
+            (Some(path), _) => ClamdEndpoint::Unix(path),
+            (None, Some(addr)) => ClamdEndpoint::Tcp(addr),
+            (None, None) => return None,
+        };
+        let timeout = env("CLAMD_TIMEOUT_SECS")
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(30);
+
+        Some(Self {
+            endpoint,
+            timeout: Duration::from_secs(timeout),
+        })
+    }
+
+    async fn instream(&self, bytes: &[u8]) -> Result<String> {
+        match &self.endpoint {
+            #[cfg(unix)]
+            ClamdEndpoint::Unix(path) => {
+                let stream = tokio::net::UnixStream::connect(path)
+                    .await
+                    .with_context(|| format!("connect clamd socket {path}"))?;
+                instream(stream, bytes).await
+            }
+            #[cfg(not(unix))]
+            ClamdEndpoint::Unix(_) => bail!("CLAMD_SOCKET needs a unix platform"),
+            ClamdEndpoint::Tcp(addr) => {
+                let stream = tokio::net::TcpStream::connect(addr)
+                    .await
+                    .with_context(|| format!("connect clamd at {addr}"))?;
+                instream(stream, bytes).await
+            }
+        }
+    }
+}
+
+async fn instream<S>(mut stream: S, bytes: &[u8]) -> Result<String>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    stream.write_all(b"zINSTREAM\0").await?;
+    for chunk in bytes.chunks(INSTREAM_CHUNK) {
+        stream
+            .write_all(&(chunk.len() as u32).to_be_bytes())
+            .await?;
+        stream.write_all(chunk).await?;
+    }
+    stream.write_all(&0u32.to_be_bytes()).await?;
+    stream.flush().await?;
+
+    let mut reply = Vec::new();
+    stream.read_to_end(&mut reply).await?;
+    Ok(String::from_utf8_lossy(&reply)
+        .trim_end_matches(['\0', '\n'])
+        .to_string())
+}
+
+/// `stream: OK`, `stream: <signature> FOUND` or `<message> ERROR`.
+fn parse_reply(reply: &str) -> Result<Option<String>> {
+    let body = reply.strip_prefix("stream:").unwrap_or(reply).trim();
+    if body == "OK" {
+        return Ok(None);
+    }
+    if let Some(signature) = body.strip_suffix(" FOUND") {
+        return Ok(Some(signature.trim().to_string()));
+    }
+    Err(anyhow!("clamd: {body}"))
+}
+
+#[async_trait]
+impl Scanner for ClamdScanner {
+    fn name(&self) -> &'static str {
+        "clamav"
+    }
+
+    async fn scan(&self, input: &ScanInput<'_>) -> Result<Vec<ScanFinding>> {
+        let reply = match tokio::time::timeout(self.timeout, self.instream(input.bytes)).await {
+            Ok(reply) => reply?,
+            Err(_) => bail!("clamd timed out after {}s", self.timeout.as_secs()),
+        };
+
+        Ok(parse_reply(&reply)?
+            .map(|signature| ScanFinding {
+                scanner: "clamav",
+                verdict: ScanVerdict::Malicious,
+                detail: format!("clamd matched {signature}"),
+                rule: signature,
+            })
+            .into_iter()
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_reply;
+
+    #[test]
+    fn parses_clamd_replies() {
+        assert_eq!(parse_reply("stream: OK").unwrap(), None);
+        assert_eq!(
+            parse_reply("stream: Win.Test.EICAR_HDB-1 FOUND").unwrap(),
+            Some("Win.Test.EICAR_HDB-1".into())
+        );
+        assert!(parse_reply("INSTREAM size limit exceeded. ERROR").is_err());
+    }
+}
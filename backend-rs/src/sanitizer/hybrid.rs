@@ -2,12 +2,21 @@
 
 use anyhow::Result;
 
-/// Extremely minimal sanitizer placeholder.
-/// Later we’ll plug in all your multi-engine checks.
-pub async fn hybrid_sanitize(bytes: &[u8], mime_type: &str, url: Option<&str>) -> Result<()> {
-    println!(
-        "(sanitizer) size={} bytes, mime={mime_type}, url={url:?}",
-        bytes.len()
-    );
-    Ok(())
+use super::{ScanInput, ScanPipeline, ScanReport};
+
+/// One-shot scan with the pipeline configured from the environment. The
+/// server keeps a shared pipeline in `AppState`; this is for callers without
+/// one. `source` is a filename or URL, recorded for scanners that use it.
+pub async fn hybrid_sanitize(
+    bytes: &[u8],
+    mime_type: &str,
+    source: Option<&str>,
+) -> Result<ScanReport> {
+    Ok(ScanPipeline::from_env()
+        .scan(&ScanInput {
+            bytes,
+            declared_mime: mime_type,
+            filename: source,
+        })
+        .await)
 }
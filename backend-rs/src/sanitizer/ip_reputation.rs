@@ -0,0 +1,452 @@
+// src/sanitizer/ip_reputation.rs
+
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use ipnet::IpNet;
+use serde::Serialize;
+
+use super::ipinfo::IpInfoProvider;
+
+/// Anonymising-network categories a provider can report for an address.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct IpReputation {
+    pub tor: bool,
+    pub vpn: bool,
+    pub proxy: bool,
+    pub hosting: bool,
+}
+
+impl IpReputation {
+    /// Maps ipinfo (`tor`, `relay`, ...) and MaxMind Anonymous IP
+    /// (`is_tor_exit_node`, ...) field names onto our categories. Returns
+    /// false for columns we don't track.
+    fn set(&mut self, field: &str, value: bool) -> bool {
+        let slot = match field.trim().to_ascii_lowercase().as_str() {
+            "tor" | "is_tor_exit_node" => &mut self.tor,
+            "vpn" | "is_anonymous_vpn" => &mut self.vpn,
+            "proxy" | "relay" | "is_public_proxy" | "is_residential_proxy" => &mut self.proxy,
+            "hosting" | "is_hosting_provider" => &mut self.hosting,
+            _ => return false,
+        };
+        *slot |= value;
+        true
+    }
+
+    pub fn categories(&self) -> Vec<&'static str> {
+        [
+            ("tor", self.tor),
+            ("vpn", self.vpn),
+            ("proxy", self.proxy),
+            ("hosting", self.hosting),
+        ]
+        .into_iter()
+        .filter_map(|(name, set)| set.then_some(name))
+        .collect()
+    }
+}
+
+fn truthy(value: &str) -> bool {
+    matches!(
+        value.trim().to_ascii_lowercase().as_str(),
+        "true" | "t" | "1" | "yes" | "y"
+    )
+}
+
+fn json_truthy(value: &serde_json::Value) -> bool {
+    match value {
+        serde_json::Value::Bool(b) => *b,
+        serde_json::Value::Number(n) => n.as_f64().is_some_and(|n| n != 0.0),
+        serde_json::Value::String(s) => truthy(s),
+        _ => false,
+    }
+}
+
+#[async_trait]
+pub trait IpReputationProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// `Ok(None)` means the address is not listed by this provider.
+    async fn lookup(&self, ip: IpAddr) -> Result<Option<IpReputation>>;
+}
+
+pub type SharedIpReputation = Arc<dyn IpReputationProvider>;
+
+/// `IP_REPUTATION_DB` points at a local `.mmdb` or `.csv` database and is
+/// loaded once at boot. Without it, `IPINFO_TOKEN` enables the ipinfo.io
+/// privacy API instead. Neither means public links are not checked.
+pub fn ip_reputation_from_env() -> Result<Option<SharedIpReputation>> {
+    let env = |key: &str| {
+        std::env::var(key)
+            .ok()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+
+    if let Some(path) = env("IP_REPUTATION_DB") {
+        let provider: SharedIpReputation = if path.ends_with(".mmdb") {
+            Arc::new(MmdbReputation::open(&path)?)
+        } else if path.ends_with(".csv") {
+            Arc::new(CsvReputation::open(&path)?)
+        } else {
+            bail!("IP_REPUTATION_DB must be a .mmdb or .csv file, got {path}");
+        };
+        return Ok(Some(provider));
+    }
+
+    Ok(env("IPINFO_TOKEN").map(|token| Arc::new(IpInfoProvider::new(token)) as SharedIpReputation))
+}
+
+// ---------------------------------------------------------------
+// CLIENT ADDRESS
+// ---------------------------------------------------------------
+
+/// Reverse proxies whose `X-Forwarded-For` entries are believed.
+/// `TRUSTED_PROXIES` is a comma-separated list of addresses or CIDR ranges.
+/// Empty means the socket peer is always the client.
+#[derive(Debug, Clone, Default)]
+pub struct TrustedProxies {
+    nets: Vec<IpNet>,
+}
+
+impl TrustedProxies {
+    pub fn from_env() -> Result<Self> {
+        Self::parse(&std::env::var("TRUSTED_PROXIES").unwrap_or_default())
+    }
+
+    pub fn parse(list: &str) -> Result<Self> {
+        let nets = list
+            .split(',')
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(|value| {
+                value
+                    .parse::<IpNet>()
+                    .or_else(|_| IpAddr::from_str(value).map(IpNet::from))
+                    .with_context(|| format!("TRUSTED_PROXIES: bad address or range {value}"))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { nets })
+    }
+
+    /// For the boot log.
+    pub fn describe(&self) -> String {
+        if self.nets.is_empty() {
+            return "none".into();
+        }
+        self.nets
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn contains(&self, ip: IpAddr) -> bool {
+        let ip = match ip {
+            IpAddr::V6(v6) => v6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(ip),
+            v4 => v4,
+        };
+        self.nets.iter().any(|net| net.contains(&ip))
+    }
+
+    /// The address of whoever opened the connection chain. Starting from the
+    /// socket peer, each trusted proxy hands over to the hop it appended to
+    /// `X-Forwarded-For`; the first untrusted hop is the client. Entries left
+    /// of that are client-supplied and ignored.
+    pub fn client_ip(&self, peer: IpAddr, forwarded_for: Option<&str>) -> IpAddr {
+        let mut client = peer;
+        let hops = forwarded_for.unwrap_or("").rsplit(',').map(str::trim);
+        for hop in hops {
+            if !self.contains(client) {
+                break;
+            }
+            match hop.parse::<IpAddr>() {
+                Ok(ip) => client = ip,
+                Err(_) => break,
+            }
+        }
+        client
+    }
+}
+
+// ---------------------------------------------------------------
+// MMDB
+// ---------------------------------------------------------------
+
+/// A MaxMind-format database with flat privacy fields, such as ipinfo's
+/// privacy detection download or GeoIP2 Anonymous IP.
+pub struct MmdbReputation {
+    reader: maxminddb::Reader<Vec<u8>>,
+}
+
+impl MmdbReputation {
+    pub fn open(path: &str) -> Result<Self> {
+        let reader = maxminddb::Reader::open_readfile(path)
+            .with_context(|| format!("open ip reputation database {path}"))?;
+        Ok(Self { reader })
+    }
+}
+
+#[async_trait]
+impl IpReputationProvider for MmdbReputation {
+    fn name(&self) -> &'static str {
+        "mmdb"
+    }
+
+    async fn lookup(&self, ip: IpAddr) -> Result<Option<IpReputation>> {
+        let record: BTreeMap<String, serde_json::Value> = match self.reader.lookup(ip) {
+            Ok(record) => record,
+            Err(maxminddb::MaxMindDBError::AddressNotFoundError(_)) => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut reputation = IpReputation::default();
+        for (field, value) in &record {
+            reputation.set(field, json_truthy(value));
+        }
+        Ok(Some(reputation))
+    }
+}
+
+// ---------------------------------------------------------------
+// CSV
+// ---------------------------------------------------------------
+
+#[derive(Debug)]
+struct IpRange {
+    start: u128,
+    end: u128,
+    reputation: IpReputation,
+}
+
+/// Addresses compare as IPv6 so v4 and v6 ranges share one sorted table.
+fn ip_key(ip: IpAddr) -> u128 {
+    match ip {
+        IpAddr::V4(v4) => u128::from(v4.to_ipv6_mapped()),
+        IpAddr::V6(v6) => u128::from(v6),
+    }
+}
+
+/// A CSV export with either a `network` (CIDR) column or `start_ip` and
+/// `end_ip` columns, plus boolean category columns. Ranges are kept sorted
+/// in memory and are expected not to overlap.
+pub struct CsvReputation {
+    ranges: Vec<IpRange>,
+}
+
+impl CsvReputation {
+    pub fn open(path: &str) -> Result<Self> {
+        let file = std::fs::File::open(Path::new(path))
+            .with_context(|| format!("open ip reputation database {path}"))?;
+        Self::from_reader(file).with_context(|| format!("load ip reputation database {path}"))
+    }
+
+    fn from_reader<R: std::io::Read>(reader: R) -> Result<Self> {
+        let mut csv = csv::Reader::from_reader(reader);
+        let headers: Vec<String> = csv
+            .headers()?
+            .iter()
+            .map(|h| h.trim().to_ascii_lowercase())
+            .collect();
+        let column = |name: &str| headers.iter().position(|h| h == name);
+        let network = column("network");
+        let bounds = column("start_ip").zip(column("end_ip"));
+        if network.is_none() && bounds.is_none() {
+            bail!("expected a network column or start_ip and end_ip columns");
+        }
+
+        let mut ranges = Vec::new();
+        for (line, record) in csv.records().enumerate() {
+            let record = record?;
+            let field = |idx: usize| record.get(idx).unwrap_or("").trim();
+            let (start, end) = match (network, bounds) {
+                (Some(idx), _) => {
+                    let value = field(idx);
+                    let net =
+                        match value.parse::<IpNet>() {
+                            Ok(net) => net,
+                            Err(_) => IpNet::from(IpAddr::from_str(value).with_context(|| {
+                                format!("row {}: bad network {value}", line + 2)
+                            })?),
+                        };
+                    (ip_key(net.network()), ip_key(net.broadcast()))
+                }
+                (None, Some((start, end))) => {
+                    let parse = |idx: usize| {
+                        IpAddr::from_str(field(idx)).map(ip_key).with_context(|| {
+                            format!("row {}: bad address {}", line + 2, field(idx))
+                        })
+                    };
+                    (parse(start)?, parse(end)?)
+                }
+                (None, None) => unreachable!(),
+            };
+
+            let mut reputation = IpReputation::default();
+            for (idx, header) in headers.iter().enumerate() {
+                reputation.set(header, truthy(field(idx)));
+            }
+            ranges.push(IpRange {
+                start,
+                end,
+                reputation,
+            });
+        }
+
+        ranges.sort_by_key(|range| range.start);
+        Ok(Self { ranges })
+    }
+
+    fn find(&self, ip: IpAddr) -> Option<&IpReputation> {
+        let key = ip_key(ip);
+        let idx = self.ranges.partition_point(|range| range.start <= key);
+        let range = self.ranges.get(idx.checked_sub(1)?)?;
+        (key <= range.end).then_some(&range.reputation)
+    }
+}
+
+#[async_trait]
+impl IpReputationProvider for CsvReputation {
+    fn name(&self) -> &'static str {
+        "csv"
+    }
+
+    async fn lookup(&self, ip: IpAddr) -> Result<Option<IpReputation>> {
+        Ok(self.find(ip).cloned())
+    }
+}
+
+// ---------------------------------------------------------------
+// POLICY
+// ---------------------------------------------------------------
+
+/// Ordered so the strictest matching category wins.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IpPolicyAction {
+    #[default]
+    Allow,
+    Flag,
+    Deny,
+}
+
+/// The `ip_reputation` object of a document policy, e.g.
+/// `{"tor": "deny", "vpn": "flag"}`. Missing or unknown values allow.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IpReputationPolicy {
+    pub tor: IpPolicyAction,
+    pub vpn: IpPolicyAction,
+    pub proxy: IpPolicyAction,
+    pub hosting: IpPolicyAction,
+}
+
+impl IpReputationPolicy {
+    pub fn from_document_policy(policy: &serde_json::Value) -> Self {
+        let action = |category: &str| match policy
+            .pointer(&format!("/ip_reputation/{category}"))
+            .and_then(|value| value.as_str())
+        {
+            Some("deny") => IpPolicyAction::Deny,
+            Some("flag") => IpPolicyAction::Flag,
+            _ => IpPolicyAction::Allow,
+        };
+        Self {
+            tor: action("tor"),
+            vpn: action("vpn"),
+            proxy: action("proxy"),
+            hosting: action("hosting"),
+        }
+    }
+
+    /// The strictest action across the address's categories, and the
+    /// categories that triggered anything other than allow.
+    pub fn evaluate(&self, reputation: &IpReputation) -> (IpPolicyAction, Vec<&'static str>) {
+        let mut action = IpPolicyAction::Allow;
+        let mut matched = Vec::new();
+        for category in reputation.categories() {
+            let category_action = match category {
+                "tor" => self.tor,
+                "vpn" => self.vpn,
+                "proxy" => self.proxy,
+                _ => self.hosting,
+            };
+            if category_action != IpPolicyAction::Allow {
+                matched.push(category);
+                action = action.max(category_action);
+            }
+        }
+        (action, matched)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn csv_ranges_and_policy() {
+        let db = CsvReputation::from_reader(
+            "network,vpn,proxy,tor,relay,hosting,service\n\
+             185.220.101.0/24,false,false,true,false,false,\n\
+             2001:db8::/32,true,false,false,false,true,ExampleVPN\n\
+             203.0.113.7,false,false,false,true,false,\n"
+                .as_bytes(),
+        )
+        .unwrap();
+        let tor = db.find("185.220.101.44".parse().unwrap()).unwrap();
+        assert_eq!(tor.categories(), vec!["tor"]);
+        let vpn = db.find("2001:db8::1".parse().unwrap()).unwrap();
+        assert_eq!(vpn.categories(), vec!["vpn", "hosting"]);
+        assert!(db.find("203.0.113.7".parse().unwrap()).unwrap().proxy);
+        assert!(db.find("203.0.113.8".parse().unwrap()).is_none());
+        assert!(db.find("8.8.8.8".parse().unwrap()).is_none());
+
+        let ranges = CsvReputation::from_reader(
+            "start_ip,end_ip,is_tor_exit_node,is_anonymous_vpn\n10.0.0.0,10.0.0.255,1,0\n"
+                .as_bytes(),
+        )
+        .unwrap();
+        assert!(ranges.find("10.0.0.9".parse().unwrap()).unwrap().tor);
+
+        let policy = IpReputationPolicy::from_document_policy(
+            &json!({ "ip_reputation": { "tor": "deny", "vpn": "flag" } }),
+        );
+        assert_eq!(policy.evaluate(tor), (IpPolicyAction::Deny, vec!["tor"]));
+        assert_eq!(policy.evaluate(vpn), (IpPolicyAction::Flag, vec!["vpn"]));
+        assert_eq!(
+            IpReputationPolicy::from_document_policy(&json!({})).evaluate(tor),
+            (IpPolicyAction::Allow, vec![])
+        );
+    }
+
+    #[test]
+    fn forwarded_for_is_only_read_through_trusted_proxies() {
+        let peer: IpAddr = "10.0.0.5".parse().unwrap();
+        let tor: IpAddr = "185.220.101.44".parse().unwrap();
+        let spoofed = Some("8.8.8.8, 185.220.101.44");
+
+        let none = TrustedProxies::default();
+        assert_eq!(none.client_ip(peer, spoofed), peer);
+        assert_eq!(none.client_ip(tor, Some("8.8.8.8")), tor);
+
+        let proxies = TrustedProxies::parse("10.0.0.0/8, 192.0.2.1").unwrap();
+        assert_eq!(proxies.client_ip(peer, spoofed), tor);
+        assert_eq!(
+            proxies.client_ip(peer, Some("8.8.8.8, 185.220.101.44, 192.0.2.1")),
+            tor
+        );
+        assert_eq!(proxies.client_ip(peer, None), peer);
+        assert_eq!(proxies.client_ip(peer, Some("not-an-ip")), peer);
+        assert_eq!(
+            proxies.client_ip("::ffff:10.0.0.5".parse().unwrap(), Some("185.220.101.44")),
+            tor
+        );
+        assert!(TrustedProxies::parse("10.0.0.0/33").is_err());
+    }
+}
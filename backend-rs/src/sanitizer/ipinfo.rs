@@ -1,35 +1,70 @@
-use crate::error::AppError;
+// src/sanitizer/ipinfo.rs
+
+use std::net::IpAddr;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
 use reqwest::Client;
 use serde::Deserialize;
 
-#[derive(Debug, Deserialize)]
-pub struct IpInfoSecurity {
-    pub vpn: Option<bool>,
-    pub proxy: Option<bool>,
-    pub tor: Option<bool>,
+use super::ip_reputation::{IpReputation, IpReputationProvider};
+
+#[derive(Debug, Default, Deserialize)]
+struct IpInfoPrivacy {
+    #[serde(default)]
+    vpn: bool,
+    #[serde(default)]
+    proxy: bool,
+    #[serde(default)]
+    tor: bool,
+    #[serde(default)]
+    relay: bool,
+    #[serde(default)]
+    hosting: bool,
 }
 
-#[derive(Debug, Deserialize)]
-pub struct IpInfoResponse {
-    pub security: Option<IpInfoSecurity>,
+/// Queries the ipinfo.io privacy detection API per request. Prefer a local
+/// database; this adds a network round trip to every public link open.
+pub struct IpInfoProvider {
+    client: Client,
+    token: String,
 }
 
-pub async fn check_ipinfo(ip: &str, token: &str) -> Result<IpInfoSecurity, AppError> {
-    let endpoint = format!("https://ipinfo.io/{}/security?token={}", ip, token);
-
-    let resp = Client::new()
-        .get(&endpoint)
-        .send()
-        .await
-        .map_err(|e| AppError::Internal(format!("IPInfo request failed: {e}")))?
-        .json::<IpInfoResponse>()
-        .await
-        .map_err(|e| AppError::Internal(format!("IPInfo JSON error: {e}")))?;
-
-    Ok(resp.security.unwrap_or(IpInfoSecurity {
-        vpn: None,
-        proxy: None,
-        tor: None,
-    }))
+impl IpInfoProvider {
+    pub fn new(token: String) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(5))
+            .build()
+            .unwrap_or_default();
+        Self { client, token }
+    }
 }
 
+#[async_trait]
+impl IpReputationProvider for IpInfoProvider {
+    fn name(&self) -> &'static str {
+        "ipinfo"
+    }
+
+    async fn lookup(&self, ip: IpAddr) -> Result<Option<IpReputation>> {
+        let privacy = self
+            .client
+            .get(format!("https://ipinfo.io/{ip}/privacy"))
+            .query(&[("token", &self.token)])
+            .send()
+            .await
+            .context("ipinfo request failed")?
+            .error_for_status()?
+            .json::<IpInfoPrivacy>()
+            .await
+            .context("ipinfo returned unexpected JSON")?;
+
+        Ok(Some(IpReputation {
+            tor: privacy.tor,
+            vpn: privacy.vpn,
+            proxy: privacy.proxy || privacy.relay,
+            hosting: privacy.hosting,
+        }))
+    }
+}
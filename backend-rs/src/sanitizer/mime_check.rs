@@ -0,0 +1,187 @@
+// src/sanitizer/mime_check.rs
+
+use anyhow::Result;
+use async_trait::async_trait;
+
+use super::{ScanFinding, ScanInput, ScanVerdict, Scanner};
+
+const PORTABLE_EXECUTABLE_MIME: &str = "application/vnd.microsoft.portable-executable";
+
+/// Types that run code when opened or loaded.
+const EXECUTABLE_MIME_TYPES: &[&str] = &[
+    PORTABLE_EXECUTABLE_MIME,
+    "application/x-executable",
+    "application/x-mach-binary",
+    "application/java",
+    "application/wasm",
+    "application/vnd.android.dex",
+    "application/vnd.android.dey",
+];
+
+/// Declared types that say nothing about the content.
+const GENERIC_MIME_TYPES: &[&str] = &["", "application/octet-stream", "binary/octet-stream"];
+
+/// Formats stored as a zip archive; magic bytes alone may only show the zip.
+const ZIP_CONTAINER_MIME_TYPES: &[&str] = &[
+    "application/zip",
+    "application/x-zip-compressed",
+    "application/epub+zip",
+    "application/java-archive",
+    "application/vnd.android.package-archive",
+    "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+    "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+    "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+    "application/vnd.oasis.opendocument.text",
+    "application/vnd.oasis.opendocument.spreadsheet",
+    "application/vnd.oasis.opendocument.presentation",
+];
+
+/// Legacy Office formats share the OLE compound-file container.
+const OLE_CONTAINER_MIME_TYPES: &[&str] = &[
+    "application/x-ole-storage",
+    "application/msword",
+    "application/vnd.ms-excel",
+    "application/vnd.ms-powerpoint",
+    "application/vnd.ms-outlook",
+];
+
+/// `infer` reports PE for anything starting with `MZ`; also require the
+/// `PE\0\0` signature at `e_lfanew` so text starting with "MZ" is not flagged.
+fn is_portable_executable(bytes: &[u8]) -> bool {
+    let Some(offset) = bytes.get(0x3c..0x40) else {
+        return false;
+    };
+    let offset = u32::from_le_bytes(offset.try_into().expect("4 bytes")) as usize;
+    bytes.get(offset..offset.saturating_add(4)) == Some(b"PE\0\0".as_slice())
+}
+
+/// MIME type from magic bytes, if the format is recognised.
+pub fn detect_mime(bytes: &[u8]) -> Option<&'static str> {
+    infer::get(bytes)
+        .map(|kind| kind.mime_type())
+        .filter(|mime| *mime != PORTABLE_EXECUTABLE_MIME || is_portable_executable(bytes))
+}
+
+fn normalize_mime(mime: &str) -> String {
+    let base = mime.split(';').next().unwrap_or_default().trim();
+    match base.to_ascii_lowercase().as_str() {
+        "image/jpg" | "image/pjpeg" => "image/jpeg".into(),
+        "application/x-pdf" => "application/pdf".into(),
+        other => other.to_string(),
+    }
+}
+
+fn mime_compatible(declared: &str, detected: &str) -> bool {
+    declared == detected
+        || GENERIC_MIME_TYPES.contains(&declared)
+        || (ZIP_CONTAINER_MIME_TYPES.contains(&declared)
+            && ZIP_CONTAINER_MIME_TYPES.contains(&detected))
+        || (OLE_CONTAINER_MIME_TYPES.contains(&declared)
+            && OLE_CONTAINER_MIME_TYPES.contains(&detected))
+}
+
+/// Compares the magic-byte type against the declared `mime_type`.
+pub struct MimeScanner;
+
+#[async_trait]
+impl Scanner for MimeScanner {
+    fn name(&self) -> &'static str {
+        "mime"
+    }
+
+    async fn scan(&self, input: &ScanInput<'_>) -> Result<Vec<ScanFinding>> {
+        let declared = normalize_mime(input.declared_mime);
+        let finding = |verdict, rule: &str, detail: String| ScanFinding {
+            scanner: "mime",
+            verdict,
+            rule: rule.to_string(),
+            detail,
+        };
+
+        let Some(detected) = detect_mime(input.bytes) else {
+            // Unrecognised bytes (plain text, CSV, ...) only contradict a
+            // declared type that does have a signature.
+            if !GENERIC_MIME_TYPES.contains(&declared.as_str())
+                && infer::is_mime_supported(&declared)
+            {
+                return Ok(vec![finding(
+                    ScanVerdict::Suspicious,
+                    "mime_mismatch",
+                    format!("declared {declared}, content has no matching signature"),
+                )]);
+            }
+            return Ok(Vec::new());
+        };
+
+        if EXECUTABLE_MIME_TYPES.contains(&detected) {
+            return Ok(vec![
+                if EXECUTABLE_MIME_TYPES.contains(&declared.as_str()) {
+                    finding(
+                        ScanVerdict::Suspicious,
+                        "executable",
+                        format!("executable content ({detected})"),
+                    )
+                } else {
+                    finding(
+                        ScanVerdict::Malicious,
+                        "disguised_executable",
+                        format!("declared {declared}, content is {detected}"),
+                    )
+                },
+            ]);
+        }
+
+        if !mime_compatible(&declared, detected) {
+            return Ok(vec![finding(
+                ScanVerdict::Suspicious,
+                "mime_mismatch",
+                format!("declared {declared}, content is {detected}"),
+            )]);
+        }
+
+        Ok(Vec::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn scan(bytes: &[u8], declared_mime: &str) -> Vec<ScanFinding> {
+        MimeScanner
+            .scan(&ScanInput {
+                bytes,
+                declared_mime,
+                filename: None,
+            })
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn flags_mismatched_and_disguised_uploads() {
+        let png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+        let mut pe = vec![0u8; 0x80];
+        pe[..2].copy_from_slice(b"MZ");
+        pe[0x3c] = 0x40;
+        pe[0x40..0x44].copy_from_slice(b"PE\0\0");
+
+        assert!(scan(png, "image/png").await.is_empty());
+        assert!(scan(png, "application/octet-stream").await.is_empty());
+        assert!(scan(b"plain notes", "text/plain").await.is_empty());
+        assert!(scan(b"MZ initials in a note", "text/plain")
+            .await
+            .is_empty());
+
+        let mismatch = scan(png, "application/pdf; charset=binary").await;
+        assert_eq!(mismatch[0].rule, "mime_mismatch");
+        assert_eq!(mismatch[0].verdict, ScanVerdict::Suspicious);
+
+        let fake_pdf = scan(b"plain notes", "application/pdf").await;
+        assert_eq!(fake_pdf[0].rule, "mime_mismatch");
+
+        let disguised = scan(&pe, "application/pdf").await;
+        assert_eq!(disguised[0].rule, "disguised_executable");
+        assert_eq!(disguised[0].verdict, ScanVerdict::Malicious);
+    }
+}
@@ -1,3 +1,422 @@
+// src/sanitizer/mod.rs
+pub mod active_content;
+pub mod clamav;
 pub mod hybrid;
+pub mod ip_reputation;
+pub mod ipinfo;
+pub mod mime_check;
+pub mod yara;
 
-pub use hybrid::hybrid_sanitize;
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::Serialize;
+
+/// Ordered so the worst finding wins when a report is folded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScanVerdict {
+    Clean,
+    Suspicious,
+    Malicious,
+}
+
+impl ScanVerdict {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ScanVerdict::Clean => "clean",
+            ScanVerdict::Suspicious => "suspicious",
+            ScanVerdict::Malicious => "malicious",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScanFinding {
+    pub scanner: &'static str,
+    pub verdict: ScanVerdict,
+    /// Signature, rule or check name.
+    pub rule: String,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScannerError {
+    pub scanner: &'static str,
+    pub error: String,
+}
+
+/// Plaintext handed to every scanner. `declared_mime` is what the client
+/// claimed; scanners must not trust it.
+pub struct ScanInput<'a> {
+    pub bytes: &'a [u8],
+    pub declared_mime: &'a str,
+    pub filename: Option<&'a str>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScanReport {
+    pub verdict: ScanVerdict,
+    pub filename: Option<String>,
+    pub declared_mime: String,
+    pub detected_mime: Option<String>,
+    pub scanners: Vec<&'static str>,
+    pub findings: Vec<ScanFinding>,
+    pub errors: Vec<ScannerError>,
+}
+
+impl ScanReport {
+    pub fn is_malicious(&self) -> bool {
+        self.verdict == ScanVerdict::Malicious
+    }
+
+    /// Comma-separated rules at the report's verdict, for error messages.
+    pub fn summary(&self) -> String {
+        let rules: Vec<&str> = self
+            .findings
+            .iter()
+            .filter(|f| f.verdict == self.verdict)
+            .map(|f| f.rule.as_str())
+            .collect();
+        if rules.is_empty() {
+            self.verdict.as_str().to_string()
+        } else {
+            format!("{} ({})", self.verdict.as_str(), rules.join(", "))
+        }
+    }
+}
+
+#[async_trait]
+pub trait Scanner: Send + Sync {
+    /// Short scanner name recorded in findings and custody events.
+    fn name(&self) -> &'static str;
+
+    /// Return findings for the input; an empty list means clean. Errors mean
+    /// the scanner could not give an answer (daemon down, rules missing).
+    async fn scan(&self, input: &ScanInput<'_>) -> Result<Vec<ScanFinding>>;
+}
+
+/// Runs every configured scanner over an upload and folds the results.
+pub struct ScanPipeline {
+    scanners: Vec<Box<dyn Scanner>>,
+    /// Treat scanner errors as malicious instead of only recording them.
+    fail_closed: bool,
+}
+
+pub type SharedScanPipeline = Arc<ScanPipeline>;
+
+impl ScanPipeline {
+    pub fn new(scanners: Vec<Box<dyn Scanner>>, fail_closed: bool) -> Self {
+        Self {
+            scanners,
+            fail_closed,
+        }
+    }
+
+    /// MIME and active-content checks always run. ClamAV joins when
+    /// `CLAMD_SOCKET` or `CLAMD_ADDR` is set, YARA when `YARA_RULES` is set.
+    /// `SANITIZER_FAIL_CLOSED=true` rejects uploads a scanner could not check.
+    pub fn from_env() -> Self {
+        let mut scanners: Vec<Box<dyn Scanner>> = vec![
+            Box::new(mime_check::MimeScanner),
+            Box::new(active_content::ActiveContentScanner),
+        ];
+        if let Some(clamd) = clamav::ClamdScanner::from_env() {
+            scanners.push(Box::new(clamd));
+        }
+        if let Some(yara) = yara::YaraScanner::from_env() {
+            scanners.push(Box::new(yara));
+        }
+
+        let fail_closed = std::env::var("SANITIZER_FAIL_CLOSED")
+            .map(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes"))
+            .unwrap_or(false);
+
+        Self::new(scanners, fail_closed)
+    }
+
+    pub fn scanner_names(&self) -> Vec<&'static str> {
+        self.scanners.iter().map(|s| s.name()).collect()
+    }
+
+    pub async fn scan(&self, input: &ScanInput<'_>) -> ScanReport {
+        let mut findings = Vec::new();
+        let mut errors = Vec::new();
+        self.run_scanners(input, &mut findings, &mut errors).await;
+        self.report(
+            findings,
+            errors,
+            input.filename,
+            input.declared_mime,
+            mime_check::detect_mime(input.bytes),
+        )
+    }
+
+    /// Start a scan that is fed one piece at a time. See [`WindowedScan`].
+    pub fn windowed(
+        &self,
+        declared_mime: &str,
+        filename: Option<&str>,
+        window: usize,
+    ) -> WindowedScan<'_> {
+        WindowedScan {
+            pipeline: self,
+            declared_mime: declared_mime.to_string(),
+            filename: filename.map(str::to_string),
+            window: window.max(2 * WINDOW_OVERLAP),
+            head: Vec::new(),
+            pending: Vec::new(),
+            fresh: 0,
+            windows: 0,
+            detected_mime: None,
+            findings: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    async fn run_scanners(
+        &self,
+        input: &ScanInput<'_>,
+        findings: &mut Vec<ScanFinding>,
+        errors: &mut Vec<ScannerError>,
+    ) {
+        for scanner in &self.scanners {
+            match scanner.scan(input).await {
+                Ok(mut found) => findings.append(&mut found),
+                Err(e) => errors.push(ScannerError {
+                    scanner: scanner.name(),
+                    error: e.to_string(),
+                }),
+            }
+        }
+    }
+
+    fn report(
+        &self,
+        mut findings: Vec<ScanFinding>,
+        errors: Vec<ScannerError>,
+        filename: Option<&str>,
+        declared_mime: &str,
+        detected_mime: Option<&str>,
+    ) -> ScanReport {
+        if self.fail_closed {
+            for err in &errors {
+                findings.push(ScanFinding {
+                    scanner: err.scanner,
+                    verdict: ScanVerdict::Malicious,
+                    rule: "scanner_unavailable".into(),
+                    detail: err.error.clone(),
+                });
+            }
+        }
+
+        let verdict = findings
+            .iter()
+            .map(|f| f.verdict)
+            .max()
+            .unwrap_or(ScanVerdict::Clean);
+
+        ScanReport {
+            verdict,
+            filename: filename.map(str::to_string),
+            declared_mime: declared_mime.to_string(),
+            detected_mime: detected_mime.map(str::to_string),
+            scanners: self.scanner_names(),
+            findings,
+            errors,
+        }
+    }
+}
+
+/// Leading bytes of the file shown to scanners with every later window, so
+/// checks keyed on magic bytes still know what kind of file they are in.
+const WINDOW_HEAD: usize = 64 * 1024;
+
+/// Bytes each window repeats from the end of the previous one, so a pattern
+/// split across a window boundary is still matched.
+const WINDOW_OVERLAP: usize = 64 * 1024;
+
+/// Scans an upload too large to hold in memory, one window at a time. Every
+/// byte is scanned; memory stays bounded by the window size.
+pub struct WindowedScan<'p> {
+    pipeline: &'p ScanPipeline,
+    declared_mime: String,
+    filename: Option<String>,
+    window: usize,
+    head: Vec<u8>,
+    /// Overlap from the previous window followed by bytes not yet scanned.
+    pending: Vec<u8>,
+    fresh: usize,
+    windows: usize,
+    detected_mime: Option<&'static str>,
+    findings: Vec<ScanFinding>,
+    errors: Vec<ScannerError>,
+}
+
+impl WindowedScan<'_> {
+    /// Feed the next bytes of the file; each window is scanned as it fills.
+    pub async fn update(&mut self, mut bytes: &[u8]) {
+        while !bytes.is_empty() {
+            let take = (self.window - self.pending.len()).min(bytes.len());
+            self.pending.extend_from_slice(&bytes[..take]);
+            self.fresh += take;
+            bytes = &bytes[take..];
+            if self.pending.len() == self.window {
+                self.scan_pending().await;
+            }
+        }
+    }
+
+    async fn scan_pending(&mut self) {
+        let mut findings = Vec::new();
+        let mut errors = Vec::new();
+        if self.windows == 0 {
+            self.head = self.pending[..self.pending.len().min(WINDOW_HEAD)].to_vec();
+            self.detected_mime = mime_check::detect_mime(&self.pending);
+            let input = ScanInput {
+                bytes: &self.pending,
+                declared_mime: &self.declared_mime,
+                filename: self.filename.as_deref(),
+            };
+            self.pipeline
+                .run_scanners(&input, &mut findings, &mut errors)
+                .await;
+        } else {
+            let mut bytes = Vec::with_capacity(self.head.len() + self.pending.len());
+            bytes.extend_from_slice(&self.head);
+            bytes.extend_from_slice(&self.pending);
+            let input = ScanInput {
+                bytes: &bytes,
+                declared_mime: &self.declared_mime,
+                filename: self.filename.as_deref(),
+            };
+            self.pipeline
+                .run_scanners(&input, &mut findings, &mut errors)
+                .await;
+        }
+        self.windows += 1;
+        self.fresh = 0;
+
+        // The head and overlap are seen more than once; report each
+        // finding and error once.
+        for finding in findings {
+            if !self
+                .findings
+                .iter()
+                .any(|f| f.scanner == finding.scanner && f.rule == finding.rule)
+            {
+                self.findings.push(finding);
+            }
+        }
+        for error in errors {
+            if !self
+                .errors
+                .iter()
+                .any(|e| e.scanner == error.scanner && e.error == error.error)
+            {
+                self.errors.push(error);
+            }
+        }
+
+        let keep = WINDOW_OVERLAP.min(self.pending.len());
+        self.pending.drain(..self.pending.len() - keep);
+    }
+
+    pub async fn finish(mut self) -> ScanReport {
+        if self.windows == 0 || self.fresh > 0 {
+            self.scan_pending().await;
+        }
+        self.pipeline.report(
+            self.findings,
+            self.errors,
+            self.filename.as_deref(),
+            &self.declared_mime,
+            self.detected_mime,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FailingScanner;
+
+    #[async_trait]
+    impl Scanner for FailingScanner {
+        fn name(&self) -> &'static str {
+            "failing"
+        }
+
+        async fn scan(&self, _input: &ScanInput<'_>) -> Result<Vec<ScanFinding>> {
+            anyhow::bail!("daemon unreachable")
+        }
+    }
+
+    fn pdf_input(bytes: &[u8]) -> ScanInput<'_> {
+        ScanInput {
+            bytes,
+            declared_mime: "application/pdf",
+            filename: Some("report.pdf"),
+        }
+    }
+
+    #[tokio::test]
+    async fn worst_finding_wins_and_errors_respect_fail_mode() {
+        let pdf = b"%PDF-1.7\n1 0 obj << /OpenAction 2 0 R >> endobj\n%%EOF";
+
+        let open = ScanPipeline::new(
+            vec![
+                Box::new(active_content::ActiveContentScanner),
+                Box::new(FailingScanner),
+            ],
+            false,
+        );
+        let report = open.scan(&pdf_input(pdf)).await;
+        assert_eq!(report.verdict, ScanVerdict::Suspicious);
+        assert_eq!(report.detected_mime.as_deref(), Some("application/pdf"));
+        assert_eq!(report.errors.len(), 1);
+
+        let closed = ScanPipeline::new(vec![Box::new(FailingScanner)], true);
+        let report = closed.scan(&pdf_input(pdf)).await;
+        assert!(report.is_malicious());
+        assert_eq!(report.summary(), "malicious (scanner_unavailable)");
+    }
+
+    #[tokio::test]
+    async fn windowed_scan_sees_every_window_and_the_boundaries() {
+        let pipeline =
+            ScanPipeline::new(vec![Box::new(active_content::ActiveContentScanner)], false);
+        let scan = |bytes: Vec<u8>| {
+            let pipeline = &pipeline;
+            async move {
+                let mut scan = pipeline.windowed("application/pdf", Some("big.pdf"), 0);
+                for piece in bytes.chunks(50_000) {
+                    scan.update(piece).await;
+                }
+                scan.finish().await
+            }
+        };
+
+        // Far past the first window, where only the file head says "PDF".
+        let mut late = b"%PDF-1.7\n".to_vec();
+        late.resize(5 * WINDOW_OVERLAP, b' ');
+        late.extend_from_slice(b"<< /Launch 2 0 R >>");
+        let report = scan(late).await;
+        assert_eq!(report.detected_mime.as_deref(), Some("application/pdf"));
+        assert_eq!(report.findings.len(), 1);
+        assert_eq!(report.findings[0].rule, "pdf_launch_action");
+
+        // Split across the boundary between the first two windows.
+        let mut split = b"%PDF-1.7\n".to_vec();
+        split.resize(2 * WINDOW_OVERLAP - 4, b' ');
+        split.extend_from_slice(b"/OpenAction 2 0 R");
+        split.resize(3 * WINDOW_OVERLAP, b' ');
+        let report = scan(split).await;
+        assert_eq!(report.findings[0].rule, "pdf_open_action");
+
+        let report = scan(Vec::new()).await;
+        assert_eq!(report.verdict, ScanVerdict::Clean);
+    }
+}
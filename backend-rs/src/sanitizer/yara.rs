@@ -0,0 +1,136 @@
+// src/sanitizer/yara.rs
+
+use std::io::Write as _;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+
+use super::{ScanFinding, ScanInput, ScanVerdict, Scanner};
+
+/// Rules tagged `suspicious` flag an upload; any other match rejects it.
+const SUSPICIOUS_TAG: &str = "suspicious";
+
+/// Runs the `yara` CLI against a temporary copy of the upload.
+pub struct YaraScanner {
+    bin: String,
+    rules: String,
+    timeout: Duration,
+}
+
+struct YaraMatch {
+    rule: String,
+    tags: Vec<String>,
+}
+
+impl YaraScanner {
+    /// Enabled by `YARA_RULES` (a `.yar` source or `.yarc` compiled rules).
+    /// `YARA_BIN` defaults to `yara`, `YARA_TIMEOUT_SECS` to 30.
+    pub fn from_env() -> Option<Self> {
+        let rules = std::env::var("YARA_RULES")
+            .ok()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())?;
+        let bin = std::env::var("YARA_BIN").unwrap_or_else(|_| "yara".to_string());
+        let timeout = std::env::var("YARA_TIMEOUT_SECS")
+            .ok()
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or(30);
+
+        Some(Self {
+            bin,
+            rules,
+            timeout: Duration::from_secs(timeout),
+        })
+    }
+}
+
+/// One match per line: `RuleName [tag1,tag2] /path/to/file`.
+fn parse_output(stdout: &str) -> Vec<YaraMatch> {
+    stdout
+        .lines()
+        .filter_map(|line| {
+            let (rule, rest) = line.trim().split_once(' ')?;
+            let tags = rest
+                .strip_prefix('[')
+                .and_then(|r| r.split_once(']'))
+                .map(|(tags, _)| {
+                    tags.split(',')
+                        .map(str::trim)
+                        .filter(|t| !t.is_empty())
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default();
+            Some(YaraMatch {
+                rule: rule.to_string(),
+                tags,
+            })
+        })
+        .collect()
+}
+
+#[async_trait]
+impl Scanner for YaraScanner {
+    fn name(&self) -> &'static str {
+        "yara"
+    }
+
+    async fn scan(&self, input: &ScanInput<'_>) -> Result<Vec<ScanFinding>> {
+        let mut file = tempfile::NamedTempFile::new().context("create yara scan file")?;
+        file.write_all(input.bytes)?;
+        file.flush()?;
+
+        let mut cmd = tokio::process::Command::new(&self.bin);
+        cmd.arg("--print-tags").arg("--no-warnings");
+        if self.rules.ends_with(".yarc") {
+            cmd.arg("--compiled-rules");
+        }
+        cmd.arg(&self.rules).arg(file.path()).kill_on_drop(true);
+
+        let output = match tokio::time::timeout(self.timeout, cmd.output()).await {
+            Ok(output) => output.with_context(|| format!("run {}", self.bin))?,
+            Err(_) => bail!("yara timed out after {}s", self.timeout.as_secs()),
+        };
+        if !output.status.success() {
+            bail!(
+                "yara exited with {}: {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+
+        Ok(parse_output(&String::from_utf8_lossy(&output.stdout))
+            .into_iter()
+            .map(|m| {
+                let verdict = if m.tags.iter().any(|t| t == SUSPICIOUS_TAG) {
+                    ScanVerdict::Suspicious
+                } else {
+                    ScanVerdict::Malicious
+                };
+                ScanFinding {
+                    scanner: "yara",
+                    verdict,
+                    detail: format!("matched rule {} [{}]", m.rule, m.tags.join(",")),
+                    rule: m.rule,
+                }
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_output;
+
+    #[test]
+    fn parses_rule_names_and_tags() {
+        let matches = parse_output(
+            "Office_AutoOpen [suspicious,office] /tmp/.tmpX\nEICAR_Test [] /tmp/.tmpX\n",
+        );
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].rule, "Office_AutoOpen");
+        assert_eq!(matches[0].tags, vec!["suspicious", "office"]);
+        assert!(matches[1].tags.is_empty());
+    }
+}
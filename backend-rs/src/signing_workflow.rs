@@ -0,0 +1,521 @@
+// src/signing_workflow.rs
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::error::AppError;
+use crate::sqlx::{self, PgPool, PgRow, Row};
+
+pub const ROUTING_SEQUENTIAL: &str = "sequential";
+pub const ROUTING_PARALLEL: &str = "parallel";
+
+pub const WORKFLOW_ACTIVE: &str = "active";
+
+pub const SIGNER_WAITING: &str = "waiting";
+pub const SIGNER_SIGNED: &str = "signed";
+
+/// An ordered signing request over one document. Signers sharing a step are
+/// notified together; a step opens once every required signer before it has
+/// signed.
+#[derive(Debug, Clone, Serialize)]
+pub struct SigningWorkflow {
+    pub id: uuid::Uuid,
+    pub doc_id: uuid::Uuid,
+    pub owner_wallet: String,
+    pub owner_chain: String,
+    pub routing: String,
+    pub status: String,
+    pub note: Option<String>,
+    pub expires_in_hours: i64,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub cancelled_at: Option<DateTime<Utc>>,
+    pub cancel_reason: Option<String>,
+    pub signers: Vec<WorkflowSigner>,
+}
+
+/// A wallet, email/phone recipient, or agent. Wallet and email signers are
+/// reached through a regular share envelope once their step opens.
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkflowSigner {
+    pub id: uuid::Uuid,
+    pub position: i32,
+    pub step: i32,
+    pub role: String,
+    pub required: bool,
+    pub name: Option<String>,
+    pub wallet: Option<String>,
+    pub chain: Option<String>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub agent_id: Option<uuid::Uuid>,
+    pub status: String,
+    pub envelope_id: Option<uuid::Uuid>,
+    pub sign_event_id: Option<uuid::Uuid>,
+    pub notified_at: Option<DateTime<Utc>>,
+    pub signed_at: Option<DateTime<Utc>>,
+}
+
+/// A validated signer ready to insert; `step` is already resolved.
+#[derive(Debug, Clone)]
+pub struct NewWorkflowSigner {
+    pub step: i32,
+    pub role: String,
+    pub required: bool,
+    pub name: Option<String>,
+    pub wallet: Option<String>,
+    pub chain: Option<String>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub agent_id: Option<uuid::Uuid>,
+}
+
+pub struct NewSigningWorkflow<'a> {
+    pub doc_id: uuid::Uuid,
+    pub owner_wallet: &'a str,
+    pub owner_chain: &'a str,
+    pub routing: &'a str,
+    pub note: Option<&'a str>,
+    pub expires_in_hours: i64,
+    pub signers: &'a [NewWorkflowSigner],
+}
+
+const WORKFLOW_COLUMNS: &str = "id, doc_id, owner_wallet, owner_chain, routing, status, note, \
+     expires_in_hours, created_at, completed_at, cancelled_at, cancel_reason";
+
+const SIGNER_COLUMNS: &str = "id, position, step, role, required, signer_name, signer_wallet, \
+     signer_chain, signer_email, signer_phone, agent_id, status, envelope_id, sign_event_id, \
+     notified_at, signed_at";
+
+impl SigningWorkflow {
+    fn from_row(row: &PgRow, signers: Vec<WorkflowSigner>) -> Self {
+        Self {
+            id: row.get("id"),
+            doc_id: row.get("doc_id"),
+            owner_wallet: row.get("owner_wallet"),
+            owner_chain: row.get("owner_chain"),
+            routing: row.get("routing"),
+            status: row.get("status"),
+            note: row.get("note"),
+            expires_in_hours: row.get::<i32, _>("expires_in_hours") as i64,
+            created_at: row.get("created_at"),
+            completed_at: row.get("completed_at"),
+            cancelled_at: row.get("cancelled_at"),
+            cancel_reason: row.get("cancel_reason"),
+            signers,
+        }
+    }
+}
+
+impl WorkflowSigner {
+    fn from_row(row: &PgRow) -> Self {
+        Self {
+            id: row.get("id"),
+            position: row.get("position"),
+            step: row.get("step"),
+            role: row.get("role"),
+            required: row.get("required"),
+            name: row.get("signer_name"),
+            wallet: row.get("signer_wallet"),
+            chain: row.get("signer_chain"),
+            email: row.get("signer_email"),
+            phone: row.get("signer_phone"),
+            agent_id: row.get("agent_id"),
+            status: row.get("status"),
+            envelope_id: row.get("envelope_id"),
+            sign_event_id: row.get("sign_event_id"),
+            notified_at: row.get("notified_at"),
+            signed_at: row.get("signed_at"),
+        }
+    }
+}
+
+fn db_error(e: sqlx::Error) -> AppError {
+    AppError::Internal(e.to_string())
+}
+
+/// Sequential routing puts each signer in its own step, parallel routing
+/// puts everyone in step 0. An explicit step on a signer wins either way.
+pub fn resolve_steps(routing: &str, explicit: &[Option<i32>]) -> Result<Vec<i32>, AppError> {
+    if routing != ROUTING_SEQUENTIAL && routing != ROUTING_PARALLEL {
+        return Err(AppError::BadRequest(
+            "routing must be sequential or parallel".into(),
+        ));
+    }
+    explicit
+        .iter()
+        .enumerate()
+        .map(|(idx, step)| match step {
+            Some(step) if *step < 0 => {
+                Err(AppError::BadRequest("step must not be negative".into()))
+            }
+            Some(step) => Ok(*step),
+            None if routing == ROUTING_SEQUENTIAL => Ok(idx as i32),
+            None => Ok(0),
+        })
+        .collect()
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Advance {
+    /// Indexes of waiting signers whose step is now open.
+    pub notify: Vec<usize>,
+    pub fully_executed: bool,
+}
+
+/// The open step is the lowest one still missing a required signature.
+/// Everyone up to and including it should have been notified; once no
+/// required signature is missing the workflow is fully executed.
+pub fn advance(signers: &[WorkflowSigner]) -> Advance {
+    let open_step = signers
+        .iter()
+        .filter(|signer| signer.required && signer.status != SIGNER_SIGNED)
+        .map(|signer| signer.step)
+        .min();
+    let Some(open_step) = open_step else {
+        return Advance {
+            notify: Vec::new(),
+            fully_executed: true,
+        };
+    };
+
+    Advance {
+        notify: signers
+            .iter()
+            .enumerate()
+            .filter(|(_, signer)| signer.step <= open_step && signer.status == SIGNER_WAITING)
+            .map(|(idx, _)| idx)
+            .collect(),
+        fully_executed: false,
+    }
+}
+
+pub async fn load_signing_workflow(
+    db: &PgPool,
+    workflow_id: uuid::Uuid,
+) -> Result<Option<SigningWorkflow>, AppError> {
+    let row = sqlx::query(&format!(
+        "select {WORKFLOW_COLUMNS} from signing_workflows where id = $1"
+    ))
+    .bind(workflow_id)
+    .fetch_optional(db)
+    .await
+    .map_err(db_error)?;
+    let Some(row) = row else {
+        return Ok(None);
+    };
+
+    let signers = sqlx::query(&format!(
+        "select {SIGNER_COLUMNS} from signing_workflow_signers where workflow_id = $1 order by step, position"
+    ))
+    .bind(workflow_id)
+    .fetch_all(db)
+    .await
+    .map_err(db_error)?;
+
+    Ok(Some(SigningWorkflow::from_row(
+        &row,
+        signers.iter().map(WorkflowSigner::from_row).collect(),
+    )))
+}
+
+pub async fn list_signing_workflows(
+    db: &PgPool,
+    doc_id: uuid::Uuid,
+) -> Result<Vec<SigningWorkflow>, AppError> {
+    let ids =
+        sqlx::query("select id from signing_workflows where doc_id = $1 order by created_at desc")
+            .bind(doc_id)
+            .fetch_all(db)
+            .await
+            .map_err(db_error)?;
+
+    let mut workflows = Vec::with_capacity(ids.len());
+    for row in ids {
+        if let Some(workflow) = load_signing_workflow(db, row.get("id")).await? {
+            workflows.push(workflow);
+        }
+    }
+    Ok(workflows)
+}
+
+pub async fn insert_signing_workflow(
+    db: &PgPool,
+    workflow: &NewSigningWorkflow<'_>,
+) -> Result<uuid::Uuid, AppError> {
+    let id = uuid::Uuid::new_v4();
+    let mut tx = db.begin().await.map_err(db_error)?;
+    sqlx::query(
+        r#"
+        insert into signing_workflows
+            (id, doc_id, owner_wallet, owner_chain, routing, status, note, expires_in_hours)
+        values ($1, $2, $3, $4, $5, 'active', $6, $7)
+        "#,
+    )
+    .bind(id)
+    .bind(workflow.doc_id)
+    .bind(workflow.owner_wallet)
+    .bind(workflow.owner_chain)
+    .bind(workflow.routing)
+    .bind(workflow.note)
+    .bind(workflow.expires_in_hours as i32)
+    .execute(&mut *tx)
+    .await
+    .map_err(db_error)?;
+
+    for (position, signer) in workflow.signers.iter().enumerate() {
+        sqlx::query(
+            r#"
+            insert into signing_workflow_signers
+                (id, workflow_id, position, step, role, required, signer_name, signer_wallet,
+                 signer_chain, signer_email, signer_phone, agent_id, status)
+            values ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, 'waiting')
+            "#,
+        )
+        .bind(uuid::Uuid::new_v4())
+        .bind(id)
+        .bind(position as i32)
+        .bind(signer.step)
+        .bind(&signer.role)
+        .bind(signer.required)
+        .bind(&signer.name)
+        .bind(&signer.wallet)
+        .bind(&signer.chain)
+        .bind(&signer.email)
+        .bind(&signer.phone)
+        .bind(signer.agent_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+    }
+    tx.commit().await.map_err(db_error)?;
+    Ok(id)
+}
+
+/// Move a waiting signer to notified. Returns false if another request got
+/// there first, so each signer is only ever notified once.
+pub async fn claim_signer_notification(
+    db: &PgPool,
+    signer_id: uuid::Uuid,
+) -> Result<bool, AppError> {
+    let result = sqlx::query(
+        "update signing_workflow_signers set status = 'notified', notified_at = now() where id = $1 and status = 'waiting'",
+    )
+    .bind(signer_id)
+    .execute(db)
+    .await
+    .map_err(db_error)?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Undo a claim whose notification failed part way, so the next advance
+/// picks the signer up again instead of leaving them notified with nothing
+/// delivered.
+pub async fn release_signer_notification(
+    db: &PgPool,
+    signer_id: uuid::Uuid,
+) -> Result<(), AppError> {
+    sqlx::query(
+        "update signing_workflow_signers set status = 'waiting', notified_at = null, envelope_id = null where id = $1 and status = 'notified'",
+    )
+    .bind(signer_id)
+    .execute(db)
+    .await
+    .map_err(db_error)?;
+    Ok(())
+}
+
+pub async fn set_signer_envelope(
+    db: &PgPool,
+    signer_id: uuid::Uuid,
+    envelope_id: uuid::Uuid,
+) -> Result<(), AppError> {
+    sqlx::query("update signing_workflow_signers set envelope_id = $2 where id = $1")
+        .bind(signer_id)
+        .bind(envelope_id)
+        .execute(db)
+        .await
+        .map_err(db_error)?;
+    Ok(())
+}
+
+/// Who just signed a document, as the signing route knows them.
+pub enum WorkflowSignerMatch<'a> {
+    Wallet { wallet: &'a str, chain: &'a str },
+    Envelope(uuid::Uuid),
+    Agent(uuid::Uuid),
+}
+
+pub struct CompletedSigner {
+    pub workflow_id: uuid::Uuid,
+    pub signer_id: uuid::Uuid,
+    pub role: String,
+    pub owner_wallet: String,
+}
+
+/// Mark matching notified signers on the document's active workflows as
+/// signed. Signers whose step has not opened yet are left alone.
+pub async fn mark_workflow_signers_signed(
+    db: &PgPool,
+    doc_id: uuid::Uuid,
+    signer: WorkflowSignerMatch<'_>,
+    sign_event_id: uuid::Uuid,
+) -> Result<Vec<CompletedSigner>, AppError> {
+    let predicate = match signer {
+        WorkflowSignerMatch::Wallet { .. } => {
+            "s.signer_chain = $4 and ((s.signer_chain = 'evm' and lower(s.signer_wallet) = lower($3)) or s.signer_wallet = $3)"
+        }
+        WorkflowSignerMatch::Envelope(_) => "s.envelope_id = $3",
+        WorkflowSignerMatch::Agent(_) => "s.agent_id = $3",
+    };
+    let sql = format!(
+        r#"
+        update signing_workflow_signers s
+        set status = 'signed', signed_at = now(), sign_event_id = $2
+        from signing_workflows w
+        where w.id = s.workflow_id
+          and w.doc_id = $1
+          and w.status = 'active'
+          and s.status = 'notified'
+          and {predicate}
+        returning s.workflow_id, s.id, s.role, w.owner_wallet
+        "#
+    );
+    let query = sqlx::query(&sql).bind(doc_id).bind(sign_event_id);
+    let query = match signer {
+        WorkflowSignerMatch::Wallet { wallet, chain } => query.bind(wallet).bind(chain),
+        WorkflowSignerMatch::Envelope(envelope_id) => query.bind(envelope_id),
+        WorkflowSignerMatch::Agent(agent_id) => query.bind(agent_id),
+    };
+    let rows = query.fetch_all(db).await.map_err(db_error)?;
+    Ok(rows
+        .iter()
+        .map(|row| CompletedSigner {
+            workflow_id: row.get("workflow_id"),
+            signer_id: row.get("id"),
+            role: row.get("role"),
+            owner_wallet: row.get("owner_wallet"),
+        })
+        .collect())
+}
+
+/// Returns false if the workflow was no longer active.
+pub async fn complete_signing_workflow(
+    db: &PgPool,
+    workflow_id: uuid::Uuid,
+) -> Result<bool, AppError> {
+    let result = sqlx::query(
+        "update signing_workflows set status = 'fully_executed', completed_at = now() where id = $1 and status = 'active'",
+    )
+    .bind(workflow_id)
+    .execute(db)
+    .await
+    .map_err(db_error)?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Cancel an active workflow and any signers who have not signed. Returns the
+/// envelopes that were still outstanding so their share links can be revoked.
+pub async fn cancel_signing_workflow(
+    db: &PgPool,
+    workflow_id: uuid::Uuid,
+    reason: &str,
+) -> Result<Option<Vec<uuid::Uuid>>, AppError> {
+    let mut tx = db.begin().await.map_err(db_error)?;
+    let cancelled = sqlx::query(
+        r#"
+        update signing_workflows
+        set status = 'cancelled', cancelled_at = now(), cancel_reason = $2
+        where id = $1 and status = 'active'
+        "#,
+    )
+    .bind(workflow_id)
+    .bind(reason)
+    .execute(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    if cancelled.rows_affected() == 0 {
+        return Ok(None);
+    }
+
+    let rows = sqlx::query(
+        r#"
+        update signing_workflow_signers
+        set status = 'cancelled'
+        where workflow_id = $1 and status <> 'signed'
+        returning envelope_id
+        "#,
+    )
+    .bind(workflow_id)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+
+    Ok(Some(
+        rows.iter()
+            .filter_map(|row| row.get::<Option<uuid::Uuid>, _>("envelope_id"))
+            .collect(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signer(step: i32, required: bool, status: &str) -> WorkflowSigner {
+        WorkflowSigner {
+            id: uuid::Uuid::new_v4(),
+            position: 0,
+            step,
+            role: "signer".into(),
+            required,
+            name: None,
+            wallet: None,
+            chain: None,
+            email: None,
+            phone: None,
+            agent_id: None,
+            status: status.into(),
+            envelope_id: None,
+            sign_event_id: None,
+            notified_at: None,
+            signed_at: None,
+        }
+    }
+
+    #[test]
+    fn steps_open_in_order() {
+        assert_eq!(
+            resolve_steps(ROUTING_SEQUENTIAL, &[None, None, Some(1)]).unwrap(),
+            vec![0, 1, 1]
+        );
+        assert_eq!(
+            resolve_steps(ROUTING_PARALLEL, &[None, None]).unwrap(),
+            vec![0, 0]
+        );
+        assert!(resolve_steps("round_robin", &[None]).is_err());
+
+        // Step 0 is open; the optional step-1 viewer waits with everyone else.
+        let mut signers = vec![
+            signer(0, true, SIGNER_WAITING),
+            signer(0, true, SIGNER_WAITING),
+            signer(1, false, SIGNER_WAITING),
+            signer(2, true, SIGNER_WAITING),
+        ];
+        assert_eq!(advance(&signers).notify, vec![0, 1]);
+
+        // One parallel signer is not enough to open the next step.
+        signers[0].status = SIGNER_SIGNED.into();
+        signers[1].status = "notified".into();
+        assert_eq!(advance(&signers), Advance::default());
+
+        // Optional signers never hold up the steps after them.
+        signers[1].status = SIGNER_SIGNED.into();
+        assert_eq!(advance(&signers).notify, vec![2, 3]);
+
+        signers[2].status = "notified".into();
+        signers[3].status = SIGNER_SIGNED.into();
+        assert!(advance(&signers).fully_executed);
+    }
+}
@@ -1,6 +1,6 @@
 pub use sqlx_core::error::Error;
 pub use sqlx_core::row::Row;
-pub use sqlx_postgres::PgPool;
+pub use sqlx_postgres::{PgListener, PgPool, PgRow};
 
 pub mod postgres {
     pub use sqlx_postgres::PgPoolOptions;
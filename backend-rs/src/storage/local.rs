@@ -0,0 +1,126 @@
+// src/storage/local.rs
+
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+
+use super::BlobStore;
+
+/// Stores blobs under a directory on the server's own disk.
+#[derive(Clone)]
+pub struct LocalDiskStorage {
+    root: PathBuf,
+}
+
+impl LocalDiskStorage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Uses `STORAGE_LOCAL_ROOT`, falling back to `~/.tidbit/blobs`.
+    pub fn from_env() -> Self {
+        let root = std::env::var("STORAGE_LOCAL_ROOT")
+            .ok()
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+            .map(PathBuf::from)
+            .unwrap_or_else(|| {
+                let mut dir = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
+                dir.push(".tidbit/blobs");
+                dir
+            });
+        Self::new(root)
+    }
+
+    fn resolve(&self, storage_path: &str) -> Result<PathBuf> {
+        let relative = Path::new(storage_path.trim_start_matches('/'));
+        if relative.as_os_str().is_empty()
+            || relative
+                .components()
+                .any(|part| !matches!(part, Component::Normal(_)))
+        {
+            bail!("Invalid storage path: {storage_path}");
+        }
+        Ok(self.root.join(relative))
+    }
+}
+
+#[async_trait]
+impl BlobStore for LocalDiskStorage {
+    fn backend_name(&self) -> &'static str {
+        "local"
+    }
+
+    async fn put_object(&self, storage_path: &str, bytes: &[u8], _content_type: &str) -> Result<()> {
+        let path = self.resolve(storage_path)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        // Write to a sibling temp file first so readers never see a torn blob.
+        let tmp = path.with_extension(format!("tmp-{}", uuid::Uuid::new_v4().simple()));
+        tokio::fs::write(&tmp, bytes).await?;
+        tokio::fs::rename(&tmp, &path).await?;
+        Ok(())
+    }
+
+    async fn object_exists(&self, storage_path: &str) -> Result<bool> {
+        Ok(tokio::fs::try_exists(self.resolve(storage_path)?).await?)
+    }
+
+    async fn download_bytes(&self, storage_path: &str) -> Result<Vec<u8>> {
+        let path = self.resolve(storage_path)?;
+        tokio::fs::read(&path)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to download object {storage_path}: {e}"))
+    }
+
+    async fn move_object(&self, source_path: &str, destination_path: &str) -> Result<()> {
+        let source = self.resolve(source_path)?;
+        let destination = self.resolve(destination_path)?;
+        if let Some(parent) = destination.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::rename(&source, &destination).await.map_err(|e| {
+            anyhow::anyhow!("Failed to move object from {source_path} to {destination_path}: {e}")
+        })
+    }
+
+    async fn delete_object(&self, storage_path: &str) -> Result<()> {
+        match tokio::fs::remove_file(self.resolve(storage_path)?).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => bail!("Failed to delete object {storage_path}: {e}"),
+        }
+    }
+
+    async fn signed_download_url(&self, _storage_path: &str, _expires_in: i64) -> Result<String> {
+        bail!("Local disk storage does not issue signed download URLs; use /api/doc/:id/blob")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LocalDiskStorage;
+    use crate::storage::BlobStore;
+
+    #[tokio::test]
+    async fn round_trips_and_moves_content_addressed_objects() {
+        let root = std::env::temp_dir().join(format!("tidbit-blobs-{}", uuid::Uuid::new_v4()));
+        let store = LocalDiskStorage::new(&root);
+
+        let path = store
+            .upload_bytes("0xOwner", "doc-1", 1, b"hello", "text/plain")
+            .await
+            .unwrap();
+        assert!(path.starts_with("0xOwner/doc-1/v1/"));
+        assert_eq!(store.download_bytes(&path).await.unwrap(), b"hello");
+
+        store.move_object(&path, "legacy/doc-1.bin").await.unwrap();
+        assert!(!store.object_exists(&path).await.unwrap());
+        assert!(store.object_exists("legacy/doc-1.bin").await.unwrap());
+        assert!(store.download_bytes("../escape.bin").await.is_err());
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+}
@@ -1,2 +1,122 @@
 // src/storage/mod.rs
+pub mod local;
+pub mod s3;
 pub mod supabase;
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+
+fn sanitize_path_segment(segment: &str) -> String {
+    segment
+        .chars()
+        .map(|ch| match ch {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' | '.' => ch,
+            _ => '_',
+        })
+        .collect()
+}
+
+/// Content-addressed object layout shared by every backend, so existing
+/// `documents.storage_path` rows resolve regardless of where blobs live.
+pub fn expected_object_path(
+    document_owner: &str,
+    document_id: &str,
+    version: i32,
+    hash_hex: &str,
+) -> String {
+    let owner = sanitize_path_segment(document_owner);
+    let doc_id = sanitize_path_segment(document_id);
+
+    format!("{owner}/{doc_id}/v{version}/{hash_hex}.bin")
+}
+
+/// Ciphertext segment of a streamed document, stored beside its header.
+pub fn stream_segment_path(
+    document_owner: &str,
+    document_id: &str,
+    version: i32,
+    index: u64,
+) -> String {
+    let owner = sanitize_path_segment(document_owner);
+    let doc_id = sanitize_path_segment(document_id);
+
+    format!("{owner}/{doc_id}/v{version}/segments/{index:08}.bin")
+}
+
+#[async_trait]
+pub trait BlobStore: Send + Sync {
+    /// Short backend name recorded in logs.
+    fn backend_name(&self) -> &'static str;
+
+    async fn put_object(&self, storage_path: &str, bytes: &[u8], content_type: &str)
+        -> Result<()>;
+
+    async fn object_exists(&self, storage_path: &str) -> Result<bool>;
+
+    async fn download_bytes(&self, storage_path: &str) -> Result<Vec<u8>>;
+
+    async fn move_object(&self, source_path: &str, destination_path: &str) -> Result<()>;
+
+    /// Remove an object; missing objects are not an error.
+    async fn delete_object(&self, storage_path: &str) -> Result<()>;
+
+    async fn signed_download_url(&self, storage_path: &str, expires_in: i64) -> Result<String>;
+
+    /// Upload bytes into a stable document/version path and return that path.
+    async fn upload_bytes(
+        &self,
+        document_owner: &str,
+        document_id: &str,
+        version: i32,
+        bytes: &[u8],
+        content_type: &str,
+    ) -> Result<String> {
+        let hash_hex = hex::encode(crate::pqc::sha3::sha3_256_bytes(bytes));
+        let path = expected_object_path(document_owner, document_id, version, &hash_hex);
+        self.put_object(&path, bytes, content_type).await?;
+        Ok(path)
+    }
+}
+
+pub type SharedBlobStore = Arc<dyn BlobStore>;
+
+/// Select the blob backend from `STORAGE_BACKEND` (`supabase`, `local`, `s3`).
+pub fn blob_store_from_env() -> Result<SharedBlobStore> {
+    let backend = std::env::var("STORAGE_BACKEND")
+        .unwrap_or_else(|_| "supabase".to_string())
+        .trim()
+        .to_ascii_lowercase();
+
+    Ok(match backend.as_str() {
+        "supabase" => Arc::new(supabase::SupabaseStorage::new(
+            std::env::var("SUPABASE_URL")?,
+            std::env::var("SUPABASE_SERVICE_ROLE_KEY")?,
+            std::env::var("SUPABASE_BUCKET")?,
+        )),
+        "local" | "filesystem" => Arc::new(local::LocalDiskStorage::from_env()),
+        "s3" | "minio" => Arc::new(s3::S3Storage::from_env()?),
+        other => anyhow::bail!("Unsupported STORAGE_BACKEND: {other}"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::expected_object_path;
+
+    #[test]
+    fn object_path_is_stable_and_safe() {
+        let path = expected_object_path(
+            "0xAbC/123",
+            "doc 01",
+            1,
+            "aa807f8803076ea4b0376cd7aa443f7cdaeb76aff936b3b874048e11c8359a0c",
+        );
+
+        assert_eq!(
+            path,
+            "0xAbC_123/doc_01/v1/aa807f8803076ea4b0376cd7aa443f7cdaeb76aff936b3b874048e11c8359a0c.bin"
+        );
+    }
+}
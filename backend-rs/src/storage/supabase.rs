@@ -1,9 +1,12 @@
 // src/storage/supabase.rs
 
 use anyhow::{bail, Result};
+use async_trait::async_trait;
 use reqwest::Client;
 use serde::Deserialize;
 
+use super::BlobStore;
+
 #[derive(Clone)]
 pub struct SupabaseStorage {
     url: String,
@@ -33,46 +36,16 @@ impl SupabaseStorage {
             .bearer_auth(&self.service_key)
             .header("apikey", &self.service_key)
     }
+}
 
-    fn sanitize_path_segment(segment: &str) -> String {
-        segment
-            .chars()
-            .map(|ch| match ch {
-                'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' | '.' => ch,
-                _ => '_',
-            })
-            .collect()
-    }
-
-    fn object_filename_from_hash(hash_hex: &str) -> String {
-        format!("{hash_hex}.bin")
-    }
-
-    pub fn expected_object_path(
-        document_owner: &str,
-        document_id: &str,
-        version: i32,
-        hash_hex: &str,
-    ) -> String {
-        let owner = Self::sanitize_path_segment(document_owner);
-        let doc_id = Self::sanitize_path_segment(document_id);
-        let filename = Self::object_filename_from_hash(hash_hex);
-
-        format!("{owner}/{doc_id}/v{version}/{filename}")
+#[async_trait]
+impl BlobStore for SupabaseStorage {
+    fn backend_name(&self) -> &'static str {
+        "supabase"
     }
 
-    /// Upload bytes into a stable document/version path inside the bucket.
-    pub async fn upload_bytes(
-        &self,
-        document_owner: &str,
-        document_id: &str,
-        version: i32,
-        bytes: &[u8],
-        content_type: &str,
-    ) -> anyhow::Result<String> {
-        let hash_hex = hex::encode(crate::pqc::sha3::sha3_256_bytes(bytes));
-        let path = Self::expected_object_path(document_owner, document_id, version, &hash_hex);
-        let url = format!("{}/storage/v1/object/{}/{}", self.url, self.bucket, path);
+    async fn put_object(&self, storage_path: &str, bytes: &[u8], content_type: &str) -> Result<()> {
+        let url = format!("{}/storage/v1/object/{}/{}", self.url, self.bucket, storage_path);
 
         let res = self
             .authed_request(self.client.put(&url))
@@ -89,10 +62,10 @@ impl SupabaseStorage {
         anyhow::bail!("Supabase upload failed ({}): {}", status, body);
         }
 
-        Ok(path)
+        Ok(())
     }
 
-    pub async fn object_exists(&self, storage_path: &str) -> Result<bool> {
+    async fn object_exists(&self, storage_path: &str) -> Result<bool> {
         let url = format!(
             "{}/storage/v1/object/authenticated/{}/{}",
             self.url, self.bucket, storage_path
@@ -123,7 +96,7 @@ impl SupabaseStorage {
         )
     }
 
-    pub async fn download_bytes(&self, storage_path: &str) -> Result<Vec<u8>> {
+    async fn download_bytes(&self, storage_path: &str) -> Result<Vec<u8>> {
         let url = format!(
             "{}/storage/v1/object/authenticated/{}/{}",
             self.url, self.bucket, storage_path
@@ -144,7 +117,7 @@ impl SupabaseStorage {
         Ok(res.bytes().await?.to_vec())
     }
 
-    pub async fn move_object(&self, source_path: &str, destination_path: &str) -> Result<()> {
+    async fn move_object(&self, source_path: &str, destination_path: &str) -> Result<()> {
         let url = format!("{}/storage/v1/object/move", self.url);
 
         let res = self
@@ -170,9 +143,25 @@ impl SupabaseStorage {
         Ok(())
     }
 
+    async fn delete_object(&self, storage_path: &str) -> Result<()> {
+        let url = format!("{}/storage/v1/object/{}/{}", self.url, self.bucket, storage_path);
+
+        let res = self.authed_request(self.client.delete(&url)).send().await?;
+        let status = res.status();
+        if !status.is_success() && status != reqwest::StatusCode::NOT_FOUND {
+            bail!(
+                "Failed to delete object {} ({}): {}",
+                storage_path,
+                status,
+                res.text().await.unwrap_or_default()
+            );
+        }
+
+        Ok(())
+    }
 
     /// Generate signed download URL (IMPORTANT: DO NOT URL-ENCODE PATH)
-    pub async fn signed_download_url(
+    async fn signed_download_url(
         &self,
         storage_path: &str,
         expires_in: i64,
@@ -208,23 +197,3 @@ impl SupabaseStorage {
         })
     }
 }
-
-#[cfg(test)]
-mod tests {
-    use super::SupabaseStorage;
-
-    #[test]
-    fn object_path_is_stable_and_safe() {
-        let path = SupabaseStorage::expected_object_path(
-            "0xAbC/123",
-            "doc 01",
-            1,
-            "aa807f8803076ea4b0376cd7aa443f7cdaeb76aff936b3b874048e11c8359a0c",
-        );
-
-        assert_eq!(
-            path,
-            "0xAbC_123/doc_01/v1/aa807f8803076ea4b0376cd7aa443f7cdaeb76aff936b3b874048e11c8359a0c.bin"
-        );
-    }
-}
@@ -0,0 +1,682 @@
+// src/webhooks.rs
+
+use std::net::{IpAddr, SocketAddr};
+
+use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use serde::Serialize;
+use serde_json::json;
+use sha2::Sha256;
+
+use crate::c2c::ledger::LedgerEvent;
+use crate::crypto::canonical::canonicalize::canonical_json;
+use crate::error::AppError;
+use crate::jobs::schedule_job;
+use crate::pqc::dilithium;
+use crate::sqlx::{self, PgPool, PgRow, Row};
+
+pub const JOB_WEBHOOK_DELIVERY: &str = "webhook_delivery";
+
+pub const SUBSCRIPTION_ACTIVE: &str = "active";
+pub const SUBSCRIPTION_DISABLED: &str = "disabled";
+
+pub const SIGNATURE_HEADER: &str = "x-tidbit-signature";
+pub const PQ_SIGNATURE_HEADER: &str = "x-tidbit-pq-signature";
+pub const TIMESTAMP_HEADER: &str = "x-tidbit-timestamp";
+pub const DELIVERY_HEADER: &str = "x-tidbit-delivery";
+pub const EVENT_HEADER: &str = "x-tidbit-event";
+
+/// A wallet's endpoint for custody events on the documents it owns. An empty
+/// filter list receives every event type.
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookSubscription {
+    pub id: uuid::Uuid,
+    pub owner_wallet: String,
+    pub url: String,
+    pub event_types: Vec<String>,
+    pub pq_signed: bool,
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+    pub disabled_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookDelivery {
+    pub id: uuid::Uuid,
+    pub subscription_id: uuid::Uuid,
+    pub event_id: uuid::Uuid,
+    pub doc_id: uuid::Uuid,
+    pub event_type: String,
+    pub status: String,
+    pub attempts: i32,
+    pub response_status: Option<i32>,
+    pub last_error: Option<String>,
+    pub replay_of: Option<uuid::Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+}
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Ranges a webhook may never reach: loopback, private, link-local (which
+/// holds cloud metadata endpoints), CGNAT, and other special-purpose blocks.
+const BLOCKED_WEBHOOK_RANGES: &[&str] = &[
+    "0.0.0.0/8",
+    "10.0.0.0/8",
+    "100.64.0.0/10",
+    "127.0.0.0/8",
+    "169.254.0.0/16",
+    "172.16.0.0/12",
+    "192.0.0.0/24",
+    "192.0.2.0/24",
+    "192.168.0.0/16",
+    "198.18.0.0/15",
+    "198.51.100.0/24",
+    "203.0.113.0/24",
+    "224.0.0.0/4",
+    "240.0.0.0/4",
+    "::/128",
+    "::1/128",
+    "64:ff9b::/96",
+    "100::/64",
+    "2001:db8::/32",
+    "fc00::/7",
+    "fe80::/10",
+    "ff00::/8",
+];
+
+/// `WEBHOOK_ALLOW_PRIVATE_TARGETS=true` lets webhooks reach loopback and
+/// private addresses. Only for local testing.
+fn private_targets_allowed() -> bool {
+    std::env::var("WEBHOOK_ALLOW_PRIVATE_TARGETS")
+        .map(|value| value.trim().eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+}
+
+pub fn is_public_webhook_address(ip: IpAddr) -> bool {
+    let ip = match ip {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(ip),
+        v4 => v4,
+    };
+    !BLOCKED_WEBHOOK_RANGES.iter().any(|range| {
+        range
+            .parse::<ipnet::IpNet>()
+            .is_ok_and(|net| net.contains(&ip))
+    })
+}
+
+/// Resolve a webhook URL's host and refuse it unless every address is
+/// public. The caller connects to exactly these addresses, so a DNS answer
+/// that changes after the check cannot redirect the request.
+pub async fn resolve_webhook_target(url: &reqwest::Url) -> Result<Vec<SocketAddr>, AppError> {
+    let host = url
+        .host_str()
+        .ok_or_else(|| AppError::BadRequest("Webhook url needs a host".into()))?;
+    let port = url
+        .port_or_known_default()
+        .ok_or_else(|| AppError::BadRequest("Webhook url needs a port".into()))?;
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.trim_matches(['[', ']']), port))
+        .await
+        .map_err(|_| AppError::BadRequest(format!("Webhook host {host} does not resolve")))?
+        .collect();
+    if addrs.is_empty() {
+        return Err(AppError::BadRequest(format!(
+            "Webhook host {host} does not resolve"
+        )));
+    }
+    if !private_targets_allowed()
+        && !addrs
+            .iter()
+            .all(|addr| is_public_webhook_address(addr.ip()))
+    {
+        return Err(AppError::BadRequest(format!(
+            "Webhook host {host} is not a public address"
+        )));
+    }
+    Ok(addrs)
+}
+
+/// What the delivery log keeps about a failed request. Transport errors are
+/// reduced to a category so the log does not describe the network.
+fn transport_error_summary(err: &reqwest::Error) -> &'static str {
+    if err.is_timeout() {
+        "request timed out"
+    } else if err.is_connect() {
+        "could not connect"
+    } else {
+        "request failed"
+    }
+}
+
+fn db_error(e: sqlx::Error) -> AppError {
+    AppError::Internal(e.to_string())
+}
+
+pub fn generate_webhook_secret() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    format!("whsec_{}", hex::encode(bytes))
+}
+
+/// Upper-case an event filter. A trailing `*` matches a prefix, so
+/// `INBOX_*` covers every inbox action.
+pub fn normalize_event_filter(raw: &str) -> Result<String, AppError> {
+    let filter = raw.trim().to_ascii_uppercase();
+    let name = filter.strip_suffix('*').unwrap_or(&filter);
+    if filter.is_empty()
+        || filter.len() > 64
+        || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    {
+        return Err(AppError::BadRequest(format!(
+            "Invalid webhook event filter: {raw}"
+        )));
+    }
+    Ok(filter)
+}
+
+pub fn event_type_matches(filters: &[String], event_type: &str) -> bool {
+    filters.is_empty()
+        || filters.iter().any(|filter| match filter.strip_suffix('*') {
+            Some(prefix) => event_type.starts_with(prefix),
+            None => filter == event_type,
+        })
+}
+
+/// The JSON document posted for one custody event. Keys serialize sorted, so
+/// the bytes are canonical and identical on every attempt and replay.
+pub fn webhook_body(ev: &LedgerEvent) -> serde_json::Value {
+    json!({
+        "event_id": ev.id,
+        "doc_id": ev.doc_id,
+        "event_type": ev.event_type,
+        "actor_wallet": ev.actor_wallet,
+        "payload": ev.payload,
+        "created_at": ev.created_at,
+        "event_hash_hex": ev.event_hash_hex,
+        "prev_event_hash_hex": ev.prev_event_hash_hex
+    })
+}
+
+/// Receivers recompute this over `{timestamp}.{body}` with their secret.
+pub fn signed_message(timestamp: i64, body: &[u8]) -> Vec<u8> {
+    let mut message = format!("{timestamp}.").into_bytes();
+    message.extend_from_slice(body);
+    message
+}
+
+pub fn hmac_signature_hex(secret: &str, message: &[u8]) -> String {
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(message);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// The server-wide ML-DSA-65 key used for `pq_signed` subscriptions.
+pub struct WebhookPqKey {
+    pub public_key_b64: String,
+    secret_key: Vec<u8>,
+}
+
+pub fn webhook_pq_key() -> Option<WebhookPqKey> {
+    let read = |name: &str| {
+        std::env::var(name)
+            .ok()
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+    let public_key_b64 = read("WEBHOOK_MLDSA_PUBLIC_KEY_B64")?;
+    let secret_key = BASE64_STANDARD
+        .decode(read("WEBHOOK_MLDSA_SECRET_KEY_B64")?)
+        .ok()?;
+    Some(WebhookPqKey {
+        public_key_b64,
+        secret_key,
+    })
+}
+
+fn subscription_from_row(row: &PgRow) -> WebhookSubscription {
+    WebhookSubscription {
+        id: row.get("id"),
+        owner_wallet: row.get("owner_wallet"),
+        url: row.get("url"),
+        event_types: serde_json::from_value(row.get("event_types_json")).unwrap_or_default(),
+        pq_signed: row.get("pq_signed"),
+        status: row.get("status"),
+        created_at: row.get("created_at"),
+        disabled_at: row.get("disabled_at"),
+    }
+}
+
+fn delivery_from_row(row: &PgRow) -> WebhookDelivery {
+    WebhookDelivery {
+        id: row.get("id"),
+        subscription_id: row.get("subscription_id"),
+        event_id: row.get("event_id"),
+        doc_id: row.get("doc_id"),
+        event_type: row.get("event_type"),
+        status: row.get("status"),
+        attempts: row.get("attempts"),
+        response_status: row.get("response_status"),
+        last_error: row.get("last_error"),
+        replay_of: row.get("replay_of"),
+        created_at: row.get("created_at"),
+        delivered_at: row.get("delivered_at"),
+    }
+}
+
+const SUBSCRIPTION_COLUMNS: &str =
+    "id, owner_wallet, url, event_types_json, pq_signed, status, created_at, disabled_at";
+
+const DELIVERY_COLUMNS: &str = "id, subscription_id, event_id, doc_id, event_type, status, attempts, response_status, last_error, replay_of, created_at, delivered_at";
+
+pub async fn insert_webhook_subscription(
+    db: &PgPool,
+    owner_wallet: &str,
+    url: &str,
+    event_types: &[String],
+    pq_signed: bool,
+    secret: &str,
+) -> Result<WebhookSubscription, AppError> {
+    let row = sqlx::query(&format!(
+        r#"
+        insert into webhook_subscriptions (id, owner_wallet, url, event_types_json, pq_signed, secret)
+        values ($1, $2, $3, $4, $5, $6)
+        returning {SUBSCRIPTION_COLUMNS}
+        "#
+    ))
+    .bind(uuid::Uuid::new_v4())
+    .bind(owner_wallet)
+    .bind(url)
+    .bind(json!(event_types))
+    .bind(pq_signed)
+    .bind(secret)
+    .fetch_one(db)
+    .await
+    .map_err(db_error)?;
+    Ok(subscription_from_row(&row))
+}
+
+pub async fn list_webhook_subscriptions(
+    db: &PgPool,
+    owner_wallet: &str,
+) -> Result<Vec<WebhookSubscription>, AppError> {
+    let rows = sqlx::query(&format!(
+        "select {SUBSCRIPTION_COLUMNS} from webhook_subscriptions where owner_wallet = $1 order by created_at desc"
+    ))
+    .bind(owner_wallet)
+    .fetch_all(db)
+    .await
+    .map_err(db_error)?;
+    Ok(rows.iter().map(subscription_from_row).collect())
+}
+
+pub async fn load_webhook_subscription(
+    db: &PgPool,
+    subscription_id: uuid::Uuid,
+    owner_wallet: &str,
+) -> Result<Option<WebhookSubscription>, AppError> {
+    let row = sqlx::query(&format!(
+        "select {SUBSCRIPTION_COLUMNS} from webhook_subscriptions where id = $1 and owner_wallet = $2"
+    ))
+    .bind(subscription_id)
+    .bind(owner_wallet)
+    .fetch_optional(db)
+    .await
+    .map_err(db_error)?;
+    Ok(row.as_ref().map(subscription_from_row))
+}
+
+/// Stop sending to a subscription. Queued deliveries are dropped when their
+/// job runs.
+pub async fn disable_webhook_subscription(
+    db: &PgPool,
+    subscription_id: uuid::Uuid,
+    owner_wallet: &str,
+) -> Result<bool, AppError> {
+    let result = sqlx::query(
+        r#"
+        update webhook_subscriptions
+        set status = $3, disabled_at = now()
+        where id = $1 and owner_wallet = $2 and status = $4
+        "#,
+    )
+    .bind(subscription_id)
+    .bind(owner_wallet)
+    .bind(SUBSCRIPTION_DISABLED)
+    .bind(SUBSCRIPTION_ACTIVE)
+    .execute(db)
+    .await
+    .map_err(db_error)?;
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn list_webhook_deliveries(
+    db: &PgPool,
+    subscription_id: uuid::Uuid,
+    limit: i64,
+) -> Result<Vec<WebhookDelivery>, AppError> {
+    let rows = sqlx::query(&format!(
+        "select {DELIVERY_COLUMNS} from webhook_deliveries where subscription_id = $1 order by created_at desc limit $2"
+    ))
+    .bind(subscription_id)
+    .bind(limit)
+    .fetch_all(db)
+    .await
+    .map_err(db_error)?;
+    Ok(rows.iter().map(delivery_from_row).collect())
+}
+
+async fn queue_delivery(
+    db: &PgPool,
+    subscription_id: uuid::Uuid,
+    event_id: uuid::Uuid,
+    doc_id: uuid::Uuid,
+    event_type: &str,
+    body: &serde_json::Value,
+    replay_of: Option<uuid::Uuid>,
+) -> Result<uuid::Uuid, AppError> {
+    let delivery_id = uuid::Uuid::new_v4();
+    sqlx::query(
+        r#"
+        insert into webhook_deliveries (id, subscription_id, event_id, doc_id, event_type, body, replay_of)
+        values ($1, $2, $3, $4, $5, $6, $7)
+        "#,
+    )
+    .bind(delivery_id)
+    .bind(subscription_id)
+    .bind(event_id)
+    .bind(doc_id)
+    .bind(event_type)
+    .bind(body)
+    .bind(replay_of)
+    .execute(db)
+    .await
+    .map_err(db_error)?;
+    schedule_job(
+        db,
+        JOB_WEBHOOK_DELIVERY,
+        Utc::now(),
+        json!({ "delivery_id": delivery_id }),
+        None,
+    )
+    .await?;
+    Ok(delivery_id)
+}
+
+/// Queue a delivery to every active subscription of the document owner whose
+/// filters match the event.
+pub async fn enqueue_webhook_deliveries(db: &PgPool, ev: &LedgerEvent) -> Result<usize, AppError> {
+    let rows = sqlx::query(
+        r#"
+        select s.id, s.owner_wallet, s.url, s.event_types_json, s.pq_signed, s.status,
+               s.created_at, s.disabled_at
+        from webhook_subscriptions s
+        join documents d on d.owner_wallet = s.owner_wallet
+        where d.id = $1 and s.status = $2
+        "#,
+    )
+    .bind(ev.doc_id)
+    .bind(SUBSCRIPTION_ACTIVE)
+    .fetch_all(db)
+    .await
+    .map_err(db_error)?;
+
+    let body = webhook_body(ev);
+    let mut queued = 0;
+    for subscription in rows.iter().map(subscription_from_row) {
+        if event_type_matches(&subscription.event_types, &ev.event_type) {
+            queue_delivery(
+                db,
+                subscription.id,
+                ev.id,
+                ev.doc_id,
+                &ev.event_type,
+                &body,
+                None,
+            )
+            .await?;
+            queued += 1;
+        }
+    }
+    Ok(queued)
+}
+
+/// Send a logged delivery again with its original body. The replay gets its
+/// own delivery row pointing back at the original.
+pub async fn replay_webhook_delivery(
+    db: &PgPool,
+    delivery_id: uuid::Uuid,
+    owner_wallet: &str,
+) -> Result<Option<uuid::Uuid>, AppError> {
+    let row = sqlx::query(
+        r#"
+        select d.subscription_id, d.event_id, d.doc_id, d.event_type, d.body
+        from webhook_deliveries d
+        join webhook_subscriptions s on s.id = d.subscription_id
+        where d.id = $1 and s.owner_wallet = $2 and s.status = $3
+        "#,
+    )
+    .bind(delivery_id)
+    .bind(owner_wallet)
+    .bind(SUBSCRIPTION_ACTIVE)
+    .fetch_optional(db)
+    .await
+    .map_err(db_error)?;
+    let Some(row) = row else {
+        return Ok(None);
+    };
+
+    let event_type: String = row.get("event_type");
+    let replay_id = queue_delivery(
+        db,
+        row.get("subscription_id"),
+        row.get("event_id"),
+        row.get("doc_id"),
+        &event_type,
+        &row.get::<serde_json::Value, _>("body"),
+        Some(delivery_id),
+    )
+    .await?;
+    Ok(Some(replay_id))
+}
+
+async fn record_attempt(
+    db: &PgPool,
+    delivery_id: uuid::Uuid,
+    status: &str,
+    response_status: Option<i32>,
+    error: Option<&str>,
+) -> Result<(), AppError> {
+    sqlx::query(
+        r#"
+        update webhook_deliveries
+        set status = $2,
+            attempts = attempts + 1,
+            response_status = $3,
+            last_error = $4,
+            last_attempt_at = now(),
+            delivered_at = case when $2 = 'delivered' then now() else delivered_at end
+        where id = $1
+        "#,
+    )
+    .bind(delivery_id)
+    .bind(status)
+    .bind(response_status)
+    .bind(error)
+    .execute(db)
+    .await
+    .map_err(db_error)?;
+    Ok(())
+}
+
+/// POST one delivery. An error leaves the job to be retried with backoff;
+/// `final_attempt` marks the log entry `failed` instead of `retrying`.
+pub async fn send_webhook_delivery(
+    db: &PgPool,
+    delivery_id: uuid::Uuid,
+    final_attempt: bool,
+) -> Result<(), AppError> {
+    let row = sqlx::query(
+        r#"
+        select d.event_type, d.body, s.url, s.secret, s.pq_signed, s.status as subscription_status
+        from webhook_deliveries d
+        join webhook_subscriptions s on s.id = d.subscription_id
+        where d.id = $1 and d.status in ('pending', 'retrying')
+        "#,
+    )
+    .bind(delivery_id)
+    .fetch_optional(db)
+    .await
+    .map_err(db_error)?;
+    let Some(row) = row else {
+        return Ok(());
+    };
+    if row.get::<String, _>("subscription_status") != SUBSCRIPTION_ACTIVE {
+        sqlx::query("update webhook_deliveries set status = 'cancelled' where id = $1")
+            .bind(delivery_id)
+            .execute(db)
+            .await
+            .map_err(db_error)?;
+        return Ok(());
+    }
+
+    let body = canonical_json(&row.get::<serde_json::Value, _>("body"));
+    let timestamp = Utc::now().timestamp();
+    let message = signed_message(timestamp, &body);
+    let secret: String = row.get("secret");
+    let event_type: String = row.get("event_type");
+    let url: String = row.get("url");
+
+    let failed_status = if final_attempt { "failed" } else { "retrying" };
+    let parsed = reqwest::Url::parse(&url)
+        .map_err(|_| AppError::Internal("webhook url is invalid".into()))?;
+    let addrs = match resolve_webhook_target(&parsed).await {
+        Ok(addrs) => addrs,
+        Err(_) => {
+            let error = "target address is not allowed";
+            record_attempt(db, delivery_id, failed_status, None, Some(error)).await?;
+            return Err(AppError::Internal(error.into()));
+        }
+    };
+
+    let mut request = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(10))
+        .redirect(reqwest::redirect::Policy::none())
+        .resolve_to_addrs(parsed.host_str().unwrap_or_default(), &addrs)
+        .build()
+        .map_err(|e| AppError::Internal(format!("webhook client: {e}")))?
+        .post(&url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header(DELIVERY_HEADER, delivery_id.to_string())
+        .header(EVENT_HEADER, &event_type)
+        .header(TIMESTAMP_HEADER, timestamp.to_string())
+        .header(
+            SIGNATURE_HEADER,
+            format!("v1={}", hmac_signature_hex(&secret, &message)),
+        );
+    if row.get::<bool, _>("pq_signed") {
+        let key = webhook_pq_key().ok_or_else(|| {
+            AppError::Internal("WEBHOOK_MLDSA_SECRET_KEY_B64 is not configured".into())
+        })?;
+        let signature = dilithium::sign(&key.secret_key, &message)?;
+        request = request.header(
+            PQ_SIGNATURE_HEADER,
+            format!("mldsa65={}", BASE64_STANDARD.encode(signature)),
+        );
+    }
+
+    match request.body(body).send().await {
+        Ok(response) if response.status().is_success() => {
+            let code = i32::from(response.status().as_u16());
+            record_attempt(db, delivery_id, "delivered", Some(code), None).await
+        }
+        Ok(response) => {
+            let code = i32::from(response.status().as_u16());
+            let error = format!("endpoint answered {code}");
+            record_attempt(db, delivery_id, failed_status, Some(code), Some(&error)).await?;
+            Err(AppError::Internal(error))
+        }
+        Err(err) => {
+            let error = transport_error_summary(&err);
+            record_attempt(db, delivery_id, failed_status, None, Some(error)).await?;
+            Err(AppError::Internal(format!(
+                "webhook delivery {delivery_id}: {err}"
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filters_match_exact_types_and_prefixes() {
+        let filters = vec![
+            normalize_event_filter("sign").unwrap(),
+            normalize_event_filter("INBOX_*").unwrap(),
+        ];
+        assert!(event_type_matches(&filters, "SIGN"));
+        assert!(event_type_matches(&filters, "INBOX_ACCEPTED"));
+        assert!(!event_type_matches(&filters, "SHARE"));
+        assert!(!event_type_matches(&filters, "SIGNED_ELSEWHERE"));
+        assert!(event_type_matches(&[], "SHARE"));
+        assert!(normalize_event_filter("SHARE;drop").is_err());
+        assert!(normalize_event_filter("*").is_ok());
+    }
+
+    #[test]
+    fn private_and_metadata_addresses_are_not_webhook_targets() {
+        for blocked in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.20.0.1",
+            "192.168.1.10",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "fd00:ec2::254",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+        ] {
+            assert!(
+                !is_public_webhook_address(blocked.parse().unwrap()),
+                "{blocked}"
+            );
+        }
+        assert!(is_public_webhook_address("93.184.216.34".parse().unwrap()));
+        assert!(is_public_webhook_address(
+            "2606:2800:220:1:248:1893:25c8:1946".parse().unwrap()
+        ));
+    }
+
+    #[tokio::test]
+    async fn literal_private_hosts_are_refused_before_sending() {
+        let url = reqwest::Url::parse("https://169.254.169.254/latest/meta-data").unwrap();
+        assert!(resolve_webhook_target(&url).await.is_err());
+        let url = reqwest::Url::parse("https://[::1]:5432/").unwrap();
+        assert!(resolve_webhook_target(&url).await.is_err());
+        let url = reqwest::Url::parse("https://93.184.216.34/hook").unwrap();
+        assert_eq!(resolve_webhook_target(&url).await.unwrap().len(), 1);
+    }
+
+    #[test]
+    fn signature_covers_timestamp_and_body() {
+        let body = canonical_json(&json!({ "b": 1, "a": 2 }));
+        assert_eq!(body, br#"{"a":2,"b":1}"#);
+        let signature = hmac_signature_hex("whsec_test", &signed_message(1_700_000_000, &body));
+        assert_eq!(signature.len(), 64);
+        assert_ne!(
+            signature,
+            hmac_signature_hex("whsec_test", &signed_message(1_700_000_001, &body))
+        );
+        assert_ne!(
+            signature,
+            hmac_signature_hex("whsec_other", &signed_message(1_700_000_000, &body))
+        );
+    }
+}
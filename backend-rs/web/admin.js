@@ -23,6 +23,31 @@ function clearAdminSession() {
 
 function clearSession() {
   localStorage.removeItem("TIDBIT_SESSION_ID");
+  localStorage.removeItem("TIDBIT_REFRESH_TOKEN");
+}
+
+// Renew the short-lived wallet session with the refresh token the main app
+// stored at login.
+async function refreshWalletSession() {
+  const refreshToken = localStorage.getItem("TIDBIT_REFRESH_TOKEN");
+  if (!refreshToken) return false;
+  const resp = await fetch(`${API}/auth/session/rotate`, {
+    method: "POST",
+    headers: authHeaders({ "Content-Type": "application/json" }),
+    body: JSON.stringify({ refresh_token: refreshToken }),
+  });
+  if (!resp.ok) return localStorage.getItem("TIDBIT_REFRESH_TOKEN") !== refreshToken;
+  const rotated = await resp.json();
+  localStorage.setItem("TIDBIT_SESSION_ID", rotated.session_id);
+  localStorage.setItem("TIDBIT_REFRESH_TOKEN", rotated.refresh_token);
+  return true;
+}
+
+async function fetchWithSessionRefresh(path, init = {}) {
+  const send = () => fetch(`${API}${path}`, { ...init, headers: authHeaders(init.headers || {}) });
+  const resp = await send();
+  if (resp.status !== 401) return resp;
+  return (await refreshWalletSession().catch(() => false)) ? send() : resp;
 }
 
 function getDeviceId() {
@@ -48,7 +73,7 @@ function authHeaders(extra = {}) {
 }
 
 async function apiGet(path) {
-  const resp = await fetch(`${API}${path}`, { headers: authHeaders(), cache: "no-store" });
+  const resp = await fetchWithSessionRefresh(path, { cache: "no-store" });
   if (!resp.ok) {
     const err = new Error(await resp.text());
     err.status = resp.status;
@@ -58,9 +83,9 @@ async function apiGet(path) {
 }
 
 async function apiPost(path, body) {
-  const resp = await fetch(`${API}${path}`, {
+  const resp = await fetchWithSessionRefresh(path, {
     method: "POST",
-    headers: authHeaders({ "Content-Type": "application/json" }),
+    headers: { "Content-Type": "application/json" },
     body: JSON.stringify(body || {}),
   });
   if (!resp.ok) {
@@ -613,6 +638,57 @@ function renderRecentEvents(items) {
   );
 }
 
+function renderQuarantine(items) {
+  const root = document.getElementById("quarantineQueue");
+  if (!root) return;
+  if (!Array.isArray(items) || !items.length) {
+    setContent(root, createMessageCard("Nothing in quarantine.", "Flagged uploads and held documents appear here for review."));
+    return;
+  }
+
+  setContent(
+    root,
+    ...items.map((item) => {
+      const card = createElement("div", { className: "event-card neutral-event" });
+      const top = createElement("div", { className: "event-top" });
+      top.appendChild(createElement("strong", { text: item.label || item.id }));
+      top.appendChild(createElement("span", { className: "muted", text: formatDateTime(item.quarantined_at) }));
+      card.appendChild(top);
+      card.appendChild(createMetaLine("Doc", item.id));
+      card.appendChild(createMetaLine("Owner", maskWallet(item.owner_wallet)));
+      card.appendChild(createMetaLine("Type", item.mime_type || "application/octet-stream"));
+      card.appendChild(createMetaLine("Held by", item.quarantined_by));
+      card.appendChild(createMetaLine("Reason", item.quarantine_reason));
+      if (item.last_scan) {
+        card.appendChild(createElement("pre", { text: JSON.stringify({ verdict: item.last_scan.verdict, findings: item.last_scan.findings }, null, 2) }));
+      }
+
+      const actions = createElement("div", { className: "doc-actions" });
+      const release = createElement("button", { className: "button-secondary", text: "Release" });
+      const purge = createElement("button", { className: "button-danger", text: "Purge" });
+      release.addEventListener("click", () => resolveQuarantine(item.id, "release"));
+      purge.addEventListener("click", () => resolveQuarantine(item.id, "purge"));
+      actions.append(release, purge);
+      card.appendChild(actions);
+      return card;
+    })
+  );
+}
+
+async function resolveQuarantine(docId, action) {
+  const question = action === "purge"
+    ? "Purge this document? Its blobs are deleted and every share is revoked."
+    : "Release this document back to its owner and recipients?";
+  if (!confirm(question)) return;
+  try {
+    await apiPost(`/api/admin/quarantine/${encodeURIComponent(docId)}/${action}`, {});
+    const queue = await apiGet("/api/admin/quarantine");
+    renderQuarantine(queue.items);
+  } catch (error) {
+    setStatus(error.message || `Quarantine ${action} failed.`, true);
+  }
+}
+
 function renderAdminAuthGate(status) {
   fillAdminUsernames(status.wallet);
   const gate = document.getElementById("adminAuthGate");
@@ -850,6 +926,8 @@ async function loadAdminConsole() {
     renderWalletActivity(overview.wallet_activity);
     renderDailySeries(overview.daily);
     renderRecentEvents(overview.recent_events);
+    const quarantine = await apiGet("/api/admin/quarantine");
+    renderQuarantine(quarantine.items);
   } catch (error) {
     document.getElementById("adminContent")?.classList.add("hidden");
     if (error.status === 401) {
@@ -29,8 +29,63 @@ function saveSessionId(sid) {
 function getSessionId() {
   return localStorage.getItem("TIDBIT_SESSION_ID");
 }
+function saveRefreshToken(token) {
+  if (token) localStorage.setItem("TIDBIT_REFRESH_TOKEN", token);
+}
+function getRefreshToken() {
+  return localStorage.getItem("TIDBIT_REFRESH_TOKEN");
+}
 function clearSession() {
   localStorage.removeItem("TIDBIT_SESSION_ID");
+  localStorage.removeItem("TIDBIT_REFRESH_TOKEN");
+}
+
+// Access sessions are short-lived. Trade the refresh token for a new pair;
+// each refresh token works once, so concurrent callers share one request.
+let sessionRefreshInFlight = null;
+function refreshWalletSession() {
+  if (sessionRefreshInFlight) return sessionRefreshInFlight;
+  const refreshToken = getRefreshToken();
+  if (!refreshToken) return Promise.resolve(null);
+  sessionRefreshInFlight = (async () => {
+    try {
+      const resp = await fetch(`${API}/auth/session/rotate`, {
+        method: "POST",
+        headers: authHeaders({ "Content-Type": "application/json" }),
+        body: JSON.stringify({ refresh_token: refreshToken }),
+      });
+      if (!resp.ok) {
+        // Another tab may have rotated first and stored the new pair.
+        return getRefreshToken() !== refreshToken ? { session_id: getSessionId() } : null;
+      }
+      const rotated = await resp.json();
+      saveSessionId(rotated.session_id);
+      saveRefreshToken(rotated.refresh_token);
+      return rotated;
+    } finally {
+      sessionRefreshInFlight = null;
+    }
+  })();
+  return sessionRefreshInFlight;
+}
+
+let sessionRefreshTimer = null;
+function scheduleSessionRefresh(expiresAt) {
+  if (sessionRefreshTimer) clearTimeout(sessionRefreshTimer);
+  if (!expiresAt || !getRefreshToken()) return;
+  const delay = Math.max(expiresAt * 1000 - Date.now() - 60_000, 5_000);
+  sessionRefreshTimer = setTimeout(async () => {
+    const rotated = await refreshWalletSession().catch(() => null);
+    if (rotated?.expires_at) scheduleSessionRefresh(rotated.expires_at);
+  }, delay);
+}
+
+async function fetchWithSessionRefresh(path, init = {}) {
+  const send = () => fetch(`${API}${path}`, { ...init, headers: authHeaders(init.headers || {}) });
+  const resp = await send();
+  if (resp.status !== 401 || !getRefreshToken()) return resp;
+  const rotated = await refreshWalletSession().catch(() => null);
+  return rotated ? send() : resp;
 }
 function getVisitorId() {
   if (window.TidbitTelemetry?.getVisitorId) {
@@ -513,6 +568,32 @@ async function importStoredPqKeypair(file) {
   });
 }
 
+// Binds the browser-local key to the active wallet. The wallet signs the
+// server's binding message and the ML-DSA key signs it too, proving possession.
+async function registerStoredPqKey() {
+  const keypair = loadStoredPqKeypair();
+  if (!keypair) {
+    throw new Error("No device-local ML-DSA key is available.");
+  }
+
+  const challenge = await apiPost("/api/identity/pq-key/challenge", {
+    public_key_b64: keypair.public_key_b64,
+  });
+  const walletSigned = await signTextWithActiveWallet(challenge.message);
+  const pqSigned = await callPqWorker("signMessage", {
+    secret_key_b64: keypair.secret_key_b64,
+    message_b64: utf8ToBase64(challenge.message),
+    signing_seed_b64: randomBase64(32),
+  });
+
+  return apiPost("/api/identity/pq-key/register", {
+    public_key_b64: keypair.public_key_b64,
+    issued_at: challenge.issued_at,
+    wallet_signature: walletSigned.signature,
+    pq_signature_b64: pqSigned.signature_b64,
+  });
+}
+
 async function signTextWithActiveWallet(message) {
   if (!currentWallet || !currentChain) {
     throw new Error("Active wallet session missing.");
@@ -583,22 +664,18 @@ async function loginWithMetamask() {
   try {
     status && (status.innerText = "Requesting nonce...");
 
+    const [address] = await provider.request({ method: "eth_requestAccounts" });
+    const chainId = await provider.request({ method: "eth_chainId" });
+
+    // The server builds the EIP-4361 message bound to this site and chain.
     const nonceRes = await fetch(`${API}/api/identity/evm/nonce`, {
       method: "POST",
-      headers: authHeaders(),
+      headers: authHeaders({ "Content-Type": "application/json" }),
+      body: JSON.stringify({ address, chain_id: chainId }),
     });
     if (!nonceRes.ok) throw new Error(await nonceRes.text());
 
-    const { session_id, nonce, message: nonceMessage } = await nonceRes.json();
-
-    const [address] = await provider.request({ method: "eth_requestAccounts" });
-
-    const message =
-      nonceMessage ||
-      `TIDBIT Authentication
-Nonce: ${nonce}
-Purpose: Login
-Version: 1`;
+    const { session_id, message } = await nonceRes.json();
 
     status && (status.innerText = "Signing message...");
 
@@ -615,6 +692,7 @@ Version: 1`;
       body: JSON.stringify({
         session_id,
         address,
+        message,
         signature,
         visitor_id: getVisitorId(),
         attribution: getAttributionSnapshot(),
@@ -625,6 +703,7 @@ Version: 1`;
 
     const verified = await verifyRes.json();
     saveSessionId(verified.session_id || session_id);
+    saveRefreshToken(verified.refresh_token);
     window.location.replace("/dashboard.html");
   } catch (err) {
     console.error(err);
@@ -645,18 +724,16 @@ async function loginWithPhantom() {
   try {
     status && (status.innerText = "Requesting Phantom nonce...");
 
+    const connectRes = await provider.connect();
+    const address = connectRes.publicKey.toString();
     const nonceRes = await fetch(`${API}/api/identity/sol/nonce`, {
       method: "POST",
-      headers: authHeaders(),
+      headers: authHeaders({ "Content-Type": "application/json" }),
+      body: JSON.stringify({ address, chain_id: "mainnet" }),
     });
     if (!nonceRes.ok) throw new Error(await nonceRes.text());
 
-    const { session_id, nonce, message: nonceMessage } = await nonceRes.json();
-    const connectRes = await provider.connect();
-    const address = connectRes.publicKey.toString();
-    const message =
-      nonceMessage ||
-      `TIDBIT Authentication\nNonce: ${nonce}\nPurpose: Login\nVersion: 1`;
+    const { session_id, message } = await nonceRes.json();
 
     status && (status.innerText = "Signing with Phantom...");
     const encoded = new TextEncoder().encode(message);
@@ -673,6 +750,7 @@ async function loginWithPhantom() {
       body: JSON.stringify({
         session_id,
         address,
+        message,
         signature,
         visitor_id: getVisitorId(),
         attribution: getAttributionSnapshot(),
@@ -683,6 +761,7 @@ async function loginWithPhantom() {
 
     const verified = await verifyRes.json();
     saveSessionId(verified.session_id || session_id);
+    saveRefreshToken(verified.refresh_token);
     window.location.replace("/dashboard.html");
   } catch (err) {
     console.error(err);
@@ -713,24 +792,90 @@ async function logout() {
 
 // ================== API ==================
 async function apiGet(path) {
-  const resp = await fetch(`${API}${path}`, {
-    headers: authHeaders(),
-    cache: "no-store",
-  });
+  const resp = await fetchWithSessionRefresh(path, { cache: "no-store" });
   if (!resp.ok) throw new Error(await resp.text());
   return resp.json();
 }
 
 async function apiPost(path, body) {
-  const resp = await fetch(`${API}${path}`, {
+  const resp = await fetchWithSessionRefresh(path, {
     method: "POST",
-    headers: authHeaders({ "Content-Type": "application/json" }),
+    headers: { "Content-Type": "application/json" },
     body: JSON.stringify(body || {}),
   });
   if (!resp.ok) throw new Error(await resp.text());
   return resp.json();
 }
 
+// EventSource cannot send the session header, so the stream is read with fetch.
+function watchCustodyEvents({ docId = null, onEvent, onResync } = {}) {
+  let stopped = false;
+  let retryMs = 1000;
+  const controller = new AbortController();
+  const query = docId ? `?doc_id=${encodeURIComponent(docId)}` : "";
+
+  const dispatch = (block) => {
+    let type = "message";
+    const data = [];
+    for (const line of block.split("\n")) {
+      if (line.startsWith("event:")) type = line.slice(6).trim();
+      else if (line.startsWith("data:")) data.push(line.slice(5).trimStart());
+    }
+    if (type === "custody" && data.length) onEvent?.(JSON.parse(data.join("\n")));
+    if (type === "resync") onResync?.();
+  };
+
+  const connect = async () => {
+    while (!stopped) {
+      try {
+        const resp = await fetch(`${API}/api/events/stream${query}`, {
+          headers: authHeaders({ Accept: "text/event-stream" }),
+          cache: "no-store",
+          signal: controller.signal,
+        });
+        if (resp.status === 401 && (await refreshWalletSession().catch(() => null))) continue;
+        if (resp.status === 401 || resp.status === 403 || resp.status === 404) return;
+        if (!resp.ok || !resp.body) throw new Error(`stream failed: ${resp.status}`);
+        retryMs = 1000;
+        const reader = resp.body.getReader();
+        const decoder = new TextDecoder();
+        let buffer = "";
+        for (;;) {
+          const { value, done } = await reader.read();
+          if (done) break;
+          buffer += decoder.decode(value, { stream: true }).replace(/\r\n/g, "\n");
+          let boundary;
+          while ((boundary = buffer.indexOf("\n\n")) >= 0) {
+            dispatch(buffer.slice(0, boundary));
+            buffer = buffer.slice(boundary + 2);
+          }
+        }
+        // Events may have been missed while reconnecting.
+        onResync?.();
+      } catch (err) {
+        if (stopped) return;
+        console.warn("custody stream", err);
+      }
+      await new Promise((resolve) => setTimeout(resolve, retryMs));
+      retryMs = Math.min(retryMs * 2, 30000);
+    }
+  };
+
+  connect();
+  return () => {
+    stopped = true;
+    controller.abort();
+  };
+}
+
+function debounce(fn, waitMs) {
+  let timer = null;
+  return (...args) => {
+    clearTimeout(timer);
+    timer = setTimeout(() => fn(...args), waitMs);
+  };
+}
+
 async function apiPublicGet(path) {
   const resp = await fetch(`${API}${path}`);
   if (!resp.ok) throw new Error(await resp.text());
@@ -888,7 +1033,7 @@ function truncateSessionId(sessionId) {
 function createSessionCard(session) {
   const card = createElement("article", { className: "event-card compact-card" });
   const top = createElement("div", { className: "event-top" });
-  top.appendChild(createElement("strong", { text: session.device_id || session.user_agent || "Browser session" }));
+  top.appendChild(createElement("strong", { text: session.device_name || session.device_id || session.user_agent || "Browser session" }));
   top.appendChild(createElement("span", { className: "muted", text: sessionStatusLabel(session) }));
   card.appendChild(top);
   card.appendChild(createMetaLine("Session", truncateSessionId(session.session_id), { className: "event-meta" }));
@@ -898,8 +1043,12 @@ function createSessionCard(session) {
   card.appendChild(createMetaLine("Status", sessionStatusDetail(session), { className: "event-meta", important: session.active || session.current }));
   card.appendChild(createMetaLine("User agent", session.user_agent || "not captured", { className: "event-meta" }));
   card.appendChild(createMetaLine("IP", session.ip_address || "not captured", { className: "event-meta" }));
+  card.appendChild(createMetaLine("Trusted", session.trusted ? "yes" : "no", { className: "event-meta" }));
 
   const actions = createElement("div", { className: "doc-actions" });
+  if (session.device_id && (session.active || session.trusted)) {
+    actions.appendChild(createDeviceTrustButton(session));
+  }
   if (session.current) {
     actions.appendChild(createElement("span", { className: "muted", text: "This device" }));
   } else if (session.active) {
@@ -927,6 +1076,9 @@ function createDocumentCard(doc) {
   card.appendChild(createMetaLine("Created", new Date(doc.created_at).toLocaleString()));
   card.appendChild(createMetaLine("Last signed", doc.last_signed_at ? new Date(doc.last_signed_at).toLocaleString() : "not signed yet"));
   card.appendChild(createMetaLine("Arweave", doc.arweave_tx || "not anchored"));
+  if (doc.quarantined_at) {
+    card.appendChild(createMetaLine("Quarantined", doc.quarantine_reason || "pending admin review"));
+  }
 
   const actions = createElement("div", { className: "doc-actions" });
   const details = createElement("a", { className: "button-link", href: `/document.html?id=${encodeURIComponent(doc.id)}`, text: "Details" });
@@ -940,7 +1092,13 @@ function createDocumentCard(doc) {
   share.onclick = () => openShareModal(doc);
   sign.onclick = () => signDocument(doc).catch((err) => alert(err.message));
   remove.onclick = () => deleteDoc(doc.id).catch((err) => alert(err.message));
-  actions.append(details, review, download, share, sign, remove);
+  actions.append(details, review, download, share, sign);
+  if (doc.access_kind !== "shared" && !doc.quarantined_at) {
+    const quarantine = createElement("button", { className: "button-secondary", text: "Quarantine" });
+    quarantine.onclick = () => quarantineDoc(doc.id).catch((err) => alert(err.message));
+    actions.append(quarantine);
+  }
+  actions.append(remove);
   card.appendChild(actions);
   return card;
 }
@@ -1171,12 +1329,10 @@ async function loadSessionInfo() {
   try {
     data = await apiGet("/auth/session");
     if (data.rotation_recommended) {
-      const rotated = await apiPost("/auth/session/rotate", {});
-      if (rotated?.session_id) {
-        saveSessionId(rotated.session_id);
-        data = rotated;
-      }
+      const rotated = await refreshWalletSession();
+      if (rotated?.wallet) data = rotated;
     }
+    scheduleSessionRefresh(data.expires_at);
   } catch (error) {
     clearSession();
     alert("This browser session is no longer active. Sign in again.");
@@ -1211,11 +1367,83 @@ async function loadAdminConsoleAccess() {
   }
 }
 
+function createDeviceTrustButton(session) {
+  if (session.trusted) {
+    const untrust = createElement("button", { className: "button-secondary", text: "Untrust Device" });
+    untrust.onclick = async () => {
+      await apiPost(`/auth/trusted-devices/${encodeURIComponent(session.device_id)}/remove`, {});
+      await loadSessionHistory();
+    };
+    return untrust;
+  }
+  const trust = createElement("button", { className: "button-secondary", text: "Trust Device" });
+  trust.onclick = async () => {
+    const name = prompt("Name this device", session.current ? "This browser" : "");
+    if (!name || !name.trim()) return;
+    await apiPost("/auth/trusted-devices", { device_id: session.device_id, name: name.trim() });
+    await loadSessionHistory();
+  };
+  return trust;
+}
+
+function sessionPolicyLabel(policy) {
+  if (!policy) return "unknown";
+  const source = policy.source === "wallet" ? "set for this wallet" : "server default";
+  if (policy.mode === "unlimited") return `Unlimited devices (${source})`;
+  if (policy.mode === "max_devices") return `Up to ${policy.max_devices} devices (${source})`;
+  return `One device at a time (${source})`;
+}
+
+function renderSessionPolicy(policy) {
+  const status = document.getElementById("sessionPolicyStatus");
+  const mode = document.getElementById("sessionPolicyMode");
+  const max = document.getElementById("sessionPolicyMax");
+  if (status) status.textContent = sessionPolicyLabel(policy);
+  if (mode && policy) mode.value = policy.mode;
+  if (max && policy?.mode === "max_devices") max.value = policy.max_devices;
+  max?.classList.toggle("hidden", mode?.value !== "max_devices");
+}
+
+async function saveSessionPolicy() {
+  const mode = document.getElementById("sessionPolicyMode")?.value || "single";
+  const maxDevices = Number(document.getElementById("sessionPolicyMax")?.value || 0);
+  const body = mode === "max_devices" ? { mode, max_devices: maxDevices } : { mode };
+  const result = await apiPost("/auth/session-policy", body);
+  if (result?.sessions_revoked) {
+    alert(`${result.sessions_revoked} session(s) were signed out to fit the new policy.`);
+  }
+  await loadSessionHistory();
+}
+
+function createAccountEventCard(event) {
+  const card = createElement("article", { className: "event-card compact-card" });
+  const top = createElement("div", { className: "event-top" });
+  top.appendChild(createElement("strong", { text: event.event_type }));
+  top.appendChild(createElement("span", { className: "muted", text: new Date(event.created_at).toLocaleString() }));
+  card.appendChild(top);
+  const payload = event.payload || {};
+  if (payload.device_id) card.appendChild(createMetaLine("Device", payload.name || payload.device_id, { className: "event-meta" }));
+  if (payload.user_agent) card.appendChild(createMetaLine("User agent", payload.user_agent, { className: "event-meta" }));
+  if (payload.ip_address) card.appendChild(createMetaLine("IP", payload.ip_address, { className: "event-meta" }));
+  if (payload.policy) card.appendChild(createMetaLine("Policy", sessionPolicyLabel(payload.policy), { className: "event-meta" }));
+  return card;
+}
+
 async function loadSessionHistory() {
   const root = document.getElementById("sessionHistory");
   if (!root) return;
 
   const data = await apiGet("/auth/sessions");
+  renderSessionPolicy(data.policy);
+  const eventsRoot = document.getElementById("accountEvents");
+  if (eventsRoot) {
+    const events = Array.isArray(data.account_events) ? data.account_events : [];
+    if (events.length) {
+      setContent(eventsRoot, ...events.map((event) => createAccountEventCard(event)));
+    } else {
+      setContent(eventsRoot, createMessageCard("event-card", "No account events yet.", "New-device logins and policy changes will appear here.", "event-meta"));
+    }
+  }
   if (!Array.isArray(data.items) || !data.items.length) {
     setContent(root, createMessageCard("event-card", "No session history yet.", "Current and recent sessions will appear here.", "event-meta"));
     return;
@@ -1433,6 +1661,13 @@ async function deleteDoc(id) {
   loadDocuments();
 }
 
+async function quarantineDoc(id) {
+  const reason = prompt("Why should this document be held for review?", "");
+  if (reason === null) return;
+  await apiPost(`/api/doc/${id}/quarantine`, { reason });
+  loadDocuments();
+}
+
 // ================== SIGN ==================
 async function signDocument(doc) {
   const message = buildDocumentSignatureMessage(doc);
@@ -1464,6 +1699,28 @@ async function signDocument(doc) {
     return;
   }
 
+  if (signatureMode === "hybrid_mldsa65") {
+    let pqSigned;
+    try {
+      pqSigned = await signWithStoredPqKey(message);
+    } catch (error) {
+      alert(`${error.message} Generate, import and register a browser-local PQ key first.`);
+      return;
+    }
+    const walletSigned = await signTextWithActiveWallet(message);
+
+    await apiPost(`/api/doc/${doc.id}/sign`, {
+      signature: walletSigned.signature,
+      signature_type: "hybrid_mldsa65",
+      pq_public_key_b64: pqSigned.pq_public_key_b64,
+      pq_signature: pqSigned.signature,
+    });
+    await afterDocumentSigned(doc.id);
+    await refreshAllPqStatus({ clearSignature: false });
+    alert("Hybrid wallet + ML-DSA signature verified and recorded");
+    return;
+  }
+
   if (signatureMode === "sol_ed25519" && currentChain !== "sol") {
     alert("Phantom / Solana signing requires a Solana session.");
     return;
@@ -2165,6 +2422,7 @@ function getReviewPqConfig() {
     exportBtnId: "pqExportKeyBtn",
     importBtnId: "pqImportKeyBtn",
     clearBtnId: "pqClearKeyBtn",
+    registerBtnId: "pqRegisterKeyBtn",
     importInputId: "pqImportFile",
   };
 }
@@ -2188,6 +2446,7 @@ async function renderPqStatus(config, { clearSignature = true } = {}) {
   const signatureField = document.getElementById(config.signatureId);
   const exportBtn = document.getElementById(config.exportBtnId);
   const clearBtn = document.getElementById(config.clearBtnId);
+  const registerBtn = config.registerBtnId ? document.getElementById(config.registerBtnId) : null;
   const keypair = loadStoredPqKeypair();
 
   if (publicKeyField) {
@@ -2198,6 +2457,7 @@ async function renderPqStatus(config, { clearSignature = true } = {}) {
   }
   if (exportBtn) exportBtn.disabled = !keypair;
   if (clearBtn) clearBtn.disabled = !keypair;
+  if (registerBtn) registerBtn.disabled = !keypair;
 
   if (!statusRoot) return;
 
@@ -2208,10 +2468,26 @@ async function renderPqStatus(config, { clearSignature = true } = {}) {
   }
 
   const fingerprint = await pqKeyFingerprint(keypair.public_key_b64);
+  let registration = "not checked";
+  if (registerBtn) {
+    try {
+      const registered = await apiGet("/api/identity/pq-key");
+      if (!registered.active) {
+        registration = "no key registered for this wallet";
+      } else if (registered.active.fingerprint_hex.startsWith(fingerprint)) {
+        registration = `registered ${new Date(registered.active.registered_at).toLocaleString()}`;
+      } else {
+        registration = "this wallet has a different key registered";
+      }
+    } catch (_) {
+      registration = "unavailable";
+    }
+  }
   setContent(
     statusRoot,
     createMetaLine("Signer", "Browser-local ML-DSA-65"),
     createMetaLine("Fingerprint", fingerprint),
+    createMetaLine("Wallet binding", registration),
     createMetaLine("Stored", keypair.created_at ? new Date(keypair.created_at).toLocaleString() : "this browser"),
     createMetaLine("Public key", `${keypair.public_key_b64.slice(0, 24)}…`)
   );
@@ -2265,6 +2541,18 @@ function bindPqControls(config) {
     }
   });
 
+  const registerBtn = config.registerBtnId ? document.getElementById(config.registerBtnId) : null;
+  registerBtn?.addEventListener("click", async () => {
+    if (!confirm("Bind this ML-DSA key to your wallet? Any previously registered key will be rotated out.")) return;
+    try {
+      await registerStoredPqKey();
+      await refreshAllPqStatus();
+      alert("ML-DSA key registered for this wallet.");
+    } catch (error) {
+      alert(error.message);
+    }
+  });
+
   clearBtn?.addEventListener("click", async () => {
     if (!confirm("Remove the browser-local ML-DSA key from this device?")) return;
     clearStoredPqKeypair();
@@ -2276,7 +2564,7 @@ function toggleSignatureMode() {
   const mode = document.getElementById("signatureMode")?.value;
   const pqFields = document.getElementById("pqFields");
   if (!pqFields) return;
-  pqFields.classList.toggle("hidden", mode !== "pq_mldsa65");
+  pqFields.classList.toggle("hidden", mode !== "pq_mldsa65" && mode !== "hybrid_mldsa65");
 }
 
 function togglePublicSignatureMode() {
@@ -2286,7 +2574,7 @@ function togglePublicSignatureMode() {
   const pqFields = document.getElementById("publicPqFields");
   if (evmFields) evmFields.classList.toggle("hidden", mode !== "evm_personal_sign");
   if (solFields) solFields.classList.toggle("hidden", mode !== "sol_ed25519");
-  if (pqFields) pqFields.classList.toggle("hidden", mode !== "pq_mldsa65");
+  if (pqFields) pqFields.classList.toggle("hidden", mode !== "pq_mldsa65" && mode !== "hybrid_mldsa65");
 }
 
 function configurePublicSignatureModes(envelope) {
@@ -2297,6 +2585,7 @@ function configurePublicSignatureModes(envelope) {
     evm_personal_sign: "MetaMask / EVM",
     sol_ed25519: "Phantom / Solana",
     pq_mldsa65: "ML-DSA PQ",
+    hybrid_mldsa65: "Wallet + ML-DSA (hybrid)",
   };
   const allowed = Array.isArray(envelope.allowed_signature_types) && envelope.allowed_signature_types.length
     ? envelope.allowed_signature_types
@@ -2915,6 +3204,27 @@ async function loadReviewPage() {
   }
 }
 
+async function refreshReviewEventsList(id) {
+  const eventsRoot = document.getElementById("reviewEvents");
+  if (!eventsRoot) return;
+  const events = await apiGet(`/api/doc/${id}/events`);
+  setContent(eventsRoot, renderHistoryCards(events, { currentWallet, ownerWallet: reviewDocument?.owner_wallet }));
+}
+
+// Only the timeline is refreshed; the preview and metadata stay as loaded.
+async function refreshDocumentHistory(id) {
+  const historyRoot = document.getElementById("history");
+  if (!historyRoot) return;
+  const events = await apiGet(`/api/doc/${id}/events`);
+  setContent(
+    historyRoot,
+    renderHistoryCards(events, {
+      currentWallet,
+      ownerWallet: currentDocumentDetails?.owner_wallet,
+    })
+  );
+}
+
 async function loadDocumentDetailsPage() {
   const id = new URLSearchParams(window.location.search).get("id");
   const historyRoot = document.getElementById("history");
@@ -3239,6 +3549,54 @@ async function submitPublicEnvelopeSign() {
     document.getElementById("publicPqSignature").value = signed.signature;
   }
 
+  if (signatureType === "hybrid_mldsa65") {
+    const evmProvider = getMetaMaskProvider();
+    const solProvider = evmProvider ? null : getPhantomProvider();
+    if (!evmProvider && !solProvider) {
+      alert("MetaMask or Phantom is required for hybrid signing.");
+      return;
+    }
+    let address;
+    if (evmProvider) {
+      [address] = await evmProvider.request({ method: "eth_requestAccounts" });
+    } else {
+      await solProvider.connect({ onlyIfTrusted: false }).catch(() => null);
+      address = solProvider.publicKey?.toString?.();
+    }
+    if (!address) {
+      alert("Wallet address unavailable.");
+      return;
+    }
+    const message =
+      `TIDBIT Public Envelope Signature\n` +
+      `Envelope ID: ${window.publicEnvelope.envelope_id}\n` +
+      `Document ID: ${window.publicEnvelope.doc_id}\n` +
+      `Hash: ${window.publicEnvelope.hash_hex}\n` +
+      `Signer: ${address}\n` +
+      `Version: ${window.publicEnvelope.version}`;
+    let pqSigned;
+    try {
+      pqSigned = await signWithStoredPqKey(message);
+    } catch (error) {
+      alert(`${error.message} Hybrid signing needs an ML-DSA key registered to this wallet.`);
+      return;
+    }
+    if (evmProvider) {
+      payload.signature = await evmProvider.request({
+        method: "personal_sign",
+        params: [message, address],
+      });
+    } else {
+      const signed = await solProvider.signMessage(new TextEncoder().encode(message), "utf8");
+      payload.signature = bytesToBase64(signed?.signature || signed);
+    }
+    payload.wallet_address = address;
+    payload.pq_public_key_b64 = pqSigned.pq_public_key_b64;
+    payload.pq_signature = pqSigned.signature;
+    document.getElementById("publicPqPublicKey").value = pqSigned.pq_public_key_b64;
+    document.getElementById("publicPqSignature").value = pqSigned.signature;
+  }
+
   await apiPublicPost(`/api/public/envelope/${encodeURIComponent(token)}/sign`, payload);
   document.getElementById("publicEnvelopeStatus").textContent =
     "Envelope completed and recorded.";
@@ -3281,6 +3639,13 @@ document.addEventListener("DOMContentLoaded", () => {
     loadAgents();
     loadAgentActivity();
     loadBillingStatus();
+    const refreshActivity = debounce(() => {
+      loadSharedActivity();
+      loadAgentActivity();
+      loadInbox();
+      loadOverview().catch(() => {});
+    }, 500);
+    watchCustodyEvents({ onEvent: refreshActivity, onResync: refreshActivity });
     document.getElementById("homeTabBtn")?.addEventListener("click", () => switchDashboardTab("home"));
     document.getElementById("docsTabBtn")?.addEventListener("click", () => switchDashboardTab("docs"));
     document.getElementById("sharedTabBtn")?.addEventListener("click", () => switchDashboardTab("shared"));
@@ -3296,6 +3661,10 @@ document.addEventListener("DOMContentLoaded", () => {
     document.getElementById("registerAgentBtn")?.addEventListener("click", () => registerAgent().catch((err) => alert(err.message)));
     document.getElementById("refreshAgentsBtn")?.addEventListener("click", () => loadAgents().catch((err) => alert(err.message)));
     document.getElementById("refreshSessionsBtn")?.addEventListener("click", () => loadSessionHistory().catch((err) => alert(err.message)));
+    document.getElementById("saveSessionPolicyBtn")?.addEventListener("click", () => saveSessionPolicy().catch((err) => alert(err.message)));
+    document.getElementById("sessionPolicyMode")?.addEventListener("change", (event) => {
+      document.getElementById("sessionPolicyMax")?.classList.toggle("hidden", event.target.value !== "max_devices");
+    });
     document.getElementById("agentPolicyDoc")?.addEventListener("change", () => loadAgentPolicy().catch((err) => alert(err.message)));
     document.getElementById("refreshAgentPolicyBtn")?.addEventListener("click", () => loadAgentPolicy().catch((err) => alert(err.message)));
     document.getElementById("saveAgentPolicyBtn")?.addEventListener("click", () => saveAgentPolicy().catch((err) => alert(err.message)));
@@ -3310,6 +3679,11 @@ document.addEventListener("DOMContentLoaded", () => {
   if (document.getElementById("reviewPreview")) {
     loadSessionInfo();
     loadReviewPage();
+    const reviewDocId = new URLSearchParams(window.location.search).get("id");
+    if (reviewDocId) {
+      const refreshReviewEvents = debounce(() => refreshReviewEventsList(reviewDocId).catch(console.error), 300);
+      watchCustodyEvents({ docId: reviewDocId, onEvent: refreshReviewEvents, onResync: refreshReviewEvents });
+    }
     bindPqControls(getReviewPqConfig());
     document.getElementById("signatureMode")?.addEventListener("change", (event) => {
       event.currentTarget.dataset.userSelected = "true";
@@ -3319,6 +3693,11 @@ document.addEventListener("DOMContentLoaded", () => {
 
   if (document.getElementById("detailMeta")) {
     loadSessionInfo();
+    const detailDocId = new URLSearchParams(window.location.search).get("id");
+    if (detailDocId) {
+      const refreshHistory = debounce(() => refreshDocumentHistory(detailDocId).catch(console.error), 300);
+      watchCustodyEvents({ docId: detailDocId, onEvent: refreshHistory, onResync: refreshHistory });
+    }
     loadDocumentDetailsPage().catch((err) => {
       console.error(err);
       setContent(